@@ -0,0 +1,255 @@
+//! Files attached to an idea (sketches, screenshots, PDFs, ...).
+//!
+//! Attachments live in their own `attachments` table and point back at their idea. The bytes are stored in the
+//! record itself, which is why uploads are capped at [`MAX_ATTACHMENT_BYTES`]. They are removed together with their
+//! idea by a database event, so every code path that really deletes an idea cascades.
+
+use serde::{Deserialize, Serialize};
+
+/// Largest accepted attachment, in bytes
+pub const MAX_ATTACHMENT_BYTES: usize = 5 * 1024 * 1024;
+
+/// Mime types that can be uploaded
+pub const ALLOWED_MIME_TYPES: &[&str] = &[
+    "image/png",
+    "image/jpeg",
+    "image/gif",
+    "image/webp",
+    "application/pdf",
+    "text/plain",
+    "text/markdown",
+];
+
+/// An attachment as sent to the client
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct Attachment {
+    pub id: String,
+    pub idea_id: String,
+    pub filename: String,
+    pub mime_type: String,
+    /// Size of the file in bytes
+    pub size: usize,
+    /// The file contents as a `data:` URL, usable directly as an image source or download link
+    pub data_url: String,
+}
+
+impl Attachment {
+    /// Whether the attachment can be previewed as an image
+    pub fn is_image(&self) -> bool {
+        self.mime_type.starts_with("image/")
+    }
+}
+
+/// Check an upload before it is stored. Returns a message suitable for showing to the user.
+pub fn validate_attachment(filename: &str, mime_type: &str, size: usize) -> Result<(), String> {
+    if filename.trim().is_empty() {
+        return Err("The file needs a name".to_string());
+    }
+    if size == 0 {
+        return Err("The file is empty".to_string());
+    }
+    if size > MAX_ATTACHMENT_BYTES {
+        return Err(format!(
+            "{} is too large (limit is {} MB)",
+            filename,
+            MAX_ATTACHMENT_BYTES / (1024 * 1024)
+        ));
+    }
+    if !ALLOWED_MIME_TYPES.contains(&mime_type) {
+        return Err(format!("Files of type {} can't be attached", mime_type));
+    }
+    Ok(())
+}
+
+/// Human-readable file size, e.g. "1.5 MB"
+pub fn format_size(bytes: usize) -> String {
+    const KB: f64 = 1024.0;
+    const MB: f64 = KB * 1024.0;
+
+    let bytes_f = bytes as f64;
+    if bytes_f >= MB {
+        format!("{:.1} MB", bytes_f / MB)
+    } else if bytes_f >= KB {
+        format!("{:.1} KB", bytes_f / KB)
+    } else {
+        format!("{} B", bytes)
+    }
+}
+
+// Server-only storage of attachments
+#[cfg(feature = "server")]
+pub mod server {
+    use super::*;
+    use crate::db::server::load_owned_idea;
+    use crate::error::IdeaError;
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use surrealdb::sql::{Bytes, Thing};
+    use surrealdb::Surreal;
+
+    /// Attachment as stored in the database
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct AttachmentRecord {
+        pub id: Option<Thing>,
+        pub idea: Thing,
+        pub filename: String,
+        pub mime_type: String,
+        pub size: usize,
+        pub data: Bytes,
+    }
+
+    impl From<AttachmentRecord> for Attachment {
+        fn from(record: AttachmentRecord) -> Self {
+            let data = record.data.into_inner();
+            Attachment {
+                id: record.id.map(|thing| thing.to_string()).unwrap_or_default(),
+                idea_id: crate::db::IdeaId::from(&record.idea).into(),
+                data_url: format!("data:{};base64,{}", record.mime_type, STANDARD.encode(&data)),
+                filename: record.filename,
+                mime_type: record.mime_type,
+                size: record.size,
+            }
+        }
+    }
+
+    /// Decode a base64 upload, rejecting anything that can't be valid before decoding it
+    pub fn decode_upload(base64_data: &str) -> Result<Vec<u8>, IdeaError> {
+        // Base64 inflates by 4/3, so anything longer can't fit under the limit once decoded
+        if base64_data.len() > MAX_ATTACHMENT_BYTES.div_ceil(3) * 4 {
+            return Err(IdeaError::PayloadTooLarge(MAX_ATTACHMENT_BYTES));
+        }
+        STANDARD
+            .decode(base64_data.trim())
+            .map_err(|_| IdeaError::Validation("The file couldn't be read".to_string()))
+    }
+
+    /// Attach a file to an idea owned by `user`
+    pub async fn add_attachment(
+        db: &Surreal<surrealdb::engine::any::Any>,
+        idea_id: &str,
+        user: Option<&Thing>,
+        filename: &str,
+        mime_type: &str,
+        data: Vec<u8>,
+    ) -> Result<AttachmentRecord, IdeaError> {
+        check_attachment(filename, mime_type, &data)?;
+
+        let idea = load_owned_idea(db, idea_id, user).await?;
+        let idea = idea.id.ok_or_else(|| IdeaError::NotFound(idea_id.to_string()))?;
+
+        let record = attachment_record(idea, filename, mime_type, data)?;
+        let created: Option<AttachmentRecord> = db.create("attachments").content(record).await?;
+        created.ok_or_else(|| IdeaError::Database("Failed to store attachment".to_string()))
+    }
+
+    fn check_attachment(filename: &str, mime_type: &str, data: &[u8]) -> Result<(), IdeaError> {
+        if data.len() > MAX_ATTACHMENT_BYTES {
+            return Err(IdeaError::PayloadTooLarge(MAX_ATTACHMENT_BYTES));
+        }
+        validate_attachment(filename, mime_type, data.len()).map_err(IdeaError::Validation)
+    }
+
+    /// The record [`add_attachment`] stores for `data` on `idea`, checked the same way, for storing it some other way
+    pub fn attachment_record(
+        idea: Thing,
+        filename: &str,
+        mime_type: &str,
+        data: Vec<u8>,
+    ) -> Result<AttachmentRecord, IdeaError> {
+        check_attachment(filename, mime_type, &data)?;
+        Ok(AttachmentRecord {
+            id: None,
+            idea,
+            filename: filename.trim().to_string(),
+            mime_type: mime_type.to_string(),
+            size: data.len(),
+            data: Bytes::from(data),
+        })
+    }
+
+    /// Attachments of an idea owned by `user`, sorted by filename
+    pub async fn attachments_for(
+        db: &Surreal<surrealdb::engine::any::Any>,
+        idea_id: &str,
+        user: Option<&Thing>,
+    ) -> Result<Vec<AttachmentRecord>, IdeaError> {
+        let idea = load_owned_idea(db, idea_id, user).await?;
+
+        let attachments: Vec<AttachmentRecord> = db
+            .query("SELECT * FROM attachments WHERE idea = $idea ORDER BY filename ASC")
+            .bind(("idea", idea.id))
+            .await?
+            .take(0)?;
+        Ok(attachments)
+    }
+
+    /// Delete a single attachment, checking that `user` owns its idea
+    pub async fn delete_attachment(
+        db: &Surreal<surrealdb::engine::any::Any>,
+        id: &str,
+        user: Option<&Thing>,
+    ) -> Result<(), IdeaError> {
+        let (table, record_id) = id
+            .split_once(':')
+            .filter(|(table, _)| *table == "attachments")
+            .ok_or_else(|| IdeaError::InvalidId(id.to_string()))?;
+
+        let attachment: Option<AttachmentRecord> = db.select((table, record_id)).await?;
+        let attachment = attachment.ok_or_else(|| IdeaError::NotFound(id.to_string()))?;
+        load_owned_idea(db, crate::db::IdeaId::from(&attachment.idea).as_str(), user).await?;
+
+        let _deleted: Option<AttachmentRecord> = db.delete((table, record_id)).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_accepts_allowed_types() {
+        for mime in ALLOWED_MIME_TYPES {
+            assert!(validate_attachment("file", mime, 10).is_ok(), "{}", mime);
+        }
+    }
+
+    #[test]
+    fn test_validate_rejects_unknown_types() {
+        assert!(validate_attachment("run.exe", "application/x-msdownload", 10).is_err());
+        assert!(validate_attachment("page.html", "text/html", 10).is_err());
+        assert!(validate_attachment("image.svg", "image/svg+xml", 10).is_err());
+    }
+
+    #[test]
+    fn test_validate_size_limits() {
+        assert!(validate_attachment("a.png", "image/png", MAX_ATTACHMENT_BYTES).is_ok());
+        assert!(validate_attachment("a.png", "image/png", MAX_ATTACHMENT_BYTES + 1).is_err());
+        assert!(validate_attachment("a.png", "image/png", 0).is_err());
+    }
+
+    #[test]
+    fn test_validate_requires_filename() {
+        assert!(validate_attachment("  ", "image/png", 10).is_err());
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(1536), "1.5 KB");
+        assert_eq!(format_size(5 * 1024 * 1024), "5.0 MB");
+    }
+
+    #[test]
+    fn test_is_image() {
+        let image = Attachment {
+            mime_type: "image/png".to_string(),
+            ..Default::default()
+        };
+        let pdf = Attachment {
+            mime_type: "application/pdf".to_string(),
+            ..Default::default()
+        };
+        assert!(image.is_image());
+        assert!(!pdf.is_image());
+    }
+}
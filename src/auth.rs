@@ -0,0 +1,362 @@
+//! Minimal email/password authentication with cookie-based sessions.
+
+use serde::{Deserialize, Serialize};
+
+/// The signed-in user as seen by the client
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct User {
+    pub id: String,
+    pub email: String,
+}
+
+/// Minimum accepted password length
+pub const MIN_PASSWORD_LEN: usize = 8;
+
+/// Normalize an email address for storage and lookup
+pub fn normalize_email(email: &str) -> String {
+    email.trim().to_lowercase()
+}
+
+/// Check registration input, returning a user-facing message on failure
+pub fn validate_credentials(email: &str, password: &str) -> Result<(), String> {
+    let email = normalize_email(email);
+    let valid_email = match email.split_once('@') {
+        Some((local, domain)) => !local.is_empty() && domain.contains('.') && !domain.starts_with('.'),
+        None => false,
+    };
+    if !valid_email {
+        return Err("Please enter a valid email address".to_string());
+    }
+    if password.chars().count() < MIN_PASSWORD_LEN {
+        return Err(format!(
+            "Password must be at least {} characters",
+            MIN_PASSWORD_LEN
+        ));
+    }
+    Ok(())
+}
+
+// Server-only session and password handling
+#[cfg(feature = "server")]
+pub mod server {
+    use super::*;
+    use crate::error::IdeaError;
+    use argon2::{
+        password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+        Argon2,
+    };
+    use surrealdb::{sql::Thing, Surreal};
+
+    /// Name of the session cookie
+    pub const SESSION_COOKIE: &str = "ideas_session";
+
+    /// How long a session stays valid, in days
+    pub const SESSION_DAYS: u32 = 30;
+
+    /// Stored user row
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct UserRecord {
+        pub id: Option<Thing>,
+        pub email: String,
+        pub password_hash: String,
+    }
+
+    impl From<UserRecord> for User {
+        fn from(record: UserRecord) -> Self {
+            User {
+                id: record.id.map(|thing| thing.to_string()).unwrap_or_default(),
+                email: record.email,
+            }
+        }
+    }
+
+    /// Hash a password with argon2 and a random salt
+    pub fn hash_password(password: &str) -> Result<String, IdeaError> {
+        let salt = SaltString::generate(&mut OsRng);
+        Argon2::default()
+            .hash_password(password.as_bytes(), &salt)
+            .map(|hash| hash.to_string())
+            .map_err(|e| IdeaError::Database(format!("Failed to hash password: {}", e)))
+    }
+
+    /// Check a password against a stored argon2 hash
+    pub fn verify_password(password: &str, hash: &str) -> bool {
+        PasswordHash::new(hash)
+            .map(|parsed| {
+                Argon2::default()
+                    .verify_password(password.as_bytes(), &parsed)
+                    .is_ok()
+            })
+            .unwrap_or(false)
+    }
+
+    /// Create a user, rejecting duplicate emails
+    pub async fn register(
+        db: &Surreal<surrealdb::engine::any::Any>,
+        email: &str,
+        password: &str,
+    ) -> Result<UserRecord, IdeaError> {
+        validate_credentials(email, password).map_err(IdeaError::Validation)?;
+        let email = normalize_email(email);
+
+        let existing: Option<UserRecord> = db
+            .query("SELECT * FROM users WHERE email = $email LIMIT 1")
+            .bind(("email", email.clone()))
+            .await?
+            .take(0)?;
+        if existing.is_some() {
+            return Err(IdeaError::Validation(
+                "An account with this email already exists".to_string(),
+            ));
+        }
+
+        let user = UserRecord {
+            id: None,
+            email,
+            password_hash: hash_password(password)?,
+        };
+        let created: Option<UserRecord> = db.create("users").content(user).await?;
+        created.ok_or_else(|| IdeaError::Database("Failed to create user".to_string()))
+    }
+
+    /// Check credentials, returning the user on success
+    pub async fn authenticate(
+        db: &Surreal<surrealdb::engine::any::Any>,
+        email: &str,
+        password: &str,
+    ) -> Result<UserRecord, IdeaError> {
+        let user: Option<UserRecord> = db
+            .query("SELECT * FROM users WHERE email = $email LIMIT 1")
+            .bind(("email", normalize_email(email)))
+            .await?
+            .take(0)?;
+
+        match user {
+            Some(user) if verify_password(password, &user.password_hash) => Ok(user),
+            _ => Err(IdeaError::Validation("Wrong email or password".to_string())),
+        }
+    }
+
+    /// Start a session for `user`, returning the session token. Only its SHA-256 is stored, so whoever reads the
+    /// `sessions` table can't sign in with what they find there.
+    pub async fn create_session(
+        db: &Surreal<surrealdb::engine::any::Any>,
+        user: &Thing,
+    ) -> Result<String, IdeaError> {
+        let token = format!("{}{}", uuid::Uuid::new_v4().simple(), uuid::Uuid::new_v4().simple());
+
+        db.query(format!(
+            "CREATE sessions SET token = crypto::sha256($session_token), user = $user, expires_at = time::now() + {}d",
+            SESSION_DAYS
+        ))
+        .bind(("session_token", token.clone()))
+        .bind(("user", user.clone()))
+        .await?
+        .check()?;
+
+        Ok(token)
+    }
+
+    /// Look up the user behind a session token, ignoring expired sessions
+    pub async fn user_for_token(
+        db: &Surreal<surrealdb::engine::any::Any>,
+        token: &str,
+    ) -> Result<Option<UserRecord>, IdeaError> {
+        let user_id: Option<Thing> = db
+            .query("SELECT VALUE user FROM sessions WHERE token = crypto::sha256($session_token) AND expires_at > time::now() LIMIT 1")
+            .bind(("session_token", token.to_string()))
+            .await?
+            .take(0)?;
+
+        match user_id {
+            Some(id) => Ok(db.select((id.tb.as_str(), id.id.to_string().as_str())).await?),
+            None => Ok(None),
+        }
+    }
+
+    /// End a session
+    pub async fn delete_session(
+        db: &Surreal<surrealdb::engine::any::Any>,
+        token: &str,
+    ) -> Result<(), IdeaError> {
+        db.query("DELETE sessions WHERE token = crypto::sha256($session_token)")
+            .bind(("session_token", token.to_string()))
+            .await?
+            .check()?;
+        Ok(())
+    }
+
+    /// `Set-Cookie` value carrying a session token, only sent back over https when `secure`
+    pub fn session_cookie(token: &str, secure: bool) -> String {
+        format!(
+            "{}={}; HttpOnly; Path=/; SameSite=Lax; Max-Age={}{}",
+            SESSION_COOKIE,
+            token,
+            SESSION_DAYS as u64 * 24 * 60 * 60,
+            if secure { "; Secure" } else { "" }
+        )
+    }
+
+    /// `Set-Cookie` value that removes the session cookie
+    pub fn clear_session_cookie() -> String {
+        format!("{}=; HttpOnly; Path=/; SameSite=Lax; Max-Age=0", SESSION_COOKIE)
+    }
+
+    /// The user signed in on the current request, if any
+    pub async fn current_user(
+        db: &Surreal<surrealdb::engine::any::Any>,
+    ) -> Result<Option<UserRecord>, IdeaError> {
+        match crate::request::cookie(SESSION_COOKIE) {
+            Some(token) => user_for_token(db, &token).await,
+            None => Ok(None),
+        }
+    }
+
+    /// Id of the user signed in on the current request. `None` means anonymous.
+    pub async fn current_user_id(
+        db: &Surreal<surrealdb::engine::any::Any>,
+    ) -> Result<Option<Thing>, IdeaError> {
+        Ok(current_user(db).await?.and_then(|user| user.id))
+    }
+
+    /// Session token of a request handled outside a server function: the session cookie, or an
+    /// `Authorization: Bearer <token>` header for scripts that can't keep cookies
+    pub fn session_token_from(headers: &http::HeaderMap) -> Option<String> {
+        crate::request::cookie_from(headers, SESSION_COOKIE).or_else(|| {
+            crate::request::header_from(headers, http::header::AUTHORIZATION)?
+                .strip_prefix("Bearer ")
+                .map(|token| token.trim().to_string())
+                .filter(|token| !token.is_empty())
+        })
+    }
+
+    /// [`current_user_id`] for a request handled outside a server function
+    pub async fn user_id_from_headers(
+        db: &Surreal<surrealdb::engine::any::Any>,
+        headers: &http::HeaderMap,
+    ) -> Result<Option<Thing>, IdeaError> {
+        match session_token_from(headers) {
+            Some(token) => Ok(user_for_token(db, &token).await?.and_then(|user| user.id)),
+            None => Ok(None),
+        }
+    }
+
+    /// Comma-separated emails of the accounts allowed to use the deployment-wide admin functions: backups, restores,
+    /// maintenance and the like. Nobody is an admin without it.
+    pub const ADMIN_EMAILS_VAR: &str = "IDEAS_ADMIN_EMAILS";
+
+    /// The accounts named in [`ADMIN_EMAILS_VAR`], normalized, read on first use
+    pub fn admin_emails() -> &'static [String] {
+        static ADMINS: std::sync::OnceLock<Vec<String>> = std::sync::OnceLock::new();
+        ADMINS.get_or_init(|| {
+            std::env::var(ADMIN_EMAILS_VAR)
+                .map(|value| parse_admin_emails(&value))
+                .unwrap_or_default()
+        })
+    }
+
+    /// The addresses in an [`ADMIN_EMAILS_VAR`] value, skipping blank entries
+    pub fn parse_admin_emails(value: &str) -> Vec<String> {
+        value
+            .split(',')
+            .map(normalize_email)
+            .filter(|email| !email.is_empty())
+            .collect()
+    }
+
+    /// Check that `user` is one of `admins`. Anonymous visitors are asked to sign in, everyone else is refused.
+    pub fn ensure_admin(user: Option<&UserRecord>, admins: &[String]) -> Result<(), IdeaError> {
+        match user {
+            None => Err(IdeaError::Unauthorized),
+            Some(user) if admins.contains(&normalize_email(&user.email)) => Ok(()),
+            Some(_) => Err(IdeaError::Forbidden),
+        }
+    }
+
+    /// Guard for the deployment-wide admin functions: the signed-in user must be one of the [`admin_emails`]
+    pub async fn require_admin(db: &Surreal<surrealdb::engine::any::Any>) -> Result<(), IdeaError> {
+        ensure_admin(current_user(db).await?.as_ref(), admin_emails())
+    }
+
+    /// Check that `user` may modify a record owned by `owner`.
+    ///
+    /// Records without an owner belong to the anonymous space and are only reachable anonymously.
+    pub fn ensure_owner(owner: Option<&Thing>, user: Option<&Thing>) -> Result<(), IdeaError> {
+        if owner == user {
+            Ok(())
+        } else {
+            Err(IdeaError::Forbidden)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_credentials() {
+        assert!(validate_credentials("me@example.com", "long enough").is_ok());
+        assert!(validate_credentials("  ME@Example.com ", "long enough").is_ok());
+        assert!(validate_credentials("not-an-email", "long enough").is_err());
+        assert!(validate_credentials("@example.com", "long enough").is_err());
+        assert!(validate_credentials("me@localhost", "long enough").is_err());
+        assert!(validate_credentials("me@example.com", "short").is_err());
+    }
+
+    #[test]
+    fn test_normalize_email() {
+        assert_eq!(normalize_email("  Me@Example.COM "), "me@example.com");
+    }
+
+    #[cfg(feature = "server")]
+    mod server_tests {
+        use super::super::server::*;
+        use crate::error::IdeaError;
+        use surrealdb::sql::Thing;
+
+        #[test]
+        fn test_password_hashing() {
+            let hash = hash_password("correct horse").unwrap();
+            assert_ne!(hash, "correct horse");
+            assert!(verify_password("correct horse", &hash));
+            assert!(!verify_password("wrong horse", &hash));
+            assert!(!verify_password("correct horse", "not a hash"));
+        }
+
+        #[test]
+        fn test_session_cookie_is_secure_over_https() {
+            assert!(session_cookie("abc", true).ends_with("; Secure"));
+            assert!(!session_cookie("abc", false).contains("Secure"));
+        }
+
+        #[test]
+        fn test_ensure_admin() {
+            let admins = parse_admin_emails(" Admin@Example.com, ,ops@example.com");
+            assert_eq!(admins, vec!["admin@example.com", "ops@example.com"]);
+            let user = |email: &str| UserRecord {
+                id: None,
+                email: email.to_string(),
+                password_hash: String::new(),
+            };
+
+            assert_eq!(ensure_admin(Some(&user("admin@example.com")), &admins), Ok(()));
+            assert_eq!(ensure_admin(Some(&user("me@example.com")), &admins), Err(IdeaError::Forbidden));
+            assert_eq!(ensure_admin(None, &admins), Err(IdeaError::Unauthorized));
+            // Without the variable nobody is an admin
+            assert_eq!(ensure_admin(Some(&user("admin@example.com")), &[]), Err(IdeaError::Forbidden));
+        }
+
+        #[test]
+        fn test_ensure_owner() {
+            let alice = Thing::from(("users", "alice"));
+            let bob = Thing::from(("users", "bob"));
+
+            assert!(ensure_owner(Some(&alice), Some(&alice)).is_ok());
+            assert!(ensure_owner(None, None).is_ok());
+            assert!(ensure_owner(Some(&alice), Some(&bob)).is_err());
+            assert!(ensure_owner(Some(&alice), None).is_err());
+            assert!(ensure_owner(None, Some(&alice)).is_err());
+        }
+    }
+}
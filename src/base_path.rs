@@ -0,0 +1,76 @@
+//! Serving the app below a path prefix, e.g. at `https://example.com/ideas/` behind a reverse proxy.
+//!
+//! `APP_BASE_PATH` names the prefix. The server reads it when it starts and mounts every route under it: the pages,
+//! the server functions, the REST API and the feed. The web build takes it at compile time, since the browser has no
+//! environment, so build the client with the same value (and `dx build --base-path` for the bundled assets). URLs the
+//! app writes out itself go through [`with_base_path`].
+
+/// Environment variable holding the prefix, such as `/ideas`. Unset or empty serves the app at the root.
+pub const BASE_PATH_VAR: &str = "APP_BASE_PATH";
+
+/// `raw` as a prefix that can be put in front of a path: `""` for the root, otherwise one leading slash and no
+/// trailing one, so `ideas/`, `/ideas` and ` /ideas/ ` all give `/ideas`
+pub fn normalize_base_path(raw: &str) -> String {
+    let trimmed = raw.trim().trim_matches('/');
+    if trimmed.is_empty() {
+        String::new()
+    } else {
+        format!("/{}", trimmed)
+    }
+}
+
+/// The prefix of this build. The server prefers [`BASE_PATH_VAR`] from its environment and falls back to the value
+/// compiled in; the client only has the compiled-in one.
+pub fn base_path() -> String {
+    #[cfg(feature = "server")]
+    if let Ok(raw) = std::env::var(BASE_PATH_VAR) {
+        return normalize_base_path(&raw);
+    }
+    normalize_base_path(option_env!("APP_BASE_PATH").unwrap_or_default())
+}
+
+/// `path`, which starts with `/`, below [`base_path`]
+pub fn with_base_path(path: &str) -> String {
+    prefixed(&base_path(), path)
+}
+
+/// `path` below `base`, a prefix from [`normalize_base_path`]. The root under a prefix is `/ideas/`, not `/ideas`.
+pub fn prefixed(base: &str, path: &str) -> String {
+    format!("{}{}", base, path)
+}
+
+#[cfg(feature = "server")]
+pub mod server {
+    use axum::Router;
+
+    /// `router` moved below `base`, a prefix from [`super::normalize_base_path`]. Nothing answers outside the prefix
+    /// any more, so the bare paths give 404. An empty `base` leaves the router as it is.
+    pub fn mount_at_base_path(router: Router, base: &str) -> Router {
+        if base.is_empty() {
+            router
+        } else {
+            Router::new().nest(base, router)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_base_path() {
+        assert_eq!(normalize_base_path(""), "");
+        assert_eq!(normalize_base_path(" / "), "");
+        assert_eq!(normalize_base_path("ideas"), "/ideas");
+        assert_eq!(normalize_base_path("/ideas/"), "/ideas");
+        assert_eq!(normalize_base_path(" //tools/ideas// "), "/tools/ideas");
+    }
+
+    #[test]
+    fn test_prefixed() {
+        assert_eq!(prefixed("", "/feed.xml"), "/feed.xml");
+        assert_eq!(prefixed("/ideas", "/feed.xml"), "/ideas/feed.xml");
+        assert_eq!(prefixed("/ideas", "/"), "/ideas/");
+    }
+}
@@ -0,0 +1,88 @@
+//! Fill the local database with sample ideas for development.
+//!
+//! Run with `cargo run --bin seed --features server -- [count]`. The ideas are created unowned, so they show up for
+//! anonymous visitors, and are written in a single batch.
+//!
+//! `seed digest [since] [--text] [--base-url URL]` prints the digest of the unowned ideas instead (see
+//! `dioxus_surrealdb_template::digest`), as HTML or plain text, for piping into a mailer. `since` is a date or an
+//! RFC 3339 time and defaults to a week ago.
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use dioxus_surrealdb_template::db::server::{get_db, submit_ideas, SurrealIdeaRepository, MAX_BATCH_IDEAS};
+use dioxus_surrealdb_template::digest::{server::digest_for, DEFAULT_DIGEST_DAYS};
+use dioxus_surrealdb_template::sample::{sample_ideas, DEFAULT_SAMPLE_COUNT};
+
+const DIGEST_USAGE: &str = "usage: seed digest [since] [--text] [--base-url URL]";
+
+#[tokio::main]
+async fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("digest") {
+        digest(&args[1..]).await;
+        return;
+    }
+
+    let count = match args.first() {
+        Some(arg) => match arg.parse::<usize>() {
+            Ok(count) if (1..=MAX_BATCH_IDEAS).contains(&count) => count,
+            _ => {
+                eprintln!("usage: seed [count], with count between 1 and {}", MAX_BATCH_IDEAS);
+                std::process::exit(2);
+            }
+        },
+        None => DEFAULT_SAMPLE_COUNT,
+    };
+
+    let repo = SurrealIdeaRepository::new(get_db().await.clone());
+    match submit_ideas(&repo, None, sample_ideas(count)).await {
+        Ok(created) => println!("Created {} ideas", created.len()),
+        Err(e) => {
+            eprintln!("Seeding failed: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// A date is read as its midnight in UTC
+fn parse_since(value: &str) -> Option<DateTime<Utc>> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Some(time.with_timezone(&Utc));
+    }
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()?;
+    Some(date.and_hms_opt(0, 0, 0)?.and_utc())
+}
+
+async fn digest(args: &[String]) {
+    let now = Utc::now();
+    let mut since = now - Duration::days(DEFAULT_DIGEST_DAYS);
+    let mut text = false;
+    let mut base_url = "http://localhost:8080".to_string();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--text" => text = true,
+            "--base-url" => match args.next() {
+                Some(url) => base_url = url.clone(),
+                None => usage(),
+            },
+            value => match parse_since(value) {
+                Some(time) => since = time,
+                None => usage(),
+            },
+        }
+    }
+
+    match digest_for(get_db().await, None, since, now).await {
+        Ok(digest) if text => print!("{}", digest.to_text(&base_url)),
+        Ok(digest) => print!("{}", digest.to_html(&base_url)),
+        Err(e) => {
+            eprintln!("Digest failed: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn usage() -> ! {
+    eprintln!("{}", DIGEST_USAGE);
+    std::process::exit(2);
+}
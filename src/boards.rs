@@ -0,0 +1,301 @@
+//! Boards: separate sets of ideas, e.g. "work" and "personal".
+//!
+//! Every idea belongs to one board. The default board is created by `define_schema`, can't be deleted and also
+//! holds the ideas stored before boards existed, which have no `board` field. Other boards belong to whoever
+//! created them, like templates. The board a browser works on is kept in the [`BOARD_COOKIE`] cookie, which
+//! `select_board_server` sets, so the repository server functions use can scope every list, search and count to it
+//! without each call naming the board.
+//!
+//! A board's slug is picked from its name when it is created and then never changes, so links to `/b/<slug>` keep
+//! working after a rename. It is also the board's record key.
+
+use crate::error::IdeaError;
+use crate::utils::{slugify, unique_slug};
+use serde::{Deserialize, Serialize};
+
+/// Table boards are stored in
+pub const BOARD_TABLE: &str = "boards";
+/// Slug, and record key, of the board every deployment has
+pub const DEFAULT_BOARD_SLUG: &str = "default";
+/// Name the default board starts with
+pub const DEFAULT_BOARD_NAME: &str = "Ideas";
+/// Cookie holding the slug of the board the browser works on
+pub const BOARD_COOKIE: &str = "board";
+/// Longest board name accepted, in characters
+pub const MAX_BOARD_NAME_CHARS: usize = 40;
+
+/// A board as sent to the client
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Board {
+    /// Always `boards:<slug>`
+    pub id: String,
+    pub name: String,
+    pub slug: String,
+}
+
+impl Board {
+    /// The board that can't be deleted
+    pub fn is_default(&self) -> bool {
+        self.slug == DEFAULT_BOARD_SLUG
+    }
+}
+
+/// `name` trimmed, or why it can't name a board
+pub fn check_board_name(name: &str) -> Result<String, IdeaError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(IdeaError::Validation("The board needs a name".to_string()));
+    }
+    if name.chars().count() > MAX_BOARD_NAME_CHARS {
+        return Err(IdeaError::Validation(format!(
+            "Board names are at most {} characters",
+            MAX_BOARD_NAME_CHARS
+        )));
+    }
+    Ok(name.to_string())
+}
+
+/// The slug of a new board named `name`, unlike every slug in `taken`. Only ASCII is kept, as the slug also travels
+/// in a cookie, and the default board's slug is never handed out.
+pub fn board_slug(name: &str, taken: &[String]) -> String {
+    let ascii: String = slugify(name).chars().filter(|c| c.is_ascii_alphanumeric() || *c == '-').collect();
+    let base = ascii.split('-').filter(|part| !part.is_empty()).collect::<Vec<_>>().join("-");
+    let base = if base.is_empty() { "board".to_string() } else { base };
+    let mut taken = taken.to_vec();
+    taken.push(DEFAULT_BOARD_SLUG.to_string());
+    unique_slug(&base, &taken)
+}
+
+/// Whether `slug` could have come from [`board_slug`], so a cookie or URL segment is never used as anything else
+pub fn is_board_slug(slug: &str) -> bool {
+    !slug.is_empty()
+        && slug.split('-').all(|part| !part.is_empty())
+        && slug.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+}
+
+// Server-only storage of boards and the scoping of ideas
+#[cfg(feature = "server")]
+pub mod server {
+    use super::*;
+    use crate::auth::server::ensure_owner;
+    use crate::db::server::with_transaction;
+    use crate::db::IdeaRecord;
+    use surrealdb::sql::Thing;
+    use surrealdb::Surreal;
+
+    /// Board as stored in the database
+    #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+    pub struct BoardRecord {
+        pub id: Option<Thing>,
+        pub name: String,
+        #[serde(default)]
+        pub slug: String,
+        /// User who created the board. `None` for the default board and anonymous boards.
+        #[serde(default)]
+        pub owner: Option<Thing>,
+    }
+
+    impl From<BoardRecord> for Board {
+        fn from(record: BoardRecord) -> Self {
+            Board {
+                id: board_thing(&record.slug).to_string(),
+                name: record.name,
+                slug: record.slug,
+            }
+        }
+    }
+
+    /// The record id of the board with `slug`
+    pub fn board_thing(slug: &str) -> Thing {
+        Thing::from((BOARD_TABLE, slug))
+    }
+
+    pub fn default_board() -> Thing {
+        board_thing(DEFAULT_BOARD_SLUG)
+    }
+
+    /// Whether `idea` is on `board`. Ideas without a board are on the default one.
+    pub fn in_board(idea: &IdeaRecord, board: &Thing) -> bool {
+        match &idea.board {
+            Some(own) => own == board,
+            None => *board == default_board(),
+        }
+    }
+
+    /// The board the current request works on: the one named by [`BOARD_COOKIE`], or the default board
+    pub fn current_board() -> Thing {
+        crate::request::cookie(BOARD_COOKIE)
+            .filter(|slug| is_board_slug(slug))
+            .map(|slug| board_thing(&slug))
+            .unwrap_or_else(default_board)
+    }
+
+    /// `Set-Cookie` value that makes `slug` the board of the following requests
+    pub fn board_cookie(slug: &str) -> String {
+        format!("{}={}; Path=/; SameSite=Lax; Max-Age={}", BOARD_COOKIE, slug, 365 * 24 * 60 * 60)
+    }
+
+    /// Create the default board unless it exists. Safe to run on every startup, and a renamed default board keeps
+    /// its name.
+    pub async fn seed_default_board(db: &Surreal<surrealdb::engine::any::Any>) -> surrealdb::Result<()> {
+        let board = BoardRecord {
+            id: Some(default_board()),
+            name: DEFAULT_BOARD_NAME.to_string(),
+            slug: DEFAULT_BOARD_SLUG.to_string(),
+            owner: None,
+        };
+        db.query("INSERT IGNORE INTO boards $board")
+            .bind(("board", board))
+            .await?
+            .check()?;
+        Ok(())
+    }
+
+    /// The default board followed by the boards of `owner`, sorted by name
+    pub async fn list_boards(
+        db: &Surreal<surrealdb::engine::any::Any>,
+        owner: Option<&Thing>,
+    ) -> Result<Vec<BoardRecord>, IdeaError> {
+        let mut boards: Vec<BoardRecord> = db
+            .query("SELECT * FROM boards WHERE slug = $default OR owner = $owner")
+            .bind(("default", DEFAULT_BOARD_SLUG))
+            .bind(("owner", owner.cloned()))
+            .await?
+            .take(0)?;
+        boards.sort_by_key(|board| (board.slug != DEFAULT_BOARD_SLUG, board.name.to_lowercase()));
+        Ok(boards)
+    }
+
+    /// The board with `slug`, if `user` may use it: the default board or one of their own
+    pub async fn load_board(
+        db: &Surreal<surrealdb::engine::any::Any>,
+        slug: &str,
+        user: Option<&Thing>,
+    ) -> Result<BoardRecord, IdeaError> {
+        let not_found = || IdeaError::NotFound(board_thing(slug).to_string());
+        if !is_board_slug(slug) {
+            return Err(not_found());
+        }
+        let board: Option<BoardRecord> = db.select((BOARD_TABLE, slug)).await?;
+        let board = board.ok_or_else(not_found)?;
+        if board.slug != DEFAULT_BOARD_SLUG {
+            ensure_owner(board.owner.as_ref(), user)?;
+        }
+        Ok(board)
+    }
+
+    pub async fn create_board(
+        db: &Surreal<surrealdb::engine::any::Any>,
+        owner: Option<Thing>,
+        name: &str,
+    ) -> Result<BoardRecord, IdeaError> {
+        let name = check_board_name(name)?;
+        let taken: Vec<String> = db.query("SELECT VALUE slug FROM boards").await?.take(0)?;
+        let slug = board_slug(&name, &taken);
+
+        let record = BoardRecord {
+            id: None,
+            name,
+            slug: slug.clone(),
+            owner,
+        };
+        let created: Option<BoardRecord> = db.create((BOARD_TABLE, slug.as_str())).content(record).await?;
+        created.ok_or_else(|| IdeaError::Database("Failed to create board".to_string()))
+    }
+
+    /// Give a board a new name. Its slug, and so every link to it, stays.
+    pub async fn rename_board(
+        db: &Surreal<surrealdb::engine::any::Any>,
+        slug: &str,
+        user: Option<&Thing>,
+        name: &str,
+    ) -> Result<BoardRecord, IdeaError> {
+        let name = check_board_name(name)?;
+        let existing = load_board(db, slug, user).await?;
+
+        let record = BoardRecord {
+            id: None,
+            name,
+            ..existing
+        };
+        let updated: Option<BoardRecord> = db.update((BOARD_TABLE, slug)).content(record).await?;
+        updated.ok_or_else(|| IdeaError::NotFound(board_thing(slug).to_string()))
+    }
+
+    /// Delete a board of `user`, returning how many ideas moved out of it.
+    ///
+    /// A board still holding ideas, trashed ones included, is only deleted with `move_to`, the slug of another board
+    /// `user` may use, which then takes them. The default board can't be deleted.
+    pub async fn delete_board(
+        db: &Surreal<surrealdb::engine::any::Any>,
+        slug: &str,
+        user: Option<&Thing>,
+        move_to: Option<&str>,
+    ) -> Result<usize, IdeaError> {
+        if slug == DEFAULT_BOARD_SLUG {
+            return Err(IdeaError::Validation("The default board can't be deleted".to_string()));
+        }
+        load_board(db, slug, user).await?;
+        let board = board_thing(slug);
+
+        let held: Vec<Thing> = db
+            .query("SELECT VALUE id FROM ideas WHERE board = $board")
+            .bind(("board", board.clone()))
+            .await?
+            .take(0)?;
+        let target = match move_to {
+            Some(target) if target == slug => {
+                return Err(IdeaError::Validation("Ideas can't move to the board being deleted".to_string()));
+            }
+            Some(target) => Some(board_thing(&load_board(db, target, user).await?.slug)),
+            None if held.is_empty() => None,
+            None => {
+                return Err(IdeaError::Validation(format!(
+                    "The board still holds {} idea(s); pick a board to move them to",
+                    held.len()
+                )));
+            }
+        };
+
+        with_transaction(db, |txn| {
+            txn.bind("board", board);
+            if let Some(target) = target {
+                txn.bind("target", target);
+                txn.push("UPDATE ideas SET board = $target WHERE board = $board");
+            }
+            txn.push("DELETE $board");
+            Ok(())
+        })
+        .await?;
+        Ok(held.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_board_names_are_trimmed_and_bounded() {
+        assert_eq!(check_board_name("  Work ").unwrap(), "Work");
+        assert!(matches!(check_board_name("   "), Err(IdeaError::Validation(_))));
+        assert!(check_board_name(&"é".repeat(MAX_BOARD_NAME_CHARS)).is_ok());
+        assert!(check_board_name(&"é".repeat(MAX_BOARD_NAME_CHARS + 1)).is_err());
+    }
+
+    #[test]
+    fn test_board_slugs_are_unique_and_never_the_default() {
+        assert_eq!(board_slug("Work Stuff", &[]), "work-stuff");
+        assert_eq!(board_slug("Work", &["work".to_string()]), "work-2");
+        assert_eq!(board_slug("Default", &[]), "default-2");
+        assert_eq!(board_slug("日本", &[]), "board");
+        assert_eq!(board_slug("Café crème", &[]), "caf-crme");
+
+        for slug in ["work-2", "default", "board"] {
+            assert!(is_board_slug(slug), "{}", slug);
+        }
+        for slug in ["", "Work", "a b", "../admin", "x;y", "-work", "a--b", "日本"] {
+            assert!(!is_board_slug(slug), "{}", slug);
+        }
+    }
+}
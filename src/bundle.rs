@@ -0,0 +1,355 @@
+//! One idea with everything attached to it, as a JSON document that moves it to another instance.
+//!
+//! A bundle holds the idea, its attachments (base64), its `relates_to` links, its revisions and its history. Ids in
+//! it are only there to tell the links apart: importing gives the idea and every row a fresh id, owned by whoever
+//! imports it. `schema_version` says which shape the bundle has; [`parse_bundle`] reads every version up to
+//! [`BUNDLE_SCHEMA_VERSION`] and refuses newer ones instead of guessing at fields it doesn't know.
+
+use crate::db::Idea;
+use crate::error::IdeaError;
+use crate::graph::GraphEdge;
+use crate::history::IdeaEvent;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Version of the bundle shape written by this build
+pub const BUNDLE_SCHEMA_VERSION: u32 = 1;
+
+/// An idea and its related rows, see the module docs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IdeaBundle {
+    pub schema_version: u32,
+    pub exported_at: DateTime<Utc>,
+    /// The idea with the id it had where it was exported
+    pub idea: Idea,
+    #[serde(default)]
+    pub attachments: Vec<BundledAttachment>,
+    /// Links from or to the idea, by the ids of the exporting instance
+    #[serde(default)]
+    pub links: Vec<GraphEdge>,
+    /// Snapshots of the notes and statements, newest first
+    #[serde(default)]
+    pub revisions: Vec<BundledRevision>,
+    /// History entries, newest first
+    #[serde(default)]
+    pub history: Vec<IdeaEvent>,
+}
+
+/// A file attached to the bundled idea
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BundledAttachment {
+    pub filename: String,
+    pub mime_type: String,
+    /// The file contents, base64-encoded
+    pub data: String,
+}
+
+/// A snapshot of the bundled idea's notes and statements
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BundledRevision {
+    pub what_must_be_true: Vec<String>,
+    pub development_notes: String,
+    pub at: DateTime<Utc>,
+}
+
+/// What importing a bundle created
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BundleImport {
+    /// The new idea
+    pub idea: Idea,
+    pub attachments: usize,
+    pub revisions: usize,
+    pub history: usize,
+    /// Links recreated
+    pub links: usize,
+    /// Links left out because the idea at their other end isn't in this instance
+    pub links_dropped: usize,
+}
+
+/// Read a bundle, checking its version before anything else
+pub fn parse_bundle(text: &str) -> Result<IdeaBundle, IdeaError> {
+    let value: serde_json::Value = serde_json::from_str(text)
+        .map_err(|e| IdeaError::Validation(format!("The bundle isn't valid JSON: {}", e)))?;
+    let version = value
+        .get("schema_version")
+        .and_then(serde_json::Value::as_u64)
+        .ok_or_else(|| IdeaError::Validation("This isn't an idea bundle: it has no schema_version".to_string()))?;
+    if version == 0 || version > u64::from(BUNDLE_SCHEMA_VERSION) {
+        return Err(IdeaError::Validation(format!(
+            "The bundle has schema version {}, but this app reads up to version {}",
+            version, BUNDLE_SCHEMA_VERSION
+        )));
+    }
+    serde_json::from_value(value).map_err(|e| IdeaError::Validation(format!("The bundle couldn't be read: {}", e)))
+}
+
+// Server-only export and import
+#[cfg(feature = "server")]
+pub mod server {
+    use super::*;
+    use crate::attachments::server::{attachment_record, attachments_for, decode_upload};
+    use crate::db::server::{load_owned_idea, IdeaRepository, SurrealIdeaRepository, Transaction};
+    use crate::db::{tidy_author, tidy_tags, IdeaId, IdeaRecord, IDEA_TABLE};
+    use crate::history::server::IdeaEventRecord;
+    use crate::revisions::server::IdeaRevisionRecord;
+    use crate::revisions::MAX_REVISIONS;
+    use crate::utils::{normalize_title, sanitize_text};
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use surrealdb::sql::Thing;
+    use surrealdb::Surreal;
+
+    /// Bundle the idea `id` owned by `user` with its attachments, links, revisions and history
+    pub async fn export_bundle(
+        db: &Surreal<surrealdb::engine::any::Any>,
+        id: &str,
+        user: Option<&Thing>,
+    ) -> Result<IdeaBundle, IdeaError> {
+        #[derive(Deserialize)]
+        struct LinkRow {
+            #[serde(rename = "in")]
+            from: Thing,
+            #[serde(rename = "out")]
+            to: Thing,
+        }
+
+        let record = load_owned_idea(db, id, user).await?;
+        let thing = record.id.clone().ok_or_else(|| IdeaError::NotFound(id.to_string()))?;
+        let repo = SurrealIdeaRepository::new(db.clone());
+
+        let attachments = attachments_for(db, id, user)
+            .await?
+            .into_iter()
+            .map(|attachment| BundledAttachment {
+                filename: attachment.filename,
+                mime_type: attachment.mime_type,
+                data: STANDARD.encode(attachment.data.into_inner()),
+            })
+            .collect();
+        let links: Vec<LinkRow> = db
+            .query("SELECT in, out FROM relates_to WHERE in = $idea OR out = $idea")
+            .bind(("idea", thing.clone()))
+            .await?
+            .take(0)?;
+        let revisions = repo
+            .revisions(&thing)
+            .await?
+            .into_iter()
+            .map(|revision| BundledRevision {
+                what_must_be_true: revision.what_must_be_true,
+                development_notes: revision.development_notes,
+                at: revision.at,
+            })
+            .collect();
+        let history = repo.history(&thing).await?.into_iter().map(IdeaEvent::from).collect();
+
+        Ok(IdeaBundle {
+            schema_version: BUNDLE_SCHEMA_VERSION,
+            exported_at: Utc::now(),
+            idea: record.into(),
+            attachments,
+            links: links
+                .into_iter()
+                .map(|link| GraphEdge {
+                    from: IdeaId::from(&link.from).into(),
+                    to: IdeaId::from(&link.to).into(),
+                })
+                .collect(),
+            revisions,
+            history,
+        })
+    }
+
+    /// Recreate `bundle` as a new idea of `owner`, with fresh ids for it and every related row. With
+    /// `remap_links`, links whose other end isn't an idea here are dropped; without, every link is recreated as it
+    /// was. Everything is read and checked first and then written in one transaction, so a failure leaves nothing
+    /// behind.
+    pub async fn import_bundle(
+        repo: &SurrealIdeaRepository,
+        bundle: IdeaBundle,
+        owner: Option<Thing>,
+        remap_links: bool,
+    ) -> Result<BundleImport, IdeaError> {
+        let idea = &bundle.idea;
+        // Cleaned here already so the slug is made from the title that is stored
+        let title = normalize_title(&sanitize_text(&idea.title));
+        // The id is picked here rather than by the database so the related rows can name it in the same transaction
+        let thing = Thing::from((IDEA_TABLE, uuid::Uuid::new_v4().simple().to_string().as_str()));
+        let record = repo.new_record(IdeaRecord {
+            id: Some(thing.clone()),
+            slug: repo.slug_for(&title, None).await?,
+            title,
+            description: idea.description.clone(),
+            tags: tidy_tags(idea.tags.clone()),
+            what_must_be_true: idea.what_must_be_true.clone(),
+            development_notes: idea.development_notes.clone(),
+            sections: idea.sections.clone(),
+            previous_slugs: Vec::new(),
+            rank: repo.next_rank().await?,
+            owner,
+            deleted_at: None,
+            pinned: idea.pinned,
+            due_date: idea.due_date,
+            status: idea.status,
+            visibility: idea.visibility,
+            author: tidy_author(idea.author.clone()),
+            icon: idea.icon.clone(),
+            custom_fields: idea.custom_fields.clone(),
+            created_at: None,
+            board: None,
+        })?;
+
+        let attachments = bundle
+            .attachments
+            .iter()
+            .map(|attachment| {
+                let data = decode_upload(&attachment.data)?;
+                attachment_record(thing.clone(), &attachment.filename, &attachment.mime_type, data)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        // The bundle has the newest first, and only as many as an idea keeps are brought over
+        let revisions: Vec<IdeaRevisionRecord> = bundle
+            .revisions
+            .iter()
+            .take(MAX_REVISIONS)
+            .map(|revision| IdeaRevisionRecord {
+                id: None,
+                idea: thing.clone(),
+                what_must_be_true: revision.what_must_be_true.clone(),
+                development_notes: revision.development_notes.clone(),
+                at: revision.at,
+            })
+            .collect();
+        let history: Vec<IdeaEventRecord> = bundle
+            .history
+            .iter()
+            .map(|event| IdeaEventRecord {
+                id: None,
+                idea: thing.clone(),
+                kind: event.kind,
+                summary: event.summary.clone(),
+                at: event.at,
+            })
+            .collect();
+        let (links, links_dropped) = bundle_links(repo, &bundle, &thing, remap_links).await?;
+
+        let imported = BundleImport {
+            idea: Idea::default(),
+            attachments: attachments.len(),
+            revisions: revisions.len(),
+            history: history.len(),
+            links: links.len(),
+            links_dropped,
+        };
+        let (created, mut response) = repo
+            .transaction(|transaction| {
+                transaction.bind("record", record);
+                let created = transaction.push("CREATE ideas CONTENT $record");
+                insert_rows(transaction, "attachments", attachments);
+                insert_rows(transaction, "idea_revisions", revisions);
+                insert_rows(transaction, "idea_events", history);
+                for (index, (from, to)) in links.into_iter().enumerate() {
+                    transaction.bind(format!("from{}", index), from);
+                    transaction.bind(format!("to{}", index), to);
+                    transaction.push(format!("RELATE $from{}->relates_to->$to{}", index, index));
+                }
+                Ok(created)
+            })
+            .await?;
+        let created: Option<IdeaRecord> = response.take(created)?;
+        let created = created.ok_or_else(|| IdeaError::Database("Failed to create idea".to_string()))?;
+        Ok(BundleImport {
+            idea: created.into(),
+            ..imported
+        })
+    }
+
+    /// Add inserting `rows` into `table` to `transaction`, when there are any
+    fn insert_rows<T: Serialize + 'static>(transaction: &mut Transaction<'_>, table: &str, rows: Vec<T>) {
+        if !rows.is_empty() {
+            transaction.bind(table, rows);
+            transaction.push(format!("INSERT INTO {} ${}", table, table));
+        }
+    }
+
+    /// The links of `bundle` to recreate for its new `idea`, and how many were left out
+    async fn bundle_links(
+        repo: &SurrealIdeaRepository,
+        bundle: &IdeaBundle,
+        idea: &Thing,
+        remap_links: bool,
+    ) -> Result<(Vec<(Thing, Thing)>, usize), IdeaError> {
+        let mut links = Vec::new();
+        let mut dropped = 0;
+        let old_id = bundle.idea.id.clone().unwrap_or_default();
+        // The bundled idea's end moves to its new id. The other end keeps its id, which names the same idea here
+        // only when both instances share data; with `remap_links` the link is kept only if that idea exists.
+        let end = |id: &str| if id == old_id { Ok(idea.clone()) } else { IdeaId::parse(id).map(|id| Thing::from(&id)) };
+        for link in &bundle.links {
+            let (Ok(from), Ok(to)) = (end(&link.from), end(&link.to)) else {
+                dropped += 1;
+                continue;
+            };
+            let other = if from == *idea { &to } else { &from };
+            if remap_links && other != idea && repo.get(&IdeaId::from(other).to_string()).await.is_err() {
+                dropped += 1;
+                continue;
+            }
+            links.push((from, to));
+        }
+        Ok((links, dropped))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bundle() -> IdeaBundle {
+        IdeaBundle {
+            schema_version: BUNDLE_SCHEMA_VERSION,
+            exported_at: Utc::now(),
+            idea: Idea {
+                id: Some("ideas:abc".to_string()),
+                title: "Bundled".to_string(),
+                ..Default::default()
+            },
+            attachments: Vec::new(),
+            links: vec![GraphEdge {
+                from: "ideas:abc".to_string(),
+                to: "ideas:def".to_string(),
+            }],
+            revisions: Vec::new(),
+            history: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_bundle_round_trips_through_json() {
+        let bundle = bundle();
+        let text = serde_json::to_string(&bundle).unwrap();
+        assert_eq!(parse_bundle(&text).unwrap(), bundle);
+    }
+
+    #[test]
+    fn test_future_versions_are_refused() {
+        let mut value = serde_json::to_value(bundle()).unwrap();
+        value["schema_version"] = (BUNDLE_SCHEMA_VERSION + 1).into();
+        // Fields a newer version might add don't matter, the version is checked first
+        value["reactions"] = serde_json::json!([{ "emoji": "👍" }]);
+        value["idea"] = serde_json::json!("a shape this version doesn't know");
+
+        let Err(IdeaError::Validation(message)) = parse_bundle(&value.to_string()) else {
+            panic!("a newer bundle must be refused");
+        };
+        assert!(message.contains(&format!("schema version {}", BUNDLE_SCHEMA_VERSION + 1)));
+    }
+
+    #[test]
+    fn test_malformed_bundles_are_refused() {
+        for text in ["", "not json", "{}", "{\"schema_version\": \"1\"}", "{\"schema_version\": 0}", "[1, 2]"] {
+            assert!(matches!(parse_bundle(text), Err(IdeaError::Validation(_))), "{}", text);
+        }
+        // The right version but not the right shape
+        assert!(matches!(parse_bundle("{\"schema_version\": 1, \"idea\": 5}"), Err(IdeaError::Validation(_))));
+    }
+}
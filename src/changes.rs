@@ -0,0 +1,251 @@
+//! "Someone else changed this idea" notifications.
+//!
+//! Each browser tab picks a random client id. Saves carry it, and the tab subscribes to the idea it has open with
+//! the same id, so the server can tell the tab about every change except its own. Subscriptions are served as
+//! server-sent events from `/api/v1/ideas/{id}/events`; the fan-out itself is [`server::ChangeHub`].
+
+use crate::base_path::with_base_path;
+use crate::utils::percent_encode;
+
+/// A random identifier for this tab, sent with saves and subscriptions
+pub fn new_client_id() -> String {
+    let now = chrono::Utc::now();
+    // Millisecond time plus the sub-second nanos mixed by a large odd constant: unique enough to tell tabs apart
+    let salt = u64::from(now.timestamp_subsec_nanos()).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    format!("{:x}{:016x}", now.timestamp_millis(), salt)
+}
+
+/// URL of the event stream for changes to `idea_id` made by anyone but `client_id`
+pub fn events_url(idea_id: &str, client_id: &str) -> String {
+    with_base_path(&format!(
+        "/api/v1/ideas/{}/events?client={}",
+        percent_encode(idea_id),
+        percent_encode(client_id)
+    ))
+}
+
+// Server-only fan-out of change events
+#[cfg(feature = "server")]
+pub mod server {
+    use serde::Serialize;
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex, OnceLock};
+    use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+
+    /// Sent to subscribers when an idea they have open changes
+    #[derive(Debug, Clone, PartialEq, Serialize)]
+    pub struct IdeaChanged {
+        pub idea_id: String,
+    }
+
+    struct Subscriber {
+        key: u64,
+        client_id: String,
+        sender: UnboundedSender<IdeaChanged>,
+    }
+
+    #[derive(Default)]
+    struct Registry {
+        next_key: u64,
+        by_idea: HashMap<String, Vec<Subscriber>>,
+    }
+
+    /// Registry of who has which idea open. Clones share the same registry.
+    #[derive(Clone, Default)]
+    pub struct ChangeHub {
+        registry: Arc<Mutex<Registry>>,
+    }
+
+    impl ChangeHub {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        fn lock(&self) -> std::sync::MutexGuard<'_, Registry> {
+            self.registry.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+        }
+
+        /// Listen for changes to `idea_id` made by clients other than `client_id`.
+        /// Dropping the subscription unsubscribes.
+        pub fn subscribe(&self, idea_id: &str, client_id: &str) -> Subscription {
+            let idea_id = registry_key(idea_id);
+            let (sender, receiver) = unbounded_channel();
+            let mut registry = self.lock();
+            registry.next_key += 1;
+            let key = registry.next_key;
+            registry.by_idea.entry(idea_id.clone()).or_default().push(Subscriber {
+                key,
+                client_id: client_id.to_string(),
+                sender,
+            });
+
+            Subscription {
+                receiver,
+                hub: self.clone(),
+                idea_id,
+                key,
+            }
+        }
+
+        fn unsubscribe(&self, idea_id: &str, key: u64) {
+            let mut registry = self.lock();
+            if let Some(subscribers) = registry.by_idea.get_mut(idea_id) {
+                subscribers.retain(|subscriber| subscriber.key != key);
+                if subscribers.is_empty() {
+                    registry.by_idea.remove(idea_id);
+                }
+            }
+        }
+
+        /// Tell everyone watching `idea_id` except `editor` that it changed. Returns how many were notified.
+        pub fn broadcast(&self, idea_id: &str, editor: Option<&str>) -> usize {
+            let idea_id = registry_key(idea_id);
+            let mut registry = self.lock();
+            let Some(subscribers) = registry.by_idea.get_mut(&idea_id) else {
+                return 0;
+            };
+
+            // Subscribers whose receiving end is gone are dropped on the way
+            subscribers.retain(|subscriber| !subscriber.sender.is_closed());
+            let event = IdeaChanged { idea_id };
+            subscribers
+                .iter()
+                .filter(|subscriber| Some(subscriber.client_id.as_str()) != editor)
+                .filter(|subscriber| subscriber.sender.send(event.clone()).is_ok())
+                .count()
+        }
+
+        /// Number of ideas somebody is watching
+        #[cfg(test)]
+        fn watched_ideas(&self) -> usize {
+            self.lock().by_idea.len()
+        }
+    }
+
+    /// `idea_id` in its plain `ideas:<key>` shape, so a subscription from a link with `ideas:⟨abc⟩` or an encoded
+    /// id hears about saves made under `ideas:abc`
+    fn registry_key(idea_id: &str) -> String {
+        crate::db::IdeaId::parse(idea_id).map_or_else(|_| idea_id.to_string(), String::from)
+    }
+
+    /// An open subscription. Receive from `receiver`; drop it to unsubscribe.
+    pub struct Subscription {
+        pub receiver: UnboundedReceiver<IdeaChanged>,
+        hub: ChangeHub,
+        idea_id: String,
+        key: u64,
+    }
+
+    impl Drop for Subscription {
+        fn drop(&mut self) {
+            self.hub.unsubscribe(&self.idea_id, self.key);
+        }
+    }
+
+    /// The hub shared by the server functions and the event stream route
+    pub fn hub() -> &'static ChangeHub {
+        static HUB: OnceLock<ChangeHub> = OnceLock::new();
+        HUB.get_or_init(ChangeHub::new)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_broadcast_reaches_other_clients_only() {
+            let hub = ChangeHub::new();
+            let mut alice = hub.subscribe("ideas:1", "alice");
+            let mut bob = hub.subscribe("ideas:1", "bob");
+
+            assert_eq!(hub.broadcast("ideas:1", Some("alice")), 1);
+            assert!(alice.receiver.try_recv().is_err());
+            assert_eq!(
+                bob.receiver.try_recv().unwrap(),
+                IdeaChanged {
+                    idea_id: "ideas:1".to_string()
+                }
+            );
+
+            // Edits from outside the app (no editor) reach everyone
+            assert_eq!(hub.broadcast("ideas:1", None), 2);
+        }
+
+        #[test]
+        fn test_broadcast_is_scoped_to_the_idea() {
+            let hub = ChangeHub::new();
+            let mut watcher = hub.subscribe("ideas:1", "alice");
+
+            assert_eq!(hub.broadcast("ideas:2", None), 0);
+            assert!(watcher.receiver.try_recv().is_err());
+        }
+
+        #[test]
+        fn test_dropping_the_subscription_unsubscribes() {
+            let hub = ChangeHub::new();
+            let first = hub.subscribe("ideas:1", "alice");
+            let second = hub.subscribe("ideas:1", "bob");
+            assert_eq!(hub.watched_ideas(), 1);
+
+            drop(first);
+            assert_eq!(hub.broadcast("ideas:1", None), 1);
+
+            drop(second);
+            assert_eq!(hub.watched_ideas(), 0);
+            assert_eq!(hub.broadcast("ideas:1", None), 0);
+        }
+
+        #[test]
+        fn test_closed_receivers_are_pruned() {
+            let hub = ChangeHub::new();
+            let mut subscription = hub.subscribe("ideas:1", "alice");
+            subscription.receiver.close();
+
+            assert_eq!(hub.broadcast("ideas:1", None), 0);
+        }
+
+        #[test]
+        fn test_same_client_can_watch_in_two_places() {
+            let hub = ChangeHub::new();
+            let _first = hub.subscribe("ideas:1", "alice");
+            let _second = hub.subscribe("ideas:1", "alice");
+
+            assert_eq!(hub.broadcast("ideas:1", Some("bob")), 2);
+        }
+
+        #[test]
+        fn test_any_id_shape_reaches_the_same_watchers() {
+            let hub = ChangeHub::new();
+            let mut watcher = hub.subscribe("ideas%3A%E2%9F%A81%E2%9F%A9", "alice");
+
+            assert_eq!(hub.broadcast("ideas:1", None), 1);
+            assert_eq!(
+                watcher.receiver.try_recv().unwrap(),
+                IdeaChanged {
+                    idea_id: "ideas:1".to_string()
+                }
+            );
+            assert_eq!(hub.broadcast("1", None), 1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_events_url_encodes_the_id() {
+        assert_eq!(
+            events_url("ideas:abc", "tab1"),
+            "/api/v1/ideas/ideas%3Aabc/events?client=tab1"
+        );
+    }
+
+    #[test]
+    fn test_client_ids_differ() {
+        let first = new_client_id();
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        assert_ne!(first, new_client_id());
+    }
+}
@@ -0,0 +1,70 @@
+//! Failures seen by the client, reported to the server log.
+//!
+//! [`log_error`] prints a failure locally (the browser console, or stderr on desktop) and sends it to
+//! `report_client_error_server`, which writes it to the server log with the `client` target. A report that fails
+//! is only printed locally and never reported again, so a dropped connection can't turn into reports about failed
+//! reports. Reports are clipped on both ends and rate-limited per client apart from writes.
+
+use crate::server_functions::report_client_error_server;
+use dioxus::core::spawn_forever;
+use std::fmt::Display;
+
+/// Longest context kept, in characters
+pub const MAX_CONTEXT_CHARS: usize = 100;
+/// Longest message kept, in characters
+pub const MAX_MESSAGE_CHARS: usize = 2000;
+
+/// `text` on a single line and cut to `max` characters, so a report can't forge extra log lines
+pub fn clip(text: &str, max: usize) -> String {
+    text.trim()
+        .chars()
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .take(max)
+        .collect()
+}
+
+/// Record a failure the user may not see, e.g. `log_error("delete idea", &e)`. Never fails and never blocks.
+pub fn log_error(context: &str, error: impl Display) {
+    let context = clip(context, MAX_CONTEXT_CHARS);
+    let message = clip(&error.to_string(), MAX_MESSAGE_CHARS);
+    print_locally(&context, &message);
+
+    // Outlives the component that failed, which may be gone by then (a deleted card)
+    spawn_forever(async move {
+        if let Err(e) = report_client_error_server(context, message).await {
+            print_locally("report client error", &e);
+        }
+    });
+}
+
+fn print_locally(context: &str, message: impl Display) {
+    #[cfg(target_arch = "wasm32")]
+    web_sys::console::error_1(&format!("{}: {}", context, message).into());
+    #[cfg(not(target_arch = "wasm32"))]
+    eprintln!("{}: {}", context, message);
+}
+
+// Server-side logging of the reports
+#[cfg(feature = "server")]
+pub mod server {
+    use super::*;
+
+    /// Write a client's report to the server log, clipped again since the client can send anything
+    pub fn record(context: &str, message: &str, client: Option<&str>) {
+        let context = clip(context, MAX_CONTEXT_CHARS);
+        let message = clip(message, MAX_MESSAGE_CHARS);
+        tracing::warn!(target: "client", context = %context, client, "{}", message);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clip_keeps_reports_on_one_line() {
+        assert_eq!(clip("  save failed\nFAKE LOG LINE\r\t ", 100), "save failed FAKE LOG LINE");
+        assert_eq!(clip("ééééé", 3), "ééé");
+        assert_eq!(clip(&"x".repeat(MAX_MESSAGE_CHARS + 10), MAX_MESSAGE_CHARS).len(), MAX_MESSAGE_CHARS);
+    }
+}
@@ -0,0 +1,38 @@
+//! Writing text to the system clipboard.
+//!
+//! The web build goes through `navigator.clipboard`, which the browser may refuse (no permission, page not
+//! focused, plain http). The desktop build writes to the system clipboard directly. Either way a failure comes back
+//! as a message to show the user.
+
+/// Put `text` on the clipboard
+#[cfg(feature = "desktop")]
+pub async fn copy_text(text: String) -> Result<(), String> {
+    arboard::Clipboard::new()
+        .and_then(|mut clipboard| clipboard.set_text(text))
+        .map_err(|e| format!("Couldn't copy: {}", e))
+}
+
+/// Put `text` on the clipboard
+#[cfg(not(feature = "desktop"))]
+pub async fn copy_text(text: String) -> Result<(), String> {
+    use dioxus::prelude::document;
+
+    // The script answers with an empty string on success and the browser's reason otherwise
+    let mut script = document::eval(
+        r#"
+        const text = await dioxus.recv();
+        try {
+            await navigator.clipboard.writeText(text);
+            dioxus.send("");
+        } catch (error) {
+            dioxus.send(String(error && error.message ? error.message : error));
+        }
+        "#,
+    );
+    script.send(text).map_err(|e| format!("Couldn't copy: {}", e))?;
+    match script.recv::<String>().await {
+        Ok(reason) if reason.is_empty() => Ok(()),
+        Ok(reason) => Err(format!("Couldn't copy: {}", reason)),
+        Err(e) => Err(format!("Couldn't copy: {}", e)),
+    }
+}
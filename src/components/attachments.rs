@@ -0,0 +1,141 @@
+use crate::attachments::{format_size, validate_attachment, Attachment, ALLOWED_MIME_TYPES};
+use crate::error::error_message;
+use crate::hooks::use_stylesheet;
+use crate::server_functions::{
+    delete_attachment_server, list_attachments_server, upload_attachment_server,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use dioxus::prelude::*;
+
+const ATTACHMENTS_CSS: Asset = asset!("/assets/styling/attachments.css");
+
+/// Files attached to an idea, with an upload input and image thumbnails
+#[component]
+pub fn Attachments(idea_id: String) -> Element {
+    use_stylesheet(ATTACHMENTS_CSS);
+
+    let mut is_uploading = use_signal(|| false);
+    let mut error = use_signal(|| String::new());
+
+    let list_id = idea_id.clone();
+    let mut attachments = use_resource(move || {
+        let idea_id = list_id.clone();
+        async move { list_attachments_server(idea_id).await }
+    });
+
+    rsx! {
+        div {
+            class: "attachments-section",
+            h2 { "attachments" }
+
+            match &*attachments.read() {
+                Some(Ok(list)) if list.is_empty() => rsx! {
+                    p { class: "attachments-empty", "No files attached yet." }
+                },
+                Some(Ok(list)) => rsx! {
+                    div {
+                        class: "attachment-grid",
+                        for attachment in list.clone() {
+                            AttachmentTile {
+                                key: "{attachment.id}",
+                                attachment,
+                                on_delete: move |id: String| async move {
+                                    match delete_attachment_server(id).await {
+                                        Ok(()) => attachments.restart(),
+                                        Err(e) => error.set(error_message(&e)),
+                                    }
+                                },
+                            }
+                        }
+                    }
+                },
+                Some(Err(e)) => rsx! {
+                    p { class: "attachments-error", "Failed to load attachments: {error_message(e)}" }
+                },
+                None => rsx! {},
+            }
+
+            label {
+                class: "upload-btn",
+                class: if is_uploading() { "uploading" },
+                if is_uploading() { "uploading..." } else { "attach a file" }
+                input {
+                    r#type: "file",
+                    multiple: true,
+                    accept: ALLOWED_MIME_TYPES.join(","),
+                    disabled: is_uploading(),
+                    onchange: move |evt: FormEvent| {
+                        let idea_id = idea_id.clone();
+                        async move {
+                            is_uploading.set(true);
+                            error.set(String::new());
+
+                            for file in evt.files() {
+                                let filename = file.name();
+                                let mime_type = file.content_type().unwrap_or_default();
+
+                                // Check locally first so oversized files aren't sent at all
+                                if let Err(message) = validate_attachment(&filename, &mime_type, file.size() as usize) {
+                                    error.set(message);
+                                    continue;
+                                }
+
+                                let bytes = match file.read_bytes().await {
+                                    Ok(bytes) => bytes,
+                                    Err(_) => {
+                                        error.set(format!("Couldn't read {}", filename));
+                                        continue;
+                                    }
+                                };
+
+                                let data = STANDARD.encode(&bytes);
+                                if let Err(e) = upload_attachment_server(idea_id.clone(), filename, mime_type, data).await {
+                                    error.set(error_message(&e));
+                                }
+                            }
+
+                            is_uploading.set(false);
+                            attachments.restart();
+                        }
+                    },
+                }
+            }
+
+            if !error().is_empty() {
+                p { class: "attachments-error", "{error}" }
+            }
+        }
+    }
+}
+
+/// One attachment: a thumbnail for images, a file chip for everything else
+#[component]
+fn AttachmentTile(attachment: Attachment, on_delete: EventHandler<String>) -> Element {
+    let id = attachment.id.clone();
+
+    rsx! {
+        div {
+            class: "attachment-tile",
+            a {
+                href: "{attachment.data_url}",
+                download: "{attachment.filename}",
+                title: "{attachment.filename}",
+                if attachment.is_image() {
+                    img { class: "attachment-thumb", src: "{attachment.data_url}", alt: "{attachment.filename}" }
+                } else {
+                    div { class: "attachment-file", "{attachment.filename}" }
+                }
+            }
+            div {
+                class: "attachment-meta",
+                span { class: "attachment-size", "{format_size(attachment.size)}" }
+                button {
+                    r#type: "button",
+                    class: "attachment-delete",
+                    onclick: move |_| on_delete.call(id.clone()),
+                    "×"
+                }
+            }
+        }
+    }
+}
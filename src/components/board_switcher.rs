@@ -0,0 +1,193 @@
+use crate::boards::{Board, DEFAULT_BOARD_SLUG};
+use crate::components::ConfirmDialog;
+use crate::error::error_message;
+use crate::hooks::{use_app_config, use_settings, use_stylesheet, use_toasts};
+use crate::server_functions::{
+    create_board_server, current_board_server, delete_board_server, list_boards_server, rename_board_server,
+};
+use crate::utils::ListQuery;
+use crate::Route;
+use dioxus::prelude::*;
+
+const BOARD_SWITCHER_CSS: Asset = asset!("/assets/styling/board_switcher.css");
+
+/// Navbar dropdown for picking the board of ideas to work on, with a panel to create, rename and delete boards.
+///
+/// Picking a board opens its `/b/<slug>` page, which does the switching. Deleting a board moves whatever it holds to
+/// the board picked in the panel, the default board unless another one is.
+#[component]
+pub fn BoardSwitcher() -> Element {
+    use_stylesheet(BOARD_SWITCHER_CSS);
+
+    let mut settings = use_settings();
+    let mut toasts = use_toasts();
+    let read_only = use_app_config().read_only();
+
+    let mut boards = use_resource(|| async move { list_boards_server().await.unwrap_or_default() });
+    // The server decides which board the lists come from; a board deleted elsewhere leaves the stored one stale
+    use_hook(move || {
+        spawn(async move {
+            if let Ok(board) = current_board_server().await {
+                settings.set_active_board(board.slug);
+            }
+        });
+    });
+
+    let mut manage_open = use_signal(|| false);
+    let mut new_name = use_signal(String::new);
+    let mut rename = use_signal(String::new);
+    let mut move_to = use_signal(String::new);
+    let mut confirm_delete = use_signal(|| false);
+
+    let all: Vec<Board> = boards().unwrap_or_default();
+    let active = settings.active_board();
+    let current = all.iter().find(|board| board.slug == active).cloned().unwrap_or_default();
+    let others: Vec<Board> = all.iter().filter(|board| board.slug != active).cloned().collect();
+
+    let open = move |slug: String| {
+        navigator().push(Route::BoardIdeas {
+            slug,
+            query: ListQuery::default(),
+        });
+    };
+
+    let create = move |event: FormEvent| {
+        event.prevent_default();
+        spawn(async move {
+            match create_board_server(new_name()).await {
+                Ok(board) => {
+                    new_name.set(String::new());
+                    boards.restart();
+                    open(board.slug);
+                }
+                Err(e) => toasts.error(format!("Couldn't create the board: {}", error_message(&e))),
+            }
+        });
+    };
+
+    let save_name = move |event: FormEvent| {
+        event.prevent_default();
+        spawn(async move {
+            match rename_board_server(settings.active_board(), rename()).await {
+                Ok(_) => {
+                    rename.set(String::new());
+                    boards.restart();
+                }
+                Err(e) => toasts.error(format!("Couldn't rename the board: {}", error_message(&e))),
+            }
+        });
+    };
+
+    // Without a pick the ideas go to the default board, which the picker shows as selected
+    let delete = move |_| {
+        confirm_delete.set(false);
+        let target = Some(move_to()).filter(|slug| !slug.is_empty()).unwrap_or(DEFAULT_BOARD_SLUG.to_string());
+        spawn(async move {
+            match delete_board_server(settings.active_board(), Some(target.clone())).await {
+                Ok(moved) => {
+                    toasts.success(format!("Board deleted, {} idea(s) moved", moved));
+                    manage_open.set(false);
+                    move_to.set(String::new());
+                    boards.restart();
+                    open(target);
+                }
+                Err(e) => toasts.error(format!("Couldn't delete the board: {}", error_message(&e))),
+            }
+        });
+    };
+
+    rsx! {
+        div {
+            class: "board-switcher",
+            select {
+                aria_label: "board",
+                onchange: move |event: FormEvent| open(event.value()),
+                for board in all.iter().cloned() {
+                    option {
+                        key: "{board.slug}",
+                        value: "{board.slug}",
+                        selected: board.slug == active,
+                        "{board.name}"
+                    }
+                }
+            }
+            if !read_only {
+                button {
+                    r#type: "button",
+                    class: "navbar-link",
+                    title: "manage boards",
+                    aria_expanded: manage_open(),
+                    onclick: move |_| manage_open.toggle(),
+                    "boards"
+                }
+            }
+
+            if manage_open() && !read_only {
+                div {
+                    class: "board-panel",
+                    form {
+                        onsubmit: create,
+                        input {
+                            r#type: "text",
+                            placeholder: "new board",
+                            aria_label: "name of the new board",
+                            value: "{new_name}",
+                            oninput: move |event| new_name.set(event.value()),
+                        }
+                        button { r#type: "submit", disabled: new_name().trim().is_empty(), "create" }
+                    }
+
+                    if !current.is_default() && !current.slug.is_empty() {
+                        form {
+                            onsubmit: save_name,
+                            input {
+                                r#type: "text",
+                                placeholder: "{current.name}",
+                                aria_label: "new name of {current.name}",
+                                value: "{rename}",
+                                oninput: move |event| rename.set(event.value()),
+                            }
+                            button { r#type: "submit", disabled: rename().trim().is_empty(), "rename" }
+                        }
+                        div {
+                            class: "board-delete",
+                            label {
+                                "move its ideas to "
+                                select {
+                                    onchange: move |event: FormEvent| move_to.set(event.value()),
+                                    for board in others.iter().cloned() {
+                                        option {
+                                            key: "{board.slug}",
+                                            value: "{board.slug}",
+                                            selected: if move_to().is_empty() {
+                                                board.is_default()
+                                            } else {
+                                                board.slug == move_to()
+                                            },
+                                            "{board.name}"
+                                        }
+                                    }
+                                }
+                            }
+                            button {
+                                r#type: "button",
+                                class: "danger",
+                                onclick: move |_| confirm_delete.set(true),
+                                "delete board"
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        if confirm_delete() {
+            ConfirmDialog {
+                message: format!("Delete the board \"{}\"? Its ideas move to the board picked.", current.name),
+                confirm_label: "delete",
+                on_confirm: delete,
+                on_cancel: move |_| confirm_delete.set(false),
+            }
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use crate::components::CopyIdeaButton;
+use crate::db::IdeaSummary;
+use crate::hooks::{use_app_config, FocusHandle};
+use crate::utils::{card_actions, CardAction};
+use crate::Route;
+use dioxus::prelude::*;
+
+/// The copy, pin, develop and delete buttons in the header of an idea card, as far as [`card_actions`] offers them.
+/// Develop links to `develop_to`. Delete only calls `on_delete`, so the card can ask first; it registers with
+/// `delete_button` so focus can be brought back to it when the card is done asking.
+#[component]
+pub fn CardActions(
+    idea: IdeaSummary,
+    develop_to: Option<Route>,
+    on_toggle_pin: EventHandler<String>,
+    on_delete: EventHandler<()>,
+    delete_button: FocusHandle,
+) -> Element {
+    let mut delete_button = delete_button;
+    let read_only = use_app_config().read_only();
+    let Some(id) = idea.id.clone() else {
+        return rsx! {};
+    };
+    let pin_label = if idea.pinned {
+        format!("unpin {}", idea.title)
+    } else {
+        format!("pin {} to the top", idea.title)
+    };
+
+    rsx! {
+        div {
+            class: "idea-actions",
+            for action in card_actions(true, read_only) {
+                match action {
+                    CardAction::Copy => rsx! {
+                        CopyIdeaButton { idea_id: id.clone(), title: idea.title.clone(), class: "copy-btn" }
+                    },
+                    CardAction::Pin => {
+                        let id = id.clone();
+                        rsx! {
+                            button {
+                                r#type: "button",
+                                class: "pin-btn",
+                                class: if idea.pinned { "active" },
+                                title: "{pin_label}",
+                                aria_label: "{pin_label}",
+                                aria_pressed: idea.pinned,
+                                onclick: move |_| on_toggle_pin.call(id.clone()),
+                                if idea.pinned { "◆" } else { "◇" }
+                            }
+                        }
+                    }
+                    CardAction::Develop => match develop_to.clone() {
+                        Some(to) => rsx! {
+                            Link { to, class: "develop-btn", aria_label: "develop {idea.title}", "→" }
+                        },
+                        None => rsx! {},
+                    },
+                    CardAction::Delete => rsx! {
+                        button {
+                            r#type: "button",
+                            class: "delete-btn",
+                            aria_label: "move {idea.title} to the trash",
+                            onmounted: move |event| delete_button.mounted(event),
+                            onclick: move |evt| {
+                                evt.prevent_default();
+                                evt.stop_propagation();
+                                on_delete.call(());
+                            },
+                            "×"
+                        }
+                    },
+                    // Shown elsewhere on the card
+                    CardAction::EditTags | CardAction::AddStatement => rsx! {},
+                }
+            }
+        }
+    }
+}
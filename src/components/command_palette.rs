@@ -0,0 +1,276 @@
+use crate::db::IdeaSummary;
+use crate::error::error_message;
+use crate::hooks::{
+    use_app_config, use_focus_on_mount, use_idea_list_revision, use_new_idea_request, use_stylesheet, use_theme,
+    use_trash_revision, use_unsaved_changes,
+};
+use crate::server_functions::{delete_idea_server, get_idea_summaries_server, toggle_pin_server};
+use crate::utils::{best_matches, ListQuery};
+use crate::Route;
+use dioxus::prelude::*;
+
+const COMMAND_PALETTE_CSS: Asset = asset!("/assets/styling/command_palette.css");
+
+/// Commands listed at once; typing narrows the rest down
+const MAX_RESULTS: usize = 8;
+
+/// Something the palette can do
+#[derive(Debug, Clone, PartialEq)]
+enum Command {
+    NewIdea,
+    GoToTrash,
+    ToggleTheme,
+    Open { idea: IdeaSummary },
+    Develop { id: String, title: String },
+    TogglePin { id: String, title: String, pinned: bool },
+    Delete { id: String, title: String },
+}
+
+impl Command {
+    /// What the palette shows, and what the query is matched against
+    fn label(&self) -> String {
+        match self {
+            Command::NewIdea => "new idea".to_string(),
+            Command::GoToTrash => "go to trash".to_string(),
+            Command::ToggleTheme => "toggle theme".to_string(),
+            Command::Open { idea } => format!("open: {}", idea.title),
+            Command::Develop { title, .. } => format!("develop: {}", title),
+            Command::TogglePin { title, pinned: true, .. } => format!("unpin: {}", title),
+            Command::TogglePin { title, pinned: false, .. } => format!("pin: {}", title),
+            Command::Delete { title, .. } => format!("delete: {}", title),
+        }
+    }
+}
+
+/// The global commands followed by every action on every idea. A read-only deployment only gets the ones that
+/// change nothing.
+fn commands(ideas: &[IdeaSummary], read_only: bool) -> Vec<Command> {
+    let mut commands = vec![Command::GoToTrash, Command::ToggleTheme];
+    if !read_only {
+        commands.insert(0, Command::NewIdea);
+    }
+    for idea in ideas {
+        commands.push(Command::Open { idea: idea.clone() });
+        let (false, Some(id)) = (read_only, idea.id.clone()) else {
+            continue;
+        };
+        let title = idea.title.clone();
+        commands.push(Command::Develop { id: id.clone(), title: title.clone() });
+        commands.push(Command::TogglePin { id: id.clone(), title: title.clone(), pinned: idea.pinned });
+        commands.push(Command::Delete { id, title });
+    }
+    commands
+}
+
+/// Keyboard-driven list of commands over the signed-in user's ideas, shown while `open` is set (Cmd/Ctrl+K).
+///
+/// Stays mounted so the ideas fetched on the first open are reused by the next ones; pins and deletes made here
+/// update that copy.
+#[component]
+pub fn CommandPalette(open: Signal<bool>) -> Element {
+    use_stylesheet(COMMAND_PALETTE_CSS);
+
+    let mut ideas = use_signal(|| Option::<Vec<IdeaSummary>>::None);
+    let mut error = use_signal(|| Option::<String>::None);
+    let mut fetch_started = use_signal(|| false);
+
+    let mut theme = use_theme();
+    let app_config = use_app_config();
+    let mut new_idea = use_new_idea_request();
+    // Going somewhere from the palette asks before leaving unsaved edits, like the links do
+    let mut unsaved = use_unsaved_changes();
+    let mut trash_revision = use_trash_revision();
+    let mut idea_list_revision = use_idea_list_revision();
+
+    // Fetch the ideas the first time the palette opens; a failed fetch is tried again on the next open
+    use_effect(move || {
+        if !open() || *fetch_started.peek() {
+            return;
+        }
+        fetch_started.set(true);
+        spawn(async move {
+            match get_idea_summaries_server().await {
+                Ok(list) => {
+                    error.set(None);
+                    ideas.set(Some(list));
+                }
+                Err(e) => {
+                    error.set(Some(format!("Failed to load ideas: {}", error_message(&e))));
+                    fetch_started.set(false);
+                }
+            }
+        });
+    });
+
+    let run = move |command: Command| {
+        open.set(false);
+        error.set(None);
+        match command {
+            Command::NewIdea => {
+                new_idea.request();
+                unsaved.push(Route::Home { query: ListQuery::default() });
+            }
+            Command::GoToTrash => {
+                unsaved.push(Route::Trash {});
+            }
+            Command::ToggleTheme => theme.cycle(),
+            Command::Open { idea } => {
+                let to = match idea.id {
+                    Some(id) if idea.slug.is_empty() => Route::IdeaDevelopment { id },
+                    _ => Route::IdeaBySlug { slug: idea.slug },
+                };
+                unsaved.push(to);
+            }
+            Command::Develop { id, .. } => {
+                unsaved.push(Route::IdeaDevelopment { id });
+            }
+            Command::TogglePin { id, title, .. } => {
+                spawn(async move {
+                    match toggle_pin_server(id.clone()).await {
+                        Ok(pinned) => {
+                            if let Some(list) = ideas.write().as_mut() {
+                                for idea in list.iter_mut().filter(|idea| idea.id.as_ref() == Some(&id)) {
+                                    idea.pinned = pinned;
+                                }
+                            }
+                            idea_list_revision.bump();
+                        }
+                        // Reopen so the failure is seen where the command was run
+                        Err(e) => {
+                            error.set(Some(format!("Failed to pin {}: {}", title, error_message(&e))));
+                            open.set(true);
+                        }
+                    }
+                });
+            }
+            Command::Delete { id, title } => {
+                spawn(async move {
+                    match delete_idea_server(id.clone()).await {
+                        Ok(_) => {
+                            if let Some(list) = ideas.write().as_mut() {
+                                list.retain(|idea| idea.id.as_ref() != Some(&id));
+                            }
+                            trash_revision.bump();
+                            idea_list_revision.bump();
+                        }
+                        Err(e) => {
+                            error.set(Some(format!("Failed to delete {}: {}", title, error_message(&e))));
+                            open.set(true);
+                        }
+                    }
+                });
+            }
+        }
+    };
+
+    rsx! {
+        if open() {
+            PaletteDialog {
+                commands: commands(ideas().as_deref().unwrap_or_default(), app_config.read_only()),
+                loading: ideas().is_none() && error().is_none(),
+                error: error(),
+                on_run: run,
+                on_close: move |_| open.set(false),
+            }
+        }
+    }
+}
+
+/// The open palette: a search box over `commands` and the best matches, navigated with the arrow keys. Mounted
+/// afresh on each open so the query starts empty.
+#[component]
+fn PaletteDialog(
+    commands: Vec<Command>,
+    loading: bool,
+    error: Option<String>,
+    on_run: EventHandler<Command>,
+    on_close: EventHandler<()>,
+) -> Element {
+    let mut query = use_signal(String::new);
+    let mut selected = use_signal(|| 0usize);
+    let mut input = use_focus_on_mount();
+
+    let matches: Vec<Command> = best_matches(&query(), &commands, Command::label, MAX_RESULTS)
+        .into_iter()
+        .cloned()
+        .collect();
+    let count = matches.len();
+    // The selection may point past the end after the query narrowed the list
+    let current = selected().min(count.saturating_sub(1));
+    let highlighted = matches.get(current).cloned();
+
+    rsx! {
+        div {
+            class: "palette-backdrop",
+            onclick: move |_| on_close.call(()),
+            div {
+                class: "palette",
+                role: "dialog",
+                aria_modal: "true",
+                aria_label: "command palette",
+                onclick: move |event| event.stop_propagation(),
+                input {
+                    class: "palette-input",
+                    r#type: "text",
+                    placeholder: "type a command or an idea...",
+                    aria_controls: "palette-results",
+                    value: "{query}",
+                    onmounted: move |event| input.mounted(event),
+                    oninput: move |event| {
+                        query.set(event.value());
+                        selected.set(0);
+                    },
+                    onkeydown: move |event| match event.key() {
+                        Key::ArrowDown if count > 0 => {
+                            event.prevent_default();
+                            selected.set((current + 1) % count);
+                        }
+                        Key::ArrowUp if count > 0 => {
+                            event.prevent_default();
+                            selected.set((current + count - 1) % count);
+                        }
+                        Key::Enter => {
+                            event.prevent_default();
+                            if let Some(command) = highlighted.clone() {
+                                on_run.call(command);
+                            }
+                        }
+                        Key::Escape => {
+                            event.prevent_default();
+                            on_close.call(());
+                        }
+                        _ => {}
+                    },
+                }
+
+                if let Some(message) = error {
+                    p { class: "palette-error", "{message}" }
+                }
+
+                ul {
+                    id: "palette-results",
+                    class: "palette-results",
+                    role: "listbox",
+                    for (index, command) in matches.into_iter().enumerate() {
+                        li {
+                            key: "{index}-{command.label()}",
+                            class: "palette-item",
+                            class: if index == current { "selected" },
+                            role: "option",
+                            aria_selected: index == current,
+                            onmouseenter: move |_| selected.set(index),
+                            onclick: move |_| on_run.call(command.clone()),
+                            "{command.label()}"
+                        }
+                    }
+                }
+
+                if loading {
+                    p { class: "palette-hint", "Loading ideas..." }
+                } else if count == 0 {
+                    p { class: "palette-hint", "No matching commands." }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,65 @@
+use crate::hooks::{use_focus_trap, use_stylesheet};
+use dioxus::prelude::*;
+
+const CONFIRM_DIALOG_CSS: Asset = asset!("/assets/styling/confirm_dialog.css");
+
+/// Modal question with a confirm and a cancel button. Render it only while the question is open; both handlers
+/// should close it.
+///
+/// The confirm button is focused when the dialog opens, Tab stays inside it and Escape cancels. Returning focus to
+/// whatever opened the dialog is up to the caller.
+#[component]
+pub fn ConfirmDialog(
+    message: String,
+    #[props(default = "confirm".to_string())] confirm_label: String,
+    #[props(default = "cancel".to_string())] cancel_label: String,
+    on_confirm: EventHandler<()>,
+    on_cancel: EventHandler<()>,
+) -> Element {
+    use_stylesheet(CONFIRM_DIALOG_CSS);
+
+    let mut trap = use_focus_trap();
+
+    rsx! {
+        div {
+            class: "confirm-backdrop",
+            onclick: move |_| on_cancel.call(()),
+            div {
+                class: "confirm-dialog",
+                role: "alertdialog",
+                aria_modal: "true",
+                aria_label: "{message}",
+                // Clicks inside the dialog shouldn't count as clicking the backdrop
+                onclick: move |event| event.stop_propagation(),
+                onkeydown: move |event| {
+                    if event.key() == Key::Escape {
+                        event.prevent_default();
+                        on_cancel.call(());
+                    } else {
+                        trap.keydown(&event);
+                    }
+                },
+                p { class: "confirm-message", "{message}" }
+                div {
+                    class: "confirm-buttons",
+                    button {
+                        r#type: "button",
+                        class: "confirm-btn",
+                        onmounted: move |event| trap.mounted(0, event),
+                        onfocus: move |_| trap.focused(0),
+                        onclick: move |_| on_confirm.call(()),
+                        "{confirm_label}"
+                    }
+                    button {
+                        r#type: "button",
+                        class: "cancel-btn",
+                        onmounted: move |event| trap.mounted(1, event),
+                        onfocus: move |_| trap.focused(1),
+                        onclick: move |_| on_cancel.call(()),
+                        "{cancel_label}"
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use crate::clipboard::copy_text;
+use crate::db::Idea;
+use crate::error::error_message;
+use crate::hooks::use_toasts;
+use crate::markdown::idea_to_markdown;
+use crate::server_functions::get_idea_by_id_server;
+use dioxus::prelude::*;
+
+/// Button copying an idea to the clipboard as markdown, confirmed or reported by a toast.
+///
+/// `idea` is the idea as the page shows it, edits included; without it the idea is fetched when clicked. `class`
+/// styles the button for where it sits.
+#[component]
+pub fn CopyIdeaButton(
+    idea_id: String,
+    title: String,
+    #[props(default)] idea: Option<Idea>,
+    class: String,
+) -> Element {
+    let mut toasts = use_toasts();
+    let mut copying = use_signal(|| false);
+
+    let copy = move |_| {
+        let idea_id = idea_id.clone();
+        let idea = idea.clone();
+        spawn(async move {
+            copying.set(true);
+            let idea = match idea {
+                Some(idea) => Ok(idea),
+                None => get_idea_by_id_server(idea_id).await.map_err(|e| error_message(&e)),
+            };
+            let copied = match idea {
+                Ok(idea) => copy_text(idea_to_markdown(&idea)).await,
+                Err(message) => Err(format!("Couldn't load the idea: {}", message)),
+            };
+            match copied {
+                Ok(()) => toasts.success("copied"),
+                Err(message) => toasts.error(message),
+            }
+            copying.set(false);
+        });
+    };
+
+    rsx! {
+        button {
+            r#type: "button",
+            class,
+            disabled: copying(),
+            title: "copy as markdown",
+            aria_label: "copy {title} as markdown",
+            onclick: copy,
+            "copy"
+        }
+    }
+}
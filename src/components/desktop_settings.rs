@@ -0,0 +1,132 @@
+use crate::components::ConfirmDialog;
+use crate::data_dir::{data_folder, AppDirs, DB_PATH_VAR};
+use crate::hooks::use_stylesheet;
+use dioxus::prelude::*;
+use std::path::Path;
+
+const DESKTOP_SETTINGS_CSS: Asset = asset!("/assets/styling/desktop_settings.css");
+
+/// Desktop-only settings menu in the navbar: shows where the database lives and lets the user open or move that
+/// folder.
+///
+/// The database connection is opened once per process, so a new folder takes effect after a restart, which the menu
+/// offers right away.
+#[component]
+pub fn DesktopSettings() -> Element {
+    use_stylesheet(DESKTOP_SETTINGS_CSS);
+
+    let mut open = use_signal(|| false);
+    let mut folder = use_signal(data_folder);
+    let mut error = use_signal(|| Option::<String>::None);
+    let mut confirm_restart = use_signal(|| false);
+
+    let choose = move |_| async move {
+        error.set(None);
+        let Some(picked) = rfd::AsyncFileDialog::new()
+            .set_title("Choose data folder")
+            .set_directory(folder())
+            .pick_folder()
+            .await
+        else {
+            return;
+        };
+
+        let Some(dirs) = AppDirs::platform() else {
+            error.set(Some("No home directory to remember the choice in".to_string()));
+            return;
+        };
+        match dirs.choose_folder(picked.path()) {
+            Ok(()) if std::env::var(DB_PATH_VAR).is_ok() => {
+                error.set(Some(format!("{} is set and takes precedence over the chosen folder", DB_PATH_VAR)));
+            }
+            Ok(()) => confirm_restart.set(true),
+            Err(e) => error.set(Some(format!("Failed to save the choice: {}", e))),
+        }
+    };
+
+    let reset = move |_| {
+        error.set(None);
+        if let Some(dirs) = AppDirs::platform() {
+            match dirs.forget_chosen_folder() {
+                Ok(()) => confirm_restart.set(true),
+                Err(e) => error.set(Some(format!("Failed to reset the folder: {}", e))),
+            }
+        }
+    };
+
+    rsx! {
+        div {
+            class: "desktop-settings",
+            button {
+                r#type: "button",
+                class: "navbar-link",
+                aria_haspopup: "menu",
+                aria_expanded: open(),
+                onclick: move |_| {
+                    // Pick up a folder changed since the menu was last opened
+                    folder.set(data_folder());
+                    open.toggle();
+                },
+                "settings"
+            }
+
+            if open() {
+                div {
+                    class: "settings-menu",
+                    role: "menu",
+                    p { class: "settings-folder", title: "data folder", "{folder().display()}" }
+                    button {
+                        r#type: "button",
+                        role: "menuitem",
+                        onclick: move |_| {
+                            if let Err(e) = reveal(&folder()) {
+                                error.set(Some(format!("Failed to open the folder: {}", e)));
+                            }
+                        },
+                        "reveal data folder"
+                    }
+                    button { r#type: "button", role: "menuitem", onclick: choose, "choose data folder" }
+                    button { r#type: "button", role: "menuitem", onclick: reset, "use default folder" }
+                    if let Some(message) = error() {
+                        p { class: "settings-error", role: "alert", "{message}" }
+                    }
+                }
+            }
+
+            if confirm_restart() {
+                ConfirmDialog {
+                    message: "The new data folder is used from the next launch. Restart now?",
+                    confirm_label: "restart now",
+                    cancel_label: "later",
+                    on_confirm: move |_| {
+                        if let Err(e) = restart() {
+                            error.set(Some(format!("Failed to restart: {}", e)));
+                        }
+                        confirm_restart.set(false);
+                    },
+                    on_cancel: move |_| confirm_restart.set(false),
+                }
+            }
+        }
+    }
+}
+
+/// Open `folder` in the platform file manager
+fn reveal(folder: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(folder)?;
+    let opener = if cfg!(target_os = "macos") {
+        "open"
+    } else if cfg!(target_os = "windows") {
+        "explorer"
+    } else {
+        "xdg-open"
+    };
+    std::process::Command::new(opener).arg(folder).spawn().map(|_| ())
+}
+
+/// Start a fresh copy of the app and exit this one
+fn restart() -> std::io::Result<()> {
+    let exe = std::env::current_exe()?;
+    std::process::Command::new(exe).args(std::env::args_os().skip(1)).spawn()?;
+    std::process::exit(0)
+}
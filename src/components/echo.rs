@@ -1,3 +1,4 @@
+use crate::hooks::use_stylesheet;
 use dioxus::prelude::*;
 
 const ECHO_CSS: Asset = asset!("/assets/styling/echo.css");
@@ -5,6 +6,8 @@ const ECHO_CSS: Asset = asset!("/assets/styling/echo.css");
 /// Echo component that demonstrates fullstack server functions.
 #[component]
 pub fn Echo() -> Element {
+    use_stylesheet(ECHO_CSS);
+
     // use_signal is a hook. Hooks in dioxus must be run in a consistent order every time the component is rendered.
     // That means they can't be run inside other hooks, async blocks, if statements, or loops.
     //
@@ -13,8 +16,6 @@ pub fn Echo() -> Element {
     let mut response = use_signal(|| String::new());
 
     rsx! {
-        document::Link { rel: "stylesheet", href: ECHO_CSS }
-
         div {
             id: "echo",
             h4 { "ServerFn Echo" }
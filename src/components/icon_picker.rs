@@ -0,0 +1,130 @@
+use crate::client_errors::log_error;
+use crate::error::error_message;
+use crate::hooks::use_stylesheet;
+use crate::server_functions::set_idea_icon_server;
+use crate::utils::{is_emoji_icon, tidy_icon, ICON_CHOICES};
+use dioxus::prelude::*;
+
+const ICON_PICKER_CSS: Asset = asset!("/assets/styling/icon_picker.css");
+
+/// Button showing the icon of an idea, opening a grid of emoji to pick another from, a field for any other single
+/// emoji, and a button removing it. `on_changed` gets the icon the server stored.
+#[component]
+pub fn IconPicker(
+    idea_id: String,
+    icon: Option<String>,
+    client_id: String,
+    on_changed: EventHandler<Option<String>>,
+) -> Element {
+    use_stylesheet(ICON_PICKER_CSS);
+
+    let mut open = use_signal(|| false);
+    let mut custom = use_signal(String::new);
+    let mut error = use_signal(|| Option::<String>::None);
+    let mut is_saving = use_signal(|| false);
+
+    let pick = use_callback(move |choice: Option<String>| {
+        let idea_id = idea_id.clone();
+        let client_id = client_id.clone();
+        let choice = tidy_icon(choice);
+        // Checked here too, so a typo doesn't cost a round trip
+        if choice.as_deref().is_some_and(|choice| !is_emoji_icon(choice)) {
+            error.set(Some("Pick a single emoji".to_string()));
+            return;
+        }
+        spawn(async move {
+            is_saving.set(true);
+            match set_idea_icon_server(idea_id, choice, client_id).await {
+                Ok(stored) => {
+                    error.set(None);
+                    custom.set(String::new());
+                    open.set(false);
+                    on_changed.call(stored);
+                }
+                Err(e) => {
+                    log_error("set idea icon", &e);
+                    error.set(Some(error_message(&e)));
+                }
+            }
+            is_saving.set(false);
+        });
+    });
+
+    let label = match &icon {
+        Some(icon) => format!("change icon {}", icon),
+        None => "add an icon".to_string(),
+    };
+
+    rsx! {
+        div {
+            class: "icon-picker",
+            onkeydown: move |e| {
+                if e.key() == Key::Escape {
+                    open.set(false);
+                }
+            },
+            button {
+                r#type: "button",
+                class: if icon.is_some() { "icon-toggle has-icon" } else { "icon-toggle" },
+                aria_label: "{label}",
+                aria_expanded: open(),
+                title: "{label}",
+                disabled: is_saving(),
+                onclick: move |_| open.toggle(),
+                if let Some(icon) = &icon {
+                    "{icon}"
+                } else {
+                    "+ icon"
+                }
+            }
+            if open() {
+                div {
+                    class: "icon-popover",
+                    role: "dialog",
+                    aria_label: "pick an icon",
+                    div {
+                        class: "icon-grid",
+                        for choice in ICON_CHOICES {
+                            button {
+                                key: "{choice}",
+                                r#type: "button",
+                                class: "icon-choice",
+                                aria_pressed: icon.as_deref() == Some(choice),
+                                disabled: is_saving(),
+                                onclick: move |_| pick.call(Some(choice.to_string())),
+                                "{choice}"
+                            }
+                        }
+                    }
+                    form {
+                        class: "icon-custom",
+                        onsubmit: move |e| {
+                            e.prevent_default();
+                            pick.call(Some(custom()));
+                        },
+                        input {
+                            r#type: "text",
+                            placeholder: "or type any emoji",
+                            aria_label: "any emoji",
+                            value: "{custom}",
+                            oninput: move |e| custom.set(e.value()),
+                        }
+                        button { r#type: "submit", disabled: is_saving() || custom().trim().is_empty(), "use" }
+                    }
+                    if icon.is_some() {
+                        button {
+                            r#type: "button",
+                            class: "icon-remove",
+                            disabled: is_saving(),
+                            onclick: move |_| pick.call(None),
+                            "remove icon"
+                        }
+                    }
+                    if let Some(message) = error() {
+                        p { class: "icon-error", role: "alert", "{message}" }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,223 @@
+use crate::client_errors::log_error;
+use crate::components::{
+    CardActions, ConfirmDialog, IdeaPreview, IdeaPreviewCard, LinkedText, QuickStatement, SearchSnippet, TagChips,
+    Timestamp,
+};
+use crate::db::IdeaSummary;
+use crate::hooks::{
+    use_app_config, use_focus_handle, use_hover_card, use_stylesheet, use_swipe, use_trash_revision, use_viewport,
+};
+use crate::server_functions::delete_idea_server;
+use crate::settings::{ListDensity, COMPACT_TAGS};
+use crate::tags::TagColors;
+use crate::utils::{
+    card_actions, due_label, due_status, local_now, percent_encode, CardAction, SearchHit, TagChange,
+};
+use crate::Route;
+use dioxus::prelude::*;
+use std::collections::HashMap;
+
+// Cards are styled along with the list they were split out of
+const IDEA_CARD_CSS: Asset = asset!("/assets/styling/idea_list.css");
+
+/// The development page of `idea`, at the shareable slug route when it has one. `None` before the idea is stored.
+pub fn develop_route(idea: &IdeaSummary) -> Option<Route> {
+    let id = idea.id.as_ref()?;
+    Some(if idea.slug.is_empty() {
+        Route::IdeaDevelopment { id: percent_encode(id) }
+    } else {
+        Route::IdeaBySlug { slug: idea.slug.clone() }
+    })
+}
+
+/// A single idea card with its [`CardActions`], its [`TagChips`] and a quick way to add a "what must be true"
+/// statement. Clicking a tag calls `on_tag_click` with it; `selected_tags` are shown pressed. Compact cards leave out
+/// the description and show only the first few tags. Deleting asks first, moves the idea to the trash and then calls
+/// `on_delete_success`. A read-only deployment leaves out pin, delete, editing tags and adding statements. On a
+/// phone, swiping the card left reveals larger develop and delete buttons. Hovering or long-pressing the card shows a
+/// preview, kept in `previews` so it is fetched once. A `search_hit` is shown in place of the description.
+#[component]
+pub fn IdeaCard(
+    idea: IdeaSummary,
+    tag_colors: TagColors,
+    density: ListDensity,
+    selected_tags: Vec<String>,
+    on_tag_click: EventHandler<String>,
+    on_delete_success: EventHandler<()>,
+    on_toggle_pin: EventHandler<String>,
+    on_change_tags: EventHandler<(String, TagChange)>,
+    previews: Signal<HashMap<String, IdeaPreview>>,
+    #[props(default)] search_hit: Option<SearchHit>,
+) -> Element {
+    use_stylesheet(IDEA_CARD_CSS);
+
+    let mut trash_revision = use_trash_revision();
+    let actions = card_actions(idea.id.is_some(), use_app_config().read_only());
+    let mut confirming_delete = use_signal(|| false);
+    // Focus goes back to the delete button when the confirmation is dismissed
+    let delete_button = use_focus_handle();
+    let compact = density == ListDensity::Compact;
+    let narrow = use_viewport().is_narrow();
+    let mut swipe = use_swipe();
+    let mut preview = use_hover_card();
+    let offset = if narrow { swipe.offset() } else { 0.0 };
+    let develop_to = develop_route(&idea);
+    let tag_editor = match (actions.contains(&CardAction::EditTags), idea.id.clone()) {
+        (true, Some(id)) => Some(EventHandler::new(move |change: TagChange| on_change_tags.call((id.clone(), change)))),
+        _ => None,
+    };
+
+    rsx! {
+        div {
+            class: "idea-card",
+            class: if narrow { "swipeable" },
+            onmounted: move |event| preview.mounted(event),
+            onpointerenter: move |evt| preview.enter(&evt),
+            onpointerleave: move |evt| preview.leave(&evt),
+            onpointerdown: move |evt| preview.press(&evt),
+            onpointerup: move |_| preview.release(),
+            onpointercancel: move |_| preview.release(),
+
+            if let (true, Some(id)) = (preview.is_open(), idea.id.clone()) {
+                IdeaPreviewCard { id, previews, card: preview }
+            }
+
+            // Revealed by swiping the card left on a phone; only there while they can be seen
+            if narrow && (swipe.is_open() || swipe.is_swiping()) {
+                div {
+                    class: "swipe-actions",
+                    if let Some(to) = develop_to.clone() {
+                        Link { to, class: "swipe-develop", aria_label: "develop {idea.title}", "develop" }
+                    }
+                    if actions.contains(&CardAction::Delete) {
+                        button {
+                            r#type: "button",
+                            class: "swipe-delete",
+                            aria_label: "move {idea.title} to the trash",
+                            onclick: move |_| {
+                                swipe.close();
+                                confirming_delete.set(true);
+                            },
+                            "delete"
+                        }
+                    }
+                }
+            }
+
+            if let (true, Some(id)) = (confirming_delete(), idea.id.clone()) {
+                ConfirmDialog {
+                    message: format!("Move \"{}\" to the trash?", idea.title),
+                    confirm_label: "move to trash",
+                    on_confirm: move |_| {
+                        confirming_delete.set(false);
+                        let id = id.clone();
+                        spawn(async move {
+                            match delete_idea_server(id).await {
+                                Ok(_) => {
+                                    trash_revision.bump();
+                                    on_delete_success.call(());
+                                }
+                                Err(e) => {
+                                    log_error("delete idea", &e);
+                                    delete_button.focus();
+                                }
+                            }
+                        });
+                    },
+                    on_cancel: move |_| {
+                        confirming_delete.set(false);
+                        delete_button.focus();
+                    },
+                }
+            }
+
+            // Everything that slides with the finger. The dialog above stays outside, as a transform would pin it to
+            // the card instead of the window.
+            div {
+                class: "swipe-content",
+                class: if swipe.is_swiping() { "swiping" },
+                style: "transform: translateX({offset}px)",
+                onpointerdown: move |evt| {
+                    if narrow {
+                        swipe.down(&evt);
+                    }
+                },
+                onpointermove: move |evt| {
+                    swipe.moved(&evt);
+                    // A finger sliding the card is swiping, not pressing
+                    if swipe.is_swiping() {
+                        preview.release();
+                    }
+                },
+                onpointerup: move |_| swipe.up(),
+                onpointercancel: move |_| swipe.cancel(),
+
+                // Header with title and action buttons
+                div {
+                    class: "idea-header",
+                    h3 {
+                        if let Some(icon) = &idea.icon {
+                            span { class: "idea-icon", aria_hidden: "true", "{icon}" }
+                        }
+                        "{idea.title}"
+                    }
+                    CardActions {
+                        idea: idea.clone(),
+                        develop_to: develop_to.clone(),
+                        on_toggle_pin,
+                        on_delete: move |_| confirming_delete.set(true),
+                        delete_button,
+                    }
+                }
+
+                if let Some(author) = &idea.author {
+                    p { class: "idea-author", "by {author}" }
+                }
+                if let Some(at) = idea.created_at {
+                    p {
+                        class: "idea-created",
+                        "added "
+                        Timestamp { at }
+                    }
+                }
+                if let Some(hit) = search_hit {
+                    SearchSnippet { hit }
+                } else if !compact {
+                    p {
+                        class: "description",
+                        LinkedText { text: idea.short_description.clone() }
+                    }
+                }
+                if let Some(idea_id) = idea.id.clone() {
+                    QuickStatement {
+                        idea_id,
+                        title: idea.title.clone(),
+                        count: idea.what_must_be_true_count,
+                        read_only: !actions.contains(&CardAction::AddStatement),
+                    }
+                }
+                if let Some(due) = idea.due_date {
+                    {
+                        let now = local_now();
+                        rsx! {
+                            p {
+                                class: "due-chip",
+                                "data-due": due_status(due, now).as_str(),
+                                "{due_label(due, now)}"
+                            }
+                        }
+                    }
+                }
+                TagChips {
+                    title: idea.title.clone(),
+                    tags: idea.tags.clone(),
+                    limit: compact.then_some(COMPACT_TAGS),
+                    selected: selected_tags,
+                    tag_colors,
+                    on_tag_click,
+                    on_change_tags: tag_editor,
+                }
+            }
+        }
+    }
+}
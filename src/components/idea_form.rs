@@ -1,85 +1,437 @@
-use crate::server_functions::submit_idea_server;
+use crate::client_errors::log_error;
+use crate::components::ConfirmDialog;
+use crate::db::{normalize_tags, tidy_author, IdeaSubmission, IdeaSummary};
+use crate::error::error_message;
+use crate::form_config::FormConfig;
+use crate::hooks::{use_focus_on_mount, use_settings, use_stylesheet, use_viewport};
+use crate::idempotency::new_idempotency_key;
+use crate::server_functions::{get_form_config_server, list_templates_server, submit_idea_server};
+use crate::storage::{default_storage, Storage};
+use crate::templates::{parse_tags, plan_template, IdeaTemplate, TemplateFields, TemplatePlan};
+use crate::utils::{normalize_newlines, normalize_statements, normalize_title};
+use crate::Route;
 use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 const IDEA_FORM_CSS: Asset = asset!("/assets/styling/idea_form.css");
 
-/// Form component for submitting new ideas
+/// Storage key for the unsubmitted form draft
+const DRAFT_KEY: &str = "idea_form_draft";
+
+/// Unsubmitted form input, persisted so accidental navigation doesn't lose it
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+struct FormDraft {
+    title: String,
+    description: String,
+    tags_input: String,
+    /// What was typed or picked in the deployment's own fields, by field name
+    #[serde(default)]
+    custom_inputs: HashMap<String, String>,
+}
+
+impl FormDraft {
+    fn is_empty(&self) -> bool {
+        self.title.trim().is_empty()
+            && self.description.trim().is_empty()
+            && self.tags_input.trim().is_empty()
+            && self.custom_inputs.values().all(|input| input.trim().is_empty())
+    }
+}
+
+/// Form component for submitting new ideas. On a phone it covers the whole screen as a sheet, whose "×" closes it
+/// and keeps the draft, unlike "cancel".
 #[component]
 pub fn IdeaForm(on_submit_success: EventHandler<()>, on_cancel: EventHandler<()>) -> Element {
+    use_stylesheet(IDEA_FORM_CSS);
+
+    // Restore a previously unsubmitted draft, if any
+    let restored_draft = use_hook(|| {
+        default_storage()
+            .get::<FormDraft>(DRAFT_KEY)
+            .filter(|draft| !draft.is_empty())
+    });
+
     // State for form inputs
-    let mut title = use_signal(|| String::new());
-    let mut description = use_signal(|| String::new());
-    let mut tags_input = use_signal(|| String::new());
+    let mut title = use_signal(|| restored_draft.clone().unwrap_or_default().title);
+    let mut description = use_signal(|| restored_draft.clone().unwrap_or_default().description);
+    let mut tags_input = use_signal(|| restored_draft.clone().unwrap_or_default().tags_input);
+    let mut custom_inputs = use_signal(|| restored_draft.clone().unwrap_or_default().custom_inputs);
     let mut is_submitting = use_signal(|| false);
     let mut success_message = use_signal(|| String::new());
+    let mut show_restored_notice = use_signal(|| restored_draft.is_some());
+    let mut title_input = use_focus_on_mount();
+    // Existing ideas with a similar title, shown instead of submitting until the user submits anyway
+    let mut possible_duplicates = use_signal(Vec::<IdeaSummary>::new);
+    // Sent with every submit of this form session, so a retry after a lost response doesn't create the idea twice
+    let mut idempotency_key = use_signal(new_idempotency_key);
 
-    rsx! {
-        document::Link { rel: "stylesheet", href: IDEA_FORM_CSS }
+    // Templates: the one applied last (to tell its text from the user's) and a pick waiting for confirmation
+    let templates = use_resource(|| async move { list_templates_server().await.unwrap_or_default() });
+    let mut applied_template = use_signal(|| None::<IdeaTemplate>);
+    let mut what_must_be_true = use_signal(Vec::<String>::new);
+    let mut pending_template = use_signal(|| None::<(IdeaTemplate, TemplatePlan)>);
+    // The name ideas are credited to is a setting, so it stays filled in between ideas and reloads
+    let mut settings = use_settings();
+    // Required and hidden fields, and the deployment's own ones. The server checks the same rules, so the default
+    // form is good enough while the config loads or if it fails to.
+    let form_config = use_resource(|| async move { get_form_config_server().await.unwrap_or_default() });
+
+    // Persist the current input on every change
+    let save_draft = move || {
+        let draft = FormDraft {
+            title: title(),
+            description: description(),
+            tags_input: tags_input(),
+            custom_inputs: custom_inputs(),
+        };
+        if draft.is_empty() {
+            default_storage().remove(DRAFT_KEY);
+        } else {
+            default_storage().set(DRAFT_KEY, &draft);
+        }
+    };
+
+    let mut apply_template = move |template: IdeaTemplate, fields: TemplateFields| {
+        description.set(fields.description);
+        tags_input.set(fields.tags_input);
+        what_must_be_true.set(template.default_wmbt.clone());
+        applied_template.set(Some(template));
+        save_draft();
+    };
+
+    let pick_template = move |event: Event<FormData>| {
+        let picked = templates
+            .read()
+            .as_ref()
+            .and_then(|templates| templates.iter().find(|template| template.id == event.value()).cloned());
+        let Some(template) = picked else {
+            // "none" forgets the template but leaves whatever it filled in
+            applied_template.set(None);
+            what_must_be_true.set(Vec::new());
+            return;
+        };
+
+        let current = TemplateFields {
+            description: description(),
+            tags_input: tags_input(),
+        };
+        let plan = plan_template(&current, applied_template.peek().as_ref(), &template);
+        if plan.needs_confirmation {
+            pending_template.set(Some((template, plan)));
+        } else {
+            apply_template(template, plan.fields);
+        }
+    };
 
+    let mut set_custom_input = move |name: String, input: String| {
+        custom_inputs.write().insert(name, input);
+        save_draft();
+    };
+
+    let submit = move |force: bool| async move {
+        is_submitting.set(true);
+
+        // The server tidies these the same way as the preview below
+        let tags = parse_tags(&tags_input());
+        let author = tidy_author(Some(settings.display_name()));
+        // Sent as typed; the server turns them into values of each field's kind. Inputs of fields the form no
+        // longer has, kept in an old draft, are left out.
+        let config = form_config().unwrap_or_default();
+        let custom_fields: HashMap<String, serde_json::Value> = custom_inputs()
+            .into_iter()
+            .filter(|(name, _)| config.custom_fields.iter().any(|field| field.name == *name))
+            .map(|(name, input)| (name, serde_json::Value::String(input)))
+            .collect();
+
+        // Call server function
+        // Whitespace is normalized like the server stores it, so what is sent is what comes back
+        let submission = IdeaSubmission {
+            title: normalize_title(&title()),
+            description: normalize_newlines(&description()),
+            tags,
+            what_must_be_true: normalize_statements(&what_must_be_true()),
+            author,
+            custom_fields,
+        };
+        let result = submit_idea_server(submission, Some(idempotency_key()), force).await;
+        match result {
+            Ok(outcome) if outcome.created.is_none() => {
+                success_message.set(String::new());
+                possible_duplicates.set(outcome.possible_duplicates);
+            }
+            Ok(_) => {
+                success_message.set("idea submitted successfully".to_string());
+                // Only now, seeing the success, is the next submit a new idea
+                idempotency_key.set(new_idempotency_key());
+                // Clear form
+                title.set(String::new());
+                description.set(String::new());
+                tags_input.set(String::new());
+                custom_inputs.set(HashMap::new());
+                what_must_be_true.set(Vec::new());
+                applied_template.set(None);
+                possible_duplicates.set(Vec::new());
+                default_storage().remove(DRAFT_KEY);
+                show_restored_notice.set(false);
+                // Notify parent component
+                on_submit_success.call(());
+            }
+            Err(e) => {
+                log_error("submit idea", &e);
+                // Structured errors (e.g. rate limiting) read better without the transport prefix
+                success_message.set(format!("error: {}", error_message(&e)));
+            }
+        }
+
+        is_submitting.set(false);
+    };
+
+    let selected_template = applied_template().map(|template| template.id).unwrap_or_default();
+    // The tags as they will be stored
+    let tag_preview = normalize_tags(parse_tags(&tags_input()));
+    let sheet = use_viewport().is_narrow();
+    let config: FormConfig = form_config().unwrap_or_default();
+
+    rsx! {
         div {
             id: "idea-form-container",
-            h2 { "submit your idea" }
+            class: if sheet { "sheet" },
+            role: if sheet { "dialog" },
+            aria_modal: if sheet { "true" },
+            aria_label: if sheet { "submit your idea" },
+
+            div {
+                class: "form-title",
+                h2 { "submit your idea" }
+                if sheet {
+                    button {
+                        r#type: "button",
+                        class: "sheet-close",
+                        aria_label: "close the form, keeping the draft",
+                        disabled: is_submitting(),
+                        onclick: move |_| on_cancel.call(()),
+                        "×"
+                    }
+                }
+            }
+
+            if show_restored_notice() {
+                div {
+                    class: "draft-notice",
+                    span { "restored unsaved draft" }
+                    button {
+                        r#type: "button",
+                        onclick: move |_| show_restored_notice.set(false),
+                        "dismiss"
+                    }
+                }
+            }
+
+            if let Some((template, plan)) = pending_template() {
+                ConfirmDialog {
+                    message: format!("Replace your description with the \"{}\" template?", template.name),
+                    confirm_label: "replace",
+                    cancel_label: "keep mine",
+                    on_confirm: {
+                        let template = template.clone();
+                        let fields = plan.fields.clone();
+                        move |_| {
+                            pending_template.set(None);
+                            apply_template(template.clone(), fields.clone());
+                        }
+                    },
+                    // Declining still adds the template's tags and statements
+                    on_cancel: move |_| {
+                        pending_template.set(None);
+                        apply_template(template.clone(), plan.keep_description.clone());
+                    },
+                }
+            }
 
             form {
-                onsubmit: move |event| async move {
+                onsubmit: move |event| {
                     event.prevent_default();
-                    is_submitting.set(true);
-
-                    // Parse tags from comma-separated input
-                    let tags: Vec<String> = tags_input()
-                        .split(',')
-                        .map(|s| s.trim().to_string())
-                        .filter(|s| !s.is_empty())
-                        .collect();
-
-                    // Call server function
-                    match submit_idea_server(title(), description(), tags).await {
-                        Ok(_) => {
-                            success_message.set("idea submitted successfully".to_string());
-                            // Clear form
-                            title.set(String::new());
-                            description.set(String::new());
-                            tags_input.set(String::new());
-                            // Notify parent component
-                            on_submit_success.call(());
-                        }
-                        Err(e) => {
-                            success_message.set(format!("error: {}", e));
+                    submit(false)
+                },
+
+                div {
+                    class: "form-field",
+                    label { r#for: "idea-template", "template" }
+                    select {
+                        id: "idea-template",
+                        class: "template-picker",
+                        onchange: pick_template,
+                        option { value: "", selected: selected_template.is_empty(), "none" }
+                        for template in templates().unwrap_or_default() {
+                            option {
+                                key: "{template.id}",
+                                value: "{template.id}",
+                                selected: template.id == selected_template,
+                                "{template.name}"
+                            }
                         }
                     }
-
-                    is_submitting.set(false);
-                },
+                }
 
                 div {
                     class: "form-field",
-                    label { "title" }
+                    label { r#for: "idea-title", "title" }
                     input {
+                        id: "idea-title",
                         r#type: "text",
+                        onmounted: move |event| title_input.mounted(event),
                         value: "{title}",
-                        oninput: move |e| title.set(e.value()),
+                        oninput: move |e| {
+                            title.set(e.value());
+                            // A new title needs a new check
+                            possible_duplicates.set(Vec::new());
+                            save_draft();
+                        },
                         required: true,
                     }
                 }
 
-                div {
-                    class: "form-field",
-                    label { "description" }
-                    textarea {
-                        value: "{description}",
-                        oninput: move |e| description.set(e.value()),
-                        rows: 4,
-                        required: true,
+                if config.description.is_shown() {
+                    div {
+                        class: "form-field",
+                        label {
+                            r#for: "idea-description",
+                            if config.description.is_required() { "description" } else { "description (optional)" }
+                        }
+                        textarea {
+                            id: "idea-description",
+                            value: "{description}",
+                            oninput: move |e| {
+                                description.set(e.value());
+                                save_draft();
+                            },
+                            rows: 4,
+                            required: config.description.is_required(),
+                        }
+                    }
+                }
+
+                if config.tags.is_shown() {
+                    div {
+                        class: "form-field",
+                        label { r#for: "idea-tags", "tags (comma-separated)" }
+                        input {
+                            id: "idea-tags",
+                            r#type: "text",
+                            value: "{tags_input}",
+                            oninput: move |e| {
+                                tags_input.set(e.value());
+                                save_draft();
+                            },
+                            required: config.tags.is_required(),
+                        }
+                        match &tag_preview {
+                            Ok(tags) if !tags.is_empty() => rsx! {
+                                div {
+                                    class: "tag-preview",
+                                    for tag in tags.clone() {
+                                        span { class: "tag", "{tag}" }
+                                    }
+                                }
+                            },
+                            Ok(_) => rsx! {},
+                            Err(e) => rsx! {
+                                p { class: "field-error", role: "alert", "{e}" }
+                            },
+                        }
+                    }
+                }
+
+                for (index, field) in config.custom_fields.iter().cloned().enumerate() {
+                    div {
+                        key: "{field.name}",
+                        class: "form-field",
+                        label { r#for: "idea-custom-{index}", "{field.label()}" }
+                        match field.input_type() {
+                            Some(input_type) => rsx! {
+                                input {
+                                    id: "idea-custom-{index}",
+                                    r#type: input_type,
+                                    step: if input_type == "number" { "any" },
+                                    value: custom_inputs.read().get(&field.name).cloned().unwrap_or_default(),
+                                    required: field.required,
+                                    oninput: {
+                                        let name = field.name.clone();
+                                        move |e: Event<FormData>| set_custom_input(name.clone(), e.value())
+                                    },
+                                }
+                            },
+                            None => rsx! {
+                                select {
+                                    id: "idea-custom-{index}",
+                                    required: field.required,
+                                    onchange: {
+                                        let name = field.name.clone();
+                                        move |e: Event<FormData>| set_custom_input(name.clone(), e.value())
+                                    },
+                                    option { value: "", "choose…" }
+                                    for choice in field.options.clone() {
+                                        option {
+                                            key: "{choice}",
+                                            selected: custom_inputs.read().get(&field.name) == Some(&choice),
+                                            value: "{choice}",
+                                            "{choice}"
+                                        }
+                                    }
+                                }
+                            },
+                        }
                     }
                 }
 
                 div {
                     class: "form-field",
-                    label { "tags (comma-separated)" }
+                    label { r#for: "idea-author", "your name (optional)" }
                     input {
+                        id: "idea-author",
                         r#type: "text",
-                        value: "{tags_input}",
-                        oninput: move |e| tags_input.set(e.value()),
+                        autocomplete: "name",
+                        value: "{settings.display_name()}",
+                        oninput: move |e| settings.set_display_name(e.value()),
+                    }
+                }
+
+                if !what_must_be_true().is_empty() {
+                    div {
+                        class: "form-field",
+                        label { "what must be true" }
+                        ul {
+                            class: "template-wmbt",
+                            for statement in what_must_be_true() {
+                                li { "{statement}" }
+                            }
+                        }
+                    }
+                }
+
+                if !possible_duplicates().is_empty() {
+                    div {
+                        class: "duplicate-warning",
+                        role: "alert",
+                        p { "similar ideas already exist:" }
+                        ul {
+                            for idea in possible_duplicates() {
+                                if let Some(id) = idea.id.clone() {
+                                    li {
+                                        key: "{id}",
+                                        Link { to: Route::IdeaDevelopment { id }, "{idea.title}" }
+                                    }
+                                }
+                            }
+                        }
+                        button {
+                            r#type: "button",
+                            class: "submit-anyway-btn",
+                            disabled: is_submitting(),
+                            onclick: move |_| submit(true),
+                            "submit anyway"
+                        }
                     }
                 }
 
@@ -87,14 +439,17 @@ pub fn IdeaForm(on_submit_success: EventHandler<()>, on_cancel: EventHandler<()>
                     class: "form-buttons",
                     button {
                         r#type: "submit",
-                        disabled: is_submitting(),
+                        disabled: is_submitting() || tag_preview.is_err(),
                         class: "submit-btn",
                         "submit idea"
                     }
                     button {
                         r#type: "button",
                         class: "cancel-btn",
-                        onclick: move |_| on_cancel.call(()),
+                        onclick: move |_| {
+                            default_storage().remove(DRAFT_KEY);
+                            on_cancel.call(());
+                        },
                         disabled: is_submitting(),
                         "cancel"
                     }
@@ -0,0 +1,90 @@
+use crate::error::error_message;
+use crate::history::{collapse_edits, IdeaEvent};
+use crate::components::Timestamp;
+use crate::hooks::use_stylesheet;
+use crate::server_functions::get_idea_history_server;
+use dioxus::prelude::*;
+
+const IDEA_HISTORY_CSS: Asset = asset!("/assets/styling/idea_history.css");
+
+/// Collapsible timeline of everything that happened to an idea. Loads each time it is opened, so it includes the
+/// edits made since the page loaded.
+#[component]
+pub fn IdeaHistory(idea_id: String) -> Element {
+    use_stylesheet(IDEA_HISTORY_CSS);
+
+    let mut open = use_signal(|| false);
+
+    let mut events = use_resource(move || {
+        let idea_id = idea_id.clone();
+        async move {
+            if !open() {
+                return None;
+            }
+            Some(get_idea_history_server(idea_id).await.map(collapse_edits))
+        }
+    });
+
+    rsx! {
+        div {
+            class: "history-section",
+            button {
+                r#type: "button",
+                class: "history-toggle",
+                aria_expanded: open(),
+                aria_controls: "idea-history",
+                onclick: move |_| open.toggle(),
+                h2 {
+                    span { class: "history-caret", if open() { "▾" } else { "▸" } }
+                    "history"
+                }
+            }
+
+            if open() {
+                div {
+                    id: "idea-history",
+                    match &*events.read() {
+                        Some(Some(Ok(list))) if list.is_empty() => rsx! {
+                            p { class: "history-empty", "Nothing recorded yet." }
+                        },
+                        Some(Some(Ok(list))) => rsx! {
+                            ol {
+                                class: "history-timeline",
+                                for event in list.clone() {
+                                    HistoryEntry { event }
+                                }
+                            }
+                        },
+                        Some(Some(Err(e))) => rsx! {
+                            p { class: "history-error", "Failed to load history: {error_message(e)}" }
+                            button {
+                                r#type: "button",
+                                class: "retry-btn",
+                                onclick: move |_| events.restart(),
+                                "retry"
+                            }
+                        },
+                        _ => rsx! {
+                            p { class: "history-empty", "Loading history..." }
+                        },
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// One line of the timeline: what happened, a summary of the changes and when
+#[component]
+fn HistoryEntry(event: IdeaEvent) -> Element {
+    rsx! {
+        li {
+            class: "history-event",
+            span { class: "history-kind", "{event.kind.as_str()}" }
+            if !event.summary.is_empty() {
+                span { class: "history-summary", "{event.summary}" }
+            }
+            Timestamp { at: event.at, class: "history-time" }
+        }
+    }
+}
@@ -1,129 +1,879 @@
-use crate::server_functions::{delete_idea_server, get_all_ideas_server};
+use crate::client_errors::log_error;
+use crate::components::{develop_route, IdeaCard, IdeaPreview, SkeletonCard};
+use crate::db::{AuthorFilter, IdeaSummary, TagMatchMode};
+use crate::error::error_message;
+use crate::hooks::{
+    use_app_config, use_coalesced_resource, use_drag_list, use_idea_list_revision, use_infinite_scroll, use_debounce,
+    use_settings, use_stylesheet, use_tag_colors, use_toasts,
+};
+use crate::settings::{ListDensity, TimeDisplay};
+use crate::tags::TagColors;
+use crate::server_functions::{
+    add_idea_tag_server, count_ideas_server, get_idea_authors_server, get_idea_page_server,
+    get_ideas_after_server, get_malformed_ideas_server, remove_idea_tag_server, reorder_idea_server,
+    search_ideas_server, toggle_pin_server,
+};
+use crate::utils::{
+    append_page, due_label, due_status, edit_tags, group_ideas_by_tag, local_now, merge_search_results, move_item,
+    neighbors_at, rollback_tags, settle_tags, split_pinned, IdeaFilter, ListQuery, RetryPolicy, SearchHit,
+    SortColumn, SortOrder, StaleData, TagChange, MAX_PAGE_SIZE, PAGE_SIZE,
+};
 use crate::Route;
 use dioxus::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
 
 const IDEA_LIST_CSS: Asset = asset!("/assets/styling/idea_list.css");
 
-/// Component to display all submitted ideas
+/// How long typing in the search has to pause before the server is asked
+const SEARCH_DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// How the idea list is laid out
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ViewMode {
+    /// Flat list in the order returned by the server
+    List,
+    /// Ideas bucketed under a heading per tag
+    Grouped,
+}
+
+/// A page of the list as it was loaded
+#[derive(Debug, Clone, PartialEq)]
+struct ListPage {
+    ideas: Vec<IdeaSummary>,
+    /// Where the next page starts when the list pages by cursor
+    next_cursor: Option<String>,
+    has_more: bool,
+}
+
+/// The page after `cursor` when `by_cursor`, see [`ListQuery::pages_by_cursor`], and otherwise the one after `offset`
+/// ideas of `filter`
+async fn fetch_page(
+    filter: IdeaFilter,
+    by_cursor: bool,
+    offset: usize,
+    cursor: Option<String>,
+) -> Result<ListPage> {
+    if by_cursor {
+        let page = get_ideas_after_server(cursor, PAGE_SIZE as u64).await?;
+        return Ok(ListPage {
+            has_more: page.next_cursor.is_some(),
+            ideas: page.ideas,
+            next_cursor: page.next_cursor,
+        });
+    }
+    let ideas = get_idea_page_server(filter, offset, PAGE_SIZE).await?;
+    Ok(ListPage {
+        has_more: ideas.len() == PAGE_SIZE,
+        ideas,
+        next_cursor: None,
+    })
+}
+
+/// Component to display all submitted ideas.
+///
+/// Search, tag filter and sort order come from `query`, the home route's query string. Changing them replaces the
+/// current history entry, so going back to the list restores the same view. The server filters and sorts; the list
+/// loads [`PAGE_SIZE`] ideas at a time as it is scrolled, pinned ones first. Listing every idea by date added, it
+/// pages by cursor instead, so ideas added or deleted meanwhile don't shift the next page; pinned ones then turn up
+/// in their section as the pages holding them load.
+///
+/// Typing in the search narrows the ideas already loaded right away, matching titles and tags the way the command
+/// palette does. The query string, and with it the server search, follows once typing pauses, and what the server
+/// finds beyond the loaded ideas is added below them, see [`merge_search_results`].
+///
+/// `captured` is an idea just added above the list, shown first, if the filter lets it through, until the list is
+/// fetched again.
 #[component]
-pub fn IdeaList(refresh_trigger: Signal<u32>, on_delete_success: EventHandler<()>) -> Element {
-    // Use use_resource to fetch ideas from server
-    let ideas = use_resource(move || async move {
-        // Re-run when refresh_trigger changes
-        let _ = refresh_trigger();
-        get_all_ideas_server().await
+pub fn IdeaList(
+    query: ReadSignal<ListQuery>,
+    captured: ReadSignal<Option<IdeaSummary>>,
+    on_delete_success: EventHandler<()>,
+) -> Element {
+    use_stylesheet(IDEA_LIST_CSS);
+
+    // Ideas loaded so far, kept on screen while a refresh is in flight or after it fails
+    let mut cached = use_signal(StaleData::<Vec<IdeaSummary>>::default);
+    // Where the next page starts on the server: every idea the loaded pages held, including skipped duplicates
+    let mut fetched = use_signal(|| 0usize);
+    // Where the next page starts when the list pages by cursor
+    let mut next_cursor = use_signal(|| None::<String>);
+    let mut has_more = use_signal(|| false);
+    let mut loading_more = use_signal(|| false);
+    // Bumped by every refresh, so a page requested before it is dropped instead of appended to the new list
+    let mut generation = use_signal(|| 0u32);
+
+    // Ideas added, pinned or deleted anywhere in the app
+    let idea_list_revision = use_idea_list_revision();
+    // Hover previews fetched so far, by idea id. Dropped whenever an idea changes, as any of them might be stale.
+    let mut previews = use_signal(HashMap::<String, IdeaPreview>::new);
+    use_effect(move || {
+        let _ = idea_list_revision.get();
+        previews.write().clear();
     });
 
-    rsx! {
-        document::Link { rel: "stylesheet", href: IDEA_LIST_CSS }
+    // Fetch the first page from the server, retrying transient failures before showing an error. A burst of
+    // changes, like deleting several ideas in a row, makes one more fetch after the running one instead of a fetch
+    // each, and a page that a newer change overtook is never shown.
+    let mut ideas = use_coalesced_resource(
+        RetryPolicy::default(),
+        // Re-run whenever the ideas or the filter change
+        move || {
+            let _ = idea_list_revision.get();
+            query().filter(local_now())
+        },
+        move |filter| async move {
+            cached.write().start_refresh();
+            fetch_page(filter, query.peek().pages_by_cursor(), 0, None).await
+        },
+    );
+    // How many ideas the filter lets through, for the footer
+    let total = use_resource(move || async move {
+        let _ = idea_list_revision.get();
+        let filter = query().filter(local_now());
+        count_ideas_server(Some(filter)).await.ok().map(|counts| counts.total)
+    });
+
+    // Names to offer in the author filter
+    let authors = use_resource(move || async move {
+        let _ = idea_list_revision.get();
+        get_idea_authors_server().await.unwrap_or_default()
+    });
 
+    // Stored ideas the server had to leave out because they can't be read
+    let malformed = use_resource(move || async move {
+        let _ = idea_list_revision.get();
+        get_malformed_ideas_server().await.map(|records| records.len()).unwrap_or(0)
+    });
+    // How many were there when the warning was dismissed; a different count brings it back
+    let mut dismissed_malformed = use_signal(|| 0usize);
+
+    // A settled first page replaces whatever was loaded
+    let first_page = ideas.value();
+    use_effect(move || {
+        if let Some(result) = &*first_page.read() {
+            if let Ok(page) = result {
+                fetched.set(page.ideas.len());
+                next_cursor.set(page.next_cursor.clone());
+                has_more.set(page.has_more);
+                *generation.write() += 1;
+            }
+            let result = result.clone().map(|page| page.ideas).map_err(|e| error_message(&e));
+            cached.write().apply(result);
+        }
+    });
+
+    use_effect(move || {
+        let Some(idea) = captured() else {
+            return;
+        };
+        let shown = query.peek().apply(std::slice::from_ref(&idea), local_now());
+        if let Some(listed) = cached.write().data.as_mut() {
+            for idea in shown {
+                if !listed.iter().any(|known| known.id == idea.id) {
+                    listed.insert(0, idea);
+                }
+            }
+        }
+    });
+
+    // Append the next page, unless one is on its way or the first page isn't in yet
+    let mut load_more = move || {
+        if loading_more() || !has_more() || cached.read().is_refreshing {
+            return;
+        }
+        let offset = fetched();
+        let cursor = next_cursor();
+        let started = *generation.peek();
+        loading_more.set(true);
+        spawn(async move {
+            let query = query.peek().clone();
+            let result = fetch_page(query.filter(local_now()), query.pages_by_cursor(), offset, cursor).await;
+            if *generation.peek() == started {
+                match result {
+                    Ok(page) => {
+                        fetched.set(offset + page.ideas.len());
+                        next_cursor.set(page.next_cursor);
+                        has_more.set(page.has_more);
+                        if let Some(listed) = cached.write().data.as_mut() {
+                            append_page(listed, page.ideas);
+                        }
+                    }
+                    Err(e) => log_error("load more ideas", &e),
+                }
+            }
+            loading_more.set(false);
+        });
+    };
+    use_infinite_scroll(use_callback(move |_| load_more()));
+
+    let mut view_mode = use_signal(|| ViewMode::List);
+    let mut settings = use_settings();
+    let density = settings.density();
+    let mut drag = use_drag_list();
+    let tag_colors = use_tag_colors();
+    let read_only = use_app_config().read_only();
+
+    // Move an idea in the manual order: update the cached list right away, then persist the new rank. Pinned ideas
+    // have their own section, so indices are positions among the unpinned ones.
+    let mut reorder = move |from: usize, to: usize| {
+        let Some(list) = cached.read().data.clone() else {
+            return;
+        };
+        let (pinned, mut list) = split_pinned(list);
+        move_item(&mut list, from, to);
+        let ids: Vec<Option<String>> = list.iter().map(|idea| idea.id.clone()).collect();
+        let Some(Some(id)) = ids.get(to).cloned() else {
+            return;
+        };
+        let (before, after) = neighbors_at(&ids, to);
+        cached.write().data = Some(pinned.into_iter().chain(list).collect());
+
+        spawn(async move {
+            if reorder_idea_server(id, before.flatten(), after.flatten()).await.is_err() {
+                // Refetch so the list shows the order that was actually persisted
+                ideas.restart();
+            }
+        });
+    };
+    // Pin state of a cached idea, changed locally without refetching the list
+    let mut set_pinned = move |id: &str, pinned: bool| {
+        if let Some(idea) = cached.write().data.iter_mut().flatten().find(|idea| idea.id.as_deref() == Some(id)) {
+            idea.pinned = pinned;
+        }
+    };
+    // Flip the pin right away and put it back if the server refuses
+    let toggle_pin = move |id: String| {
+        let was_pinned = cached
+            .read()
+            .data
+            .iter()
+            .flatten()
+            .find(|idea| idea.id.as_deref() == Some(id.as_str()))
+            .is_some_and(|idea| idea.pinned);
+        set_pinned(&id, !was_pinned);
+
+        spawn(async move {
+            match toggle_pin_server(id.clone()).await {
+                Ok(pinned) => set_pinned(&id, pinned),
+                Err(_) => set_pinned(&id, was_pinned),
+            }
+        });
+    };
+
+    // Add or remove a tag right away, then show what the server stored, or put the tags back if it refuses
+    let mut toasts = use_toasts();
+    let change_tags = move |(id, change): (String, TagChange)| {
+        let edit = match cached.write().data.as_mut().map(|list| edit_tags(list, &id, &change)) {
+            Some(Ok(Some(edit))) => edit,
+            Some(Err(e)) => {
+                toasts.error(e.to_string());
+                return;
+            }
+            _ => return,
+        };
+
+        spawn(async move {
+            let result = match change {
+                TagChange::Add(tag) => add_idea_tag_server(id, tag).await,
+                TagChange::Remove(tag) => remove_idea_tag_server(id, tag).await,
+            };
+            match result {
+                Ok(stored) => {
+                    if let Some(list) = cached.write().data.as_mut() {
+                        settle_tags(list, &edit, stored);
+                    }
+                }
+                Err(e) => {
+                    log_error("change tags", &e);
+                    if let Some(list) = cached.write().data.as_mut() {
+                        rollback_tags(list, &edit);
+                    }
+                    toasts.error(format!("Couldn't change the tags: {}", error_message(&e)));
+                }
+            }
+        });
+    };
+    // Clicking a tag on a card toggles it in the filter
+    let filter_by_tag = move |tag: String| {
+        navigator().replace(Route::Home { query: query.peek().with_tag_toggled(&tag) });
+    };
+    // Every tag of the loaded ideas, offered while typing a new one
+    let known_tags = {
+        let mut known: Vec<String> = cached.read().data.iter().flatten().flat_map(|idea| idea.tags.clone()).collect();
+        known.sort_by_key(|tag| tag.to_lowercase());
+        known.dedup_by_key(|tag| tag.to_lowercase());
+        known
+    };
+
+    // Tags whose section is collapsed in grouped mode (sections are open by default)
+    let mut collapsed_tags = use_signal(HashSet::<String>::new);
+
+    // Filters change the URL rather than local state
+    let set_query = move |query: ListQuery| {
+        navigator().replace(Route::Home { query });
+    };
+
+    // The search as typed, ahead of the query string until typing pauses
+    let mut typed = use_signal(|| query.peek().search.clone());
+    // The search last put in the query string from here, to tell it apart from going back or clearing the filters
+    let mut pushed = use_signal(|| query.peek().search.clone());
+    let mut search_debounce = use_debounce();
+    use_effect(move || {
+        let search = query().search;
+        if search != *pushed.peek() {
+            pushed.set(search.clone());
+            typed.set(search);
+        }
+    });
+    let push_search = move || {
+        let search = typed.peek().clone();
+        pushed.set(search.clone());
+        set_query(ListQuery { search, ..query.peek().clone() });
+    };
+    // The last list loaded without a search, with the query it was loaded for, so a search typed over it can look
+    // at every loaded idea and not just the ones the previous search left
+    let mut unsearched = use_signal(|| None::<(ListQuery, Vec<IdeaSummary>)>);
+    use_effect(move || {
+        let query = query();
+        let cached = cached.read();
+        if query.search.is_empty() && !cached.is_refreshing {
+            if let Some(ideas) = &cached.data {
+                unsearched.set(Some((query, ideas.clone())));
+            }
+        }
+    });
+
+    // Where the server search found each idea, by id, for its card to show
+    let search = use_memo(move || query().search);
+    let search_hits = use_resource(move || async move {
+        let _ = idea_list_revision.get();
+        let search = search();
+        if search.trim().is_empty() {
+            return HashMap::new();
+        }
+        match search_ideas_server(search, MAX_PAGE_SIZE).await {
+            Ok(hits) => hits.into_iter().filter_map(|hit| Some((hit.idea.id.clone()?, hit))).collect(),
+            Err(e) => {
+                log_error("search snippets", &e);
+                HashMap::new()
+            }
+        }
+    });
+
+    let query = query();
+    let typed_search = typed();
+    // The server results are for an older search, or the ones for this one are still on their way
+    let searching_deeper = query.search != typed_search || cached.read().is_refreshing;
+    let all_ideas = match cached.read().data.clone() {
+        Some(loaded) if query.search != typed_search || !typed_search.trim().is_empty() => {
+            let without_search = ListQuery { search: String::new(), ..query.clone() };
+            let local = match &*unsearched.read() {
+                Some((loaded_for, ideas)) if *loaded_for == without_search => ideas.clone(),
+                _ => loaded.clone(),
+            };
+            let server = if searching_deeper { Vec::new() } else { loaded };
+            Some(merge_search_results(&typed_search, &local, &server))
+        }
+        // The server already filtered and sorted the loaded ideas
+        loaded => loaded,
+    };
+    let is_unfiltered = query.is_unfiltered() && typed_search.trim().is_empty();
+    // Snippets of an older search are left out while the one typed since is on its way
+    let hits: HashMap<String, SearchHit> = if query.search == typed_search {
+        search_hits().unwrap_or_default()
+    } else {
+        HashMap::new()
+    };
+    let matching = all_ideas.clone().unwrap_or_default();
+    // Pinned ideas are shown in their own section, so dragging only covers the others
+    let (pinned_ideas, matching) = split_pinned(matching);
+    let can_drag = query.is_manual_unfiltered() && is_unfiltered && !read_only;
+    let idea_count = matching.len();
+    let loaded_count = pinned_ideas.len() + idea_count;
+
+    let footer = rsx! {
+        if loading_more() {
+            div { class: "loading-more", role: "status", span { class: "spinner" } "loading more ideas" }
+        }
+        if let Some(Some(total)) = total() {
+            if total > 0 {
+                footer {
+                    class: "list-footer",
+                    span { "{loaded_count.min(total)} of {total} loaded" }
+                    // For keyboards, and for pages where scrolling doesn't get close enough
+                    if has_more() && !loading_more() {
+                        button { r#type: "button", onclick: move |_| load_more(), "load more" }
+                    }
+                }
+            }
+        }
+    };
+
+    rsx! {
         div {
             id: "idea-list-container",
 
-            match ideas() {
-                Some(Ok(ideas_vec)) => rsx! {
-                    if ideas_vec.is_empty() {
-                        p { class: "empty-state", "No ideas submitted yet. Be the first!" }
-                    } else {
-                        for idea in ideas_vec {
-                            div {
-                                class: "idea-card",
-                                // Header with title and action buttons
-                                div {
-                                    class: "idea-header",
-                                    h3 { "{idea.title}" }
-
-                                    div {
-                                        class: "idea-actions",
-
-                                        // Develop button (only if idea has an ID)
-                                        if let Some(id) = &idea.id {
-                                            {
-                                                let id = id.to_owned();
-                                                rsx! {
-                                                    Link {
-                                                        to: Route::IdeaDevelopment { id: id.clone() },
-                                                        class: "develop-btn",
-                                                        "→"
-                                                    }
-                                                }
-                                            }
-                                        }
+            div {
+                class: "view-options",
+                // Segmented control to switch between the flat and grouped views
+                div {
+                    class: "view-mode-toggle",
+                    button {
+                        r#type: "button",
+                        class: if view_mode() == ViewMode::List { "active" },
+                        aria_pressed: view_mode() == ViewMode::List,
+                        onclick: move |_| view_mode.set(ViewMode::List),
+                        "list"
+                    }
+                    button {
+                        r#type: "button",
+                        class: if view_mode() == ViewMode::Grouped { "active" },
+                        aria_pressed: view_mode() == ViewMode::Grouped,
+                        onclick: move |_| view_mode.set(ViewMode::Grouped),
+                        "grouped"
+                    }
+                }
+
+                // How much of each idea to show, remembered across visits
+                div {
+                    class: "view-mode-toggle density-toggle",
+                    for option in ListDensity::ALL {
+                        button {
+                            r#type: "button",
+                            class: if density == option { "active" },
+                            aria_pressed: density == option,
+                            onclick: move |_| settings.set_density(option),
+                            "{option.as_str()}"
+                        }
+                    }
+                }
+
+                // "3 h ago" or the exact time, everywhere times are shown
+                div {
+                    class: "view-mode-toggle time-toggle",
+                    aria_label: "how times are shown",
+                    for option in TimeDisplay::ALL {
+                        button {
+                            r#type: "button",
+                            class: if settings.time_display() == option { "active" },
+                            aria_pressed: settings.time_display() == option,
+                            onclick: move |_| settings.set_time_display(option),
+                            "{option.as_str()} times"
+                        }
+                    }
+                }
+            }
+
+            div {
+                class: "list-filters",
+                input {
+                    r#type: "search",
+                    class: "list-search",
+                    placeholder: "search",
+                    aria_label: "search ideas",
+                    value: "{typed_search}",
+                    oninput: move |e: FormEvent| {
+                        typed.set(e.value());
+                        search_debounce.call(SEARCH_DEBOUNCE, push_search);
+                    },
+                }
+                select {
+                    class: "list-sort",
+                    aria_label: "sort ideas",
+                    onchange: {
+                        let query = query.clone();
+                        move |e: FormEvent| {
+                            let sort = SortOrder::parse(&e.value()).unwrap_or_default();
+                            set_query(ListQuery { sort, ..query.clone() })
+                        }
+                    },
+                    for order in SortOrder::ALL {
+                        option {
+                            value: order.as_str(),
+                            selected: order == query.sort,
+                            "{order.label()}"
+                        }
+                    }
+                }
+                select {
+                    class: "list-author",
+                    aria_label: "filter by author",
+                    onchange: {
+                        let query = query.clone();
+                        move |e: FormEvent| {
+                            let author = match e.value().as_str() {
+                                "none" => Some(AuthorFilter::Unattributed),
+                                value => value.strip_prefix("by:").map(|name| AuthorFilter::By(name.to_string())),
+                            };
+                            set_query(ListQuery { author, ..query.clone() })
+                        }
+                    },
+                    option { value: "all", selected: query.author.is_none(), "anyone" }
+                    for name in authors().unwrap_or_default() {
+                        option {
+                            key: "{name}",
+                            value: "by:{name}",
+                            selected: query.author.as_ref().and_then(AuthorFilter::name) == Some(name.as_str()),
+                            "{name}"
+                        }
+                    }
+                    option {
+                        value: "none",
+                        selected: query.author == Some(AuthorFilter::Unattributed),
+                        "unattributed"
+                    }
+                }
+                button {
+                    r#type: "button",
+                    class: "due-filter",
+                    class: if query.due_soon { "active" },
+                    aria_pressed: query.due_soon,
+                    title: "only ideas that are overdue or due soon",
+                    onclick: {
+                        let query = query.clone();
+                        move |_| set_query(ListQuery { due_soon: !query.due_soon, ..query.clone() })
+                    },
+                    "due soon"
+                }
+                // The report takes the tag filter along
+                Link {
+                    class: "report-link",
+                    to: Route::ListReport {
+                        query: ListQuery { tags: query.tags.clone(), tag_mode: query.tag_mode, ..Default::default() },
+                    },
+                    "print"
+                }
+                // Only worth choosing once there is more than one tag to combine
+                if query.tags.len() > 1 {
+                    button {
+                        r#type: "button",
+                        class: "tag-mode",
+                        title: "switch between ideas with all of these tags and ideas with any of them",
+                        onclick: {
+                            let query = query.clone();
+                            move |_| {
+                                let tag_mode = match query.tag_mode {
+                                    TagMatchMode::All => TagMatchMode::Any,
+                                    TagMatchMode::Any => TagMatchMode::All,
+                                };
+                                set_query(ListQuery { tag_mode, ..query.clone() })
+                            }
+                        },
+                        "match {query.tag_mode.as_str()}"
+                    }
+                }
+                for tag in query.tags.clone() {
+                    button {
+                        r#type: "button",
+                        class: "active-tag",
+                        title: "remove {tag} from the tag filter",
+                        onclick: {
+                            let filtered = query.with_tag_toggled(&tag);
+                            move |_| set_query(filtered.clone())
+                        },
+                        "{tag} ×"
+                    }
+                }
+            }
 
-                                        // Delete button (only if idea has an ID)
-                                        if let Some(id) = &idea.id {
-                                            {
-                                                let id = id.to_owned();
-                                                rsx! { button {
-                                                r#type: "button",
-                                                class: "delete-btn",
-                                                onclick: move |evt| {
-                                                evt.prevent_default();
-                                                evt.stop_propagation();
-
-                                                let id = id.clone();
-
-                                                spawn(async move {
-                                                    #[cfg(target_arch = "wasm32")]
-                                                    web_sys::console::log_1(&format!("🔍 Delete clicked for ID: {}", id).into());
-
-                                                    // Use native JavaScript confirm
-                                                    #[cfg(target_arch = "wasm32")]
-                                                    let confirmed = {
-                                                        let window = web_sys::window().expect("no global window");
-                                                        window.confirm_with_message("Delete this idea?").unwrap_or(false)
-                                                    };
-
-                                                    #[cfg(not(target_arch = "wasm32"))]
-                                                    let confirmed = false;
-
-                                                    #[cfg(target_arch = "wasm32")]
-                                                    web_sys::console::log_1(&format!("🤔 Confirmed: {}", confirmed).into());
-
-                                                    if confirmed {
-                                                        #[cfg(target_arch = "wasm32")]
-                                                        web_sys::console::log_1(&"✅ Calling delete_idea_server".into());
-
-                                                        match delete_idea_server(id).await {
-                                                            Ok(_) => {
-                                                                #[cfg(target_arch = "wasm32")]
-                                                                web_sys::console::log_1(&"🎉 Delete successful".into());
-                                                                on_delete_success.call(());
-                                                            }
-                                                            Err(_e) => {
-                                                                #[cfg(target_arch = "wasm32")]
-                                                                web_sys::console::log_1(&format!("💥 Delete failed: {}", _e).into());
-                                                            }
-                                                        }
-                                                    }
-                                                });
-                                            },
-                                            "×"
-                                        } }
+            if let Some(unreadable) = malformed().filter(|count| *count > 0 && *count != dismissed_malformed()) {
+                div {
+                    class: "malformed-warning",
+                    role: "status",
+                    span {
+                        if unreadable == 1 {
+                            "1 record could not be read"
+                        } else {
+                            "{unreadable} records could not be read"
+                        }
+                    }
+                    Link { to: Route::Admin {}, "inspect" }
+                    button {
+                        r#type: "button",
+                        aria_label: "dismiss",
+                        onclick: move |_| dismissed_malformed.set(unreadable),
+                        "×"
+                    }
+                }
+            }
+
+            if cached.read().is_refreshing && !cached.read().is_first_load() {
+                div { class: "refreshing-indicator" }
+            }
+
+            if searching_deeper && !typed_search.trim().is_empty() && all_ideas.is_some() {
+                div {
+                    class: "searching-deeper",
+                    role: "status",
+                    span { class: "spinner" }
+                    "searching all ideas…"
+                }
+            }
+
+            if let Some(error) = cached.read().error.clone() {
+                div {
+                    class: if cached.read().data.is_some() { "error-banner" } else { "error-banner error" },
+                    span { "Failed to load ideas: {error}" }
+                    button {
+                        r#type: "button",
+                        class: "retry-btn",
+                        onclick: move |_| ideas.restart(),
+                        "retry"
+                    }
+                }
+            }
+
+            // Suggestions for the tag input of every card
+            datalist {
+                id: "known-tags",
+                for tag in known_tags {
+                    option { value: "{tag}" }
+                }
+            }
+
+            // The table lists pinned ideas in its first rows instead
+            if !pinned_ideas.is_empty() && density != ListDensity::Table {
+                section {
+                    class: "pinned-section",
+                    aria_label: "pinned ideas",
+                    h2 { class: "pinned-heading", "pinned" }
+                    for idea in pinned_ideas.clone() {
+                        IdeaCard {
+                            key: "{idea.id.clone().unwrap_or_default()}",
+                            search_hit: idea.id.as_ref().and_then(|id| hits.get(id)).cloned(),
+                            idea,
+                            tag_colors: tag_colors(),
+                            density,
+                            selected_tags: query.tags.clone(),
+                            on_tag_click: filter_by_tag,
+                            on_delete_success,
+                            on_toggle_pin: toggle_pin,
+                            on_change_tags: change_tags,
+                            previews,
+                        }
+                    }
+                }
+            }
+
+            match all_ideas {
+                Some(all_ideas) if all_ideas.is_empty() && is_unfiltered => rsx! {
+                    p { class: "empty-state", "No ideas submitted yet. Be the first!" }
+                },
+                Some(_) if matching.is_empty() && !pinned_ideas.is_empty() => rsx! {},
+                Some(_) if matching.is_empty() => rsx! {
+                    p { class: "empty-state", "No ideas match this filter." }
+                },
+                Some(_) if density == ListDensity::Table => rsx! {
+                    IdeaTable {
+                        ideas: pinned_ideas.iter().chain(&matching).cloned().collect::<Vec<_>>(),
+                        query: query.clone(),
+                        tag_colors: tag_colors(),
+                    }
+                    {footer.clone()}
+                },
+                Some(_) if view_mode() == ViewMode::Grouped => rsx! {
+                    for (tag, group) in group_ideas_by_tag(&matching) {
+                        div {
+                            key: "{tag}",
+                            class: "tag-group",
+                            {
+                                let is_collapsed = collapsed_tags.read().contains(&tag);
+                                let toggle_tag = tag.clone();
+                                rsx! {
+                                    button {
+                                        r#type: "button",
+                                        class: "tag-group-header",
+                                        aria_expanded: !is_collapsed,
+                                        onclick: move |_| {
+                                            let mut collapsed = collapsed_tags.write();
+                                            if !collapsed.remove(&toggle_tag) {
+                                                collapsed.insert(toggle_tag.clone());
+                                            }
+                                        },
+                                        span { class: "tag-group-caret", if is_collapsed { "▸" } else { "▾" } }
+                                        span { class: "tag-group-name", "{tag}" }
+                                        span { class: "tag-group-count", "{group.len()}" }
+                                    }
+                                    if !is_collapsed {
+                                        for idea in group {
+                                            IdeaCard {
+                                                search_hit: idea.id.as_ref().and_then(|id| hits.get(id)).cloned(),
+                                                idea,
+                                                tag_colors: tag_colors(),
+                                                density,
+                                                selected_tags: query.tags.clone(),
+                                                on_tag_click: filter_by_tag,
+                                                on_delete_success,
+                                                on_toggle_pin: toggle_pin,
+                                                on_change_tags: change_tags,
+                                                previews,
                                             }
                                         }
                                     }
                                 }
-                                p { class: "description", "{idea.description}" }
-                                if !idea.tags.is_empty() {
-                                    div {
-                                        class: "tags",
-                                        for tag in idea.tags {
-                                            span { class: "tag", "{tag}" }
-                                        }
+                            }
+                        }
+                    }
+                    {footer.clone()}
+                },
+                Some(_) => rsx! {
+                    div {
+                        class: "sortable-list",
+                        onpointerup: move |_| {
+                            if let Some((from, to)) = drag.finish() {
+                                reorder(from, to);
+                            }
+                        },
+                        onpointerleave: move |_| drag.cancel(),
+
+                        // Indices are positions among the loaded ideas, which start at the top of the manual order
+                        for (index, idea) in matching.into_iter().enumerate() {
+                            div {
+                                key: "{idea.id.clone().unwrap_or_default()}",
+                                class: "sortable-item",
+                                class: if drag.is_dragging(index) { "dragging" },
+                                class: if drag.is_over(index) { "drop-target" },
+                                onpointerenter: move |_| drag.enter(index),
+
+                                // Dragging only makes sense while the whole list is shown in manual order
+                                // Keyboard users move the focused handle with the arrow keys instead
+                                if can_drag {
+                                    button {
+                                        r#type: "button",
+                                        class: "drag-handle",
+                                        title: "drag to reorder",
+                                        aria_label: "reorder {idea.title} (arrow up or down)",
+                                        onpointerdown: move |evt| {
+                                            evt.prevent_default();
+                                            drag.start(index);
+                                        },
+                                        onkeydown: move |evt| {
+                                            let to = match evt.key() {
+                                                Key::ArrowUp if index > 0 => index - 1,
+                                                Key::ArrowDown if index + 1 < idea_count => index + 1,
+                                                _ => return,
+                                            };
+                                            evt.prevent_default();
+                                            reorder(index, to);
+                                        },
+                                        "⋮⋮"
                                     }
                                 }
+                                IdeaCard {
+                                    search_hit: idea.id.as_ref().and_then(|id| hits.get(id)).cloned(),
+                                    idea,
+                                    tag_colors: tag_colors(),
+                                    density,
+                                    selected_tags: query.tags.clone(),
+                                    on_tag_click: filter_by_tag,
+                                    on_delete_success,
+                                    on_toggle_pin: toggle_pin,
+                                    on_change_tags: change_tags,
+                                    previews,
+                                }
                             }
                         }
                     }
+                    {footer.clone()}
                 },
-                Some(Err(e)) => rsx! {
-                    p { class: "error", "Failed to load ideas: {e}" }
+                None if cached.read().is_first_load() => rsx! {
+                    SkeletonCard {}
+                    SkeletonCard {}
+                    SkeletonCard {}
                 },
-                None => rsx! {
-                    p { class: "loading", "Loading ideas..." }
+                None => rsx! {},
+            }
+        }
+    }
+}
+
+/// The ideas as table rows, pinned ones marked. Sorting by a column goes through the same [`SortOrder`] in the URL
+/// as the dropdown, so both always agree.
+///
+/// Ideas have no status or last-updated date, so the columns are the title, the tags, the due date and how many
+/// "what must be true" statements were written down.
+#[component]
+fn IdeaTable(ideas: Vec<IdeaSummary>, query: ListQuery, tag_colors: TagColors) -> Element {
+    let now = local_now();
+
+    rsx! {
+        table {
+            class: "idea-table",
+            thead {
+                tr {
+                    SortHeader { column: SortColumn::Title, label: "title", query: query.clone() }
+                    th { scope: "col", "tags" }
+                    SortHeader { column: SortColumn::Due, label: "due", query: query.clone() }
+                    th { scope: "col", class: "numeric", "statements" }
                 }
             }
+            tbody {
+                for idea in ideas {
+                    tr {
+                        key: "{idea.id.clone().unwrap_or_default()}",
+                        td {
+                            if idea.pinned {
+                                span { class: "table-pin", title: "pinned", "◆ " }
+                            }
+                            if let Some(icon) = &idea.icon {
+                                span { class: "idea-icon", aria_hidden: "true", "{icon}" }
+                            }
+                            if let Some(to) = develop_route(&idea) {
+                                Link { to, "{idea.title}" }
+                            } else {
+                                "{idea.title}"
+                            }
+                        }
+                        td {
+                            class: "tags",
+                            for tag in idea.tags {
+                                span { class: "tag", "data-color": tag_colors.color_for(&tag), "{tag}" }
+                            }
+                        }
+                        td {
+                            if let Some(due) = idea.due_date {
+                                span {
+                                    class: "due-chip",
+                                    "data-due": due_status(due, now).as_str(),
+                                    "{due_label(due, now)}"
+                                }
+                            }
+                        }
+                        td { class: "numeric", "{idea.what_must_be_true_count}" }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Header of a sortable column: clicking it sorts on the column, ascending first
+#[component]
+fn SortHeader(column: SortColumn, label: &'static str, query: ListQuery) -> Element {
+    let direction = query.sort.direction(column);
+    let (aria_sort, arrow) = match direction {
+        Some(true) => ("ascending", " ▲"),
+        Some(false) => ("descending", " ▼"),
+        None => ("none", ""),
+    };
+    let sorted = ListQuery {
+        sort: query.sort.toggled(column),
+        ..query
+    };
+
+    rsx! {
+        th {
+            scope: "col",
+            aria_sort,
+            button {
+                r#type: "button",
+                class: "sort-header",
+                onclick: move |_| {
+                    navigator().replace(Route::Home { query: sorted.clone() });
+                },
+                "{label}{arrow}"
+            }
         }
     }
 }
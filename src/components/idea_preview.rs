@@ -0,0 +1,113 @@
+use crate::db::Idea;
+use crate::components::Timestamp;
+use crate::hooks::{use_stylesheet, HoverCard};
+use crate::server_functions::{get_idea_by_id_server, get_idea_history_server};
+use crate::utils::{truncate_text, HoverPlacement};
+use chrono::{DateTime, Utc};
+use dioxus::prelude::*;
+use dioxus::CapturedError;
+use std::collections::HashMap;
+
+const IDEA_PREVIEW_CSS: Asset = asset!("/assets/styling/idea_preview.css");
+
+/// "What must be true" statements shown in a preview before the rest are counted
+const PREVIEW_STATEMENTS: usize = 3;
+
+/// Characters of the notes shown in a preview
+const PREVIEW_NOTES_CHARS: usize = 200;
+
+/// What the hover preview of an idea shows. Previews are kept by idea id in a map the list owns, so hovering the
+/// same card again shows it right away.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IdeaPreview {
+    statements: Vec<String>,
+    /// Statements beyond the ones shown
+    more_statements: usize,
+    notes: String,
+    /// The last change in the idea's history, or when it was stored
+    updated_at: Option<DateTime<Utc>>,
+}
+
+impl IdeaPreview {
+    fn new(idea: Idea, updated_at: Option<DateTime<Utc>>) -> Self {
+        let shown = idea.what_must_be_true.len().min(PREVIEW_STATEMENTS);
+        Self {
+            more_statements: idea.what_must_be_true.len() - shown,
+            statements: idea.what_must_be_true.into_iter().take(shown).collect(),
+            notes: truncate_text(idea.development_notes.trim(), PREVIEW_NOTES_CHARS),
+            updated_at: updated_at.or(idea.created_at),
+        }
+    }
+}
+
+/// Floating preview of the idea `id` next to the card `card` is anchored to, with its first statements, the start
+/// of its notes and when it last changed. Render it while the card is open; it fetches the idea the first time
+/// and keeps it in `previews`.
+#[component]
+pub fn IdeaPreviewCard(id: String, previews: Signal<HashMap<String, IdeaPreview>>, card: HoverCard) -> Element {
+    use_stylesheet(IDEA_PREVIEW_CSS);
+
+    let mut previews = previews;
+    let key = id.clone();
+    let loading = use_resource(use_reactive!(|(id,)| async move {
+        if previews.peek().contains_key(&id) {
+            return Ok(());
+        }
+        let idea = get_idea_by_id_server(id.clone()).await?;
+        // History is newest first; without it the preview falls back to the creation time
+        let updated_at = get_idea_history_server(id.clone())
+            .await
+            .ok()
+            .and_then(|events| events.first().map(|event| event.at));
+        previews.write().insert(id, IdeaPreview::new(idea, updated_at));
+        Ok::<(), CapturedError>(())
+    }));
+
+    let preview = previews.read().get(&key).cloned();
+    let side = match card.placement() {
+        Some(HoverPlacement::Above) => "above",
+        _ => "below",
+    };
+
+    rsx! {
+        div {
+            class: "idea-preview",
+            "data-side": side,
+            role: "tooltip",
+            style: "{card.style()}",
+            match (preview, &*loading.read()) {
+                (Some(preview), _) => rsx! {
+                    if preview.statements.is_empty() {
+                        p { class: "hint", "No statements yet." }
+                    } else {
+                        ul {
+                            class: "preview-statements",
+                            for (index, statement) in preview.statements.iter().enumerate() {
+                                li { key: "{index}", "{statement}" }
+                            }
+                        }
+                    }
+                    if preview.more_statements > 0 {
+                        p { class: "hint", "+{preview.more_statements} more" }
+                    }
+                    if !preview.notes.is_empty() {
+                        p { class: "preview-notes", "{preview.notes}" }
+                    }
+                    if let Some(at) = preview.updated_at {
+                        p {
+                            class: "preview-updated",
+                            "updated "
+                            Timestamp { at }
+                        }
+                    }
+                },
+                (None, Some(Err(e))) => rsx! {
+                    p { class: "error", "Couldn't load the preview: {e}" }
+                },
+                (None, _) => rsx! {
+                    p { class: "hint", "loading..." }
+                },
+            }
+        }
+    }
+}
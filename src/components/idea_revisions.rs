@@ -0,0 +1,186 @@
+use crate::error::error_message;
+use crate::components::Timestamp;
+use crate::hooks::use_stylesheet;
+use crate::revisions::IdeaRevision;
+use crate::server_functions::{get_idea_revisions_server, restore_revision_server};
+use crate::utils::{diff_text, DiffSpan};
+use dioxus::prelude::*;
+
+const IDEA_REVISIONS_CSS: Asset = asset!("/assets/styling/idea_revisions.css");
+
+/// Collapsible list of the snapshots of an idea's notes and statements, each with a button putting it back and one
+/// comparing its notes with `current_notes`. `on_restored` runs after a restore so the page can reload the idea.
+#[component]
+pub fn IdeaRevisions(
+    idea_id: String,
+    client_id: String,
+    current_notes: String,
+    on_restored: EventHandler<()>,
+) -> Element {
+    use_stylesheet(IDEA_REVISIONS_CSS);
+
+    let mut open = use_signal(|| false);
+    let mut compared = use_signal(|| Option::<String>::None);
+    let mut restoring = use_signal(|| Option::<String>::None);
+    let mut restore_error = use_signal(|| Option::<String>::None);
+
+    let mut revisions = use_resource({
+        let idea_id = idea_id.clone();
+        move || {
+            let idea_id = idea_id.clone();
+            async move {
+                if !open() {
+                    return None;
+                }
+                Some(get_idea_revisions_server(idea_id).await)
+            }
+        }
+    });
+
+    let restore = move |revision_id: String| {
+        let idea_id = idea_id.clone();
+        let client_id = client_id.clone();
+        spawn(async move {
+            restoring.set(Some(revision_id.clone()));
+            match restore_revision_server(idea_id, revision_id, client_id).await {
+                Ok(_) => {
+                    restore_error.set(None);
+                    revisions.restart();
+                    on_restored.call(());
+                }
+                Err(e) => restore_error.set(Some(error_message(&e))),
+            }
+            restoring.set(None);
+        });
+    };
+
+    rsx! {
+        div {
+            class: "revisions-section",
+            button {
+                r#type: "button",
+                class: "revisions-toggle",
+                aria_expanded: open(),
+                aria_controls: "idea-revisions",
+                onclick: move |_| open.toggle(),
+                h2 {
+                    span { class: "revisions-caret", if open() { "▾" } else { "▸" } }
+                    "notes history"
+                }
+            }
+
+            if open() {
+                div {
+                    id: "idea-revisions",
+                    if let Some(message) = restore_error() {
+                        p { class: "revisions-error", "Failed to restore: {message}" }
+                    }
+                    match &*revisions.read() {
+                        Some(Some(Ok(list))) if list.is_empty() => rsx! {
+                            p { class: "revisions-empty", "No earlier versions yet." }
+                        },
+                        Some(Some(Ok(list))) => rsx! {
+                            ol {
+                                class: "revisions-list",
+                                for revision in list.clone() {
+                                    RevisionEntry {
+                                        key: "{revision.id}",
+                                        busy: restoring().is_some(),
+                                        comparing: compared().as_ref() == Some(&revision.id),
+                                        current_notes: current_notes.clone(),
+                                        revision,
+                                        on_restore: restore.clone(),
+                                        on_compare: move |id: String| {
+                                            // A second click on the same version closes its diff
+                                            let same = compared().as_ref() == Some(&id);
+                                            compared.set(if same { None } else { Some(id) });
+                                        },
+                                    }
+                                }
+                            }
+                        },
+                        Some(Some(Err(e))) => rsx! {
+                            p { class: "revisions-error", "Failed to load earlier versions: {error_message(e)}" }
+                            button {
+                                r#type: "button",
+                                class: "retry-btn",
+                                onclick: move |_| revisions.restart(),
+                                "retry"
+                            }
+                        },
+                        _ => rsx! {
+                            p { class: "revisions-empty", "Loading earlier versions..." }
+                        },
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// One snapshot: when it was saved, the first line it changed, a restore button and, when `comparing`, how its
+/// notes differ from `current_notes`
+#[component]
+fn RevisionEntry(
+    revision: IdeaRevision,
+    busy: bool,
+    comparing: bool,
+    current_notes: String,
+    on_restore: EventHandler<String>,
+    on_compare: EventHandler<String>,
+) -> Element {
+    let id = revision.id.clone();
+    let compare_id = revision.id.clone();
+
+    rsx! {
+        li {
+            class: "revision",
+            Timestamp { at: revision.at, class: "revision-time" }
+            span { class: "revision-preview", "{revision.preview}" }
+            button {
+                r#type: "button",
+                class: "revision-compare",
+                aria_pressed: comparing,
+                title: "show how the notes changed since this version",
+                onclick: move |_| on_compare.call(compare_id.clone()),
+                "diff"
+            }
+            button {
+                r#type: "button",
+                class: "revision-restore",
+                disabled: busy,
+                title: "replace the current notes and statements with this version",
+                onclick: move |_| on_restore.call(id.clone()),
+                "restore"
+            }
+            if comparing {
+                NotesDiff { old: revision.development_notes.clone(), new: current_notes }
+            }
+        }
+    }
+}
+
+/// Word diff from `old` notes to `new` ones: added words in green, removed ones struck through
+#[component]
+fn NotesDiff(old: String, new: String) -> Element {
+    let spans = diff_text(&old, &new);
+    let unchanged = spans.iter().all(|span| matches!(span, DiffSpan::Equal(_)));
+
+    rsx! {
+        div {
+            class: "revision-diff",
+            aria_label: "changes from this version to the current notes",
+            if unchanged {
+                p { class: "revisions-empty", "The notes are the same as now." }
+            } else {
+                for (index, span) in spans.into_iter().enumerate() {
+                    match span {
+                        DiffSpan::Equal(text) => rsx! { span { key: "{index}", "{text}" } },
+                        DiffSpan::Added(text) => rsx! { ins { key: "{index}", class: "diff-added", "{text}" } },
+                        DiffSpan::Removed(text) => rsx! { del { key: "{index}", class: "diff-removed", "{text}" } },
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use crate::components::ConfirmDialog;
+use crate::hooks::use_unsaved_changes;
+use crate::Route;
+use dioxus::prelude::*;
+
+/// A [`Link`] that asks before leaving unsaved changes: while an editor on the page is dirty, clicking it opens the
+/// [`LeaveDialog`] instead of navigating.
+#[component]
+pub fn GuardedLink(
+    to: Route,
+    class: Option<String>,
+    aria_label: Option<String>,
+    onclick: Option<EventHandler<MouseEvent>>,
+    children: Element,
+) -> Element {
+    let mut changes = use_unsaved_changes();
+    let dirty = changes.is_dirty();
+    let target = to.clone();
+
+    rsx! {
+        Link {
+            to,
+            class,
+            aria_label,
+            // The router only follows the link itself while there is nothing to lose
+            onclick_only: dirty,
+            onclick: move |event| {
+                if let Some(onclick) = onclick {
+                    onclick.call(event);
+                }
+                if dirty {
+                    changes.push(target.clone());
+                }
+            },
+            {children}
+        }
+    }
+}
+
+/// "Leave without saving?" for the navigation held back by unsaved changes. Rendered once by the layout.
+#[component]
+pub fn LeaveDialog() -> Element {
+    let mut changes = use_unsaved_changes();
+
+    rsx! {
+        if changes.is_pending() {
+            ConfirmDialog {
+                message: "Leave without saving?",
+                confirm_label: "leave",
+                cancel_label: "stay",
+                on_confirm: move |_| changes.confirm(),
+                on_cancel: move |_| changes.cancel(),
+            }
+        }
+    }
+}
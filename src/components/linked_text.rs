@@ -0,0 +1,24 @@
+use crate::utils::{linkify, TextSegment};
+use dioxus::prelude::*;
+
+/// `text` with its `http(s)` URLs as links opening in a new tab, see [`linkify`]. Every part is rendered as a text
+/// node, so markup in the text stays visible text.
+#[component]
+pub fn LinkedText(text: String) -> Element {
+    rsx! {
+        for (index, segment) in linkify(&text).into_iter().enumerate() {
+            match segment {
+                TextSegment::Plain(plain) => rsx! { span { key: "{index}", "{plain}" } },
+                TextSegment::Link(url) => rsx! {
+                    a {
+                        key: "{index}",
+                        href: "{url}",
+                        target: "_blank",
+                        rel: "noopener noreferrer",
+                        "{url}"
+                    }
+                },
+            }
+        }
+    }
+}
@@ -0,0 +1,146 @@
+use crate::components::ConfirmDialog;
+use crate::db::IdeaSummary;
+use crate::error::error_message;
+use crate::hooks::{use_idea_list_revision, use_stylesheet};
+use crate::server_functions::{get_idea_summaries_server, merge_ideas_server};
+use dioxus::prelude::*;
+
+const MERGE_PICKER_CSS: Asset = asset!("/assets/styling/merge_picker.css");
+
+/// Most ideas listed in the picker at once; typing narrows the list down
+const MAX_CHOICES: usize = 8;
+
+/// Ideas other than `idea_id` whose title contains `query`, ignoring case
+fn merge_targets<'a>(ideas: &'a [IdeaSummary], idea_id: &str, query: &str) -> Vec<&'a IdeaSummary> {
+    let query = query.trim().to_lowercase();
+    ideas
+        .iter()
+        .filter(|idea| idea.id.as_deref() != Some(idea_id))
+        .filter(|idea| idea.title.to_lowercase().contains(&query))
+        .take(MAX_CHOICES)
+        .collect()
+}
+
+/// "merge into..." action: pick another idea by title, confirm, and this idea is folded into it and deleted.
+/// `on_merged` receives the id of the idea it was merged into.
+#[component]
+pub fn MergePicker(idea_id: String, on_merged: EventHandler<String>) -> Element {
+    use_stylesheet(MERGE_PICKER_CSS);
+
+    let mut open = use_signal(|| false);
+    let mut query = use_signal(String::new);
+    let mut target = use_signal(|| Option::<IdeaSummary>::None);
+    let mut error = use_signal(|| Option::<String>::None);
+    let mut is_merging = use_signal(|| false);
+    let mut idea_list_revision = use_idea_list_revision();
+
+    // Loaded when the picker opens, so the choices include ideas created since the page loaded
+    let ideas = use_resource(move || async move {
+        if !open() {
+            return Vec::new();
+        }
+        get_idea_summaries_server().await.unwrap_or_default()
+    });
+
+    let mut merge = {
+        let idea_id = idea_id.clone();
+        move |primary: IdeaSummary| {
+            let secondary_id = idea_id.clone();
+            target.set(None);
+            spawn(async move {
+                let Some(primary_id) = primary.id else {
+                    return;
+                };
+                is_merging.set(true);
+                match merge_ideas_server(primary_id.clone(), secondary_id).await {
+                    Ok(_) => {
+                        open.set(false);
+                        idea_list_revision.bump();
+                        on_merged.call(primary_id);
+                    }
+                    Err(e) => error.set(Some(error_message(&e))),
+                }
+                is_merging.set(false);
+            });
+        }
+    };
+
+    let choices: Vec<IdeaSummary> = merge_targets(&ideas.read().clone().unwrap_or_default(), &idea_id, &query())
+        .into_iter()
+        .cloned()
+        .collect();
+
+    rsx! {
+        div {
+            class: "merge-section",
+            button {
+                r#type: "button",
+                class: "merge-toggle",
+                aria_expanded: open(),
+                disabled: is_merging(),
+                onclick: move |_| {
+                    open.toggle();
+                    query.set(String::new());
+                    error.set(None);
+                },
+                if is_merging() { "merging..." } else { "merge into..." }
+            }
+
+            if open() {
+                div {
+                    class: "merge-picker",
+                    input {
+                        r#type: "search",
+                        placeholder: "find the idea to merge into",
+                        aria_label: "idea to merge into",
+                        value: "{query}",
+                        oninput: move |e| query.set(e.value()),
+                        onmounted: move |e| async move {
+                            let _ = e.set_focus(true).await;
+                        },
+                    }
+                    if ideas.read().is_none() {
+                        p { class: "merge-empty", "Loading ideas..." }
+                    } else if choices.is_empty() {
+                        p { class: "merge-empty", "No other idea matches." }
+                    } else {
+                        ul {
+                            class: "merge-choices",
+                            for choice in choices {
+                                li {
+                                    key: "{choice.id.clone().unwrap_or_default()}",
+                                    button {
+                                        r#type: "button",
+                                        class: "merge-choice",
+                                        onclick: {
+                                            let choice = choice.clone();
+                                            move |_| target.set(Some(choice.clone()))
+                                        },
+                                        "{choice.title}"
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+
+            if let Some(message) = error() {
+                p { class: "merge-error", "Merge failed: {message}" }
+            }
+        }
+
+        if let Some(primary) = target() {
+            ConfirmDialog {
+                message: format!(
+                    "Merge this idea into \u{201c}{}\u{201d}? Its text, tags and attachments move there and this idea is deleted.",
+                    primary.title
+                ),
+                confirm_label: "merge",
+                on_confirm: move |_| merge(primary.clone()),
+                on_cancel: move |_| target.set(None),
+            }
+        }
+    }
+}
+
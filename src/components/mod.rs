@@ -13,3 +13,104 @@ pub use idea_form::IdeaForm;
 
 mod idea_list;
 pub use idea_list::IdeaList;
+
+mod idea_card;
+pub use idea_card::{develop_route, IdeaCard};
+
+mod card_actions;
+pub use card_actions::CardActions;
+
+mod tag_chips;
+pub use tag_chips::TagChips;
+
+mod skeleton_card;
+pub use skeleton_card::SkeletonCard;
+
+mod attachments;
+pub use attachments::Attachments;
+
+mod recent_ideas;
+pub use recent_ideas::RecentIdeasStrip;
+
+mod upcoming_ideas;
+pub use upcoming_ideas::UpcomingIdeas;
+
+mod status_strip;
+pub use status_strip::StatusStrip;
+
+mod confirm_dialog;
+pub use confirm_dialog::ConfirmDialog;
+
+mod leave_guard;
+pub use leave_guard::{GuardedLink, LeaveDialog};
+
+mod visibility_toggle;
+pub use visibility_toggle::VisibilityToggle;
+
+mod tag_manager;
+pub use tag_manager::TagManager;
+
+mod idea_history;
+pub use idea_history::IdeaHistory;
+
+mod merge_picker;
+pub use merge_picker::MergePicker;
+
+mod idea_revisions;
+pub use idea_revisions::IdeaRevisions;
+
+mod command_palette;
+pub use command_palette::CommandPalette;
+
+mod toast;
+pub use toast::ToastHost;
+
+mod copy_idea;
+pub use copy_idea::CopyIdeaButton;
+
+mod quick_statement;
+pub use quick_statement::QuickStatement;
+
+mod quick_capture;
+pub use quick_capture::QuickCapture;
+
+mod note_sections;
+pub use note_sections::NoteSections;
+
+mod icon_picker;
+pub use icon_picker::IconPicker;
+
+#[cfg(feature = "desktop")]
+mod desktop_settings;
+#[cfg(feature = "desktop")]
+pub use desktop_settings::DesktopSettings;
+
+mod linked_text;
+pub use linked_text::LinkedText;
+
+mod sparkline;
+pub use sparkline::Sparkline;
+
+mod stylesheets;
+pub use stylesheets::Stylesheets;
+
+mod onboarding;
+pub use onboarding::Onboarding;
+
+mod board_switcher;
+pub use board_switcher::BoardSwitcher;
+
+mod idea_preview;
+pub use idea_preview::{IdeaPreview, IdeaPreviewCard};
+
+mod timestamp;
+pub use timestamp::Timestamp;
+
+mod route_error;
+pub use route_error::RouteError;
+
+mod storage_banner;
+pub use storage_banner::StorageBanner;
+
+mod search_snippet;
+pub use search_snippet::SearchSnippet;
@@ -0,0 +1,187 @@
+use crate::client_errors::log_error;
+use crate::db::{Idea, NoteSection};
+use crate::error::error_message;
+use crate::hooks::{use_debounce, use_settings, use_stylesheet, use_toasts};
+use crate::markdown::{join_note_sections, split_note_sections, STARTER_SECTIONS};
+use crate::server_functions::update_idea_sections_server;
+use crate::utils::textarea_rows;
+use dioxus::prelude::*;
+
+const NOTE_SECTIONS_CSS: Asset = asset!("/assets/styling/note_sections.css");
+
+/// Height of a section's textarea before it starts growing
+const MIN_SECTION_ROWS: usize = 4;
+/// Characters that fit on one line of a section's textarea
+const SECTION_COLUMNS: usize = 80;
+
+/// The notes of an idea as sections, one collapsible editor each, or the button turning the plain `notes` into
+/// sections when there are none yet.
+///
+/// Sections save on their own, after the same pause as the rest of the page, or with their own button while
+/// auto-save is off. Converting either way also saves the plain notes: turning them into sections empties them, and
+/// turning sections back appends them to the notes. `on_saved` gets the idea after every save.
+#[component]
+pub fn NoteSections(
+    idea_id: String,
+    client_id: String,
+    notes: String,
+    sections: Vec<NoteSection>,
+    on_saved: EventHandler<Idea>,
+) -> Element {
+    use_stylesheet(NOTE_SECTIONS_CSS);
+
+    let mut edited = use_signal(|| sections.clone());
+    // Whether the sections changed since the last save
+    let mut dirty = use_signal(|| false);
+    let mut busy = use_signal(|| false);
+    // Bumped when a section is removed, so the editors below it are rebuilt with the text that moved up
+    let mut removals = use_signal(|| 0u32);
+    let mut debounce = use_debounce();
+    let settings = use_settings();
+    let mut toasts = use_toasts();
+
+    let save = use_callback(move |(sections, notes): (Vec<NoteSection>, Option<String>)| {
+        let idea_id = idea_id.clone();
+        let client_id = client_id.clone();
+        spawn(async move {
+            busy.set(true);
+            match update_idea_sections_server(idea_id, sections, notes, client_id).await {
+                Ok(idea) => {
+                    dirty.set(false);
+                    on_saved.call(idea);
+                }
+                Err(e) => {
+                    log_error("save note sections", &e);
+                    toasts.error(format!("Sections not saved: {}", error_message(&e)));
+                }
+            }
+            busy.set(false);
+        });
+    });
+
+    let mut changed = move |sections: Vec<NoteSection>| {
+        edited.set(sections);
+        dirty.set(true);
+        if let Some(delay) = settings.auto_save().delay() {
+            debounce.call(delay, move || save.call((edited(), None)));
+        }
+    };
+
+    let convert = {
+        let notes = notes.clone();
+        move |_| {
+            let mut sections = split_note_sections(&notes);
+            if sections.is_empty() {
+                sections = STARTER_SECTIONS
+                    .iter()
+                    .map(|heading| NoteSection {
+                        heading: heading.to_string(),
+                        body: String::new(),
+                    })
+                    .collect();
+            }
+            edited.set(sections.clone());
+            save.call((sections, Some(String::new())));
+        }
+    };
+
+    let convert_back = move |_| {
+        debounce.cancel();
+        let joined = join_note_sections(&edited());
+        let notes = [notes.trim_end(), joined.as_str()]
+            .into_iter()
+            .filter(|text| !text.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        edited.set(Vec::new());
+        save.call((Vec::new(), Some(notes)));
+    };
+
+    let count = edited.read().len();
+
+    rsx! {
+        div {
+            class: "note-sections",
+            if count == 0 {
+                button {
+                    r#type: "button",
+                    class: "sections-toggle",
+                    disabled: busy(),
+                    title: "Split the notes at their headings into sections, or start with a few if there are none",
+                    onclick: convert,
+                    "convert to sections"
+                }
+            } else {
+                for index in 0..count {
+                    details {
+                        key: "{removals}-{index}",
+                        class: "note-section",
+                        open: true,
+                        summary {
+                            input {
+                                class: "section-heading",
+                                r#type: "text",
+                                value: "{edited.read()[index].heading}",
+                                placeholder: "Heading",
+                                oninput: move |e| {
+                                    let mut sections = edited();
+                                    sections[index].heading = e.value();
+                                    changed(sections);
+                                }
+                            }
+                            button {
+                                r#type: "button",
+                                class: "section-remove",
+                                title: "Remove this section",
+                                onclick: move |_| {
+                                    let mut sections = edited();
+                                    sections.remove(index);
+                                    *removals.write() += 1;
+                                    changed(sections);
+                                },
+                                "×"
+                            }
+                        }
+                        textarea {
+                            initial_value: "{edited.read()[index].body}",
+                            rows: "{textarea_rows(&edited.read()[index].body, MIN_SECTION_ROWS, SECTION_COLUMNS)}",
+                            oninput: move |e| {
+                                let mut sections = edited();
+                                sections[index].body = e.value();
+                                changed(sections);
+                            }
+                        }
+                    }
+                }
+                div {
+                    class: "sections-actions",
+                    button {
+                        r#type: "button",
+                        onclick: move |_| {
+                            let mut sections = edited();
+                            sections.push(NoteSection::default());
+                            changed(sections);
+                        },
+                        "+ section"
+                    }
+                    if settings.auto_save().delay().is_none() && dirty() {
+                        button {
+                            r#type: "button",
+                            disabled: busy(),
+                            onclick: move |_| save.call((edited(), None)),
+                            "save sections"
+                        }
+                    }
+                    button {
+                        r#type: "button",
+                        class: "sections-toggle",
+                        disabled: busy(),
+                        title: "Write the sections back into the plain notes under their headings",
+                        onclick: convert_back,
+                        "convert back to plain notes"
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use crate::error::error_message;
+use crate::hooks::{use_app_config, use_idea_list_revision, use_stylesheet};
+use crate::onboarding::{example_ideas, load_tour_dismissed, save_tour_dismissed, should_show_tour};
+use crate::server_functions::{count_ideas_server, create_ideas_batch_server};
+use crate::storage::default_storage;
+use dioxus::prelude::*;
+
+const ONBOARDING_CSS: Asset = asset!("/assets/styling/onboarding.css");
+
+/// Welcome panel for a list without ideas: how ideas are captured, developed and tested against what must be true,
+/// with a button that creates a few examples. Hidden once there are ideas or after "start from scratch".
+#[component]
+pub fn Onboarding() -> Element {
+    use_stylesheet(ONBOARDING_CSS);
+
+    let mut idea_list_revision = use_idea_list_revision();
+    let read_only = use_app_config().read_only();
+    let mut dismissed = use_signal(|| load_tour_dismissed(&default_storage()));
+    let mut creating = use_signal(|| false);
+    let mut error = use_signal(|| None::<String>);
+
+    let total = use_resource(move || async move {
+        let _ = idea_list_revision.get();
+        count_ideas_server(None).await.ok().map(|counts| counts.total)
+    });
+
+    if read_only || !should_show_tour(total().flatten(), dismissed()) {
+        return rsx! {};
+    }
+
+    let mut dismiss = move || {
+        save_tour_dismissed(&default_storage());
+        dismissed.set(true);
+    };
+
+    let create_examples = move |_| {
+        creating.set(true);
+        error.set(None);
+        spawn(async move {
+            match create_ideas_batch_server(example_ideas()).await {
+                Ok(_) => {
+                    dismiss();
+                    idea_list_revision.bump();
+                }
+                Err(e) => error.set(Some(error_message(&e))),
+            }
+            creating.set(false);
+        });
+    };
+
+    rsx! {
+        section {
+            class: "onboarding",
+            aria_label: "welcome",
+            h2 { "Welcome" }
+            p { "This is where ideas go from a passing thought to something you can act on:" }
+            ol {
+                class: "onboarding-steps",
+                li {
+                    strong { "Capture. " }
+                    "Add an idea with a title and a line about it. Half-formed is fine."
+                }
+                li {
+                    strong { "Develop. " }
+                    "Open it to write notes, tag it and move it across the board as it grows."
+                }
+                li {
+                    strong { "Test. " }
+                    "List what must be true for it to work, then check those assumptions one by one."
+                }
+            }
+            if let Some(message) = error() {
+                p { class: "onboarding-error", role: "alert", "Couldn't create the examples: {message}" }
+            }
+            div {
+                class: "onboarding-actions",
+                button {
+                    r#type: "button",
+                    class: "onboarding-examples",
+                    disabled: creating(),
+                    onclick: create_examples,
+                    if creating() { "creating…" } else { "create example ideas" }
+                }
+                button {
+                    r#type: "button",
+                    class: "onboarding-dismiss",
+                    onclick: move |_| dismiss(),
+                    "start from scratch"
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use crate::client_errors::log_error;
+use crate::db::{tidy_author, Idea, IdeaSubmission};
+use crate::error::error_message;
+use crate::hooks::{use_focus_handle, use_settings, use_stylesheet};
+use crate::server_functions::submit_idea_server;
+use crate::utils::parse_quick_capture;
+use dioxus::prelude::*;
+
+const QUICK_CAPTURE_CSS: Asset = asset!("/assets/styling/quick_capture.css");
+
+/// One-line input above the list that adds an idea on Enter: the line is its title, `#tag` words its tags, see
+/// [`parse_quick_capture`]. The input keeps focus and empties after each idea, so several can go in one after the
+/// other; `on_captured` gets each idea the server created.
+///
+/// A line whose title looks like an existing idea's is held back once with the look-alike named, and goes in when
+/// Enter is pressed again on the same line.
+#[component]
+pub fn QuickCapture(on_captured: EventHandler<Idea>) -> Element {
+    use_stylesheet(QUICK_CAPTURE_CSS);
+
+    let settings = use_settings();
+    let mut text = use_signal(String::new);
+    let mut error = use_signal(|| Option::<String>::None);
+    // The line the look-alike warning was about; Enter on it again adds it anyway
+    let mut warned = use_signal(|| Option::<String>::None);
+    let mut saving = use_signal(|| false);
+    let mut input = use_focus_handle();
+
+    let mut capture = move || {
+        // Not disabled while saving, which would take the focus away, so a second Enter is dropped here
+        if saving() {
+            return;
+        }
+        let line = text();
+        let Some((title, tags)) = parse_quick_capture(&line) else {
+            error.set(Some("Write a title, tags go after a #".to_string()));
+            return;
+        };
+        let force = warned().as_deref() == Some(line.as_str());
+        let author = tidy_author(Some(settings.display_name()));
+        spawn(async move {
+            saving.set(true);
+            let submission = IdeaSubmission {
+                title,
+                tags,
+                author,
+                ..Default::default()
+            };
+            let result = submit_idea_server(submission, None, force).await;
+            match result {
+                Ok(outcome) => match outcome.created {
+                    Some(idea) => {
+                        text.set(String::new());
+                        error.set(None);
+                        warned.set(None);
+                        on_captured.call(idea);
+                    }
+                    None => {
+                        let similar = outcome.possible_duplicates.first().map(|idea| idea.title.clone());
+                        let similar = similar.unwrap_or_default();
+                        error.set(Some(format!("Looks like \"{}\"; press Enter again to add it anyway", similar)));
+                        warned.set(Some(line));
+                    }
+                },
+                Err(e) => {
+                    log_error("quick capture", &e);
+                    error.set(Some(error_message(&e)));
+                }
+            }
+            saving.set(false);
+            input.focus();
+        });
+    };
+
+    rsx! {
+        form {
+            class: "quick-capture",
+            onsubmit: move |event| {
+                event.prevent_default();
+                capture();
+            },
+            input {
+                r#type: "text",
+                class: "quick-capture-input",
+                placeholder: "capture an idea, #tags optional",
+                aria_label: "capture an idea",
+                aria_busy: saving(),
+                value: "{text}",
+                onmounted: move |event| input.mounted(event),
+                oninput: move |event| {
+                    text.set(event.value());
+                    error.set(None);
+                },
+            }
+            if let Some(message) = error() {
+                p { class: "quick-capture-error", role: "alert", "{message}" }
+            }
+        }
+    }
+}
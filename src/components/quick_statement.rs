@@ -0,0 +1,104 @@
+use crate::error::error_message;
+use crate::hooks::use_focus_on_mount;
+use crate::server_functions::append_wmbt_server;
+use dioxus::prelude::*;
+
+/// A card's "what must be true" count, with a "+" that opens a one-line input appending a statement without
+/// leaving the list. Escape or clicking away closes the input.
+///
+/// The count follows the server's answer to the last append until the list is fetched again, so adding a statement
+/// doesn't refetch the whole list. `read_only` leaves out the "+".
+#[component]
+pub fn QuickStatement(idea_id: String, title: String, count: usize, read_only: bool) -> Element {
+    let mut open = use_signal(|| false);
+    let mut statement = use_signal(String::new);
+    let mut error = use_signal(|| Option::<String>::None);
+    let mut saving = use_signal(|| false);
+    // The count the card had when a statement was appended, and the count the server answered with. Only used
+    // while the card still has that count, so a refetched list wins.
+    let mut appended = use_signal(|| Option::<(usize, usize)>::None);
+    let mut input = use_focus_on_mount();
+
+    let shown = match appended() {
+        Some((before, after)) if before == count => after,
+        _ => count,
+    };
+
+    let mut close = move || {
+        open.set(false);
+        statement.set(String::new());
+        error.set(None);
+    };
+
+    let mut submit = move || {
+        let text = statement().trim().to_string();
+        if text.is_empty() {
+            error.set(Some("Write a statement first".to_string()));
+            return;
+        }
+        let idea_id = idea_id.clone();
+        spawn(async move {
+            saving.set(true);
+            match append_wmbt_server(idea_id, text).await {
+                Ok(total) => {
+                    appended.set(Some((count, total)));
+                    statement.set(String::new());
+                    error.set(None);
+                }
+                Err(e) => error.set(Some(error_message(&e))),
+            }
+            saving.set(false);
+            input.focus();
+        });
+    };
+
+    rsx! {
+        div {
+            class: "quick-statement",
+            if shown > 0 {
+                span { class: "wmbt-count", title: "what must be true", "{shown} must be true" }
+            }
+            if !read_only && !open() {
+                button {
+                    r#type: "button",
+                    class: "quick-add-btn",
+                    title: "add a \"what must be true\" statement",
+                    aria_label: "add a statement to {title}",
+                    onclick: move |_| open.set(true),
+                    "+"
+                }
+            }
+            if open() {
+                form {
+                    class: "quick-add-form",
+                    onsubmit: move |event| {
+                        event.prevent_default();
+                        submit();
+                    },
+                    input {
+                        r#type: "text",
+                        placeholder: "what must be true?",
+                        aria_label: "new statement for {title}",
+                        value: "{statement}",
+                        disabled: saving(),
+                        onmounted: move |event| input.mounted(event),
+                        oninput: move |event| statement.set(event.value()),
+                        onkeydown: move |event| {
+                            if event.key() == Key::Escape {
+                                close();
+                            }
+                        },
+                        onblur: move |_| {
+                            if !saving() {
+                                close();
+                            }
+                        },
+                    }
+                }
+                if let Some(message) = error() {
+                    p { class: "quick-add-error", role: "alert", "{message}" }
+                }
+            }
+        }
+    }
+}
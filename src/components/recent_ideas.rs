@@ -0,0 +1,93 @@
+use crate::db::Idea;
+use crate::error::IdeaError;
+use crate::hooks::use_stylesheet;
+use crate::recent::RecentIdeas;
+use crate::server_functions::get_idea_by_id_server;
+use crate::storage::default_storage;
+use crate::Route;
+use dioxus::prelude::*;
+
+const RECENT_IDEAS_CSS: Asset = asset!("/assets/styling/recent_ideas.css");
+
+/// Horizontal strip of the ideas opened most recently, hidden while there are none
+#[component]
+pub fn RecentIdeasStrip() -> Element {
+    use_stylesheet(RECENT_IDEAS_CSS);
+
+    let mut recent = use_signal(|| RecentIdeas::load(&default_storage()));
+
+    // Resolve the stored ids, dropping ideas that were deleted since they were visited
+    let ideas = use_resource(move || async move {
+        let ids = recent.read().ids();
+        let mut found: Vec<Idea> = Vec::new();
+        let mut gone: Vec<String> = Vec::new();
+
+        for id in ids {
+            match get_idea_by_id_server(id.clone()).await {
+                Ok(idea) if idea.deleted_at.is_none() => found.push(idea),
+                // Trashed ideas may still be restored, so they stay remembered but hidden
+                Ok(_) => {}
+                Err(e) => {
+                    if matches!(
+                        IdeaError::from_error(&e),
+                        Some(IdeaError::NotFound(_) | IdeaError::InvalidId(_))
+                    ) {
+                        gone.push(id);
+                    }
+                }
+            }
+        }
+
+        if !gone.is_empty() {
+            // Updated in storage only: writing the signal here would run this resource again
+            let storage = default_storage();
+            let mut stored = RecentIdeas::load(&storage);
+            for id in &gone {
+                stored.remove(id);
+            }
+            stored.save(&storage);
+        }
+
+        found
+    });
+
+    let clear = move |_| {
+        recent.write().clear();
+        recent.read().save(&default_storage());
+    };
+
+    let found = ideas().unwrap_or_default();
+    if found.is_empty() {
+        return rsx! {};
+    }
+
+    rsx! {
+        div {
+            class: "recent-ideas",
+            div {
+                class: "recent-ideas-header",
+                h3 { "recently viewed" }
+                button {
+                    r#type: "button",
+                    class: "recent-clear-btn",
+                    onclick: clear,
+                    "clear"
+                }
+            }
+            div {
+                class: "recent-ideas-strip",
+                for idea in found {
+                    Link {
+                        key: "{idea.id.clone().unwrap_or_default()}",
+                        class: "recent-idea",
+                        to: Route::IdeaDevelopment { id: idea.id.clone().unwrap_or_default() },
+                        if let Some(icon) = &idea.icon {
+                            span { class: "idea-icon", aria_hidden: "true", "{icon}" }
+                        }
+                        "{idea.title}"
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use crate::client_errors::log_error;
+use crate::hooks::use_stylesheet;
+use crate::Route;
+use dioxus::prelude::*;
+
+const ROUTE_ERROR_CSS: Asset = asset!("/assets/styling/route_error.css");
+
+/// Shown in place of a page that failed to render, while the navbar around it keeps working. "Report" sends the
+/// failure to the server log through [`log_error`], once; "try again" renders the page again and "reload" loads
+/// the whole app anew.
+#[component]
+pub fn RouteError(message: String, on_retry: EventHandler<()>) -> Element {
+    use_stylesheet(ROUTE_ERROR_CSS);
+
+    let route = use_route::<Route>();
+    let mut reported = use_signal(|| false);
+    let report_message = message.clone();
+
+    rsx! {
+        div {
+            class: "route-error",
+            role: "alert",
+            h1 { "This page ran into a problem" }
+            p { "The rest of the app still works. Try again, or report it so it can be fixed." }
+            p { class: "route-error-detail", "{message}" }
+            div {
+                class: "route-error-actions",
+                button {
+                    r#type: "button",
+                    disabled: reported(),
+                    onclick: move |_| {
+                        log_error(&format!("render {}", route), &report_message);
+                        reported.set(true);
+                    },
+                    if reported() { "reported" } else { "report" }
+                }
+                button {
+                    r#type: "button",
+                    onclick: move |_| on_retry.call(()),
+                    "try again"
+                }
+                button {
+                    r#type: "button",
+                    onclick: move |_| {
+                        document::eval("window.location.reload();");
+                    },
+                    "reload"
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,20 @@
+use crate::utils::{highlight, SearchHit};
+use dioxus::prelude::*;
+
+/// Where the search found an idea: the matched field and the text around the match, with every match marked
+#[component]
+pub fn SearchSnippet(hit: SearchHit) -> Element {
+    rsx! {
+        p {
+            class: "search-snippet",
+            span { class: "search-field", "{hit.field.as_str()}: " }
+            for (text, marked) in highlight(&hit.snippet, &hit.ranges) {
+                if marked {
+                    mark { "{text}" }
+                } else {
+                    "{text}"
+                }
+            }
+        }
+    }
+}
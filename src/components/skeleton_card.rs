@@ -0,0 +1,19 @@
+use dioxus::prelude::*;
+
+/// Placeholder card with pulsing bars, shown while the idea list loads for the first time
+#[component]
+pub fn SkeletonCard() -> Element {
+    rsx! {
+        div {
+            class: "idea-card skeleton-card",
+            div { class: "skeleton-line skeleton-title" }
+            div { class: "skeleton-line" }
+            div { class: "skeleton-line skeleton-short" }
+            div {
+                class: "tags",
+                span { class: "skeleton-chip" }
+                span { class: "skeleton-chip" }
+            }
+        }
+    }
+}
@@ -0,0 +1,37 @@
+use crate::utils::bar_rects;
+use dioxus::prelude::*;
+
+/// Space between two bars, in SVG user units
+const BAR_GAP: f64 = 2.0;
+
+/// A small bar chart of `values` drawn as plain SVG rectangles, stretched to the width of its container. `label`
+/// describes the whole chart to screen readers, which can't read the bars.
+#[component]
+pub fn Sparkline(
+    values: Vec<usize>,
+    label: String,
+    #[props(default = 240.0)] width: f64,
+    #[props(default = 48.0)] height: f64,
+) -> Element {
+    let bars = bar_rects(&values, width, height, BAR_GAP);
+
+    rsx! {
+        svg {
+            class: "sparkline",
+            view_box: "0 0 {width} {height}",
+            preserve_aspect_ratio: "none",
+            role: "img",
+            "aria-label": "{label}",
+            for (index, bar) in bars.into_iter().enumerate() {
+                rect {
+                    key: "{index}",
+                    class: "sparkline-bar",
+                    x: "{bar.x}",
+                    y: "{bar.y}",
+                    width: "{bar.width}",
+                    height: "{bar.height}",
+                }
+            }
+        }
+    }
+}
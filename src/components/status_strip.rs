@@ -0,0 +1,69 @@
+use crate::db::IdeaStatus;
+use crate::hooks::{use_idea_list_revision, use_stylesheet};
+use crate::server_functions::count_ideas_server;
+use crate::utils::{segment_widths, ListQuery};
+use crate::Route;
+use dioxus::prelude::*;
+
+const STATUS_STRIP_CSS: Asset = asset!("/assets/styling/status_strip.css");
+
+/// How the ideas are spread over the statuses, as one bar split into colored segments. Clicking a segment lists only
+/// that status, and clicking it again every status. Refetched whenever ideas change; hidden while there are none.
+#[component]
+pub fn StatusStrip(query: ListQuery) -> Element {
+    use_stylesheet(STATUS_STRIP_CSS);
+
+    let idea_list_revision = use_idea_list_revision();
+    let counts = use_resource(move || async move {
+        let _ = idea_list_revision.get();
+        count_ideas_server(None).await.ok()
+    });
+
+    let Some(counts) = counts().flatten().filter(|counts| counts.total > 0) else {
+        return rsx! {};
+    };
+    let total = counts.total;
+    let widths = segment_widths(&counts.by_status.iter().map(|(_, count)| *count).collect::<Vec<_>>());
+    let done = counts
+        .by_status
+        .iter()
+        .find(|(status, _)| *status == IdeaStatus::Done)
+        .map_or(0, |(_, count)| *count);
+
+    rsx! {
+        div {
+            class: "status-strip",
+            p {
+                class: "status-strip-summary",
+                "{done} of {total} done"
+            }
+            div {
+                class: "status-bar",
+                role: "group",
+                aria_label: "ideas by status",
+                for ((status, count), width) in counts.by_status.into_iter().zip(widths) {
+                    if width > 0 {
+                        button {
+                            key: "{status}",
+                            r#type: "button",
+                            class: "status-segment",
+                            class: if query.status == Some(status) { "active" },
+                            "data-status": status.as_str(),
+                            style: "flex: 0 0 {width}%",
+                            title: "{count} {status}",
+                            aria_label: "{count} {status}",
+                            aria_pressed: query.status == Some(status),
+                            onclick: {
+                                let query = query.with_status_toggled(status);
+                                move |_| {
+                                    navigator().replace(Route::Home { query: query.clone() });
+                                }
+                            },
+                            "{count}"
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,38 @@
+use crate::hooks::use_stylesheet;
+use crate::limits::{LimitLevel, StorageReport};
+use dioxus::prelude::*;
+
+const STORAGE_BANNER_CSS: Asset = asset!("/assets/styling/storage_banner.css");
+
+/// Says when the database is past [`crate::limits::WARNING_PERCENT`] of one of the deployment's storage limits,
+/// and that nothing new is taken once one is reached. Hidden while every resource is fine.
+#[component]
+pub fn StorageBanner(report: StorageReport) -> Element {
+    use_stylesheet(STORAGE_BANNER_CSS);
+
+    let level = report.level();
+    if level == LimitLevel::Fine {
+        return rsx! {};
+    }
+    let full = level == LimitLevel::Full;
+
+    rsx! {
+        div {
+            class: "storage-banner",
+            class: if full { "storage-full" },
+            role: if full { "alert" } else { "status" },
+            p {
+                if full {
+                    "This board is full: delete ideas or attachments before adding new ones."
+                } else {
+                    "This board is getting full."
+                }
+            }
+            ul {
+                for warning in report.warnings() {
+                    li { key: "{warning}", "{warning}" }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,21 @@
+use crate::hooks::StylesheetsContext;
+use dioxus::prelude::*;
+
+/// Styles of the whole app, linked before any component's own
+const MAIN_CSS: Asset = asset!("/assets/styling/main.css");
+
+/// Every stylesheet of the app, linked once in the document head. Mounted once at the root, below
+/// [`crate::hooks::use_stylesheets_provider`], it links the sheets components registered with
+/// [`crate::hooks::use_stylesheet`]. They stay linked after the components that asked for them unmount, so going
+/// back to a page doesn't load its styles again.
+#[component]
+pub fn Stylesheets() -> Element {
+    let stylesheets = use_context::<StylesheetsContext>();
+
+    rsx! {
+        document::Link { rel: "stylesheet", href: MAIN_CSS }
+        for href in stylesheets.get().hrefs().to_vec() {
+            document::Link { key: "{href}", rel: "stylesheet", href }
+        }
+    }
+}
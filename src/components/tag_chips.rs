@@ -0,0 +1,111 @@
+use crate::settings::tag_overflow;
+use crate::tags::TagColors;
+use crate::utils::TagChange;
+use dioxus::prelude::*;
+
+/// The tags of the idea `title` as chips in their colors. Clicking one calls `on_tag_click` with it, and tags in
+/// `selected` are shown pressed. With a `limit`, only that many are shown and a "+n" chip names the others.
+///
+/// With `on_change_tags` the tags are edited in place: hovering one reveals an "×" that removes it and the trailing
+/// "+" opens an input to add one, suggested from a `known-tags` datalist when the page has one. Nothing is shown for
+/// an idea without tags that can't get any.
+#[component]
+pub fn TagChips(
+    title: String,
+    tags: Vec<String>,
+    limit: Option<usize>,
+    selected: Vec<String>,
+    tag_colors: TagColors,
+    on_tag_click: EventHandler<String>,
+    on_change_tags: Option<EventHandler<TagChange>>,
+) -> Element {
+    // The tag being typed, `None` while the input is closed
+    let mut new_tag = use_signal(|| None::<String>);
+
+    if tags.is_empty() && on_change_tags.is_none() {
+        return rsx! {};
+    }
+    let (shown_tags, hidden_tags) = tag_overflow(&tags, limit.unwrap_or(tags.len()));
+    let shown_tags = shown_tags.to_vec();
+    let hidden_label = tags[shown_tags.len()..].join(", ");
+
+    rsx! {
+        div {
+            class: "tags",
+            for tag in shown_tags {
+                {
+                    let is_selected = selected.iter().any(|selected| selected == tag.trim());
+                    let hint = if is_selected { format!("stop filtering on {tag}") } else { format!("filter on {tag}") };
+                    let clicked = tag.clone();
+                    let removed = tag.clone();
+                    rsx! {
+                        span {
+                            class: "tag-chip",
+                            button {
+                                r#type: "button",
+                                class: "tag",
+                                "data-color": tag_colors.color_for(&tag),
+                                class: if is_selected { "active" },
+                                title: "{hint}",
+                                aria_pressed: is_selected,
+                                onclick: move |_| on_tag_click.call(clicked.clone()),
+                                "{tag}"
+                            }
+                            if let Some(on_change_tags) = on_change_tags {
+                                button {
+                                    r#type: "button",
+                                    class: "tag-remove",
+                                    title: "remove {tag}",
+                                    aria_label: "remove tag {tag} from {title}",
+                                    onclick: move |_| on_change_tags.call(TagChange::Remove(removed.clone())),
+                                    "×"
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            if hidden_tags > 0 {
+                span { class: "tag tag-overflow", title: "{hidden_label}", "+{hidden_tags}" }
+            }
+            if let Some(on_change_tags) = on_change_tags {
+                match new_tag() {
+                    Some(typed) => rsx! {
+                        input {
+                            class: "tag-input",
+                            r#type: "text",
+                            list: "known-tags",
+                            placeholder: "tag",
+                            aria_label: "new tag for {title}",
+                            autofocus: true,
+                            value: "{typed}",
+                            oninput: move |evt| new_tag.set(Some(evt.value())),
+                            onkeydown: move |evt| match evt.key() {
+                                Key::Enter => {
+                                    evt.prevent_default();
+                                    let tag = new_tag.take().unwrap_or_default();
+                                    if !tag.trim().is_empty() {
+                                        on_change_tags.call(TagChange::Add(tag));
+                                    }
+                                }
+                                Key::Escape => new_tag.set(None),
+                                _ => {}
+                            },
+                            onblur: move |_| new_tag.set(None),
+                        }
+                    },
+                    None => rsx! {
+                        button {
+                            r#type: "button",
+                            class: "tag tag-add",
+                            title: "add a tag",
+                            aria_label: "add a tag to {title}",
+                            onclick: move |_| new_tag.set(Some(String::new())),
+                            "+"
+                        }
+                    },
+                }
+            }
+        }
+    }
+}
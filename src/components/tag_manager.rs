@@ -0,0 +1,80 @@
+use crate::error::error_message;
+use crate::hooks::use_stylesheet;
+use crate::server_functions::{get_idea_summaries_server, get_tag_colors_server, set_tag_color_server};
+use crate::tags::TAG_PALETTE;
+use dioxus::prelude::*;
+
+const TAG_MANAGER_CSS: Asset = asset!("/assets/styling/tag_manager.css");
+
+/// Value of the "no pinned color" option
+const AUTO: &str = "auto";
+
+/// Panel listing every tag in use with a color picker, for pinning a tag to a palette color
+#[component]
+pub fn TagManager() -> Element {
+    use_stylesheet(TAG_MANAGER_CSS);
+
+    let tags = use_resource(|| async move {
+        let ideas = get_idea_summaries_server().await.unwrap_or_default();
+        let mut tags: Vec<String> = Vec::new();
+        for tag in ideas.iter().flat_map(|idea| idea.tags.iter()).map(|tag| tag.trim()) {
+            if !tag.is_empty() && !tags.iter().any(|known| known.eq_ignore_ascii_case(tag)) {
+                tags.push(tag.to_string());
+            }
+        }
+        tags.sort_by_key(|tag| tag.to_lowercase());
+        tags
+    });
+    let mut colors = use_resource(|| async move { get_tag_colors_server().await.unwrap_or_default() });
+    let mut message = use_signal(String::new);
+
+    let colors_now = colors().unwrap_or_default();
+
+    rsx! {
+        match tags() {
+            None => rsx! { p { class: "hint", "loading tags..." } },
+            Some(tags) if tags.is_empty() => rsx! { p { class: "hint", "No tags in use yet." } },
+            Some(tags) => rsx! {
+                ul {
+                    class: "tag-manager",
+                    for tag in tags {
+                        li {
+                            key: "{tag}",
+                            span { class: "tag", "data-color": colors_now.color_for(&tag), "{tag}" }
+                            select {
+                                aria_label: "color for {tag}",
+                                onchange: {
+                                    let tag = tag.clone();
+                                    move |event: FormEvent| {
+                                        let tag = tag.clone();
+                                        async move {
+                                            let value = event.value();
+                                            let color = (value != AUTO).then_some(value);
+                                            match set_tag_color_server(tag, color).await {
+                                                Ok(()) => message.set(String::new()),
+                                                Err(e) => message.set(format!("error: {}", error_message(&e))),
+                                            }
+                                            colors.restart();
+                                        }
+                                    }
+                                },
+                                option { value: AUTO, selected: colors_now.pinned(&tag).is_none(), "auto" }
+                                for color in TAG_PALETTE {
+                                    option {
+                                        value: color,
+                                        selected: colors_now.pinned(&tag) == Some(color),
+                                        "{color}"
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            },
+        }
+
+        if !message().is_empty() {
+            p { class: "message", "{message}" }
+        }
+    }
+}
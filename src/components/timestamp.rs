@@ -0,0 +1,15 @@
+use crate::hooks::use_relative_time;
+use crate::utils::absolute_time;
+use chrono::{DateTime, Utc};
+use dioxus::prelude::*;
+
+/// A `time` element showing `at` as picked in the settings, "3 h ago" by default, with the exact time on hover
+#[component]
+pub fn Timestamp(at: DateTime<Utc>, #[props(default)] class: String) -> Element {
+    let shown = use_relative_time(at);
+    let exact = absolute_time(at);
+
+    rsx! {
+        time { class, datetime: at.to_rfc3339(), title: exact, "{shown}" }
+    }
+}
@@ -0,0 +1,30 @@
+use crate::hooks::{use_stylesheet, use_toasts};
+use dioxus::prelude::*;
+
+const TOAST_CSS: Asset = asset!("/assets/styling/toast.css");
+
+/// Corner of the page where toasts raised through [`crate::hooks::Toasts`] appear. Clicking one dismisses it.
+#[component]
+pub fn ToastHost() -> Element {
+    use_stylesheet(TOAST_CSS);
+
+    let mut toasts = use_toasts();
+
+    rsx! {
+        div {
+            class: "toasts",
+            aria_live: "polite",
+            for toast in toasts.list() {
+                button {
+                    key: "{toast.id}",
+                    r#type: "button",
+                    class: "toast",
+                    "data-kind": toast.kind.as_str(),
+                    title: "dismiss",
+                    onclick: move |_| toasts.dismiss(toast.id),
+                    "{toast.message}"
+                }
+            }
+        }
+    }
+}
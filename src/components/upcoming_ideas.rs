@@ -0,0 +1,54 @@
+use crate::hooks::{use_idea_list_revision, use_stylesheet};
+use crate::server_functions::get_ideas_due_before_server;
+use crate::utils::{due_label, due_status, local_now, DUE_SOON_DAYS};
+use crate::Route;
+use dioxus::prelude::*;
+
+const UPCOMING_IDEAS_CSS: Asset = asset!("/assets/styling/upcoming_ideas.css");
+
+/// Ideas that are overdue or due within [`DUE_SOON_DAYS`] days, soonest first. Hidden while there are none.
+#[component]
+pub fn UpcomingIdeas() -> Element {
+    use_stylesheet(UPCOMING_IDEAS_CSS);
+
+    let idea_list_revision = use_idea_list_revision();
+    let upcoming = use_resource(move || async move {
+        let _ = idea_list_revision.get();
+        // Exclusive bound: everything up to and including the last "soon" day
+        let until = local_now().date() + chrono::Days::new(DUE_SOON_DAYS as u64 + 1);
+        get_ideas_due_before_server(until).await.unwrap_or_default()
+    });
+
+    let ideas = upcoming().unwrap_or_default();
+    if ideas.is_empty() {
+        return rsx! {};
+    }
+    let now = local_now();
+
+    rsx! {
+        div {
+            class: "upcoming-ideas",
+            h3 { "upcoming" }
+            ul {
+                class: "upcoming-list",
+                for idea in ideas {
+                    if let (Some(id), Some(due)) = (idea.id.clone(), idea.due_date) {
+                        li {
+                            key: "{id}",
+                            Link {
+                                class: "upcoming-idea",
+                                to: Route::IdeaDevelopment { id },
+                                span { class: "upcoming-title", "{idea.title}" }
+                                span {
+                                    class: "due-chip",
+                                    "data-due": due_status(due, now).as_str(),
+                                    "{due_label(due, now)}"
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
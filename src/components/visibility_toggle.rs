@@ -0,0 +1,53 @@
+use crate::client_errors::log_error;
+use crate::db::Visibility;
+use crate::error::error_message;
+use crate::hooks::use_toasts;
+use crate::server_functions::set_idea_visibility_server;
+use dioxus::prelude::*;
+
+/// Button switching an idea between private and public, labelled with what it is now. Only anonymous visitors of a
+/// public deployment tell the two apart. `on_changed` gets the visibility the server stored; failures are toasts.
+#[component]
+pub fn VisibilityToggle(
+    idea_id: String,
+    visibility: Visibility,
+    class: String,
+    on_changed: EventHandler<Visibility>,
+) -> Element {
+    let mut toasts = use_toasts();
+    let mut saving = use_signal(|| false);
+
+    let toggle = move |_| {
+        let idea_id = idea_id.clone();
+        let next = match visibility {
+            Visibility::Public => Visibility::Private,
+            Visibility::Private => Visibility::Public,
+        };
+        spawn(async move {
+            saving.set(true);
+            match set_idea_visibility_server(idea_id, next).await {
+                Ok(stored) => on_changed.call(stored),
+                Err(e) => {
+                    log_error("set idea visibility", &e);
+                    toasts.error(format!("Couldn't make the idea {}: {}", next.as_str(), error_message(&e)));
+                }
+            }
+            saving.set(false);
+        });
+    };
+
+    rsx! {
+        button {
+            r#type: "button",
+            class,
+            disabled: saving(),
+            aria_pressed: visibility == Visibility::Public,
+            title: match visibility {
+                Visibility::Public => "visitors of a public deployment can read this idea",
+                Visibility::Private => "only you can read this idea",
+            },
+            onclick: toggle,
+            "{visibility.as_str()}"
+        }
+    }
+}
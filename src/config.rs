@@ -0,0 +1,183 @@
+//! Settings of a deployment, read from the environment once when the server starts.
+//!
+//! The server hands the [`AppConfig`] to every request with [`server::with_config`], so server functions and REST
+//! routes look it up instead of reading environment variables themselves. The client fetches the same struct with
+//! `get_app_config_server()` to hide what the server would refuse anyway.
+
+use crate::error::IdeaError;
+use crate::limits::StorageLimits;
+use serde::{Deserialize, Serialize};
+
+/// Set to `1` (or `true`, `yes`, `on`) to refuse every write, e.g. for a public copy of the board
+pub const READ_ONLY_VAR: &str = "IDEAS_READ_ONLY";
+
+/// Number of days an idea stays in the trash before maintenance deletes it for good; `0` keeps trash forever
+pub const TRASH_RETENTION_VAR: &str = "IDEAS_TRASH_RETENTION_DAYS";
+
+/// Days trashed ideas are kept when [`TRASH_RETENTION_VAR`] isn't set
+pub const DEFAULT_TRASH_RETENTION_DAYS: u32 = 30;
+
+/// How this deployment behaves
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AppConfig {
+    /// Ideas, templates, tag colors and accounts can be read but not changed. Signing in still works.
+    pub read_only: bool,
+    /// Days after which [`crate::maintenance`] purges trashed ideas, or `0` to never purge them
+    #[serde(default)]
+    pub trash_retention_days: u32,
+    /// How much the database may hold, see [`crate::limits`]. Unlimited by default.
+    #[serde(default)]
+    pub limits: StorageLimits,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            read_only: false,
+            trash_retention_days: DEFAULT_TRASH_RETENTION_DAYS,
+            limits: StorageLimits::default(),
+        }
+    }
+}
+
+impl AppConfig {
+    /// Read the settings from [`READ_ONLY_VAR`], [`TRASH_RETENTION_VAR`] and the limit variables of
+    /// [`crate::limits`], keeping the default for anything unset or unrecognized
+    pub fn from_env() -> Self {
+        Self::from_lookup(|key| std::env::var(key).ok())
+    }
+
+    /// Same as [`AppConfig::from_env`] with a custom variable lookup
+    pub fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Self {
+        let flag = |key: &str| {
+            lookup(key).is_some_and(|value| {
+                matches!(value.trim().to_ascii_lowercase().as_str(), "1" | "true" | "yes" | "on")
+            })
+        };
+        let days = lookup(TRASH_RETENTION_VAR).and_then(|value| value.trim().parse().ok());
+        Self {
+            read_only: flag(READ_ONLY_VAR),
+            trash_retention_days: days.unwrap_or(DEFAULT_TRASH_RETENTION_DAYS),
+            limits: StorageLimits::from_lookup(&lookup),
+        }
+    }
+
+    /// Refuse a write on a read-only deployment
+    pub fn check_writable(&self) -> Result<(), IdeaError> {
+        if self.read_only {
+            Err(IdeaError::ReadOnly)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(feature = "server")]
+pub mod server {
+    use super::*;
+
+    tokio::task_local! {
+        /// Settings of the request being handled, for the routes that run outside a server function call
+        static REQUEST_CONFIG: AppConfig;
+    }
+
+    /// Make `config` the settings of every request handled by `router`. Layer this over the whole router, like
+    /// [`crate::db::server::with_db`].
+    pub fn with_config(router: axum::Router, config: AppConfig) -> axum::Router {
+        router
+            .layer(axum::middleware::from_fn_with_state(config, scope_config))
+            .layer(axum::Extension(config))
+    }
+
+    async fn scope_config(
+        axum::extract::State(config): axum::extract::State<AppConfig>,
+        request: axum::extract::Request,
+        next: axum::middleware::Next,
+    ) -> axum::response::Response {
+        REQUEST_CONFIG.scope(config, next.run(request)).await
+    }
+
+    /// Settings of the server handling the current request, be it a server function call, a REST route or the
+    /// feed; the defaults outside one
+    pub fn app_config() -> AppConfig {
+        crate::request::extension::<AppConfig>()
+            .or_else(|| REQUEST_CONFIG.try_with(|config| *config).ok())
+            .unwrap_or_default()
+    }
+
+    /// Guard for server functions that change data, called before anything else
+    pub fn refuse_if_read_only() -> Result<(), IdeaError> {
+        app_config().check_writable()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Server functions that may run on a read-only deployment although they are rate-limited: signing in and out
+    /// only touch sessions, and error reports only go to the log
+    const ALWAYS_ALLOWED: [&str; 3] = ["login_server", "logout_server", "report_client_error_server"];
+
+    #[test]
+    fn test_config_from_lookup() {
+        for value in ["1", "true", " YES ", "on"] {
+            let config = AppConfig::from_lookup(|key| (key == READ_ONLY_VAR).then(|| value.to_string()));
+            assert!(config.read_only, "{:?}", value);
+        }
+        for value in ["0", "false", "", "maybe"] {
+            let config = AppConfig::from_lookup(|_| Some(value.to_string()));
+            assert!(!config.read_only, "{:?}", value);
+        }
+        assert_eq!(AppConfig::from_lookup(|_| None), AppConfig::default());
+    }
+
+    #[test]
+    fn test_read_only_refuses_writes() {
+        let read_only = AppConfig { read_only: true, ..Default::default() };
+        assert_eq!(read_only.check_writable(), Err(IdeaError::ReadOnly));
+        assert_eq!(AppConfig::default().check_writable(), Ok(()));
+    }
+
+    #[test]
+    fn test_trash_retention_from_lookup() {
+        let with_days = |value: &str| {
+            AppConfig::from_lookup(|key| (key == TRASH_RETENTION_VAR).then(|| value.to_string())).trash_retention_days
+        };
+        assert_eq!(with_days(" 7 "), 7);
+        assert_eq!(with_days("0"), 0);
+        assert_eq!(with_days("-3"), DEFAULT_TRASH_RETENTION_DAYS);
+        assert_eq!(with_days("soon"), DEFAULT_TRASH_RETENTION_DAYS);
+        assert_eq!(AppConfig::from_lookup(|_| None).trash_retention_days, DEFAULT_TRASH_RETENTION_DAYS);
+    }
+
+    #[test]
+    fn test_limits_from_lookup() {
+        let config = AppConfig::from_lookup(|key| (key == crate::limits::MAX_IDEAS_VAR).then(|| "100".to_string()));
+        assert_eq!(config.limits.max_ideas, Some(100));
+        assert_eq!(config.limits.max_notes_bytes, None);
+        assert!(AppConfig::default().limits.is_unlimited());
+    }
+
+    #[test]
+    fn test_every_mutating_server_function_checks_read_only() {
+        // Writes are the functions charged by the rate limiter; each must refuse first on a read-only deployment
+        let source = include_str!("server_functions.rs");
+        let mut writes = 0;
+        for function in source.split("#[post(").skip(1) {
+            let name = function
+                .split("pub async fn ")
+                .nth(1)
+                .and_then(|rest| rest.split('(').next())
+                .unwrap_or_default();
+            if !function.contains("crate::rate_limit::server::") || ALWAYS_ALLOWED.contains(&name) {
+                continue;
+            }
+            writes += 1;
+            let guard = function.find("refuse_if_read_only()?");
+            let charge = function.find("crate::rate_limit::server::");
+            assert!(guard.is_some() && guard < charge, "{} doesn't refuse writes first", name);
+        }
+        assert!(writes >= 20, "only found {} writes", writes);
+    }
+}
@@ -0,0 +1,157 @@
+//! Where the database lives.
+//!
+//! Resolved in this order:
+//! 1. `IDEAS_DB_PATH`, the database path itself
+//! 2. a data folder chosen in the desktop settings menu, remembered in the platform config directory
+//! 3. the platform app-data directory (`~/Library/Application Support/...`, `%APPDATA%\...`, `~/.local/share/...`)
+//! 4. `ideas.db` in the working directory, only when the platform has no home directory to put it in
+//!
+//! The database is `ideas.db` inside the data folder in every case but the first, so launching the app from a file
+//! manager or a terminal opens the same database.
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Environment variable overriding the database path
+pub const DB_PATH_VAR: &str = "IDEAS_DB_PATH";
+/// Name of the database inside the data folder
+pub const DB_NAME: &str = "ideas.db";
+/// File in the config directory holding the folder picked in the settings menu
+const CHOSEN_FOLDER_FILE: &str = "data_folder";
+
+/// Per-user directories of the app
+#[derive(Debug, Clone, PartialEq)]
+pub struct AppDirs {
+    /// Default data folder
+    pub data: PathBuf,
+    /// Where settings are remembered
+    pub config: PathBuf,
+}
+
+impl AppDirs {
+    /// The platform's directories for this app. `None` when no home directory can be found.
+    pub fn platform() -> Option<Self> {
+        directories::ProjectDirs::from("", "", "ideas").map(|dirs| Self {
+            data: dirs.data_dir().to_path_buf(),
+            config: dirs.config_dir().to_path_buf(),
+        })
+    }
+
+    /// The data folder picked in the settings menu, if any
+    pub fn chosen_folder(&self) -> Option<PathBuf> {
+        let saved = std::fs::read_to_string(self.config.join(CHOSEN_FOLDER_FILE)).ok()?;
+        let saved = saved.trim();
+        (!saved.is_empty()).then(|| PathBuf::from(saved))
+    }
+
+    /// Remember `folder` as the data folder for the next launch
+    #[cfg(any(feature = "desktop", test))]
+    pub fn choose_folder(&self, folder: &Path) -> io::Result<()> {
+        std::fs::create_dir_all(&self.config)?;
+        std::fs::write(self.config.join(CHOSEN_FOLDER_FILE), folder.to_string_lossy().as_bytes())
+    }
+
+    /// Go back to the default data folder on the next launch
+    #[cfg(any(feature = "desktop", test))]
+    pub fn forget_chosen_folder(&self) -> io::Result<()> {
+        match std::fs::remove_file(self.config.join(CHOSEN_FOLDER_FILE)) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The database path for the given environment and directories, following the order in the module docs
+pub fn resolve_db_path(lookup: impl Fn(&str) -> Option<String>, dirs: Option<&AppDirs>) -> PathBuf {
+    if let Some(path) = lookup(DB_PATH_VAR).filter(|path| !path.trim().is_empty()) {
+        return PathBuf::from(path.trim());
+    }
+
+    match dirs {
+        Some(dirs) => dirs.chosen_folder().unwrap_or_else(|| dirs.data.clone()).join(DB_NAME),
+        None => PathBuf::from(DB_NAME),
+    }
+}
+
+/// The database path of this process
+pub fn db_path() -> PathBuf {
+    resolve_db_path(|key| std::env::var(key).ok(), AppDirs::platform().as_ref())
+}
+
+/// Folder holding the database, as shown and opened by the settings menu
+#[cfg(feature = "desktop")]
+pub fn data_folder() -> PathBuf {
+    folder_of(&db_path())
+}
+
+fn folder_of(db_path: &Path) -> PathBuf {
+    match db_path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => PathBuf::from("."),
+    }
+}
+
+/// [`db_path`], creating its folder if it doesn't exist yet
+#[cfg(feature = "server")]
+pub fn prepare_db_path() -> io::Result<PathBuf> {
+    let path = db_path();
+    std::fs::create_dir_all(folder_of(&path))?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dirs_in(root: &Path) -> AppDirs {
+        AppDirs {
+            data: root.join("data"),
+            config: root.join("config"),
+        }
+    }
+
+    fn no_env(_: &str) -> Option<String> {
+        None
+    }
+
+    #[test]
+    fn test_env_variable_wins() {
+        let root = tempfile::tempdir().unwrap();
+        let dirs = dirs_in(root.path());
+        dirs.choose_folder(Path::new("/somewhere/else")).unwrap();
+
+        let lookup = |key: &str| (key == DB_PATH_VAR).then(|| " /tmp/custom.db ".to_string());
+        assert_eq!(resolve_db_path(lookup, Some(&dirs)), PathBuf::from("/tmp/custom.db"));
+    }
+
+    #[test]
+    fn test_blank_env_variable_is_ignored() {
+        let root = tempfile::tempdir().unwrap();
+        let dirs = dirs_in(root.path());
+
+        let lookup = |_: &str| Some("  ".to_string());
+        assert_eq!(resolve_db_path(lookup, Some(&dirs)), dirs.data.join(DB_NAME));
+    }
+
+    #[test]
+    fn test_chosen_folder_is_remembered_and_forgotten() {
+        let root = tempfile::tempdir().unwrap();
+        let dirs = dirs_in(root.path());
+        assert_eq!(resolve_db_path(no_env, Some(&dirs)), dirs.data.join(DB_NAME));
+
+        let chosen = root.path().join("Dropbox");
+        dirs.choose_folder(&chosen).unwrap();
+        assert_eq!(resolve_db_path(no_env, Some(&dirs)), chosen.join(DB_NAME));
+
+        dirs.forget_chosen_folder().unwrap();
+        // Forgetting twice is fine
+        dirs.forget_chosen_folder().unwrap();
+        assert_eq!(resolve_db_path(no_env, Some(&dirs)), dirs.data.join(DB_NAME));
+    }
+
+    #[test]
+    fn test_working_directory_without_platform_dirs() {
+        assert_eq!(resolve_db_path(no_env, None), PathBuf::from(DB_NAME));
+        assert_eq!(folder_of(Path::new(DB_NAME)), PathBuf::from("."));
+    }
+}
@@ -1,8 +1,16 @@
+use crate::error::IdeaError;
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+// The stable, library-independent form of idea ids
+mod id;
+pub use id::{IdeaId, IDEA_TABLE};
 
 /// Idea model for storing user-submitted ideas (shared between client and server)
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
 pub struct Idea {
+    /// Always `ideas:<key>`, see [`IdeaId`]. `None` before the idea is stored.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub id: Option<String>,
     pub title: String,
@@ -12,11 +20,407 @@ pub struct Idea {
     pub what_must_be_true: Vec<String>,
     #[serde(default)]
     pub development_notes: String,
+    /// The notes as headed sections, for ideas switched to structured notes. Empty while the idea keeps its notes in
+    /// `development_notes`.
+    #[serde(default)]
+    pub sections: Vec<NoteSection>,
+    /// URL-friendly identifier derived from the title
+    #[serde(default)]
+    pub slug: String,
+    /// Slugs used before the title was last changed, kept so old links still resolve
+    #[serde(default)]
+    pub previous_slugs: Vec<String>,
+    /// Manual sort position, lower ranks are listed first
+    #[serde(default)]
+    pub rank: f64,
+    /// When the idea was moved to the trash. `None` for active ideas.
+    #[serde(default)]
+    pub deleted_at: Option<DateTime<Utc>>,
+    /// Pinned ideas are listed above the others whatever the sort order
+    #[serde(default)]
+    pub pinned: bool,
+    /// Day the idea is time-boxed to, sent as an ISO date (`2024-06-01`)
+    #[serde(default)]
+    pub due_date: Option<NaiveDate>,
+    /// Column of the board the idea sits in
+    #[serde(default)]
+    pub status: IdeaStatus,
+    /// Whether anonymous visitors of a public deployment can read the idea
+    #[serde(default)]
+    pub visibility: Visibility,
+    /// Display name of whoever added the idea, as they set it in their browser. `None` when they didn't say.
+    #[serde(default)]
+    pub author: Option<String>,
+    /// A single emoji shown before the title, see [`crate::utils::is_emoji_icon`]
+    #[serde(default)]
+    pub icon: Option<String>,
+    /// Values of the deployment's own form fields by field name, see [`crate::form_config`]
+    #[serde(default)]
+    pub custom_fields: HashMap<String, serde_json::Value>,
+    /// When the idea was stored. `None` for ideas from before creation times were kept.
+    #[serde(default)]
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+/// One headed part of an idea's structured notes, like "Problem" or "Experiment"
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct NoteSection {
+    /// Empty for the text that came before the first heading of converted notes
+    pub heading: String,
+    pub body: String,
+}
+
+/// Characters of the description kept in an [`IdeaSummary`]
+pub const SUMMARY_DESCRIPTION_CHARS: usize = 200;
+
+/// What the idea list needs of an idea. Notes and statements can be long and only the development page shows them,
+/// so the list gets their sizes instead.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct IdeaSummary {
+    pub id: Option<String>,
+    pub title: String,
+    /// The description cut to [`SUMMARY_DESCRIPTION_CHARS`] characters
+    pub short_description: String,
+    pub tags: Vec<String>,
+    pub slug: String,
+    /// Number of "what must be true" statements
+    pub what_must_be_true_count: usize,
+    /// Length of the development notes in characters
+    pub notes_length: usize,
+    pub pinned: bool,
+    pub due_date: Option<NaiveDate>,
+    #[serde(default)]
+    pub status: IdeaStatus,
+    #[serde(default)]
+    pub author: Option<String>,
+    #[serde(default)]
+    pub icon: Option<String>,
+    #[serde(default)]
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+impl From<Idea> for IdeaSummary {
+    fn from(idea: Idea) -> Self {
+        IdeaSummary {
+            id: idea.id,
+            title: idea.title,
+            short_description: crate::utils::truncate_text(&idea.description, SUMMARY_DESCRIPTION_CHARS),
+            tags: idea.tags,
+            slug: idea.slug,
+            what_must_be_true_count: idea.what_must_be_true.len(),
+            notes_length: idea.development_notes.chars().count(),
+            pinned: idea.pinned,
+            due_date: idea.due_date,
+            status: idea.status,
+            author: idea.author,
+            icon: idea.icon,
+            created_at: idea.created_at,
+        }
+    }
+}
+
+/// What submitting an idea did. Without `force`, an idea whose title looks like an existing one is held back: it
+/// comes back with `created` empty and the look-alikes listed. A forced submit creates the idea and still lists them.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct SubmitOutcome {
+    pub created: Option<Idea>,
+    /// Existing ideas with a similar title, most similar first
+    pub possible_duplicates: Vec<IdeaSummary>,
+}
+
+/// A new idea as the submit form sends it
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct IdeaSubmission {
+    pub title: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub what_must_be_true: Vec<String>,
+    /// Name the idea is credited to
+    #[serde(default)]
+    pub author: Option<String>,
+    /// Inputs of the deployment's own form fields, checked against its [`crate::form_config::FormConfig`]
+    #[serde(default)]
+    pub custom_fields: HashMap<String, serde_json::Value>,
+}
+
+/// The editable content of an idea as the edit page saves it. Sections are saved on their own.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct IdeaEdit {
+    pub title: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub what_must_be_true: Vec<String>,
+    #[serde(default)]
+    pub development_notes: String,
+    #[serde(default)]
+    pub due_date: Option<NaiveDate>,
+}
+
+/// Size of a user's collection, as shown in the navbar
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct IdeaCounts {
+    /// Ideas outside the trash
+    pub total: usize,
+    /// The most used tags among them, see [`crate::tags::top_tags`]
+    pub top_tags: Vec<crate::tags::TagCount>,
+    /// How many of them have each status, in [`IdeaStatus::ALL`] order, see [`crate::utils::status_counts`]
+    #[serde(default)]
+    pub by_status: Vec<(IdeaStatus, usize)>,
+}
+
+/// How well the server's cache of idea lists is doing, see `db::cache`
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CacheStats {
+    /// List reads answered from the cache
+    pub hits: u64,
+    /// List reads that went to the database
+    pub misses: u64,
+    /// Lists cached now, one per owner
+    pub entries: usize,
+}
+
+/// What deleting an idea for good takes with it, asked for before and reported after
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DeleteImpact {
+    pub attachments: u64,
+    /// `relates_to` links from or to the idea
+    pub links: u64,
+    pub revisions: u64,
+}
+
+impl DeleteImpact {
+    /// "This will also remove 4 attachments and 1 link", or `None` when nothing goes with the idea
+    pub fn summary(&self) -> Option<String> {
+        let counted = |count: u64, one: &str, many: &str| match count {
+            0 => None,
+            1 => Some(format!("1 {}", one)),
+            count => Some(format!("{} {}", count, many)),
+        };
+        let parts: Vec<String> = [
+            counted(self.attachments, "attachment", "attachments"),
+            counted(self.links, "link", "links"),
+            counted(self.revisions, "revision", "revisions"),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        let listed = match parts.as_slice() {
+            [] => return None,
+            [only] => only.clone(),
+            [rest @ .., last] => format!("{} and {}", rest.join(", "), last),
+        };
+        Some(format!("This will also remove {}", listed))
+    }
+}
+
+/// A stored idea that can't be read as an idea any more, e.g. after the database was edited by hand or written by a
+/// different version of the app. Lists leave it out instead of failing as a whole.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct MalformedRecord {
+    /// The record's id, in the same `ideas:<key>` form as [`Idea::id`]
+    pub id: String,
+    /// Why it couldn't be read
+    pub error: String,
+}
+
+/// Longest tag accepted, in characters
+pub const MAX_TAG_CHARS: usize = 40;
+
+/// Tidy a list of tags for storage: trim them, drop blanks, keep one tag per spelling ignoring case (written the
+/// way it first appears, so "Rust" stays "Rust" next to a later "rust") and sort them, ignoring case, so the same
+/// set of tags is always stored the same way
+pub fn tidy_tags(tags: Vec<String>) -> Vec<String> {
+    let mut tidy: Vec<String> = Vec::with_capacity(tags.len());
+    for tag in tags {
+        let tag = tag.trim();
+        if !tag.is_empty() && !tidy.iter().any(|kept| kept.to_lowercase() == tag.to_lowercase()) {
+            tidy.push(tag.to_string());
+        }
+    }
+    tidy.sort_by(|a, b| a.to_lowercase().cmp(&b.to_lowercase()).then_with(|| a.cmp(b)));
+    tidy
+}
+
+/// Refuse tags longer than [`MAX_TAG_CHARS`]
+pub fn check_tags(tags: &[String]) -> Result<(), IdeaError> {
+    match tags.iter().find(|tag| tag.trim().chars().count() > MAX_TAG_CHARS) {
+        Some(tag) => Err(IdeaError::Validation(format!(
+            "The tag \"{}\" is longer than {} characters",
+            tag.trim(),
+            MAX_TAG_CHARS
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// The tags as they will be stored, see [`tidy_tags`], or why they can't be
+pub fn normalize_tags(tags: Vec<String>) -> Result<Vec<String>, IdeaError> {
+    let tags = tidy_tags(tags);
+    check_tags(&tags)?;
+    Ok(tags)
+}
+
+/// Tags stored before they were normalized, made valid: tags longer than [`MAX_TAG_CHARS`] are cut short, then
+/// tidied with [`tidy_tags`]
+pub fn repair_tags(tags: &[String]) -> Vec<String> {
+    tidy_tags(
+        tags.iter()
+            .map(|tag| tag.trim().chars().take(MAX_TAG_CHARS).collect::<String>())
+            .collect(),
+    )
+}
+
+/// How a filter on several tags matches: ideas carrying every selected tag, or any of them
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TagMatchMode {
+    #[default]
+    All,
+    Any,
+}
+
+impl TagMatchMode {
+    /// Value used in the query string
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TagMatchMode::All => "all",
+            TagMatchMode::Any => "any",
+        }
+    }
+
+    /// Parse a query string value. Unknown values give `None`.
+    pub fn parse(value: &str) -> Option<Self> {
+        [TagMatchMode::All, TagMatchMode::Any]
+            .into_iter()
+            .find(|mode| mode.as_str() == value)
+    }
+
+    /// Whether an idea tagged `tags` passes a filter on `selected`. Tags compare trimmed, and an empty selection
+    /// lets every idea through.
+    pub fn matches(self, tags: &[String], selected: &[String]) -> bool {
+        let carries = |wanted: &String| tags.iter().any(|tag| tag.trim() == wanted.trim());
+        match self {
+            _ if selected.is_empty() => true,
+            TagMatchMode::All => selected.iter().all(carries),
+            TagMatchMode::Any => selected.iter().any(carries),
+        }
+    }
+}
+
+/// An author as it will be stored: trimmed, with a blank one meaning nobody
+pub fn tidy_author(author: Option<String>) -> Option<String> {
+    author
+        .map(|author| author.trim().to_string())
+        .filter(|author| !author.is_empty())
+}
+
+/// Which ideas a filter on the author lets through
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AuthorFilter {
+    /// Ideas added under this name
+    By(String),
+    /// Ideas added without a name, including every idea from before authors existed
+    Unattributed,
+}
+
+impl AuthorFilter {
+    /// The filter for a stored author: `None` and blank names are the unattributed bucket
+    pub fn for_author(author: Option<&str>) -> Self {
+        match tidy_author(author.map(str::to_string)) {
+            Some(author) => AuthorFilter::By(author),
+            None => AuthorFilter::Unattributed,
+        }
+    }
+
+    /// Whether an idea added by `author` passes. Names compare trimmed and exactly.
+    pub fn matches(&self, author: Option<&str>) -> bool {
+        *self == AuthorFilter::for_author(author)
+    }
+
+    /// The name filtered on, `None` for the unattributed bucket
+    pub fn name(&self) -> Option<&str> {
+        match self {
+            AuthorFilter::By(author) => Some(author),
+            AuthorFilter::Unattributed => None,
+        }
+    }
+}
+
+/// How far along an idea is. Each status is a column of the board; new ideas start in the inbox.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IdeaStatus {
+    #[default]
+    Inbox,
+    Exploring,
+    Building,
+    Done,
+    Dropped,
+}
+
+impl IdeaStatus {
+    /// Every status, in the board's default column order
+    pub const ALL: [IdeaStatus; 5] = [
+        IdeaStatus::Inbox,
+        IdeaStatus::Exploring,
+        IdeaStatus::Building,
+        IdeaStatus::Done,
+        IdeaStatus::Dropped,
+    ];
+
+    /// Stable name, used for storage and in requests
+    pub fn as_str(self) -> &'static str {
+        match self {
+            IdeaStatus::Inbox => "inbox",
+            IdeaStatus::Exploring => "exploring",
+            IdeaStatus::Building => "building",
+            IdeaStatus::Done => "done",
+            IdeaStatus::Dropped => "dropped",
+        }
+    }
+
+    /// Unknown values give `None`
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|status| status.as_str() == value.trim())
+    }
+}
+
+impl std::fmt::Display for IdeaStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Who can read an idea on a public deployment, a read-only one anyone may visit. Ideas are private until made public.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Visibility {
+    /// Readable by anyone visiting a public deployment
+    Public,
+    /// Only readable by whoever owns the idea; on a public deployment anonymous visitors don't see it
+    #[default]
+    Private,
+}
+
+impl Visibility {
+    /// Stable name, used for storage and in requests
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Visibility::Public => "public",
+            Visibility::Private => "private",
+        }
+    }
 }
 
 // Server-side internal representation with SurrealDB types
 #[cfg(feature = "server")]
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct IdeaRecord {
     pub id: Option<surrealdb::sql::Thing>,
     pub title: String,
@@ -26,70 +430,756 @@ pub struct IdeaRecord {
     pub what_must_be_true: Vec<String>,
     #[serde(default)]
     pub development_notes: String,
+    #[serde(default)]
+    pub sections: Vec<NoteSection>,
+    #[serde(default)]
+    pub slug: String,
+    #[serde(default)]
+    pub previous_slugs: Vec<String>,
+    #[serde(default)]
+    pub rank: f64,
+    /// User who created the idea. `None` for ideas created anonymously.
+    #[serde(default)]
+    pub owner: Option<surrealdb::sql::Thing>,
+    /// Board the idea is on, see [`crate::boards`]. `None` for ideas stored before boards existed, which are on the
+    /// default board.
+    #[serde(default)]
+    pub board: Option<surrealdb::sql::Thing>,
+    #[serde(default)]
+    pub deleted_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub pinned: bool,
+    #[serde(default)]
+    pub due_date: Option<NaiveDate>,
+    #[serde(default)]
+    pub status: IdeaStatus,
+    /// Ideas stored before visibility existed have no such field and read as private
+    #[serde(default)]
+    pub visibility: Visibility,
+    /// Ideas stored before authors existed have no such field and read as unattributed
+    #[serde(default)]
+    pub author: Option<String>,
+    #[serde(default)]
+    pub icon: Option<String>,
+    /// Any values, including those of fields the form no longer has
+    #[serde(default)]
+    pub custom_fields: HashMap<String, serde_json::Value>,
+    /// Stamped when the idea is created; ideas stored before creation times were kept have none
+    #[serde(default)]
+    pub created_at: Option<DateTime<Utc>>,
 }
 
 #[cfg(feature = "server")]
 impl From<IdeaRecord> for Idea {
     fn from(record: IdeaRecord) -> Self {
         Idea {
-            id: record.id.map(|thing| thing.to_string()),
+            id: record.id.map(|thing| IdeaId::from(&thing).into()),
             title: record.title,
             description: record.description,
             tags: record.tags,
             what_must_be_true: record.what_must_be_true,
             development_notes: record.development_notes,
+            sections: record.sections,
+            slug: record.slug,
+            previous_slugs: record.previous_slugs,
+            rank: record.rank,
+            deleted_at: record.deleted_at,
+            pinned: record.pinned,
+            due_date: record.due_date,
+            status: record.status,
+            visibility: record.visibility,
+            author: record.author,
+            icon: record.icon,
+            custom_fields: record.custom_fields,
+            created_at: record.created_at,
         }
     }
 }
 
+// Storage-agnostic access to ideas, re-exported from `server`
+#[cfg(feature = "server")]
+pub mod repository;
+
+// Cached idea lists, re-exported from `server`
+#[cfg(feature = "server")]
+pub mod cache;
+
+// Private ideas on a public deployment, re-exported from `server`
+#[cfg(feature = "server")]
+pub mod visibility;
+
+// Choice of storage engine, re-exported from `server`
+#[cfg(feature = "server")]
+pub mod storage;
+
+// Statements written all or nothing, re-exported from `server`
+#[cfg(feature = "server")]
+pub mod transaction;
+
 // Server-only database code
 #[cfg(feature = "server")]
 pub mod server {
     use super::*;
-    use surrealdb::{engine::local::RocksDb, Surreal};
+    use crate::error::IdeaError;
+    use crate::utils::{slugify, unique_slug, PageCursor, MAX_PAGE_SIZE};
+    use surrealdb::{sql::Thing, Surreal};
     use tokio::sync::OnceCell;
 
-    /// Static database instance that's lazily initialized
-    static DB: OnceCell<Surreal<surrealdb::engine::local::Db>> = OnceCell::const_new();
+    pub use super::repository::{
+        append_wmbt, change_tags, delete_impact, edit_idea, get_owned, idea_revisions, merge_idea_fields, merge_ideas, purge_idea, similar_ideas, restore_idea, restore_ideas, restore_revision, set_icon, set_status, set_visibility, submit_idea, submit_idea_content, submit_idea_with_fields, submit_ideas, toggle_pin, touch_idea, trash_idea,
+        IdeaChanges, IdeaRepository, MAX_BATCH_IDEAS, MockIdeaRepository, SurrealIdeaRepository,
+    };
+    pub use super::cache::{IdeaCache, LIST_CACHE_TTL};
+    pub use super::visibility::{ensure_readable, hides_private, is_readable, readable_by};
+    pub use super::storage::{connect, connect_with, StorageSettings};
+    pub use super::transaction::{with_transaction, Transaction};
 
-    /// Get or initialize the database instance
-    pub async fn get_db() -> &'static Surreal<surrealdb::engine::local::Db> {
-        DB.get_or_init(|| async {
-            // Use RocksDB-based local database
-            let db = Surreal::new::<RocksDb>("ideas.db")
-                .await
-                .expect("Failed to create database");
+    /// Open the configured database and define its schema, panicking when that fails. See [`super::storage`] for
+    /// how the engine is chosen.
+    pub async fn open_db() -> Surreal<surrealdb::engine::any::Any> {
+        connect().await.expect("Failed to open the database")
+    }
+
+    /// Process-wide database for code running outside a server, like the seed binary.
+    ///
+    /// Server functions use [`request_db`] instead, so each server works on the database it was given.
+    pub async fn get_db() -> &'static Surreal<surrealdb::engine::any::Any> {
+        static DB: OnceCell<Surreal<surrealdb::engine::any::Any>> = OnceCell::const_new();
 
-            db.use_ns("ideas_ns")
-                .use_db("ideas_db")
-                .await
-                .expect("Failed to select namespace and database");
+        DB.get_or_init(open_db).await
+    }
 
-            db
+    /// Make `db` the database of every request handled by `router`, with a fresh [`IdeaCache`] in front of it and
+    /// a fresh [`crate::limits::server::UsageCache`] measuring it. Layer this over the whole router, after the server
+    /// function and REST routes are merged in.
+    pub fn with_db(router: axum::Router, db: Surreal<surrealdb::engine::any::Any>) -> axum::Router {
+        router
+            .layer(axum::Extension(db))
+            .layer(axum::Extension(IdeaCache::default()))
+            .layer(axum::Extension(crate::limits::server::UsageCache::default()))
+    }
+
+    /// The database of the server handling the current server function call, as given to [`with_db`]
+    pub fn request_db() -> Result<Surreal<surrealdb::engine::any::Any>, IdeaError> {
+        crate::request::extension::<Surreal<surrealdb::engine::any::Any>>()
+            .ok_or_else(|| IdeaError::Database("no database for this request".to_string()))
+    }
+
+    /// The repository server functions use to reach the database of the current request, through its cache and
+    /// scoped to the request's board, see [`crate::boards::server::current_board`]
+    pub fn repository() -> Result<SurrealIdeaRepository, IdeaError> {
+        let repo = SurrealIdeaRepository::new(request_db()?).in_board(crate::boards::server::current_board());
+        Ok(match crate::request::extension::<IdeaCache>() {
+            Some(cache) => repo.with_cache(cache),
+            None => repo,
         })
-        .await
+    }
+
+    /// Clear the list cache of the current request's database. Server functions that write ideas without going
+    /// through [`repository`] call it once they are done.
+    pub fn invalidate_cached_ideas() {
+        if let Some(cache) = crate::request::extension::<IdeaCache>() {
+            cache.invalidate();
+        }
+    }
+
+    /// Hits and misses of the list cache of the current request's database
+    pub fn cache_stats() -> CacheStats {
+        crate::request::extension::<IdeaCache>()
+            .map(|cache| cache.stats())
+            .unwrap_or_default()
+    }
+
+    /// Define tables and indexes. Every statement is idempotent so this runs on each startup.
+    pub async fn define_schema(db: &Surreal<surrealdb::engine::any::Any>) -> surrealdb::Result<()> {
+        db.query(
+            "DEFINE INDEX IF NOT EXISTS idea_slug ON TABLE ideas COLUMNS slug;
+             DEFINE INDEX IF NOT EXISTS idea_previous_slugs ON TABLE ideas COLUMNS previous_slugs;
+             DEFINE INDEX IF NOT EXISTS idea_rank ON TABLE ideas COLUMNS rank;
+             DEFINE INDEX IF NOT EXISTS idea_owner ON TABLE ideas COLUMNS owner;
+             DEFINE INDEX IF NOT EXISTS idea_deleted_at ON TABLE ideas COLUMNS deleted_at;
+             DEFINE INDEX IF NOT EXISTS idea_due_date ON TABLE ideas COLUMNS due_date;
+             DEFINE INDEX IF NOT EXISTS idea_author ON TABLE ideas COLUMNS author;
+             DEFINE INDEX IF NOT EXISTS idea_board ON TABLE ideas COLUMNS board;
+             DEFINE INDEX IF NOT EXISTS user_email ON TABLE users COLUMNS email UNIQUE;
+             DEFINE INDEX IF NOT EXISTS session_token ON TABLE sessions COLUMNS token UNIQUE;
+             DEFINE INDEX IF NOT EXISTS attachment_idea ON TABLE attachments COLUMNS idea;
+             DEFINE EVENT IF NOT EXISTS idea_attachments_cascade ON TABLE ideas
+                 WHEN $event = 'DELETE' THEN (DELETE attachments WHERE idea = $before.id);
+             DEFINE INDEX IF NOT EXISTS idea_event_idea ON TABLE idea_events COLUMNS idea;
+             DEFINE EVENT IF NOT EXISTS idea_history_cascade ON TABLE ideas
+                 WHEN $event = 'DELETE' THEN (DELETE idea_events WHERE idea = $before.id);
+             DEFINE INDEX IF NOT EXISTS idea_revision_idea ON TABLE idea_revisions COLUMNS idea;
+             DEFINE EVENT IF NOT EXISTS idea_revisions_cascade ON TABLE ideas
+                 WHEN $event = 'DELETE' THEN (DELETE idea_revisions WHERE idea = $before.id);
+             DEFINE TABLE IF NOT EXISTS relates_to TYPE RELATION IN ideas OUT ideas;",
+        )
+        .await?
+        .check()?;
+
+        crate::templates::server::seed_builtin_templates(db).await?;
+        crate::boards::server::seed_default_board(db).await?;
+
+        Ok(())
+    }
+
+    /// Build a slug for `title` that doesn't collide with the current or previous slug of any other idea.
+    ///
+    /// `exclude` is the record being renamed, so its own slugs don't count as collisions.
+    pub async fn slug_for_title(
+        db: &Surreal<surrealdb::engine::any::Any>,
+        title: &str,
+        exclude: Option<Thing>,
+    ) -> surrealdb::Result<String> {
+        let base = slugify(title);
+        let taken = taken_slugs(db, &base, exclude).await?;
+        Ok(unique_slug(&base, &taken))
+    }
+
+    /// The current and previous slugs starting with `base` of every idea but `exclude`
+    pub async fn taken_slugs(
+        db: &Surreal<surrealdb::engine::any::Any>,
+        base: &str,
+        exclude: Option<Thing>,
+    ) -> surrealdb::Result<Vec<String>> {
+        #[derive(Deserialize)]
+        struct SlugRow {
+            id: Thing,
+            #[serde(default)]
+            slug: String,
+            #[serde(default)]
+            previous_slugs: Vec<String>,
+        }
+
+        let rows: Vec<SlugRow> = db
+            .query(
+                // Ideas stored before slugs existed have neither field
+                "SELECT id, slug ?? '' AS slug, previous_slugs ?? [] AS previous_slugs FROM ideas \
+                 WHERE string::starts_with(slug ?? '', $base) \
+                 OR array::len(previous_slugs ?? []) > 0",
+            )
+            .bind(("base", base.to_string()))
+            .await?
+            .take(0)?;
+
+        Ok(rows
+            .into_iter()
+            .filter(|row| Some(&row.id) != exclude.as_ref())
+            .flat_map(|row| std::iter::once(row.slug).chain(row.previous_slugs))
+            .filter(|slug| slug.starts_with(base))
+            .collect())
+    }
+
+    /// Load an idea, checking that `user` owns it and may read it, see [`ensure_readable`]
+    pub async fn load_owned_idea(
+        db: &Surreal<surrealdb::engine::any::Any>,
+        id: &str,
+        user: Option<&Thing>,
+    ) -> Result<IdeaRecord, IdeaError> {
+        let id = IdeaId::parse(id)?;
+
+        let idea: Option<IdeaRecord> = db.select((IDEA_TABLE, id.key())).await?;
+        let idea = idea.ok_or_else(|| IdeaError::NotFound(id.to_string()))?;
+
+        ensure_readable(&idea, user)?;
+        Ok(idea)
+    }
+
+    /// Ideas owned by `owner` that aren't in the trash and that they may read (see [`readable_by`]), in manual order.
+    /// Records that can't be read are left out and logged, see [`malformed_ideas`].
+    pub async fn active_ideas(
+        db: &Surreal<surrealdb::engine::any::Any>,
+        owner: Option<&Thing>,
+    ) -> surrealdb::Result<Vec<IdeaRecord>> {
+        let condition = format!("{} AND deleted_at = NONE", readable_by(owner));
+        let (ideas, malformed) = decode_ideas(db, &condition, owner).await?;
+        if !malformed.is_empty() {
+            tracing::warn!(count = malformed.len(), "left out idea records that could not be read");
+        }
+        Ok(ideas)
+    }
+
+    /// Just the id of a record, which reads whatever else the record holds
+    #[derive(Deserialize)]
+    struct RecordId {
+        id: Thing,
+    }
+
+    /// Records of `owner`'s ideas, trashed ones included, that can't be read as ideas, and why
+    pub async fn malformed_ideas(
+        db: &Surreal<surrealdb::engine::any::Any>,
+        owner: Option<&Thing>,
+    ) -> surrealdb::Result<Vec<MalformedRecord>> {
+        Ok(decode_ideas(db, readable_by(owner), owner).await?.1)
+    }
+
+    /// Delete a record of `owner` that [`malformed_ideas`] reported. It can't be read, so it can't go through the
+    /// trash or the repository like a readable idea.
+    pub async fn delete_malformed_idea(
+        db: &Surreal<surrealdb::engine::any::Any>,
+        owner: Option<&Thing>,
+        id: &str,
+    ) -> Result<(), IdeaError> {
+        let id = IdeaId::parse(id)?;
+        let deleted: Vec<RecordId> = db
+            .query("DELETE $id WHERE owner = $owner RETURN BEFORE")
+            .bind(("id", Thing::from(&id)))
+            .bind(("owner", owner.cloned()))
+            .await?
+            .take(0)?;
+        if deleted.is_empty() {
+            return Err(IdeaError::NotFound(id.to_string()));
+        }
+        Ok(())
+    }
+
+    /// The ideas matching `condition` (which may use `$owner`) in manual order, and the records among them that
+    /// don't read as ideas. All of them are decoded in one go first; only when that fails is each record read on
+    /// its own, so a single bad record costs one query per idea instead of the whole list.
+    async fn decode_ideas(
+        db: &Surreal<surrealdb::engine::any::Any>,
+        condition: &str,
+        owner: Option<&Thing>,
+    ) -> surrealdb::Result<(Vec<IdeaRecord>, Vec<MalformedRecord>)> {
+        let all: surrealdb::Result<Vec<IdeaRecord>> = db
+            .query(format!("SELECT * FROM ideas WHERE {condition} ORDER BY rank ASC"))
+            .bind(("owner", owner.cloned()))
+            .await?
+            .take(0);
+        if let Ok(ideas) = all {
+            return Ok((ideas, Vec::new()));
+        }
+
+        // Only the ids, so listing them can't fail on a bad field
+        let ids: Vec<RecordId> = db
+            .query(format!("SELECT id, rank FROM ideas WHERE {condition} ORDER BY rank ASC"))
+            .bind(("owner", owner.cloned()))
+            .await?
+            .take(0)?;
+        let mut ideas = Vec::new();
+        let mut malformed = Vec::new();
+        for RecordId { id } in ids {
+            let read: surrealdb::Result<Option<IdeaRecord>> =
+                db.query("SELECT * FROM ONLY $id").bind(("id", id.clone())).await?.take(0);
+            match read {
+                Ok(Some(idea)) => ideas.push(idea),
+                // Deleted in the meantime
+                Ok(None) => {}
+                Err(e) => malformed.push(MalformedRecord {
+                    id: IdeaId::from(&id).into(),
+                    error: e.to_string(),
+                }),
+            }
+        }
+        Ok((ideas, malformed))
+    }
+
+    /// Ideas owned by `owner` outside the trash carrying all or any of `tags`, in manual order. No tags means no
+    /// filter; tags nobody uses simply match nothing.
+    pub async fn ideas_with_tags(
+        db: &Surreal<surrealdb::engine::any::Any>,
+        owner: Option<&Thing>,
+        tags: &[String],
+        mode: TagMatchMode,
+    ) -> surrealdb::Result<Vec<IdeaRecord>> {
+        if tags.is_empty() {
+            return active_ideas(db, owner).await;
+        }
+        let condition = match mode {
+            TagMatchMode::All => "tags CONTAINSALL $tags",
+            TagMatchMode::Any => "tags CONTAINSANY $tags",
+        };
+        let tags: Vec<String> = tags.iter().map(|tag| tag.trim().to_string()).collect();
+        db.query(format!(
+            "SELECT * FROM ideas WHERE {condition} AND {} AND deleted_at = NONE ORDER BY rank ASC",
+            readable_by(owner)
+        ))
+        .bind(("tags", tags))
+        .bind(("owner", owner.cloned()))
+        .await?
+        .take(0)
+    }
+
+    /// Ideas owned by `owner` outside the trash added by `author`, in manual order. `None` gives the unattributed
+    /// ones, which includes ideas stored before authors existed.
+    pub async fn ideas_by_author(
+        db: &Surreal<surrealdb::engine::any::Any>,
+        owner: Option<&Thing>,
+        author: &AuthorFilter,
+    ) -> surrealdb::Result<Vec<IdeaRecord>> {
+        let condition = match author {
+            AuthorFilter::By(_) => "author = $author",
+            AuthorFilter::Unattributed => "(author = NONE OR author = NULL)",
+        };
+        db.query(format!(
+            "SELECT * FROM ideas WHERE {condition} AND {} AND deleted_at = NONE ORDER BY rank ASC",
+            readable_by(owner)
+        ))
+        .bind(("author", author.name().map(str::to_string)))
+        .bind(("owner", owner.cloned()))
+        .await?
+        .take(0)
+    }
+
+    /// Every author among the ideas of `owner` outside the trash, once each, sorted ignoring case
+    pub async fn idea_authors(
+        db: &Surreal<surrealdb::engine::any::Any>,
+        owner: Option<&Thing>,
+    ) -> surrealdb::Result<Vec<String>> {
+        let mut authors: Vec<String> = db
+            .query(format!(
+                "SELECT VALUE author FROM ideas WHERE type::is::string(author) AND {} AND deleted_at = NONE",
+                readable_by(owner)
+            ))
+            .bind(("owner", owner.cloned()))
+            .await?
+            .take(0)?;
+        authors.sort_by(|a, b| a.to_lowercase().cmp(&b.to_lowercase()).then_with(|| a.cmp(b)));
+        authors.dedup();
+        Ok(authors)
+    }
+
+    /// Up to `limit` ideas owned by `owner` outside the trash in the order they were created, starting just after
+    /// `cursor` or at the oldest, with the cursor of the page after them. That cursor is `None` once the last idea
+    /// is in. With a `board`, only the ideas on it are paged.
+    ///
+    /// Creation times may be stored as datetimes or as strings, and ideas from before they were kept have none, so
+    /// the query reads them through `type::datetime` with the epoch in place of a missing one, as
+    /// [`crate::utils::creation_key`] does. One idea more than asked for is fetched to tell whether another page
+    /// follows.
+    pub async fn ideas_after(
+        db: &Surreal<surrealdb::engine::any::Any>,
+        owner: Option<&Thing>,
+        board: Option<&Thing>,
+        cursor: Option<&str>,
+        limit: usize,
+    ) -> Result<(Vec<IdeaRecord>, Option<String>), IdeaError> {
+        let after = cursor.map(PageCursor::decode).transpose()?;
+        let limit = limit.clamp(1, MAX_PAGE_SIZE);
+        let created = "type::datetime(created_at ?? $epoch)";
+        let boundary = match after {
+            Some(_) => format!(
+                " AND ({created} > type::datetime($at) OR ({created} = type::datetime($at) AND id > $id))"
+            ),
+            None => String::new(),
+        };
+        let on_board = match board {
+            Some(board) if *board == crate::boards::server::default_board() => " AND (board = $board OR board = NONE)",
+            Some(_) => " AND board = $board",
+            None => "",
+        };
+        let (at, id) = match &after {
+            Some(after) => (after.at.to_rfc3339(), Some(Thing::from(&IdeaId::parse(&after.id)?))),
+            None => (String::new(), None),
+        };
+
+        let mut ideas: Vec<IdeaRecord> = db
+            .query(format!(
+                "SELECT *, {created} AS created_order FROM ideas
+                 WHERE {readable} AND deleted_at = NONE{on_board}{boundary}
+                 ORDER BY created_order, id LIMIT $limit",
+                readable = readable_by(owner),
+            ))
+            .bind(("epoch", DateTime::<Utc>::default().to_rfc3339()))
+            .bind(("at", at))
+            .bind(("id", id))
+            .bind(("owner", owner.cloned()))
+            .bind(("board", board.cloned()))
+            .bind(("limit", limit + 1))
+            .await?
+            .take(0)?;
+
+        let next = if ideas.len() > limit {
+            ideas.truncate(limit);
+            let last = ideas.last().and_then(|last| Some((last.created_at, last.id.as_ref()?)));
+            last.map(|(created_at, id)| PageCursor::after(created_at, IdeaId::from(id).to_string()).encode())
+        } else {
+            None
+        };
+        Ok((ideas, next))
+    }
+
+    /// Ideas owned by `owner` outside the trash that are due before `date`, soonest first.
+    ///
+    /// Due dates are stored as ISO strings, so comparing them as strings compares the dates.
+    pub async fn ideas_due_before(
+        db: &Surreal<surrealdb::engine::any::Any>,
+        owner: Option<&Thing>,
+        date: NaiveDate,
+    ) -> surrealdb::Result<Vec<IdeaRecord>> {
+        db.query(format!(
+            "SELECT * FROM ideas WHERE due_date != NONE AND due_date < $date AND {} AND deleted_at = NONE
+             ORDER BY due_date ASC",
+            readable_by(owner)
+        ))
+        .bind(("date", date))
+        .bind(("owner", owner.cloned()))
+        .await?
+        .take(0)
+    }
+
+    /// Ideas owned by `owner` that are in the trash, most recently deleted first
+    pub async fn trashed_ideas(
+        db: &Surreal<surrealdb::engine::any::Any>,
+        owner: Option<&Thing>,
+    ) -> surrealdb::Result<Vec<IdeaRecord>> {
+        let mut ideas: Vec<IdeaRecord> = db
+            .query(format!("SELECT * FROM ideas WHERE {} AND deleted_at != NONE", readable_by(owner)))
+            .bind(("owner", owner.cloned()))
+            .await?
+            .take(0)?;
+
+        ideas.sort_by_key(|idea| std::cmp::Reverse(idea.deleted_at));
+        Ok(ideas)
+    }
+
+    /// Number of ideas `owner` has in the trash
+    pub async fn count_trashed_ideas(
+        db: &Surreal<surrealdb::engine::any::Any>,
+        owner: Option<&Thing>,
+    ) -> surrealdb::Result<usize> {
+        let count: Option<usize> = db
+            .query(format!("SELECT count() FROM ideas WHERE {} AND deleted_at != NONE GROUP ALL", readable_by(owner)))
+            .bind(("owner", owner.cloned()))
+            .await?
+            .take((0, "count"))?;
+
+        Ok(count.unwrap_or(0))
+    }
+
+    /// Permanently delete every idea `owner` has in the trash, returning how many were removed
+    pub async fn empty_trash(
+        db: &Surreal<surrealdb::engine::any::Any>,
+        owner: Option<&Thing>,
+    ) -> Result<usize, IdeaError> {
+        let deleted: Vec<IdeaRecord> = db
+            .query("DELETE ideas WHERE owner = $owner AND deleted_at != NONE RETURN BEFORE")
+            .bind(("owner", owner.cloned()))
+            .await?
+            .take(0)?;
+        Ok(deleted.len())
+    }
+
+    /// Rewrite the tags of every idea, trashed ones included, with [`repair_tags`]. Returns how many ideas changed;
+    /// running it again returns 0.
+    pub async fn normalize_stored_tags(db: &Surreal<surrealdb::engine::any::Any>) -> Result<usize, IdeaError> {
+        #[derive(Deserialize)]
+        struct TagsRow {
+            id: Thing,
+            tags: Vec<String>,
+        }
+
+        let rows: Vec<TagsRow> = db.query("SELECT id, tags FROM ideas").await?.take(0)?;
+        let mut changed = 0;
+        for row in rows {
+            let tags = repair_tags(&row.tags);
+            if tags != row.tags {
+                db.query("UPDATE $id SET tags = $tags")
+                    .bind(("id", row.id))
+                    .bind(("tags", tags))
+                    .await?
+                    .check()?;
+                changed += 1;
+            }
+        }
+        Ok(changed)
+    }
+
+    /// Rank for a new idea, placing it after every existing one
+    pub async fn rank_for_new_idea(db: &Surreal<surrealdb::engine::any::Any>) -> surrealdb::Result<f64> {
+        let max: Option<f64> = db
+            .query("SELECT VALUE rank FROM ideas WHERE rank != NONE ORDER BY rank DESC LIMIT 1")
+            .await?
+            .take(0)?;
+
+        Ok(crate::utils::next_rank(max))
+    }
+
+    /// Find an idea by its current slug, falling back to slugs it used before a rename
+    pub async fn find_by_slug(
+        db: &Surreal<surrealdb::engine::any::Any>,
+        slug: &str,
+    ) -> surrealdb::Result<Option<IdeaRecord>> {
+        let current: Option<IdeaRecord> = db
+            .query("SELECT * FROM ideas WHERE slug = $slug LIMIT 1")
+            .bind(("slug", slug.to_string()))
+            .await?
+            .take(0)?;
+
+        if current.is_some() {
+            return Ok(current);
+        }
+
+        db.query("SELECT * FROM ideas WHERE $slug IN previous_slugs LIMIT 1")
+            .bind(("slug", slug.to_string()))
+            .await?
+            .take(0)
     }
 
     /// Get a test database instance (uses memory-based storage for tests)
     /// This is used in integration tests to avoid polluting the production database
-    pub async fn get_test_db() -> &'static Surreal<surrealdb::engine::local::Db> {
-        use surrealdb::engine::local::Mem;
+    pub async fn get_test_db() -> &'static Surreal<surrealdb::engine::any::Any> {
+        static TEST_DB: OnceCell<Surreal<surrealdb::engine::any::Any>> = OnceCell::const_new();
 
-        static TEST_DB: OnceCell<Surreal<surrealdb::engine::local::Db>> = OnceCell::const_new();
+        TEST_DB.get_or_init(new_test_db).await
+    }
 
-        TEST_DB.get_or_init(|| async {
-            let db = Surreal::new::<Mem>(())
-                .await
-                .expect("Failed to create test database");
+    /// Create a fresh, isolated memory-based database.
+    /// Use this instead of [`get_test_db`] for tests that wipe tables and would disturb tests running in parallel.
+    pub async fn new_test_db() -> Surreal<surrealdb::engine::any::Any> {
+        connect_with(&StorageSettings::memory(), "test_ns", "test_db")
+            .await
+            .expect("Failed to create test database")
+    }
 
-            db.use_ns("test_ns")
-                .use_db("test_db")
-                .await
-                .expect("Failed to select test namespace and database");
+    /// Tables no backup holds and no restore touches: `users` has the password hashes and `sessions` what signs
+    /// each user in
+    pub const ACCOUNT_TABLES: [&str; 2] = ["users", "sessions"];
 
-            db
-        })
-        .await
+    /// Every table of the database but the [`ACCOUNT_TABLES`]
+    async fn backup_tables(db: &Surreal<surrealdb::engine::any::Any>) -> surrealdb::Result<Vec<String>> {
+        #[derive(Deserialize)]
+        struct DbInfo {
+            #[serde(default)]
+            tables: std::collections::BTreeMap<String, String>,
+        }
+
+        let info: Option<DbInfo> = db.query("INFO FOR DB").await?.check()?.take(0)?;
+        Ok(info
+            .map(|info| info.tables.into_keys())
+            .into_iter()
+            .flatten()
+            .filter(|table| !ACCOUNT_TABLES.contains(&table.as_str()))
+            .collect())
+    }
+
+    /// Export the database as a SurrealQL dump, leaving out the [`ACCOUNT_TABLES`]
+    pub async fn backup_to_string(db: &Surreal<surrealdb::engine::any::Any>) -> surrealdb::Result<String> {
+        use futures::StreamExt;
+
+        let tables = backup_tables(db).await?;
+        let mut stream = db.export(()).with_config().tables(tables).await?;
+        let mut bytes = Vec::new();
+        while let Some(chunk) = stream.next().await {
+            bytes.extend(chunk?);
+        }
+
+        Ok(String::from_utf8_lossy(&bytes).into_owned())
+    }
+
+    /// [`backup_to_string`] written to `path`
+    pub async fn backup_to_file(
+        db: &Surreal<surrealdb::engine::any::Any>,
+        path: impl AsRef<std::path::Path>,
+    ) -> surrealdb::Result<()> {
+        let tables = backup_tables(db).await?;
+        db.export(path.as_ref()).with_config().tables(tables).await
+    }
+
+    /// Errors that can happen while restoring a dump
+    #[derive(Debug)]
+    pub enum RestoreError {
+        /// The dump isn't valid SurrealQL; nothing was touched
+        InvalidDump(String),
+        /// The dump failed while running and the previous data was put back
+        RolledBack(String),
+        /// Reading the dump file failed
+        Io(std::io::Error),
+        /// The database failed and the previous state couldn't be restored either
+        Database(surrealdb::Error),
+    }
+
+    impl std::fmt::Display for RestoreError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            match self {
+                RestoreError::InvalidDump(e) => write!(f, "Invalid backup: {}", e),
+                RestoreError::RolledBack(e) => {
+                    write!(f, "Restore failed and previous data was kept: {}", e)
+                }
+                RestoreError::Io(e) => write!(f, "Failed to read backup: {}", e),
+                RestoreError::Database(e) => write!(f, "Database error during restore: {}", e),
+            }
+        }
+    }
+
+    impl std::error::Error for RestoreError {}
+
+    impl From<surrealdb::Error> for RestoreError {
+        fn from(e: surrealdb::Error) -> Self {
+            RestoreError::Database(e)
+        }
+    }
+
+    /// Remove every table of the database but the [`ACCOUNT_TABLES`] (data and definitions), as a dump defines each
+    /// table it exports. The tables are read from the database, so ones added later are wiped without being listed
+    /// here.
+    async fn wipe_tables(db: &Surreal<surrealdb::engine::any::Any>) -> surrealdb::Result<()> {
+        for table in backup_tables(db).await? {
+            db.query(format!("REMOVE TABLE IF EXISTS `{}`", table))
+                .await?
+                .check()?;
+        }
+        Ok(())
+    }
+
+    /// `dump` with every field definition allowed to replace an existing one. An export defines a relation table and
+    /// then the `in` and `out` fields that defining it already created, which would otherwise fail on import.
+    fn importable(dump: &str) -> String {
+        dump.lines()
+            .map(|line| match line.strip_prefix("DEFINE FIELD ") {
+                Some(rest) if !rest.starts_with("OVERWRITE ") => format!("DEFINE FIELD OVERWRITE {}", rest),
+                _ => line.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Restore a SurrealQL dump produced by [`backup_to_string`].
+    ///
+    /// The dump is parsed before anything is touched, and a snapshot of the current data is taken first so a dump
+    /// that fails halfway through never leaves the ideas table half-wiped.
+    pub async fn restore_from_dump(
+        db: &Surreal<surrealdb::engine::any::Any>,
+        dump: &str,
+        wipe_first: bool,
+    ) -> Result<(), RestoreError> {
+        // Reject malformed dumps up front
+        surrealdb::sql::parse(dump).map_err(|e| RestoreError::InvalidDump(e.to_string()))?;
+
+        let snapshot = backup_to_string(db).await?;
+
+        if wipe_first {
+            wipe_tables(db).await?;
+        }
+
+        let applied = match db.query(importable(dump)).await {
+            Ok(response) => response.check().map(|_| ()),
+            Err(e) => Err(e),
+        };
+
+        match applied {
+            Ok(()) => {
+                define_schema(db).await?;
+                Ok(())
+            }
+            Err(e) => {
+                // Put the previous data back
+                wipe_tables(db).await?;
+                db.query(importable(&snapshot)).await?.check()?;
+                define_schema(db).await?;
+                Err(RestoreError::RolledBack(e.to_string()))
+            }
+        }
+    }
+
+    /// Restore a SurrealQL dump file written by [`backup_to_file`]
+    pub async fn restore_from_file(
+        db: &Surreal<surrealdb::engine::any::Any>,
+        path: impl AsRef<std::path::Path>,
+        wipe_first: bool,
+    ) -> Result<(), RestoreError> {
+        let dump = tokio::fs::read_to_string(path).await.map_err(RestoreError::Io)?;
+        restore_from_dump(db, &dump, wipe_first).await
     }
 }
 
@@ -100,6 +1190,17 @@ mod tests {
     /// Unit tests for the Idea data model
     /// These tests demonstrate TDD for data structures and serialization
 
+    #[test]
+    fn test_delete_impact_summary() {
+        assert_eq!(DeleteImpact::default().summary(), None);
+        let impact = DeleteImpact { attachments: 4, links: 1, revisions: 0 };
+        assert_eq!(impact.summary().unwrap(), "This will also remove 4 attachments and 1 link");
+        let impact = DeleteImpact { attachments: 1, links: 2, revisions: 3 };
+        assert_eq!(impact.summary().unwrap(), "This will also remove 1 attachment, 2 links and 3 revisions");
+        let impact = DeleteImpact { revisions: 1, ..Default::default() };
+        assert_eq!(impact.summary().unwrap(), "This will also remove 1 revision");
+    }
+
     #[test]
     fn test_idea_creation() {
         let idea = Idea {
@@ -109,6 +1210,7 @@ mod tests {
             tags: vec!["test".to_string(), "rust".to_string()],
             what_must_be_true: vec![],
             development_notes: String::new(),
+            ..Default::default()
         };
 
         assert_eq!(idea.title, "Test Idea");
@@ -128,6 +1230,7 @@ mod tests {
                 "Must be documented".to_string(),
             ],
             development_notes: "These are my notes".to_string(),
+            ..Default::default()
         };
 
         assert_eq!(idea.what_must_be_true.len(), 2);
@@ -144,6 +1247,7 @@ mod tests {
             tags: vec!["json".to_string()],
             what_must_be_true: vec!["Must serialize".to_string()],
             development_notes: "Test notes".to_string(),
+            ..Default::default()
         };
 
         // Test serialization
@@ -170,6 +1274,7 @@ mod tests {
         let idea: Idea = serde_json::from_str(json).expect("Failed to deserialize");
         assert_eq!(idea.what_must_be_true.len(), 0);
         assert_eq!(idea.development_notes, "");
+        assert_eq!(idea.author, None);
     }
 
     #[test]
@@ -181,6 +1286,7 @@ mod tests {
             tags: vec![],
             what_must_be_true: vec![],
             development_notes: String::new(),
+            ..Default::default()
         };
 
         let idea2 = Idea {
@@ -190,6 +1296,7 @@ mod tests {
             tags: vec![],
             what_must_be_true: vec![],
             development_notes: String::new(),
+            ..Default::default()
         };
 
         assert_eq!(idea1, idea2);
@@ -208,6 +1315,7 @@ mod tests {
                 tags: vec!["server".to_string()],
                 what_must_be_true: vec!["Must work on server".to_string()],
                 development_notes: "Server notes".to_string(),
+                ..Default::default()
             };
 
             assert_eq!(record.title, "Test Record");
@@ -225,6 +1333,7 @@ mod tests {
                 tags: vec!["convert".to_string()],
                 what_must_be_true: vec!["Must convert".to_string()],
                 development_notes: "Conversion notes".to_string(),
+                ..Default::default()
             };
 
             let idea: Idea = record.into();
@@ -234,4 +1343,128 @@ mod tests {
             assert_eq!(idea.what_must_be_true[0], "Must convert");
         }
     }
+
+    #[test]
+    fn test_summary_leaves_out_long_fields() {
+        let idea = Idea {
+            id: Some("ideas:long".to_string()),
+            title: "Long".to_string(),
+            description: "🚀".repeat(SUMMARY_DESCRIPTION_CHARS + 10),
+            tags: vec!["big".to_string()],
+            what_must_be_true: vec!["one".to_string(), "two".to_string()],
+            development_notes: "ü".repeat(5000),
+            slug: "long".to_string(),
+            ..Default::default()
+        };
+
+        let summary = IdeaSummary::from(idea);
+        assert_eq!(summary.short_description, format!("{}…", "🚀".repeat(SUMMARY_DESCRIPTION_CHARS)));
+        assert_eq!(summary.what_must_be_true_count, 2);
+        assert_eq!(summary.notes_length, 5000);
+
+        let json = serde_json::to_value(&summary).unwrap();
+        let mut keys: Vec<&String> = json.as_object().unwrap().keys().collect();
+        keys.sort();
+        assert_eq!(
+            keys,
+            vec![
+                "author",
+                "created_at",
+                "due_date",
+                "icon",
+                "id",
+                "notes_length",
+                "pinned",
+                "short_description",
+                "slug",
+                "status",
+                "tags",
+                "title",
+                "what_must_be_true_count"
+            ]
+        );
+        assert!(json.to_string().len() < 1500);
+    }
+
+    #[test]
+    fn test_summary_keeps_short_descriptions() {
+        let idea = Idea {
+            description: "Short <em>and</em> sweet 🎉".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(IdeaSummary::from(idea).short_description, "Short <em>and</em> sweet 🎉");
+    }
+
+    #[test]
+    fn test_tag_match_modes() {
+        let tags = |list: &[&str]| list.iter().map(|tag| tag.to_string()).collect::<Vec<_>>();
+        let idea = tags(&["garden", " kitchen "]);
+
+        assert!(TagMatchMode::All.matches(&idea, &tags(&["garden", "kitchen"])));
+        assert!(!TagMatchMode::All.matches(&idea, &tags(&["garden", "money"])));
+        assert!(TagMatchMode::Any.matches(&idea, &tags(&["garden", "money"])));
+        assert!(!TagMatchMode::Any.matches(&idea, &tags(&["money"])));
+        assert!(TagMatchMode::All.matches(&idea, &[]) && TagMatchMode::Any.matches(&idea, &[]));
+
+        assert_eq!(serde_json::to_string(&TagMatchMode::Any).unwrap(), "\"any\"");
+        assert_eq!(TagMatchMode::parse("all"), Some(TagMatchMode::All));
+        assert_eq!(TagMatchMode::parse("both"), None);
+    }
+
+    #[test]
+    fn test_author_filter() {
+        assert_eq!(tidy_author(Some("  Sam ".to_string())), Some("Sam".to_string()));
+        assert_eq!(tidy_author(Some(" \t".to_string())), None);
+        assert_eq!(tidy_author(None), None);
+
+        let sam = AuthorFilter::By("Sam".to_string());
+        assert!(sam.matches(Some(" Sam")));
+        assert!(!sam.matches(Some("sam")));
+        assert!(!sam.matches(None));
+
+        // Missing and blank authors share one bucket
+        assert!(AuthorFilter::Unattributed.matches(None));
+        assert!(AuthorFilter::Unattributed.matches(Some("  ")));
+        assert!(!AuthorFilter::Unattributed.matches(Some("Sam")));
+        assert_eq!(AuthorFilter::for_author(Some("")), AuthorFilter::Unattributed);
+        assert_eq!(sam.name(), Some("Sam"));
+    }
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn test_tidy_tags() {
+        assert_eq!(
+            tidy_tags(strings(&["Rust ", "rust", " ", "api", "RUST", "Api", "db"])),
+            strings(&["api", "db", "Rust"])
+        );
+        // The order typed doesn't matter
+        assert_eq!(tidy_tags(strings(&["b", "a", "C"])), tidy_tags(strings(&["C", "b", "a"])));
+        assert!(tidy_tags(strings(&["", "  "])).is_empty());
+    }
+
+    #[test]
+    fn test_normalize_tags_refuses_long_tags() {
+        let longest = "x".repeat(MAX_TAG_CHARS);
+        assert_eq!(normalize_tags(vec![longest.clone()]), Ok(vec![longest.clone()]));
+        // Surrounding whitespace doesn't count
+        assert!(normalize_tags(vec![format!(" {} ", longest)]).is_ok());
+        assert!(matches!(
+            normalize_tags(vec![format!("{}x", longest)]),
+            Err(IdeaError::Validation(_))
+        ));
+        // Characters are counted, not bytes
+        assert!(normalize_tags(vec!["é".repeat(MAX_TAG_CHARS)]).is_ok());
+    }
+
+    #[test]
+    fn test_repair_tags() {
+        let long = "y".repeat(MAX_TAG_CHARS + 5);
+        let repaired = repair_tags(&[long, "ux".to_string(), " UX".to_string()]);
+        assert_eq!(repaired, vec!["ux".to_string(), "y".repeat(MAX_TAG_CHARS)]);
+        assert!(normalize_tags(repaired.clone()).is_ok());
+        assert_eq!(repair_tags(&repaired), repaired);
+    }
 }
@@ -0,0 +1,180 @@
+//! Short-lived copies of idea lists, so page views that follow each other don't read every idea again.
+//!
+//! The list, the summaries, the pages and the counts all come from [`super::IdeaRepository::list`] and filter in
+//! memory, so one entry per owner covers them whatever the filter. Entries expire after [`LIST_CACHE_TTL`], but
+//! that is only a backstop: every write clears the cache once it is done. A list read that was already running when
+//! a write cleared it isn't kept, because it may have seen the data from before the write.
+
+use super::{CacheStats, IdeaRecord};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+use surrealdb::sql::Thing;
+
+/// How long a cached list is served before it is read again
+pub const LIST_CACHE_TTL: Duration = Duration::from_secs(30);
+
+struct Entry {
+    ideas: Vec<IdeaRecord>,
+    stored_at: Instant,
+}
+
+#[derive(Default)]
+struct Lists {
+    /// Bumped by every invalidation, to recognize reads that started before it
+    generation: u64,
+    by_owner: HashMap<Option<String>, Entry>,
+}
+
+/// Cached idea lists of one database, by owner. Clones share the same entries and counters.
+#[derive(Clone)]
+pub struct IdeaCache {
+    lists: Arc<RwLock<Lists>>,
+    hits: Arc<AtomicU64>,
+    misses: Arc<AtomicU64>,
+    ttl: Duration,
+}
+
+impl std::fmt::Debug for IdeaCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IdeaCache").field("stats", &self.stats()).finish()
+    }
+}
+
+impl Default for IdeaCache {
+    fn default() -> Self {
+        Self::with_ttl(LIST_CACHE_TTL)
+    }
+}
+
+impl IdeaCache {
+    /// A cache whose entries expire after `ttl`
+    pub fn with_ttl(ttl: Duration) -> Self {
+        Self {
+            lists: Arc::default(),
+            hits: Arc::default(),
+            misses: Arc::default(),
+            ttl,
+        }
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, Lists> {
+        self.lists.read().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, Lists> {
+        self.lists.write().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// The cached list of `owner`, counting a hit. On a miss, the generation to hand to [`Self::store`] once the
+    /// list was read.
+    pub fn lookup(&self, owner: Option<&Thing>) -> Result<Vec<IdeaRecord>, u64> {
+        let lists = self.read();
+        match lists.by_owner.get(&owner_key(owner)) {
+            Some(entry) if entry.stored_at.elapsed() < self.ttl => {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                Ok(entry.ideas.clone())
+            }
+            _ => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                Err(lists.generation)
+            }
+        }
+    }
+
+    /// Keep the list of `owner` read after [`Self::lookup`] returned `generation`, unless the cache was invalidated
+    /// since
+    pub fn store(&self, owner: Option<&Thing>, generation: u64, ideas: Vec<IdeaRecord>) {
+        let mut lists = self.write();
+        if lists.generation == generation {
+            lists.by_owner.insert(
+                owner_key(owner),
+                Entry {
+                    ideas,
+                    stored_at: Instant::now(),
+                },
+            );
+        }
+    }
+
+    /// Forget every cached list. Call it after a write, not before, so no read can cache the old data in between.
+    pub fn invalidate(&self) {
+        let mut lists = self.write();
+        lists.generation += 1;
+        lists.by_owner.clear();
+    }
+
+    /// Hits and misses since the server started, and the lists cached now
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: self.read().by_owner.len(),
+        }
+    }
+}
+
+fn owner_key(owner: Option<&Thing>) -> Option<String> {
+    owner.map(|owner| owner.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn idea(title: &str) -> IdeaRecord {
+        IdeaRecord {
+            title: title.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn titles(ideas: &[IdeaRecord]) -> Vec<&str> {
+        ideas.iter().map(|idea| idea.title.as_str()).collect()
+    }
+
+    #[test]
+    fn test_stored_list_is_served_until_invalidated() {
+        let cache = IdeaCache::default();
+        let generation = cache.lookup(None).unwrap_err();
+        cache.store(None, generation, vec![idea("First")]);
+        assert_eq!(titles(&cache.lookup(None).unwrap()), vec!["First"]);
+        assert_eq!(cache.stats(), CacheStats { hits: 1, misses: 1, entries: 1 });
+
+        cache.invalidate();
+        assert!(cache.lookup(None).is_err());
+        assert_eq!(cache.stats(), CacheStats { hits: 1, misses: 2, entries: 0 });
+    }
+
+    #[test]
+    fn test_owners_are_cached_apart() {
+        let cache = IdeaCache::default();
+        let alice = Thing::from(("users", "alice"));
+        let generation = cache.lookup(Some(&alice)).unwrap_err();
+        cache.store(Some(&alice), generation, vec![idea("Alice's")]);
+
+        assert!(cache.lookup(None).is_err());
+        assert_eq!(titles(&cache.lookup(Some(&alice)).unwrap()), vec!["Alice's"]);
+    }
+
+    #[test]
+    fn test_read_overtaken_by_a_write_is_not_kept() {
+        let cache = IdeaCache::default();
+        let generation = cache.lookup(None).unwrap_err();
+        // A write lands while the list is being read
+        cache.invalidate();
+        cache.store(None, generation, vec![idea("Stale")]);
+
+        assert!(cache.lookup(None).is_err());
+    }
+
+    #[test]
+    fn test_entries_expire() {
+        let cache = IdeaCache::with_ttl(Duration::ZERO);
+        let generation = cache.lookup(None).unwrap_err();
+        cache.store(None, generation, vec![idea("Short-lived")]);
+
+        assert!(cache.lookup(None).is_err());
+    }
+}
@@ -0,0 +1,169 @@
+//! Ids of ideas as the client sees them.
+//!
+//! An idea id is always the string `ideas:<key>`: the table name, a colon and the record key, with no escaping
+//! (no `⟨⟩` brackets, whatever the database library prints for a record id). This is what [`super::Idea::id`]
+//! holds, what goes into URLs like `/idea/ideas:abc123` and what server functions take. [`IdeaId::parse`] also
+//! reads the other shapes people paste and turns them into this one. Only the conversions at
+//! the bottom of this file know the database's record id type, so a library upgrade that changes it stops there.
+
+use crate::error::IdeaError;
+use crate::utils::percent_decode;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Table every idea lives in
+pub const IDEA_TABLE: &str = "ideas";
+
+/// A checked `ideas:<key>` id. Serializes as the plain string.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct IdeaId(String);
+
+impl IdeaId {
+    /// Read an idea id in any of the shapes that reach the server: `ideas:abc`, a bare key `abc`, the escaped
+    /// `ideas:⟨abc⟩` the database library prints, or any of those percent-encoded, as a pasted link would be. The
+    /// result is always the plain `ideas:<key>`. Ids of another table, and keys that are empty or hold a colon, are
+    /// refused; whether the idea exists is for the lookup to tell.
+    pub fn parse(id: &str) -> Result<Self, IdeaError> {
+        let invalid = || IdeaError::InvalidId(id.to_string());
+
+        let mut text = id.trim().to_string();
+        // A link can be encoded twice on its way here, once by us and once by the router
+        for _ in 0..MAX_DECODES {
+            if !text.contains('%') {
+                break;
+            }
+            text = percent_decode(&text);
+        }
+        let key = match text.split_once(':') {
+            Some((table, key)) if table.trim() == IDEA_TABLE => key,
+            Some(_) => return Err(invalid()),
+            None => text.as_str(),
+        };
+        let key = unescape_key(key.trim());
+        if key.is_empty() || key.contains(':') {
+            return Err(invalid());
+        }
+        Ok(Self(format!("{}:{}", IDEA_TABLE, key)))
+    }
+
+    /// The record key, without the table
+    pub fn key(&self) -> &str {
+        &self.0[IDEA_TABLE.len() + 1..]
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+/// Most rounds of percent-decoding [`IdeaId::parse`] applies
+const MAX_DECODES: usize = 3;
+
+/// `key` without the brackets or backticks the database library puts around keys it escapes
+fn unescape_key(key: &str) -> &str {
+    [('⟨', '⟩'), ('<', '>'), ('`', '`')]
+        .iter()
+        .find_map(|(open, close)| key.strip_prefix(*open)?.strip_suffix(*close))
+        .unwrap_or(key)
+}
+
+impl fmt::Display for IdeaId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl FromStr for IdeaId {
+    type Err = IdeaError;
+
+    fn from_str(id: &str) -> Result<Self, Self::Err> {
+        Self::parse(id)
+    }
+}
+
+impl TryFrom<String> for IdeaId {
+    type Error = IdeaError;
+
+    fn try_from(id: String) -> Result<Self, Self::Error> {
+        Self::parse(&id)
+    }
+}
+
+impl From<IdeaId> for String {
+    fn from(id: IdeaId) -> Self {
+        id.0
+    }
+}
+
+#[cfg(feature = "server")]
+impl From<&surrealdb::sql::Thing> for IdeaId {
+    fn from(thing: &surrealdb::sql::Thing) -> Self {
+        // `to_raw` leaves the key unescaped, unlike `Display`
+        Self(format!("{}:{}", thing.tb, thing.id.to_raw()))
+    }
+}
+
+#[cfg(feature = "server")]
+impl From<&IdeaId> for surrealdb::sql::Thing {
+    fn from(id: &IdeaId) -> Self {
+        surrealdb::sql::Thing::from((IDEA_TABLE, id.key()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let id = IdeaId::parse("ideas:abc123").unwrap();
+        assert_eq!(id.key(), "abc123");
+        assert_eq!(id.to_string(), "ideas:abc123");
+
+        for invalid in ["ideas:", "users:abc123", "ideas:a:b", "", "  ", "ideas:⟨⟩", "users%3Aabc123"] {
+            assert_eq!(IdeaId::parse(invalid), Err(IdeaError::InvalidId(invalid.to_string())));
+        }
+    }
+
+    #[test]
+    fn test_parse_reads_pasted_shapes() {
+        for pasted in [
+            "abc123",
+            "ideas:abc123",
+            "ideas:⟨abc123⟩",
+            "⟨abc123⟩",
+            "ideas:<abc123>",
+            "ideas:`abc123`",
+            " ideas:abc123 ",
+            "ideas%3Aabc123",
+            "ideas%3A%E2%9F%A8abc123%E2%9F%A9",
+            "ideas%253Aabc123",
+        ] {
+            assert_eq!(IdeaId::parse(pasted).map(String::from), Ok("ideas:abc123".to_string()), "{}", pasted);
+        }
+        // Keys the library escapes keep their characters
+        assert_eq!(IdeaId::parse("ideas:⟨with-dash⟩").unwrap().key(), "with-dash");
+    }
+
+    #[test]
+    fn test_serializes_as_plain_string() {
+        let id: IdeaId = "ideas:abc123".parse().unwrap();
+        assert_eq!(serde_json::to_string(&id).unwrap(), "\"ideas:abc123\"");
+        assert_eq!(serde_json::from_str::<IdeaId>("\"ideas:abc123\"").unwrap(), id);
+        assert!(serde_json::from_str::<IdeaId>("\"users:nope\"").is_err());
+    }
+
+    #[cfg(feature = "server")]
+    #[test]
+    fn test_thing_round_trip_without_escaping() {
+        use surrealdb::sql::Thing;
+
+        // A key the library escapes when displaying it
+        let thing = Thing::from(("ideas", "with-dash"));
+        let id = IdeaId::from(&thing);
+        assert_eq!(id.as_str(), "ideas:with-dash");
+        assert_eq!(Thing::from(&id), thing);
+    }
+}
@@ -0,0 +1,2511 @@
+//! Storage-agnostic access to ideas.
+//!
+//! Server functions talk to an [`IdeaRepository`] instead of the Surreal client, so the rules around it (ownership,
+//! slugs, the trash) live in the generic functions at the bottom of this file and can be exercised against
+//! [`MockIdeaRepository`] without starting a database. [`SurrealIdeaRepository`] is the real implementation; both
+//! report failures as [`IdeaError`] so callers can't tell them apart.
+
+use super::cache::IdeaCache;
+use super::server::{active_ideas, find_by_slug, rank_for_new_idea, slug_for_title, taken_slugs};
+use super::visibility::{ensure_readable, is_readable, readable_by};
+use super::transaction::{with_transaction, Transaction};
+use super::{
+    check_tags, tidy_author, tidy_tags, DeleteImpact, Idea, IdeaId, IdeaRecord, IdeaStatus, NoteSection, Visibility,
+    IDEA_TABLE,
+};
+use crate::boards::server::in_board;
+use crate::error::IdeaError;
+use crate::history::server::IdeaEventRecord;
+use crate::history::{summarize_edit, IdeaEventKind};
+use crate::revisions::server::IdeaRevisionRecord;
+use crate::revisions::MAX_REVISIONS;
+use crate::utils::{
+    apply_tag_change, is_emoji_icon, is_possible_duplicate, next_rank, normalize_newlines, normalize_statements,
+    normalize_title, sanitize_text, slugify, tidy_icon, title_similarity, unique_slug, TagChange,
+};
+use chrono::{NaiveDate, Utc};
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use surrealdb::{sql::Thing, Response, Surreal};
+
+/// Largest number of ideas accepted by [`submit_ideas`] in one call
+pub const MAX_BATCH_IDEAS: usize = 1000;
+/// Most look-alikes returned by [`similar_ideas`]
+pub const MAX_POSSIBLE_DUPLICATES: usize = 5;
+/// Tries an append makes when appends to the same idea keep conflicting with each other
+const APPEND_ATTEMPTS: usize = 5;
+
+/// Persistence operations for ideas
+pub trait IdeaRepository {
+    /// Store a new idea, returning it with its id
+    fn create(&self, idea: IdeaRecord) -> impl Future<Output = Result<IdeaRecord, IdeaError>> + Send;
+
+    /// Store several ideas at once. Either all of them are created or, if any is invalid, none is.
+    fn create_many(
+        &self,
+        ideas: Vec<IdeaRecord>,
+    ) -> impl Future<Output = Result<Vec<IdeaRecord>, IdeaError>> + Send;
+
+    /// Load an idea by id, in trash or not
+    fn get(&self, id: &str) -> impl Future<Output = Result<IdeaRecord, IdeaError>> + Send;
+
+    /// Ideas of `owner` that aren't in the trash, in manual order
+    fn list(&self, owner: Option<&Thing>) -> impl Future<Output = Result<Vec<IdeaRecord>, IdeaError>> + Send;
+
+    /// Replace every field of an idea except its id
+    fn update(&self, id: &str, idea: IdeaRecord) -> impl Future<Output = Result<IdeaRecord, IdeaError>> + Send;
+
+    /// Overwrite only the fields present in `patch`
+    fn merge(
+        &self,
+        id: &str,
+        patch: serde_json::Value,
+    ) -> impl Future<Output = Result<IdeaRecord, IdeaError>> + Send;
+
+    /// Add `statement` to the end of an idea's "what must be true" list in a single write, so appends and edits
+    /// landing at the same time can't drop it
+    fn append_statement(
+        &self,
+        id: &str,
+        statement: String,
+    ) -> impl Future<Output = Result<IdeaRecord, IdeaError>> + Send;
+
+    /// Set an idea's tags to `tags` if they are still `expected`, in a single write, returning `None` when they
+    /// changed since they were read
+    fn replace_tags(
+        &self,
+        id: &str,
+        expected: Vec<String>,
+        tags: Vec<String>,
+    ) -> impl Future<Output = Result<Option<IdeaRecord>, IdeaError>> + Send;
+
+    /// Permanently delete an idea with its attachments, links, revisions and history, all or nothing, returning
+    /// what went with it
+    fn delete(&self, id: &str) -> impl Future<Output = Result<DeleteImpact, IdeaError>> + Send;
+
+    /// What [`Self::delete`] would remove along with the idea
+    fn delete_impact(&self, id: &str) -> impl Future<Output = Result<DeleteImpact, IdeaError>> + Send;
+
+    /// Ideas of `owner` outside the trash whose title, description, notes or tags contain `query`, ignoring case
+    fn search(
+        &self,
+        owner: Option<&Thing>,
+        query: &str,
+    ) -> impl Future<Output = Result<Vec<IdeaRecord>, IdeaError>> + Send;
+
+    /// A slug for `title` that no other idea uses now or used before. `exclude` is the idea being renamed.
+    fn slug_for(
+        &self,
+        title: &str,
+        exclude: Option<&Thing>,
+    ) -> impl Future<Output = Result<String, IdeaError>> + Send;
+
+    /// The current and previous slugs of every idea that start with `base`, which a slug built on it must avoid
+    fn taken_slugs(&self, base: &str) -> impl Future<Output = Result<Vec<String>, IdeaError>> + Send;
+
+    /// Rank that places a new idea after every existing one
+    fn next_rank(&self) -> impl Future<Output = Result<f64, IdeaError>> + Send;
+
+    /// Find an idea by its current or a previous slug
+    fn find_by_slug(&self, slug: &str) -> impl Future<Output = Result<Option<IdeaRecord>, IdeaError>> + Send;
+
+    /// Append an entry to an idea's history
+    fn log_event(&self, event: IdeaEventRecord) -> impl Future<Output = Result<(), IdeaError>> + Send;
+
+    /// History of an idea, newest first
+    fn history(&self, idea: &Thing) -> impl Future<Output = Result<Vec<IdeaEventRecord>, IdeaError>> + Send;
+
+    /// Store a snapshot of an idea's notes and statements, pruning all but the newest [`MAX_REVISIONS`] of that
+    /// idea. A snapshot that already has an id overwrites the stored one.
+    fn save_revision(&self, revision: IdeaRevisionRecord) -> impl Future<Output = Result<(), IdeaError>> + Send;
+
+    /// Snapshots of an idea, newest first
+    fn revisions(&self, idea: &Thing) -> impl Future<Output = Result<Vec<IdeaRevisionRecord>, IdeaError>> + Send;
+
+    /// Replace `primary` with `merged`, move the attachments of `secondary` over to it, delete `secondary` and log
+    /// `event`, all or nothing
+    fn absorb(
+        &self,
+        primary: &str,
+        merged: IdeaRecord,
+        secondary: &str,
+        event: IdeaEventRecord,
+    ) -> impl Future<Output = Result<IdeaRecord, IdeaError>> + Send;
+}
+
+/// Rules every stored idea must satisfy, shared by both implementations
+fn check_record(idea: &IdeaRecord) -> Result<(), IdeaError> {
+    if idea.title.trim().is_empty() {
+        return Err(IdeaError::Validation("Title is required".to_string()));
+    }
+    if idea.icon.as_deref().is_some_and(|icon| !is_emoji_icon(icon)) {
+        return Err(IdeaError::Validation("The icon must be a single emoji".to_string()));
+    }
+    check_tags(&idea.tags)
+}
+
+/// `idea` with [`sanitize_text`] applied to every free-text field and its whitespace normalized: the title trimmed
+/// with its inner runs of whitespace collapsed, tags and statements trimmed (dropping blank ones), and the line
+/// endings of the description and notes turned into `\n`.
+fn sanitize_record(idea: IdeaRecord) -> IdeaRecord {
+    IdeaRecord {
+        title: clean_title(&idea.title),
+        description: clean_text(&idea.description),
+        tags: sanitize_tags(&idea.tags),
+        what_must_be_true: clean_statements(&idea.what_must_be_true),
+        development_notes: clean_text(&idea.development_notes),
+        sections: clean_sections(&idea.sections),
+        author: tidy_author(idea.author.as_deref().map(sanitize_text)),
+        icon: tidy_icon(idea.icon),
+        custom_fields: sanitize_custom_fields(idea.custom_fields),
+        ..idea
+    }
+}
+
+fn clean_title(title: &str) -> String {
+    normalize_title(&sanitize_text(title))
+}
+
+/// A description or notes. Line endings go first, as [`sanitize_text`] would drop a lone `\r` instead.
+fn clean_text(text: &str) -> String {
+    sanitize_text(&normalize_newlines(text))
+}
+
+/// Sections of structured notes, each heading cleaned like a title and each body like the notes
+fn clean_sections(sections: &[NoteSection]) -> Vec<NoteSection> {
+    sections
+        .iter()
+        .map(|section| NoteSection {
+            heading: clean_title(&section.heading),
+            body: clean_text(&section.body),
+        })
+        .collect()
+}
+
+fn clean_statements(statements: &[String]) -> Vec<String> {
+    normalize_statements(&statements.iter().map(|statement| sanitize_text(statement)).collect::<Vec<_>>())
+}
+
+/// Custom field values with [`sanitize_text`] applied to the strings among them
+fn sanitize_custom_fields(values: HashMap<String, serde_json::Value>) -> HashMap<String, serde_json::Value> {
+    values
+        .into_iter()
+        .map(|(name, value)| match value {
+            serde_json::Value::String(text) => (name, serde_json::Value::String(sanitize_text(&text))),
+            other => (name, other),
+        })
+        .collect()
+}
+
+fn sanitize_tags(tags: &[String]) -> Vec<String> {
+    tags.iter()
+        .map(|tag| sanitize_text(tag).trim().to_string())
+        .filter(|tag| !tag.is_empty())
+        .collect()
+}
+
+/// The free-text fields of a merge patch cleaned like [`sanitize_record`] does; other fields pass as they are
+fn sanitize_patch(mut patch: serde_json::Value) -> serde_json::Value {
+    if let serde_json::Value::Object(fields) = &mut patch {
+        for (key, field) in fields.iter_mut() {
+            match (key.as_str(), field) {
+                ("title", serde_json::Value::String(text)) => *text = clean_title(text),
+                ("description" | "development_notes", serde_json::Value::String(text)) => *text = clean_text(text),
+                ("author", serde_json::Value::String(text)) => *text = sanitize_text(text),
+                ("tags" | "what_must_be_true", serde_json::Value::Array(items)) => {
+                    for item in items.iter_mut() {
+                        if let serde_json::Value::String(text) = item {
+                            *text = sanitize_text(text).trim().to_string();
+                        }
+                    }
+                    items.retain(|item| item.as_str().is_none_or(|text| !text.is_empty()));
+                }
+                ("sections", serde_json::Value::Array(sections)) => {
+                    for section in sections.iter_mut() {
+                        if let Some(serde_json::Value::String(text)) = section.get_mut("heading") {
+                            *text = clean_title(text);
+                        }
+                        if let Some(serde_json::Value::String(text)) = section.get_mut("body") {
+                            *text = clean_text(text);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    patch
+}
+
+/// `idea` with its creation time, unless it already has one
+fn stamped(idea: IdeaRecord) -> IdeaRecord {
+    IdeaRecord {
+        created_at: idea.created_at.or_else(|| Some(Utc::now())),
+        ..idea
+    }
+}
+
+/// The ideas among `ideas` on `board`, or all of them for a repository that isn't scoped to one
+fn on_board(mut ideas: Vec<IdeaRecord>, board: Option<&Thing>) -> Vec<IdeaRecord> {
+    if let Some(board) = board {
+        ideas.retain(|idea| in_board(idea, board));
+    }
+    ideas
+}
+
+/// `idea` put on `board`, unless it names a board of its own
+fn placed(idea: IdeaRecord, board: Option<&Thing>) -> IdeaRecord {
+    IdeaRecord {
+        board: idea.board.or_else(|| board.cloned()),
+        ..idea
+    }
+}
+
+/// [`sanitize_record`] followed by [`check_record`]: what every write stores
+fn clean_record(idea: IdeaRecord) -> Result<IdeaRecord, IdeaError> {
+    let idea = sanitize_record(idea);
+    check_record(&idea)?;
+    Ok(idea)
+}
+
+/// [`clean_record`] for a whole batch, reporting every invalid record with its position in the batch
+fn clean_batch(ideas: Vec<IdeaRecord>) -> Result<Vec<IdeaRecord>, IdeaError> {
+    let ideas: Vec<IdeaRecord> = ideas.into_iter().map(sanitize_record).collect();
+    let problems: Vec<String> = ideas
+        .iter()
+        .enumerate()
+        .filter_map(|(index, idea)| check_record(idea).err().map(|e| format!("idea {}: {}", index, e)))
+        .collect();
+
+    if problems.is_empty() {
+        Ok(ideas)
+    } else {
+        Err(IdeaError::Validation(problems.join("; ")))
+    }
+}
+
+/// [`IdeaRepository`] backed by SurrealDB
+#[derive(Debug, Clone)]
+pub struct SurrealIdeaRepository {
+    db: Surreal<surrealdb::engine::any::Any>,
+    cache: Option<IdeaCache>,
+    board: Option<Thing>,
+}
+
+impl SurrealIdeaRepository {
+    pub fn new(db: Surreal<surrealdb::engine::any::Any>) -> Self {
+        Self {
+            db,
+            cache: None,
+            board: None,
+        }
+    }
+
+    /// Only list and search the ideas on `board`, and create new ideas there. Without it every board is seen.
+    pub fn in_board(self, board: Thing) -> Self {
+        Self {
+            board: Some(board),
+            ..self
+        }
+    }
+
+    /// Serve [`IdeaRepository::list`] from `cache`, which every write through this repository clears
+    pub fn with_cache(self, cache: IdeaCache) -> Self {
+        Self {
+            cache: Some(cache),
+            ..self
+        }
+    }
+
+    /// `result` of a write, after clearing the cache whether it succeeded or not
+    fn written<T, E>(&self, result: Result<T, E>) -> Result<T, E> {
+        if let Some(cache) = &self.cache {
+            cache.invalidate();
+        }
+        result
+    }
+
+    /// `idea` cleaned, stamped and placed on this repository's board, as [`IdeaRepository::create`] stores it, for
+    /// creating it with other writes in [`Self::transaction`]
+    pub fn new_record(&self, idea: IdeaRecord) -> Result<IdeaRecord, IdeaError> {
+        Ok(placed(stamped(clean_record(idea)?), self.board.as_ref()))
+    }
+
+    /// [`with_transaction`] on this repository's database, clearing the cache like any other write
+    pub async fn transaction<'a, T>(
+        &'a self,
+        build: impl FnOnce(&mut Transaction<'a>) -> Result<T, IdeaError>,
+    ) -> Result<(T, Response), IdeaError> {
+        self.written(with_transaction(&self.db, build).await)
+    }
+}
+
+/// Whether a write failed only because a concurrent transaction touched the same record, so running it again can
+/// succeed
+fn is_write_conflict(error: &surrealdb::Error) -> bool {
+    use surrealdb::error::Db;
+    match error {
+        surrealdb::Error::Db(Db::TxRetryable) => true,
+        // A statement whose transaction failed to commit carries the reason in its message
+        surrealdb::Error::Db(Db::QueryNotExecutedDetail { message }) => message.contains(&Db::TxRetryable.to_string()),
+        _ => false,
+    }
+}
+
+/// The idea a single-record select, update or delete returned, or `NotFound`.
+///
+/// These calls give back an `Option` in the library version pinned by Cargo.lock; other versions have returned a
+/// `Vec`. Every such call goes through here, so a change of shape only needs handling in one place.
+fn found(record: Option<IdeaRecord>, id: &IdeaId) -> Result<IdeaRecord, IdeaError> {
+    record.ok_or_else(|| IdeaError::NotFound(id.to_string()))
+}
+
+impl IdeaRepository for SurrealIdeaRepository {
+    async fn create(&self, idea: IdeaRecord) -> Result<IdeaRecord, IdeaError> {
+        let idea = placed(stamped(clean_record(idea)?), self.board.as_ref());
+        let created: Option<IdeaRecord> = self.written(self.db.create("ideas").content(idea).await)?;
+        created.ok_or_else(|| IdeaError::Database("Failed to create idea".to_string()))
+    }
+
+    async fn create_many(&self, ideas: Vec<IdeaRecord>) -> Result<Vec<IdeaRecord>, IdeaError> {
+        let ideas: Vec<IdeaRecord> = clean_batch(ideas)?
+            .into_iter()
+            .map(|idea| placed(stamped(idea), self.board.as_ref()))
+            .collect();
+        if ideas.is_empty() {
+            return Ok(Vec::new());
+        }
+        // A single INSERT statement runs in its own transaction, so a failure part way through stores nothing
+        let created: Vec<IdeaRecord> = self.written(self.db.insert("ideas").content(ideas).await)?;
+        Ok(created)
+    }
+
+    async fn get(&self, id: &str) -> Result<IdeaRecord, IdeaError> {
+        let id = IdeaId::parse(id)?;
+        found(self.db.select((IDEA_TABLE, id.key())).await?, &id)
+    }
+
+    async fn list(&self, owner: Option<&Thing>) -> Result<Vec<IdeaRecord>, IdeaError> {
+        // The cache holds every board's ideas of an owner, so switching boards doesn't miss it
+        let Some(cache) = &self.cache else {
+            return Ok(on_board(active_ideas(&self.db, owner).await?, self.board.as_ref()));
+        };
+        let generation = match cache.lookup(owner) {
+            Ok(ideas) => return Ok(on_board(ideas, self.board.as_ref())),
+            Err(generation) => generation,
+        };
+        let ideas = active_ideas(&self.db, owner).await?;
+        cache.store(owner, generation, ideas.clone());
+        Ok(on_board(ideas, self.board.as_ref()))
+    }
+
+    async fn update(&self, id: &str, idea: IdeaRecord) -> Result<IdeaRecord, IdeaError> {
+        let idea = clean_record(idea)?;
+        let id = IdeaId::parse(id)?;
+        let updated = self.written(
+            self.db
+                .update((IDEA_TABLE, id.key()))
+                .content(IdeaRecord { id: None, ..idea })
+                .await,
+        )?;
+        found(updated, &id)
+    }
+
+    async fn merge(&self, id: &str, patch: serde_json::Value) -> Result<IdeaRecord, IdeaError> {
+        let patch = sanitize_patch(patch);
+        // Check the record exists first: merging into a missing id would otherwise fail silently
+        let existing = self.get(id).await?;
+        let id = IdeaId::parse(id)?;
+        let mut merged = merge_json(&existing, &patch)?;
+        merged.id = None;
+        check_record(&merged)?;
+
+        found(self.written(self.db.update((IDEA_TABLE, id.key())).merge(patch).await)?, &id)
+    }
+
+    async fn append_statement(&self, id: &str, statement: String) -> Result<IdeaRecord, IdeaError> {
+        let statement = sanitize_text(&statement).trim().to_string();
+        let id = IdeaId::parse(id)?;
+        // UPDATE doesn't create missing records, so an unknown id comes back empty. Appends racing on one idea
+        // conflict when they commit, and the ones that lose run again.
+        let append = "UPDATE $id SET what_must_be_true = array::append(what_must_be_true ?? [], $statement) \
+                      RETURN AFTER";
+        let mut attempt = 1;
+        let response = loop {
+            let response = self
+                .db
+                .query(append)
+                .bind(("id", Thing::from(&id)))
+                .bind(("statement", statement.clone()))
+                .await
+                .and_then(Response::check);
+            match response {
+                Err(error) if is_write_conflict(&error) && attempt < APPEND_ATTEMPTS => attempt += 1,
+                response => break response,
+            }
+        };
+        let updated: Option<IdeaRecord> = self.written(response)?.take(0)?;
+        found(updated, &id)
+    }
+
+    async fn replace_tags(
+        &self,
+        id: &str,
+        expected: Vec<String>,
+        tags: Vec<String>,
+    ) -> Result<Option<IdeaRecord>, IdeaError> {
+        let id = IdeaId::parse(id)?;
+        // The condition and the write are one statement, so no other write can land between them
+        let response = self
+            .db
+            .query("UPDATE $id SET tags = $tags WHERE (tags ?? []) = $expected RETURN AFTER")
+            .bind(("id", Thing::from(&id)))
+            .bind(("expected", expected))
+            .bind(("tags", tags))
+            .await;
+        let updated: Option<IdeaRecord> = self.written(response)?.take(0)?;
+        Ok(updated)
+    }
+
+    async fn delete(&self, id: &str) -> Result<DeleteImpact, IdeaError> {
+        let id = IdeaId::parse(id)?;
+        let thing = Thing::from(&id);
+        // The cascade events on ideas would remove most of these too, but not in a way that can be counted
+        let ([attachments, links, revisions, idea], mut response) = self
+            .transaction(|txn| {
+                txn.bind("id", thing);
+                let attachments = txn.push("DELETE attachments WHERE idea = $id RETURN BEFORE");
+                let links = txn.push("DELETE relates_to WHERE in = $id OR out = $id RETURN BEFORE");
+                let revisions = txn.push("DELETE idea_revisions WHERE idea = $id RETURN BEFORE");
+                txn.push("DELETE idea_events WHERE idea = $id");
+                let idea = txn.push("DELETE $id RETURN BEFORE");
+                Ok([attachments, links, revisions, idea])
+            })
+            .await?;
+        let attachments: Vec<Thing> = response.take((attachments, "id"))?;
+        let links: Vec<Thing> = response.take((links, "id"))?;
+        let revisions: Vec<Thing> = response.take((revisions, "id"))?;
+        let deleted: Option<IdeaRecord> = response.take(idea)?;
+        found(deleted, &id)?;
+        Ok(DeleteImpact {
+            attachments: attachments.len() as u64,
+            links: links.len() as u64,
+            revisions: revisions.len() as u64,
+        })
+    }
+
+    async fn delete_impact(&self, id: &str) -> Result<DeleteImpact, IdeaError> {
+        let id = IdeaId::parse(id)?;
+        let mut response = self
+            .db
+            .query(
+                "SELECT count() AS count FROM attachments WHERE idea = $id GROUP ALL;
+                 SELECT count() AS count FROM relates_to WHERE in = $id OR out = $id GROUP ALL;
+                 SELECT count() AS count FROM idea_revisions WHERE idea = $id GROUP ALL;",
+            )
+            .bind(("id", Thing::from(&id)))
+            .await?;
+        let mut count = |index: usize| -> Result<u64, IdeaError> {
+            let count: Option<u64> = response.take((index, "count"))?;
+            Ok(count.unwrap_or(0))
+        };
+        Ok(DeleteImpact {
+            attachments: count(0)?,
+            links: count(1)?,
+            revisions: count(2)?,
+        })
+    }
+
+    async fn search(&self, owner: Option<&Thing>, query: &str) -> Result<Vec<IdeaRecord>, IdeaError> {
+        let query = query.trim().to_lowercase();
+        if query.is_empty() {
+            return self.list(owner).await;
+        }
+
+        let ideas: Vec<IdeaRecord> = self
+            .db
+            .query(format!(
+                "SELECT * FROM ideas WHERE {} AND deleted_at = NONE AND (
+                     string::contains(string::lowercase(title), $query)
+                     OR string::contains(string::lowercase(description), $query)
+                     OR string::contains(string::lowercase(development_notes ?? ''), $query)
+                     OR string::contains(string::lowercase(array::join(sections.body ?? [], ' ')), $query)
+                     OR string::contains(string::lowercase(array::join(tags, ' ')), $query)
+                 ) ORDER BY rank ASC",
+                readable_by(owner)
+            ))
+            .bind(("owner", owner.cloned()))
+            .bind(("query", query))
+            .await?
+            .take(0)?;
+        Ok(on_board(ideas, self.board.as_ref()))
+    }
+
+    async fn slug_for(&self, title: &str, exclude: Option<&Thing>) -> Result<String, IdeaError> {
+        Ok(slug_for_title(&self.db, title, exclude.cloned()).await?)
+    }
+
+    async fn taken_slugs(&self, base: &str) -> Result<Vec<String>, IdeaError> {
+        Ok(taken_slugs(&self.db, base, None).await?)
+    }
+
+    async fn next_rank(&self) -> Result<f64, IdeaError> {
+        Ok(rank_for_new_idea(&self.db).await?)
+    }
+
+    async fn find_by_slug(&self, slug: &str) -> Result<Option<IdeaRecord>, IdeaError> {
+        Ok(find_by_slug(&self.db, slug).await?)
+    }
+
+    async fn log_event(&self, event: IdeaEventRecord) -> Result<(), IdeaError> {
+        let _: Option<IdeaEventRecord> = self.db.create("idea_events").content(event).await?;
+        Ok(())
+    }
+
+    async fn history(&self, idea: &Thing) -> Result<Vec<IdeaEventRecord>, IdeaError> {
+        let mut events: Vec<IdeaEventRecord> = self
+            .db
+            .query("SELECT * FROM idea_events WHERE idea = $idea")
+            .bind(("idea", idea.clone()))
+            .await?
+            .take(0)?;
+        events.sort_by_key(|event| std::cmp::Reverse(event.at));
+        Ok(events)
+    }
+
+    async fn save_revision(&self, revision: IdeaRevisionRecord) -> Result<(), IdeaError> {
+        match revision.id.clone() {
+            Some(id) => {
+                self.db
+                    .query("UPDATE $id CONTENT $revision")
+                    .bind(("id", id))
+                    .bind(("revision", IdeaRevisionRecord { id: None, ..revision }))
+                    .await?
+                    .check()?;
+            }
+            None => {
+                // The snapshot and the pruning it calls for land together, so a failed prune can't leave one too many
+                with_transaction(&self.db, |txn| {
+                    txn.bind("idea", revision.idea.clone());
+                    txn.bind("revision", revision);
+                    txn.bind("keep", MAX_REVISIONS);
+                    txn.push("CREATE idea_revisions CONTENT $revision");
+                    txn.push(
+                        "DELETE idea_revisions WHERE id INSIDE \
+                         (SELECT id, at FROM idea_revisions WHERE idea = $idea ORDER BY at DESC START $keep).id",
+                    );
+                    Ok(())
+                })
+                .await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn revisions(&self, idea: &Thing) -> Result<Vec<IdeaRevisionRecord>, IdeaError> {
+        let mut revisions: Vec<IdeaRevisionRecord> = self
+            .db
+            .query("SELECT * FROM idea_revisions WHERE idea = $idea")
+            .bind(("idea", idea.clone()))
+            .await?
+            .take(0)?;
+        revisions.sort_by_key(|revision| std::cmp::Reverse(revision.at));
+        Ok(revisions)
+    }
+
+    async fn absorb(
+        &self,
+        primary: &str,
+        merged: IdeaRecord,
+        secondary: &str,
+        event: IdeaEventRecord,
+    ) -> Result<IdeaRecord, IdeaError> {
+        let merged = clean_record(merged)?;
+        let primary_id = Thing::from(&IdeaId::parse(primary)?);
+        let secondary_id = Thing::from(&IdeaId::parse(secondary)?);
+
+        // Attachments are moved before the delete, so the cascade event on ideas finds none left to remove
+        self.transaction(|txn| {
+            txn.bind("primary", primary_id);
+            txn.bind("merged", IdeaRecord { id: None, ..merged });
+            txn.bind("secondary", secondary_id);
+            txn.bind("event", event);
+            txn.push("UPDATE $primary CONTENT $merged");
+            txn.push("UPDATE attachments SET idea = $primary WHERE idea = $secondary");
+            txn.push("DELETE $secondary");
+            txn.push("CREATE idea_events CONTENT $event");
+            Ok(())
+        })
+        .await?;
+        self.get(primary).await
+    }
+}
+
+/// Apply a JSON merge patch to an idea
+fn merge_json(idea: &IdeaRecord, patch: &serde_json::Value) -> Result<IdeaRecord, IdeaError> {
+    let serde_json::Value::Object(fields) = patch else {
+        return Err(IdeaError::Validation("A patch must be an object".to_string()));
+    };
+
+    let mut value = serde_json::to_value(idea).map_err(|e| IdeaError::Database(e.to_string()))?;
+    if let serde_json::Value::Object(target) = &mut value {
+        for (key, field) in fields {
+            target.insert(key.clone(), field.clone());
+        }
+    }
+    serde_json::from_value(value).map_err(|e| IdeaError::Validation(format!("Invalid patch: {}", e)))
+}
+
+/// In-memory [`IdeaRepository`] for tests. Clones share the same data.
+#[derive(Debug, Clone, Default)]
+pub struct MockIdeaRepository {
+    ideas: Arc<Mutex<BTreeMap<String, IdeaRecord>>>,
+    next_id: Arc<Mutex<u64>>,
+    events: Arc<Mutex<Vec<IdeaEventRecord>>>,
+    revisions: Arc<Mutex<Vec<IdeaRevisionRecord>>>,
+    failure: Option<String>,
+    board: Option<Thing>,
+}
+
+impl MockIdeaRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A repository where every call fails with a database error, for testing error paths
+    pub fn failing(message: &str) -> Self {
+        Self {
+            failure: Some(message.to_string()),
+            ..Self::default()
+        }
+    }
+
+    /// The same data scoped to `board`, like [`SurrealIdeaRepository::in_board`]. Clones share the data, so a clone
+    /// scoped to each board sees the others' writes.
+    pub fn in_board(self, board: Thing) -> Self {
+        Self {
+            board: Some(board),
+            ..self
+        }
+    }
+
+    fn fail_if_broken(&self) -> Result<(), IdeaError> {
+        match &self.failure {
+            Some(message) => Err(IdeaError::Database(message.clone())),
+            None => Ok(()),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BTreeMap<String, IdeaRecord>> {
+        self.ideas.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn events(&self) -> std::sync::MutexGuard<'_, Vec<IdeaEventRecord>> {
+        self.events.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn revision_list(&self) -> std::sync::MutexGuard<'_, Vec<IdeaRevisionRecord>> {
+        self.revisions.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// The idea stored under `id`, in any of the forms [`IdeaId::parse`] reads
+    fn stored(&self, id: &str) -> Result<IdeaRecord, IdeaError> {
+        let id = IdeaId::parse(id)?;
+        self.lock()
+            .get(id.as_str())
+            .cloned()
+            .ok_or_else(|| IdeaError::NotFound(id.to_string()))
+    }
+
+    /// A fresh id in `table`
+    fn next_thing(&self, table: &str) -> Thing {
+        let mut next_id = self.next_id.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        *next_id += 1;
+        Thing::from((table, format!("mock{}", next_id).as_str()))
+    }
+
+    fn sorted_by_rank(mut ideas: Vec<IdeaRecord>) -> Vec<IdeaRecord> {
+        ideas.sort_by(|a, b| a.rank.total_cmp(&b.rank));
+        ideas
+    }
+}
+
+impl IdeaRepository for MockIdeaRepository {
+    async fn create(&self, idea: IdeaRecord) -> Result<IdeaRecord, IdeaError> {
+        self.fail_if_broken()?;
+        let idea = placed(stamped(clean_record(idea)?), self.board.as_ref());
+
+        let id = idea.id.clone().unwrap_or_else(|| self.next_thing(IDEA_TABLE));
+        let created = IdeaRecord {
+            id: Some(id.clone()),
+            ..idea
+        };
+        self.lock().insert(IdeaId::from(&id).into(), created.clone());
+        Ok(created)
+    }
+
+    async fn create_many(&self, ideas: Vec<IdeaRecord>) -> Result<Vec<IdeaRecord>, IdeaError> {
+        self.fail_if_broken()?;
+        let ideas = clean_batch(ideas)?;
+
+        let mut created = Vec::with_capacity(ideas.len());
+        for idea in ideas {
+            created.push(self.create(idea).await?);
+        }
+        Ok(created)
+    }
+
+    async fn get(&self, id: &str) -> Result<IdeaRecord, IdeaError> {
+        self.fail_if_broken()?;
+        self.stored(id)
+    }
+
+    async fn list(&self, owner: Option<&Thing>) -> Result<Vec<IdeaRecord>, IdeaError> {
+        self.fail_if_broken()?;
+        let ideas = self
+            .lock()
+            .values()
+            .filter(|idea| is_readable(idea, owner) && idea.deleted_at.is_none())
+            .cloned()
+            .collect();
+        Ok(Self::sorted_by_rank(on_board(ideas, self.board.as_ref())))
+    }
+
+    async fn update(&self, id: &str, idea: IdeaRecord) -> Result<IdeaRecord, IdeaError> {
+        self.fail_if_broken()?;
+        let idea = clean_record(idea)?;
+        let existing = self.stored(id)?;
+
+        let updated = IdeaRecord { id: existing.id, ..idea };
+        self.lock().insert(IdeaId::parse(id)?.into(), updated.clone());
+        Ok(updated)
+    }
+
+    async fn merge(&self, id: &str, patch: serde_json::Value) -> Result<IdeaRecord, IdeaError> {
+        self.fail_if_broken()?;
+        let existing = self.stored(id)?;
+
+        let mut merged = merge_json(&existing, &sanitize_patch(patch))?;
+        merged.id = existing.id;
+        check_record(&merged)?;
+        self.lock().insert(IdeaId::parse(id)?.into(), merged.clone());
+        Ok(merged)
+    }
+
+    async fn append_statement(&self, id: &str, statement: String) -> Result<IdeaRecord, IdeaError> {
+        self.fail_if_broken()?;
+        let id = IdeaId::parse(id)?;
+        let mut ideas = self.lock();
+        let idea = ideas.get_mut(id.as_str()).ok_or_else(|| IdeaError::NotFound(id.to_string()))?;
+        idea.what_must_be_true.push(sanitize_text(&statement).trim().to_string());
+        Ok(idea.clone())
+    }
+
+    async fn replace_tags(
+        &self,
+        id: &str,
+        expected: Vec<String>,
+        tags: Vec<String>,
+    ) -> Result<Option<IdeaRecord>, IdeaError> {
+        self.fail_if_broken()?;
+        let id = IdeaId::parse(id)?;
+        let mut ideas = self.lock();
+        let Some(idea) = ideas.get_mut(id.as_str()).filter(|idea| idea.tags == expected) else {
+            return Ok(None);
+        };
+        idea.tags = tags;
+        Ok(Some(idea.clone()))
+    }
+
+    async fn delete(&self, id: &str) -> Result<DeleteImpact, IdeaError> {
+        let impact = self.delete_impact(id).await?;
+        let id = IdeaId::parse(id)?;
+        self.lock().remove(id.as_str()).ok_or_else(|| IdeaError::NotFound(id.to_string()))?;
+        // Mirrors the cascade on the ideas table
+        self.events().retain(|event| IdeaId::from(&event.idea) != id);
+        self.revision_list().retain(|revision| IdeaId::from(&revision.idea) != id);
+        Ok(impact)
+    }
+
+    /// Only revisions: the mock keeps no attachments or links
+    async fn delete_impact(&self, id: &str) -> Result<DeleteImpact, IdeaError> {
+        self.fail_if_broken()?;
+        let id = IdeaId::parse(id)?;
+        let revisions = self.revision_list().iter().filter(|revision| IdeaId::from(&revision.idea) == id).count();
+        Ok(DeleteImpact {
+            revisions: revisions as u64,
+            ..Default::default()
+        })
+    }
+
+    async fn search(&self, owner: Option<&Thing>, query: &str) -> Result<Vec<IdeaRecord>, IdeaError> {
+        let query = query.trim().to_lowercase();
+        let ideas = self.list(owner).await?;
+        Ok(ideas
+            .into_iter()
+            .filter(|idea| {
+                idea.title.to_lowercase().contains(&query)
+                    || idea.description.to_lowercase().contains(&query)
+                    || idea.development_notes.to_lowercase().contains(&query)
+                    || idea.sections.iter().any(|section| section.body.to_lowercase().contains(&query))
+                    || idea.tags.join(" ").to_lowercase().contains(&query)
+            })
+            .collect())
+    }
+
+    async fn slug_for(&self, title: &str, exclude: Option<&Thing>) -> Result<String, IdeaError> {
+        self.fail_if_broken()?;
+        let taken: Vec<String> = self
+            .lock()
+            .values()
+            .filter(|idea| idea.id.as_ref() != exclude)
+            .flat_map(|idea| std::iter::once(idea.slug.clone()).chain(idea.previous_slugs.clone()))
+            .collect();
+        Ok(unique_slug(&slugify(title), &taken))
+    }
+
+    async fn taken_slugs(&self, base: &str) -> Result<Vec<String>, IdeaError> {
+        self.fail_if_broken()?;
+        Ok(self
+            .lock()
+            .values()
+            .flat_map(|idea| std::iter::once(idea.slug.clone()).chain(idea.previous_slugs.clone()))
+            .filter(|slug| slug.starts_with(base))
+            .collect())
+    }
+
+    async fn next_rank(&self) -> Result<f64, IdeaError> {
+        self.fail_if_broken()?;
+        let max = self.lock().values().map(|idea| idea.rank).reduce(f64::max);
+        Ok(next_rank(max))
+    }
+
+    async fn find_by_slug(&self, slug: &str) -> Result<Option<IdeaRecord>, IdeaError> {
+        self.fail_if_broken()?;
+        let ideas = self.lock();
+        let current = ideas.values().find(|idea| idea.slug == slug);
+        let previous = || ideas.values().find(|idea| idea.previous_slugs.iter().any(|s| s == slug));
+        Ok(current.or_else(previous).cloned())
+    }
+
+    async fn log_event(&self, event: IdeaEventRecord) -> Result<(), IdeaError> {
+        self.fail_if_broken()?;
+        self.events().push(event);
+        Ok(())
+    }
+
+    async fn history(&self, idea: &Thing) -> Result<Vec<IdeaEventRecord>, IdeaError> {
+        self.fail_if_broken()?;
+        let mut events: Vec<IdeaEventRecord> =
+            self.events().iter().filter(|event| &event.idea == idea).cloned().collect();
+        // Newest first. Reversing before the stable sort keeps events logged within the same instant newest first too.
+        events.reverse();
+        events.sort_by_key(|event| std::cmp::Reverse(event.at));
+        Ok(events)
+    }
+
+    async fn save_revision(&self, revision: IdeaRevisionRecord) -> Result<(), IdeaError> {
+        self.fail_if_broken()?;
+        let mut revisions = self.revision_list();
+        match &revision.id {
+            Some(id) => {
+                let stored = revisions
+                    .iter_mut()
+                    .find(|stored| stored.id.as_ref() == Some(id))
+                    .ok_or_else(|| IdeaError::NotFound(id.to_string()))?;
+                *stored = revision;
+            }
+            None => {
+                let idea = revision.idea.clone();
+                revisions.push(IdeaRevisionRecord {
+                    id: Some(self.next_thing("idea_revisions")),
+                    ..revision
+                });
+                // Oldest first, so the first ones of this idea are the ones to prune
+                let excess = revisions.iter().filter(|stored| stored.idea == idea).count().saturating_sub(MAX_REVISIONS);
+                let mut pruned = 0;
+                revisions.retain(|stored| {
+                    let prune = stored.idea == idea && pruned < excess;
+                    pruned += usize::from(prune);
+                    !prune
+                });
+            }
+        }
+        Ok(())
+    }
+
+    async fn revisions(&self, idea: &Thing) -> Result<Vec<IdeaRevisionRecord>, IdeaError> {
+        self.fail_if_broken()?;
+        let mut revisions: Vec<IdeaRevisionRecord> =
+            self.revision_list().iter().filter(|revision| &revision.idea == idea).cloned().collect();
+        // Newest first, like the history
+        revisions.reverse();
+        revisions.sort_by_key(|revision| std::cmp::Reverse(revision.at));
+        Ok(revisions)
+    }
+
+    async fn absorb(
+        &self,
+        primary: &str,
+        merged: IdeaRecord,
+        secondary: &str,
+        event: IdeaEventRecord,
+    ) -> Result<IdeaRecord, IdeaError> {
+        self.fail_if_broken()?;
+        let merged = clean_record(merged)?;
+        let existing = self.stored(primary)?;
+        self.stored(secondary)?;
+
+        // The mock has no attachments to move
+        let merged = IdeaRecord { id: existing.id, ..merged };
+        self.lock().insert(IdeaId::parse(primary)?.into(), merged.clone());
+        self.delete(secondary).await?;
+        self.events().push(event);
+        Ok(merged)
+    }
+}
+
+// Operations built on the repository. These hold the rules; server functions only add request handling.
+
+/// Load an idea, checking that `user` owns it and may read it, see [`ensure_readable`]
+pub async fn get_owned(
+    repo: &impl IdeaRepository,
+    id: &str,
+    user: Option<&Thing>,
+) -> Result<IdeaRecord, IdeaError> {
+    let idea = repo.get(id).await?;
+    ensure_readable(&idea, user)?;
+    Ok(idea)
+}
+
+/// Create an idea owned by `owner`, placed at the end of the manual order
+pub async fn submit_idea(
+    repo: &impl IdeaRepository,
+    owner: Option<Thing>,
+    title: String,
+    description: String,
+    tags: Vec<String>,
+) -> Result<IdeaRecord, IdeaError> {
+    let content = IdeaChanges {
+        title,
+        description,
+        tags,
+        what_must_be_true: Vec::new(),
+        development_notes: String::new(),
+        due_date: None,
+        sections: None,
+    };
+    submit_idea_content(repo, owner, None, content).await
+}
+
+/// Like [`submit_idea`], with every content field given up front (e.g. prefilled from a template) and credited to
+/// `author`. A blank author is stored as none.
+pub async fn submit_idea_content(
+    repo: &impl IdeaRepository,
+    owner: Option<Thing>,
+    author: Option<String>,
+    content: IdeaChanges,
+) -> Result<IdeaRecord, IdeaError> {
+    submit_idea_with_fields(repo, owner, author, content, HashMap::new()).await
+}
+
+/// Like [`submit_idea_content`], with values for the deployment's custom form fields. Check them against the
+/// [`crate::form_config::FormConfig`] first; they are stored as they are.
+pub async fn submit_idea_with_fields(
+    repo: &impl IdeaRepository,
+    owner: Option<Thing>,
+    author: Option<String>,
+    content: IdeaChanges,
+    custom_fields: HashMap<String, serde_json::Value>,
+) -> Result<IdeaRecord, IdeaError> {
+    let content = content.sanitized();
+    let slug = repo.slug_for(&content.title, None).await?;
+    let rank = repo.next_rank().await?;
+
+    let created = repo
+        .create(IdeaRecord {
+            id: None,
+            title: content.title,
+            description: content.description,
+            tags: tidy_tags(content.tags),
+            what_must_be_true: content.what_must_be_true,
+            development_notes: content.development_notes,
+            sections: content.sections.unwrap_or_default(),
+            slug,
+            previous_slugs: Vec::new(),
+            rank,
+            owner,
+            deleted_at: None,
+            pinned: false,
+            due_date: content.due_date,
+            status: IdeaStatus::default(),
+            visibility: Visibility::default(),
+            author: tidy_author(author),
+            icon: None,
+            custom_fields,
+            created_at: None,
+            board: None,
+        })
+        .await?;
+    log_event(repo, &created, IdeaEventKind::Created, "").await?;
+    Ok(created)
+}
+
+/// Ideas of `owner` outside the trash whose title looks like `title`, most similar first
+pub async fn similar_ideas(
+    repo: &impl IdeaRepository,
+    owner: Option<&Thing>,
+    title: &str,
+) -> Result<Vec<IdeaRecord>, IdeaError> {
+    let mut scored: Vec<(f64, IdeaRecord)> = repo
+        .list(owner)
+        .await?
+        .into_iter()
+        .filter(|idea| is_possible_duplicate(&idea.title, title))
+        .map(|idea| (title_similarity(&idea.title, title), idea))
+        .collect();
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+    Ok(scored.into_iter().take(MAX_POSSIBLE_DUPLICATES).map(|(_, idea)| idea).collect())
+}
+
+/// Append an entry to the history of a stored idea
+async fn log_event(
+    repo: &impl IdeaRepository,
+    idea: &IdeaRecord,
+    kind: IdeaEventKind,
+    summary: &str,
+) -> Result<(), IdeaError> {
+    match &idea.id {
+        Some(id) => repo.log_event(IdeaEventRecord::now(id.clone(), kind, summary)).await,
+        None => Ok(()),
+    }
+}
+
+/// Create a batch of ideas owned by `owner` in one write, placed after every existing idea in the given order.
+///
+/// Only the content of each idea is used; ids, slugs, ranks and trash state are assigned fresh. If any idea is
+/// invalid nothing is stored, and the error names every offending index.
+pub async fn submit_ideas(
+    repo: &impl IdeaRepository,
+    owner: Option<Thing>,
+    ideas: Vec<Idea>,
+) -> Result<Vec<IdeaRecord>, IdeaError> {
+    store_batch(repo, owner, ideas, false).await
+}
+
+/// [`submit_ideas`] for ideas coming back from an export: each keeps its id, when it has one, when it was created
+/// and whether it was pinned. Storing an id that is already taken fails the whole batch, so check for those first.
+pub async fn restore_ideas(
+    repo: &impl IdeaRepository,
+    owner: Option<Thing>,
+    ideas: Vec<Idea>,
+) -> Result<Vec<IdeaRecord>, IdeaError> {
+    store_batch(repo, owner, ideas, true).await
+}
+
+async fn store_batch(
+    repo: &impl IdeaRepository,
+    owner: Option<Thing>,
+    ideas: Vec<Idea>,
+    restoring: bool,
+) -> Result<Vec<IdeaRecord>, IdeaError> {
+    if ideas.len() > MAX_BATCH_IDEAS {
+        return Err(IdeaError::Validation(format!(
+            "A batch can hold at most {} ideas",
+            MAX_BATCH_IDEAS
+        )));
+    }
+
+    let records: Vec<IdeaRecord> = ideas
+        .into_iter()
+        .map(|idea| {
+            let id = match idea.id.as_deref() {
+                Some(id) if restoring => Some(Thing::from(&IdeaId::parse(id)?)),
+                _ => None,
+            };
+            Ok((id, idea))
+        })
+        .collect::<Result<Vec<_>, IdeaError>>()?
+        .into_iter()
+        .map(|(id, idea)| IdeaRecord {
+            id,
+            title: idea.title,
+            description: idea.description,
+            tags: tidy_tags(idea.tags),
+            what_must_be_true: idea.what_must_be_true,
+            development_notes: idea.development_notes,
+            sections: idea.sections,
+            slug: String::new(),
+            previous_slugs: Vec::new(),
+            rank: 0.0,
+            owner: owner.clone(),
+            deleted_at: None,
+            pinned: restoring && idea.pinned,
+            due_date: idea.due_date,
+            status: idea.status,
+            visibility: idea.visibility,
+            author: tidy_author(idea.author),
+            icon: idea.icon,
+            custom_fields: idea.custom_fields,
+            created_at: if restoring { idea.created_at } else { None },
+            board: None,
+        })
+        .collect();
+    // Fail before spending a query per record on slugs, which come from the sanitized titles
+    let mut records = clean_batch(records)?;
+
+    let mut rank = repo.next_rank().await?;
+    let mut batch_slugs: Vec<String> = Vec::with_capacity(records.len());
+    for record in &mut records {
+        // The repository only knows stored slugs, so the batch's own are added for titles repeated within it
+        let base = slugify(&record.title);
+        let mut taken = repo.taken_slugs(&base).await?;
+        taken.extend(batch_slugs.iter().cloned());
+        let slug = unique_slug(&base, &taken);
+        batch_slugs.push(slug.clone());
+        record.slug = slug;
+        record.rank = rank;
+        rank = next_rank(Some(rank));
+    }
+
+    let created = repo.create_many(records).await?;
+    for idea in &created {
+        log_event(repo, idea, IdeaEventKind::Created, "imported").await?;
+    }
+    Ok(created)
+}
+
+/// New content for an idea
+#[derive(Debug, Clone, Default)]
+pub struct IdeaChanges {
+    pub title: String,
+    pub description: String,
+    pub tags: Vec<String>,
+    pub what_must_be_true: Vec<String>,
+    pub development_notes: String,
+    pub due_date: Option<NaiveDate>,
+    /// Structured notes, see [`NoteSection`]. `None` keeps the idea's current sections, so editors that don't
+    /// show them can't wipe them.
+    pub sections: Option<Vec<NoteSection>>,
+}
+
+impl IdeaChanges {
+    /// The changes sanitized and with their whitespace normalized, as the repository will store them
+    fn sanitized(self) -> Self {
+        Self {
+            title: clean_title(&self.title),
+            description: clean_text(&self.description),
+            tags: sanitize_tags(&self.tags),
+            what_must_be_true: clean_statements(&self.what_must_be_true),
+            development_notes: clean_text(&self.development_notes),
+            due_date: self.due_date,
+            sections: self.sections.as_deref().map(clean_sections),
+        }
+    }
+}
+
+/// Replace the content of an idea owned by `user`.
+///
+/// The slug is only regenerated when the title changes, and the old slug is kept so existing links still work.
+pub async fn edit_idea(
+    repo: &impl IdeaRepository,
+    id: &str,
+    user: Option<&Thing>,
+    changes: IdeaChanges,
+) -> Result<IdeaRecord, IdeaError> {
+    save_changes(repo, id, user, changes, true).await
+}
+
+/// [`edit_idea`], choosing whether a changed text may update the newest snapshot when it was taken moments ago
+/// (`coalesce`) or always gets a snapshot of its own
+async fn save_changes(
+    repo: &impl IdeaRepository,
+    id: &str,
+    user: Option<&Thing>,
+    changes: IdeaChanges,
+    coalesce: bool,
+) -> Result<IdeaRecord, IdeaError> {
+    // Cleaned up front, so a title that only differs by stripped markup or spacing doesn't count as a rename
+    let changes = changes.sanitized();
+    let existing = get_owned(repo, id, user).await?;
+    let before: Idea = existing.clone().into();
+
+    let mut slug = existing.slug;
+    let mut previous_slugs = existing.previous_slugs;
+    if changes.title != existing.title || slug.is_empty() {
+        let new_slug = repo.slug_for(&changes.title, existing.id.as_ref()).await?;
+        if new_slug != slug {
+            if !slug.is_empty() && !previous_slugs.contains(&slug) {
+                previous_slugs.push(slug);
+            }
+            slug = new_slug;
+        }
+    }
+
+    let updated = IdeaRecord {
+        id: None,
+        title: changes.title,
+        description: changes.description,
+        tags: tidy_tags(changes.tags),
+        what_must_be_true: changes.what_must_be_true,
+        development_notes: changes.development_notes,
+        sections: changes.sections.unwrap_or(existing.sections),
+        slug,
+        previous_slugs,
+        rank: existing.rank,
+        owner: existing.owner,
+        deleted_at: existing.deleted_at,
+        pinned: existing.pinned,
+        due_date: changes.due_date,
+        status: existing.status,
+        visibility: existing.visibility,
+        author: existing.author,
+        icon: existing.icon,
+        custom_fields: existing.custom_fields,
+        created_at: existing.created_at,
+        board: existing.board,
+    };
+    let updated = repo.update(id, updated).await?;
+    // Saving unchanged content (e.g. the editor saving twice) leaves no trace
+    if let Some(summary) = summarize_edit(&before, &updated.clone().into()) {
+        log_event(repo, &updated, IdeaEventKind::Edited, &summary).await?;
+    }
+    snapshot_texts(repo, &before, &updated, coalesce).await?;
+    Ok(updated)
+}
+
+/// Take a snapshot of the notes and statements of `after` if they differ from `before`.
+///
+/// The first snapshot of an idea is preceded by one of its content before the edit, so text written before
+/// snapshots existed can be restored too.
+async fn snapshot_texts(
+    repo: &impl IdeaRepository,
+    before: &Idea,
+    after: &IdeaRecord,
+    coalesce: bool,
+) -> Result<(), IdeaError> {
+    let Some(idea) = &after.id else {
+        return Ok(());
+    };
+    if before.what_must_be_true == after.what_must_be_true && before.development_notes == after.development_notes {
+        return Ok(());
+    }
+
+    let snapshot = IdeaRevisionRecord::now(
+        idea.clone(),
+        after.what_must_be_true.clone(),
+        after.development_notes.clone(),
+    );
+    match repo.revisions(idea).await?.into_iter().next() {
+        Some(newest) if coalesce && newest.is_same_burst(snapshot.at) => {
+            repo.save_revision(IdeaRevisionRecord { id: newest.id, at: newest.at, ..snapshot }).await
+        }
+        Some(_) => repo.save_revision(snapshot).await,
+        None => {
+            if !before.what_must_be_true.is_empty() || !before.development_notes.trim().is_empty() {
+                // Stamped just before the snapshot, so the history lists it as the older of the two
+                let original = IdeaRevisionRecord {
+                    at: snapshot.at - chrono::Duration::milliseconds(1),
+                    ..IdeaRevisionRecord::now(
+                        idea.clone(),
+                        before.what_must_be_true.clone(),
+                        before.development_notes.clone(),
+                    )
+                };
+                repo.save_revision(original).await?;
+            }
+            repo.save_revision(snapshot).await
+        }
+    }
+}
+
+/// Snapshots of the notes and statements of an idea owned by `user`, newest first
+pub async fn idea_revisions(
+    repo: &impl IdeaRepository,
+    id: &str,
+    user: Option<&Thing>,
+) -> Result<Vec<IdeaRevisionRecord>, IdeaError> {
+    let idea = get_owned(repo, id, user).await?;
+    match &idea.id {
+        Some(idea_id) => repo.revisions(idea_id).await,
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Put the notes and statements of one of its snapshots back into an idea owned by `user`. The rest of the idea is
+/// left alone. The restored text gets a snapshot of its own, so the restore can be undone the same way.
+pub async fn restore_revision(
+    repo: &impl IdeaRepository,
+    id: &str,
+    revision_id: &str,
+    user: Option<&Thing>,
+) -> Result<IdeaRecord, IdeaError> {
+    let revision = idea_revisions(repo, id, user)
+        .await?
+        .into_iter()
+        .find(|revision| revision.id.as_ref().is_some_and(|stored| stored.to_string() == revision_id))
+        .ok_or_else(|| IdeaError::NotFound(revision_id.to_string()))?;
+
+    let current = repo.get(id).await?;
+    let changes = IdeaChanges {
+        title: current.title,
+        description: current.description,
+        tags: current.tags,
+        what_must_be_true: revision.what_must_be_true,
+        development_notes: revision.development_notes,
+        due_date: current.due_date,
+        sections: None,
+    };
+    save_changes(repo, id, user, changes, false).await
+}
+
+/// Move an idea to the trash. Trashing an idea that is already there keeps its original deletion time.
+pub async fn trash_idea(repo: &impl IdeaRepository, id: &str, user: Option<&Thing>) -> Result<(), IdeaError> {
+    let idea = get_owned(repo, id, user).await?;
+    if idea.deleted_at.is_none() {
+        repo.merge(id, serde_json::json!({ "deleted_at": Utc::now() })).await?;
+        log_event(repo, &idea, IdeaEventKind::Trashed, "").await?;
+    }
+    Ok(())
+}
+
+/// Take an idea back out of the trash
+pub async fn restore_idea(
+    repo: &impl IdeaRepository,
+    id: &str,
+    user: Option<&Thing>,
+) -> Result<IdeaRecord, IdeaError> {
+    let idea = get_owned(repo, id, user).await?;
+    let was_trashed = idea.deleted_at.is_some();
+    // A full update rather than a merge, so the field is removed instead of set to null
+    let restored = repo.update(id, IdeaRecord { deleted_at: None, ..idea }).await?;
+    if was_trashed {
+        log_event(repo, &restored, IdeaEventKind::Restored, "").await?;
+    }
+    Ok(restored)
+}
+
+/// Pin an unpinned idea or unpin a pinned one, returning it with its new state
+pub async fn toggle_pin(repo: &impl IdeaRepository, id: &str, user: Option<&Thing>) -> Result<IdeaRecord, IdeaError> {
+    let idea = get_owned(repo, id, user).await?;
+    let toggled = repo.merge(id, serde_json::json!({ "pinned": !idea.pinned })).await?;
+    let kind = if toggled.pinned { IdeaEventKind::Pinned } else { IdeaEventKind::Unpinned };
+    log_event(repo, &toggled, kind, "").await?;
+    Ok(toggled)
+}
+
+/// Tries [`change_tags`] makes before giving up on an idea whose tags keep changing under it
+const TAG_CHANGE_ATTEMPTS: usize = 5;
+
+/// Add or remove one tag of an idea owned by `user`, returning the idea with the tags now stored.
+///
+/// The new tags are worked out from the stored ones and written only if those are still the same, trying again with
+/// a fresh read otherwise, so changes to the same idea racing each other all land. A change that leaves the tags as
+/// they are writes nothing and leaves no trace.
+pub async fn change_tags(
+    repo: &impl IdeaRepository,
+    id: &str,
+    user: Option<&Thing>,
+    change: TagChange,
+) -> Result<IdeaRecord, IdeaError> {
+    let summary = match &change {
+        TagChange::Add(tag) => format!("added tag {}", tag.trim()),
+        TagChange::Remove(tag) => format!("removed tag {}", tag.trim()),
+    };
+    for _ in 0..TAG_CHANGE_ATTEMPTS {
+        let idea = get_owned(repo, id, user).await?;
+        let tags = apply_tag_change(&idea.tags, &change)?;
+        if tags == idea.tags {
+            return Ok(idea);
+        }
+        if let Some(updated) = repo.replace_tags(id, idea.tags, tags).await? {
+            log_event(repo, &updated, IdeaEventKind::Edited, &summary).await?;
+            return Ok(updated);
+        }
+    }
+    Err(IdeaError::Database("The tags kept changing, try again".to_string()))
+}
+
+/// Mark an idea owned by `user` as looked at, returning the history entry that says so. Only that entry is written:
+/// the idea, its revisions and its place in the order stay as they were, but its latest activity is now.
+pub async fn touch_idea(
+    repo: &impl IdeaRepository,
+    id: &str,
+    user: Option<&Thing>,
+) -> Result<IdeaEventRecord, IdeaError> {
+    let idea = get_owned(repo, id, user).await?;
+    if idea.deleted_at.is_some() {
+        return Err(IdeaError::Validation("Restore the idea from the trash first".to_string()));
+    }
+    let thing = idea.id.ok_or_else(|| IdeaError::NotFound(id.to_string()))?;
+    let event = IdeaEventRecord::now(thing, IdeaEventKind::Reviewed, "kept in review");
+    repo.log_event(event.clone()).await?;
+    Ok(event)
+}
+
+/// Move an idea owned by `user` to another column of the board. Its rank is left alone, so it keeps its place in
+/// the manual order. Setting the status it already has changes nothing and leaves no trace.
+pub async fn set_status(
+    repo: &impl IdeaRepository,
+    id: &str,
+    user: Option<&Thing>,
+    status: IdeaStatus,
+) -> Result<IdeaRecord, IdeaError> {
+    let idea = get_owned(repo, id, user).await?;
+    if idea.status == status {
+        return Ok(idea);
+    }
+    let moved = repo.merge(id, serde_json::json!({ "status": status })).await?;
+    log_event(repo, &moved, IdeaEventKind::Edited, &format!("moved to {}", status)).await?;
+    Ok(moved)
+}
+
+/// Make an idea owned by `user` public or private. Setting the visibility it already has changes nothing and leaves
+/// no trace.
+pub async fn set_visibility(
+    repo: &impl IdeaRepository,
+    id: &str,
+    user: Option<&Thing>,
+    visibility: Visibility,
+) -> Result<IdeaRecord, IdeaError> {
+    let idea = get_owned(repo, id, user).await?;
+    if idea.visibility == visibility {
+        return Ok(idea);
+    }
+    let changed = repo.merge(id, serde_json::json!({ "visibility": visibility })).await?;
+    log_event(repo, &changed, IdeaEventKind::Edited, &format!("made {}", visibility.as_str())).await?;
+    Ok(changed)
+}
+
+/// Give an idea owned by `user` an icon, or take it away with `None` or a blank one. Anything but a single emoji is
+/// refused, see [`is_emoji_icon`].
+pub async fn set_icon(
+    repo: &impl IdeaRepository,
+    id: &str,
+    user: Option<&Thing>,
+    icon: Option<String>,
+) -> Result<IdeaRecord, IdeaError> {
+    let icon = tidy_icon(icon);
+    if icon.as_deref().is_some_and(|icon| !is_emoji_icon(icon)) {
+        return Err(IdeaError::Validation("The icon must be a single emoji".to_string()));
+    }
+    let idea = get_owned(repo, id, user).await?;
+    if idea.icon == icon {
+        return Ok(idea);
+    }
+    let updated = repo.merge(id, serde_json::json!({ "icon": icon })).await?;
+    let summary = match &icon {
+        Some(icon) => format!("icon {}", icon),
+        None => "removed icon".to_string(),
+    };
+    log_event(repo, &updated, IdeaEventKind::Edited, &summary).await?;
+    Ok(updated)
+}
+
+/// Append a "what must be true" statement to an idea owned by `user`, see [`IdeaRepository::append_statement`].
+/// Blank statements are refused.
+pub async fn append_wmbt(
+    repo: &impl IdeaRepository,
+    id: &str,
+    user: Option<&Thing>,
+    statement: &str,
+) -> Result<IdeaRecord, IdeaError> {
+    let statement = sanitize_text(statement);
+    let statement = statement.trim();
+    if statement.is_empty() {
+        return Err(IdeaError::Validation("The statement is empty".to_string()));
+    }
+    get_owned(repo, id, user).await?;
+
+    let updated = repo.append_statement(id, statement.to_string()).await?;
+    log_event(repo, &updated, IdeaEventKind::Edited, "added 1 statement").await?;
+    Ok(updated)
+}
+
+/// Heading placed in front of text carried over from a merged idea
+fn merged_heading(title: &str) -> String {
+    format!("## merged from {}", title)
+}
+
+/// `primary` text with the `secondary` text appended under a [`merged_heading`]. Blank parts are left out.
+fn append_merged(primary: &str, secondary: &str, title: &str) -> String {
+    match (primary.trim().is_empty(), secondary.trim().is_empty()) {
+        (_, true) => primary.to_string(),
+        (true, false) => format!("{}\n\n{}", merged_heading(title), secondary),
+        (false, false) => format!("{}\n\n{}\n\n{}", primary.trim_end(), merged_heading(title), secondary),
+    }
+}
+
+/// The idea that results from merging `secondary` into `primary`.
+///
+/// The primary keeps its identity: id, title, slug, owner, rank and trash state. The secondary's slugs join the
+/// primary's previous slugs so links to it lead to the merged idea. Descriptions and notes are appended under a
+/// heading naming the secondary, and the secondary's note sections follow the primary's. Tags and "what must be
+/// true" statements are combined without duplicates, the idea stays pinned if either was, and the earlier due date
+/// wins. The primary's icon and custom field values win, the secondary fills in the rest.
+pub fn merge_idea_fields(primary: &IdeaRecord, secondary: &IdeaRecord) -> IdeaRecord {
+    let tags = tidy_tags(primary.tags.iter().chain(&secondary.tags).cloned().collect());
+
+    let mut what_must_be_true = primary.what_must_be_true.clone();
+    for statement in &secondary.what_must_be_true {
+        if !what_must_be_true.iter().any(|existing| existing.trim() == statement.trim()) {
+            what_must_be_true.push(statement.clone());
+        }
+    }
+
+    let mut previous_slugs = primary.previous_slugs.clone();
+    for slug in std::iter::once(&secondary.slug).chain(&secondary.previous_slugs) {
+        if !slug.is_empty() && *slug != primary.slug && !previous_slugs.contains(slug) {
+            previous_slugs.push(slug.clone());
+        }
+    }
+
+    let due_date = match (primary.due_date, secondary.due_date) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    };
+
+    let mut custom_fields = secondary.custom_fields.clone();
+    custom_fields.extend(primary.custom_fields.clone());
+
+    IdeaRecord {
+        description: append_merged(&primary.description, &secondary.description, &secondary.title),
+        tags,
+        what_must_be_true,
+        development_notes: append_merged(
+            &primary.development_notes,
+            &secondary.development_notes,
+            &secondary.title,
+        ),
+        sections: [primary.sections.clone(), secondary.sections.clone()].concat(),
+        previous_slugs,
+        pinned: primary.pinned || secondary.pinned,
+        due_date,
+        icon: primary.icon.clone().or_else(|| secondary.icon.clone()),
+        custom_fields,
+        ..primary.clone()
+    }
+}
+
+/// Merge the idea `secondary_id` into `primary_id`, both owned by `user`, and return the merged idea.
+///
+/// The secondary is deleted and its attachments move to the primary in the same transaction. Both ideas must be
+/// outside the trash, and an idea can't be merged into itself.
+pub async fn merge_ideas(
+    repo: &impl IdeaRepository,
+    primary_id: &str,
+    secondary_id: &str,
+    user: Option<&Thing>,
+) -> Result<IdeaRecord, IdeaError> {
+    let primary = get_owned(repo, primary_id, user).await?;
+    let secondary = get_owned(repo, secondary_id, user).await?;
+    if primary.id == secondary.id {
+        return Err(IdeaError::Validation("An idea can't be merged into itself".to_string()));
+    }
+    if primary.deleted_at.is_some() || secondary.deleted_at.is_some() {
+        return Err(IdeaError::Validation("Restore the idea from the trash before merging it".to_string()));
+    }
+
+    let Some(primary_thing) = primary.id.clone() else {
+        return Err(IdeaError::NotFound(primary_id.to_string()));
+    };
+    let summary = format!("merged in \u{201c}{}\u{201d}", secondary.title);
+    let event = IdeaEventRecord::now(primary_thing, IdeaEventKind::Merged, summary);
+    repo.absorb(primary_id, merge_idea_fields(&primary, &secondary), secondary_id, event).await
+}
+
+/// What purging an idea owned by `user` would remove along with it
+pub async fn delete_impact(
+    repo: &impl IdeaRepository,
+    id: &str,
+    user: Option<&Thing>,
+) -> Result<DeleteImpact, IdeaError> {
+    get_owned(repo, id, user).await?;
+    repo.delete_impact(id).await
+}
+
+/// Permanently delete an idea, returning what went with it. Only ideas already in the trash can be purged.
+///
+/// Together with [`super::server::empty_trash`] and the trash retention of [`crate::maintenance`] this is the only
+/// place ideas are really deleted.
+pub async fn purge_idea(
+    repo: &impl IdeaRepository,
+    id: &str,
+    user: Option<&Thing>,
+) -> Result<DeleteImpact, IdeaError> {
+    let idea = get_owned(repo, id, user).await?;
+    if idea.deleted_at.is_none() {
+        return Err(IdeaError::Validation(
+            "Move the idea to the trash before deleting it for good".to_string(),
+        ));
+    }
+    repo.delete(id).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::MAX_TAG_CHARS;
+
+    fn user(name: &str) -> Thing {
+        Thing::from(("users", name))
+    }
+
+    fn id_of(idea: &IdeaRecord) -> String {
+        idea.id.as_ref().unwrap().to_string()
+    }
+
+    async fn submit(repo: &MockIdeaRepository, owner: Option<Thing>, title: &str) -> IdeaRecord {
+        submit_idea(repo, owner, title.to_string(), "Description".to_string(), vec![])
+            .await
+            .unwrap()
+    }
+
+    fn changes(title: &str) -> IdeaChanges {
+        IdeaChanges {
+            title: title.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_submit_assigns_slug_and_rank() {
+        let repo = MockIdeaRepository::new();
+
+        let first = submit(&repo, None, "Same Title").await;
+        let second = submit(&repo, None, "Same Title").await;
+
+        assert_eq!(first.slug, "same-title");
+        assert_eq!(second.slug, "same-title-2");
+        assert!(second.rank > first.rank);
+    }
+
+    #[tokio::test]
+    async fn test_empty_title_is_rejected() {
+        let repo = MockIdeaRepository::new();
+
+        let result = submit_idea(&repo, None, "   ".to_string(), String::new(), vec![]).await;
+        assert!(matches!(result, Err(IdeaError::Validation(_))));
+
+        let idea = submit(&repo, None, "Valid").await;
+        let result = edit_idea(&repo, &id_of(&idea), None, changes("")).await;
+        assert!(matches!(result, Err(IdeaError::Validation(_))));
+    }
+
+    #[tokio::test]
+    async fn test_get_reports_missing_and_malformed_ids() {
+        let repo = MockIdeaRepository::new();
+
+        assert_eq!(
+            repo.get("ideas:missing").await.unwrap_err(),
+            IdeaError::NotFound("ideas:missing".to_string())
+        );
+        assert!(matches!(repo.get("users:abc").await, Err(IdeaError::InvalidId(_))));
+        // A bare key is an id in the ideas table, so an unknown one isn't there rather than malformed
+        assert_eq!(repo.get("no-colon").await.unwrap_err(), IdeaError::NotFound("ideas:no-colon".to_string()));
+        assert!(matches!(repo.delete("ideas:missing").await, Err(IdeaError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_ownership_is_enforced() {
+        let repo = MockIdeaRepository::new();
+        let idea = submit(&repo, Some(user("alice")), "Alice's").await;
+        let id = id_of(&idea);
+
+        assert!(get_owned(&repo, &id, Some(&user("alice"))).await.is_ok());
+        assert_eq!(
+            get_owned(&repo, &id, Some(&user("bob"))).await.unwrap_err(),
+            IdeaError::Forbidden
+        );
+        assert_eq!(
+            edit_idea(&repo, &id, None, changes("Stolen")).await.unwrap_err(),
+            IdeaError::Forbidden
+        );
+        assert_eq!(trash_idea(&repo, &id, None).await.unwrap_err(), IdeaError::Forbidden);
+    }
+
+    fn batch(titles: &[&str]) -> Vec<Idea> {
+        titles
+            .iter()
+            .map(|title| Idea {
+                title: title.to_string(),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_submit_ideas_orders_and_deduplicates() {
+        let repo = MockIdeaRepository::new();
+        let existing = submit(&repo, None, "Twin").await;
+
+        let created = submit_ideas(&repo, None, batch(&["Twin", "Twin", "Solo"])).await.unwrap();
+
+        let slugs: Vec<&str> = created.iter().map(|idea| idea.slug.as_str()).collect();
+        assert_eq!(slugs, vec!["twin-2", "twin-3", "solo"]);
+        assert!(created[0].rank > existing.rank);
+        assert!(created.windows(2).all(|pair| pair[0].rank < pair[1].rank));
+        assert_eq!(repo.list(None).await.unwrap().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_batch_stores_nothing() {
+        let repo = MockIdeaRepository::new();
+
+        let result = submit_ideas(&repo, None, batch(&["Fine", "", "Also fine", " "])).await;
+        assert_eq!(
+            result.unwrap_err(),
+            IdeaError::Validation("idea 1: Title is required; idea 3: Title is required".to_string())
+        );
+        assert!(repo.list(None).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_edit_keeps_previous_slug() {
+        let repo = MockIdeaRepository::new();
+        let idea = submit(&repo, None, "Old Name").await;
+        let id = id_of(&idea);
+
+        let renamed = edit_idea(&repo, &id, None, changes("New Name")).await.unwrap();
+        assert_eq!(renamed.slug, "new-name");
+        assert_eq!(renamed.previous_slugs, vec!["old-name".to_string()]);
+        assert_eq!(renamed.rank, idea.rank);
+
+        let found = repo.find_by_slug("old-name").await.unwrap().unwrap();
+        assert_eq!(found.id, idea.id);
+    }
+
+    #[tokio::test]
+    async fn test_trash_restore_purge() {
+        let repo = MockIdeaRepository::new();
+        let idea = submit(&repo, None, "Round trip").await;
+        let id = id_of(&idea);
+
+        trash_idea(&repo, &id, None).await.unwrap();
+        assert!(repo.list(None).await.unwrap().is_empty());
+
+        restore_idea(&repo, &id, None).await.unwrap();
+        assert_eq!(repo.list(None).await.unwrap().len(), 1);
+
+        // Purging requires the idea to be in the trash first
+        assert!(matches!(
+            purge_idea(&repo, &id, None).await,
+            Err(IdeaError::Validation(_))
+        ));
+        trash_idea(&repo, &id, None).await.unwrap();
+        purge_idea(&repo, &id, None).await.unwrap();
+        assert!(matches!(repo.get(&id).await, Err(IdeaError::NotFound(_))));
+    }
+
+    async fn kinds(repo: &MockIdeaRepository, idea: &IdeaRecord) -> Vec<IdeaEventKind> {
+        let events = repo.history(idea.id.as_ref().unwrap()).await.unwrap();
+        events.into_iter().map(|event| event.kind).collect()
+    }
+
+    #[tokio::test]
+    async fn test_every_change_is_logged() {
+        let repo = MockIdeaRepository::new();
+        let idea = submit(&repo, None, "Logged").await;
+        let id = id_of(&idea);
+
+        let edited = IdeaChanges {
+            title: "Logged".to_string(),
+            description: "Description".to_string(),
+            development_notes: "First notes".to_string(),
+            ..Default::default()
+        };
+        edit_idea(&repo, &id, None, edited.clone()).await.unwrap();
+        // Saving the same content again isn't an edit
+        edit_idea(&repo, &id, None, edited).await.unwrap();
+        trash_idea(&repo, &id, None).await.unwrap();
+        restore_idea(&repo, &id, None).await.unwrap();
+
+        assert_eq!(
+            kinds(&repo, &idea).await,
+            vec![
+                IdeaEventKind::Restored,
+                IdeaEventKind::Trashed,
+                IdeaEventKind::Edited,
+                IdeaEventKind::Created
+            ]
+        );
+        let history = repo.history(idea.id.as_ref().unwrap()).await.unwrap();
+        assert_eq!(history[2].summary, "edited notes");
+    }
+
+    #[tokio::test]
+    async fn test_history_survives_trash_but_not_purge() {
+        let repo = MockIdeaRepository::new();
+        let idea = submit(&repo, None, "Short lived").await;
+        let other = submit(&repo, None, "Bystander").await;
+        let id = id_of(&idea);
+
+        trash_idea(&repo, &id, None).await.unwrap();
+        assert_eq!(kinds(&repo, &idea).await.len(), 2);
+
+        purge_idea(&repo, &id, None).await.unwrap();
+        assert!(kinds(&repo, &idea).await.is_empty());
+        assert_eq!(kinds(&repo, &other).await, vec![IdeaEventKind::Created]);
+    }
+
+    #[tokio::test]
+    async fn test_append_wmbt() {
+        let repo = MockIdeaRepository::new();
+        let idea = submit(&repo, Some(user("alice")), "Grow tomatoes").await;
+        let id = id_of(&idea);
+
+        let updated = append_wmbt(&repo, &id, Some(&user("alice")), "  Enough sun  ").await.unwrap();
+        assert_eq!(updated.what_must_be_true, vec!["Enough sun"]);
+        let updated = append_wmbt(&repo, &id, Some(&user("alice")), "Water nearby").await.unwrap();
+        assert_eq!(updated.what_must_be_true, vec!["Enough sun", "Water nearby"]);
+        assert_eq!(repo.history(idea.id.as_ref().unwrap()).await.unwrap()[0].summary, "added 1 statement");
+
+        assert!(matches!(
+            append_wmbt(&repo, &id, Some(&user("alice")), "  ").await,
+            Err(IdeaError::Validation(_))
+        ));
+        assert_eq!(
+            append_wmbt(&repo, &id, Some(&user("bob")), "Mine now").await.unwrap_err(),
+            IdeaError::Forbidden
+        );
+        assert_eq!(repo.get(&id).await.unwrap().what_must_be_true.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_set_status_keeps_rank() {
+        let repo = MockIdeaRepository::new();
+        let idea = submit(&repo, Some(user("alice")), "Move me").await;
+        let id = id_of(&idea);
+        assert_eq!(idea.status, IdeaStatus::Inbox);
+
+        let moved = set_status(&repo, &id, Some(&user("alice")), IdeaStatus::Building).await.unwrap();
+        assert_eq!(moved.status, IdeaStatus::Building);
+        assert_eq!(moved.rank, idea.rank);
+        // Editing the content keeps the status
+        let edited = edit_idea(&repo, &id, Some(&user("alice")), changes("Still building")).await.unwrap();
+        assert_eq!(edited.status, IdeaStatus::Building);
+        assert_eq!(repo.history(idea.id.as_ref().unwrap()).await.unwrap()[1].summary, "moved to building");
+
+        // Moving to the same column logs nothing
+        set_status(&repo, &id, Some(&user("alice")), IdeaStatus::Building).await.unwrap();
+        assert_eq!(repo.history(idea.id.as_ref().unwrap()).await.unwrap().len(), 3);
+        assert_eq!(
+            set_status(&repo, &id, Some(&user("bob")), IdeaStatus::Done).await.unwrap_err(),
+            IdeaError::Forbidden
+        );
+    }
+
+    #[tokio::test]
+    async fn test_set_visibility_is_kept_by_edits() {
+        let repo = MockIdeaRepository::new();
+        let idea = submit(&repo, Some(user("alice")), "Show me").await;
+        let id = id_of(&idea);
+        assert_eq!(idea.visibility, Visibility::Private);
+
+        let shown = set_visibility(&repo, &id, Some(&user("alice")), Visibility::Public).await.unwrap();
+        assert_eq!(shown.visibility, Visibility::Public);
+        let edited = edit_idea(&repo, &id, Some(&user("alice")), changes("Still shown")).await.unwrap();
+        assert_eq!(edited.visibility, Visibility::Public);
+
+        // Setting it again logs nothing
+        set_visibility(&repo, &id, Some(&user("alice")), Visibility::Public).await.unwrap();
+        let history = repo.history(idea.id.as_ref().unwrap()).await.unwrap();
+        assert_eq!(history.len(), 3);
+        assert!(history.iter().any(|event| event.summary == "made public"));
+        assert_eq!(
+            set_visibility(&repo, &id, Some(&user("bob")), Visibility::Private).await.unwrap_err(),
+            IdeaError::Forbidden
+        );
+    }
+
+    #[tokio::test]
+    async fn test_set_icon_takes_one_emoji() {
+        let repo = MockIdeaRepository::new();
+        let idea = submit(&repo, Some(user("alice")), "Coding club").await;
+        let id = id_of(&idea);
+        let owner = user("alice");
+        let alice = Some(&owner);
+
+        let iconed = set_icon(&repo, &id, alice, Some(" 👩‍💻 ".to_string())).await.unwrap();
+        assert_eq!(iconed.icon.as_deref(), Some("👩‍💻"));
+        assert_eq!(repo.history(idea.id.as_ref().unwrap()).await.unwrap()[0].summary, "icon 👩‍💻");
+
+        for icon in ["ab", "💡💡", "x"] {
+            assert!(matches!(
+                set_icon(&repo, &id, alice, Some(icon.to_string())).await,
+                Err(IdeaError::Validation(_))
+            ));
+        }
+        assert!(matches!(
+            repo.merge(&id, serde_json::json!({ "icon": "no" })).await,
+            Err(IdeaError::Validation(_))
+        ));
+        assert_eq!(
+            set_icon(&repo, &id, Some(&user("bob")), None).await.unwrap_err(),
+            IdeaError::Forbidden
+        );
+
+        // Editing the content keeps the icon, and a blank one removes it
+        let edited = edit_idea(&repo, &id, alice, changes("Coding club")).await.unwrap();
+        assert_eq!(edited.icon.as_deref(), Some("👩‍💻"));
+        assert_eq!(set_icon(&repo, &id, alice, Some(" ".to_string())).await.unwrap().icon, None);
+    }
+
+    #[tokio::test]
+    async fn test_toggle_pin() {
+        let repo = MockIdeaRepository::new();
+        let idea = submit(&repo, Some(user("alice")), "Pin me").await;
+        let id = id_of(&idea);
+        assert!(!idea.pinned);
+
+        let pinned = toggle_pin(&repo, &id, Some(&user("alice"))).await.unwrap();
+        assert!(pinned.pinned);
+        // Editing the content keeps the pin
+        let edited = edit_idea(&repo, &id, Some(&user("alice")), changes("Still pinned")).await.unwrap();
+        assert!(edited.pinned);
+
+        assert_eq!(
+            toggle_pin(&repo, &id, Some(&user("bob"))).await.unwrap_err(),
+            IdeaError::Forbidden
+        );
+        let unpinned = toggle_pin(&repo, &id, Some(&user("alice"))).await.unwrap();
+        assert!(!unpinned.pinned);
+        assert_eq!(kinds(&repo, &idea).await[..2], [IdeaEventKind::Unpinned, IdeaEventKind::Edited]);
+    }
+
+    #[tokio::test]
+    async fn test_touch_idea_only_logs_an_event() {
+        let repo = MockIdeaRepository::new();
+        let owner = user("alice");
+        let alice = Some(&owner);
+        let idea = submit(&repo, Some(owner.clone()), "Look at me").await;
+        let id = id_of(&idea);
+        let thing = idea.id.clone().unwrap();
+        let before = Idea::from(repo.get(&id).await.unwrap());
+
+        let event = touch_idea(&repo, &id, alice).await.unwrap();
+        assert_eq!((event.kind, event.idea.clone()), (IdeaEventKind::Reviewed, thing.clone()));
+        assert_eq!(Idea::from(repo.get(&id).await.unwrap()), before);
+        assert!(repo.revisions(&thing).await.unwrap().is_empty());
+        assert_eq!(repo.history(&thing).await.unwrap()[0].at, event.at);
+
+        assert_eq!(touch_idea(&repo, &id, Some(&user("bob"))).await.unwrap_err(), IdeaError::Forbidden);
+        trash_idea(&repo, &id, alice).await.unwrap();
+        assert!(matches!(touch_idea(&repo, &id, alice).await, Err(IdeaError::Validation(_))));
+    }
+
+    #[tokio::test]
+    async fn test_change_tags_adds_and_removes_one_tag() {
+        let repo = MockIdeaRepository::new();
+        let owner = user("alice");
+        let alice = Some(&owner);
+        let idea = submit(&repo, Some(owner.clone()), "Tag me").await;
+        let id = id_of(&idea);
+        let add = |tag: &str| TagChange::Add(tag.to_string());
+
+        let added = change_tags(&repo, &id, alice, add(" Web ")).await.unwrap();
+        assert_eq!(added.tags, vec!["Web"]);
+        let added = change_tags(&repo, &id, alice, add("api")).await.unwrap();
+        assert_eq!(added.tags, vec!["api", "Web"]);
+        let history = repo.history(idea.id.as_ref().unwrap()).await.unwrap();
+        assert_eq!((history[0].kind, history[0].summary.as_str()), (IdeaEventKind::Edited, "added tag api"));
+
+        // Neither a tag it already has nor removing one it lacks is a change worth logging
+        let events = kinds(&repo, &idea).await.len();
+        change_tags(&repo, &id, alice, add("WEB")).await.unwrap();
+        change_tags(&repo, &id, alice, TagChange::Remove("ux".to_string())).await.unwrap();
+        assert_eq!(kinds(&repo, &idea).await.len(), events);
+
+        let removed = change_tags(&repo, &id, alice, TagChange::Remove("web".to_string())).await.unwrap();
+        assert_eq!(removed.tags, vec!["api"]);
+        assert_eq!(
+            change_tags(&repo, &id, Some(&user("bob")), add("mine")).await.unwrap_err(),
+            IdeaError::Forbidden
+        );
+        let too_long = add(&"x".repeat(MAX_TAG_CHARS + 1));
+        assert!(matches!(change_tags(&repo, &id, alice, too_long).await, Err(IdeaError::Validation(_))));
+        assert_eq!(repo.get(&id).await.unwrap().tags, vec!["api"]);
+    }
+
+    #[tokio::test]
+    async fn test_merge_only_touches_given_fields() {
+        let repo = MockIdeaRepository::new();
+        let idea = submit(&repo, None, "Merge me").await;
+        let id = id_of(&idea);
+
+        let merged = repo
+            .merge(&id, serde_json::json!({ "development_notes": "notes" }))
+            .await
+            .unwrap();
+        assert_eq!(merged.development_notes, "notes");
+        assert_eq!(merged.title, "Merge me");
+        assert_eq!(merged.id, idea.id);
+
+        assert!(matches!(
+            repo.merge(&id, serde_json::json!(["not", "an", "object"])).await,
+            Err(IdeaError::Validation(_))
+        ));
+        assert!(matches!(
+            repo.merge(&id, serde_json::json!({ "rank": "high" })).await,
+            Err(IdeaError::Validation(_))
+        ));
+    }
+
+    fn record(title: &str, slug: &str) -> IdeaRecord {
+        IdeaRecord {
+            id: Some(Thing::from(("ideas", slug))),
+            title: title.to_string(),
+            description: String::new(),
+            tags: Vec::new(),
+            what_must_be_true: Vec::new(),
+            development_notes: String::new(),
+            sections: Vec::new(),
+            slug: slug.to_string(),
+            previous_slugs: Vec::new(),
+            rank: 1.0,
+            owner: None,
+            deleted_at: None,
+            pinned: false,
+            due_date: None,
+            status: IdeaStatus::default(),
+            visibility: Visibility::default(),
+            author: None,
+            icon: None,
+            custom_fields: HashMap::new(),
+            created_at: None,
+            board: None,
+        }
+    }
+
+    #[test]
+    fn test_merge_idea_fields() {
+        let date = |day: u32| NaiveDate::from_ymd_opt(2024, 6, day);
+        let primary = IdeaRecord {
+            description: "Grow herbs".to_string(),
+            tags: vec!["garden".to_string()],
+            what_must_be_true: vec!["Enough light".to_string()],
+            due_date: date(20),
+            ..record("Herb box", "herb-box")
+        };
+        let secondary = IdeaRecord {
+            description: "Grow basil".to_string(),
+            tags: vec!["Garden".to_string(), "kitchen".to_string()],
+            what_must_be_true: vec![" Enough light".to_string(), "Someone waters it".to_string()],
+            development_notes: "Ask the neighbours".to_string(),
+            previous_slugs: vec!["basil".to_string()],
+            pinned: true,
+            due_date: date(12),
+            rank: 9.0,
+            ..record("Basil pot", "basil-pot")
+        };
+
+        let merged = merge_idea_fields(&primary, &secondary);
+        assert_eq!(merged.id, primary.id);
+        assert_eq!(merged.title, "Herb box");
+        assert_eq!(merged.slug, "herb-box");
+        assert_eq!(merged.rank, 1.0);
+        assert_eq!(merged.description, "Grow herbs\n\n## merged from Basil pot\n\nGrow basil");
+        assert_eq!(merged.development_notes, "## merged from Basil pot\n\nAsk the neighbours");
+        assert_eq!(merged.tags, vec!["garden", "kitchen"]);
+        assert_eq!(merged.what_must_be_true, vec!["Enough light", "Someone waters it"]);
+        assert_eq!(merged.previous_slugs, vec!["basil-pot", "basil"]);
+        assert!(merged.pinned);
+        assert_eq!(merged.due_date, date(12));
+
+        // Nothing to carry over leaves the text alone
+        let empty = record("Empty", "empty");
+        let merged = merge_idea_fields(&primary, &empty);
+        assert_eq!(merged.description, "Grow herbs");
+        assert_eq!(merged.development_notes, "");
+        assert_eq!(merged.due_date, date(20));
+    }
+
+    #[tokio::test]
+    async fn test_merge_ideas() {
+        let repo = MockIdeaRepository::new();
+        let alice = Some(user("alice"));
+        let primary = submit(&repo, alice.clone(), "Herb box").await;
+        let secondary = submit(&repo, alice.clone(), "Basil pot").await;
+        let (primary_id, secondary_id) = (id_of(&primary), id_of(&secondary));
+
+        let merged = merge_ideas(&repo, &primary_id, &secondary_id, alice.as_ref()).await.unwrap();
+        assert_eq!(merged.id, primary.id);
+        assert!(merged.description.contains("## merged from Basil pot"));
+        assert!(matches!(repo.get(&secondary_id).await, Err(IdeaError::NotFound(_))));
+        // Links to the merged idea lead to the one it was merged into
+        assert_eq!(repo.find_by_slug("basil-pot").await.unwrap().unwrap().id, primary.id);
+
+        let history = repo.history(primary.id.as_ref().unwrap()).await.unwrap();
+        assert_eq!(history[0].kind, IdeaEventKind::Merged);
+        assert_eq!(history[0].summary, "merged in \u{201c}Basil pot\u{201d}");
+        assert!(kinds(&repo, &secondary).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_merge_ideas_is_refused() {
+        let repo = MockIdeaRepository::new();
+        let alice = Some(user("alice"));
+        let idea = submit(&repo, alice.clone(), "Mine").await;
+        let trashed = submit(&repo, alice.clone(), "Trashed").await;
+        let foreign = submit(&repo, Some(user("bob")), "Bob's").await;
+        trash_idea(&repo, &id_of(&trashed), alice.as_ref()).await.unwrap();
+
+        let id = id_of(&idea);
+        assert!(matches!(
+            merge_ideas(&repo, &id, &id, alice.as_ref()).await,
+            Err(IdeaError::Validation(_))
+        ));
+        assert!(matches!(
+            merge_ideas(&repo, &id, &id_of(&trashed), alice.as_ref()).await,
+            Err(IdeaError::Validation(_))
+        ));
+        assert_eq!(
+            merge_ideas(&repo, &id, &id_of(&foreign), alice.as_ref()).await.unwrap_err(),
+            IdeaError::Forbidden
+        );
+        assert_eq!(repo.list(alice.as_ref()).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_similar_ideas() {
+        let repo = MockIdeaRepository::new();
+        submit(&repo, None, "Recipe planner").await;
+        submit(&repo, None, "Recipe planer").await;
+        submit(&repo, None, "Garden").await;
+        submit(&repo, Some(user("alice")), "Recipe planner").await;
+        let trashed = submit(&repo, None, "Recipe planners").await;
+        trash_idea(&repo, &id_of(&trashed), None).await.unwrap();
+
+        let similar = similar_ideas(&repo, None, "recipe  Planner!").await.unwrap();
+        let titles: Vec<&str> = similar.iter().map(|idea| idea.title.as_str()).collect();
+        assert_eq!(titles, vec!["Recipe planner", "Recipe planer"]);
+        assert!(similar_ideas(&repo, None, "Bike repair").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_search_is_case_insensitive_and_scoped() {
+        let repo = MockIdeaRepository::new();
+        submit(&repo, None, "Rust Compiler").await;
+        submit(&repo, None, "Garden").await;
+        submit(&repo, Some(user("alice")), "Rusty bike").await;
+
+        let found = repo.search(None, "rust").await.unwrap();
+        let titles: Vec<&str> = found.iter().map(|idea| idea.title.as_str()).collect();
+        assert_eq!(titles, vec!["Rust Compiler"]);
+
+        assert_eq!(repo.search(None, "  ").await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_database_failures_keep_their_shape() {
+        let repo = MockIdeaRepository::failing("disk full");
+
+        let result = submit_idea(&repo, None, "Anything".to_string(), String::new(), vec![]).await;
+        assert_eq!(result.unwrap_err(), IdeaError::Database("disk full".to_string()));
+        assert_eq!(
+            repo.list(None).await.unwrap_err(),
+            IdeaError::Database("disk full".to_string())
+        );
+    }
+
+    fn notes(title: &str, notes: &str) -> IdeaChanges {
+        IdeaChanges {
+            development_notes: notes.to_string(),
+            ..changes(title)
+        }
+    }
+
+    /// Move the newest snapshot of `idea` back in time, so the next save starts a new one
+    async fn age_newest_revision(repo: &MockIdeaRepository, idea: &Thing) {
+        let newest = repo.revisions(idea).await.unwrap().remove(0);
+        let at = newest.at - chrono::Duration::hours(1);
+        repo.save_revision(IdeaRevisionRecord { at, ..newest }).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_saves_in_one_sitting_share_a_snapshot() {
+        let repo = MockIdeaRepository::new();
+        let idea = submit(&repo, None, "Drafts").await;
+        let id = id_of(&idea);
+        let thing = idea.id.clone().unwrap();
+
+        // Only notes and statements are snapshotted
+        edit_idea(&repo, &id, None, changes("Renamed")).await.unwrap();
+        assert!(repo.revisions(&thing).await.unwrap().is_empty());
+
+        edit_idea(&repo, &id, None, notes("Renamed", "F")).await.unwrap();
+        edit_idea(&repo, &id, None, notes("Renamed", "First draft")).await.unwrap();
+        let revisions = repo.revisions(&thing).await.unwrap();
+        assert_eq!(revisions.len(), 1);
+        assert_eq!(revisions[0].development_notes, "First draft");
+
+        age_newest_revision(&repo, &thing).await;
+        edit_idea(&repo, &id, None, notes("Renamed", "Second draft")).await.unwrap();
+        let notes: Vec<String> = repo
+            .revisions(&thing)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|revision| revision.development_notes)
+            .collect();
+        assert_eq!(notes, vec!["Second draft", "First draft"]);
+    }
+
+    #[tokio::test]
+    async fn test_revisions_are_capped() {
+        let repo = MockIdeaRepository::new();
+        let idea = submit(&repo, None, "Busy").await;
+        let other = submit(&repo, None, "Quiet").await;
+        let thing = idea.id.clone().unwrap();
+        repo.save_revision(IdeaRevisionRecord::now(other.id.clone().unwrap(), vec![], "Keep me".to_string()))
+            .await
+            .unwrap();
+
+        for n in 0..MAX_REVISIONS + 5 {
+            let revision = IdeaRevisionRecord::now(thing.clone(), vec![], format!("version {}", n));
+            repo.save_revision(revision).await.unwrap();
+        }
+
+        let revisions = repo.revisions(&thing).await.unwrap();
+        assert_eq!(revisions.len(), MAX_REVISIONS);
+        assert_eq!(revisions[0].development_notes, format!("version {}", MAX_REVISIONS + 4));
+        assert_eq!(revisions[MAX_REVISIONS - 1].development_notes, "version 5");
+        // Other ideas keep their snapshots
+        assert_eq!(repo.revisions(other.id.as_ref().unwrap()).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_restore_revision_takes_a_snapshot() {
+        let repo = MockIdeaRepository::new();
+        let idea = submit(&repo, None, "Regrets").await;
+        let id = id_of(&idea);
+        let thing = idea.id.clone().unwrap();
+
+        let mut first = notes("Regrets", "Intro\n\nThe paragraph I want back");
+        first.what_must_be_true = vec!["People care".to_string()];
+        first.description = "Description".to_string();
+        edit_idea(&repo, &id, None, first).await.unwrap();
+        age_newest_revision(&repo, &thing).await;
+        let mut second = notes("Regrets", "Intro");
+        second.description = "Description".to_string();
+        edit_idea(&repo, &id, None, second).await.unwrap();
+
+        let revisions = idea_revisions(&repo, &id, None).await.unwrap();
+        let wanted = revisions[1].id.as_ref().unwrap().to_string();
+        let restored = restore_revision(&repo, &id, &wanted, None).await.unwrap();
+        assert_eq!(restored.development_notes, "Intro\n\nThe paragraph I want back");
+        assert_eq!(restored.what_must_be_true, vec!["People care"]);
+        assert_eq!(restored.description, "Description");
+
+        // The restore is a snapshot of its own even right after the last save
+        let revisions = idea_revisions(&repo, &id, None).await.unwrap();
+        assert_eq!(revisions.len(), 3);
+        assert_eq!(revisions[0].development_notes, restored.development_notes);
+
+        let missing = restore_revision(&repo, &id, "idea_revisions:nope", None).await;
+        assert_eq!(missing.unwrap_err(), IdeaError::NotFound("idea_revisions:nope".to_string()));
+        let stranger = restore_revision(&repo, &id, &wanted, Some(&user("mallory"))).await;
+        assert_eq!(stranger.unwrap_err(), IdeaError::Forbidden);
+    }
+
+    #[tokio::test]
+    async fn test_first_snapshot_keeps_the_text_from_before() {
+        let repo = MockIdeaRepository::new();
+        let content = IdeaChanges {
+            development_notes: "Written from a template".to_string(),
+            ..changes("Templated")
+        };
+        let idea = submit_idea_content(&repo, None, None, content).await.unwrap();
+        let id = id_of(&idea);
+
+        edit_idea(&repo, &id, None, notes("Templated", "")).await.unwrap();
+        let notes: Vec<String> = idea_revisions(&repo, &id, None)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|revision| revision.development_notes)
+            .collect();
+        assert_eq!(notes, vec!["", "Written from a template"]);
+
+        trash_idea(&repo, &id, None).await.unwrap();
+        purge_idea(&repo, &id, None).await.unwrap();
+        assert!(repo.revisions(idea.id.as_ref().unwrap()).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_author_is_tidied_and_kept_by_edits() {
+        let repo = MockIdeaRepository::new();
+        let named = submit_idea_content(&repo, None, Some("  Sam ".to_string()), changes("Named")).await.unwrap();
+        assert_eq!(named.author.as_deref(), Some("Sam"));
+        let blank = submit_idea_content(&repo, None, Some("   ".to_string()), changes("Blank")).await.unwrap();
+        assert_eq!(blank.author, None);
+
+        let edited = edit_idea(&repo, &id_of(&named), None, changes("Renamed")).await.unwrap();
+        assert_eq!(edited.author.as_deref(), Some("Sam"));
+
+        let imported = submit_ideas(
+            &repo,
+            None,
+            vec![Idea { title: "Imported".to_string(), author: Some(" ".to_string()), ..Default::default() }],
+        )
+        .await
+        .unwrap();
+        assert_eq!(imported[0].author, None);
+    }
+
+    #[tokio::test]
+    async fn test_every_write_path_sanitizes_text() {
+        let repo = MockIdeaRepository::new();
+        let payload = "<img src=x onerror=alert(1)>";
+
+        let content = IdeaChanges {
+            title: "<b>Parser</b> for Vec<String>".to_string(),
+            description: format!("Fast{}<script>alert('x')</script>", payload),
+            tags: vec!["<i>rust</i>".to_string(), "<br>".to_string()],
+            what_must_be_true: vec!["<a href=\"javascript:alert(1)\">links</a> work".to_string()],
+            development_notes: "Try `<br>` in code\u{7}".to_string(),
+            due_date: None,
+            sections: None,
+        };
+        let created = submit_idea_content(&repo, None, Some("<u>Sam</u>".to_string()), content).await.unwrap();
+        assert_eq!(created.title, "Parser for Vec<String>");
+        assert_eq!(created.slug, "parser-for-vec-string");
+        assert_eq!(created.description, "Fast");
+        assert_eq!(created.tags, vec!["rust"]);
+        assert_eq!(created.what_must_be_true, vec!["links work"]);
+        assert_eq!(created.development_notes, "Try `<br>` in code");
+        assert_eq!(created.author.as_deref(), Some("Sam"));
+        let id = id_of(&created);
+
+        // Edits, as sent by the app and the REST API
+        let edited = edit_idea(&repo, &id, None, changes(&format!("Renamed{}", payload))).await.unwrap();
+        assert_eq!(edited.title, "Renamed");
+
+        // Patches straight to the repository
+        let patched = repo
+            .merge(&id, serde_json::json!({ "description": payload, "tags": ["<b></b>", "ok"] }))
+            .await
+            .unwrap();
+        assert_eq!(patched.description, "");
+        assert_eq!(patched.tags, vec!["ok"]);
+
+        // Appended statements, and one that is nothing but markup
+        let appended = append_wmbt(&repo, &id, None, "<svg/onload=alert(1)>Users care").await.unwrap();
+        assert_eq!(appended.what_must_be_true.last().map(String::as_str), Some("Users care"));
+        assert!(matches!(append_wmbt(&repo, &id, None, payload).await, Err(IdeaError::Validation(_))));
+
+        // Batches and imports, where a title made only of markup is as missing as an empty one
+        let imported = submit_ideas(
+            &repo,
+            None,
+            vec![Idea { title: "<script>x</script>Kept".to_string(), ..Default::default() }],
+        )
+        .await
+        .unwrap();
+        assert_eq!(imported[0].title, "Kept");
+        let result = submit_ideas(&repo, None, vec![Idea { title: payload.to_string(), ..Default::default() }]).await;
+        assert!(matches!(result, Err(IdeaError::Validation(message)) if message == "idea 0: Title is required"));
+    }
+
+    #[tokio::test]
+    async fn test_every_write_path_normalizes_whitespace() {
+        let repo = MockIdeaRepository::new();
+        let content = IdeaChanges {
+            title: "  Ship \t the\r\n  garden ".to_string(),
+            description: "line one\r\nline two\rline three\n".to_string(),
+            tags: vec![" ops ".to_string(), "   ".to_string()],
+            what_must_be_true: vec!["  soil ".to_string(), "  ".to_string(), "\tsun".to_string()],
+            development_notes: "  indented\r\n".to_string(),
+            due_date: None,
+            sections: None,
+        };
+        let created = submit_idea_content(&repo, None, None, content).await.unwrap();
+        assert_eq!(created.title, "Ship the garden");
+        assert_eq!(created.description, "line one\nline two\nline three\n");
+        assert_eq!(created.tags, vec!["ops"]);
+        assert_eq!(created.what_must_be_true, vec!["soil", "sun"]);
+        // Only the line endings of notes change
+        assert_eq!(created.development_notes, "  indented\n");
+        let id = id_of(&created);
+
+        // A title that only differs by spacing isn't a rename
+        let edited = edit_idea(&repo, &id, None, changes("Ship the  garden  ")).await.unwrap();
+        assert_eq!(edited.title, "Ship the garden");
+        assert_eq!(edited.slug, created.slug);
+        assert!(edited.previous_slugs.is_empty());
+
+        let patched = repo
+            .merge(
+                &id,
+                serde_json::json!({
+                    "title": " Patched \n title ",
+                    "development_notes": "a\r\nb",
+                    "what_must_be_true": [" kept ", " "],
+                }),
+            )
+            .await
+            .unwrap();
+        assert_eq!(patched.title, "Patched title");
+        assert_eq!(patched.development_notes, "a\nb");
+        assert_eq!(patched.what_must_be_true, vec!["kept"]);
+
+        let appended = repo.append_statement(&id, "  trailing  ".to_string()).await.unwrap();
+        assert_eq!(appended.what_must_be_true.last().map(String::as_str), Some("trailing"));
+
+        let imported = submit_ideas(&repo, None, vec![Idea { title: " Imported\r\n".to_string(), ..Default::default() }])
+            .await
+            .unwrap();
+        assert_eq!(imported[0].title, "Imported");
+    }
+
+    #[tokio::test]
+    async fn test_custom_fields_are_sanitized_kept_by_edits_and_merged() {
+        let fields = |pairs: &[(&str, serde_json::Value)]| -> HashMap<String, serde_json::Value> {
+            pairs.iter().map(|(name, value)| (name.to_string(), value.clone())).collect()
+        };
+        let repo = MockIdeaRepository::new();
+        let values = fields(&[("sponsor", serde_json::json!("<b>Sam</b>")), ("estimate", serde_json::json!(3))]);
+        let created = submit_idea_with_fields(&repo, None, None, changes("Custom"), values).await.unwrap();
+        assert_eq!(created.custom_fields, fields(&[("sponsor", "Sam".into()), ("estimate", 3.into())]));
+
+        let edited = edit_idea(&repo, &id_of(&created), None, changes("Renamed")).await.unwrap();
+        assert_eq!(edited.custom_fields, created.custom_fields);
+
+        let secondary = IdeaRecord {
+            custom_fields: fields(&[("sponsor", "Alex".into()), ("team", "ops".into())]),
+            ..record("Other", "other")
+        };
+        let merged = merge_idea_fields(&edited, &secondary);
+        assert_eq!(
+            merged.custom_fields,
+            fields(&[("sponsor", "Sam".into()), ("estimate", 3.into()), ("team", "ops".into())])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_boards_never_see_each_others_ideas() {
+        use crate::boards::server::{board_thing, default_board};
+
+        let shared = MockIdeaRepository::new();
+        let work = shared.clone().in_board(board_thing("work"));
+        let home = shared.clone().in_board(board_thing("home"));
+        let fallback = shared.clone().in_board(default_board());
+
+        let report = submit(&work, None, "Quarterly report").await;
+        submit(&home, None, "Paint the fence").await;
+        assert_eq!(report.board, Some(board_thing("work")));
+
+        // Stored before boards existed, so it belongs to the default board
+        let legacy = record("Legacy report", "legacy-report");
+        shared.lock().insert(IdeaId::from(legacy.id.as_ref().unwrap()).into(), legacy);
+
+        let titles = |ideas: Vec<IdeaRecord>| ideas.into_iter().map(|idea| idea.title).collect::<Vec<_>>();
+        assert_eq!(titles(work.list(None).await.unwrap()), ["Quarterly report"]);
+        assert_eq!(titles(home.list(None).await.unwrap()), ["Paint the fence"]);
+        assert_eq!(titles(fallback.list(None).await.unwrap()), ["Legacy report"]);
+        assert_eq!(titles(home.search(None, "report").await.unwrap()), Vec::<String>::new());
+        assert_eq!(titles(work.search(None, "report").await.unwrap()), ["Quarterly report"]);
+        assert_eq!(shared.list(None).await.unwrap().len(), 3);
+    }
+}
@@ -0,0 +1,151 @@
+//! Which storage engine the server opens.
+//!
+//! Engines are compiled in with cargo features: `storage-rocksdb` (the default), `storage-surrealkv` and
+//! `storage-tikv`. `DATABASE_URL` overrides the choice with any endpoint the build supports, such as a remote
+//! `ws://` server or a `tikv://` cluster, and `DB_USER`/`DB_PASS` sign in as root when set. Without it the server
+//! opens the first compiled-in local engine at the path from [`crate::data_dir`]. The in-memory engine is always
+//! compiled in, for tests.
+
+use crate::error::IdeaError;
+use std::path::PathBuf;
+use surrealdb::{engine::any::Any, opt::auth::Root, Surreal};
+
+#[cfg(not(any(feature = "storage-rocksdb", feature = "storage-surrealkv", feature = "storage-tikv")))]
+compile_error!(
+    "the server needs a storage engine: enable one of the `storage-rocksdb`, `storage-surrealkv` or `storage-tikv` \
+     features (`storage-rocksdb` is in the default features)"
+);
+
+/// Environment variable holding the endpoint to connect to instead of the local engine
+pub const DATABASE_URL_VAR: &str = "DATABASE_URL";
+/// Environment variables holding the root credentials for [`DATABASE_URL_VAR`]
+pub const DB_USER_VAR: &str = "DB_USER";
+pub const DB_PASS_VAR: &str = "DB_PASS";
+
+/// Endpoint of the in-memory engine
+pub const MEM_ENDPOINT: &str = "mem://";
+
+/// TiKV placement driver used when `storage-tikv` is the only engine and no `DATABASE_URL` is set
+pub const DEFAULT_TIKV_ENDPOINT: &str = "tikv://127.0.0.1:2379";
+
+/// Local engines in order of preference, paired with whether this build has them
+const LOCAL_ENGINES: &[(&str, bool)] = &[
+    ("rocksdb", cfg!(feature = "storage-rocksdb")),
+    ("surrealkv", cfg!(feature = "storage-surrealkv")),
+];
+
+/// Where to connect and as whom
+#[derive(Debug, Clone, PartialEq)]
+pub struct StorageSettings {
+    pub endpoint: String,
+    /// Root user name and password
+    pub credentials: Option<(String, String)>,
+}
+
+impl StorageSettings {
+    /// The in-memory engine, empty on every connect
+    pub fn memory() -> Self {
+        Self {
+            endpoint: MEM_ENDPOINT.to_string(),
+            credentials: None,
+        }
+    }
+
+    /// Settings from the environment as seen through `lookup`. `db_path` gives the on-disk path and is only called
+    /// when a local engine is used.
+    pub fn from_lookup(
+        lookup: impl Fn(&str) -> Option<String>,
+        db_path: impl FnOnce() -> std::io::Result<PathBuf>,
+    ) -> Result<Self, IdeaError> {
+        let value = |key: &str| lookup(key).map(|value| value.trim().to_string()).filter(|value| !value.is_empty());
+        let credentials = value(DB_USER_VAR).zip(value(DB_PASS_VAR));
+
+        if let Some(endpoint) = value(DATABASE_URL_VAR) {
+            return Ok(Self { endpoint, credentials });
+        }
+        let endpoint = match LOCAL_ENGINES.iter().find(|(_, enabled)| *enabled) {
+            Some((scheme, _)) => {
+                let path = db_path().map_err(|e| IdeaError::Database(format!("no data folder: {}", e)))?;
+                format!("{}://{}", scheme, path.display())
+            }
+            None => DEFAULT_TIKV_ENDPOINT.to_string(),
+        };
+        Ok(Self { endpoint, credentials })
+    }
+
+    /// Settings of this process
+    pub fn from_env() -> Result<Self, IdeaError> {
+        Self::from_lookup(|key| std::env::var(key).ok(), crate::data_dir::prepare_db_path)
+    }
+}
+
+/// Connect to `settings`, select `namespace` and `database` and define the schema
+pub async fn connect_with(
+    settings: &StorageSettings,
+    namespace: &str,
+    database: &str,
+) -> Result<Surreal<Any>, IdeaError> {
+    let db = surrealdb::engine::any::connect(settings.endpoint.as_str()).await?;
+    if let Some((username, password)) = &settings.credentials {
+        db.signin(Root { username, password }).await?;
+    }
+    db.use_ns(namespace).use_db(database).await?;
+    super::server::define_schema(&db).await?;
+
+    Ok(db)
+}
+
+/// Connect to the database this process is configured for, as described in the module docs
+pub async fn connect() -> Result<Surreal<Any>, IdeaError> {
+    connect_with(&StorageSettings::from_env()?, "ideas_ns", "ideas_db").await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn settings(vars: &[(&str, &str)]) -> Result<StorageSettings, IdeaError> {
+        let vars: HashMap<String, String> = vars.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        StorageSettings::from_lookup(|key| vars.get(key).cloned(), || Ok(PathBuf::from("/data/ideas.db")))
+    }
+
+    #[test]
+    fn test_database_url_wins() {
+        let settings = settings(&[(DATABASE_URL_VAR, " ws://db:8000 "), (DB_USER_VAR, "root"), (DB_PASS_VAR, "pw")]);
+        assert_eq!(
+            settings.unwrap(),
+            StorageSettings {
+                endpoint: "ws://db:8000".to_string(),
+                credentials: Some(("root".to_string(), "pw".to_string())),
+            }
+        );
+    }
+
+    #[test]
+    fn test_credentials_need_both_parts() {
+        let settings = settings(&[(DATABASE_URL_VAR, "ws://db:8000"), (DB_USER_VAR, "root")]).unwrap();
+        assert_eq!(settings.credentials, None);
+    }
+
+    #[test]
+    fn test_local_engine_without_url() {
+        let endpoint = settings(&[(DATABASE_URL_VAR, "  ")]).unwrap().endpoint;
+        if cfg!(feature = "storage-rocksdb") {
+            assert_eq!(endpoint, "rocksdb:///data/ideas.db");
+        } else if cfg!(feature = "storage-surrealkv") {
+            assert_eq!(endpoint, "surrealkv:///data/ideas.db");
+        } else {
+            assert_eq!(endpoint, DEFAULT_TIKV_ENDPOINT);
+        }
+    }
+
+    #[test]
+    fn test_missing_data_folder_is_reported() {
+        if !LOCAL_ENGINES.iter().any(|(_, enabled)| *enabled) {
+            return;
+        }
+        let result = StorageSettings::from_lookup(|_| None, || Err(std::io::Error::other("read-only filesystem")));
+        assert!(matches!(result, Err(IdeaError::Database(message)) if message.contains("read-only filesystem")));
+    }
+}
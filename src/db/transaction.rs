@@ -0,0 +1,69 @@
+//! Several statements written as one transaction.
+//!
+//! The Surreal client has no transaction handle that calls can be made through, so a transaction is one request:
+//! `BEGIN TRANSACTION`, the statements, `COMMIT TRANSACTION`. [`with_transaction`] sends the statements a closure
+//! adds to a [`Transaction`] that way. When the closure fails nothing is sent, and when any statement fails the
+//! database cancels the transaction, so either every write lands or none does. Reads the statements depend on are
+//! made before, since the closure runs before anything is sent.
+
+use crate::error::IdeaError;
+use serde::Serialize;
+use surrealdb::engine::any::Any;
+use surrealdb::method::Query;
+use surrealdb::{Response, Surreal};
+
+/// The statements of a transaction being built by [`with_transaction`]
+pub struct Transaction<'a> {
+    /// Only `None` while a statement or binding is being added
+    query: Option<Query<'a, Any>>,
+    statements: usize,
+}
+
+impl<'a> Transaction<'a> {
+    fn extend(&mut self, add: impl FnOnce(Query<'a, Any>) -> Query<'a, Any>) {
+        let query = self.query.take().expect("the query is only taken while being extended");
+        self.query = Some(add(query));
+    }
+
+    /// Add a single statement, returning the index of its result in the response. `BEGIN` and `COMMIT` have no
+    /// results, so the first statement's is 0.
+    pub fn push(&mut self, statement: impl Into<String>) -> usize {
+        let statement = statement.into();
+        self.extend(|query| query.query(statement));
+        self.statements += 1;
+        self.statements - 1
+    }
+
+    /// Bind `$name` to `value` for every statement
+    pub fn bind(&mut self, name: impl Into<String>, value: impl Serialize + 'static) {
+        let name = name.into();
+        self.extend(|query| query.bind((name, value)));
+    }
+
+    /// Number of statements added so far
+    pub fn len(&self) -> usize {
+        self.statements
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.statements == 0
+    }
+}
+
+/// Run the statements `build` adds as one transaction, returning what `build` returned (typically the indices of
+/// the results it needs) and the response. Nothing is sent when `build` fails, and nothing is written when a
+/// statement fails; the error of the first failed statement is returned.
+pub async fn with_transaction<'a, T>(
+    db: &'a Surreal<Any>,
+    build: impl FnOnce(&mut Transaction<'a>) -> Result<T, IdeaError>,
+) -> Result<(T, Response), IdeaError> {
+    let mut transaction = Transaction {
+        query: Some(db.query("BEGIN TRANSACTION")),
+        statements: 0,
+    };
+    let built = build(&mut transaction)?;
+    transaction.extend(|query| query.query("COMMIT TRANSACTION"));
+    let query = transaction.query.take().expect("the query is only taken while being extended");
+    let response = query.await?.check()?;
+    Ok((built, response))
+}
@@ -0,0 +1,43 @@
+//! Private ideas on a public deployment.
+//!
+//! A read-only deployment is public: anyone may read it without signing in. Such visitors only see the ideas made
+//! [`Visibility::Public`]; everyone else reads by ownership alone, as before. Every read of ideas on someone's behalf
+//! goes through here: queries select with [`readable_by`], lists kept in memory filter with [`is_readable`] and
+//! single ideas are checked with [`ensure_readable`]. A private idea answers `NotFound` rather than `Forbidden`, so
+//! a visitor can't tell it exists.
+
+use super::{IdeaId, IdeaRecord, Visibility};
+use crate::auth::server::ensure_owner;
+use crate::error::IdeaError;
+use surrealdb::sql::Thing;
+
+/// Whether `reader` (`None` for an anonymous visitor) only gets to see public ideas: on a read-only deployment,
+/// without a session. Reads outside a request use the default settings and see everything of their owner.
+pub fn hides_private(reader: Option<&Thing>) -> bool {
+    reader.is_none() && crate::config::server::app_config().read_only
+}
+
+/// SurrealQL condition for the ideas `reader` may read, on the `$owner` the query binds to `reader`
+pub fn readable_by(reader: Option<&Thing>) -> &'static str {
+    if hides_private(reader) {
+        "owner = $owner AND visibility = 'public'"
+    } else {
+        "owner = $owner"
+    }
+}
+
+/// Whether `reader` may read `idea`, the same rule as [`readable_by`]
+pub fn is_readable(idea: &IdeaRecord, reader: Option<&Thing>) -> bool {
+    idea.owner.as_ref() == reader && !(idea.visibility == Visibility::Private && hides_private(reader))
+}
+
+/// Check that `reader` may read `idea`: `Forbidden` for someone else's, `NotFound` for a private one they aren't
+/// allowed to know about
+pub fn ensure_readable(idea: &IdeaRecord, reader: Option<&Thing>) -> Result<(), IdeaError> {
+    ensure_owner(idea.owner.as_ref(), reader)?;
+    if idea.visibility == Visibility::Private && hides_private(reader) {
+        let id = idea.id.as_ref().map(|id| IdeaId::from(id).to_string()).unwrap_or_default();
+        return Err(IdeaError::NotFound(id));
+    }
+    Ok(())
+}
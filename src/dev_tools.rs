@@ -0,0 +1,176 @@
+//! Developer conveniences, compiled only with the `dev-tools` feature so production builds don't carry them.
+//!
+//! Resetting the database empties the idea tables in place, which works while the server holds RocksDB open. It
+//! takes two calls: `get_reset_token_server()` hands out a token, and `reset_database_server()` only runs when given
+//! that token back within [`RESET_TOKEN_TTL`], so a stray `curl` can't wipe anything.
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// How long a reset token stays valid
+pub const RESET_TOKEN_TTL: Duration = Duration::from_secs(60);
+
+/// What a reset did
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResetReport {
+    /// Ideas deleted, trashed ones included
+    pub ideas_removed: usize,
+    /// Sample ideas written afterwards
+    pub ideas_seeded: usize,
+}
+
+#[cfg(feature = "server")]
+pub mod server {
+    use super::*;
+    use crate::db::server::{define_schema, submit_ideas, with_transaction, SurrealIdeaRepository};
+    use crate::error::IdeaError;
+    use crate::rate_limit::{Clock, SystemClock};
+    use crate::sample::{sample_ideas, DEFAULT_SAMPLE_COUNT};
+    use std::sync::{Mutex, OnceLock};
+    use surrealdb::{engine::any::Any, Surreal};
+
+    /// Tables emptied by a reset. Users and sessions are kept, so whoever resets stays signed in.
+    const RESET_TABLES: &[&str] = &[
+        "relates_to",
+        "attachments",
+        "idea_events",
+        "idea_revisions",
+        "ideas",
+        "tag_meta",
+        "templates",
+    ];
+
+    /// The one reset token currently handed out, and when
+    pub struct ResetTokens<C: Clock> {
+        clock: C,
+        current: Mutex<Option<(String, Duration)>>,
+    }
+
+    impl<C: Clock> ResetTokens<C> {
+        pub fn new(clock: C) -> Self {
+            Self {
+                clock,
+                current: Mutex::new(None),
+            }
+        }
+
+        /// Hand out `token`, replacing any earlier one
+        pub fn issue(&self, token: String) {
+            let now = self.clock.now();
+            *self.current.lock().unwrap_or_else(|e| e.into_inner()) = Some((token, now));
+        }
+
+        /// Accept `token` if it is the one handed out last and younger than [`RESET_TOKEN_TTL`]. Either way the
+        /// token is used up, so a wrong guess means fetching a new one.
+        pub fn redeem(&self, token: &str) -> Result<(), IdeaError> {
+            let issued = self.current.lock().unwrap_or_else(|e| e.into_inner()).take();
+            match issued {
+                Some((expected, _)) if expected != token => Err(IdeaError::Validation("Wrong reset token".to_string())),
+                Some((_, at)) if self.clock.now().saturating_sub(at) <= RESET_TOKEN_TTL => Ok(()),
+                Some(_) => Err(IdeaError::Validation("The reset token expired, fetch a new one".to_string())),
+                None => Err(IdeaError::Validation("Fetch a reset token first".to_string())),
+            }
+        }
+    }
+
+    /// The tokens of this server process
+    pub fn reset_tokens() -> &'static ResetTokens<SystemClock> {
+        static TOKENS: OnceLock<ResetTokens<SystemClock>> = OnceLock::new();
+        TOKENS.get_or_init(|| ResetTokens::new(SystemClock::default()))
+    }
+
+    /// A fresh random token
+    pub fn new_token() -> String {
+        uuid::Uuid::new_v4().simple().to_string()
+    }
+
+    /// Delete every row of the idea tables in one transaction, define the schema again (which brings back the
+    /// built-in templates) and, with `seed`, write [`DEFAULT_SAMPLE_COUNT`] sample ideas
+    pub async fn reset_database(db: &Surreal<Any>, seed: bool) -> Result<ResetReport, IdeaError> {
+        let ideas: Option<usize> = db
+            .query("SELECT count() FROM ideas GROUP ALL")
+            .await?
+            .take((0, "count"))?;
+
+        with_transaction(db, |txn| {
+            for table in RESET_TABLES {
+                txn.push(format!("DELETE {}", table));
+            }
+            Ok(())
+        })
+        .await?;
+        define_schema(db).await?;
+
+        let ideas_seeded = if seed {
+            let repo = SurrealIdeaRepository::new(db.clone());
+            submit_ideas(&repo, None, sample_ideas(DEFAULT_SAMPLE_COUNT)).await?.len()
+        } else {
+            0
+        };
+        Ok(ResetReport {
+            ideas_removed: ideas.unwrap_or(0),
+            ideas_seeded,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "server")]
+    mod server_tests {
+        use super::super::server::*;
+        use super::super::*;
+        use crate::error::IdeaError;
+        use crate::rate_limit::Clock;
+        use std::sync::{Arc, Mutex};
+
+        /// Clock that only moves when told to
+        #[derive(Clone, Default)]
+        struct ManualClock(Arc<Mutex<Duration>>);
+
+        impl ManualClock {
+            fn advance(&self, by: Duration) {
+                *self.0.lock().unwrap() += by;
+            }
+        }
+
+        impl Clock for ManualClock {
+            fn now(&self) -> Duration {
+                *self.0.lock().unwrap()
+            }
+        }
+
+        #[test]
+        fn test_token_is_accepted_once_within_the_ttl() {
+            let clock = ManualClock::default();
+            let tokens = ResetTokens::new(clock.clone());
+            tokens.issue("abc".to_string());
+            clock.advance(RESET_TOKEN_TTL);
+            assert_eq!(tokens.redeem("abc"), Ok(()));
+            assert!(tokens.redeem("abc").is_err());
+        }
+
+        #[test]
+        fn test_token_expires() {
+            let clock = ManualClock::default();
+            let tokens = ResetTokens::new(clock.clone());
+            tokens.issue("abc".to_string());
+            clock.advance(RESET_TOKEN_TTL + Duration::from_secs(1));
+            assert!(matches!(tokens.redeem("abc"), Err(IdeaError::Validation(message)) if message.contains("expired")));
+        }
+
+        #[test]
+        fn test_wrong_token_uses_it_up() {
+            let tokens = ResetTokens::new(ManualClock::default());
+            assert!(tokens.redeem("abc").is_err());
+            tokens.issue("abc".to_string());
+            assert!(tokens.redeem("abd").is_err());
+            assert!(tokens.redeem("abc").is_err());
+
+            // Only the latest token counts
+            tokens.issue("first".to_string());
+            tokens.issue("second".to_string());
+            assert!(tokens.redeem("first").is_err());
+        }
+    }
+}
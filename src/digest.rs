@@ -0,0 +1,542 @@
+//! A digest of what happened to the ideas since a given time, as the body of an email.
+//!
+//! Nothing here sends mail: the digest is an HTML document with a plain-text alternative, to be handed to whatever
+//! mailer is at hand by `generate_digest_server`, `GET /api/v1/digest` or `seed digest`. The HTML carries its styles
+//! inline and loads nothing, since mail clients strip `<style>` blocks and block remote assets.
+//!
+//! Like the feed, what happened comes from the activity log: ideas created in the period, ideas moved to another
+//! column of the board, and "what must be true" statements written. Statements can't be ticked off, so the digest
+//! counts the ones added rather than the ones completed.
+
+use crate::db::Idea;
+use crate::feed::idea_url;
+use crate::history::{IdeaEvent, IdeaEventKind};
+use crate::utils::truncate_text;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Length of a new idea's description in the digest
+pub const DIGEST_DESCRIPTION_CHARS: usize = 280;
+/// Days covered when no start is given
+pub const DEFAULT_DIGEST_DAYS: i64 = 7;
+
+const BODY_STYLE: &str = "margin:0;padding:24px;background:#ffffff;color:#1a1a1a;\
+                          font-family:Helvetica,Arial,sans-serif;font-size:15px;line-height:1.5";
+const TITLE_STYLE: &str = "margin:0 0 24px;font-size:22px;font-weight:600";
+const HEADING_STYLE: &str = "margin:28px 0 12px;padding-bottom:4px;border-bottom:1px solid #dddddd;font-size:17px";
+const ITEM_STYLE: &str = "margin:0 0 14px";
+const LINK_STYLE: &str = "color:#1a1a1a;font-weight:600";
+const DESCRIPTION_STYLE: &str = "margin:4px 0 0;color:#444444";
+const TAGS_STYLE: &str = "margin:4px 0 0;color:#777777;font-size:12px;text-transform:uppercase";
+const EMPTY_STYLE: &str = "color:#777777";
+
+/// An idea moved to another column during the period
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatusChange {
+    pub idea: Idea,
+    /// When it was last moved
+    pub at: DateTime<Utc>,
+}
+
+/// Statements added to an idea during the period
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatementsAdded {
+    pub idea: Idea,
+    pub count: usize,
+    /// When the last of them was added
+    pub at: DateTime<Utc>,
+}
+
+/// What happened between `since` and `until`, each group oldest first
+#[derive(Debug, Clone, PartialEq)]
+pub struct Digest {
+    pub since: DateTime<Utc>,
+    pub until: DateTime<Utc>,
+    pub new_ideas: Vec<Idea>,
+    pub status_changes: Vec<StatusChange>,
+    pub statements: Vec<StatementsAdded>,
+}
+
+/// The digest as sent to whoever mails it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DigestEmail {
+    pub subject: String,
+    pub html: String,
+    pub text: String,
+}
+
+/// The statements an edit summary says were added, e.g. 2 for "added 2 statements, edited notes"
+fn statements_added(summary: &str) -> usize {
+    summary
+        .split(", ")
+        .filter_map(|part| part.strip_prefix("added ")?.split_once(' '))
+        .filter(|(_, noun)| *noun == "statement" || *noun == "statements")
+        .filter_map(|(count, _)| count.parse::<usize>().ok())
+        .sum()
+}
+
+/// Gather what happened to `ideas` between `since` and `until` from `events`, the log entries of each idea id in
+/// any order. Ideas created before their creation was logged count as new by their `created_at`.
+pub fn digest(
+    ideas: Vec<Idea>,
+    events: &[(String, IdeaEvent)],
+    since: DateTime<Utc>,
+    until: DateTime<Utc>,
+) -> Digest {
+    let in_period = |at: DateTime<Utc>| since <= at && at <= until;
+    let mut created = Vec::new();
+    let mut status_changes = Vec::new();
+    let mut statements = Vec::new();
+
+    for idea in ideas {
+        let id = idea.id.clone().unwrap_or_default();
+        let logged: Vec<&IdeaEvent> = events
+            .iter()
+            .filter(|(idea_id, event)| *idea_id == id && in_period(event.at))
+            .map(|(_, event)| event)
+            .collect();
+        let edits = logged.iter().filter(|event| event.kind == IdeaEventKind::Edited);
+
+        let moved = edits
+            .clone()
+            .filter(|event| event.summary.split(", ").any(|part| part.starts_with("moved to ")))
+            .map(|event| event.at)
+            .max();
+        if let Some(at) = moved {
+            status_changes.push(StatusChange { idea: idea.clone(), at });
+        }
+
+        let added: Vec<(usize, DateTime<Utc>)> = edits
+            .map(|event| (statements_added(&event.summary), event.at))
+            .filter(|(count, _)| *count > 0)
+            .collect();
+        if let Some(at) = added.iter().map(|(_, at)| *at).max() {
+            let count = added.iter().map(|(count, _)| count).sum();
+            statements.push(StatementsAdded { idea: idea.clone(), count, at });
+        }
+
+        let creation = logged
+            .iter()
+            .find(|event| event.kind == IdeaEventKind::Created)
+            .map(|event| event.at)
+            .or(idea.created_at.filter(|at| in_period(*at)));
+        if let Some(at) = creation {
+            created.push((at, idea));
+        }
+    }
+
+    created.sort_by_key(|(at, _)| *at);
+    status_changes.sort_by_key(|change| change.at);
+    statements.sort_by_key(|added| added.at);
+    Digest {
+        since,
+        until,
+        new_ideas: created.into_iter().map(|(_, idea)| idea).collect(),
+        status_changes,
+        statements,
+    }
+}
+
+/// "3 May 2024"
+fn day(time: DateTime<Utc>) -> String {
+    time.format("%-d %B %Y").to_string()
+}
+
+/// "1 new statement", "3 new statements"
+fn new_statements(count: usize) -> String {
+    if count == 1 {
+        "1 new statement".to_string()
+    } else {
+        format!("{} new statements", count)
+    }
+}
+
+/// Escape text for HTML content and attribute values
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+impl Digest {
+    /// Nothing happened in the period
+    pub fn is_empty(&self) -> bool {
+        self.new_ideas.is_empty() && self.status_changes.is_empty() && self.statements.is_empty()
+    }
+
+    pub fn subject(&self) -> String {
+        format!("Ideas digest, {} to {}", day(self.since), day(self.until))
+    }
+
+    /// The digest as a self-contained HTML document. `base_url` is where the app is served and prefixes every link,
+    /// as in the feed. Empty elements are closed, so the document also reads as XML.
+    pub fn to_html(&self, base_url: &str) -> String {
+        let base = base_url.trim_end_matches('/');
+        let link = |idea: &Idea| {
+            format!(
+                "<a href=\"{}\" style=\"{}\">{}</a>",
+                escape_html(&idea_url(base, idea)),
+                LINK_STYLE,
+                escape_html(&idea.title)
+            )
+        };
+        let heading = |html: &mut String, text: &str| {
+            html.push_str(&format!("<h2 style=\"{}\">{}</h2>\n", HEADING_STYLE, text));
+        };
+
+        let subject = escape_html(&self.subject());
+        let mut html = String::from("<!DOCTYPE html>\n<html lang=\"en\">\n");
+        html.push_str(&format!("<head><meta charset=\"utf-8\" /><title>{}</title></head>\n", subject));
+        html.push_str(&format!("<body style=\"{}\">\n", BODY_STYLE));
+        html.push_str(&format!("<h1 style=\"{}\">{}</h1>\n", TITLE_STYLE, subject));
+        if self.is_empty() {
+            html.push_str(&format!("<p style=\"{}\">Nothing happened in this period.</p>\n", EMPTY_STYLE));
+        }
+
+        if !self.new_ideas.is_empty() {
+            heading(&mut html, "New ideas");
+        }
+        for idea in &self.new_ideas {
+            html.push_str(&format!("<div style=\"{}\">{}", ITEM_STYLE, link(idea)));
+            if !idea.description.is_empty() {
+                let description = truncate_text(&idea.description, DIGEST_DESCRIPTION_CHARS);
+                html.push_str(&format!("<p style=\"{}\">{}</p>", DESCRIPTION_STYLE, escape_html(&description)));
+            }
+            if !idea.tags.is_empty() {
+                html.push_str(&format!("<p style=\"{}\">{}</p>", TAGS_STYLE, escape_html(&idea.tags.join(", "))));
+            }
+            html.push_str("</div>\n");
+        }
+
+        if !self.status_changes.is_empty() {
+            heading(&mut html, "Moved on the board");
+        }
+        for change in &self.status_changes {
+            html.push_str(&format!(
+                "<div style=\"{}\">{} is now <strong>{}</strong></div>\n",
+                ITEM_STYLE,
+                link(&change.idea),
+                change.idea.status
+            ));
+        }
+
+        if !self.statements.is_empty() {
+            heading(&mut html, "What must be true");
+        }
+        for added in &self.statements {
+            html.push_str(&format!(
+                "<div style=\"{}\">{}: {}</div>\n",
+                ITEM_STYLE,
+                link(&added.idea),
+                new_statements(added.count)
+            ));
+        }
+
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+
+    /// The digest as plain text, for mail clients that don't show HTML
+    pub fn to_text(&self, base_url: &str) -> String {
+        let base = base_url.trim_end_matches('/');
+        let heading = |text: &mut String, title: &str| {
+            text.push_str(&format!("\n{}\n{}\n\n", title, "-".repeat(title.chars().count())));
+        };
+
+        let mut text = format!("{}\n", self.subject());
+        if self.is_empty() {
+            text.push_str("\nNothing happened in this period.\n");
+        }
+
+        if !self.new_ideas.is_empty() {
+            heading(&mut text, "New ideas");
+        }
+        for idea in &self.new_ideas {
+            text.push_str(&format!("* {}\n  {}\n", idea.title, idea_url(base, idea)));
+            if !idea.description.is_empty() {
+                let description = truncate_text(&idea.description, DIGEST_DESCRIPTION_CHARS);
+                for line in description.lines().filter(|line| !line.trim().is_empty()) {
+                    text.push_str(&format!("  {}\n", line.trim_end()));
+                }
+            }
+            if !idea.tags.is_empty() {
+                text.push_str(&format!("  tags: {}\n", idea.tags.join(", ")));
+            }
+        }
+
+        if !self.status_changes.is_empty() {
+            heading(&mut text, "Moved on the board");
+        }
+        for change in &self.status_changes {
+            let idea = &change.idea;
+            text.push_str(&format!("* {}: now {}\n  {}\n", idea.title, idea.status, idea_url(base, idea)));
+        }
+
+        if !self.statements.is_empty() {
+            heading(&mut text, "What must be true");
+        }
+        for added in &self.statements {
+            let idea = &added.idea;
+            let count = new_statements(added.count);
+            text.push_str(&format!("* {}: {}\n  {}\n", idea.title, count, idea_url(base, idea)));
+        }
+
+        text
+    }
+
+    /// Subject, HTML and text together, linking below `base_url`
+    pub fn to_email(&self, base_url: &str) -> DigestEmail {
+        DigestEmail {
+            subject: self.subject(),
+            html: self.to_html(base_url),
+            text: self.to_text(base_url),
+        }
+    }
+}
+
+// Server-only gathering of the digest
+#[cfg(feature = "server")]
+pub mod server {
+    use super::*;
+    use crate::db::server::active_ideas;
+    use crate::db::IdeaId;
+    use crate::error::IdeaError;
+    use crate::history::server::IdeaEventRecord;
+    use surrealdb::{sql::Thing, Surreal};
+
+    /// What happened between `since` and `until` to the ideas of `owner` that aren't in the trash
+    pub async fn digest_for(
+        db: &Surreal<surrealdb::engine::any::Any>,
+        owner: Option<&Thing>,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> Result<Digest, IdeaError> {
+        if since > until {
+            return Err(IdeaError::Validation("The digest has to start before it ends".to_string()));
+        }
+        let records = active_ideas(db, owner).await?;
+        let ids: Vec<Thing> = records.iter().filter_map(|record| record.id.clone()).collect();
+        let logged: Vec<IdeaEventRecord> = db
+            .query("SELECT * FROM idea_events WHERE idea INSIDE $ideas")
+            .bind(("ideas", ids))
+            .await?
+            .take(0)?;
+
+        let events: Vec<(String, IdeaEvent)> = logged
+            .into_iter()
+            .map(|record| (IdeaId::from(&record.idea).into(), record.into()))
+            .collect();
+        Ok(digest(records.into_iter().map(Idea::from).collect(), &events, since, until))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::IdeaStatus;
+    use quick_xml::events::Event;
+    use quick_xml::Reader;
+
+    fn at(time: &str) -> DateTime<Utc> {
+        time.parse().unwrap()
+    }
+
+    fn idea(id: &str, title: &str, description: &str) -> Idea {
+        Idea {
+            id: Some(format!("ideas:{}", id)),
+            title: title.to_string(),
+            description: description.to_string(),
+            slug: id.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn event(id: &str, kind: IdeaEventKind, summary: &str, time: &str) -> (String, IdeaEvent) {
+        let event = IdeaEvent {
+            kind,
+            summary: summary.to_string(),
+            at: at(time),
+        };
+        (format!("ideas:{}", id), event)
+    }
+
+    /// A week with one of everything, plus activity just outside it
+    fn fixture() -> Digest {
+        let fish = Idea {
+            tags: vec!["food".to_string(), "r&d".to_string()],
+            ..idea("fish", "Fish & chips <fast>", "Cheap\n\nand quick")
+        };
+        let rocket = Idea {
+            status: IdeaStatus::Building,
+            ..idea("rocket", "Rocket", "To the moon")
+        };
+        let old = idea("old", "Old news", "");
+        let ideas = vec![old, rocket, fish];
+        let events = vec![
+            event("old", IdeaEventKind::Created, "", "2024-04-20T09:00:00Z"),
+            event("old", IdeaEventKind::Edited, "edited notes", "2024-05-02T09:00:00Z"),
+            event("rocket", IdeaEventKind::Created, "", "2024-05-03T10:00:00Z"),
+            event("rocket", IdeaEventKind::Edited, "moved to exploring", "2024-05-04T10:00:00Z"),
+            event("rocket", IdeaEventKind::Edited, "added 2 statements, edited notes", "2024-05-05T10:00:00Z"),
+            event("rocket", IdeaEventKind::Edited, "added 1 statement", "2024-05-06T10:00:00Z"),
+            event("rocket", IdeaEventKind::Edited, "moved to building", "2024-05-06T11:00:00Z"),
+            event("fish", IdeaEventKind::Created, "", "2024-05-01T12:00:00Z"),
+            // After the end of the period
+            event("fish", IdeaEventKind::Edited, "added 5 statements", "2024-05-09T00:00:00Z"),
+        ];
+        digest(ideas, &events, at("2024-05-01T00:00:00Z"), at("2024-05-08T00:00:00Z"))
+    }
+
+    #[test]
+    fn test_activity_is_grouped() {
+        let digest = fixture();
+        let titles = |ideas: Vec<&Idea>| ideas.iter().map(|idea| idea.title.clone()).collect::<Vec<_>>();
+
+        assert_eq!(titles(digest.new_ideas.iter().collect()), vec!["Fish & chips <fast>", "Rocket"]);
+        assert_eq!(titles(digest.status_changes.iter().map(|change| &change.idea).collect()), vec!["Rocket"]);
+        assert_eq!(digest.status_changes[0].at, at("2024-05-06T11:00:00Z"));
+        assert_eq!(digest.statements.len(), 1);
+        assert_eq!((digest.statements[0].idea.title.as_str(), digest.statements[0].count), ("Rocket", 3));
+    }
+
+    #[test]
+    fn test_text_snapshot() {
+        let expected = "\
+Ideas digest, 1 May 2024 to 8 May 2024
+
+New ideas
+---------
+
+* Fish & chips <fast>
+  https://ideas.example.com/i/fish
+  Cheap
+  and quick
+  tags: food, r&d
+* Rocket
+  https://ideas.example.com/i/rocket
+  To the moon
+
+Moved on the board
+------------------
+
+* Rocket: now building
+  https://ideas.example.com/i/rocket
+
+What must be true
+-----------------
+
+* Rocket: 3 new statements
+  https://ideas.example.com/i/rocket
+";
+        assert_eq!(fixture().to_text("https://ideas.example.com/"), expected);
+    }
+
+    #[test]
+    fn test_html_snapshot() {
+        let html = fixture().to_html("https://ideas.example.com");
+        let item = |inner: &str| format!("<div style=\"{}\">{}</div>", ITEM_STYLE, inner);
+        let link = |slug: &str, title: &str| {
+            format!("<a href=\"https://ideas.example.com/i/{}\" style=\"{}\">{}</a>", slug, LINK_STYLE, title)
+        };
+        let fish = format!(
+            "{}<p style=\"{}\">Cheap\n\nand quick</p><p style=\"{}\">food, r&amp;d</p>",
+            link("fish", "Fish &amp; chips &lt;fast&gt;"),
+            DESCRIPTION_STYLE,
+            TAGS_STYLE
+        );
+        let rocket = format!("{}<p style=\"{}\">To the moon</p>", link("rocket", "Rocket"), DESCRIPTION_STYLE);
+        let heading = |text: &str| format!("<h2 style=\"{}\">{}</h2>", HEADING_STYLE, text);
+        let subject = "Ideas digest, 1 May 2024 to 8 May 2024";
+
+        let expected = [
+            "<!DOCTYPE html>".to_string(),
+            "<html lang=\"en\">".to_string(),
+            format!("<head><meta charset=\"utf-8\" /><title>{}</title></head>", subject),
+            format!("<body style=\"{}\">", BODY_STYLE),
+            format!("<h1 style=\"{}\">{}</h1>", TITLE_STYLE, subject),
+            heading("New ideas"),
+            item(&fish),
+            item(&rocket),
+            heading("Moved on the board"),
+            item(&format!("{} is now <strong>building</strong>", link("rocket", "Rocket"))),
+            heading("What must be true"),
+            item(&format!("{}: 3 new statements", link("rocket", "Rocket"))),
+            "</body>".to_string(),
+            "</html>".to_string(),
+        ];
+        assert_eq!(html, expected.join("\n") + "\n");
+    }
+
+    #[test]
+    fn test_html_is_self_contained_and_well_formed() {
+        let html = fixture().to_html("https://ideas.example.com");
+        for external in ["<link", "<style", "<img", "<script", "src="] {
+            assert!(!html.contains(external), "{}", external);
+        }
+
+        // Every element in the body is styled inline
+        let mut reader = Reader::from_str(html.trim_start_matches("<!DOCTYPE html>"));
+        let mut in_body = false;
+        loop {
+            match reader.read_event().unwrap() {
+                Event::Start(element) | Event::Empty(element) => {
+                    let name = String::from_utf8(element.name().as_ref().to_vec()).unwrap();
+                    in_body |= name == "body";
+                    let styled = element.attributes().any(|attribute| attribute.unwrap().key.as_ref() == b"style");
+                    assert!(!in_body || styled || name == "strong", "{} has no style", name);
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+        assert!(in_body);
+    }
+
+    #[test]
+    fn test_quiet_period() {
+        let digest = digest(
+            vec![idea("old", "Old news", "")],
+            &[event("old", IdeaEventKind::Edited, "edited notes", "2024-05-02T09:00:00Z")],
+            at("2024-05-01T00:00:00Z"),
+            at("2024-05-08T00:00:00Z"),
+        );
+        assert!(digest.is_empty());
+        assert_eq!(
+            digest.to_text("http://localhost:8080"),
+            "Ideas digest, 1 May 2024 to 8 May 2024\n\nNothing happened in this period.\n"
+        );
+        assert!(digest.to_html("http://localhost:8080").contains("Nothing happened in this period."));
+    }
+
+    #[test]
+    fn test_ideas_without_a_logged_creation_count_by_created_at() {
+        let unlogged = Idea {
+            created_at: Some(at("2024-05-02T00:00:00Z")),
+            ..idea("unlogged", "Unlogged", "")
+        };
+        let before = Idea {
+            created_at: Some(at("2024-04-02T00:00:00Z")),
+            ..idea("before", "Before", "")
+        };
+        let digest = digest(vec![unlogged, before], &[], at("2024-05-01T00:00:00Z"), at("2024-05-08T00:00:00Z"));
+        assert_eq!(digest.new_ideas.len(), 1);
+        assert_eq!(digest.new_ideas[0].title, "Unlogged");
+    }
+
+    #[test]
+    fn test_statements_added_are_read_from_summaries() {
+        assert_eq!(statements_added("added 1 statement"), 1);
+        assert_eq!(statements_added("renamed to \"x\", added 2 statements, edited 1 statement"), 2);
+        assert_eq!(statements_added("added 2 tags, removed 1 statement"), 0);
+        assert_eq!(statements_added("added tag statement"), 0);
+    }
+}
@@ -0,0 +1,219 @@
+//! Structured errors returned by the server functions.
+//!
+//! Server functions still return `ServerFnError`, but the message carries an encoded [`IdeaError`] so the client
+//! can tell "not found" from "forbidden" without matching on human-readable text. Use [`IdeaError::from_error`]
+//! on the client to get the structured error back and [`error_message`] to show it to the user.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Marker placed in front of the encoded error inside the `ServerFnError` message
+const MARKER: &str = "IdeaError:";
+
+/// Everything that can go wrong in a server function, in a shape both sides understand
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "detail")]
+pub enum IdeaError {
+    /// The id string couldn't be parsed
+    InvalidId(String),
+    /// The record doesn't exist
+    NotFound(String),
+    /// The record exists but belongs to someone else
+    Forbidden,
+    /// The request needs a signed-in user
+    Unauthorized,
+    /// The input was rejected
+    Validation(String),
+    /// The database failed
+    Database(String),
+    /// Too many writes from this client; retry after this many seconds
+    RateLimited(u64),
+    /// The request was bigger than the limit, in bytes
+    PayloadTooLarge(usize),
+    /// The deployment is read-only and refuses every write
+    ReadOnly,
+    /// One of the deployment's storage limits is reached, named here (`ideas`, `notes` or `attachments`), see
+    /// [`crate::limits`]
+    StorageFull(String),
+}
+
+impl fmt::Display for IdeaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IdeaError::InvalidId(id) => write!(f, "Invalid ID format: {}", id),
+            IdeaError::NotFound(id) => write!(f, "Idea not found: {}", id),
+            IdeaError::Forbidden => write!(f, "You don't have access to this idea"),
+            IdeaError::Unauthorized => write!(f, "Please sign in first"),
+            IdeaError::Validation(message) => write!(f, "{}", message),
+            IdeaError::Database(message) => write!(f, "Database error: {}", message),
+            IdeaError::RateLimited(seconds) => write!(f, "slow down, try again in {}s", seconds),
+            IdeaError::PayloadTooLarge(limit) => {
+                write!(f, "That's too much data at once (limit is {} bytes)", limit)
+            }
+            IdeaError::ReadOnly => write!(f, "This board is read-only"),
+            IdeaError::StorageFull(resource) => {
+                write!(f, "This board is out of room for {}: delete some to make space", resource)
+            }
+        }
+    }
+}
+
+// Deliberately not `std::error::Error`: that keeps `?` in server functions going through the explicit
+// `From<IdeaError> for ServerFnError` below, which preserves the structured encoding.
+
+impl IdeaError {
+    /// Encode the error into a string that survives the trip through a `ServerFnError` message
+    pub fn encode(&self) -> String {
+        format!(
+            "{}{}",
+            MARKER,
+            serde_json::to_string(self).unwrap_or_else(|_| "{}".to_string())
+        )
+    }
+
+    /// Whether trying the same request again could succeed. Only database failures are treated as transient;
+    /// everything else would fail the same way again.
+    pub fn is_retriable(&self) -> bool {
+        matches!(self, IdeaError::Database(_))
+    }
+
+    /// Recover a structured error from any error whose message contains an encoded [`IdeaError`]
+    pub fn from_error(error: &impl fmt::Display) -> Option<IdeaError> {
+        let message = error.to_string();
+        let start = message.find(MARKER)? + MARKER.len();
+        serde_json::Deserializer::from_str(&message[start..])
+            .into_iter::<IdeaError>()
+            .next()?
+            .ok()
+    }
+}
+
+/// Human-readable message for an error returned by a server function
+pub fn error_message(error: &impl fmt::Display) -> String {
+    match IdeaError::from_error(error) {
+        Some(idea_error) => idea_error.to_string(),
+        None => error.to_string(),
+    }
+}
+
+/// Whether a failed server call is worth retrying. Errors without a structured [`IdeaError`] come from the
+/// transport (network down, server restarting, ...) and are assumed to be transient.
+pub fn is_retriable(error: &impl fmt::Display) -> bool {
+    match IdeaError::from_error(error) {
+        Some(idea_error) => idea_error.is_retriable(),
+        None => true,
+    }
+}
+
+/// Why a page couldn't load the record it is about, as far as the reader needs to know
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoadFailure {
+    /// The id in the link can't name a record, so going back is the only way on
+    Malformed,
+    /// The record is gone; it may still be in the trash
+    Deleted,
+    /// The record belongs to someone else, or needs a signed-in user
+    NoAccess,
+    /// Anything else, which loading again might get past
+    Transient,
+}
+
+impl LoadFailure {
+    /// The failure behind a failed load
+    pub fn of(error: &impl fmt::Display) -> Self {
+        match IdeaError::from_error(error) {
+            Some(IdeaError::InvalidId(_)) => LoadFailure::Malformed,
+            Some(IdeaError::NotFound(_)) => LoadFailure::Deleted,
+            Some(IdeaError::Forbidden | IdeaError::Unauthorized) => LoadFailure::NoAccess,
+            _ => LoadFailure::Transient,
+        }
+    }
+}
+
+#[cfg(feature = "server")]
+impl From<IdeaError> for dioxus::prelude::ServerFnError {
+    fn from(error: IdeaError) -> Self {
+        dioxus::prelude::ServerFnError::new(error.encode())
+    }
+}
+
+#[cfg(feature = "server")]
+impl From<surrealdb::Error> for IdeaError {
+    fn from(error: surrealdb::Error) -> Self {
+        IdeaError::Database(error.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_roundtrip() {
+        let errors = vec![
+            IdeaError::InvalidId("bad".to_string()),
+            IdeaError::NotFound("ideas:1".to_string()),
+            IdeaError::Forbidden,
+            IdeaError::Unauthorized,
+            IdeaError::Validation("title is required".to_string()),
+            IdeaError::Database("boom".to_string()),
+            IdeaError::RateLimited(12),
+            IdeaError::PayloadTooLarge(65536),
+            IdeaError::ReadOnly,
+            IdeaError::StorageFull("ideas".to_string()),
+        ];
+
+        for error in errors {
+            assert_eq!(IdeaError::from_error(&error.encode()), Some(error));
+        }
+    }
+
+    #[test]
+    fn test_decode_with_surrounding_text() {
+        // Transport layers may wrap the message with their own prefix
+        let wrapped = format!(
+            "error running server function: {} (details)",
+            IdeaError::Forbidden.encode()
+        );
+        assert_eq!(IdeaError::from_error(&wrapped), Some(IdeaError::Forbidden));
+    }
+
+    #[test]
+    fn test_plain_errors_are_not_decoded() {
+        assert_eq!(IdeaError::from_error(&"Server-only function"), None);
+        assert_eq!(error_message(&"Server-only function"), "Server-only function");
+    }
+
+    #[test]
+    fn test_retriable_classification() {
+        assert!(is_retriable(&IdeaError::Database("timeout".to_string()).encode()));
+        assert!(is_retriable(&"error sending request: connection refused"));
+
+        assert!(!is_retriable(&IdeaError::NotFound("ideas:1".to_string()).encode()));
+        assert!(!is_retriable(&IdeaError::Forbidden.encode()));
+        assert!(!is_retriable(&IdeaError::Validation("bad".to_string()).encode()));
+        assert!(!is_retriable(&IdeaError::RateLimited(5).encode()));
+    }
+
+    #[test]
+    fn test_load_failures() {
+        let failure = |error: IdeaError| LoadFailure::of(&error.encode());
+        assert_eq!(failure(IdeaError::InvalidId("whatever".to_string())), LoadFailure::Malformed);
+        assert_eq!(failure(IdeaError::NotFound("ideas:gone".to_string())), LoadFailure::Deleted);
+        assert_eq!(failure(IdeaError::Forbidden), LoadFailure::NoAccess);
+        assert_eq!(failure(IdeaError::Unauthorized), LoadFailure::NoAccess);
+        assert_eq!(failure(IdeaError::Database("timeout".to_string())), LoadFailure::Transient);
+        assert_eq!(failure(IdeaError::RateLimited(3)), LoadFailure::Transient);
+        // Transport errors carry no structured error
+        assert_eq!(LoadFailure::of(&"error sending request: connection refused"), LoadFailure::Transient);
+    }
+
+    #[test]
+    fn test_error_message_uses_display() {
+        let encoded = IdeaError::NotFound("ideas:1".to_string()).encode();
+        assert_eq!(error_message(&encoded), "Idea not found: ideas:1");
+
+        let encoded = IdeaError::RateLimited(7).encode();
+        assert_eq!(error_message(&encoded), "slow down, try again in 7s");
+    }
+}
@@ -0,0 +1,307 @@
+//! Atom feed of the most recently added ideas, served at `/feed.xml` for feed readers.
+//!
+//! Ideas carry no timestamps of their own, so the dates come from their activity log: an idea was published when
+//! its first event was logged (its creation) and updated at its latest one. Ideas from before the log existed have
+//! no events and are left out.
+
+use crate::db::Idea;
+use crate::utils::truncate_text;
+use chrono::{DateTime, SecondsFormat, Utc};
+
+/// Entries in the feed
+pub const FEED_ENTRIES: usize = 25;
+/// Length of an entry's summary, cut from the description
+pub const FEED_SUMMARY_CHARS: usize = 280;
+/// Title of the feed itself
+const FEED_TITLE: &str = "ideas...";
+
+/// An idea as it appears in the feed
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeedEntry {
+    pub idea: Idea,
+    pub published: DateTime<Utc>,
+    pub updated: DateTime<Utc>,
+}
+
+/// The [`FEED_ENTRIES`] most recently created of `ideas`, newest first. `events` are the times logged for each
+/// idea id, in any order.
+pub fn feed_entries(ideas: Vec<Idea>, events: &[(String, DateTime<Utc>)]) -> Vec<FeedEntry> {
+    let mut entries: Vec<FeedEntry> = ideas
+        .into_iter()
+        .filter_map(|idea| {
+            let id = idea.id.as_deref()?;
+            let times = events.iter().filter(|(idea_id, _)| idea_id == id).map(|(_, at)| *at);
+            let published = times.clone().min()?;
+            let updated = times.max()?;
+            Some(FeedEntry { idea, published, updated })
+        })
+        .collect();
+    entries.sort_by_key(|entry| std::cmp::Reverse(entry.published));
+    entries.truncate(FEED_ENTRIES);
+    entries
+}
+
+/// Escape text for XML content and attribute values
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// RFC 3339 time, as Atom wants it
+fn atom_time(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+/// Link to the idea's shareable slug page below `base`, or to its id route when it has no slug
+pub(crate) fn idea_url(base: &str, idea: &Idea) -> String {
+    if idea.slug.is_empty() {
+        format!("{}/idea/{}", base, idea.id.as_deref().unwrap_or_default())
+    } else {
+        format!("{}/i/{}", base, idea.slug)
+    }
+}
+
+/// Render `entries` as an Atom document. `base_url` is where the app is served, e.g. `https://ideas.example.com`,
+/// and prefixes every link.
+///
+/// Entries link to the idea's shareable slug page and are identified by the id route, which survives renames. The
+/// feed is as recent as its latest entry.
+pub fn atom_feed(entries: &[FeedEntry], base_url: &str) -> String {
+    let base = base_url.trim_end_matches('/');
+    let updated = entries.iter().map(|entry| entry.updated).max().unwrap_or(DateTime::UNIX_EPOCH);
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+    xml.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+    xml.push_str(&format!("  <title>{}</title>\n", escape_xml(FEED_TITLE)));
+    xml.push_str(&format!("  <id>{}/</id>\n", escape_xml(base)));
+    xml.push_str(&format!("  <link rel=\"alternate\" href=\"{}/\"/>\n", escape_xml(base)));
+    xml.push_str(&format!("  <link rel=\"self\" href=\"{}/feed.xml\"/>\n", escape_xml(base)));
+    xml.push_str(&format!("  <updated>{}</updated>\n", atom_time(updated)));
+
+    for entry in entries {
+        let idea = &entry.idea;
+        let id = idea.id.as_deref().unwrap_or_default();
+        let link = idea_url(base, idea);
+        xml.push_str("  <entry>\n");
+        xml.push_str(&format!("    <title>{}</title>\n", escape_xml(&idea.title)));
+        xml.push_str(&format!("    <id>{}/idea/{}</id>\n", escape_xml(base), escape_xml(id)));
+        xml.push_str(&format!("    <link rel=\"alternate\" href=\"{}\"/>\n", escape_xml(&link)));
+        xml.push_str(&format!("    <published>{}</published>\n", atom_time(entry.published)));
+        xml.push_str(&format!("    <updated>{}</updated>\n", atom_time(entry.updated)));
+        // Atom requires an author on every entry unless the feed has one
+        xml.push_str("    <author><name>ideas</name></author>\n");
+        if !idea.description.is_empty() {
+            let summary = truncate_text(&idea.description, FEED_SUMMARY_CHARS);
+            xml.push_str(&format!("    <summary>{}</summary>\n", escape_xml(&summary)));
+        }
+        for tag in &idea.tags {
+            xml.push_str(&format!("    <category term=\"{}\"/>\n", escape_xml(tag)));
+        }
+        xml.push_str("  </entry>\n");
+    }
+
+    xml.push_str("</feed>\n");
+    xml
+}
+
+// Server-only route serving the feed
+#[cfg(feature = "server")]
+pub mod server {
+    use super::*;
+    use crate::auth::server::user_id_from_headers;
+    use crate::base_path::with_base_path;
+    use crate::db::server::active_ideas;
+    use crate::db::IdeaId;
+    use crate::error::IdeaError;
+    use crate::request::base_url_from;
+    use crate::rest::ApiError;
+    use axum::extract::State;
+    use axum::http::{header, HeaderMap};
+    use axum::response::{IntoResponse, Response};
+    use axum::routing::get;
+    use axum::Router;
+    use serde::Deserialize;
+    use surrealdb::{sql::Thing, Surreal};
+
+    /// The `/feed.xml` route, backed by `db`
+    pub fn router(db: Surreal<surrealdb::engine::any::Any>) -> Router {
+        Router::new().route("/feed.xml", get(feed)).with_state(db)
+    }
+
+    /// The feed of the anonymous ideas, or of the user whose session token comes with the request
+    async fn feed(State(db): State<Surreal<surrealdb::engine::any::Any>>, headers: HeaderMap) -> Response {
+        match render(&db, &headers).await {
+            Ok(xml) => ([(header::CONTENT_TYPE, "application/atom+xml; charset=utf-8")], xml).into_response(),
+            Err(error) => ApiError(error).into_response(),
+        }
+    }
+
+    async fn render(db: &Surreal<surrealdb::engine::any::Any>, headers: &HeaderMap) -> Result<String, IdeaError> {
+        #[derive(Deserialize)]
+        struct EventTime {
+            idea: Thing,
+            at: DateTime<Utc>,
+        }
+
+        let owner = user_id_from_headers(db, headers).await?;
+        let records = active_ideas(db, owner.as_ref()).await?;
+        let ids: Vec<Thing> = records.iter().filter_map(|record| record.id.clone()).collect();
+        let times: Vec<EventTime> = db
+            .query("SELECT idea, at FROM idea_events WHERE idea INSIDE $ideas")
+            .bind(("ideas", ids))
+            .await?
+            .take(0)?;
+
+        let events: Vec<(String, DateTime<Utc>)> = times
+            .into_iter()
+            .map(|time| (IdeaId::from(&time.idea).into(), time.at))
+            .collect();
+        let entries = feed_entries(records.into_iter().map(Idea::from).collect(), &events);
+        // Links point below the base path, where the pages are served
+        Ok(atom_feed(&entries, &format!("{}{}", base_url_from(headers), with_base_path(""))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quick_xml::events::Event;
+    use quick_xml::Reader;
+
+    fn at(time: &str) -> DateTime<Utc> {
+        time.parse().unwrap()
+    }
+
+    fn idea(id: &str, title: &str, description: &str) -> Idea {
+        Idea {
+            id: Some(id.to_string()),
+            title: title.to_string(),
+            description: description.to_string(),
+            slug: id.trim_start_matches("ideas:").to_string(),
+            tags: vec!["r&d".to_string()],
+            ..Default::default()
+        }
+    }
+
+    /// Each element's path and text, plus its `href` or `term` attribute, read back with a real XML parser
+    fn parse(xml: &str) -> Vec<(String, String)> {
+        let mut reader = Reader::from_str(xml);
+        reader.config_mut().trim_text(true);
+        let mut path: Vec<String> = Vec::new();
+        let mut found = Vec::new();
+        loop {
+            match reader.read_event().unwrap() {
+                Event::Start(element) => path.push(String::from_utf8(element.name().as_ref().to_vec()).unwrap()),
+                Event::End(_) => {
+                    path.pop();
+                }
+                Event::Empty(element) => {
+                    let name = String::from_utf8(element.name().as_ref().to_vec()).unwrap();
+                    for attribute in element.attributes() {
+                        let attribute = attribute.unwrap();
+                        let key = String::from_utf8(attribute.key.as_ref().to_vec()).unwrap();
+                        if key == "href" || key == "term" {
+                            let value = attribute.unescape_value().unwrap().into_owned();
+                            found.push((format!("{}/{}@{}", path.join("/"), name, key), value));
+                        }
+                    }
+                }
+                Event::Text(text) => found.push((path.join("/"), text.unescape().unwrap().into_owned())),
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+        found
+    }
+
+    fn values<'a>(parsed: &'a [(String, String)], path: &str) -> Vec<&'a str> {
+        parsed
+            .iter()
+            .filter(|(p, _)| p == path)
+            .map(|(_, value)| value.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_escaping_survives_a_parser() {
+        let entries = vec![FeedEntry {
+            idea: idea("ideas:a", "Fish & chips <fast>", "Cheap \"and\" 'quick'"),
+            published: at("2024-05-01T10:00:00Z"),
+            updated: at("2024-05-02T12:30:00Z"),
+        }];
+        let parsed = parse(&atom_feed(&entries, "https://ideas.example.com/"));
+
+        assert_eq!(values(&parsed, "feed/title"), vec!["ideas..."]);
+        assert_eq!(values(&parsed, "feed/updated"), vec!["2024-05-02T12:30:00Z"]);
+        assert_eq!(values(&parsed, "feed/entry/title"), vec!["Fish & chips <fast>"]);
+        assert_eq!(values(&parsed, "feed/entry/summary"), vec!["Cheap \"and\" 'quick'"]);
+        assert_eq!(values(&parsed, "feed/entry/link@href"), vec!["https://ideas.example.com/i/a"]);
+        assert_eq!(values(&parsed, "feed/entry/id"), vec!["https://ideas.example.com/idea/ideas:a"]);
+        assert_eq!(values(&parsed, "feed/entry/published"), vec!["2024-05-01T10:00:00Z"]);
+        assert_eq!(values(&parsed, "feed/entry/category@term"), vec!["r&d"]);
+    }
+
+    #[test]
+    fn test_summary_is_cut_and_empty_descriptions_have_none() {
+        let long = "x".repeat(FEED_SUMMARY_CHARS + 10);
+        let entries = vec![
+            FeedEntry {
+                idea: idea("ideas:a", "Long", &long),
+                published: at("2024-05-01T10:00:00Z"),
+                updated: at("2024-05-01T10:00:00Z"),
+            },
+            FeedEntry {
+                idea: Idea { slug: String::new(), ..idea("ideas:b", "Bare", "") },
+                published: at("2024-05-01T10:00:00Z"),
+                updated: at("2024-05-01T10:00:00Z"),
+            },
+        ];
+        let parsed = parse(&atom_feed(&entries, "http://localhost:8080"));
+
+        let summaries = values(&parsed, "feed/entry/summary");
+        assert_eq!(summaries.len(), 1);
+        assert_eq!(summaries[0].chars().count(), FEED_SUMMARY_CHARS + 1);
+        // Without a slug the link falls back to the id route
+        assert_eq!(values(&parsed, "feed/entry/link@href")[1], "http://localhost:8080/idea/ideas:b");
+    }
+
+    #[test]
+    fn test_empty_feed_is_still_valid() {
+        let parsed = parse(&atom_feed(&[], "http://localhost:8080"));
+        assert_eq!(values(&parsed, "feed/updated"), vec!["1970-01-01T00:00:00Z"]);
+        assert!(values(&parsed, "feed/entry/title").is_empty());
+    }
+
+    #[test]
+    fn test_entries_are_newest_first_and_capped() {
+        let ideas: Vec<Idea> = (0..FEED_ENTRIES + 5)
+            .map(|n| idea(&format!("ideas:i{}", n), &format!("Idea {}", n), ""))
+            .chain([idea("ideas:old", "Before the log", "")])
+            .collect();
+        let mut events: Vec<(String, DateTime<Utc>)> = (0..FEED_ENTRIES + 5)
+            .map(|n| (format!("ideas:i{}", n), at("2024-01-01T00:00:00Z") + chrono::Duration::days(n as i64)))
+            .collect();
+        // A later edit of the first idea updates it without making it newer
+        events.push(("ideas:i0".to_string(), at("2025-01-01T00:00:00Z")));
+
+        let entries = feed_entries(ideas, &events);
+        assert_eq!(entries.len(), FEED_ENTRIES);
+        assert_eq!(entries[0].idea.title, format!("Idea {}", FEED_ENTRIES + 4));
+        assert!(entries.windows(2).all(|pair| pair[0].published >= pair[1].published));
+        assert!(entries.iter().all(|entry| entry.idea.title != "Idea 0" && entry.idea.title != "Before the log"));
+
+        let first = feed_entries(vec![idea("ideas:i0", "Idea 0", "")], &events);
+        assert_eq!(first[0].published, at("2024-01-01T00:00:00Z"));
+        assert_eq!(first[0].updated, at("2025-01-01T00:00:00Z"));
+    }
+}
@@ -0,0 +1,455 @@
+//! The shape of the new-idea form, set per deployment.
+//!
+//! The description and the tags can each be optional, required or hidden, and a deployment can add fields of its
+//! own, like a "business value" dropdown or an estimate in days. The [`FormConfig`] is one row of the `config`
+//! table, changed with `set_form_config_server()` from the admin page and read by the form through
+//! `get_form_config_server()`. Custom values are stored in the idea's `custom_fields` under the field name.
+//!
+//! The schema only applies to ideas as they are submitted: ideas keep the values of fields removed since, and load
+//! whatever their `custom_fields` hold.
+
+use crate::error::IdeaError;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Most custom fields a form can have
+pub const MAX_CUSTOM_FIELDS: usize = 20;
+
+/// Longest value of a custom text field, in characters
+pub const MAX_CUSTOM_TEXT_CHARS: usize = 500;
+
+/// How the form treats a built-in field
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldRule {
+    #[default]
+    Optional,
+    Required,
+    /// Not shown, so never required either
+    Hidden,
+}
+
+impl FieldRule {
+    pub fn is_shown(self) -> bool {
+        self != FieldRule::Hidden
+    }
+
+    pub fn is_required(self) -> bool {
+        self == FieldRule::Required
+    }
+}
+
+/// What a custom field holds and which input the form shows for it
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CustomFieldKind {
+    /// One line of text, stored as a string
+    #[default]
+    Text,
+    /// One of the field's options, stored as a string
+    Select,
+    /// Stored as a JSON number
+    Number,
+}
+
+/// A field a deployment added to the form
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CustomField {
+    /// Key of the value in an idea's `custom_fields`, and the label of the input
+    pub name: String,
+    #[serde(default)]
+    pub kind: CustomFieldKind,
+    /// Choices of a select field, in the order offered
+    #[serde(default)]
+    pub options: Vec<String>,
+    #[serde(default)]
+    pub required: bool,
+}
+
+impl CustomField {
+    /// Label of the input, marking optional fields like the form's own
+    pub fn label(&self) -> String {
+        if self.required {
+            self.name.clone()
+        } else {
+            format!("{} (optional)", self.name)
+        }
+    }
+
+    /// `type` of the `input` element for the field, `None` for a select
+    pub fn input_type(&self) -> Option<&'static str> {
+        match self.kind {
+            CustomFieldKind::Text => Some("text"),
+            CustomFieldKind::Number => Some("number"),
+            CustomFieldKind::Select => None,
+        }
+    }
+
+    /// The value for what was typed or picked in the field's input, `None` when it was left blank
+    pub fn value_from_input(&self, input: &str) -> Result<Option<Value>, String> {
+        let input = input.trim();
+        if input.is_empty() {
+            return Ok(None);
+        }
+        match self.kind {
+            CustomFieldKind::Text if input.chars().count() > MAX_CUSTOM_TEXT_CHARS => {
+                Err(format!("{} is longer than {} characters", self.name, MAX_CUSTOM_TEXT_CHARS))
+            }
+            CustomFieldKind::Text => Ok(Some(Value::from(input))),
+            CustomFieldKind::Select if self.options.iter().any(|option| option == input) => {
+                Ok(Some(Value::from(input)))
+            }
+            CustomFieldKind::Select => Err(format!("{} isn't one of the choices for {}", input, self.name)),
+            CustomFieldKind::Number => {
+                // Whole numbers stay integers, so 3 doesn't come back as 3.0
+                let number = match input.parse::<i64>() {
+                    Ok(integer) => Some(Value::from(integer)),
+                    // `from_f64` turns down infinity and NaN, which parse from "inf" and "NaN"
+                    Err(_) => input.parse::<f64>().ok().and_then(serde_json::Number::from_f64).map(Value::Number),
+                };
+                number.map(Some).ok_or_else(|| format!("{} must be a number", self.name))
+            }
+        }
+    }
+
+    /// A stored value, sent in by a client: strings go through [`CustomField::value_from_input`], numbers are
+    /// taken as they are by number fields
+    fn check_value(&self, value: Value) -> Result<Option<Value>, String> {
+        match value {
+            Value::Null => Ok(None),
+            Value::String(input) => self.value_from_input(&input),
+            Value::Number(_) if self.kind == CustomFieldKind::Number => Ok(Some(value)),
+            _ => Err(format!("{} has a value of the wrong kind", self.name)),
+        }
+    }
+}
+
+/// A custom value as text, for an input or for showing it on the idea
+pub fn display_value(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// The new-idea form of a deployment, see the module docs. The title is always required.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FormConfig {
+    pub description: FieldRule,
+    pub tags: FieldRule,
+    pub custom_fields: Vec<CustomField>,
+}
+
+impl Default for FormConfig {
+    /// The form as it was before it could be configured: a required description and optional tags
+    fn default() -> Self {
+        Self {
+            description: FieldRule::Required,
+            tags: FieldRule::Optional,
+            custom_fields: Vec::new(),
+        }
+    }
+}
+
+impl FormConfig {
+    /// The config with names and options trimmed and blank options dropped, or every problem with it: fields
+    /// need a name of their own, a select needs options to pick from and there can be at most
+    /// [`MAX_CUSTOM_FIELDS`] of them
+    pub fn checked(mut self) -> Result<Self, IdeaError> {
+        let mut problems = Vec::new();
+        if self.custom_fields.len() > MAX_CUSTOM_FIELDS {
+            problems.push(format!("A form can have at most {} custom fields", MAX_CUSTOM_FIELDS));
+        }
+        let mut names: Vec<String> = Vec::new();
+        for (index, field) in self.custom_fields.iter_mut().enumerate() {
+            field.name = field.name.trim().to_string();
+            field.options = field
+                .options
+                .iter()
+                .map(|option| option.trim().to_string())
+                .filter(|option| !option.is_empty())
+                .collect();
+
+            if field.name.is_empty() {
+                problems.push(format!("field {}: the name is empty", index));
+            } else if names.iter().any(|name| name.eq_ignore_ascii_case(&field.name)) {
+                problems.push(format!("field {}: {} is used twice", index, field.name));
+            }
+            match field.kind {
+                CustomFieldKind::Select if field.options.is_empty() => {
+                    problems.push(format!("field {}: a select needs options", index));
+                }
+                CustomFieldKind::Select => {
+                    let mut options = field.options.clone();
+                    options.sort();
+                    options.dedup();
+                    if options.len() != field.options.len() {
+                        problems.push(format!("field {}: an option is listed twice", index));
+                    }
+                }
+                _ if !field.options.is_empty() => {
+                    problems.push(format!("field {}: only a select has options", index));
+                }
+                _ => {}
+            }
+            names.push(field.name.clone());
+        }
+
+        if problems.is_empty() {
+            Ok(self)
+        } else {
+            Err(IdeaError::Validation(problems.join("; ")))
+        }
+    }
+
+    /// Check a submitted idea against the form: the required built-in fields must be filled in, and `values`
+    /// must only hold this form's custom fields, each of its kind. Returns the custom values to store, without
+    /// blank ones. Every problem is reported at once.
+    pub fn validate(
+        &self,
+        description: &str,
+        tags: &[String],
+        mut values: HashMap<String, Value>,
+    ) -> Result<HashMap<String, Value>, IdeaError> {
+        let mut problems = Vec::new();
+        if self.description.is_required() && description.trim().is_empty() {
+            problems.push("Description is required".to_string());
+        }
+        if self.tags.is_required() && tags.iter().all(|tag| tag.trim().is_empty()) {
+            problems.push("At least one tag is required".to_string());
+        }
+
+        let mut checked = HashMap::new();
+        for field in &self.custom_fields {
+            match field.check_value(values.remove(&field.name).unwrap_or(Value::Null)) {
+                Ok(Some(value)) => {
+                    checked.insert(field.name.clone(), value);
+                }
+                Ok(None) if field.required => problems.push(format!("{} is required", field.name)),
+                Ok(None) => {}
+                Err(problem) => problems.push(problem),
+            }
+        }
+        let mut unknown: Vec<&String> = values.keys().collect();
+        unknown.sort();
+        problems.extend(unknown.into_iter().map(|name| format!("The form has no field {}", name)));
+
+        if problems.is_empty() {
+            Ok(checked)
+        } else {
+            Err(IdeaError::Validation(problems.join("; ")))
+        }
+    }
+}
+
+// Server-only storage of the form config
+#[cfg(feature = "server")]
+pub mod server {
+    use super::*;
+    use surrealdb::Surreal;
+
+    /// Key of the `config` row holding the form
+    const FORM_CONFIG_KEY: &str = "form";
+
+    /// The stored form, or the default one while none was saved
+    pub async fn form_config(db: &Surreal<surrealdb::engine::any::Any>) -> Result<FormConfig, IdeaError> {
+        let stored: Vec<FormConfig> = db
+            .query("SELECT VALUE value FROM type::thing('config', $key)")
+            .bind(("key", FORM_CONFIG_KEY))
+            .await?
+            .take(0)?;
+        Ok(stored.into_iter().next().unwrap_or_default())
+    }
+
+    /// Replace the stored form with `config`, once it passed [`FormConfig::checked`]
+    pub async fn set_form_config(
+        db: &Surreal<surrealdb::engine::any::Any>,
+        config: FormConfig,
+    ) -> Result<FormConfig, IdeaError> {
+        let config = config.checked()?;
+        db.query("UPSERT type::thing('config', $key) SET value = $value")
+            .bind(("key", FORM_CONFIG_KEY))
+            .bind(("value", config.clone()))
+            .await?
+            .check()?;
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn field(name: &str, kind: CustomFieldKind, options: &[&str], required: bool) -> CustomField {
+        CustomField {
+            name: name.to_string(),
+            kind,
+            options: options.iter().map(|option| option.to_string()).collect(),
+            required,
+        }
+    }
+
+    fn form() -> FormConfig {
+        FormConfig {
+            description: FieldRule::Optional,
+            tags: FieldRule::Required,
+            custom_fields: vec![
+                field("business value", CustomFieldKind::Select, &["low", "high"], true),
+                field("estimate (days)", CustomFieldKind::Number, &[], false),
+                field("sponsor", CustomFieldKind::Text, &[], false),
+            ],
+        }
+    }
+
+    fn values(pairs: &[(&str, Value)]) -> HashMap<String, Value> {
+        pairs.iter().map(|(name, value)| (name.to_string(), value.clone())).collect()
+    }
+
+    fn tags(tags: &[&str]) -> Vec<String> {
+        tags.iter().map(|tag| tag.to_string()).collect()
+    }
+
+    #[test]
+    fn test_valid_submission_keeps_filled_in_values() {
+        let submitted = values(&[
+            ("business value", json!("high")),
+            ("estimate (days)", json!("2.5")),
+            ("sponsor", json!("  ")),
+        ]);
+        let stored = form().validate("", &tags(&["ops"]), submitted).unwrap();
+        assert_eq!(stored, values(&[("business value", json!("high")), ("estimate (days)", json!(2.5))]));
+
+        // Numbers may come as numbers too, and whole ones stay whole
+        let submitted = values(&[("business value", json!("low")), ("estimate (days)", json!(3))]);
+        let stored = form().validate("", &tags(&["ops"]), submitted).unwrap();
+        assert_eq!(stored["estimate (days)"], json!(3));
+        let stored = form()
+            .validate("", &tags(&["ops"]), values(&[("business value", json!("low")), ("estimate (days)", json!("3"))]))
+            .unwrap();
+        assert_eq!(stored["estimate (days)"], json!(3));
+    }
+
+    #[test]
+    fn test_every_problem_is_reported() {
+        let submitted = values(&[
+            ("estimate (days)", json!("soon")),
+            ("sponsor", json!(["not", "text"])),
+            ("colour", json!("blue")),
+        ]);
+        let Err(IdeaError::Validation(message)) = form().validate("", &[], submitted) else {
+            panic!("the submission must be refused");
+        };
+        assert_eq!(
+            message,
+            "At least one tag is required; business value is required; estimate (days) must be a number; sponsor \
+             has a value of the wrong kind; The form has no field colour"
+        );
+    }
+
+    #[test]
+    fn test_select_only_takes_its_options() {
+        let submitted = values(&[("business value", json!("medium"))]);
+        let result = form().validate("", &tags(&["ops"]), submitted);
+        assert_eq!(
+            result,
+            Err(IdeaError::Validation("medium isn't one of the choices for business value".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_builtin_rules() {
+        let default = FormConfig::default();
+        assert_eq!(
+            default.validate(" ", &[], HashMap::new()),
+            Err(IdeaError::Validation("Description is required".to_string()))
+        );
+        assert_eq!(default.validate("Something", &[], HashMap::new()), Ok(HashMap::new()));
+
+        // Hidden fields are never required
+        let hidden = FormConfig {
+            description: FieldRule::Hidden,
+            tags: FieldRule::Hidden,
+            custom_fields: Vec::new(),
+        };
+        assert_eq!(hidden.validate("", &[], HashMap::new()), Ok(HashMap::new()));
+        assert!(!FieldRule::Hidden.is_shown() && !FieldRule::Hidden.is_required());
+    }
+
+    #[test]
+    fn test_text_values_are_capped() {
+        let sponsor = field("sponsor", CustomFieldKind::Text, &[], false);
+        let long = "x".repeat(MAX_CUSTOM_TEXT_CHARS + 1);
+        assert!(sponsor.value_from_input(&long).is_err());
+        assert_eq!(sponsor.value_from_input(" Sam "), Ok(Some(json!("Sam"))));
+    }
+
+    #[test]
+    fn test_checked_tidies_and_refuses_bad_schemas() {
+        let config = FormConfig {
+            custom_fields: vec![field(" value ", CustomFieldKind::Select, &[" low", "", "high "], false)],
+            ..Default::default()
+        };
+        let checked = config.checked().unwrap();
+        assert_eq!(checked.custom_fields[0].name, "value");
+        assert_eq!(checked.custom_fields[0].options, vec!["low", "high"]);
+
+        let config = FormConfig {
+            custom_fields: vec![
+                field("", CustomFieldKind::Text, &[], false),
+                field("Value", CustomFieldKind::Select, &[], false),
+                field("value", CustomFieldKind::Number, &["1"], false),
+                field("size", CustomFieldKind::Select, &["s", "s"], false),
+            ],
+            ..Default::default()
+        };
+        let Err(IdeaError::Validation(message)) = config.checked() else {
+            panic!("the schema must be refused");
+        };
+        assert_eq!(
+            message,
+            "field 0: the name is empty; field 1: a select needs options; field 2: value is used twice; field 2: only \
+             a select has options; field 3: an option is listed twice"
+        );
+
+        let too_many = FormConfig {
+            custom_fields: (0..=MAX_CUSTOM_FIELDS)
+                .map(|index| field(&format!("field {}", index), CustomFieldKind::Text, &[], false))
+                .collect(),
+            ..Default::default()
+        };
+        assert!(too_many.checked().is_err());
+    }
+
+    #[test]
+    fn test_inputs_render_by_kind() {
+        let [value, estimate, sponsor] = <[CustomField; 3]>::try_from(form().custom_fields).unwrap();
+        assert_eq!(value.input_type(), None);
+        assert_eq!(estimate.input_type(), Some("number"));
+        assert_eq!(sponsor.input_type(), Some("text"));
+
+        assert_eq!(value.label(), "business value");
+        assert_eq!(estimate.label(), "estimate (days) (optional)");
+
+        assert_eq!(display_value(&json!("high")), "high");
+        assert_eq!(display_value(&json!(3)), "3");
+        assert_eq!(display_value(&json!(2.5)), "2.5");
+        assert_eq!(display_value(&Value::Null), "");
+        // Values of fields removed or changed since still show
+        assert_eq!(display_value(&json!({ "legacy": true })), "{\"legacy\":true}");
+    }
+
+    #[test]
+    fn test_stored_configs_of_any_age_load() {
+        let config: FormConfig = serde_json::from_str("{}").unwrap();
+        assert_eq!(config, FormConfig::default());
+        let config: FormConfig =
+            serde_json::from_str(r#"{"tags": "required", "custom_fields": [{"name": "team"}]}"#).unwrap();
+        assert_eq!(config.tags, FieldRule::Required);
+        assert_eq!(config.description, FieldRule::Required);
+        assert_eq!(config.custom_fields[0].kind, CustomFieldKind::Text);
+    }
+}
@@ -0,0 +1,289 @@
+//! Graph of ideas and the `relates_to` links between them.
+//!
+//! Links are SurrealDB graph edges, `RELATE ideas:a->relates_to->ideas:b`, which the database removes along with
+//! either idea when it is purged. Nothing in the app creates them yet. The graph view places the ideas with a
+//! small force-directed layout: linked ideas pull together, every pair pushes apart, and the moves shrink each
+//! iteration until the layout settles. The starting positions come from a seeded generator, so the same graph is
+//! always drawn the same way.
+
+use crate::db::Idea;
+use serde::{Deserialize, Serialize};
+
+/// Size of the area the layout places nodes in; the view scales it to fit
+pub const GRAPH_WIDTH: f64 = 800.0;
+pub const GRAPH_HEIGHT: f64 = 600.0;
+/// Space kept free along the edges of the area, so labels aren't cut off
+pub const GRAPH_MARGIN: f64 = 40.0;
+/// Seed of the starting positions
+pub const LAYOUT_SEED: u64 = 42;
+/// Rounds of repulsion and attraction
+pub const LAYOUT_ITERATIONS: usize = 300;
+/// Pull towards the centre, keeping unlinked ideas from drifting to the corners
+const GRAVITY: f64 = 0.05;
+
+/// An idea in the graph
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GraphNode {
+    pub id: String,
+    pub title: String,
+    pub slug: String,
+}
+
+/// A link from one idea to another, by id
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GraphEdge {
+    pub from: String,
+    pub to: String,
+}
+
+/// The ideas of a user and the links between them
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct IdeaGraph {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+impl IdeaGraph {
+    /// Graph of `ideas`, keeping only the links whose ends are both among them
+    pub fn new(ideas: Vec<Idea>, links: Vec<GraphEdge>) -> Self {
+        let nodes: Vec<GraphNode> = ideas
+            .into_iter()
+            .filter_map(|idea| {
+                Some(GraphNode {
+                    id: idea.id?,
+                    title: idea.title,
+                    slug: idea.slug,
+                })
+            })
+            .collect();
+        let known = |id: &str| nodes.iter().any(|node| node.id == id);
+        let edges = links
+            .into_iter()
+            .filter(|edge| edge.from != edge.to && known(&edge.from) && known(&edge.to))
+            .collect();
+        Self { nodes, edges }
+    }
+
+    /// The edges as pairs of node indices
+    pub fn edge_indices(&self) -> Vec<(usize, usize)> {
+        let index = |id: &str| self.nodes.iter().position(|node| node.id == id);
+        self.edges
+            .iter()
+            .filter_map(|edge| Some((index(&edge.from)?, index(&edge.to)?)))
+            .collect()
+    }
+
+    /// Where to draw each node, in the order of `nodes`
+    pub fn layout(&self) -> Vec<Point> {
+        layout(self.nodes.len(), &self.edge_indices(), LAYOUT_SEED, LAYOUT_ITERATIONS)
+    }
+}
+
+/// A position in the layout area
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+}
+
+/// Linear congruential generator: tiny, and the same numbers for the same seed on every platform
+struct Lcg(u64);
+
+impl Lcg {
+    /// A number in `0.0..1.0`
+    fn next_unit(&mut self) -> f64 {
+        self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (self.0 >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// Place `node_count` nodes joined by `edges` (pairs of node indices) inside the layout area.
+///
+/// Fruchterman-Reingold: every pair of nodes repels, every edge attracts, and each node moves along its net force
+/// by at most a "temperature" that cools to zero over `iterations`. The result only depends on the arguments.
+pub fn layout(node_count: usize, edges: &[(usize, usize)], seed: u64, iterations: usize) -> Vec<Point> {
+    let mut rng = Lcg(seed);
+    let mut positions: Vec<Point> = (0..node_count)
+        .map(|_| Point {
+            x: GRAPH_MARGIN + rng.next_unit() * (GRAPH_WIDTH - 2.0 * GRAPH_MARGIN),
+            y: GRAPH_MARGIN + rng.next_unit() * (GRAPH_HEIGHT - 2.0 * GRAPH_MARGIN),
+        })
+        .collect();
+    if node_count == 0 {
+        return positions;
+    }
+
+    // Distance two linked nodes settle at
+    let ideal = (GRAPH_WIDTH * GRAPH_HEIGHT / node_count as f64).sqrt() * 0.5;
+    let mut temperature = GRAPH_WIDTH / 10.0;
+    let cooling = temperature / (iterations as f64 + 1.0);
+
+    for _ in 0..iterations {
+        let mut moves = vec![(0.0, 0.0); node_count];
+
+        for a in 0..node_count {
+            for b in a + 1..node_count {
+                let (dx, dy, distance) = offset(positions[a], positions[b]);
+                let force = ideal * ideal / distance;
+                moves[a].0 += dx / distance * force;
+                moves[a].1 += dy / distance * force;
+                moves[b].0 -= dx / distance * force;
+                moves[b].1 -= dy / distance * force;
+            }
+        }
+
+        for &(a, b) in edges {
+            if a == b || a >= node_count || b >= node_count {
+                continue;
+            }
+            let (dx, dy, distance) = offset(positions[a], positions[b]);
+            let force = distance * distance / ideal;
+            moves[a].0 -= dx / distance * force;
+            moves[a].1 -= dy / distance * force;
+            moves[b].0 += dx / distance * force;
+            moves[b].1 += dy / distance * force;
+        }
+
+        for (position, (mx, my)) in positions.iter_mut().zip(moves) {
+            let mx = mx - (position.x - GRAPH_WIDTH / 2.0) * GRAVITY * ideal / 10.0;
+            let my = my - (position.y - GRAPH_HEIGHT / 2.0) * GRAVITY * ideal / 10.0;
+            let length = (mx * mx + my * my).sqrt();
+            if length > 0.0 {
+                let step = length.min(temperature);
+                position.x += mx / length * step;
+                position.y += my / length * step;
+            }
+            position.x = position.x.clamp(GRAPH_MARGIN, GRAPH_WIDTH - GRAPH_MARGIN);
+            position.y = position.y.clamp(GRAPH_MARGIN, GRAPH_HEIGHT - GRAPH_MARGIN);
+        }
+
+        temperature -= cooling;
+    }
+
+    positions
+}
+
+/// How far `a` is from `b` along each axis and in a straight line, never quite zero so forces stay finite
+fn offset(a: Point, b: Point) -> (f64, f64, f64) {
+    let dx = a.x - b.x;
+    let dy = a.y - b.y;
+    (dx, dy, (dx * dx + dy * dy).sqrt().max(0.01))
+}
+
+// Server-only queries of the links
+#[cfg(feature = "server")]
+pub mod server {
+    use super::*;
+    use crate::db::IdeaId;
+    use surrealdb::sql::Thing;
+    use surrealdb::Surreal;
+
+    /// The `relates_to` edges whose ends are both among `ideas`
+    pub async fn idea_links(
+        db: &Surreal<surrealdb::engine::any::Any>,
+        ideas: &[Thing],
+    ) -> surrealdb::Result<Vec<GraphEdge>> {
+        #[derive(Deserialize)]
+        struct LinkRow {
+            #[serde(rename = "in")]
+            from: Thing,
+            #[serde(rename = "out")]
+            to: Thing,
+        }
+
+        let rows: Vec<LinkRow> = db
+            .query("SELECT in, out FROM relates_to WHERE in INSIDE $ideas AND out INSIDE $ideas")
+            .bind(("ideas", ideas.to_vec()))
+            .await?
+            .take(0)?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| GraphEdge {
+                from: IdeaId::from(&row.from).into(),
+                to: IdeaId::from(&row.to).into(),
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn distance(a: Point, b: Point) -> f64 {
+        offset(a, b).2
+    }
+
+    /// Two triangles joined by one edge, and a node on its own
+    fn fixture() -> (usize, Vec<(usize, usize)>) {
+        (7, vec![(0, 1), (1, 2), (2, 0), (3, 4), (4, 5), (5, 3), (2, 3)])
+    }
+
+    #[test]
+    fn test_layout_is_deterministic() {
+        let (count, edges) = fixture();
+        let first = layout(count, &edges, LAYOUT_SEED, LAYOUT_ITERATIONS);
+        let second = layout(count, &edges, LAYOUT_SEED, LAYOUT_ITERATIONS);
+        assert_eq!(first, second);
+
+        let other_seed = layout(count, &edges, LAYOUT_SEED + 1, LAYOUT_ITERATIONS);
+        assert_ne!(first, other_seed);
+    }
+
+    #[test]
+    fn test_layout_stays_inside_the_area() {
+        let (count, edges) = fixture();
+        for point in layout(count, &edges, LAYOUT_SEED, LAYOUT_ITERATIONS) {
+            assert!((GRAPH_MARGIN..=GRAPH_WIDTH - GRAPH_MARGIN).contains(&point.x));
+            assert!((GRAPH_MARGIN..=GRAPH_HEIGHT - GRAPH_MARGIN).contains(&point.y));
+        }
+    }
+
+    #[test]
+    fn test_linked_nodes_end_up_closer() {
+        let (count, edges) = fixture();
+        let points = layout(count, &edges, LAYOUT_SEED, LAYOUT_ITERATIONS);
+
+        let linked: f64 = edges.iter().map(|&(a, b)| distance(points[a], points[b])).sum::<f64>() / edges.len() as f64;
+        let unlinked: Vec<f64> = (0..count)
+            .flat_map(|a| (a + 1..count).map(move |b| (a, b)))
+            .filter(|&(a, b)| !edges.contains(&(a, b)) && !edges.contains(&(b, a)))
+            .map(|(a, b)| distance(points[a], points[b]))
+            .collect();
+        let unlinked = unlinked.iter().sum::<f64>() / unlinked.len() as f64;
+        assert!(linked < unlinked, "linked {} vs unlinked {}", linked, unlinked);
+    }
+
+    #[test]
+    fn test_layout_settles() {
+        // Once cooled, more rounds barely move anything
+        let (count, edges) = fixture();
+        let settled = layout(count, &edges, LAYOUT_SEED, LAYOUT_ITERATIONS);
+        let longer = layout(count, &edges, LAYOUT_SEED, LAYOUT_ITERATIONS * 2);
+        for (a, b) in settled.iter().zip(&longer) {
+            assert!(distance(*a, *b) < GRAPH_WIDTH / 10.0);
+        }
+    }
+
+    #[test]
+    fn test_graph_drops_dangling_and_self_links() {
+        let idea = |id: &str| Idea {
+            id: Some(id.to_string()),
+            title: id.to_string(),
+            ..Default::default()
+        };
+        let edge = |from: &str, to: &str| GraphEdge {
+            from: from.to_string(),
+            to: to.to_string(),
+        };
+        let graph = IdeaGraph::new(
+            vec![idea("ideas:a"), idea("ideas:b"), Idea::default()],
+            vec![edge("ideas:a", "ideas:b"), edge("ideas:a", "ideas:gone"), edge("ideas:b", "ideas:b")],
+        );
+        assert_eq!(graph.nodes.len(), 2);
+        assert_eq!(graph.edges, vec![edge("ideas:a", "ideas:b")]);
+        assert_eq!(graph.edge_indices(), vec![(0, 1)]);
+        assert!(layout(0, &[], LAYOUT_SEED, LAYOUT_ITERATIONS).is_empty());
+    }
+}
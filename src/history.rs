@@ -0,0 +1,324 @@
+//! Activity log of each idea.
+//!
+//! Every change to an idea appends an entry to the `idea_events` table: created, edited (with a summary of what
+//! changed), trashed, restored, pinned, unpinned, merged with another idea and kept in a review. Entries are never
+//! rewritten. They stay while the idea is in the trash and are removed together with it by a database event when it
+//! is purged, or when it is merged into another idea.
+
+use crate::db::Idea;
+use crate::utils::format_due_date;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+/// What happened to an idea
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IdeaEventKind {
+    Created,
+    Edited,
+    Trashed,
+    Restored,
+    Pinned,
+    Unpinned,
+    Merged,
+    /// Looked at in a review and kept as it is
+    Reviewed,
+}
+
+impl IdeaEventKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            IdeaEventKind::Created => "created",
+            IdeaEventKind::Edited => "edited",
+            IdeaEventKind::Trashed => "moved to trash",
+            IdeaEventKind::Restored => "restored",
+            IdeaEventKind::Pinned => "pinned",
+            IdeaEventKind::Unpinned => "unpinned",
+            IdeaEventKind::Merged => "merged",
+            IdeaEventKind::Reviewed => "reviewed",
+        }
+    }
+}
+
+/// One entry of an idea's history as sent to the client
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IdeaEvent {
+    pub kind: IdeaEventKind,
+    pub summary: String,
+    pub at: DateTime<Utc>,
+}
+
+/// "1 tag", "3 tags"
+fn count_of(count: usize, noun: &str) -> String {
+    if count == 1 {
+        format!("1 {}", noun)
+    } else {
+        format!("{} {}s", count, noun)
+    }
+}
+
+/// How many entries of `after` are missing from `before` and the other way round, counting duplicates
+fn added_and_removed(before: &[String], after: &[String]) -> (usize, usize) {
+    let mut remaining: Vec<&String> = before.iter().collect();
+    let mut added = 0;
+    for entry in after {
+        match remaining.iter().position(|candidate| *candidate == entry) {
+            Some(index) => {
+                remaining.swap_remove(index);
+            }
+            None => added += 1,
+        }
+    }
+    (added, remaining.len())
+}
+
+/// Describe what changed between two versions of an idea, e.g. "added 2 statements, edited notes". `None` when
+/// nothing did.
+///
+/// A statement whose text changed counts as edited rather than as one removed and one added.
+pub fn summarize_edit(before: &Idea, after: &Idea) -> Option<String> {
+    let mut parts = Vec::new();
+
+    if before.title != after.title {
+        parts.push(format!("renamed to \"{}\"", after.title));
+    }
+    if before.description != after.description {
+        parts.push("edited description".to_string());
+    }
+
+    let (tags_added, tags_removed) = added_and_removed(&before.tags, &after.tags);
+    if tags_added > 0 {
+        parts.push(format!("added {}", count_of(tags_added, "tag")));
+    }
+    if tags_removed > 0 {
+        parts.push(format!("removed {}", count_of(tags_removed, "tag")));
+    }
+
+    let (added, removed) = added_and_removed(&before.what_must_be_true, &after.what_must_be_true);
+    let edited = added.min(removed);
+    if added > edited {
+        parts.push(format!("added {}", count_of(added - edited, "statement")));
+    }
+    if edited > 0 {
+        parts.push(format!("edited {}", count_of(edited, "statement")));
+    }
+    if removed > edited {
+        parts.push(format!("removed {}", count_of(removed - edited, "statement")));
+    }
+
+    if before.development_notes != after.development_notes {
+        parts.push("edited notes".to_string());
+    }
+    if before.sections != after.sections {
+        parts.push("edited sections".to_string());
+    }
+
+    if before.due_date != after.due_date {
+        parts.push(match after.due_date {
+            Some(date) => format!("due {}", format_due_date(date)),
+            None => "removed due date".to_string(),
+        });
+    }
+
+    (!parts.is_empty()).then(|| parts.join(", "))
+}
+
+/// Edits fewer than this many minutes apart are shown as one entry
+pub const EDIT_BURST_MINUTES: i64 = 10;
+
+/// Merge runs of edits into single timeline entries.
+///
+/// The editor saves as the user types, so one sitting produces many edit events. Events are newest first; a run
+/// of edits each within [`EDIT_BURST_MINUTES`] of the next keeps the newest time and lists each kind of change once.
+pub fn collapse_edits(events: Vec<IdeaEvent>) -> Vec<IdeaEvent> {
+    let mut collapsed: Vec<IdeaEvent> = Vec::with_capacity(events.len());
+    // Time of the previous event, which may already be folded into the last entry
+    let mut previous_at: Option<DateTime<Utc>> = None;
+
+    for event in events {
+        let joins_run = matches!(
+            (collapsed.last(), previous_at),
+            (Some(last), Some(previous_at))
+                if last.kind == IdeaEventKind::Edited
+                    && event.kind == IdeaEventKind::Edited
+                    && previous_at - event.at <= Duration::minutes(EDIT_BURST_MINUTES)
+        );
+        previous_at = Some(event.at);
+
+        match collapsed.last_mut() {
+            Some(last) if joins_run => {
+                for part in event.summary.split(", ") {
+                    if !last.summary.split(", ").any(|existing| existing == part) {
+                        last.summary = format!("{}, {}", last.summary, part);
+                    }
+                }
+            }
+            _ => collapsed.push(event),
+        }
+    }
+
+    collapsed
+}
+
+// Server-only storage of the log
+#[cfg(feature = "server")]
+pub mod server {
+    use super::*;
+    use surrealdb::sql::Thing;
+
+    /// Log entry as stored in the database
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct IdeaEventRecord {
+        pub id: Option<Thing>,
+        pub idea: Thing,
+        pub kind: IdeaEventKind,
+        pub summary: String,
+        pub at: DateTime<Utc>,
+    }
+
+    impl IdeaEventRecord {
+        /// A new entry for `idea`, timestamped now
+        pub fn now(idea: Thing, kind: IdeaEventKind, summary: impl Into<String>) -> Self {
+            Self {
+                id: None,
+                idea,
+                kind,
+                summary: summary.into(),
+                at: Utc::now(),
+            }
+        }
+    }
+
+    impl From<IdeaEventRecord> for IdeaEvent {
+        fn from(record: IdeaEventRecord) -> Self {
+            IdeaEvent {
+                kind: record.kind,
+                summary: record.summary,
+                at: record.at,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    fn idea(statements: &[&str], notes: &str) -> Idea {
+        Idea {
+            title: "Idea".to_string(),
+            what_must_be_true: strings(statements),
+            development_notes: notes.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn event(kind: IdeaEventKind, summary: &str, minutes_ago: i64) -> IdeaEvent {
+        let now = DateTime::parse_from_rfc3339("2024-05-01T12:00:00Z").unwrap().with_timezone(&Utc);
+        IdeaEvent {
+            kind,
+            summary: summary.to_string(),
+            at: now - Duration::minutes(minutes_ago),
+        }
+    }
+
+    #[test]
+    fn test_summary_of_added_statements_and_notes() {
+        let before = idea(&["a"], "");
+        let after = idea(&["a", "b", "c"], "Some notes");
+
+        assert_eq!(summarize_edit(&before, &after).unwrap(), "added 2 statements, edited notes");
+    }
+
+    #[test]
+    fn test_converting_notes_to_sections_is_an_edit_of_both() {
+        let before = idea(&[], "## Problem\nRust");
+        let mut after = idea(&[], "");
+        after.sections = vec![crate::db::NoteSection {
+            heading: "Problem".to_string(),
+            body: "Rust".to_string(),
+        }];
+        assert_eq!(summarize_edit(&before, &after).unwrap(), "edited notes, edited sections");
+    }
+
+    #[test]
+    fn test_changed_statement_counts_as_edited() {
+        let before = idea(&["a", "b"], "");
+        let after = idea(&["a", "b!"], "");
+        assert_eq!(summarize_edit(&before, &after).unwrap(), "edited 1 statement");
+
+        let after = idea(&["b"], "");
+        assert_eq!(summarize_edit(&before, &after).unwrap(), "removed 1 statement");
+    }
+
+    #[test]
+    fn test_summary_of_title_description_and_tags() {
+        let before = Idea {
+            title: "Old".to_string(),
+            tags: strings(&["ux", "api"]),
+            ..Default::default()
+        };
+        let after = Idea {
+            title: "New".to_string(),
+            description: "Now described".to_string(),
+            tags: strings(&["ux", "infra", "db"]),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            summarize_edit(&before, &after).unwrap(),
+            "renamed to \"New\", edited description, added 2 tags, removed 1 tag"
+        );
+    }
+
+    #[test]
+    fn test_summary_of_due_date() {
+        let before = idea(&[], "");
+        let after = Idea {
+            due_date: crate::utils::parse_due_date("2024-06-01"),
+            ..before.clone()
+        };
+
+        assert_eq!(summarize_edit(&before, &after).unwrap(), "due 2024-06-01");
+        assert_eq!(summarize_edit(&after, &before).unwrap(), "removed due date");
+    }
+
+    #[test]
+    fn test_reordering_is_not_a_change() {
+        let before = idea(&["a", "b"], "notes");
+        let after = idea(&["b", "a"], "notes");
+        assert_eq!(summarize_edit(&before, &after), None);
+    }
+
+    #[test]
+    fn test_edit_bursts_are_collapsed() {
+        let events = vec![
+            event(IdeaEventKind::Edited, "edited notes", 0),
+            event(IdeaEventKind::Edited, "edited notes", 4),
+            event(IdeaEventKind::Edited, "added 1 statement", 9),
+            event(IdeaEventKind::Created, "", 12),
+        ];
+
+        let collapsed = collapse_edits(events);
+        assert_eq!(collapsed.len(), 2);
+        assert_eq!(collapsed[0].summary, "edited notes, added 1 statement");
+        assert_eq!(collapsed[0].at, event(IdeaEventKind::Edited, "", 0).at);
+        assert_eq!(collapsed[1].kind, IdeaEventKind::Created);
+    }
+
+    #[test]
+    fn test_gaps_and_other_events_split_bursts() {
+        let events = vec![
+            event(IdeaEventKind::Edited, "edited notes", 0),
+            event(IdeaEventKind::Edited, "edited notes", 30),
+            event(IdeaEventKind::Restored, "", 31),
+            event(IdeaEventKind::Edited, "edited notes", 32),
+        ];
+
+        assert_eq!(collapse_edits(events).len(), 4);
+    }
+}
@@ -0,0 +1,1681 @@
+//! Custom hooks shared by components. Like every hook, these must be called unconditionally and in the same
+//! order on each render.
+
+use crate::config::AppConfig;
+use crate::error::is_retriable;
+use crate::server_functions::{get_app_config_server, get_tag_colors_server, health_server};
+use crate::settings::{
+    load_active_board, load_display_name, save_active_board, save_display_name, AutoSave, BoardLayout, ListDensity,
+    TimeDisplay,
+};
+use crate::storage::{default_storage, Storage};
+use crate::tags::TagColors;
+use crate::theme::Theme;
+use crate::utils::{
+    absolute_time, hover_card_position, local_now, local_time, relative_time, Activity, Coalescer, HoverIntent,
+    HoverPlacement, HoverPosition, IdleChange, IdleTracker, LeaveGuard, RetryPolicy, SwipeAxis, SwipeGesture,
+    HOVER_INTENT_MS, SWIPE_ACTIONS_WIDTH,
+};
+use crate::Route;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use dioxus::core::spawn_forever;
+use dioxus::prelude::*;
+use std::cell::RefCell;
+use std::future::Future;
+use std::rc::Rc;
+use std::time::Duration;
+
+/// Pointer-driven drag state for reordering a list, returned by [`use_drag_list`].
+///
+/// Items are indices by default. Boards drag between several lists by using a position that names the list too,
+/// e.g. `(column, index)`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DragList<T: 'static = usize> {
+    dragging: Signal<Option<T>>,
+    over: Signal<Option<T>>,
+}
+
+impl<T: Copy + PartialEq + 'static> DragList<T> {
+    /// Start dragging the item at `index` (call from the handle's `onpointerdown`)
+    pub fn start(&mut self, index: T) {
+        self.dragging.set(Some(index));
+        self.over.set(Some(index));
+    }
+
+    /// The pointer moved over the item at `index` (call from the item's `onpointerenter`)
+    pub fn enter(&mut self, index: T) {
+        if self.dragging.peek().is_some() {
+            self.over.set(Some(index));
+        }
+    }
+
+    /// Finish the drag (call from `onpointerup`), returning `(from, to)` when the item actually moved
+    pub fn finish(&mut self) -> Option<(T, T)> {
+        let moved = match (*self.dragging.peek(), *self.over.peek()) {
+            (Some(from), Some(to)) if from != to => Some((from, to)),
+            _ => None,
+        };
+        self.cancel();
+        moved
+    }
+
+    /// Abort the drag without moving anything
+    pub fn cancel(&mut self) {
+        self.dragging.set(None);
+        self.over.set(None);
+    }
+
+    /// Whether the item at `index` is the one being dragged
+    pub fn is_dragging(&self, index: T) -> bool {
+        (self.dragging)() == Some(index)
+    }
+
+    /// Whether the item at `index` is the current drop target
+    pub fn is_over(&self, index: T) -> bool {
+        (self.dragging)().is_some() && (self.over)() == Some(index) && !self.is_dragging(index)
+    }
+
+    /// Whether the current drop target matches `target`, e.g. any position in one column
+    pub fn is_over_where(&self, target: impl Fn(T) -> bool) -> bool {
+        match ((self.dragging)(), (self.over)()) {
+            (Some(dragging), Some(over)) => over != dragging && target(over),
+            _ => false,
+        }
+    }
+}
+
+/// Track a drag-to-reorder gesture over a list using pointer events
+pub fn use_drag_list<T: Copy + PartialEq + 'static>() -> DragList<T> {
+    let dragging = use_signal(|| None);
+    let over = use_signal(|| None);
+    DragList { dragging, over }
+}
+
+/// Swipe state of one card, returned by [`use_swipe`]: whether its actions are revealed and the touch in progress.
+///
+/// Only touch pointers swipe; a mouse keeps selecting text and clicking the buttons as usual. Give the card
+/// `touch-action: pan-y` so the browser keeps vertical scrolling to itself and sends `pointercancel` when it takes
+/// over.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Swipe {
+    gesture: Signal<Option<SwipeGesture>>,
+    open: Signal<bool>,
+}
+
+impl Swipe {
+    /// Call from the card's `onpointerdown`
+    pub fn down(&mut self, event: &PointerEvent) {
+        if event.pointer_type() != "touch" {
+            return;
+        }
+        let point = event.client_coordinates();
+        let open = *self.open.peek();
+        self.gesture.set(Some(SwipeGesture::start(point.x, point.y, swipe_clock(), open)));
+    }
+
+    /// Call from the card's `onpointermove`
+    pub fn moved(&mut self, event: &PointerEvent) {
+        if self.gesture.peek().is_none() {
+            return;
+        }
+        let point = event.client_coordinates();
+        if let Some(gesture) = self.gesture.write().as_mut() {
+            gesture.move_to(point.x, point.y, swipe_clock());
+        }
+    }
+
+    /// Call from the card's `onpointerup`: the card snaps open or closed
+    pub fn up(&mut self) {
+        let finished = self.gesture.write().take();
+        if let Some(gesture) = finished {
+            self.open.set(gesture.finish());
+        }
+    }
+
+    /// Call from the card's `onpointercancel`: the browser scrolled instead, so the card goes back where it was
+    pub fn cancel(&mut self) {
+        self.gesture.set(None);
+    }
+
+    /// Hide the actions again, e.g. after one of them was used
+    pub fn close(&mut self) {
+        self.gesture.set(None);
+        self.open.set(false);
+    }
+
+    /// Whether the actions are revealed
+    pub fn is_open(&self) -> bool {
+        (self.open)()
+    }
+
+    /// Whether a finger is moving the card, during which it follows without the snap animation
+    pub fn is_swiping(&self) -> bool {
+        (self.gesture)().is_some_and(|gesture| gesture.axis() == SwipeAxis::Horizontal)
+    }
+
+    /// Horizontal offset of the card in pixels, for a `translateX`
+    pub fn offset(&self) -> f64 {
+        match (self.gesture)() {
+            Some(gesture) => gesture.offset(),
+            None if (self.open)() => -SWIPE_ACTIONS_WIDTH,
+            None => 0.0,
+        }
+    }
+}
+
+/// Milliseconds for [`SwipeGesture`], which only compares them
+fn swipe_clock() -> f64 {
+    chrono::Utc::now().timestamp_millis() as f64
+}
+
+/// Swipe a card left to reveal actions behind it, see [`Swipe`]
+pub fn use_swipe() -> Swipe {
+    let gesture = use_signal(|| None);
+    let open = use_signal(|| false);
+    Swipe { gesture, open }
+}
+
+/// Height [`use_hover_card`] plans for when picking a side of the anchor; the card's CSS caps it at this
+pub const HOVER_CARD_HEIGHT: f64 = 240.0;
+
+/// A floating card anchored to an element, returned by [`use_hover_card`].
+///
+/// With a mouse the card opens once the pointer rested on the anchor for [`HOVER_INTENT_MS`] and closes when it
+/// leaves. On a touch screen a press held as long opens it, and a second press closes it. Scrolling the page always
+/// closes it, as the card stays where it was put.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HoverCard {
+    intent: Signal<HoverIntent>,
+    anchor: Signal<Option<Rc<MountedData>>>,
+    position: Signal<Option<HoverPosition>>,
+}
+
+impl HoverCard {
+    /// Pass the anchor's `onmounted` event here
+    pub fn mounted(&mut self, event: MountedEvent) {
+        self.anchor.set(Some(event.data()));
+    }
+
+    /// Call from the anchor's `onpointerenter`
+    pub fn enter(&mut self, event: &PointerEvent) {
+        if event.pointer_type() != "touch" {
+            self.start();
+        }
+    }
+
+    /// Call from the anchor's `onpointerleave`. A card opened by a long press ignores it, as lifting the finger
+    /// counts as leaving.
+    pub fn leave(&mut self, event: &PointerEvent) {
+        if event.pointer_type() != "touch" {
+            self.close();
+        }
+    }
+
+    /// Call from the anchor's `onpointerdown`
+    pub fn press(&mut self, event: &PointerEvent) {
+        if event.pointer_type() != "touch" {
+            return;
+        }
+        if self.intent.peek().is_open() {
+            self.close();
+        } else {
+            self.start();
+        }
+    }
+
+    /// Call from the anchor's `onpointerup` and `onpointercancel`: a press let go early, or turned into a scroll or
+    /// a swipe, opens nothing
+    pub fn release(&mut self) {
+        if !self.intent.peek().is_open() {
+            self.intent.write().leave();
+        }
+    }
+
+    pub fn close(&mut self) {
+        self.intent.write().leave();
+        self.position.set(None);
+    }
+
+    /// Whether the card is open and placed
+    pub fn is_open(&self) -> bool {
+        (self.position)().is_some()
+    }
+
+    /// Which side of the anchor the card is on, for styling its pointer
+    pub fn placement(&self) -> Option<HoverPlacement> {
+        (self.position)().map(|position| position.placement)
+    }
+
+    /// Inline CSS placing the card, for an element with `position: fixed`
+    pub fn style(&self) -> String {
+        (self.position)().map(|position| position.style()).unwrap_or_default()
+    }
+
+    fn start(&mut self) {
+        self.intent.write().enter(hover_clock());
+        let Some(opens_at) = self.intent.peek().opens_at() else {
+            return;
+        };
+        let mut card = *self;
+        spawn(async move {
+            sleep(Duration::from_millis((opens_at - hover_clock()).max(0.0).ceil() as u64)).await;
+            // A leave and a new enter in between leave this timer early for the new delay
+            if card.intent.write().tick(hover_clock()) {
+                card.place().await;
+            }
+        });
+    }
+
+    /// Put the open card on whichever side of the anchor has room
+    async fn place(&mut self) {
+        let Some(anchor) = self.anchor.peek().clone() else {
+            return;
+        };
+        let Ok(rect) = anchor.get_client_rect().await else {
+            return;
+        };
+        let viewport = document::eval("dioxus.send(window.innerHeight)")
+            .recv::<f64>()
+            .await
+            .unwrap_or(rect.max_y() + 2.0 * HOVER_CARD_HEIGHT);
+        // The pointer may have left while the anchor was measured
+        if self.intent.peek().is_open() {
+            let position = hover_card_position(rect.min_x(), rect.min_y(), rect.max_y(), HOVER_CARD_HEIGHT, viewport);
+            self.position.set(Some(position));
+        }
+    }
+}
+
+/// Milliseconds for [`HoverIntent`], which only compares them
+fn hover_clock() -> f64 {
+    chrono::Utc::now().timestamp_millis() as f64
+}
+
+/// A card that floats next to an element on hover or long press, see [`HoverCard`]. Give the element the
+/// handlers listed there and render the card while [`HoverCard::is_open`].
+pub fn use_hover_card() -> HoverCard {
+    let intent = use_signal(|| HoverIntent::new(HOVER_INTENT_MS));
+    let anchor = use_signal(|| None);
+    let position = use_signal(|| None);
+    let mut card = HoverCard { intent, anchor, position };
+
+    // Listen for the next scroll while the card is open. Capturing catches scrolling containers as well as the page.
+    use_effect(move || {
+        if position().is_none() {
+            return;
+        }
+        spawn(async move {
+            let mut scrolled = document::eval(
+                r#"
+                window.addEventListener("scroll", () => dioxus.send(true), {
+                    once: true, capture: true, passive: true,
+                });
+                "#,
+            );
+            if scrolled.recv::<bool>().await.is_ok() {
+                card.close();
+            }
+        });
+    });
+
+    card
+}
+
+/// Counter bumped whenever ideas move in or out of the trash, so the navbar's trash badge knows to refetch.
+///
+/// Provided once by the layout through [`use_trash_revision_provider`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrashRevision(Signal<u32>);
+
+impl TrashRevision {
+    /// Record that the trash changed
+    pub fn bump(&mut self) {
+        *self.0.write() += 1;
+    }
+
+    /// Current revision; reading it subscribes the caller to changes
+    pub fn get(&self) -> u32 {
+        (self.0)()
+    }
+}
+
+/// Provide the [`TrashRevision`] context for every route below the caller
+pub fn use_trash_revision_provider() -> TrashRevision {
+    use_context_provider(|| TrashRevision(Signal::new(0)))
+}
+
+/// The [`TrashRevision`] provided by the layout
+pub fn use_trash_revision() -> TrashRevision {
+    use_context::<TrashRevision>()
+}
+
+/// Counter bumped whenever ideas are added, deleted, restored or changed from any page or the command palette, so
+/// everything showing them refetches: the idea list, the upcoming strip and the navbar's counts.
+///
+/// Provided once by the layout through [`use_idea_list_revision_provider`], above both the navbar and the routes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IdeaListRevision(Signal<u32>);
+
+impl IdeaListRevision {
+    /// Record that some idea changed
+    pub fn bump(&mut self) {
+        *self.0.write() += 1;
+    }
+
+    /// Current revision; reading it subscribes the caller to changes
+    pub fn get(&self) -> u32 {
+        (self.0)()
+    }
+}
+
+/// Provide the [`IdeaListRevision`] context for every route below the caller
+pub fn use_idea_list_revision_provider() -> IdeaListRevision {
+    use_context_provider(|| IdeaListRevision(Signal::new(0)))
+}
+
+/// The [`IdeaListRevision`] provided by the layout
+pub fn use_idea_list_revision() -> IdeaListRevision {
+    use_context::<IdeaListRevision>()
+}
+
+/// Asks the home page to open its "add idea" form, e.g. from the command palette. The request stays pending until
+/// the home page takes it, so it can be made before navigating there.
+///
+/// Provided once by the layout through [`use_new_idea_request_provider`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NewIdeaRequest(Signal<bool>);
+
+impl NewIdeaRequest {
+    /// Ask for the form
+    pub fn request(&mut self) {
+        self.0.set(true);
+    }
+
+    /// Whether the form was asked for, clearing the request; reading it subscribes the caller to new requests
+    pub fn take(&mut self) -> bool {
+        let pending = (self.0)();
+        if pending {
+            self.0.set(false);
+        }
+        pending
+    }
+}
+
+/// Provide the [`NewIdeaRequest`] context for every route below the caller
+pub fn use_new_idea_request_provider() -> NewIdeaRequest {
+    use_context_provider(|| NewIdeaRequest(Signal::new(false)))
+}
+
+/// The [`NewIdeaRequest`] provided by the layout
+pub fn use_new_idea_request() -> NewIdeaRequest {
+    use_context::<NewIdeaRequest>()
+}
+
+/// How long a toast stays on screen
+const TOAST_DURATION: Duration = Duration::from_secs(3);
+
+/// Whether a toast confirms something or reports a failure
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToastKind {
+    Success,
+    Error,
+}
+
+impl ToastKind {
+    /// Value of the `data-kind` attribute the styles key on
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ToastKind::Success => "success",
+            ToastKind::Error => "error",
+        }
+    }
+}
+
+/// A short message shown in the corner of the page
+#[derive(Debug, Clone, PartialEq)]
+pub struct Toast {
+    pub id: u32,
+    pub message: String,
+    pub kind: ToastKind,
+}
+
+/// The toasts on screen, shown by the `ToastHost` component. Each one goes away after [`TOAST_DURATION`].
+///
+/// Provided once by the layout through [`use_toasts_provider`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Toasts {
+    list: Signal<Vec<Toast>>,
+    next_id: Signal<u32>,
+}
+
+impl Toasts {
+    /// Confirm that something worked
+    pub fn success(&mut self, message: impl Into<String>) {
+        self.show(message.into(), ToastKind::Success);
+    }
+
+    /// Report a failure
+    pub fn error(&mut self, message: impl Into<String>) {
+        self.show(message.into(), ToastKind::Error);
+    }
+
+    fn show(&mut self, message: String, kind: ToastKind) {
+        let id = *self.next_id.peek();
+        self.next_id.set(id + 1);
+        self.list.write().push(Toast { id, message, kind });
+
+        // Outlives the component that raised the toast, which may be gone by then (a deleted card)
+        let mut toasts = *self;
+        spawn_forever(async move {
+            sleep(TOAST_DURATION).await;
+            toasts.dismiss(id);
+        });
+    }
+
+    /// Remove a toast before it expires
+    pub fn dismiss(&mut self, id: u32) {
+        self.list.write().retain(|toast| toast.id != id);
+    }
+
+    /// The toasts on screen, oldest first; reading them subscribes the caller to changes
+    pub fn list(&self) -> Vec<Toast> {
+        (self.list)()
+    }
+}
+
+/// Provide the [`Toasts`] context for every route below the caller
+pub fn use_toasts_provider() -> Toasts {
+    use_context_provider(|| Toasts {
+        list: Signal::new(Vec::new()),
+        next_id: Signal::new(0),
+    })
+}
+
+/// The [`Toasts`] provided by the layout
+pub fn use_toasts() -> Toasts {
+    use_context::<Toasts>()
+}
+
+/// Toggle `open` on Cmd+K (Ctrl+K elsewhere) wherever focus is on the page. The listener is installed once and
+/// lives as long as the page, so call this from the layout.
+pub fn use_palette_shortcut(mut open: Signal<bool>) {
+    // Effects only run in the client, where there is a page to listen on
+    use_effect(move || {
+        spawn(async move {
+            let mut shortcut = document::eval(
+                r#"
+                window.addEventListener("keydown", (event) => {
+                    if ((event.metaKey || event.ctrlKey) && event.key.toLowerCase() === "k") {
+                        event.preventDefault();
+                        dioxus.send(true);
+                    }
+                });
+                "#,
+            );
+            while shortcut.recv::<bool>().await.is_ok() {
+                open.toggle();
+            }
+        });
+    });
+}
+
+/// Removes the listener installed by [`use_undo_shortcuts`]
+const UNDO_SHORTCUTS_STOP_JS: &str = r#"
+    if (window.__undoShortcuts) {
+        window.removeEventListener("keydown", window.__undoShortcuts);
+        window.__undoShortcuts = null;
+    }
+"#;
+
+/// Call `on_undo` on Cmd+Z (Ctrl+Z elsewhere) and `on_redo` on Shift+Cmd+Z, instead of the browser's own undo of
+/// the focused field. Unlike [`use_palette_shortcut`] the listener belongs to the calling page and is removed when
+/// it unmounts.
+pub fn use_undo_shortcuts(on_undo: Callback<()>, on_redo: Callback<()>) {
+    // Effects only run in the client, where there is a page to listen on
+    use_effect(move || {
+        spawn(async move {
+            let mut shortcut = document::eval(&format!(
+                r#"
+                {UNDO_SHORTCUTS_STOP_JS}
+                const listener = (event) => {{
+                    if ((event.metaKey || event.ctrlKey) && event.key.toLowerCase() === "z") {{
+                        event.preventDefault();
+                        dioxus.send(event.shiftKey);
+                    }}
+                }};
+                window.addEventListener("keydown", listener);
+                window.__undoShortcuts = listener;
+                "#
+            ));
+            while let Ok(redo) = shortcut.recv::<bool>().await {
+                if redo {
+                    on_redo.call(());
+                } else {
+                    on_undo.call(());
+                }
+            }
+        });
+    });
+    use_drop(|| {
+        document::eval(UNDO_SHORTCUTS_STOP_JS);
+    });
+}
+
+/// Distance from the end of the page, in pixels, at which [`use_infinite_scroll`] asks for more
+pub const INFINITE_SCROLL_MARGIN: u32 = 400;
+
+/// Removes the listeners installed by [`use_infinite_scroll`]
+const INFINITE_SCROLL_STOP_JS: &str = r#"
+    if (window.__infiniteScroll) {
+        window.removeEventListener("scroll", window.__infiniteScroll.check);
+        window.removeEventListener("resize", window.__infiniteScroll.check);
+        window.__infiniteScroll.observer.disconnect();
+        window.__infiniteScroll = null;
+    }
+"#;
+
+/// Call `on_near_end` whenever the end of the page comes within [`INFINITE_SCROLL_MARGIN`] pixels of the bottom of
+/// the window, standing in for an intersection observer on the last row. The check runs on scroll, on resize and
+/// whenever the content changes size, so a first page too short to scroll still asks for the next one.
+///
+/// The listener goes through `eval`, which works the same in the browser and the desktop webview, and is removed
+/// when the component unmounts. `on_near_end` may be called again before the last call's load finished.
+pub fn use_infinite_scroll(on_near_end: Callback<()>) {
+    // Effects only run in the client, where there is a page to scroll
+    use_effect(move || {
+        spawn(async move {
+            let mut near_end = document::eval(&format!(
+                r#"
+                {INFINITE_SCROLL_STOP_JS}
+                const check = () => {{
+                    const end = document.documentElement.scrollHeight - {INFINITE_SCROLL_MARGIN};
+                    if (window.innerHeight + window.scrollY >= end) {{
+                        dioxus.send(true);
+                    }}
+                }};
+                const observer = new ResizeObserver(check);
+                observer.observe(document.body);
+                window.addEventListener("scroll", check, {{ passive: true }});
+                window.addEventListener("resize", check);
+                window.__infiniteScroll = {{ check, observer }};
+                "#
+            ));
+            while near_end.recv::<bool>().await.is_ok() {
+                on_near_end.call(());
+            }
+        });
+    });
+    use_drop(|| {
+        document::eval(INFINITE_SCROLL_STOP_JS);
+    });
+}
+
+/// Like `use_resource`, but transient failures are retried with exponential backoff before the resource settles
+/// on an error. Fatal errors (see [`is_retriable`]) are returned right away.
+///
+/// `fetch` is called once per attempt. `restart()` on the returned resource starts a fresh round of attempts,
+/// which is what a manual "retry" button should do.
+pub fn use_retrying_resource<T, E, F>(
+    policy: RetryPolicy,
+    fetch: impl Fn() -> F + Clone + 'static,
+) -> Resource<Result<T, E>>
+where
+    T: 'static,
+    E: std::fmt::Display + 'static,
+    F: Future<Output = Result<T, E>> + 'static,
+{
+    use_resource(move || fetch_with_retries(policy, fetch.clone()))
+}
+
+/// Call `fetch` until it succeeds, fails for good or runs out of the attempts `policy` allows
+async fn fetch_with_retries<T, E, F>(policy: RetryPolicy, fetch: impl Fn() -> F) -> Result<T, E>
+where
+    E: std::fmt::Display,
+    F: Future<Output = Result<T, E>>,
+{
+    let mut retry = 0;
+    loop {
+        match fetch().await {
+            Ok(value) => return Ok(value),
+            Err(error) if retry + 1 < policy.max_attempts && is_retriable(&error) => {
+                sleep(policy.delay(retry, jitter())).await;
+                retry += 1;
+            }
+            Err(error) => return Err(error),
+        }
+    }
+}
+
+/// A resource from [`use_coalesced_resource`]
+pub struct CoalescedResource<T: 'static> {
+    value: Signal<Option<T>>,
+    restarts: Signal<u32>,
+}
+
+impl<T> Clone for CoalescedResource<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for CoalescedResource<T> {}
+
+impl<T> CoalescedResource<T> {
+    /// The newest response, `None` until the first one arrives; reading it subscribes the caller
+    pub fn value(&self) -> Signal<Option<T>> {
+        self.value
+    }
+
+    /// Fetch again with the current inputs, e.g. from a "retry" button
+    pub fn restart(&mut self) {
+        *self.restarts.write() += 1;
+    }
+}
+
+/// Like [`use_retrying_resource`], for inputs that change in bursts, e.g. the idea list revision when several
+/// ideas are deleted in a row.
+///
+/// `trigger` reads the inputs and is re-run whenever a signal it reads changes; `fetch` loads the data for what it
+/// returned. Only one fetch runs at a time: triggers that come in meanwhile are folded into a single fetch after it,
+/// and a response that a newer trigger has overtaken is dropped, so an answer from before a delete can't bring the
+/// deleted idea back. See [`Coalescer`] for the bookkeeping.
+pub fn use_coalesced_resource<K, T, E, F>(
+    policy: RetryPolicy,
+    trigger: impl Fn() -> K + 'static,
+    fetch: impl Fn(K) -> F + 'static,
+) -> CoalescedResource<Result<T, E>>
+where
+    K: Clone + 'static,
+    T: 'static,
+    E: std::fmt::Display + 'static,
+    F: Future<Output = Result<T, E>> + 'static,
+{
+    let mut value = use_signal(|| None);
+    let restarts = use_signal(|| 0);
+    let state = use_hook(|| Rc::new(RefCell::new(Coalescer::default())));
+    let fetch = use_hook(|| Rc::new(fetch));
+
+    use_effect(move || {
+        let _ = restarts();
+        let Some(mut next) = state.borrow_mut().trigger(trigger()) else {
+            return;
+        };
+        let state = state.clone();
+        let fetch = fetch.clone();
+        spawn(async move {
+            loop {
+                let (generation, key) = next;
+                let result = fetch_with_retries(policy, || fetch(key.clone())).await;
+                let settled = state.borrow_mut().settle(generation);
+                if settled.apply {
+                    value.set(Some(result));
+                }
+                match settled.next {
+                    Some(fetch_next) => next = fetch_next,
+                    None => break,
+                }
+            }
+        });
+    });
+
+    CoalescedResource { value, restarts }
+}
+
+/// How often [`use_reconnect`] asks whether the server answers again
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// While `offline` is true, ask the server every [`RECONNECT_INTERVAL`] whether it answers again and call `on_back`
+/// once it does, e.g. to send edits that failed to save. Nobody is waiting while the tab is idle, so it doesn't ask
+/// then, but asks right away when the tab wakes.
+pub fn use_reconnect(offline: Memo<bool>, on_back: Callback<()>) {
+    let idle = use_idle_state();
+    let check = move || async move {
+        if *offline.peek() && health_server().await.is_ok() {
+            on_back.call(());
+        }
+    };
+    // Effects only run in the client; the loop ends with the component
+    use_effect(move || {
+        spawn(async move {
+            loop {
+                sleep(RECONNECT_INTERVAL).await;
+                if !idle.peek_idle() {
+                    check().await;
+                }
+            }
+        });
+    });
+    use_effect(move || {
+        if idle.wakes() > 0 {
+            spawn(check());
+        }
+    });
+}
+
+/// How long the tab goes without pointer or keyboard activity before [`use_idle`] calls it idle
+pub const IDLE_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// How often [`use_idle`] checks whether the timeout passed
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Whether anyone is using the tab, provided at the app root by [`use_idle`]. Live subscriptions and polling pause
+/// while it is idle and catch up when it wakes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Idle {
+    idle: Signal<bool>,
+    wakes: Signal<u32>,
+}
+
+impl Idle {
+    /// Whether the tab is idle; reading it subscribes the caller to changes
+    pub fn is_idle(&self) -> bool {
+        (self.idle)()
+    }
+
+    /// Whether the tab is idle, without subscribing, for loops and event handlers
+    pub fn peek_idle(&self) -> bool {
+        *self.idle.peek()
+    }
+
+    /// How often the tab woke up so far; reading it subscribes the caller, e.g. to refresh after every wake-up
+    pub fn wakes(&self) -> u32 {
+        (self.wakes)()
+    }
+}
+
+/// Watch pointer and keyboard activity and provide the [`Idle`] state, which turns idle after `timeout` without
+/// any. See [`IdleTracker`] for when it wakes again. Call once at the app root.
+pub fn use_idle(timeout: Duration) -> Idle {
+    let mut idle = use_signal(|| false);
+    let mut wakes = use_signal(|| 0u32);
+    let tracker = use_hook(|| Rc::new(RefCell::new(IdleTracker::new(timeout.as_millis() as f64, idle_clock()))));
+
+    let mut apply = move |change: Option<IdleChange>| match change {
+        Some(IdleChange::WentIdle) => idle.set(true),
+        Some(IdleChange::Woke) => {
+            idle.set(false);
+            *wakes.write() += 1;
+        }
+        None => {}
+    };
+
+    // Effects only run in the client, where there is a page to watch
+    use_effect(move || {
+        let events = tracker.clone();
+        spawn(async move {
+            // Pointer moves are thinned out in the page, so a sweep across the screen is a few messages
+            let mut activity = document::eval(
+                r#"
+                let lastMove = 0;
+                window.addEventListener("pointermove", () => {
+                    const now = Date.now();
+                    if (now - lastMove >= 250) {
+                        lastMove = now;
+                        dioxus.send("pointer");
+                    }
+                }, { passive: true });
+                for (const name of ["keydown", "pointerdown", "wheel", "touchstart"]) {
+                    window.addEventListener(name, () => dioxus.send("input"), { passive: true });
+                }
+                "#,
+            );
+            while let Ok(kind) = activity.recv::<String>().await {
+                let activity = if kind == "pointer" { Activity::Pointer } else { Activity::Input };
+                let change = events.borrow_mut().record(activity, idle_clock());
+                apply(change);
+            }
+        });
+        let ticks = tracker.clone();
+        spawn(async move {
+            loop {
+                sleep(IDLE_CHECK_INTERVAL).await;
+                let change = ticks.borrow_mut().tick(idle_clock());
+                apply(change);
+            }
+        });
+    });
+
+    use_context_provider(|| Idle { idle, wakes })
+}
+
+/// The [`Idle`] state provided at the app root by [`use_idle`]
+pub fn use_idle_state() -> Idle {
+    use_context::<Idle>()
+}
+
+/// Milliseconds for [`IdleTracker`], which only compares them
+fn idle_clock() -> f64 {
+    chrono::Utc::now().timestamp_millis() as f64
+}
+
+/// Runs an action once calls stop coming for a while, see [`use_debounce`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Debounce(Signal<u64>);
+
+impl Debounce {
+    /// Run `action` after `delay`, unless `call` or [`Debounce::cancel`] comes again first
+    pub fn call(&mut self, delay: Duration, action: impl FnOnce() + 'static) {
+        let generation = self.bump();
+        let current = self.0;
+        spawn(async move {
+            sleep(delay).await;
+            if *current.peek() == generation {
+                action();
+            }
+        });
+    }
+
+    /// Drop the pending action, if any
+    pub fn cancel(&mut self) {
+        self.bump();
+    }
+
+    fn bump(&mut self) -> u64 {
+        let mut generation = self.0.write();
+        *generation += 1;
+        *generation
+    }
+}
+
+/// Debouncer for the calling component; pending actions die with it
+pub fn use_debounce() -> Debounce {
+    Debounce(use_signal(|| 0))
+}
+
+/// Editors with unsaved changes and the navigation held back by them, see [`LeaveGuard`]. Links that go through
+/// `GuardedLink`, and code that navigates with [`UnsavedChanges::push`], wait for the `LeaveDialog` question while an
+/// editor is dirty; on web, leaving or reloading the page asks the browser's own question.
+///
+/// Provided once by the layout through [`use_unsaved_changes_provider`]; editors register with
+/// [`use_unsaved_registration`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UnsavedChanges(Signal<LeaveGuard<Route>>);
+
+impl UnsavedChanges {
+    /// Whether any editor on screen has unsaved changes
+    pub fn is_dirty(&self) -> bool {
+        self.0.read().is_dirty()
+    }
+
+    /// Go to `to`, after the user confirms leaving unsaved changes if there are any
+    pub fn push(&mut self, to: Route) {
+        let to = self.0.write().navigate(to);
+        if let Some(to) = to {
+            navigator().push(to);
+        }
+    }
+
+    /// Whether a navigation waits for the user to confirm
+    pub fn is_pending(&self) -> bool {
+        self.0.read().pending().is_some()
+    }
+
+    /// Leave the unsaved changes and go where the held navigation was going
+    pub fn confirm(&mut self) {
+        let to = self.0.write().confirm();
+        if let Some(to) = to {
+            navigator().push(to);
+        }
+    }
+
+    /// Stay with the unsaved changes
+    pub fn cancel(&mut self) {
+        self.0.write().cancel();
+    }
+}
+
+/// Provide the [`UnsavedChanges`] context for every route below the caller
+pub fn use_unsaved_changes_provider() -> UnsavedChanges {
+    let changes = use_context_provider(|| UnsavedChanges(Signal::new(LeaveGuard::default())));
+    // Closing the tab or reloading can't wait for the app's dialog, only for the browser's
+    #[cfg(target_arch = "wasm32")]
+    use_effect(move || {
+        let handler = if changes.is_dirty() {
+            "(event) => { event.preventDefault(); event.returnValue = \"\"; }"
+        } else {
+            "null"
+        };
+        document::eval(&format!("window.onbeforeunload = {};", handler));
+    });
+    changes
+}
+
+/// The [`UnsavedChanges`] provided by the layout
+pub fn use_unsaved_changes() -> UnsavedChanges {
+    use_context::<UnsavedChanges>()
+}
+
+/// Count the calling component as an editor with unsaved changes whenever `dirty` is true, until it unmounts
+pub fn use_unsaved_registration(dirty: Memo<bool>) {
+    let changes = use_unsaved_changes();
+    let mut guard = changes.0;
+    let id = use_hook(|| guard.write().register());
+    use_effect(move || {
+        let dirty = dirty();
+        guard.write().set_dirty(id, dirty);
+    });
+    use_drop(move || guard.write().unregister(id));
+}
+
+/// A cheap source of jitter in `[0, 1)`; it only needs to differ between clients, not be random
+fn jitter() -> f64 {
+    f64::from(chrono::Utc::now().timestamp_subsec_nanos() % 1000) / 1000.0
+}
+
+#[cfg(target_arch = "wasm32")]
+async fn sleep(duration: Duration) {
+    gloo_timers::future::sleep(duration).await;
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+async fn sleep(duration: Duration) {
+    tokio::time::sleep(duration).await;
+}
+
+/// The selected theme plus what the platform prefers, provided at the app root by [`use_theme_provider`]
+#[derive(Clone, Copy)]
+pub struct ThemeContext {
+    theme: Signal<Theme>,
+    prefers_dark: Signal<bool>,
+    storage: Signal<Rc<dyn Storage>>,
+}
+
+impl ThemeContext {
+    /// The theme the user picked
+    pub fn theme(&self) -> Theme {
+        (self.theme)()
+    }
+
+    /// The concrete theme to render, for the `data-theme` attribute
+    pub fn resolved(&self) -> &'static str {
+        (self.theme)().resolve((self.prefers_dark)())
+    }
+
+    /// Pick a theme and persist it
+    pub fn set(&mut self, theme: Theme) {
+        self.theme.set(theme);
+        theme.save(&**self.storage.peek());
+    }
+
+    /// Switch to the next theme (light → dark → system)
+    pub fn cycle(&mut self) {
+        let next = self.theme.peek().next();
+        self.set(next);
+    }
+}
+
+/// Provide the [`ThemeContext`] backed by the platform's default storage. Call once, at the app root.
+pub fn use_theme_provider() -> ThemeContext {
+    use_theme_provider_with(|| Rc::new(default_storage()))
+}
+
+/// Provide the [`ThemeContext`] backed by a specific storage
+pub fn use_theme_provider_with(storage: impl FnOnce() -> Rc<dyn Storage>) -> ThemeContext {
+    let storage = use_signal(storage);
+    let theme = use_signal(|| Theme::load(&**storage.peek()));
+    let prefers_dark = use_signal(|| false);
+
+    // Desktop has no media queries to ask, so `System` stays light there
+    #[cfg(target_arch = "wasm32")]
+    use_hook(move || watch_prefers_dark(prefers_dark));
+
+    use_context_provider(|| ThemeContext {
+        theme,
+        prefers_dark,
+        storage,
+    })
+}
+
+/// Keep `prefers_dark` in sync with the browser's `prefers-color-scheme`
+#[cfg(target_arch = "wasm32")]
+fn watch_prefers_dark(mut prefers_dark: Signal<bool>) {
+    use wasm_bindgen::{closure::Closure, JsCast};
+
+    let Some(query) = web_sys::window()
+        .and_then(|window| window.match_media("(prefers-color-scheme: dark)").ok())
+        .flatten()
+    else {
+        return;
+    };
+    prefers_dark.set(query.matches());
+
+    let listener = Closure::<dyn FnMut(web_sys::MediaQueryListEvent)>::new(
+        move |event: web_sys::MediaQueryListEvent| prefers_dark.set(event.matches()),
+    );
+    let _ = query.add_event_listener_with_callback("change", listener.as_ref().unchecked_ref());
+    // The root lives as long as the page, so the listener can too
+    listener.forget();
+}
+
+/// The [`ThemeContext`] provided at the app root
+pub fn use_theme() -> ThemeContext {
+    use_context::<ThemeContext>()
+}
+
+/// Display preferences of the idea list and the auto-save preference, provided at the app root by
+/// [`use_settings_provider`]
+#[derive(Clone, Copy)]
+pub struct SettingsContext {
+    density: Signal<ListDensity>,
+    auto_save: Signal<AutoSave>,
+    board: Signal<BoardLayout>,
+    display_name: Signal<String>,
+    active_board: Signal<String>,
+    time_display: Signal<TimeDisplay>,
+    storage: Signal<Rc<dyn Storage>>,
+}
+
+impl SettingsContext {
+    /// How much of each idea the list shows
+    pub fn density(&self) -> ListDensity {
+        (self.density)()
+    }
+
+    /// Pick a density and persist it
+    pub fn set_density(&mut self, density: ListDensity) {
+        self.density.set(density);
+        density.save(&**self.storage.peek());
+    }
+
+    /// When the development page saves edits on its own
+    pub fn auto_save(&self) -> AutoSave {
+        (self.auto_save)()
+    }
+
+    /// Pick an auto-save mode and persist it
+    pub fn set_auto_save(&mut self, auto_save: AutoSave) {
+        self.auto_save.set(auto_save);
+        auto_save.save(&**self.storage.peek());
+    }
+
+    /// Column order and collapsed columns of the board
+    pub fn board(&self) -> BoardLayout {
+        (self.board)()
+    }
+
+    /// Change the board layout and persist it
+    pub fn update_board(&mut self, change: impl FnOnce(&mut BoardLayout)) {
+        change(&mut self.board.write());
+        self.board.peek().save(&**self.storage.peek());
+    }
+
+    /// Name new ideas are credited to, empty when none is set
+    pub fn display_name(&self) -> String {
+        (self.display_name)()
+    }
+
+    /// Change the display name and persist it
+    pub fn set_display_name(&mut self, name: String) {
+        save_display_name(&**self.storage.peek(), &name);
+        self.display_name.set(name);
+    }
+
+    /// Slug of the board of ideas worked on, see [`crate::boards`]
+    pub fn active_board(&self) -> String {
+        (self.active_board)()
+    }
+
+    /// Remember the board worked on. The server only learns about it from `select_board_server`.
+    pub fn set_active_board(&mut self, slug: String) {
+        save_active_board(&**self.storage.peek(), &slug);
+        self.active_board.set(slug);
+    }
+
+    /// Whether times read "3 h ago" or show the exact time
+    pub fn time_display(&self) -> TimeDisplay {
+        (self.time_display)()
+    }
+
+    /// Pick how times are shown and persist it
+    pub fn set_time_display(&mut self, display: TimeDisplay) {
+        self.time_display.set(display);
+        display.save(&**self.storage.peek());
+    }
+}
+
+/// Provide the [`SettingsContext`], loading the persisted preferences. Call once at the app root.
+pub fn use_settings_provider() -> SettingsContext {
+    use_settings_provider_with(|| Rc::new(default_storage()))
+}
+
+/// Provide the [`SettingsContext`] backed by a specific storage
+pub fn use_settings_provider_with(storage: impl FnOnce() -> Rc<dyn Storage>) -> SettingsContext {
+    let storage = use_signal(storage);
+    let density = use_signal(|| ListDensity::load(&**storage.peek()));
+    let auto_save = use_signal(|| AutoSave::load(&**storage.peek()));
+    let board = use_signal(|| BoardLayout::load(&**storage.peek()));
+    let display_name = use_signal(|| load_display_name(&**storage.peek()));
+    let active_board = use_signal(|| load_active_board(&**storage.peek()));
+    let time_display = use_signal(|| TimeDisplay::load(&**storage.peek()));
+    use_context_provider(|| SettingsContext {
+        density,
+        auto_save,
+        board,
+        display_name,
+        active_board,
+        time_display,
+        storage,
+    })
+}
+
+/// The [`SettingsContext`] provided at the app root
+pub fn use_settings() -> SettingsContext {
+    use_context::<SettingsContext>()
+}
+
+/// How the server is deployed, fetched once when the app starts. Until the answer arrives (or if it never does)
+/// the defaults apply, so a read-only server may show edit controls briefly; it refuses the writes either way.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AppConfigContext(Signal<AppConfig>);
+
+impl AppConfigContext {
+    pub fn get(&self) -> AppConfig {
+        (self.0)()
+    }
+
+    /// Whether writes are refused, so edit controls should be hidden
+    pub fn read_only(&self) -> bool {
+        self.get().read_only
+    }
+}
+
+/// How often the [`Clock`] moves on, which is as precise as [`relative_time`] gets
+pub const CLOCK_TICK: Duration = Duration::from_secs(60);
+
+/// The local wall-clock time, moved on every [`CLOCK_TICK`] by one timer for the whole app, so a list of cards
+/// showing relative times doesn't run a timer per card. Provided at the root by [`use_clock_provider`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Clock(Signal<NaiveDateTime>);
+
+impl Clock {
+    /// The time as of the last tick; reading it subscribes the caller to every tick
+    pub fn now(&self) -> NaiveDateTime {
+        (self.0)()
+    }
+}
+
+/// Provide the [`Clock`] to the whole app. Call once at the app root.
+pub fn use_clock_provider() -> Clock {
+    let mut now = use_signal(local_now);
+    // Effects only run in the client, where the page stays open long enough for the time to move on
+    use_effect(move || {
+        spawn(async move {
+            loop {
+                sleep(CLOCK_TICK).await;
+                now.set(local_now());
+            }
+        });
+    });
+    use_context_provider(|| Clock(now))
+}
+
+/// `at` as picked in the settings: how long ago it was ("3 h ago", see [`relative_time`]), kept current by the
+/// shared [`Clock`], or the exact time
+pub fn use_relative_time(at: DateTime<Utc>) -> String {
+    let clock = use_context::<Clock>();
+    match use_settings().time_display() {
+        TimeDisplay::Relative => relative_time(local_time(at), clock.now()),
+        TimeDisplay::Absolute => absolute_time(at),
+    }
+}
+
+/// Widths below this many CSS pixels get the phone layout: swipe actions on cards and the form as a full-screen sheet
+pub const NARROW_VIEWPORT: f64 = 640.0;
+
+/// Width of the window in CSS pixels, kept up to date from resize events. Provided at the root through
+/// [`use_viewport_provider`]; until the first measurement (and while rendering on the server) it reads as
+/// [`NARROW_VIEWPORT`], so the desktop layout is the default.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ViewportWidth {
+    width: Signal<f64>,
+    narrow: Memo<bool>,
+}
+
+impl ViewportWidth {
+    /// The current width; reading it subscribes the caller to every resize
+    pub fn get(&self) -> f64 {
+        (self.width)()
+    }
+
+    /// Whether the window is phone-sized. Readers only rerender when the window crosses [`NARROW_VIEWPORT`].
+    pub fn is_narrow(&self) -> bool {
+        (self.narrow)()
+    }
+}
+
+/// Measure the window and provide its [`ViewportWidth`] to the whole app
+pub fn use_viewport_provider() -> ViewportWidth {
+    let mut width = use_signal(|| NARROW_VIEWPORT);
+    let narrow = use_memo(move || width() < NARROW_VIEWPORT);
+    // Effects only run in the client, where there is a window to measure
+    use_effect(move || {
+        spawn(async move {
+            let mut resized = document::eval(
+                r#"
+                dioxus.send(window.innerWidth);
+                window.addEventListener("resize", () => dioxus.send(window.innerWidth));
+                "#,
+            );
+            while let Ok(measured) = resized.recv::<f64>().await {
+                width.set(measured);
+            }
+        });
+    });
+    use_context_provider(|| ViewportWidth { width, narrow })
+}
+
+/// The [`ViewportWidth`] provided at the app root
+pub fn use_viewport() -> ViewportWidth {
+    use_context::<ViewportWidth>()
+}
+
+/// Fetch the deployment settings and provide them to the whole app
+pub fn use_app_config_provider() -> AppConfigContext {
+    let mut config = use_context_provider(|| AppConfigContext(Signal::new(AppConfig::default())));
+    use_future(move || async move {
+        if let Ok(fetched) = get_app_config_server().await {
+            config.0.set(fetched);
+        }
+    });
+    config
+}
+
+/// The [`AppConfigContext`] provided at the app root
+pub fn use_app_config() -> AppConfigContext {
+    use_context::<AppConfigContext>()
+}
+
+/// Hrefs of the stylesheets components asked for, each once, in the order they were first asked for
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StylesheetSet(Vec<String>);
+
+impl StylesheetSet {
+    /// Add `href` unless it is already in, returning whether it was new
+    pub fn register(&mut self, href: &str) -> bool {
+        if self.contains(href) {
+            return false;
+        }
+        self.0.push(href.to_string());
+        true
+    }
+
+    pub fn contains(&self, href: &str) -> bool {
+        self.0.iter().any(|registered| registered == href)
+    }
+
+    pub fn hrefs(&self) -> &[String] {
+        &self.0
+    }
+}
+
+/// The stylesheets of the whole app, provided at the root by [`use_stylesheets_provider`] and linked there by
+/// [`crate::components::Stylesheets`]. Components add theirs with [`use_stylesheet`] instead of rendering a
+/// `document::Link` each, so a sheet is linked once however often its components render or remount.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StylesheetsContext(Signal<StylesheetSet>);
+
+impl StylesheetsContext {
+    pub fn get(&self) -> StylesheetSet {
+        (self.0)()
+    }
+
+    /// Add `href`, only notifying the root when it wasn't linked yet
+    pub fn register(&mut self, href: &str) {
+        if !self.0.peek().contains(href) {
+            self.0.write().register(href);
+        }
+    }
+}
+
+/// Provide the [`StylesheetsContext`]. Call once at the app root.
+pub fn use_stylesheets_provider() -> StylesheetsContext {
+    use_context_provider(|| StylesheetsContext(Signal::new(StylesheetSet::default())))
+}
+
+/// Link the stylesheet at `href` (usually an `asset!`) for as long as the app runs. It is registered on the first
+/// render only, and sheets already linked by another component are not linked again.
+pub fn use_stylesheet(href: impl std::fmt::Display) {
+    let mut stylesheets = use_context::<StylesheetsContext>();
+    use_hook(move || stylesheets.register(&href.to_string()));
+}
+
+/// Pinned tag colors for rendering chips. Until they load (or if loading fails) every tag uses its hashed color.
+pub fn use_tag_colors() -> Memo<TagColors> {
+    let pinned = use_resource(|| async move { get_tag_colors_server().await.unwrap_or_default() });
+    use_memo(move || pinned().unwrap_or_default())
+}
+
+/// Watch an idea for edits made by other clients. The returned flag turns true when one arrives and is reset
+/// whenever `idea_id` changes; set it back to false after reloading.
+///
+/// Only the web build subscribes. The subscription follows `idea_id`, is closed while `paused` is true and when the
+/// component unmounts. Edits made while it was paused don't raise the flag: reload the idea when it resumes.
+pub fn use_idea_changes(idea_id: ReadSignal<String>, client_id: String, paused: Memo<bool>) -> Signal<bool> {
+    let changed = use_signal(|| false);
+
+    #[cfg(target_arch = "wasm32")]
+    {
+        let mut changed = changed;
+        // Dropping a listener closes its connection: replacing it switches ideas, and the signal itself is dropped
+        // with the component
+        let mut listener = use_signal(|| None::<ChangeListener>);
+        use_effect(move || {
+            let url = crate::changes::events_url(&idea_id(), &client_id);
+            changed.set(false);
+            if paused() {
+                listener.set(None);
+            } else {
+                listener.set(ChangeListener::open(&url, move || changed.set(true)));
+            }
+        });
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    let _ = (idea_id, client_id, paused);
+
+    changed
+}
+
+/// An open `EventSource` for an idea's change stream
+#[cfg(target_arch = "wasm32")]
+struct ChangeListener {
+    source: web_sys::EventSource,
+    _on_change: wasm_bindgen::closure::Closure<dyn FnMut(web_sys::Event)>,
+}
+
+#[cfg(target_arch = "wasm32")]
+impl ChangeListener {
+    fn open(url: &str, mut on_change: impl FnMut() + 'static) -> Option<Self> {
+        use wasm_bindgen::{closure::Closure, JsCast};
+
+        let source = web_sys::EventSource::new(url).ok()?;
+        let callback = Closure::<dyn FnMut(web_sys::Event)>::new(move |_event: web_sys::Event| on_change());
+        source
+            .add_event_listener_with_callback("changed", callback.as_ref().unchecked_ref())
+            .ok()?;
+        Some(Self {
+            source,
+            _on_change: callback,
+        })
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Drop for ChangeListener {
+    fn drop(&mut self) {
+        self.source.close();
+    }
+}
+
+/// A handle on a mounted element so focus can be moved to it later, e.g. back to the button that opened a form
+#[derive(Clone, Copy, PartialEq)]
+pub struct FocusHandle {
+    element: Signal<Option<Rc<MountedData>>>,
+    focus_on_mount: bool,
+}
+
+impl FocusHandle {
+    /// Pass the element's `onmounted` event here
+    pub fn mounted(&mut self, event: MountedEvent) {
+        let element = event.data();
+        if self.focus_on_mount {
+            focus_element(element.clone());
+        }
+        self.element.set(Some(element));
+    }
+
+    /// Move focus to the element, if it is mounted
+    pub fn focus(&self) {
+        if let Some(element) = self.element.peek().clone() {
+            focus_element(element);
+        }
+    }
+}
+
+fn focus_element(element: Rc<MountedData>) {
+    spawn(async move {
+        let _ = element.set_focus(true).await;
+    });
+}
+
+/// A [`FocusHandle`] for focusing an element on demand
+pub fn use_focus_handle() -> FocusHandle {
+    let element = use_signal(|| None);
+    FocusHandle {
+        element,
+        focus_on_mount: false,
+    }
+}
+
+/// A [`FocusHandle`] that also focuses its element as soon as it mounts, for content that appears in response to
+/// the user (a form that opens, a dialog)
+pub fn use_focus_on_mount() -> FocusHandle {
+    let element = use_signal(|| None);
+    FocusHandle {
+        element,
+        focus_on_mount: true,
+    }
+}
+
+/// Where Tab should move focus inside a trap of `count` elements, given the index of the focused one.
+///
+/// Focus wraps around at both ends. When focus is outside the trap (`None`) Tab enters at the first element and
+/// Shift+Tab at the last. Returns `None` when the trap is empty.
+pub fn trap_tab_target(current: Option<usize>, count: usize, backwards: bool) -> Option<usize> {
+    if count == 0 {
+        return None;
+    }
+    let next = match (current.filter(|&index| index < count), backwards) {
+        (None, false) => 0,
+        (None, true) => count - 1,
+        (Some(index), false) => (index + 1) % count,
+        (Some(index), true) => (index + count - 1) % count,
+    };
+    Some(next)
+}
+
+/// Keeps keyboard focus inside a dialog: Tab and Shift+Tab cycle through the registered elements instead of
+/// leaving it. Returned by [`use_focus_trap`].
+#[derive(Clone, Copy, PartialEq)]
+pub struct FocusTrap {
+    elements: Signal<Vec<Option<Rc<MountedData>>>>,
+    focused: Signal<Option<usize>>,
+}
+
+impl FocusTrap {
+    /// Register the element at position `index` in the tab order (from its `onmounted`). Index 0 gets focus first.
+    pub fn mounted(&mut self, index: usize, event: MountedEvent) {
+        let mut elements = self.elements.write();
+        if elements.len() <= index {
+            elements.resize(index + 1, None);
+        }
+        elements[index] = Some(event.data());
+        if index == 0 {
+            self.focused.set(Some(0));
+            focus_element(event.data());
+        }
+    }
+
+    /// Record that the element at `index` received focus (from its `onfocus`), e.g. after a click
+    pub fn focused(&mut self, index: usize) {
+        self.focused.set(Some(index));
+    }
+
+    /// Handle a keydown on the dialog: keeps Tab inside the trap
+    pub fn keydown(&mut self, event: &KeyboardEvent) {
+        if event.key() != Key::Tab {
+            return;
+        }
+        event.prevent_default();
+
+        let backwards = event.modifiers().contains(Modifiers::SHIFT);
+        let count = self.elements.peek().len();
+        let current = *self.focused.peek();
+        let Some(target) = trap_tab_target(current, count, backwards) else {
+            return;
+        };
+        if let Some(element) = self.elements.peek().get(target).cloned().flatten() {
+            self.focused.set(Some(target));
+            focus_element(element);
+        }
+    }
+}
+
+/// Trap keyboard focus inside a dialog while it is shown
+pub fn use_focus_trap() -> FocusTrap {
+    let elements = use_signal(Vec::new);
+    let focused = use_signal(|| None);
+    FocusTrap { elements, focused }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::MemoryStorage;
+    use crate::theme::THEME_KEY;
+    use std::cell::RefCell;
+
+    type Seen = Rc<RefCell<Vec<(Theme, &'static str)>>>;
+
+    /// Mount a root that provides the theme over `storage`, cycles it `cycles` times and records what it saw
+    fn run_theme_root(storage: MemoryStorage, cycles: usize) -> Vec<(Theme, &'static str)> {
+        let seen: Seen = Rc::default();
+
+        let mut dom = VirtualDom::new_with_props(
+            |(storage, cycles, seen): (MemoryStorage, usize, Seen)| {
+                let mut theme = use_theme_provider_with(move || Rc::new(storage));
+                use_hook(|| {
+                    seen.borrow_mut().push((theme.theme(), theme.resolved()));
+                    for _ in 0..cycles {
+                        theme.cycle();
+                        seen.borrow_mut().push((theme.theme(), theme.resolved()));
+                    }
+                });
+                rsx! {}
+            },
+            (storage, cycles, seen.clone()),
+        );
+        dom.rebuild_in_place();
+
+        let seen = seen.borrow().clone();
+        seen
+    }
+
+    #[test]
+    fn test_theme_defaults_to_system_resolved_light() {
+        let seen = run_theme_root(MemoryStorage::new(), 0);
+        assert_eq!(seen, vec![(Theme::System, "light")]);
+    }
+
+    #[test]
+    fn test_theme_cycle_is_persisted() {
+        let storage = MemoryStorage::new();
+
+        let seen = run_theme_root(storage.clone(), 2);
+        assert_eq!(
+            seen,
+            vec![
+                (Theme::System, "light"),
+                (Theme::Light, "light"),
+                (Theme::Dark, "dark"),
+            ]
+        );
+        assert_eq!(storage.get_raw(THEME_KEY), Some("dark".to_string()));
+
+        // A fresh app picks the persisted theme back up
+        let seen = run_theme_root(storage, 0);
+        assert_eq!(seen, vec![(Theme::Dark, "dark")]);
+    }
+
+    #[test]
+    fn test_density_is_persisted() {
+        let storage = MemoryStorage::new();
+        let seen: Rc<RefCell<Vec<ListDensity>>> = Rc::default();
+
+        let root = |(storage, pick, seen): (MemoryStorage, Option<ListDensity>, Rc<RefCell<Vec<ListDensity>>>)| {
+            let mut settings = use_settings_provider_with(move || Rc::new(storage));
+            use_hook(|| {
+                if let Some(density) = pick {
+                    settings.set_density(density);
+                }
+                seen.borrow_mut().push(settings.density());
+            });
+            rsx! {}
+        };
+        VirtualDom::new_with_props(root, (storage.clone(), Some(ListDensity::Compact), seen.clone())).rebuild_in_place();
+        // A fresh app picks the persisted density back up
+        VirtualDom::new_with_props(root, (storage, None, seen.clone())).rebuild_in_place();
+
+        assert_eq!(*seen.borrow(), vec![ListDensity::Compact, ListDensity::Compact]);
+    }
+
+    #[test]
+    fn test_stylesheet_set_keeps_each_href_once() {
+        let mut set = StylesheetSet::default();
+        assert!(set.register("/assets/idea_list.css"));
+        assert!(!set.register("/assets/idea_list.css"));
+        assert!(set.register("/assets/navbar.css"));
+        assert_eq!(set.hrefs(), ["/assets/idea_list.css", "/assets/navbar.css"]);
+    }
+
+    #[test]
+    fn test_components_sharing_a_stylesheet_link_it_once() {
+        type Seen = Rc<RefCell<Vec<String>>>;
+
+        /// Links the list stylesheet and, given `seen`, records what the app links by then
+        #[component]
+        fn Card(seen: Option<Seen>) -> Element {
+            use_stylesheet("/assets/idea_list.css");
+            let stylesheets = use_context::<StylesheetsContext>();
+            use_hook(move || {
+                if let Some(seen) = seen {
+                    *seen.borrow_mut() = stylesheets.get().hrefs().to_vec();
+                }
+            });
+            rsx! {}
+        }
+
+        let seen: Seen = Rc::default();
+        let mut dom = VirtualDom::new_with_props(
+            |seen: Seen| {
+                use_stylesheets_provider();
+                rsx! {
+                    Card {}
+                    Card {}
+                    // Children render in order, so this one sees what the others registered
+                    Card { seen }
+                }
+            },
+            seen.clone(),
+        );
+        dom.rebuild_in_place();
+
+        assert_eq!(*seen.borrow(), vec!["/assets/idea_list.css"]);
+    }
+
+    #[test]
+    fn test_trap_tab_wraps_forwards() {
+        assert_eq!(trap_tab_target(Some(0), 3, false), Some(1));
+        assert_eq!(trap_tab_target(Some(2), 3, false), Some(0));
+    }
+
+    #[test]
+    fn test_trap_tab_wraps_backwards() {
+        assert_eq!(trap_tab_target(Some(1), 3, true), Some(0));
+        assert_eq!(trap_tab_target(Some(0), 3, true), Some(2));
+    }
+
+    #[test]
+    fn test_trap_tab_enters_from_outside() {
+        assert_eq!(trap_tab_target(None, 3, false), Some(0));
+        assert_eq!(trap_tab_target(None, 3, true), Some(2));
+        // A stale index (an element that went away) counts as outside
+        assert_eq!(trap_tab_target(Some(5), 2, false), Some(0));
+    }
+
+    #[test]
+    fn test_trap_tab_single_and_empty() {
+        assert_eq!(trap_tab_target(Some(0), 1, false), Some(0));
+        assert_eq!(trap_tab_target(Some(0), 1, true), Some(0));
+        assert_eq!(trap_tab_target(None, 0, false), None);
+    }
+}
@@ -0,0 +1,240 @@
+//! Submitting an idea twice without creating it twice.
+//!
+//! On a spotty connection a submission can reach the server while its response is lost, and the user clicks again.
+//! The form sends a key made once per form session, see [`new_idempotency_key`], and the server remembers which idea
+//! each owner's key created in the `submission_keys` table. A submission whose key was seen within [`IDEMPOTENCY_TTL`] gets
+//! the idea created the first time instead of a second one. The form makes a new key once it saw a submission
+//! succeed, so the next idea isn't mistaken for a repeat.
+
+use crate::error::IdeaError;
+use chrono::{DateTime, Utc};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// How long the server remembers a key
+pub const IDEMPOTENCY_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// Longest key accepted, in characters
+pub const MAX_IDEMPOTENCY_KEY_CHARS: usize = 64;
+
+/// A UUID-shaped key for one session of the new-idea form.
+///
+/// Made from the time and a counter mixed like [`crate::changes::new_client_id`], which is unique enough to tell
+/// form sessions apart but isn't meant to be unguessable: keys are remembered per owner, and a key only ever replays
+/// an idea its owner may read.
+pub fn new_idempotency_key() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let now = Utc::now();
+    let count = COUNTER.fetch_add(1, Ordering::Relaxed);
+    let high = mix(now.timestamp_millis() as u64 ^ count.rotate_left(48));
+    let low = mix(u64::from(now.timestamp_subsec_nanos()) ^ count ^ high);
+    // Version 4 and the RFC 4122 variant, as a random UUID has them
+    let high = (high & !0xF000) | 0x4000;
+    let low = (low & !(0b11 << 62)) | (0b10 << 62);
+    format!(
+        "{:08x}-{:04x}-{:04x}-{:04x}-{:012x}",
+        high >> 32,
+        (high >> 16) & 0xFFFF,
+        high & 0xFFFF,
+        low >> 48,
+        low & 0xFFFF_FFFF_FFFF
+    )
+}
+
+/// The SplitMix64 finalizer: spreads every input bit over the whole output
+fn mix(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    x ^ (x >> 31)
+}
+
+/// Refuse keys that are empty, too long or hold anything but ASCII letters, digits and dashes
+pub fn check_idempotency_key(key: &str) -> Result<(), IdeaError> {
+    if key.is_empty() || key.chars().count() > MAX_IDEMPOTENCY_KEY_CHARS {
+        return Err(IdeaError::Validation(format!(
+            "The idempotency key must be 1 to {} characters",
+            MAX_IDEMPOTENCY_KEY_CHARS
+        )));
+    }
+    if !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Err(IdeaError::Validation(
+            "The idempotency key may only hold letters, digits and dashes".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Whether a key recorded at `recorded_at` is forgotten by `now`. A time in the future counts as just recorded.
+pub fn is_expired(recorded_at: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+    (now - recorded_at).to_std().is_ok_and(|age| age >= IDEMPOTENCY_TTL)
+}
+
+// Server-only storage of the keys
+#[cfg(feature = "server")]
+pub mod server {
+    use super::*;
+    use crate::db::server::{ensure_readable, IdeaRepository};
+    use crate::db::{IdeaId, IdeaRecord, SubmitOutcome};
+    use crate::maintenance::Clock;
+    use serde::{Deserialize, Serialize};
+    use std::future::Future;
+    use surrealdb::sql::Thing;
+    use surrealdb::{engine::any::Any, Surreal};
+
+    /// Which idea the first submission with a key created, for whom, and when
+    #[derive(Debug, Serialize, Deserialize)]
+    struct SubmissionKey {
+        idea: Thing,
+        owner: Option<Thing>,
+        at: DateTime<Utc>,
+    }
+
+    /// Id of `owner`'s `key` in `submission_keys`, so the same key of two owners is two records
+    fn key_id(owner: Option<&Thing>, key: &str) -> Vec<String> {
+        vec![owner.map(Thing::to_string).unwrap_or_default(), key.to_string()]
+    }
+
+    #[derive(Deserialize)]
+    struct KeyRow {
+        id: Thing,
+        at: DateTime<Utc>,
+    }
+
+    /// The idea created by `owner`'s submission with `key`, unless the key is unknown, expired by `now`, or its idea
+    /// was deleted since or isn't `owner`'s to read
+    pub async fn submitted_with(
+        db: &Surreal<Any>,
+        repo: &impl IdeaRepository,
+        owner: Option<&Thing>,
+        key: &str,
+        now: DateTime<Utc>,
+    ) -> Result<Option<IdeaRecord>, IdeaError> {
+        let stored: Option<SubmissionKey> = db
+            .query("SELECT * FROM ONLY type::thing('submission_keys', $key)")
+            .bind(("key", key_id(owner, key)))
+            .await?
+            .take(0)?;
+        let Some(stored) = stored.filter(|stored| stored.owner.as_ref() == owner && !is_expired(stored.at, now)) else {
+            return Ok(None);
+        };
+        let original = match repo.get(&IdeaId::from(&stored.idea).to_string()).await {
+            Ok(idea) => idea,
+            Err(IdeaError::NotFound(_)) => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        match ensure_readable(&original, owner) {
+            Ok(()) => Ok(Some(original)),
+            Err(IdeaError::NotFound(_) | IdeaError::Forbidden) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Remember that `owner`'s submission with `key` created `idea` at `now`, forgetting the keys that expired
+    pub async fn remember_submission(
+        db: &Surreal<Any>,
+        owner: Option<&Thing>,
+        key: &str,
+        idea: &Thing,
+        now: DateTime<Utc>,
+    ) -> Result<(), IdeaError> {
+        let record = SubmissionKey {
+            idea: idea.clone(),
+            owner: owner.cloned(),
+            at: now,
+        };
+        db.query("UPSERT type::thing('submission_keys', $key) CONTENT $record")
+            .bind(("key", key_id(owner, key)))
+            .bind(("record", record))
+            .await?
+            .check()?;
+
+        // Compared here rather than in the query, like the trash retention, so the stored time's type doesn't matter
+        let keys: Vec<KeyRow> = db.query("SELECT id, at FROM submission_keys").await?.take(0)?;
+        let expired: Vec<Thing> = keys
+            .into_iter()
+            .filter(|row| is_expired(row.at, now))
+            .map(|row| row.id)
+            .collect();
+        if !expired.is_empty() {
+            db.query("DELETE submission_keys WHERE id INSIDE $expired")
+                .bind(("expired", expired))
+                .await?
+                .check()?;
+        }
+        Ok(())
+    }
+
+    /// Run `submit` unless a submission of `owner` with `key` already created an idea within [`IDEMPOTENCY_TTL`], in
+    /// which case that idea is returned as if just created. Without a key every call submits.
+    pub async fn submit_once<F, Fut>(
+        db: &Surreal<Any>,
+        repo: &impl IdeaRepository,
+        clock: &impl Clock,
+        owner: Option<&Thing>,
+        key: Option<&str>,
+        submit: F,
+    ) -> Result<SubmitOutcome, IdeaError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<SubmitOutcome, IdeaError>>,
+    {
+        let Some(key) = key else {
+            return submit().await;
+        };
+        check_idempotency_key(key)?;
+        if let Some(original) = submitted_with(db, repo, owner, key, clock.now()).await? {
+            return Ok(SubmitOutcome {
+                created: Some(original.into()),
+                possible_duplicates: Vec::new(),
+            });
+        }
+
+        let outcome = submit().await?;
+        // Held back for possible duplicates, nothing was created: the same key may still submit
+        if let Some(id) = outcome.created.as_ref().and_then(|idea| idea.id.as_deref()) {
+            remember_submission(db, owner, key, &Thing::from(&IdeaId::parse(id)?), clock.now()).await?;
+        }
+        Ok(outcome)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keys_are_uuid_shaped_and_distinct() {
+        let keys: Vec<String> = (0..100).map(|_| new_idempotency_key()).collect();
+        for key in &keys {
+            let groups: Vec<usize> = key.split('-').map(str::len).collect();
+            assert_eq!(groups, vec![8, 4, 4, 4, 12], "{}", key);
+            assert_eq!(key.as_bytes()[14], b'4', "{}", key);
+            assert!(matches!(key.as_bytes()[19], b'8' | b'9' | b'a' | b'b'), "{}", key);
+            assert!(check_idempotency_key(key).is_ok());
+        }
+        let mut unique = keys.clone();
+        unique.sort();
+        unique.dedup();
+        assert_eq!(unique.len(), keys.len());
+    }
+
+    #[test]
+    fn test_malformed_keys_are_refused() {
+        for key in ["", "has space", "semi;colon", "ключ", &"a".repeat(MAX_IDEMPOTENCY_KEY_CHARS + 1)] {
+            assert!(matches!(check_idempotency_key(key), Err(IdeaError::Validation(_))), "{}", key);
+        }
+        assert!(check_idempotency_key(&"a".repeat(MAX_IDEMPOTENCY_KEY_CHARS)).is_ok());
+    }
+
+    #[test]
+    fn test_keys_expire_after_the_ttl() {
+        let at = Utc::now();
+        let ttl = chrono::Duration::from_std(IDEMPOTENCY_TTL).unwrap();
+        assert!(!is_expired(at, at));
+        assert!(!is_expired(at, at + ttl - chrono::Duration::seconds(1)));
+        assert!(is_expired(at, at + ttl));
+        // A clock that went back doesn't expire anything
+        assert!(!is_expired(at, at - chrono::Duration::minutes(1)));
+    }
+}
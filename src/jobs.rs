@@ -0,0 +1,403 @@
+//! Slow work that follows a write, done in the background instead of making the write wait for it.
+//!
+//! Server functions hand a [`server::Job`] to the server's queue once their write has committed, and a single worker
+//! task runs the jobs one at a time in the order they came. A job runs at most once: a failed attempt is logged and
+//! tried again up to [`MAX_JOB_ATTEMPTS`] times, but a job that succeeded never runs again and jobs still queued when
+//! the process dies are lost. Nothing a job does is needed for correctness, only for speed, so losing one costs a
+//! slower read later. On shutdown the queue stops taking jobs and the worker finishes the queued ones, see
+//! [`crate::shutdown`].
+
+use std::time::Duration;
+
+/// Jobs waiting at most; more are dropped with a warning rather than making a write wait
+pub const JOB_QUEUE_CAPACITY: usize = 1024;
+
+/// Times a failing job is tried before it is given up
+pub const MAX_JOB_ATTEMPTS: u32 = 3;
+
+/// Wait before the second attempt of a failed job, doubled before each further one
+pub const JOB_RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// Wait after attempt `attempt` (from 1) failed, before the next one
+pub fn retry_delay(attempt: u32) -> Duration {
+    JOB_RETRY_DELAY.saturating_mul(1 << attempt.saturating_sub(1).min(16))
+}
+
+// Server-only queue, worker and jobs
+#[cfg(feature = "server")]
+pub mod server {
+    use super::*;
+    use crate::db::server::{IdeaCache, IdeaRepository, SurrealIdeaRepository};
+    use crate::error::IdeaError;
+    use crate::limits::server::UsageCache;
+    use std::fmt;
+    use std::future::Future;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use surrealdb::{engine::any::Any, sql::Thing, Surreal};
+    use tokio::sync::{mpsc, Notify};
+
+    /// Work to do after a write
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum Job {
+        /// Read the ideas of `owner` back into the list cache after `id` changed, so the next list, search or
+        /// duplicate check doesn't have to
+        ReindexIdea { id: String, owner: Option<Thing> },
+        /// Measure the storage usage again, so the limits banner doesn't wait for the old measure to expire
+        RecomputeStats,
+        /// Delete revisions beyond [`crate::revisions::MAX_REVISIONS`], e.g. after a restore brought back more
+        PruneRevisions,
+    }
+
+    impl fmt::Display for Job {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Job::ReindexIdea { id, .. } => write!(f, "reindex {}", id),
+                Job::RecomputeStats => f.write_str("recompute stats"),
+                Job::PruneRevisions => f.write_str("prune revisions"),
+            }
+        }
+    }
+
+    /// A [`Job`] with the database and caches of the request that queued it
+    #[derive(Clone)]
+    pub struct QueuedJob {
+        pub job: Job,
+        pub db: Surreal<Any>,
+        pub ideas: Option<IdeaCache>,
+        pub usage: UsageCache,
+    }
+
+    impl fmt::Display for QueuedJob {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            fmt::Display::fmt(&self.job, f)
+        }
+    }
+
+    /// Do `queued`
+    pub async fn run_job(queued: QueuedJob) -> Result<(), IdeaError> {
+        match queued.job {
+            Job::ReindexIdea { owner, .. } => {
+                if let Some(cache) = queued.ideas {
+                    SurrealIdeaRepository::new(queued.db).with_cache(cache).list(owner.as_ref()).await?;
+                }
+            }
+            Job::RecomputeStats => {
+                queued.usage.refresh(&queued.db).await?;
+            }
+            Job::PruneRevisions => {
+                let pruned = crate::maintenance::server::prune_revisions(&queued.db).await?;
+                tracing::info!(target: "jobs", pruned, "pruned revisions");
+            }
+        }
+        Ok(())
+    }
+
+    #[derive(Default)]
+    struct Depth {
+        queued: AtomicUsize,
+        closed: AtomicBool,
+        idle: Notify,
+    }
+
+    impl Depth {
+        fn done(&self) {
+            if self.queued.fetch_sub(1, Ordering::SeqCst) == 1 {
+                self.idle.notify_waiters();
+            }
+        }
+    }
+
+    /// The queue of a running worker. Clones share it.
+    pub struct JobQueue<J> {
+        sender: mpsc::Sender<J>,
+        depth: Arc<Depth>,
+    }
+
+    impl<J> Clone for JobQueue<J> {
+        fn clone(&self) -> Self {
+            Self {
+                sender: self.sender.clone(),
+                depth: self.depth.clone(),
+            }
+        }
+    }
+
+    impl<J: fmt::Display + Clone + Send + 'static> JobQueue<J> {
+        /// Start a worker doing the jobs of a new queue of `capacity` with `run`. Each attempt runs as a task of its
+        /// own, so a job that panics counts as failed instead of stopping the worker.
+        pub fn start<F, Fut>(capacity: usize, run: F) -> Self
+        where
+            F: Fn(J) -> Fut + Send + Sync + 'static,
+            Fut: Future<Output = Result<(), IdeaError>> + Send + 'static,
+        {
+            let (sender, mut receiver) = mpsc::channel::<J>(capacity.max(1));
+            let depth = Arc::new(Depth::default());
+            let worker_depth = depth.clone();
+            tokio::spawn(async move {
+                while let Some(job) = receiver.recv().await {
+                    for attempt in 1..=MAX_JOB_ATTEMPTS {
+                        let error = match tokio::spawn(run(job.clone())).await {
+                            Ok(Ok(())) => break,
+                            Ok(Err(error)) => error.to_string(),
+                            Err(panic) => panic.to_string(),
+                        };
+                        if attempt == MAX_JOB_ATTEMPTS {
+                            tracing::error!(target: "jobs", %error, attempt, "giving up on {}", job);
+                        } else {
+                            tracing::warn!(target: "jobs", %error, attempt, "{} failed, retrying", job);
+                            tokio::time::sleep(retry_delay(attempt)).await;
+                        }
+                    }
+                    worker_depth.done();
+                }
+            });
+            Self { sender, depth }
+        }
+
+        /// Queue `job` behind the others. Whether it was taken: not once the queue is closed or while it is full.
+        pub fn enqueue(&self, job: J) -> bool {
+            self.depth.queued.fetch_add(1, Ordering::SeqCst);
+            // Checked after counting, like `InFlight::enter`, so `drained` can't miss a job
+            if self.is_closed() {
+                self.depth.done();
+                tracing::warn!(target: "jobs", "shutting down, dropped {}", job);
+                return false;
+            }
+            match self.sender.try_send(job) {
+                Ok(()) => true,
+                Err(error) => {
+                    self.depth.done();
+                    tracing::warn!(target: "jobs", "queue full, dropped {}", error.into_inner());
+                    false
+                }
+            }
+        }
+    }
+
+    impl<J> JobQueue<J> {
+        /// Jobs queued or running
+        pub fn depth(&self) -> usize {
+            self.depth.queued.load(Ordering::SeqCst)
+        }
+
+        /// Turn every job from now on away; the queued ones still run
+        pub fn close(&self) {
+            self.depth.closed.store(true, Ordering::SeqCst);
+        }
+
+        pub fn is_closed(&self) -> bool {
+            self.depth.closed.load(Ordering::SeqCst)
+        }
+
+        /// Wait until every queued job is done, for at most `timeout`. Whether they all were.
+        pub async fn drained(&self, timeout: Duration) -> bool {
+            let wait = async {
+                loop {
+                    let idle = self.depth.idle.notified();
+                    if self.depth() == 0 {
+                        return;
+                    }
+                    idle.await;
+                }
+            };
+            tokio::time::timeout(timeout, wait).await.is_ok()
+        }
+    }
+
+    /// The queue of the server, doing [`QueuedJob`]s with [`run_job`]
+    pub type ServerJobs = JobQueue<QueuedJob>;
+
+    /// Start the server's worker
+    pub fn start_server_jobs() -> ServerJobs {
+        JobQueue::start(JOB_QUEUE_CAPACITY, run_job)
+    }
+
+    /// Make `jobs` the queue of every request handled by `router`, like [`crate::snapshots::server::with_snapshots`]
+    pub fn with_jobs(router: axum::Router, jobs: ServerJobs) -> axum::Router {
+        router.layer(axum::Extension(jobs))
+    }
+
+    /// Queue of the server handling the current server function call, if it has one
+    pub fn request_jobs() -> Option<ServerJobs> {
+        crate::request::extension::<ServerJobs>()
+    }
+
+    /// Queue `jobs` on the current request's database, after its write committed. Servers without a queue, like
+    /// the ones tests build, skip them.
+    pub fn enqueue(jobs: impl IntoIterator<Item = Job>) {
+        let (Some(queue), Ok(db)) = (request_jobs(), crate::db::server::request_db()) else {
+            return;
+        };
+        let ideas = crate::request::extension::<IdeaCache>();
+        let usage = crate::limits::server::request_usage_cache();
+        for job in jobs {
+            queue.enqueue(QueuedJob {
+                job,
+                db: db.clone(),
+                ideas: ideas.clone(),
+                usage: usage.clone(),
+            });
+        }
+    }
+
+    /// [`Job::ReindexIdea`] for a stored idea; nothing for one without an id
+    pub fn reindex(idea: &crate::db::IdeaRecord) -> Option<Job> {
+        idea.id.as_ref().map(|id| Job::ReindexIdea {
+            id: crate::db::IdeaId::from(id).to_string(),
+            owner: idea.owner.clone(),
+        })
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use std::sync::Mutex;
+
+        /// A job of the tests: its name, and how many of its attempts fail
+        #[derive(Debug, Clone)]
+        struct MockJob(&'static str, u32);
+
+        impl fmt::Display for MockJob {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(self.0)
+            }
+        }
+
+        /// A queue recording every attempt by name, and the attempts themselves
+        fn recording_queue(capacity: usize) -> (JobQueue<MockJob>, Arc<Mutex<Vec<&'static str>>>) {
+            let attempts: Arc<Mutex<Vec<&'static str>>> = Arc::default();
+            let seen = attempts.clone();
+            let queue = JobQueue::start(capacity, move |job: MockJob| {
+                let seen = seen.clone();
+                async move {
+                    let mut seen = seen.lock().unwrap();
+                    let tries = seen.iter().filter(|name| **name == job.0).count() as u32;
+                    seen.push(job.0);
+                    if tries < job.1 {
+                        return Err(IdeaError::Database(format!("{} failed", job.0)));
+                    }
+                    Ok(())
+                }
+            });
+            (queue, attempts)
+        }
+
+        #[tokio::test]
+        async fn test_jobs_run_in_order() {
+            let (queue, attempts) = recording_queue(8);
+            assert!(queue.enqueue(MockJob("first", 0)));
+            assert!(queue.enqueue(MockJob("second", 0)));
+
+            assert!(queue.drained(Duration::from_secs(5)).await);
+            assert_eq!(*attempts.lock().unwrap(), vec!["first", "second"]);
+            assert_eq!(queue.depth(), 0);
+        }
+
+        #[tokio::test(start_paused = true)]
+        async fn test_failed_jobs_are_retried_a_bounded_number_of_times() {
+            let (queue, attempts) = recording_queue(8);
+            queue.enqueue(MockJob("flaky", 1));
+            queue.enqueue(MockJob("broken", u32::MAX));
+            queue.enqueue(MockJob("after", 0));
+
+            assert!(queue.drained(Duration::from_secs(60)).await);
+            let attempts = attempts.lock().unwrap();
+            let count = |name| attempts.iter().filter(|seen| **seen == name).count();
+            assert_eq!(count("flaky"), 2);
+            assert_eq!(count("broken"), MAX_JOB_ATTEMPTS as usize);
+            // A job given up on doesn't hold the others back
+            assert_eq!(attempts.last(), Some(&"after"));
+        }
+
+        #[tokio::test(start_paused = true)]
+        async fn test_panicking_job_does_not_stop_the_worker() {
+            let ran: Arc<Mutex<Vec<&'static str>>> = Arc::default();
+            let seen = ran.clone();
+            let queue = JobQueue::start(8, move |job: MockJob| {
+                let seen = seen.clone();
+                async move {
+                    if job.0 == "panics" {
+                        panic!("job panicked");
+                    }
+                    seen.lock().unwrap().push(job.0);
+                    Ok(())
+                }
+            });
+            queue.enqueue(MockJob("panics", 0));
+            queue.enqueue(MockJob("fine", 0));
+
+            assert!(queue.drained(Duration::from_secs(60)).await);
+            assert_eq!(*ran.lock().unwrap(), vec!["fine"]);
+        }
+
+        #[tokio::test]
+        async fn test_closed_queue_drains_and_refuses_new_jobs() {
+            let (release_tx, release_rx) = tokio::sync::oneshot::channel::<()>();
+            let release_rx = Arc::new(tokio::sync::Mutex::new(Some(release_rx)));
+            let ran: Arc<Mutex<Vec<&'static str>>> = Arc::default();
+            let seen = ran.clone();
+            let queue = JobQueue::start(8, move |job: MockJob| {
+                let release_rx = release_rx.clone();
+                let seen = seen.clone();
+                async move {
+                    // The first job holds the worker until the test lets it go
+                    if let Some(release) = release_rx.lock().await.take() {
+                        let _ = release.await;
+                    }
+                    seen.lock().unwrap().push(job.0);
+                    Ok(())
+                }
+            });
+            assert!(queue.enqueue(MockJob("running", 0)));
+            assert!(queue.enqueue(MockJob("queued", 0)));
+            queue.close();
+            assert!(!queue.enqueue(MockJob("late", 0)));
+            assert_eq!(queue.depth(), 2);
+            assert!(!queue.drained(Duration::from_millis(50)).await);
+
+            release_tx.send(()).unwrap();
+            assert!(queue.drained(Duration::from_secs(5)).await);
+            assert_eq!(*ran.lock().unwrap(), vec!["running", "queued"]);
+        }
+
+        #[tokio::test]
+        async fn test_full_queue_drops_jobs() {
+            let (release_tx, release_rx) = tokio::sync::oneshot::channel::<()>();
+            let release_rx = Arc::new(tokio::sync::Mutex::new(Some(release_rx)));
+            let queue = JobQueue::start(1, move |_: MockJob| {
+                let release_rx = release_rx.clone();
+                async move {
+                    if let Some(release) = release_rx.lock().await.take() {
+                        let _ = release.await;
+                    }
+                    Ok(())
+                }
+            });
+            assert!(queue.enqueue(MockJob("running", 0)));
+            // Wait for the worker to take it off the channel, leaving room for exactly one more
+            while queue.sender.capacity() == 0 {
+                tokio::task::yield_now().await;
+            }
+            assert!(queue.enqueue(MockJob("waiting", 0)));
+            assert!(!queue.enqueue(MockJob("dropped", 0)));
+            assert_eq!(queue.depth(), 2);
+
+            release_tx.send(()).unwrap();
+            assert!(queue.drained(Duration::from_secs(5)).await);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_delay_doubles() {
+        assert_eq!(retry_delay(1), JOB_RETRY_DELAY);
+        assert_eq!(retry_delay(2), JOB_RETRY_DELAY * 2);
+        assert_eq!(retry_delay(3), JOB_RETRY_DELAY * 4);
+        assert_eq!(retry_delay(u32::MAX), JOB_RETRY_DELAY.saturating_mul(1 << 16));
+    }
+}
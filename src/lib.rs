@@ -1,7 +1,57 @@
 // Library exports for testing and reusability
 
+pub mod attachments;
+pub mod auth;
+pub mod base_path;
+pub mod boards;
+pub mod bundle;
+pub mod changes;
+pub mod client_errors;
+pub mod clipboard;
+pub mod config;
+#[cfg(any(feature = "server", feature = "desktop"))]
+pub mod data_dir;
 pub mod db;
+#[cfg(feature = "dev-tools")]
+pub mod dev_tools;
+pub mod digest;
+pub mod error;
+pub mod feed;
+pub mod form_config;
+pub mod graph;
+pub mod history;
+pub mod idempotency;
+pub mod jobs;
+pub mod limits;
+pub mod maintenance;
+pub mod markdown;
+pub mod migrations;
+pub mod onboarding;
+pub mod rate_limit;
+pub mod recent;
+pub mod review;
+pub mod revisions;
+pub mod sample;
 pub mod server_functions;
+pub mod settings;
+pub mod shutdown;
+pub mod snapshots;
+pub mod stats;
+pub mod storage;
+pub mod tags;
+pub mod templates;
+pub mod theme;
+pub mod utils;
+pub mod webhook;
+
+#[cfg(feature = "server")]
+pub mod request;
+#[cfg(feature = "server")]
+pub mod rest;
+#[cfg(feature = "server")]
+pub mod server_router;
+#[cfg(feature = "server")]
+pub mod telemetry;
 
 // Re-export commonly used types
 pub use db::Idea;
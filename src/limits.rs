@@ -0,0 +1,433 @@
+//! Soft limits on how much the database holds, so an embedded store on a small server doesn't grow unbounded.
+//!
+//! Each limit is optional and read into [`crate::config::AppConfig`] from the environment: the number of ideas, the
+//! bytes of development notes, and the bytes of all attachments together. The trash counts, as it takes up the same
+//! room. Past [`WARNING_PERCENT`] of any limit the app warns; once one is reached, new ideas and uploads are refused
+//! with [`IdeaError::StorageFull`] until something is deleted. Editing what is already there still works.
+
+use crate::attachments::format_size;
+use crate::db::Idea;
+use crate::error::IdeaError;
+use serde::{Deserialize, Serialize};
+
+/// Most ideas the database may hold, trash included
+pub const MAX_IDEAS_VAR: &str = "IDEAS_MAX_IDEAS";
+/// Most bytes of development notes, over every idea
+pub const MAX_NOTES_BYTES_VAR: &str = "IDEAS_MAX_NOTES_BYTES";
+/// Most bytes of attachments, over every idea
+pub const MAX_ATTACHMENT_BYTES_VAR: &str = "IDEAS_MAX_ATTACHMENT_BYTES";
+
+/// Share of a limit, in percent, from which the app warns that it is getting close
+pub const WARNING_PERCENT: u64 = 80;
+
+/// What the database holds, as the limits count it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct StorageUsage {
+    pub ideas: u64,
+    /// Development notes plus the bodies of structured note sections
+    pub notes_bytes: u64,
+    pub attachment_bytes: u64,
+}
+
+impl StorageUsage {
+    /// What storing `ideas` adds: the ideas and their notes, counted like [`server::storage_usage`] does
+    pub fn of_ideas(ideas: &[Idea]) -> StorageUsage {
+        let notes_bytes = ideas
+            .iter()
+            .map(|idea| idea.development_notes.len() + idea.sections.iter().map(|s| s.body.len()).sum::<usize>())
+            .sum::<usize>();
+        StorageUsage {
+            ideas: ideas.len() as u64,
+            notes_bytes: notes_bytes as u64,
+            attachment_bytes: 0,
+        }
+    }
+
+    /// Usage after adding `other`, stopping at `u64::MAX` rather than wrapping around
+    pub fn plus(&self, other: &StorageUsage) -> StorageUsage {
+        StorageUsage {
+            ideas: self.ideas.saturating_add(other.ideas),
+            notes_bytes: self.notes_bytes.saturating_add(other.notes_bytes),
+            attachment_bytes: self.attachment_bytes.saturating_add(other.attachment_bytes),
+        }
+    }
+}
+
+/// The limits of a deployment; `None` leaves that resource unlimited, which is the default
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct StorageLimits {
+    pub max_ideas: Option<u64>,
+    pub max_notes_bytes: Option<u64>,
+    pub max_attachment_bytes: Option<u64>,
+}
+
+/// Something a limit applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resource {
+    Ideas,
+    Notes,
+    Attachments,
+}
+
+impl Resource {
+    pub const ALL: [Resource; 3] = [Resource::Ideas, Resource::Notes, Resource::Attachments];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Resource::Ideas => "ideas",
+            Resource::Notes => "notes",
+            Resource::Attachments => "attachments",
+        }
+    }
+
+    fn used(self, usage: &StorageUsage) -> u64 {
+        match self {
+            Resource::Ideas => usage.ideas,
+            Resource::Notes => usage.notes_bytes,
+            Resource::Attachments => usage.attachment_bytes,
+        }
+    }
+
+    fn limit(self, limits: &StorageLimits) -> Option<u64> {
+        match self {
+            Resource::Ideas => limits.max_ideas,
+            Resource::Notes => limits.max_notes_bytes,
+            Resource::Attachments => limits.max_attachment_bytes,
+        }
+    }
+
+    /// An amount of the resource for people, e.g. "812" ideas or "1.5 MB" of notes
+    pub fn format(self, amount: u64) -> String {
+        match self {
+            Resource::Ideas => amount.to_string(),
+            Resource::Notes | Resource::Attachments => format_size(amount as usize),
+        }
+    }
+}
+
+impl StorageLimits {
+    /// Read the limits with `lookup`. A limit that is unset, zero or not a whole number is left out.
+    pub fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Self {
+        let limit = |key: &str| {
+            lookup(key)
+                .and_then(|value| value.trim().parse::<u64>().ok())
+                .filter(|&limit| limit > 0)
+        };
+        Self {
+            max_ideas: limit(MAX_IDEAS_VAR),
+            max_notes_bytes: limit(MAX_NOTES_BYTES_VAR),
+            max_attachment_bytes: limit(MAX_ATTACHMENT_BYTES_VAR),
+        }
+    }
+
+    pub fn is_unlimited(&self) -> bool {
+        Resource::ALL.iter().all(|resource| resource.limit(self).is_none())
+    }
+
+    /// Refuse a write adding `adding` to `usage` when any limit is already reached, or when the write would take
+    /// the resource it adds to past its limit
+    pub fn check(&self, usage: &StorageUsage, adding: &StorageUsage) -> Result<(), IdeaError> {
+        let after = usage.plus(adding);
+        for resource in Resource::ALL {
+            let Some(limit) = resource.limit(self) else {
+                continue;
+            };
+            if resource.used(usage) >= limit || resource.used(&after) > limit {
+                return Err(IdeaError::StorageFull(resource.as_str().to_string()));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// How close a resource is to its limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LimitLevel {
+    /// Below [`WARNING_PERCENT`], or unlimited
+    Fine,
+    Warning,
+    /// At or past the limit: nothing more is taken
+    Full,
+}
+
+/// Usage against the limits, as the stats endpoint reports it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct StorageReport {
+    pub usage: StorageUsage,
+    pub limits: StorageLimits,
+}
+
+impl StorageReport {
+    /// Share of the limit of `resource` in use, in whole percent. `None` when it is unlimited.
+    pub fn percent(&self, resource: Resource) -> Option<u64> {
+        let limit = resource.limit(&self.limits)?;
+        Some(resource.used(&self.usage).saturating_mul(100) / limit.max(1))
+    }
+
+    pub fn level_of(&self, resource: Resource) -> LimitLevel {
+        match (resource.limit(&self.limits), self.percent(resource)) {
+            (Some(limit), _) if resource.used(&self.usage) >= limit => LimitLevel::Full,
+            (_, Some(percent)) if percent >= WARNING_PERCENT => LimitLevel::Warning,
+            _ => LimitLevel::Fine,
+        }
+    }
+
+    /// The level of the resource closest to its limit
+    pub fn level(&self) -> LimitLevel {
+        Resource::ALL.iter().map(|&resource| self.level_of(resource)).max().unwrap_or(LimitLevel::Fine)
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.level() == LimitLevel::Full
+    }
+
+    /// "ideas: 812 of 1000 (81%)" for every limited resource at [`LimitLevel::Warning`] or worse
+    pub fn warnings(&self) -> Vec<String> {
+        Resource::ALL
+            .into_iter()
+            .filter(|&resource| self.level_of(resource) != LimitLevel::Fine)
+            .filter_map(|resource| {
+                let limit = resource.limit(&self.limits)?;
+                Some(format!(
+                    "{}: {} of {} ({}%)",
+                    resource.as_str(),
+                    resource.format(resource.used(&self.usage)),
+                    resource.format(limit),
+                    self.percent(resource)?
+                ))
+            })
+            .collect()
+    }
+}
+
+// Server-only measuring of the database, cached briefly
+#[cfg(feature = "server")]
+pub mod server {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+    use surrealdb::Surreal;
+
+    /// How long a measured usage is trusted before the database is measured again
+    pub const USAGE_CACHE_TTL: Duration = Duration::from_secs(10);
+
+    /// Measure the database with aggregate queries, which only return the totals
+    pub async fn storage_usage(db: &Surreal<surrealdb::engine::any::Any>) -> Result<StorageUsage, IdeaError> {
+        #[derive(Deserialize)]
+        struct IdeaTotals {
+            ideas: u64,
+            notes_bytes: Option<u64>,
+        }
+        #[derive(Deserialize)]
+        struct AttachmentTotals {
+            attachment_bytes: Option<u64>,
+        }
+
+        let mut response = db
+            .query(
+                "SELECT count() AS ideas, math::sum(bytes::len(<bytes> (development_notes ?? '')) \
+                 + bytes::len(<bytes> array::join(sections.body ?? [], ''))) AS notes_bytes FROM ideas GROUP ALL",
+            )
+            .query("SELECT math::sum(size) AS attachment_bytes FROM attachments GROUP ALL")
+            .await?;
+        // An empty table has no group at all
+        let ideas: Option<IdeaTotals> = response.take(0)?;
+        let attachments: Option<AttachmentTotals> = response.take(1)?;
+        Ok(StorageUsage {
+            ideas: ideas.as_ref().map_or(0, |totals| totals.ideas),
+            notes_bytes: ideas.and_then(|totals| totals.notes_bytes).unwrap_or(0),
+            attachment_bytes: attachments.and_then(|totals| totals.attachment_bytes).unwrap_or(0),
+        })
+    }
+
+    /// The last measured usage of one database, shared by its clones. Writes checked through [`check_room`] are
+    /// added to it right away, so writes in quick succession can't slip past a limit between two measures; deleting
+    /// only shows once the entry expires.
+    #[derive(Clone)]
+    pub struct UsageCache {
+        measured: Arc<Mutex<Option<(Instant, StorageUsage)>>>,
+        ttl: Duration,
+    }
+
+    impl Default for UsageCache {
+        fn default() -> Self {
+            Self::with_ttl(USAGE_CACHE_TTL)
+        }
+    }
+
+    impl UsageCache {
+        pub fn with_ttl(ttl: Duration) -> Self {
+            Self {
+                measured: Arc::default(),
+                ttl,
+            }
+        }
+
+        fn lock(&self) -> std::sync::MutexGuard<'_, Option<(Instant, StorageUsage)>> {
+            self.measured.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+        }
+
+        /// The usage of `db`, measured again when the last measure expired
+        pub async fn usage(&self, db: &Surreal<surrealdb::engine::any::Any>) -> Result<StorageUsage, IdeaError> {
+            let cached = *self.lock();
+            if let Some((_, usage)) = cached.filter(|(at, _)| at.elapsed() < self.ttl) {
+                return Ok(usage);
+            }
+            self.refresh(db).await
+        }
+
+        /// Measure `db` now, whether or not the last measure expired
+        pub async fn refresh(&self, db: &Surreal<surrealdb::engine::any::Any>) -> Result<StorageUsage, IdeaError> {
+            let usage = storage_usage(db).await?;
+            *self.lock() = Some((Instant::now(), usage));
+            Ok(usage)
+        }
+
+        /// Count `adding` in the cached usage
+        fn add(&self, adding: &StorageUsage) {
+            if let Some((_, usage)) = self.lock().as_mut() {
+                *usage = usage.plus(adding);
+            }
+        }
+    }
+
+    /// Refuse a write adding `adding` when it goes against `limits`, see [`StorageLimits::check`]. The database is
+    /// only measured when there are limits at all. An accepted write is counted in `cache` before it is made.
+    pub async fn check_room(
+        db: &Surreal<surrealdb::engine::any::Any>,
+        cache: &UsageCache,
+        limits: &StorageLimits,
+        adding: &StorageUsage,
+    ) -> Result<(), IdeaError> {
+        if limits.is_unlimited() {
+            return Ok(());
+        }
+        limits.check(&cache.usage(db).await?, adding)?;
+        cache.add(adding);
+        Ok(())
+    }
+
+    /// The usage cache of the current request's database, layered over the server by
+    /// [`crate::db::server::with_db`]; a fresh one outside of it
+    pub fn request_usage_cache() -> UsageCache {
+        crate::request::extension::<UsageCache>().unwrap_or_default()
+    }
+
+    /// Guard for server functions that add ideas or attachments, with the limits of the current deployment
+    pub async fn guard_storage(adding: StorageUsage) -> Result<(), IdeaError> {
+        let limits = crate::config::server::app_config().limits;
+        let db = crate::db::server::request_db()?;
+        check_room(&db, &request_usage_cache(), &limits, &adding).await
+    }
+
+    /// Usage against the limits of the current deployment
+    pub async fn storage_report() -> Result<StorageReport, IdeaError> {
+        let limits = crate::config::server::app_config().limits;
+        let db = crate::db::server::request_db()?;
+        let usage = request_usage_cache().usage(&db).await?;
+        Ok(StorageReport { usage, limits })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usage(ideas: u64, notes_bytes: u64, attachment_bytes: u64) -> StorageUsage {
+        StorageUsage {
+            ideas,
+            notes_bytes,
+            attachment_bytes,
+        }
+    }
+
+    fn ideas_only(max: u64) -> StorageLimits {
+        StorageLimits {
+            max_ideas: Some(max),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_limits_from_lookup() {
+        let limits = StorageLimits::from_lookup(|key| match key {
+            MAX_IDEAS_VAR => Some(" 500 ".to_string()),
+            MAX_NOTES_BYTES_VAR => Some("0".to_string()),
+            MAX_ATTACHMENT_BYTES_VAR => Some("lots".to_string()),
+            _ => None,
+        });
+        assert_eq!(limits, ideas_only(500));
+        assert!(StorageLimits::from_lookup(|_| None).is_unlimited());
+        assert!(!limits.is_unlimited());
+    }
+
+    #[test]
+    fn test_check_refuses_past_the_limit() {
+        let limits = StorageLimits {
+            max_attachment_bytes: Some(1000),
+            ..ideas_only(10)
+        };
+        let one_idea = usage(1, 0, 0);
+        assert_eq!(limits.check(&usage(9, 0, 0), &one_idea), Ok(()));
+        assert_eq!(
+            limits.check(&usage(10, 0, 0), &one_idea),
+            Err(IdeaError::StorageFull("ideas".to_string()))
+        );
+
+        // An upload has to fit in what is left
+        assert_eq!(limits.check(&usage(0, 0, 600), &usage(0, 0, 400)), Ok(()));
+        assert_eq!(
+            limits.check(&usage(0, 0, 600), &usage(0, 0, 401)),
+            Err(IdeaError::StorageFull("attachments".to_string()))
+        );
+        // A full resource blocks every other addition too
+        assert_eq!(
+            limits.check(&usage(10, 0, 0), &usage(0, 0, 1)),
+            Err(IdeaError::StorageFull("ideas".to_string()))
+        );
+        assert_eq!(StorageLimits::default().check(&usage(u64::MAX, 0, 0), &one_idea), Ok(()));
+    }
+
+    #[test]
+    fn test_usage_of_ideas_counts_note_bytes() {
+        let ideas = vec![
+            Idea {
+                development_notes: "héllo".to_string(),
+                ..Default::default()
+            },
+            Idea {
+                sections: vec![crate::db::NoteSection {
+                    heading: "Not counted".to_string(),
+                    body: "abc".to_string(),
+                }],
+                ..Default::default()
+            },
+        ];
+        assert_eq!(StorageUsage::of_ideas(&ideas), usage(2, 9, 0));
+        assert_eq!(StorageUsage::of_ideas(&[]), StorageUsage::default());
+    }
+
+    #[test]
+    fn test_report_levels() {
+        let limits = StorageLimits {
+            max_notes_bytes: Some(2048),
+            ..ideas_only(10)
+        };
+        let report = |usage| StorageReport { usage, limits };
+
+        assert_eq!(report(usage(7, 0, 1_000_000)).level(), LimitLevel::Fine);
+        assert!(report(usage(7, 0, 0)).warnings().is_empty());
+
+        let warned = report(usage(8, 1024, 0));
+        assert_eq!(warned.level_of(Resource::Ideas), LimitLevel::Warning);
+        assert_eq!(warned.level_of(Resource::Notes), LimitLevel::Fine);
+        assert_eq!(warned.level_of(Resource::Attachments), LimitLevel::Fine);
+        assert_eq!(warned.warnings(), vec!["ideas: 8 of 10 (80%)"]);
+        assert!(!warned.is_full());
+
+        let full = report(usage(3, 2048, 0));
+        assert_eq!(full.level(), LimitLevel::Full);
+        assert_eq!(full.warnings(), vec!["notes: 2.0 KB of 2.0 KB (100%)"]);
+        assert_eq!(full.percent(Resource::Attachments), None);
+    }
+}
@@ -2,16 +2,113 @@
 // need dioxus
 use dioxus::prelude::*;
 
-use views::{Blog, Home, Navbar, IdeaDevelopment};
+use utils::ListQuery;
+use views::{Admin, Blog, Home, Navbar, Board, BoardIdeas, IdeaBySlug, IdeaDevelopment, IdeaGraph, IdeaReport, ListReport, Login, NotFound, Review, Stats, Trash};
 
 /// Define a components module that contains all shared components for our app.
 mod components;
 /// Define a views module that contains the UI for all Layouts and Routes for our app.
 mod views;
+/// Files attached to ideas
+mod attachments;
+/// Serving the app below a path prefix
+mod base_path;
+/// Email/password authentication and sessions
+mod auth;
+/// Separate sets of ideas, like "work" and "personal"
+mod boards;
+/// Moving one idea with everything attached to it between instances
+mod bundle;
+/// Notifications about ideas edited in another tab
+mod changes;
+/// Copying text to the system clipboard
+mod clipboard;
+/// Failures seen by the client, reported to the server log
+mod client_errors;
+/// Settings of a deployment, like read-only mode
+mod config;
+/// Location of the database on disk
+#[cfg(any(feature = "server", feature = "desktop"))]
+mod data_dir;
 /// Database module for SurrealDB integration
 mod db;
+/// Resetting the database while developing
+#[cfg(feature = "dev-tools")]
+mod dev_tools;
+/// What happened to the ideas lately, as the body of an email
+mod digest;
+/// Structured errors shared by the client and the server
+mod error;
+/// Atom feed of the newest ideas
+mod feed;
+/// Required, hidden and custom fields of the new-idea form
+mod form_config;
+/// Links between ideas and the layout of the graph view
+mod graph;
+/// Activity log of each idea
+mod history;
+/// Repeated submissions of the new-idea form that create the idea once
+mod idempotency;
+/// Slow work after a write, done by a background worker
+mod jobs;
+/// Soft limits on how much the database holds
+mod limits;
+/// Pruning of old revisions and trash, run daily
+mod maintenance;
+/// Ideas as markdown `## Title` sections, read by imports and written by "copy"
+mod markdown;
+/// The JSON export of every idea, and reading exports of older versions
+mod migrations;
+/// The welcome tour shown while there are no ideas
+mod onboarding;
+/// Recently viewed ideas, remembered client-side
+mod recent;
+/// Going through ideas nobody has touched in a while
+mod review;
+/// Snapshots of idea notes that older versions can be restored from
+mod revisions;
+/// Made-up ideas for the developer reset
+#[cfg(all(feature = "server", feature = "dev-tools"))]
+mod sample;
 /// Server functions for API endpoints
 mod server_functions;
+/// Display preferences of the idea list
+mod settings;
+/// Draining requests before the server exits on SIGINT/SIGTERM
+mod shutdown;
+/// Nightly copies of the database in a folder of rotating files
+mod snapshots;
+/// Ideas created over time, for the stats page
+mod stats;
+/// Custom hooks shared by components
+mod hooks;
+/// Client-side key-value storage (localStorage on web)
+mod storage;
+/// Tag chip colors
+mod tags;
+/// Reusable starting points for new ideas
+mod templates;
+/// Light/dark theme selection
+mod theme;
+/// Pure helpers shared by components and server functions
+mod utils;
+/// Creating ideas from other apps with a shared secret
+mod webhook;
+/// Access to the HTTP request behind a server function call
+#[cfg(feature = "server")]
+mod request;
+/// Per-client write limits for the server functions
+#[cfg(feature = "server")]
+mod rate_limit;
+/// REST routes for scripts outside the app
+#[cfg(feature = "server")]
+mod rest;
+/// The Axum router around the app: REST routes, feed, settings and database
+#[cfg(feature = "server")]
+mod server_router;
+/// Server logs and the spans around server function calls
+#[cfg(feature = "server")]
+mod telemetry;
 
 /// The Route enum is used to define the structure of internal routes in our app. All route enums need to derive
 /// the [`Routable`] trait, which provides the necessary methods for the router to work.
@@ -26,8 +123,13 @@ enum Route {
     #[layout(Navbar)]
         // The route attribute defines the URL pattern that a specific route matches. If that pattern matches the URL,
         // the component for that route will be rendered. The component name that is rendered defaults to the variant name.
-        #[route("/")]
-        Home {},
+        // The `?:..` syntax hands the whole query string to a type that converts from `&str` and back with
+        // `Display`. The idea list keeps its search, filter, sort and page there.
+        #[route("/?:..query")]
+        Home { query: ListQuery },
+        // The idea list of one board, so a board can be linked to, e.g. `/b/work?tag=ux`
+        #[route("/b/:slug?:..query")]
+        BoardIdeas { slug: String, query: ListQuery },
         // The route attribute can include dynamic parameters that implement [`std::str::FromStr`] and [`std::fmt::Display`] with the `:` syntax.
         // In this case, id will match any integer like `/blog/123` or `/blog/-456`.
         #[route("/blog/:id")]
@@ -35,38 +137,156 @@ enum Route {
         // an `id` prop of type `i32`.
         Blog { id: i32 },
         // Idea development route with String id for SurrealDB format (ideas:xyz)
+        // An address with no id at all goes back to the list instead of asking the server for a blank one
+        #[redirect("/idea", || Route::Home { query: ListQuery::default() })]
         #[route("/idea/:id")]
         IdeaDevelopment { id: String },
+        // Shareable idea route using the slug derived from the title
+        #[route("/i/:slug")]
+        IdeaBySlug { slug: String },
+        // Database maintenance tools (backup and restore)
+        #[route("/admin")]
+        Admin {},
+        // Sign in / create an account
+        #[route("/login")]
+        Login {},
+        // Deleted ideas, with restore and purge
+        #[route("/trash")]
+        Trash {},
+        // Ideas drawn as a graph of their links
+        #[route("/graph")]
+        IdeaGraph {},
+        // Ideas in a column per status
+        #[route("/board")]
+        Board {},
+        // How many ideas were created lately
+        #[route("/stats")]
+        Stats {},
+        // Ideas nobody touched in a while, one at a time
+        #[route("/review")]
+        Review {},
+    // Routes after `end_layout` render without the navbar
+    #[end_layout]
+    // Printable one-pagers. The list report takes the same tag filter as the list, e.g. `/report?tag=ux&tag=api`.
+    #[route("/report?:..query")]
+    ListReport { query: ListQuery },
+    #[route("/report/:id")]
+    IdeaReport { id: String },
+    // Any address no route above matches. It comes last so it only catches what the others don't, and gets the
+    // navbar back so there is a way on.
+    #[layout(Navbar)]
+        #[route("/:..segments")]
+        NotFound { segments: Vec<String> },
 }
 
 // We can import assets in dioxus with the `asset!` macro. This macro takes a path to an asset relative to the crate root.
 // The macro returns an `Asset` type that will display as the path to the asset in the browser or a local path in desktop bundles.
 const FAVICON: Asset = asset!("/assets/favicon.ico");
-// The asset macro also minifies some assets like CSS and JS to make bundled smaller
-const MAIN_CSS: Asset = asset!("/assets/styling/main.css");
+// The asset macro also minifies some assets like CSS and JS to make bundled smaller. Stylesheets are linked by
+// `components::Stylesheets`, which components register theirs with through `hooks::use_stylesheet`.
 
+#[cfg(not(feature = "server"))]
 fn main() {
     // The `launch` function is the main entry point for a dioxus app. It takes a component and renders it with the platform feature
     // you have enabled
+    #[cfg(feature = "web")]
+    {
+        // Under a base path, server functions are called below it and routes are matched below it
+        let base = base_path::base_path();
+        if !base.is_empty() {
+            dioxus::fullstack::set_server_url(Box::leak(base.clone().into_boxed_str()));
+            let history = dioxus::web::WebHistory::new(Some(base), true);
+            let config = dioxus::web::Config::new().history(std::rc::Rc::new(history));
+            dioxus::LaunchBuilder::new().with_cfg(config).launch(App);
+            return;
+        }
+    }
     dioxus::launch(App);
 }
 
+#[cfg(feature = "server")]
+#[tokio::main]
+async fn main() -> std::process::ExitCode {
+    // The server build serves the app like `launch` does, with the REST routes mounted on the same Axum router.
+    // The database and the settings from the environment are handed to both instead of living in globals, so server
+    // functions read them from the request.
+    // Installed before serving so every log line goes through the `RUST_LOG` filter
+    telemetry::init();
+    let db = db::server::open_db().await;
+    let config = config::AppConfig::from_env();
+    let daily = maintenance::server::spawn_daily(db.clone(), config);
+    let nightly = snapshots::server::Snapshots::new(snapshots::SnapshotSettings::from_env());
+    let snapshotting = snapshots::server::spawn_nightly(db.clone(), nightly.clone());
+    let jobs = jobs::server::start_server_jobs();
+    let in_flight = shutdown::server::InFlight::default();
+    let settings = shutdown::ShutdownSettings::from_env();
+    // Everything moves below `APP_BASE_PATH` when it is set
+    let router = server_router::with_server_routes(dioxus::server::router(App), db.clone(), config);
+    let router = snapshots::server::with_snapshots(router, nightly.clone());
+    let router = jobs::server::with_jobs(router, jobs.clone());
+    let router = shutdown::server::with_in_flight(router, in_flight.clone());
+
+    // Served here rather than through `dioxus::serve`, so every request knows the address it came from for the
+    // per-client write limits, see `request::client_ip`
+    let address = dioxus::cli_config::fullstack_address_or_localhost();
+    let listener = tokio::net::TcpListener::bind(address)
+        .await
+        .unwrap_or_else(|e| panic!("Failed to bind to {address}: {e}"));
+    let stopping = async move {
+        shutdown::server::shutdown_signal().await;
+        shutdown::server::shut_down(&db, &in_flight, &settings, Some(&nightly), Some(&jobs)).await
+    };
+    let drained = shutdown::server::serve(listener, router, stopping).await.expect("Server failed");
+
+    // The scheduled tasks hold the last handles on the database; once they are gone the engine closes
+    for task in daily.into_iter().chain([snapshotting]) {
+        task.abort();
+        task.await.ok();
+    }
+    if drained {
+        std::process::ExitCode::SUCCESS
+    } else {
+        std::process::ExitCode::FAILURE
+    }
+}
+
 /// App is the main component of our app. Components are the building blocks of dioxus apps. Each component is a function
 /// that takes some props and returns an Element. In this case, App takes no props because it is the root of our app.
 ///
 /// Components should be annotated with `#[component]` to support props, better error messages, and autocomplete
 #[component]
 fn App() -> Element {
+    // The theme is provided here so the root div can carry it for the stylesheets
+    let theme = hooks::use_theme_provider();
+    // List display preferences, shared by every page
+    hooks::use_settings_provider();
+    // Whether the server takes edits at all
+    hooks::use_app_config_provider();
+    // Whether to lay pages out for a phone
+    hooks::use_viewport_provider();
+    // One timer keeping every "3 h ago" on the page current
+    hooks::use_clock_provider();
+    // Stylesheets registered by the components on screen, linked once by `Stylesheets` below
+    hooks::use_stylesheets_provider();
+    // Whether anyone is using the tab, so live updates can pause while nobody is
+    hooks::use_idle(hooks::IDLE_TIMEOUT);
+
     // The `rsx!` macro lets us define HTML inside of rust. It expands to an Element with all of our HTML inside.
     rsx! {
         // In addition to element and text (which we will see later), rsx can contain other components. In this case,
-        // we are using the `document::Link` component to add a link to our favicon and main CSS file into the head of our app.
+        // we are using the `document::Link` component to add a link to our favicon into the head of our app, and
+        // `Stylesheets` for every CSS file.
         document::Link { rel: "icon", href: FAVICON }
-        document::Link { rel: "stylesheet", href: MAIN_CSS }
+        components::Stylesheets {}
+
 
+        div {
+            id: "app-root",
+            "data-theme": theme.resolved(),
 
-        // The router component renders the route enum we defined above. It will handle synchronization of the URL and render
-        // the layouts and components for the active route.
-        Router::<Route> {}
+            // The router component renders the route enum we defined above. It will handle synchronization of the URL and render
+            // the layouts and components for the active route.
+            Router::<Route> {}
+        }
     }
 }
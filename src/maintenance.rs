@@ -0,0 +1,172 @@
+//! Housekeeping of the stored data, run daily by the server and on demand from the admin page.
+//!
+//! Each step is a function of the database (and of the current time, given by a [`Clock`]) so it can be run on its
+//! own against the in-memory engine. [`server::run_maintenance`] runs them all and [`server::spawn_daily`] schedules
+//! that once a day.
+//!
+//! There is no compaction step: the embedded SurrealDB API doesn't expose RocksDB's manual compaction, and RocksDB
+//! already compacts in the background, reclaiming the space of the records deleted here.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::Duration;
+
+/// Time between two runs of the background task
+pub const MAINTENANCE_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Source of the current wall-clock time
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// The system clock, used in production
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A clock stopped at a given time, for tests
+#[derive(Debug, Clone, Copy)]
+pub struct FixedClock(pub DateTime<Utc>);
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.0
+    }
+}
+
+/// What a maintenance run removed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MaintenanceReport {
+    /// Revisions beyond [`crate::revisions::MAX_REVISIONS`] for their idea
+    pub revisions_pruned: usize,
+    /// Ideas that were in the trash for longer than the retention period
+    pub trash_purged: usize,
+}
+
+impl fmt::Display for MaintenanceReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "pruned {} revisions, purged {} trashed ideas",
+            self.revisions_pruned, self.trash_purged
+        )
+    }
+}
+
+#[cfg(feature = "server")]
+pub mod server {
+    use super::*;
+    use crate::config::AppConfig;
+    use crate::db::IdeaRecord;
+    use crate::error::IdeaError;
+    use crate::revisions::MAX_REVISIONS;
+    use surrealdb::{engine::any::Any, sql::Thing, Surreal};
+
+    /// Delete the oldest revisions of every idea holding more than [`MAX_REVISIONS`]. Saving a revision already
+    /// prunes its idea, so this only catches what older versions or restored backups left behind.
+    pub async fn prune_revisions(db: &Surreal<Any>) -> Result<usize, IdeaError> {
+        #[derive(Deserialize)]
+        struct CountRow {
+            idea: Thing,
+            count: usize,
+        }
+        #[derive(Deserialize)]
+        struct RevisionRow {
+            id: Thing,
+        }
+
+        let counts: Vec<CountRow> = db
+            .query("SELECT idea, count() AS count FROM idea_revisions GROUP BY idea")
+            .await?
+            .take(0)?;
+        let mut pruned = 0;
+        for row in counts.into_iter().filter(|row| row.count > MAX_REVISIONS) {
+            // The ordered field has to be selected too
+            let rows: Vec<RevisionRow> = db
+                .query("SELECT id, at FROM idea_revisions WHERE idea = $idea ORDER BY at DESC START $keep")
+                .bind(("idea", row.idea))
+                .bind(("keep", MAX_REVISIONS))
+                .await?
+                .take(0)?;
+            let stale: Vec<Thing> = rows.into_iter().map(|row| row.id).collect();
+            pruned += stale.len();
+            db.query("DELETE idea_revisions WHERE id INSIDE $stale")
+                .bind(("stale", stale))
+                .await?
+                .check()?;
+        }
+        Ok(pruned)
+    }
+
+    /// Permanently delete the ideas of every user that were trashed more than `days` days before `clock`'s time,
+    /// with their attachments, history and revisions. `0` days keeps the trash forever.
+    pub async fn purge_old_trash(db: &Surreal<Any>, clock: &impl Clock, days: u32) -> Result<usize, IdeaError> {
+        #[derive(Deserialize)]
+        struct TrashedRow {
+            id: Thing,
+            deleted_at: DateTime<Utc>,
+        }
+
+        if days == 0 {
+            return Ok(0);
+        }
+        let cutoff = clock.now() - chrono::Duration::days(days.into());
+        // Compared here rather than in the query, as older rows may hold the time as a string
+        let trashed: Vec<TrashedRow> = db
+            .query("SELECT id, deleted_at FROM ideas WHERE deleted_at != NONE")
+            .await?
+            .take(0)?;
+        let expired: Vec<Thing> = trashed
+            .into_iter()
+            .filter(|row| row.deleted_at < cutoff)
+            .map(|row| row.id)
+            .collect();
+        if expired.is_empty() {
+            return Ok(0);
+        }
+
+        let deleted: Vec<IdeaRecord> = db
+            .query("DELETE ideas WHERE id INSIDE $expired RETURN BEFORE")
+            .bind(("expired", expired))
+            .await?
+            .take(0)?;
+        Ok(deleted.len())
+    }
+
+    /// Run every maintenance step once
+    pub async fn run_maintenance(
+        db: &Surreal<Any>,
+        clock: &impl Clock,
+        config: &AppConfig,
+    ) -> Result<MaintenanceReport, IdeaError> {
+        Ok(MaintenanceReport {
+            revisions_pruned: prune_revisions(db).await?,
+            trash_purged: purge_old_trash(db, clock, config.trash_retention_days).await?,
+        })
+    }
+
+    /// Run maintenance now and then every [`MAINTENANCE_INTERVAL`] until the returned task is aborted, logging what
+    /// each run removed. Read-only deployments are left untouched and get no task.
+    pub fn spawn_daily(db: Surreal<Any>, config: AppConfig) -> Option<tokio::task::JoinHandle<()>> {
+        if config.read_only {
+            return None;
+        }
+        Some(tokio::spawn(async move {
+            let mut interval = tokio::time::interval(MAINTENANCE_INTERVAL);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                interval.tick().await;
+                match run_maintenance(&db, &SystemClock, &config).await {
+                    Ok(report) => tracing::info!(target: "maintenance", ?report, "{}", report),
+                    Err(error) => tracing::warn!(target: "maintenance", %error, "maintenance failed"),
+                }
+            }
+        }))
+    }
+}
@@ -0,0 +1,646 @@
+//! Ideas as markdown, read from an imported file and written when an idea is copied.
+//!
+//! Each `## Title` heading starts an idea. The paragraphs after it become the description, a `tags: a, b` line
+//! gives its tags, a bullet list under a heading mentioning "must be true" gives its "what must be true"
+//! statements and everything under a "Notes" heading becomes its development notes:
+//!
+//! ```markdown
+//! ## Bike repair café
+//! Monthly meetup where neighbours fix each other's bikes.
+//!
+//! tags: community, bikes
+//!
+//! ### What must be true
+//! - [ ] People own bikes that need fixing
+//! - [ ] Someone lends a space
+//!
+//! ### Notes
+//! Ask the library about the back room.
+//! ```
+//!
+//! Anything before the first `##` heading or after a `#` heading is ignored, and lines inside fenced code blocks are
+//! always text, even when they look like headings. [`parse_markdown`] never fails; [`ParsedIdea::check`] decides
+//! which of the parsed ideas can be imported. [`idea_to_markdown`] writes an idea in the same shape.
+//!
+//! Notes can also be kept as sections, one per heading: [`split_note_sections`] cuts plain notes at their headings
+//! and [`join_note_sections`] writes the sections back as plain notes.
+
+use crate::db::{Idea, NoteSection};
+use crate::templates::parse_tags;
+use serde::{Deserialize, Serialize};
+
+/// One idea read from the file
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParsedIdea {
+    /// Line of the `##` heading, counted from 1, to point at problems
+    pub line: usize,
+    pub title: String,
+    pub description: String,
+    pub tags: Vec<String>,
+    pub what_must_be_true: Vec<String>,
+    pub development_notes: String,
+}
+
+impl ParsedIdea {
+    /// Why this idea can't be imported, if it can't
+    pub fn check(&self) -> Result<(), String> {
+        if self.title.is_empty() {
+            return Err(format!("line {}: the heading has no title", self.line));
+        }
+        Ok(())
+    }
+
+    /// The idea to create
+    pub fn into_idea(self) -> Idea {
+        Idea {
+            title: self.title,
+            description: self.description,
+            tags: self.tags,
+            what_must_be_true: self.what_must_be_true,
+            development_notes: self.development_notes,
+            ..Default::default()
+        }
+    }
+}
+
+/// What an import did
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ImportSummary {
+    /// Number of ideas created
+    pub imported: usize,
+    /// Why each idea that wasn't imported was left out
+    pub skipped: Vec<String>,
+}
+
+/// The part of an idea the next lines belong to
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Section {
+    Description,
+    MustBeTrue,
+    Notes,
+}
+
+/// A markdown heading: its level and text without the `#` markers
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let trimmed = line.trim_start();
+    // Four spaces of indentation make a code block rather than a heading
+    if line.len() - trimmed.len() > 3 {
+        return None;
+    }
+    let level = trimmed.chars().take_while(|&c| c == '#').count();
+    if level == 0 || level > 6 {
+        return None;
+    }
+    let rest = &trimmed[level..];
+    if !rest.is_empty() && !rest.starts_with([' ', '\t']) {
+        return None;
+    }
+    // A closing run of `#` after a space is decoration, `## C#` keeps its `#`
+    let text = rest.trim();
+    let unclosed = text.trim_end_matches('#');
+    if unclosed.is_empty() || unclosed.ends_with([' ', '\t']) {
+        return Some((level, unclosed.trim_end()));
+    }
+    Some((level, text))
+}
+
+/// The marker opening or closing a fenced code block, if the line is one
+fn fence(line: &str) -> Option<&str> {
+    let trimmed = line.trim_start();
+    ["```", "~~~"].into_iter().find(|marker| trimmed.starts_with(marker))
+}
+
+/// The text of a list item, if the line starts one
+fn bullet(line: &str) -> Option<&str> {
+    let trimmed = line.trim_start();
+    if let Some(text) = ["- ", "* ", "+ "].iter().find_map(|marker| trimmed.strip_prefix(marker)) {
+        // Checklist boxes, as written by `idea_to_markdown`, aren't part of the statement
+        let text = ["[ ]", "[x]", "[X]"].iter().find_map(|checkbox| text.strip_prefix(checkbox)).unwrap_or(text);
+        return Some(text.trim());
+    }
+    let digits = trimmed.chars().take_while(char::is_ascii_digit).count();
+    if digits > 0 {
+        let rest = &trimmed[digits..];
+        if let Some(text) = rest.strip_prefix(". ").or_else(|| rest.strip_prefix(") ")) {
+            return Some(text.trim());
+        }
+    }
+    None
+}
+
+/// The tags of a `tags: a, b` line, if the line is one
+fn tags_line(line: &str) -> Option<&str> {
+    let (key, value) = line.trim().split_once(':')?;
+    key.eq_ignore_ascii_case("tags").then_some(value)
+}
+
+/// Lines of a description, with runs of blank lines squeezed to one and none at either end
+fn join_description(lines: &[&str]) -> String {
+    let mut text = String::new();
+    let mut blank_before = false;
+    for line in lines {
+        if line.trim().is_empty() {
+            blank_before = !text.is_empty();
+            continue;
+        }
+        if blank_before {
+            text.push_str("\n\n");
+        } else if !text.is_empty() {
+            text.push('\n');
+        }
+        text.push_str(line.trim_end());
+        blank_before = false;
+    }
+    text
+}
+
+/// Whether a sub-heading starts the development notes
+fn is_notes_heading(text: &str) -> bool {
+    matches!(text.to_lowercase().as_str(), "notes" | "development notes")
+}
+
+/// Idea under construction
+struct Draft<'a> {
+    idea: ParsedIdea,
+    description: Vec<&'a str>,
+    notes: Vec<&'a str>,
+    section: Section,
+}
+
+impl<'a> Draft<'a> {
+    /// Where a line of text goes in the current section
+    fn text(&mut self) -> &mut Vec<&'a str> {
+        match self.section {
+            Section::Notes => &mut self.notes,
+            Section::Description | Section::MustBeTrue => &mut self.description,
+        }
+    }
+
+    fn finish(self) -> ParsedIdea {
+        let mut idea = self.idea;
+        idea.description = join_description(&self.description);
+        idea.development_notes = join_description(&self.notes);
+        idea
+    }
+}
+
+/// Read every `##` section of `content` as an idea, in file order
+pub fn parse_markdown(content: &str) -> Vec<ParsedIdea> {
+    let mut ideas = Vec::new();
+    let mut draft: Option<Draft> = None;
+    // Marker of the fenced code block the current line is in
+    let mut open_fence: Option<&str> = None;
+
+    for (index, line) in content.lines().enumerate() {
+        if let Some(marker) = open_fence {
+            if fence(line) == Some(marker) {
+                open_fence = None;
+            }
+            if let Some(draft) = draft.as_mut() {
+                draft.text().push(line);
+            }
+            continue;
+        }
+        if let Some(marker) = fence(line) {
+            open_fence = Some(marker);
+            if let Some(draft) = draft.as_mut() {
+                if draft.section == Section::MustBeTrue {
+                    draft.section = Section::Description;
+                }
+                draft.text().push(line);
+            }
+            continue;
+        }
+
+        if let Some((level, text)) = heading(line) {
+            match level {
+                1 | 2 => {
+                    ideas.extend(draft.take().map(Draft::finish));
+                    if level == 2 {
+                        draft = Some(Draft {
+                            idea: ParsedIdea {
+                                line: index + 1,
+                                title: text.to_string(),
+                                ..Default::default()
+                            },
+                            description: Vec::new(),
+                            notes: Vec::new(),
+                            section: Section::Description,
+                        });
+                    }
+                }
+                _ => {
+                    if let Some(draft) = draft.as_mut() {
+                        if draft.section == Section::Notes {
+                            // The notes run to the next idea, headings included
+                            draft.notes.push(line);
+                        } else if text.to_lowercase().contains("must be true") {
+                            draft.section = Section::MustBeTrue;
+                        } else if is_notes_heading(text) {
+                            draft.section = Section::Notes;
+                        } else {
+                            // Other sub-headings are part of the description
+                            draft.section = Section::Description;
+                            draft.description.push(line);
+                        }
+                    }
+                }
+            }
+            continue;
+        }
+
+        let Some(draft) = draft.as_mut() else {
+            continue;
+        };
+        if draft.section == Section::Notes {
+            draft.notes.push(line);
+            continue;
+        }
+        if let Some(tags) = tags_line(line) {
+            for tag in parse_tags(tags) {
+                if !draft.idea.tags.contains(&tag) {
+                    draft.idea.tags.push(tag);
+                }
+            }
+            continue;
+        }
+        if draft.section == Section::MustBeTrue {
+            if line.trim().is_empty() {
+                continue;
+            }
+            if let Some(text) = bullet(line) {
+                if !text.is_empty() {
+                    draft.idea.what_must_be_true.push(text.to_string());
+                }
+                continue;
+            }
+            // An indented line continues the statement above it
+            let continues = line.starts_with([' ', '\t']);
+            if let Some(last) = draft.idea.what_must_be_true.last_mut().filter(|_| continues) {
+                last.push(' ');
+                last.push_str(line.trim());
+                continue;
+            }
+            // Anything else ends the list
+            draft.section = Section::Description;
+        }
+        draft.description.push(line);
+    }
+
+    ideas.extend(draft.map(Draft::finish));
+    ideas
+}
+
+/// Headings plain notes turned into sections start with when they have no headings of their own
+pub const STARTER_SECTIONS: [&str; 3] = ["Problem", "Hypothesis", "Experiment"];
+
+/// Lines with the blank ones at either end dropped, and nothing else changed
+fn trim_blank_lines(lines: &[&str]) -> String {
+    let start = lines.iter().position(|line| !line.trim().is_empty()).unwrap_or(lines.len());
+    let end = lines.iter().rposition(|line| !line.trim().is_empty()).map_or(start, |last| last + 1);
+    lines[start..end].iter().map(|line| line.trim_end()).collect::<Vec<_>>().join("\n")
+}
+
+/// Cut notes into sections at every heading outside fenced code. Text before the first heading becomes a section
+/// with an empty heading; blank notes give no sections at all.
+pub fn split_note_sections(notes: &str) -> Vec<NoteSection> {
+    let mut sections = Vec::new();
+    let mut current: Option<&str> = None;
+    let mut body: Vec<&str> = Vec::new();
+    let mut open_fence: Option<&str> = None;
+
+    for line in notes.lines() {
+        if let Some(marker) = open_fence {
+            if fence(line) == Some(marker) {
+                open_fence = None;
+            }
+        } else if let Some(marker) = fence(line) {
+            open_fence = Some(marker);
+        } else if let Some((_, text)) = heading(line) {
+            if current.is_some() || body.iter().any(|line| !line.trim().is_empty()) {
+                sections.push(NoteSection {
+                    heading: current.unwrap_or_default().to_string(),
+                    body: trim_blank_lines(&body),
+                });
+            }
+            current = Some(text);
+            body.clear();
+            continue;
+        }
+        body.push(line);
+    }
+    if current.is_some() || body.iter().any(|line| !line.trim().is_empty()) {
+        sections.push(NoteSection {
+            heading: current.unwrap_or_default().to_string(),
+            body: trim_blank_lines(&body),
+        });
+    }
+    sections
+}
+
+/// Sections as markdown, each heading at `level`, with a blank line between sections
+fn write_sections(sections: &[NoteSection], level: usize) -> String {
+    sections
+        .iter()
+        .filter(|section| !section.heading.trim().is_empty() || !section.body.trim().is_empty())
+        .map(|section| {
+            let heading = section.heading.trim();
+            let body = section.body.trim_end();
+            match (heading.is_empty(), body.is_empty()) {
+                (true, _) => body.to_string(),
+                (false, true) => format!("{} {}", "#".repeat(level), heading),
+                (false, false) => format!("{} {}\n{}", "#".repeat(level), heading, body),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// Sections back as plain notes, each under a `##` heading. Splitting the result gives the sections again.
+pub fn join_note_sections(sections: &[NoteSection]) -> String {
+    write_sections(sections, 2)
+}
+
+/// `idea` in the shape [`parse_markdown`] reads: the title as a `##` heading, the description, a tags line, the
+/// statements as a checklist and the notes followed by their sections, leaving out whatever is empty
+pub fn idea_to_markdown(idea: &Idea) -> String {
+    let mut blocks = vec![format!("## {}", idea.title.trim())];
+    if !idea.description.trim().is_empty() {
+        blocks.push(idea.description.trim().to_string());
+    }
+    if !idea.tags.is_empty() {
+        blocks.push(format!("tags: {}", idea.tags.join(", ")));
+    }
+    let statements: Vec<String> = idea
+        .what_must_be_true
+        .iter()
+        .map(|statement| statement.trim())
+        .filter(|statement| !statement.is_empty())
+        .map(|statement| format!("- [ ] {}", statement))
+        .collect();
+    if !statements.is_empty() {
+        blocks.push(format!("### What must be true\n{}", statements.join("\n")));
+    }
+    let notes: Vec<String> = [idea.development_notes.trim().to_string(), write_sections(&idea.sections, 4)]
+        .into_iter()
+        .filter(|text| !text.is_empty())
+        .collect();
+    if !notes.is_empty() {
+        blocks.push(format!("### Notes\n{}", notes.join("\n\n")));
+    }
+    blocks.join("\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parses_the_documented_shape() {
+        let ideas = parse_markdown(
+            "# My ideas\n\
+             intro that isn't an idea\n\
+             \n\
+             ## Bike repair café\n\
+             Monthly meetup where neighbours fix each other's bikes.\n\
+             \n\
+             Free coffee.\n\
+             \n\
+             tags: community, bikes\n\
+             \n\
+             ### What must be true\n\
+             - People own bikes that need fixing\n\
+             - Someone lends a space\n\
+             \n\
+             ## Seed library\n\
+             Swap seeds at the library.\n",
+        );
+
+        assert_eq!(ideas.len(), 2);
+        assert_eq!(
+            ideas[0],
+            ParsedIdea {
+                line: 4,
+                title: "Bike repair café".to_string(),
+                description: "Monthly meetup where neighbours fix each other's bikes.\n\nFree coffee.".to_string(),
+                tags: vec!["community".to_string(), "bikes".to_string()],
+                what_must_be_true: vec![
+                    "People own bikes that need fixing".to_string(),
+                    "Someone lends a space".to_string(),
+                ],
+                development_notes: String::new(),
+            }
+        );
+        assert_eq!(ideas[1].title, "Seed library");
+        assert_eq!(ideas[1].description, "Swap seeds at the library.");
+        assert!(ideas[1].tags.is_empty());
+    }
+
+    #[test]
+    fn test_missing_blank_lines() {
+        let ideas = parse_markdown(
+            "## One\nfirst line\nsecond line\nTags: a,b,,a\n### Must be true\n* it works\n1. it ships\n## Two\n## Three",
+        );
+        let titles: Vec<&str> = ideas.iter().map(|idea| idea.title.as_str()).collect();
+        assert_eq!(titles, vec!["One", "Two", "Three"]);
+        assert_eq!(ideas[0].description, "first line\nsecond line");
+        assert_eq!(ideas[0].tags, vec!["a", "b"]);
+        assert_eq!(ideas[0].what_must_be_true, vec!["it works", "it ships"]);
+        assert_eq!(ideas[1].description, "");
+    }
+
+    #[test]
+    fn test_code_blocks_hide_headings_and_tags() {
+        let ideas = parse_markdown(
+            "## Script\nRun this:\n```sh\n## not a heading\ntags: not, tags\n```\n~~~\n# nor this\n~~~\nafter\n",
+        );
+        assert_eq!(ideas.len(), 1);
+        assert!(ideas[0].tags.is_empty());
+        assert_eq!(
+            ideas[0].description,
+            "Run this:\n```sh\n## not a heading\ntags: not, tags\n```\n~~~\n# nor this\n~~~\nafter"
+        );
+    }
+
+    #[test]
+    fn test_an_unclosed_code_block_runs_to_the_end() {
+        let ideas = parse_markdown("## Open\n```\n## Swallowed\n");
+        assert_eq!(ideas.len(), 1);
+        assert_eq!(ideas[0].description, "```\n## Swallowed");
+    }
+
+    #[test]
+    fn test_nested_headings() {
+        let ideas = parse_markdown(
+            "## Parent\n### Background\ntext\n#### Things that must be true\n- a\n  continued\n- b\nnot a bullet\n\
+             ### Open questions\n- stays in the description\n# Archive\n## \n",
+        );
+        assert_eq!(ideas.len(), 2);
+        assert_eq!(ideas[0].what_must_be_true, vec!["a continued", "b"]);
+        assert_eq!(
+            ideas[0].description,
+            "### Background\ntext\nnot a bullet\n### Open questions\n- stays in the description"
+        );
+        assert_eq!(ideas[1].title, "");
+        assert_eq!(ideas[1].line, 12);
+    }
+
+    #[test]
+    fn test_notes_section() {
+        let ideas = parse_markdown(
+            "## Idea\nabout it\n### Notes\nfirst\n\n### Sub-heading in the notes\ntags: still notes\n- [x] done\n\
+             ```\n## fenced\n```\n## Next\n",
+        );
+        assert_eq!(ideas.len(), 2);
+        assert_eq!(ideas[0].description, "about it");
+        assert!(ideas[0].tags.is_empty());
+        assert_eq!(
+            ideas[0].development_notes,
+            "first\n\n### Sub-heading in the notes\ntags: still notes\n- [x] done\n```\n## fenced\n```"
+        );
+    }
+
+    #[test]
+    fn test_checklist_boxes_are_dropped() {
+        let ideas = parse_markdown("## Idea\n### Must be true\n- [ ] open\n* [x] done\n- [link](url) stays");
+        assert_eq!(ideas[0].what_must_be_true, vec!["open", "done", "[link](url) stays"]);
+    }
+
+    #[test]
+    fn test_idea_to_markdown() {
+        let idea = Idea {
+            title: "Seed library ".to_string(),
+            description: "Swap seeds.\n\nAt the library.".to_string(),
+            tags: vec!["garden".to_string(), "community".to_string()],
+            what_must_be_true: vec!["People save seeds".to_string(), " ".to_string()],
+            development_notes: "Ask the librarian.\n".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            idea_to_markdown(&idea),
+            "## Seed library\n\nSwap seeds.\n\nAt the library.\n\ntags: garden, community\n\n\
+             ### What must be true\n- [ ] People save seeds\n\n### Notes\nAsk the librarian."
+        );
+
+        let bare = Idea {
+            title: "Bare".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(idea_to_markdown(&bare), "## Bare");
+    }
+
+    #[test]
+    fn test_copied_ideas_import_unchanged() {
+        let idea = Idea {
+            title: "Bike repair café".to_string(),
+            description: "Monthly meetup.\n\nFree coffee.".to_string(),
+            tags: vec!["community".to_string(), "bikes".to_string()],
+            what_must_be_true: vec!["People own bikes".to_string(), "Someone lends a space".to_string()],
+            development_notes: "Ask the library.\n\n### Costs\nTools, mostly.".to_string(),
+            ..Default::default()
+        };
+        let parsed = parse_markdown(&idea_to_markdown(&idea)).remove(0).into_idea();
+        assert_eq!(parsed, idea);
+    }
+
+    #[test]
+    fn test_heading_markers() {
+        assert_eq!(heading("## Title ##"), Some((2, "Title")));
+        assert_eq!(heading("   ### Deep"), Some((3, "Deep")));
+        assert_eq!(heading("## C#"), Some((2, "C#")));
+        assert_eq!(heading("##"), Some((2, "")));
+        assert_eq!(heading("##Title"), None);
+        assert_eq!(heading("    ## indented code"), None);
+        assert_eq!(heading("####### seven"), None);
+        assert_eq!(heading("C# is a language"), None);
+    }
+
+    #[test]
+    fn test_nothing_to_import() {
+        assert!(parse_markdown("").is_empty());
+        assert!(parse_markdown("# Only a title\n\nsome text\n- a list").is_empty());
+    }
+
+    #[test]
+    fn test_check() {
+        let ideas = parse_markdown("## Fine\n##   \n");
+        assert!(ideas[0].check().is_ok());
+        assert_eq!(ideas[1].check(), Err("line 2: the heading has no title".to_string()));
+    }
+
+    #[test]
+    fn test_into_idea() {
+        let idea = parse_markdown("## Title\ntext\ntags: x\n### must be true\n- y").remove(0).into_idea();
+        assert_eq!(idea.title, "Title");
+        assert_eq!(idea.description, "text");
+        assert_eq!(idea.tags, vec!["x"]);
+        assert_eq!(idea.what_must_be_true, vec!["y"]);
+        assert_eq!(idea.id, None);
+    }
+
+    #[test]
+    fn test_notes_split_into_sections_and_back() {
+        let notes = "Why now.\n\n## Problem\nBikes rust.\n\n\n```\n## fenced, not a heading\n```\n\n## Hypothesis\n\n\
+                     ## Experiment\nOne meetup.";
+        let sections = split_note_sections(notes);
+        let headings: Vec<&str> = sections.iter().map(|section| section.heading.as_str()).collect();
+        assert_eq!(headings, vec!["", "Problem", "Hypothesis", "Experiment"]);
+        assert_eq!(sections[0].body, "Why now.");
+        assert_eq!(sections[1].body, "Bikes rust.\n\n\n```\n## fenced, not a heading\n```");
+        assert_eq!(sections[2].body, "");
+        assert_eq!(join_note_sections(&sections), notes);
+        assert_eq!(split_note_sections(&join_note_sections(&sections)), sections);
+
+        // Other heading levels are kept as section headings, and come back as `##`
+        let sections = split_note_sections("### Costs ###\nTools\n# Plan");
+        assert_eq!(
+            sections[0],
+            NoteSection {
+                heading: "Costs".to_string(),
+                body: "Tools".to_string(),
+            }
+        );
+        assert_eq!(join_note_sections(&sections), "## Costs\nTools\n\n## Plan");
+
+        assert!(split_note_sections(" \n\n").is_empty());
+        assert_eq!(split_note_sections("no headings")[0].heading, "");
+    }
+
+    #[test]
+    fn test_sections_survive_joining() {
+        let sections: Vec<NoteSection> = STARTER_SECTIONS
+            .iter()
+            .map(|heading| NoteSection {
+                heading: heading.to_string(),
+                body: format!("About the {}.\n\n- a\n- b", heading),
+            })
+            .collect();
+        assert_eq!(split_note_sections(&join_note_sections(&sections)), sections);
+
+        let blank = vec![
+            NoteSection::default(),
+            NoteSection {
+                heading: "Only".to_string(),
+                body: String::new(),
+            },
+        ];
+        assert_eq!(join_note_sections(&blank), "## Only");
+    }
+
+    #[test]
+    fn test_markdown_includes_sections() {
+        let idea = Idea {
+            title: "Seed library".to_string(),
+            development_notes: "Loose notes.".to_string(),
+            sections: vec![NoteSection {
+                heading: "Problem".to_string(),
+                body: "Seeds get lost.".to_string(),
+            }],
+            ..Default::default()
+        };
+        assert_eq!(
+            idea_to_markdown(&idea),
+            "## Seed library\n\n### Notes\nLoose notes.\n\n#### Problem\nSeeds get lost."
+        );
+    }
+}
@@ -0,0 +1,266 @@
+//! The JSON export of every idea, and bringing exports written by older versions of the app up to date.
+//!
+//! An export is `{ "version": 2, "ideas": [...] }`, each idea shaped like [`Idea`]. The first exports, version 1,
+//! were a bare list of ideas from before statements and notes existed, with ids in whatever shape the database
+//! library printed at the time. [`read_export`] reads every version from [`OLDEST_EXPORT_VERSION`] to
+//! [`EXPORT_VERSION`] by running it through the steps in [`MIGRATIONS`], one per version, and refuses newer exports
+//! rather than guess at fields it doesn't know. A change to the export shape bumps [`EXPORT_VERSION`] and adds a
+//! step here.
+
+use crate::db::{Idea, IdeaId, IDEA_TABLE};
+use crate::error::IdeaError;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// Version of the export written by this build
+pub const EXPORT_VERSION: u64 = 2;
+
+/// Oldest version [`read_export`] still reads
+pub const OLDEST_EXPORT_VERSION: u64 = 1;
+
+/// One step bringing the ideas of an export up by one version
+pub type Migration = fn(Vec<Value>) -> Result<Vec<Value>, IdeaError>;
+
+/// The step from each version to the next, starting at [`OLDEST_EXPORT_VERSION`]
+pub const MIGRATIONS: [Migration; 1] = [v1_to_v2];
+
+/// Every idea of a user, as written by this build
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IdeaExport {
+    pub version: u64,
+    pub ideas: Vec<Idea>,
+}
+
+impl IdeaExport {
+    pub fn new(ideas: Vec<Idea>) -> Self {
+        Self {
+            version: EXPORT_VERSION,
+            ideas,
+        }
+    }
+}
+
+/// Read an export of any supported version, checking the version before anything else
+pub fn read_export(text: &str) -> Result<Vec<Idea>, IdeaError> {
+    let value: Value = serde_json::from_str(text)
+        .map_err(|e| IdeaError::Validation(format!("The export isn't valid JSON: {}", e)))?;
+    let (version, ideas) = match value {
+        // Version 1 had no envelope
+        Value::Array(ideas) => (OLDEST_EXPORT_VERSION, ideas),
+        Value::Object(mut fields) => {
+            let version = fields.get("version").and_then(Value::as_u64).ok_or_else(|| {
+                IdeaError::Validation("This isn't an idea export: it has no version".to_string())
+            })?;
+            if !(OLDEST_EXPORT_VERSION..=EXPORT_VERSION).contains(&version) {
+                return Err(IdeaError::Validation(format!(
+                    "The export has version {}, but this app reads versions {} to {}",
+                    version, OLDEST_EXPORT_VERSION, EXPORT_VERSION
+                )));
+            }
+            match fields.remove("ideas") {
+                Some(Value::Array(ideas)) => (version, ideas),
+                _ => return Err(IdeaError::Validation("The export has no list of ideas".to_string())),
+            }
+        }
+        _ => {
+            let message = "This isn't an idea export: expected a list of ideas or an object with a version";
+            return Err(IdeaError::Validation(message.to_string()));
+        }
+    };
+
+    let steps = &MIGRATIONS[(version - OLDEST_EXPORT_VERSION) as usize..];
+    let ideas = steps.iter().try_fold(ideas, |ideas, step| step(ideas))?;
+    ideas
+        .into_iter()
+        .enumerate()
+        .map(|(index, idea)| {
+            serde_json::from_value(idea)
+                .map_err(|e| IdeaError::Validation(format!("Idea {} of the export couldn't be read: {}", index, e)))
+        })
+        .collect()
+}
+
+/// Version 1 to 2: ids become the plain `ideas:<key>` of [`IdeaId`], and the fields version 1 could leave out or
+/// leave `null` get their empty values
+pub fn v1_to_v2(ideas: Vec<Value>) -> Result<Vec<Value>, IdeaError> {
+    ideas
+        .into_iter()
+        .enumerate()
+        .map(|(index, idea)| {
+            let Value::Object(mut fields) = idea else {
+                return Err(IdeaError::Validation(format!("Idea {} of the export isn't an object", index)));
+            };
+            match fields.remove("id").map_or(Ok(None), v1_id) {
+                Ok(Some(id)) => {
+                    fields.insert("id".to_string(), id.to_string().into());
+                }
+                Ok(None) => {}
+                Err(id) => {
+                    let message = format!("Idea {} of the export has an id that isn't an idea id: {}", index, id);
+                    return Err(IdeaError::Validation(message));
+                }
+            }
+            for (field, empty) in [
+                ("description", Value::String(String::new())),
+                ("tags", Value::Array(Vec::new())),
+                ("what_must_be_true", Value::Array(Vec::new())),
+                ("development_notes", Value::String(String::new())),
+            ] {
+                default_field(&mut fields, field, empty);
+            }
+            Ok(Value::Object(fields))
+        })
+        .collect()
+}
+
+/// `empty` for `field` when it is missing or `null`
+fn default_field(fields: &mut Map<String, Value>, field: &str, empty: Value) {
+    if fields.get(field).is_none_or(Value::is_null) {
+        fields.insert(field.to_string(), empty);
+    }
+}
+
+/// The id of a version 1 idea: a string [`IdeaId::parse`] reads, or the `{ "tb": .., "id": { "String": .. } }`
+/// object the database library serialized record ids as. `None` for an idea stored without one, and the id back
+/// when it is neither.
+fn v1_id(id: Value) -> Result<Option<IdeaId>, Value> {
+    let text = match &id {
+        Value::Null => return Ok(None),
+        Value::String(text) => text.clone(),
+        Value::Object(thing) => {
+            let table = thing.get("tb").and_then(Value::as_str).unwrap_or(IDEA_TABLE);
+            match thing.get("id") {
+                Some(Value::String(key)) => format!("{}:{}", table, key),
+                Some(Value::Object(key)) => match key.get("String").and_then(Value::as_str) {
+                    Some(key) => format!("{}:{}", table, key),
+                    None => return Err(id),
+                },
+                _ => return Err(id),
+            }
+        }
+        _ => return Err(id),
+    };
+    IdeaId::parse(&text).map(Some).map_err(|_| id)
+}
+
+// Server-only export and import
+#[cfg(feature = "server")]
+pub mod server {
+    use super::*;
+    use crate::db::server::{restore_ideas, IdeaRepository, MAX_BATCH_IDEAS};
+    use crate::markdown::ImportSummary;
+    use std::collections::HashSet;
+    use surrealdb::sql::Thing;
+
+    /// Every active idea `owner` sees in `repo`, as an export of the current version
+    pub async fn export_ideas(repo: &impl IdeaRepository, owner: Option<&Thing>) -> Result<IdeaExport, IdeaError> {
+        let ideas = repo.list(owner).await?.into_iter().map(Idea::from).collect();
+        Ok(IdeaExport::new(ideas))
+    }
+
+    /// Store `ideas` read by [`read_export`] for `owner`, each under the id it had when exported. Ideas whose id is
+    /// already taken here, by whoever, are left out and listed, so importing the same export twice adds nothing.
+    pub async fn import_ideas(
+        repo: &impl IdeaRepository,
+        owner: Option<Thing>,
+        ideas: Vec<Idea>,
+    ) -> Result<ImportSummary, IdeaError> {
+        let mut fresh = Vec::with_capacity(ideas.len());
+        let mut skipped = Vec::new();
+        let mut seen = HashSet::new();
+        for idea in ideas {
+            if let Some(id) = &idea.id {
+                let taken = match repo.get(id).await {
+                    Ok(_) => true,
+                    Err(IdeaError::NotFound(_)) => !seen.insert(id.clone()),
+                    Err(e) => return Err(e),
+                };
+                if taken {
+                    skipped.push(format!("\"{}\" is already here as {}", idea.title, id));
+                    continue;
+                }
+            }
+            fresh.push(idea);
+        }
+
+        let mut imported = 0;
+        for batch in fresh.chunks(MAX_BATCH_IDEAS) {
+            imported += restore_ideas(repo, owner.clone(), batch.to_vec()).await?.len();
+        }
+        Ok(ImportSummary { imported, skipped })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const V1_EXPORT: &str = include_str!("../tests/fixtures/v1_export.json");
+    const V1_EXPORT_MINIMAL: &str = include_str!("../tests/fixtures/v1_export_minimal.json");
+
+    fn message(result: Result<Vec<Idea>, IdeaError>) -> String {
+        match result {
+            Err(IdeaError::Validation(message)) => message,
+            other => panic!("expected a validation error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_a_step_per_version() {
+        assert_eq!(MIGRATIONS.len() as u64, EXPORT_VERSION - OLDEST_EXPORT_VERSION);
+    }
+
+    #[test]
+    fn test_v1_fixture_is_migrated() {
+        let ideas = read_export(V1_EXPORT).unwrap();
+        let ids: Vec<Option<&str>> = ideas.iter().map(|idea| idea.id.as_deref()).collect();
+        assert_eq!(
+            ids,
+            [Some("ideas:bike-repair"), Some("ideas:seed-library"), Some("ideas:rooftop"), None]
+        );
+
+        assert_eq!(ideas[0].title, "Bike repair café");
+        assert_eq!(ideas[0].tags, ["community", "bikes"]);
+        assert!(ideas[0].pinned);
+        assert!(ideas[0].what_must_be_true.is_empty());
+        assert_eq!(ideas[1].development_notes, "Ask the librarian about shelf space");
+        assert_eq!(ideas[2].description, "");
+        assert!(ideas[2].tags.is_empty());
+        assert!(ideas[3].tags.is_empty());
+    }
+
+    #[test]
+    fn test_minimal_v1_fixture_is_migrated() {
+        let ideas = read_export(V1_EXPORT_MINIMAL).unwrap();
+        assert_eq!(ideas.len(), 1);
+        assert_eq!(ideas[0].id.as_deref(), Some("ideas:old-one"));
+        assert_eq!(ideas[0].title, "Written before tags");
+        assert_eq!(ideas[0].description, "");
+    }
+
+    #[test]
+    fn test_current_exports_round_trip() {
+        let export = IdeaExport::new(read_export(V1_EXPORT).unwrap());
+        let text = serde_json::to_string(&export).unwrap();
+        assert!(text.starts_with("{\"version\":2,"));
+        assert_eq!(read_export(&text).unwrap(), export.ideas);
+    }
+
+    #[test]
+    fn test_future_versions_are_refused_with_the_supported_range() {
+        let text = r#"{"version": 3, "ideas": [{"title": "From the future", "mood": "🙂"}]}"#;
+        assert_eq!(message(read_export(text)), "The export has version 3, but this app reads versions 1 to 2");
+        assert!(message(read_export(r#"{"version": 0, "ideas": []}"#)).contains("versions 1 to 2"));
+    }
+
+    #[test]
+    fn test_malformed_exports_are_refused() {
+        for text in ["", "not json", "5", "{}", r#"{"version": "2", "ideas": []}"#, r#"{"version": 2}"#] {
+            assert!(matches!(read_export(text), Err(IdeaError::Validation(_))), "{}", text);
+        }
+        assert!(message(read_export(r#"[{"id": "users:sam", "title": "Wrong table"}]"#)).contains("Idea 0"));
+        assert!(message(read_export(r#"[{"title": "Fine"}, 5]"#)).contains("Idea 1"));
+        // Version 2 has no defaults to fill in
+        assert!(message(read_export(r#"{"version": 2, "ideas": [{"title": "No tags"}]}"#)).contains("Idea 0"));
+    }
+}
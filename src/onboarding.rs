@@ -0,0 +1,97 @@
+//! The welcome panel shown on an empty list: what the tour says, when it shows and the example ideas it can create.
+//!
+//! Dismissing the tour is remembered in client storage, so it stays gone on this device even while the list is
+//! empty. Creating the examples goes through `create_ideas_batch_server()` like an import.
+
+use crate::db::{Idea, IdeaStatus};
+use crate::storage::Storage;
+
+/// Storage key set once the tour was dismissed
+pub const TOUR_DISMISSED_KEY: &str = "tour_dismissed";
+
+/// Whether the tour was dismissed on this device
+pub fn load_tour_dismissed(storage: &dyn Storage) -> bool {
+    storage.get_raw(TOUR_DISMISSED_KEY).is_some()
+}
+
+/// Remember that the tour was dismissed
+pub fn save_tour_dismissed(storage: &dyn Storage) {
+    storage.set_raw(TOUR_DISMISSED_KEY, "1");
+}
+
+/// Whether to show the tour, given the number of ideas outside the trash (`None` while it is loading or failed to
+/// load) and whether it was dismissed. Only a list known to be empty gets it, so it never flashes up while loading.
+pub fn should_show_tour(total: Option<usize>, dismissed: bool) -> bool {
+    total == Some(0) && !dismissed
+}
+
+/// Three ideas that walk through the workflow: one just jotted down, one being developed with its assumptions
+/// listed, and one with notes on how it is being built
+pub fn example_ideas() -> Vec<Idea> {
+    vec![
+        Idea {
+            title: "Neighbourhood tool library".to_string(),
+            description: "Lend drills, ladders and the like to neighbours instead of everyone buying their own."
+                .to_string(),
+            tags: vec!["example".to_string(), "community".to_string()],
+            status: IdeaStatus::Inbox,
+            ..Default::default()
+        },
+        Idea {
+            title: "Weekly meal planner".to_string(),
+            description: "Plan the week's dinners on Sunday and get the shopping list for free.".to_string(),
+            tags: vec!["example".to_string(), "home".to_string()],
+            what_must_be_true: vec![
+                "Planning takes less time than deciding every evening".to_string(),
+                "The household agrees to the plan".to_string(),
+            ],
+            status: IdeaStatus::Exploring,
+            ..Default::default()
+        },
+        Idea {
+            title: "Reading log".to_string(),
+            description: "Keep a line about every book finished, to look back on at the end of the year.".to_string(),
+            tags: vec!["example".to_string(), "learning".to_string()],
+            what_must_be_true: vec!["A line per book is little enough to keep up".to_string()],
+            development_notes: "Started with a plain text file; moving it into a spreadsheet once there are ten \
+                                entries."
+                .to_string(),
+            status: IdeaStatus::Building,
+            ..Default::default()
+        },
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::MemoryStorage;
+
+    #[test]
+    fn test_dismissal_is_remembered() {
+        let storage = MemoryStorage::new();
+        assert!(!load_tour_dismissed(&storage));
+        save_tour_dismissed(&storage);
+        assert!(load_tour_dismissed(&storage));
+    }
+
+    #[test]
+    fn test_tour_only_shows_on_a_list_known_to_be_empty() {
+        assert!(should_show_tour(Some(0), false));
+        assert!(!should_show_tour(Some(0), true));
+        assert!(!should_show_tour(Some(3), false));
+        // Still loading, or the count failed
+        assert!(!should_show_tour(None, false));
+    }
+
+    #[test]
+    fn test_examples_cover_the_workflow() {
+        let ideas = example_ideas();
+        assert_eq!(ideas.len(), 3);
+        assert!(ideas.iter().all(|idea| !idea.title.trim().is_empty() && idea.id.is_none()));
+        assert!(ideas.iter().any(|idea| !idea.what_must_be_true.is_empty()));
+        assert!(ideas.iter().any(|idea| !idea.development_notes.is_empty()));
+        let statuses: Vec<IdeaStatus> = ideas.iter().map(|idea| idea.status).collect();
+        assert_eq!(statuses, vec![IdeaStatus::Inbox, IdeaStatus::Exploring, IdeaStatus::Building]);
+    }
+}
@@ -0,0 +1,449 @@
+//! Abuse protection for the mutating server functions.
+//!
+//! Every write is charged against a token bucket keyed by client IP, and request bodies are capped by a layer before
+//! anything reads them. The limiter itself is plain data driven by an injected [`Clock`], so it can be tested
+//! without sleeping; the server wires a process-wide instance up in [`server::guard_write`] and
+//! [`server::with_body_limit`].
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Default number of writes a single client may make per window
+pub const DEFAULT_WRITES_PER_WINDOW: u32 = 10;
+
+/// Default length of the refill window
+pub const DEFAULT_WINDOW: Duration = Duration::from_secs(60);
+
+/// Default cap on the size of a request body
+pub const DEFAULT_MAX_PAYLOAD_BYTES: usize = 64 * 1024;
+
+/// Default cap on the body of a [`BULK_ROUTES`] request
+pub const DEFAULT_MAX_BULK_BYTES: usize = 32 * 1024 * 1024;
+
+/// Routes that take whole collections, dumps or files, and get [`RateLimitConfig::max_bulk_bytes`]
+pub const BULK_ROUTES: &[&str] = &[
+    "/api/ideas/batch",
+    "/api/ideas/import-markdown",
+    "/api/ideas/bundle/import",
+    "/api/ideas/import",
+    "/api/admin/restore",
+    "/api/attachments/upload",
+];
+
+/// Route client error reports are sent to
+pub const REPORT_ROUTE: &str = "/api/client-errors";
+
+/// Largest accepted client error report, in bytes; the client clips its reports well below this
+pub const MAX_REPORT_BYTES: usize = 16 * 1024;
+
+/// Number of tracked clients above which idle buckets are dropped
+const PRUNE_THRESHOLD: usize = 10_000;
+
+/// Source of the current time, as a duration since an arbitrary fixed point
+pub trait Clock: Send + Sync {
+    fn now(&self) -> Duration;
+}
+
+/// Monotonic wall clock used in production
+#[derive(Debug)]
+pub struct SystemClock {
+    start: std::time::Instant,
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self {
+            start: std::time::Instant::now(),
+        }
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+/// Limits applied to writes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimitConfig {
+    /// Burst size, and how many writes are refilled per window
+    pub writes_per_window: u32,
+    /// Time it takes for an empty bucket to refill completely
+    pub window: Duration,
+    /// Largest accepted request body, in bytes
+    pub max_payload_bytes: usize,
+    /// Largest accepted body of a [`BULK_ROUTES`] request, in bytes
+    pub max_bulk_bytes: usize,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            writes_per_window: DEFAULT_WRITES_PER_WINDOW,
+            window: DEFAULT_WINDOW,
+            max_payload_bytes: DEFAULT_MAX_PAYLOAD_BYTES,
+            max_bulk_bytes: DEFAULT_MAX_BULK_BYTES,
+        }
+    }
+}
+
+impl RateLimitConfig {
+    /// Read the limits from `RATE_LIMIT_WRITES`, `RATE_LIMIT_WINDOW_SECS`, `RATE_LIMIT_MAX_PAYLOAD_BYTES` and
+    /// `RATE_LIMIT_MAX_BULK_BYTES`, keeping the default for anything unset or unparsable
+    pub fn from_env() -> Self {
+        Self::from_lookup(|key| std::env::var(key).ok())
+    }
+
+    /// Same as [`RateLimitConfig::from_env`] with a custom variable lookup
+    pub fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Self {
+        let defaults = Self::default();
+        let parse = |key: &str| lookup(key).and_then(|value| value.trim().parse::<u64>().ok());
+
+        Self {
+            writes_per_window: parse("RATE_LIMIT_WRITES")
+                .filter(|&n| n > 0)
+                .map(|n| n.min(u32::MAX as u64) as u32)
+                .unwrap_or(defaults.writes_per_window),
+            window: parse("RATE_LIMIT_WINDOW_SECS")
+                .filter(|&secs| secs > 0)
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.window),
+            max_payload_bytes: parse("RATE_LIMIT_MAX_PAYLOAD_BYTES")
+                .filter(|&n| n > 0)
+                .map(|n| n as usize)
+                .unwrap_or(defaults.max_payload_bytes),
+            max_bulk_bytes: parse("RATE_LIMIT_MAX_BULK_BYTES")
+                .filter(|&n| n > 0)
+                .map(|n| n as usize)
+                .unwrap_or(defaults.max_bulk_bytes),
+        }
+    }
+
+    /// Largest body accepted by the route at `path`
+    pub fn body_limit(&self, path: &str) -> usize {
+        if BULK_ROUTES.contains(&path) {
+            self.max_bulk_bytes
+        } else if path == REPORT_ROUTE {
+            MAX_REPORT_BYTES.min(self.max_payload_bytes)
+        } else {
+            self.max_payload_bytes
+        }
+    }
+}
+
+/// Why a write was refused
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Rejection {
+    /// The client is out of tokens; one will be available after this long
+    TooManyWrites { retry_after: Duration },
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    updated: Duration,
+}
+
+/// Token-bucket rate limiter keyed by an arbitrary client identifier
+pub struct RateLimiter<C: Clock> {
+    config: RateLimitConfig,
+    clock: C,
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+impl<C: Clock> RateLimiter<C> {
+    pub fn new(config: RateLimitConfig, clock: C) -> Self {
+        Self {
+            config,
+            clock,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The limits it was created with
+    pub fn config(&self) -> &RateLimitConfig {
+        &self.config
+    }
+
+    /// Tokens regained per second
+    fn refill_rate(&self) -> f64 {
+        self.config.writes_per_window as f64 / self.config.window.as_secs_f64()
+    }
+
+    /// Charge one write to `key`
+    pub fn check(&self, key: &str) -> Result<(), Rejection> {
+        let now = self.clock.now();
+        let capacity = self.config.writes_per_window as f64;
+        let rate = self.refill_rate();
+
+        let Ok(mut buckets) = self.buckets.lock() else {
+            // A poisoned lock only means another request panicked; don't lock everyone out over it
+            return Ok(());
+        };
+
+        if buckets.len() >= PRUNE_THRESHOLD {
+            // Buckets that have refilled completely carry no state worth keeping
+            buckets.retain(|_, bucket| {
+                bucket.tokens + now.saturating_sub(bucket.updated).as_secs_f64() * rate < capacity
+            });
+        }
+
+        let bucket = buckets.entry(key.to_string()).or_insert(Bucket {
+            tokens: capacity,
+            updated: now,
+        });
+
+        let elapsed = now.saturating_sub(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * rate).min(capacity);
+        bucket.updated = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            let retry_after = Duration::from_secs_f64((1.0 - bucket.tokens) / rate);
+            Err(Rejection::TooManyWrites { retry_after })
+        }
+    }
+}
+
+#[cfg(feature = "server")]
+pub mod server {
+    use super::*;
+    use crate::error::IdeaError;
+    use axum::body::Body;
+    use axum::extract::{DefaultBodyLimit, Request, State};
+    use axum::http::header;
+    use axum::middleware::Next;
+    use axum::response::{IntoResponse, Response};
+    use axum::Router;
+    use std::sync::OnceLock;
+
+    /// The limiter shared by every server function, configured from the environment on first use
+    pub fn limiter() -> &'static RateLimiter<SystemClock> {
+        static LIMITER: OnceLock<RateLimiter<SystemClock>> = OnceLock::new();
+        LIMITER.get_or_init(|| RateLimiter::new(RateLimitConfig::from_env(), SystemClock::default()))
+    }
+
+    impl From<Rejection> for IdeaError {
+        fn from(rejection: Rejection) -> Self {
+            match rejection {
+                Rejection::TooManyWrites { retry_after } => {
+                    // Round up so "try again in 0s" never shows
+                    IdeaError::RateLimited(retry_after.as_secs_f64().ceil().max(1.0) as u64)
+                }
+            }
+        }
+    }
+
+    /// Guard for mutating server functions: charges the calling client one write. Their bodies were already
+    /// capped by [`with_body_limit`].
+    pub fn guard_write() -> Result<(), IdeaError> {
+        guard_write_for(crate::request::client_ip())
+    }
+
+    /// [`guard_write`] for a request handled outside a server function, whose client is already known
+    pub fn guard_write_for(client: Option<String>) -> Result<(), IdeaError> {
+        let client = client.unwrap_or_else(|| "unknown".to_string());
+        limiter().check(&client)?;
+        Ok(())
+    }
+
+    /// How a layer answers a request it refuses, in the error format of the routes it sits in front of
+    pub type Refusal = fn(IdeaError) -> Response;
+
+    /// Cap the request bodies of `router` at [`RateLimitConfig::body_limit`] for their route, answering bigger ones
+    /// with [`IdeaError::PayloadTooLarge`] through `refuse` before anything reads them.
+    pub fn with_body_limit(router: Router, refuse: Refusal) -> Router {
+        // The layer enforces the limits, so the extractors behind it mustn't stop at their own default
+        router
+            .layer(axum::middleware::from_fn_with_state(refuse, limit_body))
+            .layer(DefaultBodyLimit::disable())
+    }
+
+    async fn limit_body(State(refuse): State<Refusal>, request: Request, next: Next) -> Response {
+        let limit = limiter().config().body_limit(request.uri().path());
+        let declared = request
+            .headers()
+            .get(header::CONTENT_LENGTH)
+            .and_then(|length| length.to_str().ok()?.parse::<usize>().ok());
+        if declared.is_some_and(|length| length > limit) {
+            return refuse(IdeaError::PayloadTooLarge(limit));
+        }
+        // A body without a length, or longer than it said, is cut off at the limit while it is read
+        let (parts, body) = request.into_parts();
+        match axum::body::to_bytes(body, limit).await {
+            Ok(bytes) => next.run(Request::from_parts(parts, Body::from(bytes))).await,
+            Err(_) => refuse(IdeaError::PayloadTooLarge(limit)),
+        }
+    }
+
+    /// [`Refusal`] for server functions: `error` with its status, in the body the fullstack client reads server
+    /// function errors from
+    pub fn server_fn_refusal(error: IdeaError) -> Response {
+        let status = crate::rest::status_for(&error);
+        let body = serde_json::json!({ "message": error.encode(), "code": status.as_u16() });
+        (status, axum::Json(body)).into_response()
+    }
+
+    /// Client error reports a single client may send per window
+    const REPORTS_PER_WINDOW: u32 = 20;
+
+    /// Limiter for client error reports. Separate from [`limiter`], so a burst of failures doesn't use up the
+    /// writes the user has left.
+    fn report_limiter() -> &'static RateLimiter<SystemClock> {
+        static LIMITER: OnceLock<RateLimiter<SystemClock>> = OnceLock::new();
+        LIMITER.get_or_init(|| {
+            let config = RateLimitConfig {
+                writes_per_window: REPORTS_PER_WINDOW,
+                window: DEFAULT_WINDOW,
+                ..Default::default()
+            };
+            RateLimiter::new(config, SystemClock::default())
+        })
+    }
+
+    /// Guard for client error reports: charges the calling client one report. Their size is capped at
+    /// [`MAX_REPORT_BYTES`] by [`with_body_limit`].
+    pub fn guard_report() -> Result<(), IdeaError> {
+        let client = crate::request::client_ip().unwrap_or_else(|| "unknown".to_string());
+        report_limiter().check(&client)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    /// Clock that only moves when told to
+    #[derive(Clone, Default)]
+    struct ManualClock(Arc<Mutex<Duration>>);
+
+    impl ManualClock {
+        fn advance(&self, by: Duration) {
+            *self.0.lock().unwrap() += by;
+        }
+    }
+
+    impl Clock for ManualClock {
+        fn now(&self) -> Duration {
+            *self.0.lock().unwrap()
+        }
+    }
+
+    fn limiter(writes: u32, window_secs: u64) -> (RateLimiter<ManualClock>, ManualClock) {
+        let clock = ManualClock::default();
+        let config = RateLimitConfig {
+            writes_per_window: writes,
+            window: Duration::from_secs(window_secs),
+            ..Default::default()
+        };
+        (RateLimiter::new(config, clock.clone()), clock)
+    }
+
+    #[test]
+    fn test_allows_burst_then_rejects() {
+        let (limiter, _clock) = limiter(3, 60);
+
+        for _ in 0..3 {
+            assert!(limiter.check("1.2.3.4").is_ok());
+        }
+        assert_eq!(
+            limiter.check("1.2.3.4"),
+            Err(Rejection::TooManyWrites {
+                retry_after: Duration::from_secs(20)
+            })
+        );
+    }
+
+    #[test]
+    fn test_refills_over_time() {
+        let (limiter, clock) = limiter(3, 60);
+        for _ in 0..3 {
+            limiter.check("client").unwrap();
+        }
+        assert!(limiter.check("client").is_err());
+
+        clock.advance(Duration::from_secs(19));
+        assert!(limiter.check("client").is_err());
+
+        clock.advance(Duration::from_secs(1));
+        assert!(limiter.check("client").is_ok());
+        assert!(limiter.check("client").is_err());
+    }
+
+    #[test]
+    fn test_refill_is_capped_at_capacity() {
+        let (limiter, clock) = limiter(2, 60);
+        clock.advance(Duration::from_secs(3600));
+
+        assert!(limiter.check("client").is_ok());
+        assert!(limiter.check("client").is_ok());
+        assert!(limiter.check("client").is_err());
+    }
+
+    #[test]
+    fn test_clients_have_separate_buckets() {
+        let (limiter, _clock) = limiter(1, 60);
+
+        assert!(limiter.check("a").is_ok());
+        assert!(limiter.check("a").is_err());
+        assert!(limiter.check("b").is_ok());
+    }
+
+    #[test]
+    fn test_rejected_writes_do_not_extend_the_wait() {
+        let (limiter, clock) = limiter(1, 60);
+        limiter.check("client").unwrap();
+
+        // Hammering while empty must not push the next allowed write further out
+        for _ in 0..10 {
+            clock.advance(Duration::from_secs(5));
+            let _ = limiter.check("client");
+        }
+        clock.advance(Duration::from_secs(10));
+        assert!(limiter.check("client").is_ok());
+    }
+
+    #[test]
+    fn test_body_limit_by_route() {
+        let config = RateLimitConfig {
+            max_payload_bytes: 100,
+            max_bulk_bytes: 1000,
+            ..Default::default()
+        };
+
+        assert_eq!(config.body_limit("/api/ideas/update"), 100);
+        assert_eq!(config.body_limit("/api/v1/ideas"), 100);
+        assert_eq!(config.body_limit("/api/ideas/import"), 1000);
+        assert_eq!(config.body_limit("/api/attachments/upload"), 1000);
+        assert_eq!(config.body_limit(REPORT_ROUTE), 100);
+        assert_eq!(RateLimitConfig::default().body_limit(REPORT_ROUTE), MAX_REPORT_BYTES);
+    }
+
+    #[test]
+    fn test_config_from_lookup() {
+        let config = RateLimitConfig::from_lookup(|key| match key {
+            "RATE_LIMIT_WRITES" => Some("5".to_string()),
+            "RATE_LIMIT_WINDOW_SECS" => Some(" 30 ".to_string()),
+            "RATE_LIMIT_MAX_PAYLOAD_BYTES" => Some("not a number".to_string()),
+            "RATE_LIMIT_MAX_BULK_BYTES" => Some("1048576".to_string()),
+            _ => None,
+        });
+
+        assert_eq!(config.writes_per_window, 5);
+        assert_eq!(config.window, Duration::from_secs(30));
+        assert_eq!(config.max_payload_bytes, DEFAULT_MAX_PAYLOAD_BYTES);
+        assert_eq!(config.max_bulk_bytes, 1024 * 1024);
+    }
+
+    #[test]
+    fn test_config_ignores_zero() {
+        let config = RateLimitConfig::from_lookup(|_| Some("0".to_string()));
+        assert_eq!(config, RateLimitConfig::default());
+    }
+}
@@ -0,0 +1,176 @@
+//! Ideas the user opened recently, kept client-side so Home can offer a shortcut back to them.
+//!
+//! [`RecentIdeas`] is plain data; callers load it from and save it to [`crate::storage`] around each change.
+
+use crate::storage::Storage;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Storage key for the recently viewed list
+pub const RECENT_IDEAS_KEY: &str = "recent_ideas";
+
+/// Number of ideas remembered
+pub const MAX_RECENT_IDEAS: usize = 10;
+
+/// One visit to an idea
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecentVisit {
+    pub id: String,
+    pub visited_at: DateTime<Utc>,
+}
+
+/// Recently viewed ideas, most recent first, without duplicates and capped at [`MAX_RECENT_IDEAS`]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RecentIdeas {
+    visits: Vec<RecentVisit>,
+}
+
+impl RecentIdeas {
+    /// Record a visit, moving the idea to the front if it was already listed
+    pub fn record(&mut self, id: &str, visited_at: DateTime<Utc>) {
+        self.visits.retain(|visit| visit.id != id);
+        self.visits.insert(
+            0,
+            RecentVisit {
+                id: id.to_string(),
+                visited_at,
+            },
+        );
+        self.visits.truncate(MAX_RECENT_IDEAS);
+    }
+
+    /// Forget an idea, e.g. one that no longer exists
+    pub fn remove(&mut self, id: &str) {
+        self.visits.retain(|visit| visit.id != id);
+    }
+
+    pub fn clear(&mut self) {
+        self.visits.clear();
+    }
+
+    /// Ids of the visited ideas, most recent first
+    pub fn ids(&self) -> Vec<String> {
+        self.visits.iter().map(|visit| visit.id.clone()).collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.visits.is_empty()
+    }
+
+    /// The persisted list, or an empty one when nothing (or something unreadable) is stored
+    pub fn load(storage: &dyn Storage) -> Self {
+        let mut recent: Self = storage
+            .get_raw(RECENT_IDEAS_KEY)
+            .and_then(|raw| serde_json::from_str(&raw).ok())
+            .unwrap_or_default();
+        // Stored data may come from an older version with different rules
+        recent.normalize();
+        recent
+    }
+
+    pub fn save(&self, storage: &dyn Storage) {
+        if let Ok(raw) = serde_json::to_string(self) {
+            storage.set_raw(RECENT_IDEAS_KEY, &raw);
+        }
+    }
+
+    /// Restore the invariants: newest first, one entry per idea, capped
+    fn normalize(&mut self) {
+        self.visits.sort_by_key(|visit| std::cmp::Reverse(visit.visited_at));
+        let mut seen = std::collections::HashSet::new();
+        self.visits.retain(|visit| seen.insert(visit.id.clone()));
+        self.visits.truncate(MAX_RECENT_IDEAS);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::MemoryStorage;
+    use chrono::TimeZone;
+
+    fn at(seconds: i64) -> DateTime<Utc> {
+        Utc.timestamp_opt(seconds, 0).unwrap()
+    }
+
+    #[test]
+    fn test_most_recent_first() {
+        let mut recent = RecentIdeas::default();
+        recent.record("ideas:a", at(1));
+        recent.record("ideas:b", at(2));
+        recent.record("ideas:c", at(3));
+
+        assert_eq!(recent.ids(), vec!["ideas:c", "ideas:b", "ideas:a"]);
+    }
+
+    #[test]
+    fn test_revisit_moves_to_front_without_duplicating() {
+        let mut recent = RecentIdeas::default();
+        recent.record("ideas:a", at(1));
+        recent.record("ideas:b", at(2));
+        recent.record("ideas:a", at(3));
+
+        assert_eq!(recent.ids(), vec!["ideas:a", "ideas:b"]);
+        assert_eq!(recent.visits[0].visited_at, at(3));
+    }
+
+    #[test]
+    fn test_capped_at_maximum() {
+        let mut recent = RecentIdeas::default();
+        for n in 0..15 {
+            recent.record(&format!("ideas:{}", n), at(n));
+        }
+
+        assert_eq!(recent.ids().len(), MAX_RECENT_IDEAS);
+        assert_eq!(recent.ids()[0], "ideas:14");
+        assert_eq!(recent.ids()[MAX_RECENT_IDEAS - 1], "ideas:5");
+    }
+
+    #[test]
+    fn test_remove_and_clear() {
+        let mut recent = RecentIdeas::default();
+        recent.record("ideas:a", at(1));
+        recent.record("ideas:b", at(2));
+
+        recent.remove("ideas:a");
+        assert_eq!(recent.ids(), vec!["ideas:b"]);
+
+        recent.clear();
+        assert!(recent.is_empty());
+    }
+
+    #[test]
+    fn test_load_and_save() {
+        let storage = MemoryStorage::new();
+        assert!(RecentIdeas::load(&storage).is_empty());
+
+        let mut recent = RecentIdeas::default();
+        recent.record("ideas:a", at(1));
+        recent.save(&storage);
+        assert_eq!(RecentIdeas::load(&storage), recent);
+
+        storage.set_raw(RECENT_IDEAS_KEY, "[not json");
+        assert!(RecentIdeas::load(&storage).is_empty());
+    }
+
+    #[test]
+    fn test_load_repairs_stored_list() {
+        let storage = MemoryStorage::new();
+        let visits: Vec<RecentVisit> = (0..12)
+            .map(|n| RecentVisit {
+                id: format!("ideas:{}", n % 11),
+                visited_at: at(n),
+            })
+            .collect();
+        storage.set_raw(
+            RECENT_IDEAS_KEY,
+            &serde_json::to_string(&RecentIdeas { visits }).unwrap(),
+        );
+
+        let recent = RecentIdeas::load(&storage);
+        assert_eq!(recent.ids().len(), MAX_RECENT_IDEAS);
+        // "ideas:0" was visited at 0 and again at 11, so it is the most recent
+        assert_eq!(recent.ids()[0], "ideas:0");
+        assert_eq!(recent.ids()[1], "ideas:10");
+    }
+}
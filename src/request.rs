@@ -0,0 +1,212 @@
+//! Access to the HTTP request behind the current server function call.
+//!
+//! All reads of headers and cookies and all response header writes go through here, so server functions never
+//! touch the fullstack context directly. The `*_from` variants work on a plain [`HeaderMap`] for the REST routes,
+//! which run outside that context.
+
+use axum::extract::ConnectInfo;
+use axum::Extension;
+use dioxus::fullstack::FullstackContext;
+use http::{header, HeaderMap, HeaderName, HeaderValue};
+use std::net::{IpAddr, SocketAddr};
+use std::sync::OnceLock;
+
+/// Read a request header as a string
+pub fn header(name: HeaderName) -> Option<String> {
+    let context = FullstackContext::current()?;
+    let parts = context.parts_mut();
+    parts
+        .headers
+        .get(name)?
+        .to_str()
+        .ok()
+        .map(str::to_string)
+}
+
+/// A value the server attached to the request, e.g. with an Axum `Extension` layer
+pub fn extension<T: Clone + Send + Sync + 'static>() -> Option<T> {
+    let context = FullstackContext::current()?;
+    let parts = context.parts_mut();
+    parts.extensions.get::<T>().cloned()
+}
+
+/// Comma-separated addresses of the reverse proxies in front of the server. Forwarding headers are only believed on
+/// connections from one of them.
+pub const TRUSTED_PROXIES_VAR: &str = "TRUSTED_PROXIES";
+
+/// Address of the client, for per-client limits.
+///
+/// This is the address the connection came from, which the server records on every request. Only when that is one
+/// of the [`trusted_proxies`] do `X-Forwarded-For` and then `X-Real-IP` name the client instead. `None` for a request
+/// that didn't come in through the server's socket, like one handed to a router directly.
+pub fn client_ip() -> Option<String> {
+    let peer = extension::<ConnectInfo<SocketAddr>>().map(|ConnectInfo(address)| address.ip());
+    client_ip_with(peer, trusted_proxies(), header)
+}
+
+/// [`client_ip`] for a request handled outside a server function, with the connection its handler extracted
+pub fn client_ip_from(peer: Option<Extension<ConnectInfo<SocketAddr>>>, headers: &HeaderMap) -> Option<String> {
+    let peer = peer.map(|Extension(ConnectInfo(address))| address.ip());
+    client_ip_with(peer, trusted_proxies(), |name| header_from(headers, name))
+}
+
+/// The proxies named in [`TRUSTED_PROXIES_VAR`], read on first use
+pub fn trusted_proxies() -> &'static [IpAddr] {
+    static PROXIES: OnceLock<Vec<IpAddr>> = OnceLock::new();
+    PROXIES.get_or_init(|| {
+        std::env::var(TRUSTED_PROXIES_VAR)
+            .map(|value| parse_trusted_proxies(&value))
+            .unwrap_or_default()
+    })
+}
+
+/// The addresses in a [`TRUSTED_PROXIES_VAR`] value, skipping anything that isn't one
+pub fn parse_trusted_proxies(value: &str) -> Vec<IpAddr> {
+    value.split(',').filter_map(|address| address.trim().parse().ok()).collect()
+}
+
+fn client_ip_with(
+    peer: Option<IpAddr>,
+    trusted: &[IpAddr],
+    header: impl Fn(HeaderName) -> Option<String>,
+) -> Option<String> {
+    let peer = peer?;
+    if !trusted.contains(&peer) {
+        return Some(peer.to_string());
+    }
+    // Each proxy appends the address it was reached from, so reading from the right, the first hop that isn't a
+    // trusted proxy is the client. Anything further left was sent by the client and could say anything.
+    let forwarded = header(HeaderName::from_static("x-forwarded-for")).and_then(|value| {
+        value
+            .rsplit(',')
+            .map(|hop| hop.trim().parse::<IpAddr>().ok())
+            .find(|hop| hop.is_none_or(|ip| !trusted.contains(&ip)))
+            .flatten()
+    });
+    let real_ip = || header(HeaderName::from_static("x-real-ip")).and_then(|ip| ip.trim().parse::<IpAddr>().ok());
+    Some(forwarded.or_else(real_ip).unwrap_or(peer).to_string())
+}
+
+/// Where the app is served as the client reached it, e.g. `https://ideas.example.com`, for absolute links.
+///
+/// Uses the `Host` header and the `X-Forwarded-Proto` a reverse proxy sets, assuming plain http without one.
+pub fn base_url() -> String {
+    base_url_with(header)
+}
+
+/// [`base_url`] for a request handled outside a server function
+pub fn base_url_from(headers: &HeaderMap) -> String {
+    base_url_with(|name| header_from(headers, name))
+}
+
+fn base_url_with(header: impl Fn(HeaderName) -> Option<String>) -> String {
+    let host = header(header::HOST).unwrap_or_else(|| "localhost:8080".to_string());
+    let scheme = header(HeaderName::from_static("x-forwarded-proto"))
+        .and_then(|value| value.split(',').next().map(|scheme| scheme.trim().to_string()))
+        .filter(|scheme| scheme == "http" || scheme == "https")
+        .unwrap_or_else(|| "http".to_string());
+    format!("{}://{}", scheme, host.trim())
+}
+
+/// Whether the client reached the app over https, directly or through a reverse proxy
+pub fn is_https() -> bool {
+    base_url().starts_with("https://")
+}
+
+/// Read a cookie sent with the request
+pub fn cookie(name: &str) -> Option<String> {
+    parse_cookie(&header(header::COOKIE)?, name)
+}
+
+/// [`cookie`] for a request handled outside a server function
+pub fn cookie_from(headers: &HeaderMap, name: &str) -> Option<String> {
+    parse_cookie(&header_from(headers, header::COOKIE)?, name)
+}
+
+/// Read a header from a plain header map
+pub fn header_from(headers: &HeaderMap, name: HeaderName) -> Option<String> {
+    headers.get(name)?.to_str().ok().map(str::to_string)
+}
+
+fn parse_cookie(cookies: &str, name: &str) -> Option<String> {
+    cookies.split(';').find_map(|pair| {
+        let (key, value) = pair.trim().split_once('=')?;
+        (key == name).then(|| value.to_string())
+    })
+}
+
+/// Append a header to the response
+pub fn append_response_header(name: HeaderName, value: &str) {
+    let (Some(context), Ok(value)) = (FullstackContext::current(), HeaderValue::from_str(value)) else {
+        return;
+    };
+    context.add_response_header(name, value);
+}
+
+/// Set a cookie on the response
+pub fn set_cookie(cookie: &str) {
+    append_response_header(header::SET_COOKIE, cookie);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        for (name, value) in pairs {
+            map.append(HeaderName::from_static(name), HeaderValue::from_str(value).unwrap());
+        }
+        map
+    }
+
+    #[test]
+    fn test_cookie_from() {
+        let map = headers(&[("cookie", "theme=dark; ideas_session=abc123")]);
+        assert_eq!(cookie_from(&map, "ideas_session"), Some("abc123".to_string()));
+        assert_eq!(cookie_from(&map, "missing"), None);
+        assert_eq!(cookie_from(&HeaderMap::new(), "ideas_session"), None);
+    }
+
+    fn ip(address: &str) -> Option<IpAddr> {
+        address.parse().ok()
+    }
+
+    #[test]
+    fn test_client_ip_is_the_peer_without_a_trusted_proxy() {
+        let map = headers(&[("x-forwarded-for", "1.2.3.4"), ("x-real-ip", "5.6.7.8")]);
+        let header = |name| header_from(&map, name);
+        assert_eq!(client_ip_with(ip("9.9.9.9"), &[], header), Some("9.9.9.9".to_string()));
+        assert_eq!(client_ip_with(None, &[], header), None);
+    }
+
+    #[test]
+    fn test_client_ip_behind_a_trusted_proxy() {
+        let trusted = parse_trusted_proxies("10.0.0.1, 10.0.0.2, not an address");
+        assert_eq!(trusted, vec![ip("10.0.0.1").unwrap(), ip("10.0.0.2").unwrap()]);
+
+        // The hop the client claimed for itself is left of the one the first proxy added
+        let map = headers(&[("x-forwarded-for", "6.6.6.6, 1.2.3.4, 10.0.0.2"), ("x-real-ip", "5.6.7.8")]);
+        let header = |name| header_from(&map, name);
+        assert_eq!(client_ip_with(ip("10.0.0.1"), &trusted, header), Some("1.2.3.4".to_string()));
+
+        let map = headers(&[("x-real-ip", "5.6.7.8")]);
+        let header = |name| header_from(&map, name);
+        assert_eq!(client_ip_with(ip("10.0.0.1"), &trusted, header), Some("5.6.7.8".to_string()));
+
+        // A proxy that forwards nothing usable is the client as far as anyone can tell
+        let map = headers(&[("x-forwarded-for", "garbage")]);
+        let header = |name| header_from(&map, name);
+        assert_eq!(client_ip_with(ip("10.0.0.1"), &trusted, header), Some("10.0.0.1".to_string()));
+    }
+
+    #[test]
+    fn test_base_url_from() {
+        let map = headers(&[("host", "ideas.example.com"), ("x-forwarded-proto", "https")]);
+        assert_eq!(base_url_from(&map), "https://ideas.example.com");
+
+        let map = headers(&[("host", "localhost:3000"), ("x-forwarded-proto", "gopher")]);
+        assert_eq!(base_url_from(&map), "http://localhost:3000");
+        assert_eq!(base_url_from(&HeaderMap::new()), "http://localhost:8080");
+    }
+}
@@ -0,0 +1,681 @@
+//! Plain REST access to ideas, for scripts and tools outside the Dioxus app.
+//!
+//! The routes are mounted on the same Axum server as the fullstack app and go through the same repository
+//! operations, ownership checks, write limits and read-only mode as the server functions; only the transport differs. Requests are
+//! authenticated with the session cookie or an `Authorization: Bearer <session token>` header. Errors are the
+//! structured [`IdeaError`] as JSON, with a matching status code. The spec is served at `/api/v1/openapi.json`.
+
+use crate::auth::server::user_id_from_headers;
+use crate::base_path::with_base_path;
+use crate::changes::server::hub;
+use crate::config::AppConfig;
+use crate::db::server::{
+    edit_idea, get_owned, ideas_after, submit_idea_content, trash_idea, IdeaCache, IdeaChanges, IdeaRepository,
+    SurrealIdeaRepository,
+};
+use crate::db::{Idea, NoteSection};
+use crate::digest::{server::digest_for, DEFAULT_DIGEST_DAYS};
+use crate::error::IdeaError;
+use crate::limits::server::{check_room, UsageCache};
+use crate::limits::StorageUsage;
+use crate::rate_limit::server::{guard_write_for, with_body_limit};
+use crate::request::{base_url_from, client_ip_from};
+use crate::utils::PAGE_SIZE;
+use axum::extract::{ConnectInfo, Path, Query, State};
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Extension, Json, Router};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+use surrealdb::Surreal;
+
+/// Body of `POST /api/v1/ideas`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NewIdea {
+    pub title: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Name the idea is credited to
+    #[serde(default)]
+    pub author: Option<String>,
+}
+
+/// Body of `PATCH /api/v1/ideas/{id}`. Missing fields keep their current value.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IdeaPatch {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub tags: Option<Vec<String>>,
+    pub what_must_be_true: Option<Vec<String>>,
+    pub development_notes: Option<String>,
+    /// Replaces every section of the structured notes; an empty list switches the idea back to plain notes
+    pub sections: Option<Vec<NoteSection>>,
+    /// `null` removes the due date
+    #[serde(default, deserialize_with = "nullable")]
+    pub due_date: Option<Option<NaiveDate>>,
+}
+
+/// Tells a field set to `null` (`Some(None)`) apart from a missing one (`None`, through `#[serde(default)]`)
+fn nullable<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+impl IdeaPatch {
+    /// The full content of `idea` after applying the patch
+    pub fn apply_to(self, idea: &Idea) -> IdeaChanges {
+        IdeaChanges {
+            title: self.title.unwrap_or_else(|| idea.title.clone()),
+            description: self.description.unwrap_or_else(|| idea.description.clone()),
+            tags: self.tags.unwrap_or_else(|| idea.tags.clone()),
+            what_must_be_true: self.what_must_be_true.unwrap_or_else(|| idea.what_must_be_true.clone()),
+            development_notes: self.development_notes.unwrap_or_else(|| idea.development_notes.clone()),
+            due_date: self.due_date.unwrap_or(idea.due_date),
+            sections: self.sections,
+        }
+    }
+}
+
+/// Status code a REST client sees for each error
+pub fn status_for(error: &IdeaError) -> StatusCode {
+    match error {
+        IdeaError::InvalidId(_) => StatusCode::BAD_REQUEST,
+        IdeaError::NotFound(_) => StatusCode::NOT_FOUND,
+        IdeaError::Forbidden => StatusCode::FORBIDDEN,
+        IdeaError::Unauthorized => StatusCode::UNAUTHORIZED,
+        IdeaError::Validation(_) => StatusCode::UNPROCESSABLE_ENTITY,
+        IdeaError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        IdeaError::RateLimited(_) => StatusCode::TOO_MANY_REQUESTS,
+        IdeaError::PayloadTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+        IdeaError::ReadOnly => StatusCode::FORBIDDEN,
+        IdeaError::StorageFull(_) => StatusCode::INSUFFICIENT_STORAGE,
+    }
+}
+
+/// [`IdeaError`] as a REST response: its JSON form plus a human-readable `message`
+pub struct ApiError(pub IdeaError);
+
+impl From<IdeaError> for ApiError {
+    fn from(error: IdeaError) -> Self {
+        ApiError(error)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let mut body = serde_json::to_value(&self.0).unwrap_or_default();
+        if let serde_json::Value::Object(fields) = &mut body {
+            fields.insert("message".to_string(), self.0.to_string().into());
+        }
+
+        let mut response = (status_for(&self.0), Json(body)).into_response();
+        if let IdeaError::RateLimited(seconds) = self.0 {
+            response
+                .headers_mut()
+                .insert(axum::http::header::RETRY_AFTER, seconds.into());
+        }
+        response
+    }
+}
+
+type ApiResult<T> = Result<T, ApiError>;
+
+/// Guard for the routes that change data: refuses on a read-only deployment, then charges the client a write.
+/// `config` is the [`AppConfig`] layered over the server; without one the defaults apply. `peer` is the connection
+/// the request came in on, see [`client_ip_from`].
+pub(crate) fn guard_rest_write(
+    config: Option<Extension<AppConfig>>,
+    peer: Option<Extension<ConnectInfo<SocketAddr>>>,
+    headers: &HeaderMap,
+) -> Result<(), IdeaError> {
+    config.map(|Extension(config)| config).unwrap_or_default().check_writable()?;
+    guard_write_for(client_ip_from(peer, headers))
+}
+
+/// State shared by the REST handlers
+#[derive(Clone)]
+struct RestState {
+    db: Surreal<surrealdb::engine::any::Any>,
+}
+
+impl RestState {
+    /// The repository, through the list cache [`crate::db::server::with_db`] layers over the server when there is one
+    fn repo(&self, cache: Option<Extension<IdeaCache>>) -> SurrealIdeaRepository {
+        let repo = SurrealIdeaRepository::new(self.db.clone());
+        match cache {
+            Some(Extension(cache)) => repo.with_cache(cache),
+            None => repo,
+        }
+    }
+}
+
+/// The `/api/v1` routes, backed by `db`
+pub fn router(db: Surreal<surrealdb::engine::any::Any>) -> Router {
+    let router = Router::new()
+        .route("/api/v1/ideas", get(list_ideas).post(create_idea))
+        .route(
+            "/api/v1/ideas/{id}",
+            get(get_idea).patch(patch_idea).delete(delete_idea),
+        )
+        .route("/api/v1/ideas/{id}/events", get(idea_events))
+        .route("/api/v1/digest", get(digest))
+        .route("/api/v1/openapi.json", get(openapi))
+        .with_state(RestState { db });
+    with_body_limit(router, |error| ApiError(error).into_response())
+}
+
+/// Header carrying the cursor of the next page of `GET /api/v1/ideas`, left out on the last page
+pub const NEXT_CURSOR_HEADER: &str = "x-next-cursor";
+
+/// Query of `GET /api/v1/ideas`. With either field the ideas come a page at a time in the order they were added,
+/// see [`crate::utils::PageCursor`]; without, all of them in manual order.
+#[derive(Debug, Default, Deserialize)]
+struct ListParams {
+    /// Where the page starts, from the previous page's [`NEXT_CURSOR_HEADER`]
+    cursor: Option<String>,
+    limit: Option<usize>,
+}
+
+async fn list_ideas(
+    State(state): State<RestState>,
+    cache: Option<Extension<IdeaCache>>,
+    headers: HeaderMap,
+    Query(params): Query<ListParams>,
+) -> ApiResult<Response> {
+    let owner = user_id_from_headers(&state.db, &headers).await?;
+    if params.cursor.is_none() && params.limit.is_none() {
+        let ideas = state.repo(cache).list(owner.as_ref()).await?;
+        return Ok(Json(ideas.into_iter().map(Idea::from).collect::<Vec<_>>()).into_response());
+    }
+
+    let limit = params.limit.unwrap_or(PAGE_SIZE);
+    let (ideas, next_cursor) = ideas_after(&state.db, owner.as_ref(), None, params.cursor.as_deref(), limit).await?;
+    let mut response = Json(ideas.into_iter().map(Idea::from).collect::<Vec<_>>()).into_response();
+    // Cursors are URL-safe base64, always a valid header value
+    if let Some(cursor) = next_cursor.and_then(|cursor| HeaderValue::from_str(&cursor).ok()) {
+        response.headers_mut().insert(NEXT_CURSOR_HEADER, cursor);
+    }
+    Ok(response)
+}
+
+async fn create_idea(
+    State(state): State<RestState>,
+    cache: Option<Extension<IdeaCache>>,
+    usage: Option<Extension<UsageCache>>,
+    config: Option<Extension<AppConfig>>,
+    peer: Option<Extension<ConnectInfo<SocketAddr>>>,
+    headers: HeaderMap,
+    Json(new_idea): Json<NewIdea>,
+) -> ApiResult<(StatusCode, Json<Idea>)> {
+    let limits = config.as_ref().map(|Extension(config)| config.limits).unwrap_or_default();
+    guard_rest_write(config, peer, &headers)?;
+    let usage = usage.map(|Extension(usage)| usage).unwrap_or_default();
+    let one_idea = StorageUsage {
+        ideas: 1,
+        ..Default::default()
+    };
+    check_room(&state.db, &usage, &limits, &one_idea).await?;
+
+    let owner = user_id_from_headers(&state.db, &headers).await?;
+    let content = IdeaChanges {
+        title: new_idea.title,
+        description: new_idea.description,
+        tags: new_idea.tags,
+        what_must_be_true: Vec::new(),
+        development_notes: String::new(),
+        due_date: None,
+        sections: None,
+    };
+    let created = submit_idea_content(&state.repo(cache), owner, new_idea.author, content).await?;
+    Ok((StatusCode::CREATED, Json(created.into())))
+}
+
+async fn get_idea(
+    State(state): State<RestState>,
+    cache: Option<Extension<IdeaCache>>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> ApiResult<Json<Idea>> {
+    let owner = user_id_from_headers(&state.db, &headers).await?;
+    let idea = get_owned(&state.repo(cache), &id, owner.as_ref()).await?;
+    Ok(Json(idea.into()))
+}
+
+async fn patch_idea(
+    State(state): State<RestState>,
+    cache: Option<Extension<IdeaCache>>,
+    config: Option<Extension<AppConfig>>,
+    peer: Option<Extension<ConnectInfo<SocketAddr>>>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    Json(patch): Json<IdeaPatch>,
+) -> ApiResult<Json<Idea>> {
+    guard_rest_write(config, peer, &headers)?;
+
+    let owner = user_id_from_headers(&state.db, &headers).await?;
+    let repo = state.repo(cache);
+    let current: Idea = get_owned(&repo, &id, owner.as_ref()).await?.into();
+    let updated = edit_idea(&repo, &id, owner.as_ref(), patch.apply_to(&current)).await?;
+    hub().broadcast(&id, None);
+    Ok(Json(updated.into()))
+}
+
+/// Moves the idea to the trash, like deleting it in the app
+async fn delete_idea(
+    State(state): State<RestState>,
+    cache: Option<Extension<IdeaCache>>,
+    config: Option<Extension<AppConfig>>,
+    peer: Option<Extension<ConnectInfo<SocketAddr>>>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> ApiResult<StatusCode> {
+    guard_rest_write(config, peer, &headers)?;
+
+    let owner = user_id_from_headers(&state.db, &headers).await?;
+    trash_idea(&state.repo(cache), &id, owner.as_ref()).await?;
+    hub().broadcast(&id, None);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Query of the change stream
+#[derive(Debug, Deserialize)]
+struct EventsQuery {
+    /// The subscribing tab; its own saves aren't reported back to it
+    #[serde(default)]
+    client: String,
+}
+
+/// Server-sent `changed` events whenever someone else edits the idea. The subscription ends with the connection.
+async fn idea_events(
+    State(state): State<RestState>,
+    cache: Option<Extension<IdeaCache>>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    Query(query): Query<EventsQuery>,
+) -> ApiResult<Sse<impl futures::Stream<Item = Result<Event, std::convert::Infallible>>>> {
+    let owner = user_id_from_headers(&state.db, &headers).await?;
+    get_owned(&state.repo(cache), &id, owner.as_ref()).await?;
+
+    let subscription = hub().subscribe(&id, &query.client);
+    // The stream owns the subscription, so a closed connection drops it and unsubscribes
+    let stream = futures::stream::unfold(subscription, |mut subscription| async move {
+        let change = subscription.receiver.recv().await?;
+        let event = Event::default()
+            .event("changed")
+            .json_data(&change)
+            .unwrap_or_else(|_| Event::default().event("changed"));
+        Some((Ok(event), subscription))
+    });
+
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
+
+/// Query of `GET /api/v1/digest`
+#[derive(Debug, Default, Deserialize)]
+struct DigestParams {
+    /// Start of the digest, [`DEFAULT_DIGEST_DAYS`] ago when missing
+    since: Option<DateTime<Utc>>,
+    /// `text` for the plain-text version, HTML otherwise
+    format: Option<String>,
+}
+
+/// The digest of [`crate::digest`] as a document of its own, ready to pipe into a mailer
+async fn digest(
+    State(state): State<RestState>,
+    headers: HeaderMap,
+    Query(params): Query<DigestParams>,
+) -> ApiResult<Response> {
+    let owner = user_id_from_headers(&state.db, &headers).await?;
+    let until = Utc::now();
+    let since = params.since.unwrap_or(until - Duration::days(DEFAULT_DIGEST_DAYS));
+    let digest = digest_for(&state.db, owner.as_ref(), since, until).await?;
+    let base = format!("{}{}", base_url_from(&headers), with_base_path(""));
+    Ok(match params.format.as_deref() {
+        Some("text") => ([(header::CONTENT_TYPE, "text/plain; charset=utf-8")], digest.to_text(&base)).into_response(),
+        _ => ([(header::CONTENT_TYPE, "text/html; charset=utf-8")], digest.to_html(&base)).into_response(),
+    })
+}
+
+async fn openapi() -> Json<serde_json::Value> {
+    Json(openapi_spec())
+}
+
+/// OpenAPI 3.0 description of the routes in [`router`]
+pub fn openapi_spec() -> serde_json::Value {
+    use serde_json::json;
+
+    let id_param = json!({
+        "name": "id",
+        "in": "path",
+        "required": true,
+        "description": "Full record id, e.g. `ideas:abc123` (URL-encode the colon if your client requires it)",
+        "schema": { "type": "string" }
+    });
+    let error = |description: &str| {
+        json!({
+            "description": description,
+            "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Error" } } }
+        })
+    };
+    let idea_response = |description: &str| {
+        json!({
+            "description": description,
+            "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Idea" } } }
+        })
+    };
+
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "Ideas API",
+            "version": "1.0.0",
+            "description": "Ideas of the signed-in user (session cookie or bearer session token), or the anonymous \
+                            ideas when unauthenticated. Writes are rate limited per client."
+        },
+        "paths": {
+            "/api/v1/ideas": {
+                "get": {
+                    "summary": "List ideas outside the trash, all of them in manual order, or with `cursor` or \
+                                `limit` a page at a time in the order they were added",
+                    "parameters": [
+                        {
+                            "name": "cursor",
+                            "in": "query",
+                            "required": false,
+                            "description": "Where the page starts: the X-Next-Cursor of the previous page. Without \
+                                            it the first page is returned.",
+                            "schema": { "type": "string" }
+                        },
+                        {
+                            "name": "limit",
+                            "in": "query",
+                            "required": false,
+                            "description": "Ideas per page, at most 100",
+                            "schema": { "type": "integer", "minimum": 1, "maximum": 100, "default": 20 }
+                        }
+                    ],
+                    "responses": {
+                        "200": {
+                            "description": "The ideas",
+                            "headers": {
+                                "X-Next-Cursor": {
+                                    "description": "Cursor of the next page when paging; absent on the last page",
+                                    "schema": { "type": "string" }
+                                }
+                            },
+                            "content": { "application/json": { "schema": {
+                                "type": "array",
+                                "items": { "$ref": "#/components/schemas/Idea" }
+                            } } }
+                        },
+                        "422": error("Invalid cursor")
+                    }
+                },
+                "post": {
+                    "summary": "Create an idea",
+                    "requestBody": {
+                        "required": true,
+                        "content": { "application/json": { "schema": { "$ref": "#/components/schemas/NewIdea" } } }
+                    },
+                    "responses": {
+                        "201": idea_response("The created idea"),
+                        "413": error("Payload too large"),
+                        "422": error("Invalid idea"),
+                        "429": error("Too many writes; see Retry-After"),
+                        "507": error("A storage limit of the deployment is reached")
+                    }
+                }
+            },
+            "/api/v1/ideas/{id}": {
+                "parameters": [id_param],
+                "get": {
+                    "summary": "Get one idea",
+                    "responses": {
+                        "200": idea_response("The idea"),
+                        "400": error("Malformed id"),
+                        "403": error("The idea belongs to someone else"),
+                        "404": error("No such idea")
+                    }
+                },
+                "patch": {
+                    "summary": "Change some fields of an idea",
+                    "requestBody": {
+                        "required": true,
+                        "content": { "application/json": { "schema": { "$ref": "#/components/schemas/IdeaPatch" } } }
+                    },
+                    "responses": {
+                        "200": idea_response("The updated idea"),
+                        "403": error("The idea belongs to someone else"),
+                        "404": error("No such idea"),
+                        "422": error("Invalid change"),
+                        "429": error("Too many writes; see Retry-After")
+                    }
+                },
+                "delete": {
+                    "summary": "Move an idea to the trash",
+                    "responses": {
+                        "204": { "description": "Moved to the trash" },
+                        "403": error("The idea belongs to someone else"),
+                        "404": error("No such idea"),
+                        "429": error("Too many writes; see Retry-After")
+                    }
+                }
+            },
+            "/api/v1/ideas/{id}/events": {
+                "parameters": [
+                    id_param,
+                    {
+                        "name": "client",
+                        "in": "query",
+                        "required": false,
+                        "description": "Id of the subscribing tab; changes it saved itself are not reported",
+                        "schema": { "type": "string" }
+                    }
+                ],
+                "get": {
+                    "summary": "Server-sent `changed` events for every edit made by another client",
+                    "responses": {
+                        "200": { "description": "An event stream", "content": { "text/event-stream": {} } },
+                        "403": error("The idea belongs to someone else"),
+                        "404": error("No such idea")
+                    }
+                }
+            },
+            "/api/v1/digest": {
+                "get": {
+                    "summary": "What happened to the ideas since a given time, as the body of an email",
+                    "parameters": [
+                        {
+                            "name": "since",
+                            "in": "query",
+                            "required": false,
+                            "description": "Start of the digest, e.g. 2024-05-01T00:00:00Z. Defaults to a week ago.",
+                            "schema": { "type": "string", "format": "date-time" }
+                        },
+                        {
+                            "name": "format",
+                            "in": "query",
+                            "required": false,
+                            "description": "`text` for the plain-text alternative",
+                            "schema": { "type": "string", "enum": ["html", "text"], "default": "html" }
+                        }
+                    ],
+                    "responses": {
+                        "200": {
+                            "description": "A self-contained HTML document, or plain text",
+                            "content": { "text/html": {}, "text/plain": {} }
+                        },
+                        "422": error("The digest starts after it ends")
+                    }
+                }
+            },
+            "/api/v1/openapi.json": {
+                "get": {
+                    "summary": "This document",
+                    "responses": { "200": { "description": "OpenAPI 3.0 document" } }
+                }
+            }
+        },
+        "components": {
+            "schemas": {
+                "Idea": {
+                    "type": "object",
+                    "properties": {
+                        "id": { "type": "string" },
+                        "title": { "type": "string" },
+                        "description": { "type": "string" },
+                        "tags": { "type": "array", "items": { "type": "string" } },
+                        "what_must_be_true": { "type": "array", "items": { "type": "string" } },
+                        "development_notes": { "type": "string" },
+                        "sections": { "type": "array", "items": { "$ref": "#/components/schemas/NoteSection" } },
+                        "slug": { "type": "string" },
+                        "previous_slugs": { "type": "array", "items": { "type": "string" } },
+                        "rank": { "type": "number" },
+                        "deleted_at": { "type": "string", "format": "date-time", "nullable": true },
+                        "pinned": { "type": "boolean" },
+                        "due_date": { "type": "string", "format": "date", "nullable": true },
+                        "author": { "type": "string", "nullable": true },
+                        "icon": { "type": "string", "nullable": true, "description": "A single emoji" },
+                        "created_at": { "type": "string", "format": "date-time", "nullable": true }
+                    },
+                    "required": ["title", "description", "tags"]
+                },
+                "NewIdea": {
+                    "type": "object",
+                    "properties": {
+                        "title": { "type": "string" },
+                        "description": { "type": "string" },
+                        "tags": { "type": "array", "items": { "type": "string" } },
+                        "author": { "type": "string" }
+                    },
+                    "required": ["title"]
+                },
+                "IdeaPatch": {
+                    "type": "object",
+                    "properties": {
+                        "title": { "type": "string" },
+                        "description": { "type": "string" },
+                        "tags": { "type": "array", "items": { "type": "string" } },
+                        "what_must_be_true": { "type": "array", "items": { "type": "string" } },
+                        "development_notes": { "type": "string" },
+                        "sections": { "type": "array", "items": { "$ref": "#/components/schemas/NoteSection" } },
+                        "due_date": { "type": "string", "format": "date", "nullable": true }
+                    }
+                },
+                "NoteSection": {
+                    "type": "object",
+                    "properties": {
+                        "heading": { "type": "string" },
+                        "body": { "type": "string" }
+                    },
+                    "required": ["heading", "body"]
+                },
+                "Error": {
+                    "type": "object",
+                    "properties": {
+                        "kind": {
+                            "type": "string",
+                            "enum": [
+                                "InvalidId", "NotFound", "Forbidden", "Unauthorized", "Validation", "Database",
+                                "RateLimited", "PayloadTooLarge", "ReadOnly", "StorageFull"
+                            ]
+                        },
+                        "detail": { "description": "Kind-specific detail (id, message, seconds or byte limit)" },
+                        "message": { "type": "string" }
+                    },
+                    "required": ["kind", "message"]
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_codes() {
+        assert_eq!(status_for(&IdeaError::NotFound("ideas:x".to_string())), StatusCode::NOT_FOUND);
+        assert_eq!(status_for(&IdeaError::Forbidden), StatusCode::FORBIDDEN);
+        assert_eq!(
+            status_for(&IdeaError::Validation("bad".to_string())),
+            StatusCode::UNPROCESSABLE_ENTITY
+        );
+        assert_eq!(status_for(&IdeaError::RateLimited(3)), StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    #[test]
+    fn test_patch_keeps_missing_fields() {
+        let idea = Idea {
+            title: "Title".to_string(),
+            description: "Description".to_string(),
+            tags: vec!["tag".to_string()],
+            development_notes: "notes".to_string(),
+            ..Default::default()
+        };
+        let patch = IdeaPatch {
+            description: Some("New".to_string()),
+            tags: Some(vec![]),
+            ..Default::default()
+        };
+
+        let changes = patch.apply_to(&idea);
+        assert_eq!(changes.title, "Title");
+        assert_eq!(changes.description, "New");
+        assert!(changes.tags.is_empty());
+        assert_eq!(changes.development_notes, "notes");
+        // Sections aren't touched unless the patch names them
+        assert_eq!(changes.sections, None);
+    }
+
+    #[test]
+    fn test_patch_sets_and_clears_due_date() {
+        let idea = Idea {
+            due_date: NaiveDate::from_ymd_opt(2024, 6, 1),
+            ..Default::default()
+        };
+
+        let missing: IdeaPatch = serde_json::from_str("{}").unwrap();
+        assert_eq!(missing.apply_to(&idea).due_date, idea.due_date);
+
+        let cleared: IdeaPatch = serde_json::from_str(r#"{ "due_date": null }"#).unwrap();
+        assert_eq!(cleared.apply_to(&idea).due_date, None);
+
+        let moved: IdeaPatch = serde_json::from_str(r#"{ "due_date": "2024-07-15" }"#).unwrap();
+        assert_eq!(moved.apply_to(&idea).due_date, NaiveDate::from_ymd_opt(2024, 7, 15));
+    }
+
+    #[test]
+    fn test_spec_documents_every_route() {
+        let spec = openapi_spec();
+        let paths = spec["paths"].as_object().unwrap();
+
+        assert!(paths["/api/v1/ideas"].get("get").is_some());
+        assert!(paths["/api/v1/ideas"].get("post").is_some());
+        for method in ["get", "patch", "delete"] {
+            assert!(paths["/api/v1/ideas/{id}"].get(method).is_some(), "{}", method);
+        }
+        assert!(paths["/api/v1/digest"].get("get").is_some());
+        assert!(spec["components"]["schemas"].get("Error").is_some());
+
+        // Paging the list is described, cursor header included
+        let list = &paths["/api/v1/ideas"]["get"];
+        let params: Vec<&str> = list["parameters"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(|param| param["name"].as_str())
+            .collect();
+        assert_eq!(params, vec!["cursor", "limit"]);
+        assert!(list["responses"]["200"]["headers"].get("X-Next-Cursor").is_some());
+    }
+}
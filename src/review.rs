@@ -0,0 +1,314 @@
+//! The weekly review: ideas nobody has touched in a while, gone through one at a time.
+//!
+//! Like the feed, staleness comes from the activity log, as ideas carry no update time of their own: an idea was
+//! last active at its latest event, or when it was created if it has none. Done and dropped ideas are finished
+//! with and never come up. Keeping an idea in a review logs a `reviewed` event, which is all it takes to make it
+//! fresh again.
+
+use crate::db::{Idea, IdeaStatus};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Ideas without activity for this many days come up in the review
+pub const STALE_DAYS: u32 = 30;
+/// Longest staleness that can be asked for, about ten years
+pub const MAX_STALE_DAYS: u32 = 3650;
+
+/// An idea due for review
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StaleIdea {
+    pub idea: Idea,
+    /// Its latest event or creation. `None` for ideas from before either was recorded, which are the stalest.
+    pub last_active: Option<DateTime<Utc>>,
+}
+
+/// The ideas of `ideas` with no activity since `cutoff`, stalest first and by title when equally stale. `events` are
+/// the times logged for each idea id, in any order.
+pub fn stale_ideas(ideas: Vec<Idea>, events: &[(String, DateTime<Utc>)], cutoff: DateTime<Utc>) -> Vec<StaleIdea> {
+    let mut stale: Vec<StaleIdea> = ideas
+        .into_iter()
+        .filter(|idea| !matches!(idea.status, IdeaStatus::Done | IdeaStatus::Dropped))
+        .map(|idea| {
+            let id = idea.id.clone().unwrap_or_default();
+            let logged = events.iter().filter(|(idea_id, _)| *idea_id == id).map(|(_, at)| *at).max();
+            let last_active = logged.max(idea.created_at);
+            StaleIdea { idea, last_active }
+        })
+        .filter(|stale| stale.last_active.is_none_or(|at| at < cutoff))
+        .collect();
+    // `None` sorts first, so ideas of unknown age lead. The title keeps ties from following the store's order.
+    stale.sort_by(|a, b| a.last_active.cmp(&b.last_active).then_with(|| a.idea.title.cmp(&b.idea.title)));
+    stale
+}
+
+/// The time before which an idea counts as stale, `days` before `now`
+pub fn stale_cutoff(now: DateTime<Utc>, days: u32) -> DateTime<Utc> {
+    now - Duration::days(days.min(MAX_STALE_DAYS) as i64)
+}
+
+/// What was done with an idea in the review
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReviewAction {
+    /// Still worth having: its activity is bumped so it leaves the review
+    Keep,
+    /// Set aside as dropped, out of future reviews
+    Archive,
+    /// Moved to the trash
+    Delete,
+    /// Passed over for now, left as it was
+    Skip,
+}
+
+impl ReviewAction {
+    pub const ALL: [ReviewAction; 4] = [
+        ReviewAction::Keep,
+        ReviewAction::Archive,
+        ReviewAction::Delete,
+        ReviewAction::Skip,
+    ];
+
+    /// How the action reads in the closing summary, e.g. "3 kept"
+    pub fn past_tense(self) -> &'static str {
+        match self {
+            ReviewAction::Keep => "kept",
+            ReviewAction::Archive => "archived",
+            ReviewAction::Delete => "deleted",
+            ReviewAction::Skip => "skipped",
+        }
+    }
+}
+
+/// The ideas of a review and what was decided for each, in order.
+///
+/// [`ReviewQueue::decide`] settles the current item and moves on; [`ReviewQueue::undo`] takes the last decision
+/// back and makes its item current again, so it can be reverted and decided anew. Skipping is a decision like the
+/// others, which is what lets an accidental skip be undone.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReviewQueue<T> {
+    items: Vec<T>,
+    /// One per item decided so far, so also the position of the current item
+    decisions: Vec<ReviewAction>,
+}
+
+impl<T: Clone> Default for ReviewQueue<T> {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+
+impl<T: Clone> ReviewQueue<T> {
+    pub fn new(items: Vec<T>) -> Self {
+        Self {
+            items,
+            decisions: Vec::new(),
+        }
+    }
+
+    /// The item to decide on, or `None` once every item is decided
+    pub fn current(&self) -> Option<&T> {
+        self.items.get(self.decisions.len())
+    }
+
+    /// The current item's place counting from 1, and the number of items, for "4 of 17"
+    pub fn progress(&self) -> (usize, usize) {
+        ((self.decisions.len() + 1).min(self.items.len()), self.items.len())
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.decisions.len() >= self.items.len()
+    }
+
+    /// Settle the current item with `action` and move to the next, returning the item settled, or `None` when
+    /// there was none left
+    pub fn decide(&mut self, action: ReviewAction) -> Option<T> {
+        let item = self.current()?.clone();
+        self.decisions.push(action);
+        Some(item)
+    }
+
+    /// Take back the last decision, returning its item, now current again, and what had been decided
+    pub fn undo(&mut self) -> Option<(T, ReviewAction)> {
+        let action = self.decisions.pop()?;
+        Some((self.items[self.decisions.len()].clone(), action))
+    }
+
+    /// The decision [`Self::undo`] would take back
+    pub fn last_decision(&self) -> Option<ReviewAction> {
+        self.decisions.last().copied()
+    }
+
+    /// How many items were settled with `action`
+    pub fn count(&self, action: ReviewAction) -> usize {
+        self.decisions.iter().filter(|decided| **decided == action).count()
+    }
+}
+
+// Server-only lookup of the stale ideas
+#[cfg(feature = "server")]
+pub mod server {
+    use super::*;
+    use crate::boards::server::in_board;
+    use crate::db::server::active_ideas;
+    use crate::db::IdeaId;
+    use crate::error::IdeaError;
+    use surrealdb::{sql::Thing, Surreal};
+
+    /// The ideas of `owner` on `board` without activity in the `days` before `now`, stalest first
+    pub async fn stale_ideas_for(
+        db: &Surreal<surrealdb::engine::any::Any>,
+        owner: Option<&Thing>,
+        board: &Thing,
+        days: u32,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<StaleIdea>, IdeaError> {
+        #[derive(Deserialize)]
+        struct EventTime {
+            idea: Thing,
+            at: DateTime<Utc>,
+        }
+
+        let records: Vec<_> =
+            active_ideas(db, owner).await?.into_iter().filter(|record| in_board(record, board)).collect();
+        let ids: Vec<Thing> = records.iter().filter_map(|record| record.id.clone()).collect();
+        let times: Vec<EventTime> = db
+            .query("SELECT idea, at FROM idea_events WHERE idea INSIDE $ideas")
+            .bind(("ideas", ids))
+            .await?
+            .take(0)?;
+
+        let events: Vec<(String, DateTime<Utc>)> = times
+            .into_iter()
+            .map(|time| (IdeaId::from(&time.idea).into(), time.at))
+            .collect();
+        let ideas = records.into_iter().map(Idea::from).collect();
+        Ok(stale_ideas(ideas, &events, stale_cutoff(now, days)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(value: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(value).unwrap().with_timezone(&Utc)
+    }
+
+    fn idea(id: &str, created_at: Option<&str>) -> Idea {
+        Idea {
+            id: Some(id.to_string()),
+            title: id.to_string(),
+            created_at: created_at.map(at),
+            ..Default::default()
+        }
+    }
+
+    fn ids(stale: &[StaleIdea]) -> Vec<&str> {
+        stale.iter().map(|stale| stale.idea.id.as_deref().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_stale_ideas_are_ordered_by_staleness() {
+        let ideas = vec![
+            idea("ideas:fresh", Some("2024-06-01T00:00:00Z")),
+            idea("ideas:touched", Some("2024-01-01T00:00:00Z")),
+            idea("ideas:old", Some("2024-02-01T00:00:00Z")),
+            idea("ideas:older", Some("2024-01-15T00:00:00Z")),
+            idea("ideas:ancient", None),
+        ];
+        let events = vec![
+            ("ideas:touched".to_string(), at("2024-05-20T00:00:00Z")),
+            ("ideas:older".to_string(), at("2024-01-20T00:00:00Z")),
+            ("ideas:old".to_string(), at("2024-03-01T00:00:00Z")),
+            ("ideas:old".to_string(), at("2024-02-10T00:00:00Z")),
+        ];
+
+        let stale = stale_ideas(ideas, &events, at("2024-05-01T00:00:00Z"));
+        assert_eq!(ids(&stale), vec!["ideas:ancient", "ideas:older", "ideas:old"]);
+        assert_eq!(stale[2].last_active, Some(at("2024-03-01T00:00:00Z")));
+        assert_eq!(stale[0].last_active, None);
+    }
+
+    #[test]
+    fn test_equally_stale_ideas_go_by_title() {
+        let ideas = vec![
+            idea("ideas:b", Some("2024-01-01T00:00:00Z")),
+            idea("ideas:c", None),
+            idea("ideas:a", Some("2024-01-01T00:00:00Z")),
+            idea("ideas:d", None),
+        ];
+        let stale = stale_ideas(ideas, &[], at("2024-05-01T00:00:00Z"));
+        assert_eq!(ids(&stale), vec!["ideas:c", "ideas:d", "ideas:a", "ideas:b"]);
+    }
+
+    #[test]
+    fn test_finished_ideas_are_not_reviewed() {
+        let mut done = idea("ideas:done", None);
+        done.status = IdeaStatus::Done;
+        let mut dropped = idea("ideas:dropped", None);
+        dropped.status = IdeaStatus::Dropped;
+        let mut building = idea("ideas:building", None);
+        building.status = IdeaStatus::Building;
+
+        let stale = stale_ideas(vec![done, dropped, building], &[], at("2024-05-01T00:00:00Z"));
+        assert_eq!(ids(&stale), vec!["ideas:building"]);
+    }
+
+    #[test]
+    fn test_stale_cutoff() {
+        let now = at("2024-05-31T12:00:00Z");
+        assert_eq!(stale_cutoff(now, STALE_DAYS), at("2024-05-01T12:00:00Z"));
+        assert_eq!(stale_cutoff(now, 0), now);
+        assert_eq!(stale_cutoff(now, u32::MAX), stale_cutoff(now, MAX_STALE_DAYS));
+    }
+
+    #[test]
+    fn test_queue_goes_through_every_item() {
+        let mut queue = ReviewQueue::new(vec!["a", "b", "c"]);
+        assert_eq!((queue.current(), queue.progress()), (Some(&"a"), (1, 3)));
+        assert_eq!(queue.decide(ReviewAction::Keep), Some("a"));
+        assert_eq!(queue.decide(ReviewAction::Skip), Some("b"));
+        assert_eq!((queue.current(), queue.progress()), (Some(&"c"), (3, 3)));
+        assert_eq!(queue.decide(ReviewAction::Delete), Some("c"));
+
+        assert!(queue.is_finished());
+        assert_eq!((queue.current(), queue.progress()), (None, (3, 3)));
+        assert_eq!(queue.decide(ReviewAction::Keep), None);
+        assert_eq!(queue.count(ReviewAction::Keep), 1);
+        assert_eq!(queue.count(ReviewAction::Archive), 0);
+    }
+
+    #[test]
+    fn test_undo_makes_the_last_item_current_again() {
+        let mut queue = ReviewQueue::new(vec!["a", "b", "c"]);
+        queue.decide(ReviewAction::Archive);
+        queue.decide(ReviewAction::Skip);
+        assert_eq!(queue.last_decision(), Some(ReviewAction::Skip));
+
+        assert_eq!(queue.undo(), Some(("b", ReviewAction::Skip)));
+        assert_eq!((queue.current(), queue.progress()), (Some(&"b"), (2, 3)));
+        assert_eq!(queue.undo(), Some(("a", ReviewAction::Archive)));
+        assert_eq!(queue.undo(), None);
+        assert_eq!(queue.current(), Some(&"a"));
+
+        // Deciding again after an undo replaces the decision taken back
+        queue.decide(ReviewAction::Keep);
+        assert_eq!((queue.count(ReviewAction::Keep), queue.count(ReviewAction::Archive)), (1, 0));
+    }
+
+    #[test]
+    fn test_undo_after_the_last_item() {
+        let mut queue = ReviewQueue::new(vec!["only"]);
+        queue.decide(ReviewAction::Delete);
+        assert!(queue.is_finished());
+        assert_eq!(queue.undo(), Some(("only", ReviewAction::Delete)));
+        assert!(!queue.is_finished());
+    }
+
+    #[test]
+    fn test_empty_queue_is_finished() {
+        let mut queue = ReviewQueue::<&str>::default();
+        assert!(queue.is_finished());
+        assert_eq!(queue.progress(), (0, 0));
+        assert_eq!(queue.undo(), None);
+    }
+}
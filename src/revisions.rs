@@ -0,0 +1,155 @@
+//! Snapshots of the notes and statements of each idea.
+//!
+//! Saving an idea whose notes or "what must be true" statements changed writes the saved content to the
+//! `idea_revisions` table, so a deleted paragraph can be brought back later. The editor saves as the user types, so
+//! a save within [`EDIT_BURST_MINUTES`](crate::history::EDIT_BURST_MINUTES) of the newest snapshot updates that
+//! snapshot instead of adding one. Only the newest [`MAX_REVISIONS`] snapshots of an idea are kept, and they are
+//! removed together with their idea by a database event.
+
+use crate::utils::truncate_text;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Snapshots kept per idea; saving one more prunes the oldest
+pub const MAX_REVISIONS: usize = 20;
+
+/// Characters of a changed line shown as the preview of a snapshot
+const PREVIEW_CHARS: usize = 80;
+
+/// One snapshot of an idea's notes and statements as sent to the client
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IdeaRevision {
+    pub id: String,
+    pub what_must_be_true: Vec<String>,
+    pub development_notes: String,
+    pub at: DateTime<Utc>,
+    /// The first line that changed since the snapshot before it, see [`with_previews`]
+    pub preview: String,
+}
+
+impl IdeaRevision {
+    /// The non-blank lines of the snapshot: statements first, then notes
+    fn lines(&self) -> Vec<&str> {
+        self.what_must_be_true
+            .iter()
+            .map(|statement| statement.trim())
+            .chain(self.development_notes.lines().map(str::trim))
+            .filter(|line| !line.is_empty())
+            .collect()
+    }
+}
+
+/// The first line that differs between two versions, marked `+` when `after` added or changed it and `−` when
+/// `after` dropped it. `None` when both are the same.
+pub fn first_changed_line(before: &[&str], after: &[&str]) -> Option<String> {
+    let same = before.iter().zip(after).take_while(|(old, new)| old == new).count();
+    match (before.get(same), after.get(same)) {
+        (_, Some(added)) => Some(format!("+ {}", added)),
+        (Some(removed), None) => Some(format!("− {}", removed)),
+        (None, None) => None,
+    }
+}
+
+/// Fill in the preview of each snapshot, newest first as they are listed, from what changed since the next older
+/// one. The oldest snapshot is compared with empty notes.
+pub fn with_previews(mut revisions: Vec<IdeaRevision>) -> Vec<IdeaRevision> {
+    for index in 0..revisions.len() {
+        let older = revisions.get(index + 1).map(IdeaRevision::lines).unwrap_or_default();
+        let changed = first_changed_line(&older, &revisions[index].lines());
+        revisions[index].preview = changed
+            .map(|line| truncate_text(&line, PREVIEW_CHARS))
+            .unwrap_or_else(|| "no changes".to_string());
+    }
+    revisions
+}
+
+// Server-only storage of the snapshots
+#[cfg(feature = "server")]
+pub mod server {
+    use super::*;
+    use crate::history::EDIT_BURST_MINUTES;
+    use surrealdb::sql::Thing;
+
+    /// Snapshot as stored in the database
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct IdeaRevisionRecord {
+        pub id: Option<Thing>,
+        pub idea: Thing,
+        pub what_must_be_true: Vec<String>,
+        pub development_notes: String,
+        pub at: DateTime<Utc>,
+    }
+
+    impl IdeaRevisionRecord {
+        /// A new snapshot of `idea` holding the given content, timestamped now
+        pub fn now(idea: Thing, what_must_be_true: Vec<String>, development_notes: String) -> Self {
+            Self {
+                id: None,
+                idea,
+                what_must_be_true,
+                development_notes,
+                at: Utc::now(),
+            }
+        }
+
+        /// Whether a save at `at` belongs to the same sitting as this snapshot and should update it
+        pub fn is_same_burst(&self, at: DateTime<Utc>) -> bool {
+            at - self.at <= chrono::Duration::minutes(EDIT_BURST_MINUTES)
+        }
+    }
+
+    /// The preview is left empty, [`with_previews`] fills it in once the whole list is known
+    impl From<IdeaRevisionRecord> for IdeaRevision {
+        fn from(record: IdeaRevisionRecord) -> Self {
+            IdeaRevision {
+                id: record.id.map(|id| id.to_string()).unwrap_or_default(),
+                what_must_be_true: record.what_must_be_true,
+                development_notes: record.development_notes,
+                at: record.at,
+                preview: String::new(),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn revision(statements: &[&str], notes: &str) -> IdeaRevision {
+        IdeaRevision {
+            id: String::new(),
+            what_must_be_true: statements.iter().map(|statement| statement.to_string()).collect(),
+            development_notes: notes.to_string(),
+            at: Utc::now(),
+            preview: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_first_changed_line() {
+        assert_eq!(first_changed_line(&["a", "b"], &["a", "c", "d"]).as_deref(), Some("+ c"));
+        assert_eq!(first_changed_line(&["a", "b"], &["a"]).as_deref(), Some("− b"));
+        assert_eq!(first_changed_line(&[], &["a"]).as_deref(), Some("+ a"));
+        assert_eq!(first_changed_line(&["a"], &["a"]), None);
+    }
+
+    #[test]
+    fn test_previews_compare_with_the_older_snapshot() {
+        let revisions = with_previews(vec![
+            revision(&["People care"], "Intro\n\nSecond paragraph"),
+            revision(&["People care"], "Intro\n\nSecond paragraph\nThird paragraph"),
+            revision(&["People care"], "Intro\n\nSecond paragraph\nThird paragraph"),
+            revision(&[" People care "], ""),
+        ]);
+        let previews: Vec<&str> = revisions.iter().map(|revision| revision.preview.as_str()).collect();
+        assert_eq!(previews, vec!["− Third paragraph", "no changes", "+ Intro", "+ People care"]);
+    }
+
+    #[test]
+    fn test_long_previews_are_cut() {
+        let revisions = with_previews(vec![revision(&[], &"word ".repeat(40))]);
+        assert_eq!(revisions[0].preview.chars().count(), PREVIEW_CHARS + 1);
+        assert!(revisions[0].preview.ends_with('…'));
+    }
+}
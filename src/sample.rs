@@ -0,0 +1,29 @@
+//! Made-up ideas for development, written by the seed binary and by the developer reset.
+
+use crate::db::Idea;
+
+/// Number of ideas created when no count is given
+pub const DEFAULT_SAMPLE_COUNT: usize = 20;
+
+const SUBJECTS: &[&str] = &["Garden", "Budget", "Recipe", "Reading", "Workshop", "Podcast", "Bike", "Language"];
+const TWISTS: &[&str] = &["tracker", "planner", "swap", "club", "journal", "challenge"];
+const TAGS: &[&str] = &["home", "money", "learning", "community", "health"];
+
+/// The `n`th sample idea; the same `n` always gives the same idea
+pub fn sample_idea(n: usize) -> Idea {
+    let subject = SUBJECTS[n % SUBJECTS.len()];
+    let twist = TWISTS[n % TWISTS.len()];
+
+    Idea {
+        title: format!("{} {} #{}", subject, twist, n + 1),
+        description: format!("A {} for anyone who cares about their {}.", twist, subject.to_lowercase()),
+        tags: vec![TAGS[n % TAGS.len()].to_string()],
+        what_must_be_true: vec![format!("People want a better {}", twist)],
+        ..Default::default()
+    }
+}
+
+/// The first `count` sample ideas
+pub fn sample_ideas(count: usize) -> Vec<Idea> {
+    (0..count).map(sample_idea).collect()
+}
@@ -1,36 +1,231 @@
-use crate::db::Idea;
+use crate::attachments::Attachment;
+use crate::auth::User;
+use crate::boards::Board;
+use crate::bundle::BundleImport;
+use crate::config::AppConfig;
+use crate::db::{
+    CacheStats, DeleteImpact, Idea, IdeaCounts, IdeaEdit, IdeaStatus, IdeaSubmission, IdeaSummary, MalformedRecord,
+    NoteSection, SubmitOutcome, TagMatchMode, Visibility,
+};
+use crate::digest::DigestEmail;
+use crate::form_config::FormConfig;
+#[cfg(feature = "dev-tools")]
+use crate::dev_tools::ResetReport;
+use chrono::{DateTime, NaiveDate, Utc};
+use crate::graph::IdeaGraph;
+use crate::history::IdeaEvent;
+use crate::limits::StorageReport;
+use crate::maintenance::MaintenanceReport;
+use crate::markdown::ImportSummary;
+use crate::review::StaleIdea;
+use crate::revisions::IdeaRevision;
+use crate::snapshots::{Health, SnapshotReport};
+use crate::stats::{Bucket, HistogramBar, TagReport};
+use crate::tags::TagColors;
+use crate::templates::IdeaTemplate;
+use crate::utils::{CursorPage, IdeaFilter, SearchHit};
 use dioxus::prelude::*;
 
-/// Submit a new idea to the database
+/// Submit a new idea to the database.
+///
+/// The description, the tags and the custom fields are checked against the deployment's [`FormConfig`] first.
+/// Unless `force` is set, nothing is created when existing ideas have a similar title; they are returned instead so
+/// the user can check them first.
 #[post("/api/ideas/submit")]
 pub async fn submit_idea_server(
-    title: String,
-    description: String,
-    tags: Vec<String>,
-) -> Result<Idea> {
+    submission: IdeaSubmission,
+    idempotency_key: Option<String>,
+    force: bool,
+) -> Result<SubmitOutcome> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("submit_idea", {
+            crate::config::server::refuse_if_read_only()?;
+            crate::rate_limit::server::guard_write()?;
+            let IdeaSubmission {
+                title,
+                description,
+                tags,
+                what_must_be_true,
+                author,
+                custom_fields,
+            } = submission;
+
+            use crate::auth::server::current_user_id;
+            use crate::db::server::{repository, request_db, similar_ideas, submit_idea_with_fields, IdeaChanges};
+            use crate::form_config::server::form_config;
+            use crate::idempotency::server::submit_once;
+            use crate::jobs::server::{enqueue, reindex};
+            use crate::limits::{server::guard_storage, StorageUsage};
+            use crate::maintenance::SystemClock;
+
+            let db = &request_db()?;
+            let custom_fields = form_config(db).await?.validate(&description, &tags, custom_fields)?;
+            let owner = current_user_id(db).await?;
+            let repo = repository()?;
+            // A repeat of a submission whose response was lost gets the idea it created
+            let outcome = submit_once(db, &repo, &SystemClock, owner.as_ref(), idempotency_key.as_deref(), || async {
+                let possible_duplicates: Vec<IdeaSummary> = similar_ideas(&repo, owner.as_ref(), &title)
+                    .await?
+                    .into_iter()
+                    .map(|record| Idea::from(record).into())
+                    .collect();
+                if !force && !possible_duplicates.is_empty() {
+                    return Ok(SubmitOutcome {
+                        created: None,
+                        possible_duplicates,
+                    });
+                }
+                // Checked only once the idea is going to be created, so offering duplicates works on a full board
+                guard_storage(StorageUsage {
+                    ideas: 1,
+                    ..Default::default()
+                })
+                .await?;
+
+                let content = IdeaChanges {
+                    title,
+                    description,
+                    tags,
+                    what_must_be_true,
+                    development_notes: String::new(),
+                    due_date: None,
+                    sections: None,
+                };
+                let created = submit_idea_with_fields(&repo, owner.clone(), author, content, custom_fields).await?;
+                enqueue(reindex(&created));
+
+                Ok(SubmitOutcome {
+                    created: Some(created.into()),
+                    possible_duplicates,
+                })
+            })
+            .await?;
+            Ok(outcome)
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// Create many ideas in one atomic write, e.g. for an import. Only the content of each idea is used.
+///
+/// If any idea is invalid nothing is created and the error lists the index of every invalid idea.
+#[post("/api/ideas/batch")]
+pub async fn create_ideas_batch_server(ideas: Vec<Idea>) -> Result<Vec<Idea>> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("create_ideas_batch", {
+            // Imports are legitimately large, so the batch size is capped instead of the payload
+            crate::config::server::refuse_if_read_only()?;
+            crate::rate_limit::server::guard_write()?;
+
+            use crate::auth::server::current_user_id;
+            use crate::db::server::{repository, request_db, submit_ideas};
+            use crate::jobs::server::{enqueue, reindex};
+            use crate::limits::{server::guard_storage, StorageUsage};
+
+            guard_storage(StorageUsage::of_ideas(&ideas)).await?;
+            let owner = current_user_id(&request_db()?).await?;
+            let created = submit_ideas(&repository()?, owner, ideas).await?;
+            // The ideas share their owner, so reading the list back once covers them all
+            enqueue(created.last().and_then(reindex));
+
+            Ok(created.into_iter().map(|record| record.into()).collect())
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// Create an idea for each `## Title` section of a markdown file, see [`crate::markdown`].
+///
+/// Sections that can't become an idea are left out and listed in the summary; the rest are created in one write.
+#[post("/api/ideas/import-markdown")]
+pub async fn import_markdown_server(content: String) -> Result<ImportSummary> {
     #[cfg(feature = "server")]
     {
-        use crate::db::{server::get_db, IdeaRecord};
+        crate::telemetry::traced!("import_markdown", {
+            // A whole file of ideas counts as one write, like a batch
+            crate::config::server::refuse_if_read_only()?;
+            crate::rate_limit::server::guard_write()?;
+
+            use crate::auth::server::current_user_id;
+            use crate::db::server::{repository, request_db, submit_ideas};
+            use crate::error::IdeaError;
+            use crate::jobs::server::{enqueue, reindex};
+            use crate::limits::{server::guard_storage, StorageUsage};
+            use crate::markdown::parse_markdown;
+
+            let parsed = parse_markdown(&content);
+            if parsed.is_empty() {
+                let message = "No ideas found: each idea starts with a `## Title` line".to_string();
+                return Err(IdeaError::Validation(message).into());
+            }
+
+            let mut ideas = Vec::with_capacity(parsed.len());
+            let mut skipped = Vec::new();
+            for idea in parsed {
+                match idea.check() {
+                    Ok(()) => ideas.push(idea.into_idea()),
+                    Err(reason) => skipped.push(reason),
+                }
+            }
+
+            let owner = current_user_id(&request_db()?).await?;
+            let imported = if ideas.is_empty() {
+                0
+            } else {
+                guard_storage(StorageUsage::of_ideas(&ideas)).await?;
+                let created = submit_ideas(&repository()?, owner, ideas).await?;
+                enqueue(created.last().and_then(reindex));
+                created.len()
+            };
+
+            Ok(ImportSummary { imported, skipped })
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
 
-        let idea = IdeaRecord {
-            id: None,
-            title,
-            description,
-            tags,
-            what_must_be_true: Vec::new(),
-            development_notes: String::new(),
-        };
+/// Answers as long as the server is up; the editor calls it to notice the connection is back, see
+/// [`crate::hooks::use_reconnect`]
+#[post("/api/health")]
+pub async fn health_server() -> Result<Health> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("health", {
+            let snapshots = crate::snapshots::server::request_snapshots();
+            let jobs = crate::jobs::server::request_jobs();
+            Ok(Health {
+                last_snapshot: snapshots.and_then(|snapshots| snapshots.last_snapshot()),
+                queued_jobs: jobs.map_or(0, |jobs| jobs.depth()),
+            })
+        })
+    }
 
-        // Insert into SurrealDB
-        let db = get_db().await;
-        let created: Option<IdeaRecord> = db
-            .create("ideas")
-            .content(idea)
-            .await
-            .map_err(|e| ServerFnError::new(e.to_string()))?;
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
 
-        let created = created.ok_or_else(|| ServerFnError::new("Failed to create idea"))?;
-        Ok(created.into())
+/// How this deployment behaves, e.g. whether it is read-only. The app fetches it once at startup.
+#[post("/api/config")]
+pub async fn get_app_config_server() -> Result<AppConfig> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("get_app_config", { Ok(crate::config::server::app_config()) })
     }
 
     #[cfg(not(feature = "server"))]
@@ -39,20 +234,390 @@ pub async fn submit_idea_server(
     }
 }
 
-/// Get all ideas from the database
+/// Get all ideas from the database that aren't in the trash, ordered by their manual rank
 #[post("/api/ideas/all")]
 pub async fn get_all_ideas_server() -> Result<Vec<Idea>> {
     #[cfg(feature = "server")]
     {
-        use crate::db::{server::get_db, IdeaRecord};
+        crate::telemetry::traced!("get_all_ideas", {
+            use crate::auth::server::current_user_id;
+            use crate::db::server::{repository, request_db, IdeaRepository};
+
+            let owner = current_user_id(&request_db()?).await?;
+
+            // Signed-in users only see their own ideas, anonymous visitors see the unowned ones
+            let ideas = repository()?.list(owner.as_ref()).await?;
+
+            Ok(ideas.into_iter().map(|record| record.into()).collect())
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// Summaries of the ideas [`get_all_ideas_server`] returns, for the list. Much smaller when notes are long.
+#[post("/api/ideas/summaries")]
+pub async fn get_idea_summaries_server() -> Result<Vec<IdeaSummary>> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("get_idea_summaries", {
+            use crate::auth::server::current_user_id;
+            use crate::db::server::{repository, request_db, IdeaRepository};
+
+            let owner = current_user_id(&request_db()?).await?;
+            let ideas = repository()?.list(owner.as_ref()).await?;
+
+            Ok(ideas.into_iter().map(|record| Idea::from(record).into()).collect())
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// Up to `limit` ideas whose title, description, notes or tags contain `query`, in list order, each with the text
+/// around where it matched first, see [`crate::utils::search_hit`]. A blank query finds nothing.
+#[post("/api/ideas/search")]
+pub async fn search_ideas_server(query: String, limit: usize) -> Result<Vec<SearchHit>> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("search_ideas", {
+            use crate::auth::server::current_user_id;
+            use crate::db::server::{repository, request_db, IdeaRepository};
+            use crate::utils::{search_hit, MAX_PAGE_SIZE};
+
+            if query.trim().is_empty() {
+                return Ok(Vec::new());
+            }
+            let owner = current_user_id(&request_db()?).await?;
+            let hits = repository()?
+                .search(owner.as_ref(), &query)
+                .await?
+                .into_iter()
+                .filter_map(|record| search_hit(&Idea::from(record), &query))
+                .take(limit.min(MAX_PAGE_SIZE))
+                .collect();
+
+            Ok(hits)
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// Up to `limit` summaries of the ideas `filter` lets through, starting at `offset`, see [`IdeaFilter::page`]
+#[post("/api/ideas/page")]
+pub async fn get_idea_page_server(filter: IdeaFilter, offset: usize, limit: usize) -> Result<Vec<IdeaSummary>> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("get_idea_page", {
+            use crate::auth::server::current_user_id;
+            use crate::db::server::{repository, request_db, IdeaRepository};
+
+            let owner = current_user_id(&request_db()?).await?;
+            let ideas: Vec<IdeaSummary> = repository()?
+                .list(owner.as_ref())
+                .await?
+                .into_iter()
+                .map(|record| Idea::from(record).into())
+                .collect();
+
+            Ok(filter.page(&ideas, offset, limit))
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// Up to `limit` summaries of the ideas outside the trash in the order they were added, starting after `cursor` or
+/// at the oldest, see [`crate::utils::PageCursor`]. Unlike offsets, a cursor stays put when ideas are added or
+/// deleted between two pages, so nothing is skipped or listed twice. `limit` is capped at
+/// [`crate::utils::MAX_PAGE_SIZE`].
+#[post("/api/ideas/after")]
+pub async fn get_ideas_after_server(cursor: Option<String>, limit: u64) -> Result<CursorPage> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("get_ideas_after", {
+            use crate::auth::server::current_user_id;
+            use crate::boards::server::current_board;
+            use crate::db::server::{ideas_after, request_db};
+
+            let db = &request_db()?;
+            let owner = current_user_id(db).await?;
+            let limit = usize::try_from(limit).unwrap_or(usize::MAX);
+            let board = current_board();
+            let (ideas, next_cursor) =
+                ideas_after(db, owner.as_ref(), Some(&board), cursor.as_deref(), limit).await?;
+
+            Ok(CursorPage {
+                ideas: ideas.into_iter().map(|record| Idea::from(record).into()).collect(),
+                next_cursor,
+            })
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// The signed-in user's stored ideas that can't be read, which every list leaves out
+#[post("/api/ideas/malformed")]
+pub async fn get_malformed_ideas_server() -> Result<Vec<MalformedRecord>> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("get_malformed_ideas", {
+            use crate::auth::server::current_user_id;
+            use crate::db::server::{malformed_ideas, request_db};
+            use crate::error::IdeaError;
+
+            let db = &request_db()?;
+            let owner = current_user_id(db).await?;
+
+            Ok(malformed_ideas(db, owner.as_ref()).await.map_err(IdeaError::from)?)
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// Delete a stored idea that can't be read, see [`get_malformed_ideas_server`]. There is no trash for these.
+#[post("/api/ideas/malformed/delete")]
+pub async fn delete_malformed_idea_server(id: String) -> Result<()> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("delete_malformed_idea", {
+            crate::config::server::refuse_if_read_only()?;
+            crate::rate_limit::server::guard_write()?;
+
+            use crate::auth::server::current_user_id;
+            use crate::db::server::{delete_malformed_idea, request_db};
+
+            let db = &request_db()?;
+            let owner = current_user_id(db).await?;
+            delete_malformed_idea(db, owner.as_ref(), &id).await?;
+
+            Ok(())
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// The signed-in user's ideas outside the trash and the `relates_to` links between them
+#[post("/api/ideas/graph")]
+pub async fn get_idea_graph_server() -> Result<IdeaGraph> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("get_idea_graph", {
+            use crate::auth::server::current_user_id;
+            use crate::db::server::{repository, request_db, IdeaRepository};
+            use crate::error::IdeaError;
+            use crate::graph::server::idea_links;
+
+            let db = &request_db()?;
+            let owner = current_user_id(db).await?;
+            let records = repository()?.list(owner.as_ref()).await?;
+            let ids: Vec<_> = records.iter().filter_map(|record| record.id.clone()).collect();
+            let links = idea_links(db, &ids).await.map_err(IdeaError::from)?;
+
+            Ok(IdeaGraph::new(records.into_iter().map(Idea::from).collect(), links))
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// Summaries of ideas due before `date` (exclusive), soonest first, leaving out the trash
+#[post("/api/ideas/due-before")]
+pub async fn get_ideas_due_before_server(date: NaiveDate) -> Result<Vec<IdeaSummary>> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("get_ideas_due_before", {
+            use crate::auth::server::current_user_id;
+            use crate::boards::server::{current_board, in_board};
+            use crate::db::server::{ideas_due_before, request_db};
+            use crate::error::IdeaError;
+
+            let db = &request_db()?;
+            let owner = current_user_id(db).await?;
+            let board = current_board();
+            let ideas = ideas_due_before(db, owner.as_ref(), date)
+                .await
+                .map_err(IdeaError::from)?;
+
+            Ok(ideas
+                .into_iter()
+                .filter(|record| in_board(record, &board))
+                .map(|record| Idea::from(record).into())
+                .collect())
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// Ideas without activity in the last `days` days, stalest first, for the review. Done and dropped ideas and the
+/// trash are left out.
+#[post("/api/ideas/stale")]
+pub async fn get_stale_ideas_server(days: u32) -> Result<Vec<StaleIdea>> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("get_stale_ideas", {
+            use crate::auth::server::current_user_id;
+            use crate::boards::server::current_board;
+            use crate::db::server::request_db;
+            use crate::error::IdeaError;
+            use crate::review::server::stale_ideas_for;
+
+            if days == 0 {
+                return Err(IdeaError::Validation("An idea is stale after at least one day".to_string()).into());
+            }
+            let db = &request_db()?;
+            let owner = current_user_id(db).await?;
+            Ok(stale_ideas_for(db, owner.as_ref(), &current_board(), days, Utc::now()).await?)
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// A digest of what happened to the ideas since `since`: new ideas, ideas moved on the board and statements
+/// written, as an email subject with an HTML body and a plain-text alternative. Links point to where the request
+/// reached the app. Nothing is sent; see [`crate::digest`].
+#[post("/api/digest")]
+pub async fn generate_digest_server(since: DateTime<Utc>) -> Result<DigestEmail> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("generate_digest", {
+            use crate::auth::server::current_user_id;
+            use crate::base_path::with_base_path;
+            use crate::db::server::request_db;
+            use crate::digest::server::digest_for;
+            use crate::request::base_url;
+
+            let db = &request_db()?;
+            let owner = current_user_id(db).await?;
+            let digest = digest_for(db, owner.as_ref(), since, Utc::now()).await?;
+            Ok(digest.to_email(&format!("{}{}", base_url(), with_base_path(""))))
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// Summaries of the ideas carrying all or any of `tags`, in manual order, leaving out the trash. No tags returns
+/// every idea; a tag nobody uses gives an empty list rather than an error.
+#[post("/api/ideas/by-tags")]
+pub async fn get_ideas_by_tags_server(tags: Vec<String>, mode: TagMatchMode) -> Result<Vec<IdeaSummary>> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("get_ideas_by_tags", {
+            use crate::auth::server::current_user_id;
+            use crate::boards::server::{current_board, in_board};
+            use crate::db::server::{ideas_with_tags, request_db};
+            use crate::error::IdeaError;
+
+            let db = &request_db()?;
+            let owner = current_user_id(db).await?;
+            let board = current_board();
+            let ideas = ideas_with_tags(db, owner.as_ref(), &tags, mode)
+                .await
+                .map_err(IdeaError::from)?;
+
+            Ok(ideas
+                .into_iter()
+                .filter(|record| in_board(record, &board))
+                .map(|record| Idea::from(record).into())
+                .collect())
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// Ideas outside the trash added by `name`, in manual order. `None` or a blank name gives the unattributed ones.
+#[post("/api/ideas/by-author")]
+pub async fn get_ideas_by_author_server(name: Option<String>) -> Result<Vec<IdeaSummary>> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("get_ideas_by_author", {
+            use crate::auth::server::current_user_id;
+            use crate::boards::server::{current_board, in_board};
+            use crate::db::server::{ideas_by_author, request_db};
+            use crate::db::AuthorFilter;
+            use crate::error::IdeaError;
+
+            let db = &request_db()?;
+            let owner = current_user_id(db).await?;
+            let author = AuthorFilter::for_author(name.as_deref());
+            let board = current_board();
+            let ideas = ideas_by_author(db, owner.as_ref(), &author)
+                .await
+                .map_err(IdeaError::from)?;
+
+            Ok(ideas
+                .into_iter()
+                .filter(|record| in_board(record, &board))
+                .map(|record| Idea::from(record).into())
+                .collect())
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// Names ideas outside the trash were added under, for the author filter
+#[post("/api/ideas/authors")]
+pub async fn get_idea_authors_server() -> Result<Vec<String>> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("get_idea_authors", {
+            use crate::auth::server::current_user_id;
+            use crate::db::server::{idea_authors, request_db};
+            use crate::error::IdeaError;
 
-        let db = get_db().await;
-        let ideas: Vec<IdeaRecord> = db
-            .select("ideas")
-            .await
-            .map_err(|e| ServerFnError::new(e.to_string()))?;
+            let db = &request_db()?;
+            let owner = current_user_id(db).await?;
 
-        Ok(ideas.into_iter().map(|record| record.into()).collect())
+            Ok(idea_authors(db, owner.as_ref()).await.map_err(IdeaError::from)?)
+        })
     }
 
     #[cfg(not(feature = "server"))]
@@ -61,32 +626,56 @@ pub async fn get_all_ideas_server() -> Result<Vec<Idea>> {
     }
 }
 
-/// Delete an idea from the database by ID
+/// Move an idea to the trash. It can be restored from there until it is purged.
 #[post("/api/ideas/delete")]
 pub async fn delete_idea_server(id: String) -> Result<()> {
     #[cfg(feature = "server")]
     {
-        use crate::db::server::get_db;
+        crate::telemetry::traced!("delete_idea", id, {
+            crate::config::server::refuse_if_read_only()?;
+            crate::rate_limit::server::guard_write()?;
+
+            use crate::auth::server::current_user_id;
+            use crate::db::server::{repository, request_db, trash_idea};
+            use crate::jobs::server::{enqueue, Job};
+
+            let owner = current_user_id(&request_db()?).await?;
+            trash_idea(&repository()?, &id, owner.as_ref()).await?;
+            enqueue([Job::ReindexIdea { id, owner }]);
 
-        let db = get_db().await;
+            Ok(())
+        })
+    }
 
-        // Parse the ID string (format: "ideas:xyz") into table and ID parts
-        // Split by ':' to get ["ideas", "xyz"]
-        let parts: Vec<&str> = id.split(':').collect();
-        if parts.len() != 2 {
-            return Err(ServerFnError::new(format!("Invalid ID format: {}", id)).into());
-        }
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
 
-        let table = parts[0];  // "ideas"
-        let record_id = parts[1];  // "xyz"
+/// Get the ideas in the trash, most recently deleted first
+#[post("/api/ideas/archived")]
+pub async fn get_archived_ideas_server() -> Result<Vec<Idea>> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("get_archived_ideas", {
+            use crate::auth::server::current_user_id;
+            use crate::boards::server::{current_board, in_board};
+            use crate::db::server::{request_db, trashed_ideas};
 
-        // SurrealDB delete using tuple syntax (table, id)
-        let _deleted: Option<crate::db::IdeaRecord> = db
-            .delete((table, record_id))
-            .await
-            .map_err(|e| ServerFnError::new(format!("Delete failed for ID {}: {}", id, e)))?;
+            let db = &request_db()?;
+            let owner = current_user_id(db).await?;
+            let board = current_board();
+            let ideas = trashed_ideas(db, owner.as_ref())
+                .await
+                .map_err(|e| ServerFnError::new(e.to_string()))?;
 
-        Ok(())
+            Ok(ideas
+                .into_iter()
+                .filter(|record| in_board(record, &board))
+                .map(|record| record.into())
+                .collect())
+        })
     }
 
     #[cfg(not(feature = "server"))]
@@ -95,34 +684,88 @@ pub async fn delete_idea_server(id: String) -> Result<()> {
     }
 }
 
-/// Get a single idea by ID
-#[post("/api/ideas/get")]
-pub async fn get_idea_by_id_server(id: String) -> Result<Idea> {
+/// The idea with `id` if it is one of the current user's ideas in the trash, so a page that can't find an idea can
+/// offer to restore it. `None` for ideas outside the trash and ideas that are gone for good.
+#[post("/api/ideas/archived/find")]
+pub async fn find_archived_by_id_server(id: String) -> Result<Option<Idea>> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("find_archived_by_id", id, {
+            use crate::auth::server::current_user_id;
+            use crate::db::server::{get_owned, repository, request_db};
+            use crate::error::IdeaError;
+
+            let owner = current_user_id(&request_db()?).await?;
+            match get_owned(&repository()?, &id, owner.as_ref()).await {
+                Ok(idea) if idea.deleted_at.is_some() => Ok(Some(idea.into())),
+                Ok(_) | Err(IdeaError::NotFound(_)) => Ok(None),
+                Err(e) => Err(e.into()),
+            }
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// Number of ideas outside the trash, their most used tags and how many have each status. With a `filter`, only
+/// the ideas it lets through are counted, which is how many the list can load.
+#[post("/api/ideas/count")]
+pub async fn count_ideas_server(filter: Option<IdeaFilter>) -> Result<IdeaCounts> {
     #[cfg(feature = "server")]
     {
-        use crate::db::{server::get_db, IdeaRecord};
+        crate::telemetry::traced!("count_ideas", {
+            use crate::auth::server::current_user_id;
+            use crate::db::server::{repository, request_db, IdeaRepository};
+            use crate::tags::{top_tags, TOP_TAGS};
+            use crate::utils::status_counts;
 
-        let db = get_db().await;
+            let owner = current_user_id(&request_db()?).await?;
+            let mut ideas: Vec<IdeaSummary> = repository()?
+                .list(owner.as_ref())
+                .await?
+                .into_iter()
+                .map(|record| Idea::from(record).into())
+                .collect();
+            if let Some(filter) = filter {
+                ideas = filter.apply(&ideas);
+            }
+
+            Ok(IdeaCounts {
+                total: ideas.len(),
+                top_tags: top_tags(ideas.iter().map(|idea| idea.tags.as_slice()), TOP_TAGS),
+                by_status: status_counts(ideas.iter().map(|idea| idea.status)),
+            })
+        })
+    }
 
-        // Parse ID string (format: "ideas:xyz")
-        let parts: Vec<&str> = id.split(':').collect();
-        if parts.len() != 2 {
-            return Err(ServerFnError::new(format!("Invalid ID format: {}", id)).into());
-        }
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
 
-        let table = parts[0];
-        let record_id = parts[1];
+/// How many ideas were created in each of the last `buckets` days, weeks or months (the last
+/// [`crate::stats::DEFAULT_BUCKETS`] when not given), oldest first, with a zero for every period without any
+#[post("/api/stats/creation")]
+pub async fn get_creation_histogram_server(bucket: Bucket, buckets: Option<u32>) -> Result<Vec<HistogramBar>> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("get_creation_histogram", {
+            use crate::auth::server::current_user_id;
+            use crate::boards::server::current_board;
+            use crate::db::server::request_db;
+            use crate::stats::{creation_histogram, server::creation_dates, DEFAULT_BUCKETS};
 
-        // Get single record
-        let idea: Option<IdeaRecord> = db
-            .select((table, record_id))
-            .await
-            .map_err(|e| ServerFnError::new(format!("Failed to get idea {}: {}", id, e)))?;
+            let db = &request_db()?;
+            let owner = current_user_id(db).await?;
+            let created = creation_dates(db, owner.as_ref(), Some(&current_board())).await?;
+            let today = chrono::Utc::now().date_naive();
 
-        match idea {
-            Some(record) => Ok(record.into()),
-            None => Err(ServerFnError::new(format!("Idea not found: {}", id)).into()),
-        }
+            Ok(creation_histogram(&created, bucket, buckets.unwrap_or(DEFAULT_BUCKETS), today))
+        })
     }
 
     #[cfg(not(feature = "server"))]
@@ -131,52 +774,1672 @@ pub async fn get_idea_by_id_server(id: String) -> Result<Idea> {
     }
 }
 
-/// Update an existing idea
-#[post("/api/ideas/update")]
-pub async fn update_idea_server(
-    id: String,
-    title: String,
-    description: String,
-    tags: Vec<String>,
-    what_must_be_true: Vec<String>,
-    development_notes: String,
-) -> Result<Idea> {
+/// One row per tag of the current board's ideas with how many there are, how many days they have gone unchanged on
+/// average and how many statements they hold, A to Z
+#[post("/api/stats/tags")]
+pub async fn get_tag_report_server() -> Result<Vec<TagReport>> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("get_tag_report", {
+            use crate::auth::server::current_user_id;
+            use crate::boards::server::current_board;
+            use crate::db::server::request_db;
+            use crate::stats::{server::tagged_ideas, tag_report};
+
+            let db = &request_db()?;
+            let owner = current_user_id(db).await?;
+            let ideas = tagged_ideas(db, owner.as_ref(), Some(&current_board())).await?;
+
+            Ok(tag_report(&ideas, chrono::Utc::now()))
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// Hits and misses of the server's cache of idea lists since it started, see `db::cache`
+#[post("/api/stats/cache")]
+pub async fn get_cache_stats_server() -> Result<CacheStats> {
     #[cfg(feature = "server")]
     {
-        use crate::db::{server::get_db, IdeaRecord};
-
-        let db = get_db().await;
-
-        // Parse ID
-        let parts: Vec<&str> = id.split(':').collect();
-        if parts.len() != 2 {
-            return Err(ServerFnError::new(format!("Invalid ID format: {}", id)).into());
-        }
-
-        let table = parts[0];
-        let record_id = parts[1];
-
-        // Create updated record (ID will be ignored in update)
-        let updated = IdeaRecord {
-            id: None,
-            title,
-            description,
-            tags,
-            what_must_be_true,
-            development_notes,
-        };
-
-        // Update in database
-        let result: Option<IdeaRecord> = db
-            .update((table, record_id))
-            .content(updated)
-            .await
-            .map_err(|e| ServerFnError::new(format!("Failed to update idea {}: {}", id, e)))?;
-
-        match result {
-            Some(record) => Ok(record.into()),
-            None => Err(ServerFnError::new(format!("Idea not found: {}", id)).into()),
-        }
+        crate::telemetry::traced!("get_cache_stats", { Ok(crate::db::server::cache_stats()) })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// How much the database holds against the soft limits of the deployment, see [`crate::limits`]
+#[post("/api/stats/storage")]
+pub async fn get_storage_usage_server() -> Result<StorageReport> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("get_storage_usage", { Ok(crate::limits::server::storage_report().await?) })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// Number of ideas in the trash
+#[post("/api/ideas/archived/count")]
+pub async fn count_archived_ideas_server() -> Result<usize> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("count_archived_ideas", {
+            use crate::auth::server::current_user_id;
+            use crate::boards::server::{current_board, in_board};
+            use crate::db::server::{request_db, trashed_ideas};
+
+            // Counted from the trashed ideas themselves, as only they know their board
+            let db = &request_db()?;
+            let owner = current_user_id(db).await?;
+            let board = current_board();
+            let ideas = trashed_ideas(db, owner.as_ref())
+                .await
+                .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+            Ok(ideas.iter().filter(|record| in_board(record, &board)).count())
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// Take an idea back out of the trash
+#[post("/api/ideas/restore")]
+pub async fn restore_idea_server(id: String) -> Result<Idea> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("restore_idea", id, {
+            crate::config::server::refuse_if_read_only()?;
+            crate::rate_limit::server::guard_write()?;
+
+            use crate::auth::server::current_user_id;
+            use crate::db::server::{repository, request_db, restore_idea};
+            use crate::jobs::server::{enqueue, reindex};
+
+            let owner = current_user_id(&request_db()?).await?;
+            let idea = restore_idea(&repository()?, &id, owner.as_ref()).await?;
+            enqueue(reindex(&idea));
+
+            Ok(idea.into())
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// How many attachments, links and revisions purging the idea `id` would remove with it, for the confirmation
+#[post("/api/ideas/delete-impact")]
+pub async fn get_delete_impact_server(id: String) -> Result<DeleteImpact> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("get_delete_impact", id, {
+            use crate::auth::server::current_user_id;
+            use crate::db::server::{delete_impact, repository, request_db};
+
+            let owner = current_user_id(&request_db()?).await?;
+            Ok(delete_impact(&repository()?, &id, owner.as_ref()).await?)
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// Permanently delete an idea that is in the trash, with everything attached to it, in one transaction. Returns
+/// what was removed along with it.
+#[post("/api/ideas/purge")]
+pub async fn purge_idea_server(id: String) -> Result<DeleteImpact> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("purge_idea", id, {
+            crate::config::server::refuse_if_read_only()?;
+            crate::rate_limit::server::guard_write()?;
+
+            use crate::auth::server::current_user_id;
+            use crate::db::server::{purge_idea, repository, request_db};
+            use crate::jobs::server::{enqueue, Job};
+
+            let owner = current_user_id(&request_db()?).await?;
+            let impact = purge_idea(&repository()?, &id, owner.as_ref()).await?;
+            // Deleting frees space the cached measure still counts
+            enqueue([Job::RecomputeStats]);
+
+            Ok(impact)
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// Permanently delete everything in the trash, returning how many ideas were removed
+#[post("/api/ideas/empty-trash")]
+pub async fn empty_trash_server() -> Result<usize> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("empty_trash", {
+            crate::config::server::refuse_if_read_only()?;
+            crate::rate_limit::server::guard_write()?;
+
+            use crate::auth::server::current_user_id;
+            use crate::db::server::{empty_trash, request_db};
+            use crate::jobs::server::{enqueue, Job};
+
+            let db = &request_db()?;
+            let owner = current_user_id(db).await?;
+            let purged = empty_trash(db, owner.as_ref()).await?;
+            enqueue([Job::RecomputeStats]);
+
+            Ok(purged)
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// Get a single idea by ID
+#[post("/api/ideas/get")]
+pub async fn get_idea_by_id_server(id: String) -> Result<Idea> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("get_idea_by_id", id, {
+            use crate::auth::server::current_user_id;
+            use crate::db::server::{get_owned, repository, request_db};
+
+            let owner = current_user_id(&request_db()?).await?;
+
+            // Get single record, checking it belongs to the current user
+            let idea = get_owned(&repository()?, &id, owner.as_ref()).await?;
+            Ok(idea.into())
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// Update an existing idea
+#[post("/api/ideas/update")]
+pub async fn update_idea_server(id: String, edit: IdeaEdit, client_id: String) -> Result<Idea> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("update_idea", id, {
+            crate::config::server::refuse_if_read_only()?;
+            crate::rate_limit::server::guard_write()?;
+
+            use crate::auth::server::current_user_id;
+            use crate::db::server::{edit_idea, repository, request_db, IdeaChanges};
+            use crate::jobs::server::{enqueue, reindex};
+
+            let owner = current_user_id(&request_db()?).await?;
+            let changes = IdeaChanges {
+                title: edit.title,
+                description: edit.description,
+                tags: edit.tags,
+                what_must_be_true: edit.what_must_be_true,
+                development_notes: edit.development_notes,
+                due_date: edit.due_date,
+                sections: None,
+            };
+            let updated = edit_idea(&repository()?, &id, owner.as_ref(), changes).await?;
+            enqueue(reindex(&updated));
+
+            // Let other tabs with this idea open know their copy is out of date
+            crate::changes::server::hub().broadcast(&id, Some(&client_id));
+
+            Ok(updated.into())
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// Replace the note sections of an idea, leaving everything else as it is unless `development_notes` is given.
+/// Converting between plain notes and sections sends both, so neither copy of the text is lost in between.
+#[post("/api/ideas/sections")]
+pub async fn update_idea_sections_server(
+    id: String,
+    sections: Vec<NoteSection>,
+    development_notes: Option<String>,
+    client_id: String,
+) -> Result<Idea> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("update_idea_sections", id, {
+            crate::config::server::refuse_if_read_only()?;
+            crate::rate_limit::server::guard_write()?;
+
+            use crate::auth::server::current_user_id;
+            use crate::db::server::{edit_idea, get_owned, repository, request_db};
+            use crate::jobs::server::{enqueue, reindex};
+            use crate::rest::IdeaPatch;
+
+            let owner = current_user_id(&request_db()?).await?;
+            let repo = repository()?;
+            let current: Idea = get_owned(&repo, &id, owner.as_ref()).await?.into();
+            let changes = IdeaPatch {
+                development_notes,
+                sections: Some(sections),
+                ..Default::default()
+            }
+            .apply_to(&current);
+            let updated = edit_idea(&repo, &id, owner.as_ref(), changes).await?;
+            enqueue(reindex(&updated));
+
+            crate::changes::server::hub().broadcast(&id, Some(&client_id));
+
+            Ok(updated.into())
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// Get a single idea by its slug. Slugs the idea used before a rename still resolve to it.
+#[post("/api/ideas/by-slug")]
+pub async fn get_idea_by_slug_server(slug: String) -> Result<Idea> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("get_idea_by_slug", {
+            use crate::auth::server::current_user_id;
+            use crate::db::server::{ensure_readable, repository, request_db, IdeaRepository};
+            use crate::error::IdeaError;
+
+            let owner = current_user_id(&request_db()?).await?;
+            let idea = repository()?.find_by_slug(&slug).await?;
+
+            match idea {
+                Some(record) => {
+                    ensure_readable(&record, owner.as_ref())?;
+                    Ok(record.into())
+                }
+                None => Err(IdeaError::NotFound(slug).into()),
+            }
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// Move an idea to another column of the board, returning the status it has now
+#[post("/api/ideas/status")]
+pub async fn set_idea_status_server(id: String, status: IdeaStatus) -> Result<IdeaStatus> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("set_idea_status", id, {
+            crate::config::server::refuse_if_read_only()?;
+            crate::rate_limit::server::guard_write()?;
+
+            use crate::auth::server::current_user_id;
+            use crate::db::server::{repository, request_db, set_status};
+
+            let owner = current_user_id(&request_db()?).await?;
+            let idea = set_status(&repository()?, &id, owner.as_ref(), status).await?;
+            crate::changes::server::hub().broadcast(&id, None);
+
+            Ok(idea.status)
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// Make an idea public or private, returning the visibility it has now. Only matters on a public deployment,
+/// where anonymous visitors see nothing but public ideas; such a deployment is read-only, so the change is made on
+/// a writable server sharing its database.
+#[post("/api/ideas/visibility")]
+pub async fn set_idea_visibility_server(id: String, visibility: Visibility) -> Result<Visibility> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("set_idea_visibility", id, {
+            crate::config::server::refuse_if_read_only()?;
+            crate::rate_limit::server::guard_write()?;
+
+            use crate::auth::server::current_user_id;
+            use crate::db::server::{repository, request_db, set_visibility};
+
+            let owner = current_user_id(&request_db()?).await?;
+            let idea = set_visibility(&repository()?, &id, owner.as_ref(), visibility).await?;
+            crate::changes::server::hub().broadcast(&id, None);
+
+            Ok(idea.visibility)
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// Give an idea an emoji icon, or remove it with `None`, returning the icon it has now
+#[post("/api/ideas/icon")]
+pub async fn set_idea_icon_server(id: String, icon: Option<String>, client_id: String) -> Result<Option<String>> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("set_idea_icon", id, {
+            crate::config::server::refuse_if_read_only()?;
+            crate::rate_limit::server::guard_write()?;
+
+            use crate::auth::server::current_user_id;
+            use crate::db::server::{repository, request_db, set_icon};
+
+            let owner = current_user_id(&request_db()?).await?;
+            let idea = set_icon(&repository()?, &id, owner.as_ref(), icon).await?;
+            crate::changes::server::hub().broadcast(&id, Some(&client_id));
+
+            Ok(idea.icon)
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// Pin or unpin an idea, returning whether it is pinned now
+#[post("/api/ideas/toggle-pin")]
+pub async fn toggle_pin_server(id: String) -> Result<bool> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("toggle_pin", id, {
+            crate::config::server::refuse_if_read_only()?;
+            crate::rate_limit::server::guard_write()?;
+
+            use crate::auth::server::current_user_id;
+            use crate::db::server::{repository, request_db, toggle_pin};
+
+            let owner = current_user_id(&request_db()?).await?;
+            let idea = toggle_pin(&repository()?, &id, owner.as_ref()).await?;
+
+            // Open development pages show the pinned state too
+            crate::changes::server::hub().broadcast(&id, None);
+
+            Ok(idea.pinned)
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// Keep an idea in a review: its latest activity becomes now, returned, and nothing else about it changes
+#[post("/api/ideas/touch")]
+pub async fn touch_idea_server(id: String) -> Result<DateTime<Utc>> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("touch_idea", id, {
+            crate::config::server::refuse_if_read_only()?;
+            crate::rate_limit::server::guard_write()?;
+
+            use crate::auth::server::current_user_id;
+            use crate::db::server::{repository, request_db, touch_idea};
+
+            let owner = current_user_id(&request_db()?).await?;
+            Ok(touch_idea(&repository()?, &id, owner.as_ref()).await?.at)
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// Add a "what must be true" statement to the end of an idea's list, returning how many it has now. The list isn't
+/// sent back, so an edit of the whole list made meanwhile elsewhere is kept.
+#[post("/api/ideas/append-wmbt")]
+pub async fn append_wmbt_server(id: String, statement: String) -> Result<usize> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("append_wmbt", id, {
+            crate::config::server::refuse_if_read_only()?;
+            crate::rate_limit::server::guard_write()?;
+
+            use crate::auth::server::current_user_id;
+            use crate::db::server::{append_wmbt, repository, request_db};
+
+            let owner = current_user_id(&request_db()?).await?;
+            let idea = append_wmbt(&repository()?, &id, owner.as_ref(), &statement).await?;
+
+            // Open development pages offer to reload
+            crate::changes::server::hub().broadcast(&id, None);
+
+            Ok(idea.what_must_be_true.len())
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// Add `tag` to an idea, returning the tags it has now, normalized like every stored tag list. Adding and removing
+/// tags of the same idea at the same time is safe: every change lands.
+#[post("/api/ideas/tags/add")]
+pub async fn add_idea_tag_server(id: String, tag: String) -> Result<Vec<String>> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("add_idea_tag", id, {
+            crate::config::server::refuse_if_read_only()?;
+            crate::rate_limit::server::guard_write()?;
+
+            use crate::auth::server::current_user_id;
+            use crate::db::server::{change_tags, repository, request_db};
+            use crate::utils::TagChange;
+
+            let owner = current_user_id(&request_db()?).await?;
+            let idea = change_tags(&repository()?, &id, owner.as_ref(), TagChange::Add(tag)).await?;
+
+            // Open development pages offer to reload
+            crate::changes::server::hub().broadcast(&id, None);
+
+            Ok(idea.tags)
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// Remove `tag` from an idea, ignoring case, returning the tags it has now
+#[post("/api/ideas/tags/remove")]
+pub async fn remove_idea_tag_server(id: String, tag: String) -> Result<Vec<String>> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("remove_idea_tag", id, {
+            crate::config::server::refuse_if_read_only()?;
+            crate::rate_limit::server::guard_write()?;
+
+            use crate::auth::server::current_user_id;
+            use crate::db::server::{change_tags, repository, request_db};
+            use crate::utils::TagChange;
+
+            let owner = current_user_id(&request_db()?).await?;
+            let idea = change_tags(&repository()?, &id, owner.as_ref(), TagChange::Remove(tag)).await?;
+
+            // Open development pages offer to reload
+            crate::changes::server::hub().broadcast(&id, None);
+
+            Ok(idea.tags)
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// Merge the idea `secondary_id` into `primary_id` and return the merged idea. The secondary is deleted.
+#[post("/api/ideas/merge")]
+pub async fn merge_ideas_server(primary_id: String, secondary_id: String) -> Result<Idea> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("merge_ideas", primary_id, {
+            crate::config::server::refuse_if_read_only()?;
+            crate::rate_limit::server::guard_write()?;
+
+            use crate::auth::server::current_user_id;
+            use crate::db::server::{merge_ideas, repository, request_db};
+            use crate::jobs::server::{enqueue, reindex};
+
+            let owner = current_user_id(&request_db()?).await?;
+            let merged = merge_ideas(&repository()?, &primary_id, &secondary_id, owner.as_ref()).await?;
+            enqueue(reindex(&merged));
+
+            // Pages open on either idea are out of date now
+            let hub = crate::changes::server::hub();
+            hub.broadcast(&primary_id, None);
+            hub.broadcast(&secondary_id, None);
+
+            Ok(merged.into())
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// History of an idea, newest first. Works for ideas in the trash too.
+#[post("/api/ideas/history")]
+pub async fn get_idea_history_server(id: String) -> Result<Vec<IdeaEvent>> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("get_idea_history", id, {
+            use crate::auth::server::current_user_id;
+            use crate::db::server::{get_owned, repository, request_db, IdeaRepository};
+
+            let owner = current_user_id(&request_db()?).await?;
+            let repo = repository()?;
+            let idea = get_owned(&repo, &id, owner.as_ref()).await?;
+
+            let events = match &idea.id {
+                Some(idea_id) => repo.history(idea_id).await?,
+                None => Vec::new(),
+            };
+            Ok(events.into_iter().map(IdeaEvent::from).collect())
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// Snapshots of the notes and statements of an idea, newest first, each with a preview of what it changed
+#[post("/api/ideas/revisions")]
+pub async fn get_idea_revisions_server(id: String) -> Result<Vec<IdeaRevision>> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("get_idea_revisions", id, {
+            use crate::auth::server::current_user_id;
+            use crate::db::server::{idea_revisions, repository, request_db};
+            use crate::revisions::with_previews;
+
+            let owner = current_user_id(&request_db()?).await?;
+            let revisions = idea_revisions(&repository()?, &id, owner.as_ref()).await?;
+            Ok(with_previews(revisions.into_iter().map(IdeaRevision::from).collect()))
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// Put the notes and statements of a snapshot back into an idea, returning the idea as restored
+#[post("/api/ideas/revisions/restore")]
+pub async fn restore_revision_server(id: String, revision_id: String, client_id: String) -> Result<Idea> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("restore_revision", id, {
+            crate::config::server::refuse_if_read_only()?;
+            crate::rate_limit::server::guard_write()?;
+
+            use crate::auth::server::current_user_id;
+            use crate::db::server::{repository, request_db, restore_revision};
+
+            let owner = current_user_id(&request_db()?).await?;
+            let restored = restore_revision(&repository()?, &id, &revision_id, owner.as_ref()).await?;
+            crate::changes::server::hub().broadcast(&id, Some(&client_id));
+
+            Ok(restored.into())
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// The idea `id` with its attachments, links, revisions and history as a JSON bundle, see [`crate::bundle`]
+#[post("/api/ideas/bundle/export")]
+pub async fn export_idea_bundle_server(id: String) -> Result<String> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("export_idea_bundle", id, {
+            use crate::auth::server::current_user_id;
+            use crate::bundle::server::export_bundle;
+            use crate::db::server::request_db;
+
+            let db = &request_db()?;
+            let owner = current_user_id(db).await?;
+            let bundle = export_bundle(db, &id, owner.as_ref()).await?;
+
+            serde_json::to_string_pretty(&bundle).map_err(|e| ServerFnError::new(e.to_string()))
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// Recreate a bundle from [`export_idea_bundle_server`] as a new idea of the current user. With `remap_links`,
+/// links to ideas that don't exist here are dropped instead of recreated.
+#[post("/api/ideas/bundle/import")]
+pub async fn import_idea_bundle_server(bundle: String, remap_links: bool) -> Result<BundleImport> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("import_idea_bundle", {
+            // Bundles carry their attachments, so only the write rate applies, like a restore
+            crate::config::server::refuse_if_read_only()?;
+            crate::rate_limit::server::guard_write()?;
+
+            use crate::auth::server::current_user_id;
+            use crate::bundle::{parse_bundle, server::import_bundle};
+            use crate::db::server::{repository, request_db};
+            use crate::jobs::server::{enqueue, Job};
+            use crate::limits::{server::guard_storage, StorageUsage};
+
+            let bundle = parse_bundle(&bundle)?;
+            // The attachments are still base64, which takes 4 bytes for every 3 of the files
+            let attachment_bytes = bundle.attachments.iter().map(|file| file.data.len() as u64 / 4 * 3).sum();
+            guard_storage(StorageUsage {
+                attachment_bytes,
+                ..StorageUsage::of_ideas(std::slice::from_ref(&bundle.idea))
+            })
+            .await?;
+            let db = &request_db()?;
+            let owner = current_user_id(db).await?;
+            let imported = import_bundle(&repository()?, bundle, owner, remap_links).await?;
+            // A bundle may bring more revisions than an idea keeps
+            enqueue([Job::PruneRevisions]);
+
+            Ok(imported)
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// Every active idea of the current user as a JSON export, see [`crate::migrations`]
+#[post("/api/ideas/export")]
+pub async fn export_ideas_server() -> Result<String> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("export_ideas", {
+            use crate::auth::server::current_user_id;
+            use crate::db::server::{repository, request_db};
+            use crate::migrations::server::export_ideas;
+
+            let owner = current_user_id(&request_db()?).await?;
+            let export = export_ideas(&repository()?, owner.as_ref()).await?;
+
+            serde_json::to_string_pretty(&export).map_err(|e| ServerFnError::new(e.to_string()))
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// Store the ideas of a JSON export from [`export_ideas_server`], of this or an older version, for the current user.
+///
+/// Ideas keep their ids, so links to them still work; ones whose id is already taken here are listed as skipped.
+#[post("/api/ideas/import")]
+pub async fn import_ideas_server(export: String) -> Result<ImportSummary> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("import_ideas", {
+            // A whole export counts as one write, like the markdown import
+            crate::config::server::refuse_if_read_only()?;
+            crate::rate_limit::server::guard_write()?;
+
+            use crate::auth::server::current_user_id;
+            use crate::db::server::{repository, request_db};
+            use crate::limits::{server::guard_storage, StorageUsage};
+            use crate::migrations::{read_export, server::import_ideas};
+
+            let ideas = read_export(&export)?;
+            guard_storage(StorageUsage::of_ideas(&ideas)).await?;
+            let owner = current_user_id(&request_db()?).await?;
+
+            Ok(import_ideas(&repository()?, owner, ideas).await?)
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// Export the database as a SurrealQL dump, without the accounts. Only for admins, see
+/// [`crate::auth::server::require_admin`].
+#[post("/api/admin/backup")]
+pub async fn create_backup_server() -> Result<String> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("create_backup", {
+            use crate::auth::server::require_admin;
+            use crate::db::server::{backup_to_string, request_db};
+
+            let db = &request_db()?;
+            // The dump holds every user's ideas, private ones included
+            require_admin(db).await?;
+            let dump = backup_to_string(db)
+                .await
+                .map_err(|e| ServerFnError::new(format!("Backup failed: {}", e)))?;
+
+            Ok(dump)
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// Restore a SurrealQL dump created by `create_backup_server`, optionally wiping existing data first. Accounts are
+/// left as they are. Only for admins.
+#[post("/api/admin/restore")]
+pub async fn restore_backup_server(dump: String, wipe_first: bool) -> Result<()> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("restore_backup", {
+            crate::config::server::refuse_if_read_only()?;
+            crate::rate_limit::server::guard_write()?;
+
+            use crate::auth::server::require_admin;
+            use crate::db::server::{invalidate_cached_ideas, request_db, restore_from_dump};
+            use crate::jobs::server::{enqueue, Job};
+
+            let db = &request_db()?;
+            // The dump runs as it is, so nobody else gets to send one
+            require_admin(db).await?;
+            let restored = restore_from_dump(db, &dump, wipe_first).await;
+            // Even a failed restore may have wiped the tables
+            invalidate_cached_ideas();
+            restored.map_err(|e| ServerFnError::new(e.to_string()))?;
+            // Backups of older versions may hold more revisions than an idea keeps now, and a wipe frees space
+            enqueue([Job::PruneRevisions, Job::RecomputeStats]);
+
+            Ok(())
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// Tidy the tags of every stored idea the way new ones are stored (see [`crate::db::tidy_tags`]), cutting tags
+/// that are too long. Returns how many ideas changed; meant to be run once after upgrading, by an admin.
+#[post("/api/admin/normalize-tags")]
+pub async fn normalize_existing_tags_server() -> Result<usize> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("normalize_existing_tags", {
+            crate::config::server::refuse_if_read_only()?;
+            crate::rate_limit::server::guard_write()?;
+
+            use crate::auth::server::require_admin;
+            use crate::db::server::{invalidate_cached_ideas, normalize_stored_tags, request_db};
+
+            let db = &request_db()?;
+            // Every owner's ideas are rewritten
+            require_admin(db).await?;
+            let changed = normalize_stored_tags(db).await;
+            invalidate_cached_ideas();
+            let changed = changed?;
+
+            Ok(changed)
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// A token for [`reset_database_server`], valid for [`crate::dev_tools::RESET_TOKEN_TTL`] and replacing any earlier
+/// one. Only built with the `dev-tools` feature.
+#[cfg(feature = "dev-tools")]
+#[post("/api/dev/reset-token")]
+pub async fn get_reset_token_server() -> Result<String> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("get_reset_token", {
+            use crate::dev_tools::server::{new_token, reset_tokens};
+
+            let token = new_token();
+            reset_tokens().issue(token.clone());
+
+            Ok(token)
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// Delete every idea with its attachments, history and revisions, plus tag colors and custom templates, then
+/// optionally write sample ideas. `confirm` must be the token from [`get_reset_token_server`]. Only built with the
+/// `dev-tools` feature.
+#[cfg(feature = "dev-tools")]
+#[post("/api/dev/reset")]
+pub async fn reset_database_server(confirm: String, seed: bool) -> Result<ResetReport> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("reset_database", {
+            crate::config::server::refuse_if_read_only()?;
+            crate::rate_limit::server::guard_write()?;
+
+            use crate::db::server::{invalidate_cached_ideas, request_db};
+            use crate::dev_tools::server::{reset_database, reset_tokens};
+
+            reset_tokens().redeem(&confirm)?;
+            let report = reset_database(&request_db()?, seed).await;
+            invalidate_cached_ideas();
+            let report = report?;
+            tracing::warn!(target: "dev_tools", ?report, "database reset");
+
+            Ok(report)
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// Prune revisions beyond the cap and purge ideas trashed longer than the retention period, like the daily
+/// background run does. Only for admins.
+#[post("/api/admin/maintenance")]
+pub async fn run_maintenance_server() -> Result<MaintenanceReport> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("run_maintenance", {
+            crate::config::server::refuse_if_read_only()?;
+            crate::rate_limit::server::guard_write()?;
+
+            use crate::auth::server::require_admin;
+            use crate::config::server::app_config;
+            use crate::db::server::request_db;
+            use crate::maintenance::{server::run_maintenance, SystemClock};
+
+            let db = &request_db()?;
+            require_admin(db).await?;
+            let report = run_maintenance(db, &SystemClock, &app_config()).await?;
+            tracing::info!(target: "maintenance", ?report, "{}", report);
+
+            Ok(report)
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// Write a snapshot of the database to the snapshot folder now, like the nightly background run does. Only for
+/// admins.
+#[post("/api/admin/snapshot")]
+pub async fn trigger_snapshot_server() -> Result<SnapshotReport> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("trigger_snapshot", {
+            // Nothing in the database changes, but a public read-only copy shouldn't let visitors fill its disk
+            crate::config::server::refuse_if_read_only()?;
+            crate::rate_limit::server::guard_write()?;
+
+            use crate::auth::server::require_admin;
+            use crate::db::server::request_db;
+            use crate::error::IdeaError;
+            use crate::maintenance::SystemClock;
+            use crate::snapshots::server::request_snapshots;
+
+            let db = &request_db()?;
+            require_admin(db).await?;
+            let snapshots = request_snapshots()
+                .ok_or_else(|| IdeaError::Validation("This server doesn't take snapshots".to_string()))?;
+            let report = snapshots.take(db, &SystemClock).await?;
+            tracing::info!(target: "snapshots", ideas = report.ideas, bytes = report.bytes, "{}", report);
+
+            Ok(report)
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// Move an idea between two neighbours in the manual order.
+///
+/// `before_id` is the idea that should end up directly above it and `after_id` the one directly below;
+/// either is `None` when the idea moves to the top or bottom of the list.
+#[post("/api/ideas/reorder")]
+pub async fn reorder_idea_server(
+    id: String,
+    before_id: Option<String>,
+    after_id: Option<String>,
+) -> Result<()> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("reorder_idea", id, {
+            crate::config::server::refuse_if_read_only()?;
+            crate::rate_limit::server::guard_write()?;
+
+            use crate::auth::server::current_user_id;
+            use crate::db::server::{invalidate_cached_ideas, load_owned_idea, request_db, with_transaction};
+            use crate::db::IdeaId;
+            use crate::utils::{plan_reorder, RankUpdate};
+            use serde::Deserialize;
+            use surrealdb::sql::Thing;
+
+            #[derive(Deserialize)]
+            struct RankRow {
+                id: Thing,
+                #[serde(default)]
+                rank: f64,
+            }
+
+            let db = &request_db()?;
+            let owner = current_user_id(db).await?;
+            load_owned_idea(db, &id, owner.as_ref()).await?;
+
+            // Only the current user's listed ideas take part in their ordering
+            let rows: Vec<RankRow> = db
+                .query("SELECT id, rank FROM ideas WHERE owner = $owner AND deleted_at = NONE ORDER BY rank ASC")
+                .bind(("owner", owner))
+                .await
+                .map_err(|e| ServerFnError::new(e.to_string()))?
+                .take(0)
+                .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+            // Compare in the client's id format, which doesn't depend on how the library prints record ids
+            let ordered: Vec<(String, f64)> = rows
+                .iter()
+                .map(|row| (IdeaId::from(&row.id).into(), row.rank))
+                .collect();
+            let thing_for = |id: &str| {
+                rows.iter()
+                    .find(|row| IdeaId::from(&row.id).as_str() == id)
+                    .map(|row| row.id.clone())
+            };
+
+            let plan = plan_reorder(&ordered, &id, before_id.as_deref(), after_id.as_deref())
+                .map_err(ServerFnError::new)?;
+
+            match plan {
+                RankUpdate::Single(rank) => {
+                    db.query("UPDATE $id SET rank = $rank")
+                        .bind(("id", thing_for(&id)))
+                        .bind(("rank", rank))
+                        .await
+                        .and_then(surrealdb::Response::check)
+                        .map_err(|e| ServerFnError::new(format!("Failed to reorder idea {}: {}", id, e)))?;
+                }
+                RankUpdate::Renormalize(ranks) => {
+                    // Renumber every idea in one transaction so a failure can't leave a half-applied order
+                    with_transaction(db, |txn| {
+                        for (i, (idea_id, rank)) in ranks.into_iter().enumerate() {
+                            txn.bind(format!("id{i}"), thing_for(&idea_id));
+                            txn.bind(format!("rank{i}"), rank);
+                            txn.push(format!("UPDATE $id{i} SET rank = $rank{i}"));
+                        }
+                        Ok(())
+                    })
+                    .await
+                    .map_err(|e| ServerFnError::new(format!("Failed to reorder ideas: {}", e)))?;
+                }
+            }
+            invalidate_cached_ideas();
+
+            Ok(())
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// Attach a file to an idea. `base64_data` is the file contents, base64-encoded.
+#[post("/api/attachments/upload")]
+pub async fn upload_attachment_server(
+    idea_id: String,
+    filename: String,
+    mime_type: String,
+    base64_data: String,
+) -> Result<Attachment> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("upload_attachment", idea_id, {
+            // Uploads have their own size cap, so only the write rate applies here
+            crate::config::server::refuse_if_read_only()?;
+            crate::rate_limit::server::guard_write()?;
+
+            use crate::attachments::server::{add_attachment, decode_upload};
+            use crate::auth::server::current_user_id;
+            use crate::db::server::request_db;
+            use crate::limits::{server::guard_storage, StorageUsage};
+
+            let data = decode_upload(&base64_data)?;
+            guard_storage(StorageUsage {
+                attachment_bytes: data.len() as u64,
+                ..Default::default()
+            })
+            .await?;
+
+            let db = &request_db()?;
+            let owner = current_user_id(db).await?;
+            let attachment =
+                add_attachment(db, &idea_id, owner.as_ref(), &filename, &mime_type, data).await?;
+
+            Ok(attachment.into())
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// List the files attached to an idea
+#[post("/api/attachments/list")]
+pub async fn list_attachments_server(idea_id: String) -> Result<Vec<Attachment>> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("list_attachments", idea_id, {
+            use crate::attachments::server::attachments_for;
+            use crate::auth::server::current_user_id;
+            use crate::db::server::request_db;
+
+            let db = &request_db()?;
+            let owner = current_user_id(db).await?;
+            let attachments = attachments_for(db, &idea_id, owner.as_ref()).await?;
+
+            Ok(attachments.into_iter().map(|record| record.into()).collect())
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// Delete a single attachment
+#[post("/api/attachments/delete")]
+pub async fn delete_attachment_server(id: String) -> Result<()> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("delete_attachment", {
+            crate::config::server::refuse_if_read_only()?;
+            crate::rate_limit::server::guard_write()?;
+
+            use crate::attachments::server::delete_attachment;
+            use crate::auth::server::current_user_id;
+            use crate::db::server::request_db;
+            use crate::jobs::server::{enqueue, Job};
+
+            let db = &request_db()?;
+            let owner = current_user_id(db).await?;
+            delete_attachment(db, &id, owner.as_ref()).await?;
+            enqueue([Job::RecomputeStats]);
+
+            Ok(())
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// Which fields the new-idea form requires or hides, and the deployment's own fields
+#[post("/api/config/form")]
+pub async fn get_form_config_server() -> Result<FormConfig> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("get_form_config", {
+            use crate::db::server::request_db;
+            use crate::form_config::server::form_config;
+
+            Ok(form_config(&request_db()?).await?)
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// Replace the form config, returning it as stored. Ideas submitted before keep their values.
+#[post("/api/config/form/set")]
+pub async fn set_form_config_server(config: FormConfig) -> Result<FormConfig> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("set_form_config", {
+            crate::config::server::refuse_if_read_only()?;
+            crate::rate_limit::server::guard_write()?;
+
+            use crate::db::server::request_db;
+            use crate::form_config::server::set_form_config;
+
+            Ok(set_form_config(&request_db()?, config).await?)
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// Colors pinned to tags; tags without a pin use their hashed color
+#[post("/api/tags/colors")]
+pub async fn get_tag_colors_server() -> Result<TagColors> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("get_tag_colors", {
+            use crate::db::server::request_db;
+            use crate::tags::server::tag_colors;
+
+            let colors = tag_colors(&request_db()?).await?;
+            Ok(colors)
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// Pin a tag to a palette color, or unpin it with `None`
+#[post("/api/tags/set_color")]
+pub async fn set_tag_color_server(tag: String, color: Option<String>) -> Result<()> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("set_tag_color", {
+            crate::config::server::refuse_if_read_only()?;
+            crate::rate_limit::server::guard_write()?;
+
+            use crate::db::server::request_db;
+            use crate::tags::server::set_tag_color;
+
+            set_tag_color(&request_db()?, &tag, color.as_deref()).await?;
+            Ok(())
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// Templates offered by the submission form: the built-ins plus the current user's own
+#[post("/api/templates/list")]
+pub async fn list_templates_server() -> Result<Vec<IdeaTemplate>> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("list_templates", {
+            use crate::auth::server::current_user_id;
+            use crate::db::server::request_db;
+            use crate::templates::server::list_templates;
+
+            let db = &request_db()?;
+            let owner = current_user_id(db).await?;
+            let templates = list_templates(db, owner.as_ref()).await?;
+
+            Ok(templates.into_iter().map(|record| record.into()).collect())
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// Save a new template owned by the current user
+#[post("/api/templates/create")]
+pub async fn create_template_server(
+    name: String,
+    description_skeleton: String,
+    default_tags: Vec<String>,
+    default_wmbt: Vec<String>,
+) -> Result<IdeaTemplate> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("create_template", {
+            crate::config::server::refuse_if_read_only()?;
+            crate::rate_limit::server::guard_write()?;
+
+            use crate::auth::server::current_user_id;
+            use crate::db::server::request_db;
+            use crate::templates::server::{create_template, TemplateInput};
+
+            let db = &request_db()?;
+            let owner = current_user_id(db).await?;
+            let input = TemplateInput {
+                name,
+                description_skeleton,
+                default_tags,
+                default_wmbt,
+            };
+            let template = create_template(db, owner, input).await?;
+
+            Ok(template.into())
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// Replace the content of one of the current user's templates. Built-ins can't be changed.
+#[post("/api/templates/update")]
+pub async fn update_template_server(
+    id: String,
+    name: String,
+    description_skeleton: String,
+    default_tags: Vec<String>,
+    default_wmbt: Vec<String>,
+) -> Result<IdeaTemplate> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("update_template", {
+            crate::config::server::refuse_if_read_only()?;
+            crate::rate_limit::server::guard_write()?;
+
+            use crate::auth::server::current_user_id;
+            use crate::db::server::request_db;
+            use crate::templates::server::{update_template, TemplateInput};
+
+            let db = &request_db()?;
+            let owner = current_user_id(db).await?;
+            let input = TemplateInput {
+                name,
+                description_skeleton,
+                default_tags,
+                default_wmbt,
+            };
+            let template = update_template(db, &id, owner.as_ref(), input).await?;
+
+            Ok(template.into())
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// Delete one of the current user's templates
+#[post("/api/templates/delete")]
+pub async fn delete_template_server(id: String) -> Result<()> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("delete_template", {
+            crate::config::server::refuse_if_read_only()?;
+            crate::rate_limit::server::guard_write()?;
+
+            use crate::auth::server::current_user_id;
+            use crate::db::server::request_db;
+            use crate::templates::server::delete_template;
+
+            let db = &request_db()?;
+            let owner = current_user_id(db).await?;
+            delete_template(db, &id, owner.as_ref()).await?;
+
+            Ok(())
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// The boards the current user can switch to: the default board, then their own by name
+#[post("/api/boards/list")]
+pub async fn list_boards_server() -> Result<Vec<Board>> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("list_boards", {
+            use crate::auth::server::current_user_id;
+            use crate::boards::server::list_boards;
+            use crate::db::server::request_db;
+
+            let db = &request_db()?;
+            let owner = current_user_id(db).await?;
+            let boards = list_boards(db, owner.as_ref()).await?;
+
+            Ok(boards.into_iter().map(|record| record.into()).collect())
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// The board the following requests work on, see [`select_board_server`]
+#[post("/api/boards/current")]
+pub async fn current_board_server() -> Result<Board> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("current_board", {
+            use crate::auth::server::current_user_id;
+            use crate::boards::server::{current_board, load_board};
+            use crate::boards::DEFAULT_BOARD_SLUG;
+            use crate::db::server::request_db;
+
+            let db = &request_db()?;
+            let owner = current_user_id(db).await?;
+            let slug = current_board().id.to_raw();
+            // A board deleted in another tab leaves a stale cookie behind
+            let board = match load_board(db, &slug, owner.as_ref()).await {
+                Ok(board) => board,
+                Err(_) => load_board(db, DEFAULT_BOARD_SLUG, None).await?,
+            };
+
+            Ok(board.into())
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// Work on the board with `slug` from now on. Every list, search and count is scoped to it.
+#[post("/api/boards/select")]
+pub async fn select_board_server(slug: String) -> Result<Board> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("select_board", {
+            use crate::auth::server::current_user_id;
+            use crate::boards::server::{board_cookie, load_board};
+            use crate::db::server::request_db;
+
+            let db = &request_db()?;
+            let owner = current_user_id(db).await?;
+            let board = load_board(db, &slug, owner.as_ref()).await?;
+            crate::request::set_cookie(&board_cookie(&board.slug));
+
+            Ok(board.into())
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// Create a board owned by the current user. Its slug is picked from `name` and never changes.
+#[post("/api/boards/create")]
+pub async fn create_board_server(name: String) -> Result<Board> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("create_board", {
+            crate::config::server::refuse_if_read_only()?;
+            crate::rate_limit::server::guard_write()?;
+
+            use crate::auth::server::current_user_id;
+            use crate::boards::server::create_board;
+            use crate::db::server::request_db;
+
+            let db = &request_db()?;
+            let owner = current_user_id(db).await?;
+            let board = create_board(db, owner, &name).await?;
+
+            Ok(board.into())
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// Give a board a new name, keeping its slug and links
+#[post("/api/boards/rename")]
+pub async fn rename_board_server(slug: String, name: String) -> Result<Board> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("rename_board", {
+            crate::config::server::refuse_if_read_only()?;
+            crate::rate_limit::server::guard_write()?;
+
+            use crate::auth::server::current_user_id;
+            use crate::boards::server::rename_board;
+            use crate::db::server::request_db;
+
+            let db = &request_db()?;
+            let owner = current_user_id(db).await?;
+            let board = rename_board(db, &slug, owner.as_ref(), &name).await?;
+
+            Ok(board.into())
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// Delete one of the current user's boards, returning how many ideas moved out of it. A board holding ideas needs
+/// `move_to`, the slug of the board that takes them. Deleting the current board switches to the default one.
+#[post("/api/boards/delete")]
+pub async fn delete_board_server(slug: String, move_to: Option<String>) -> Result<usize> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("delete_board", {
+            crate::config::server::refuse_if_read_only()?;
+            crate::rate_limit::server::guard_write()?;
+
+            use crate::auth::server::current_user_id;
+            use crate::boards::server::{board_cookie, board_thing, current_board, delete_board};
+            use crate::boards::DEFAULT_BOARD_SLUG;
+            use crate::db::server::{invalidate_cached_ideas, request_db};
+
+            let db = &request_db()?;
+            let owner = current_user_id(db).await?;
+            // The ideas are moved with a plain query, past the repository's cache
+            let moved = delete_board(db, &slug, owner.as_ref(), move_to.as_deref()).await;
+            invalidate_cached_ideas();
+            let moved = moved?;
+            if current_board() == board_thing(&slug) {
+                crate::request::set_cookie(&board_cookie(DEFAULT_BOARD_SLUG));
+            }
+
+            Ok(moved)
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// Create an account and sign in
+#[post("/api/auth/register")]
+pub async fn register_server(email: String, password: String) -> Result<User> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("register", {
+            crate::config::server::refuse_if_read_only()?;
+            crate::rate_limit::server::guard_write()?;
+
+            use crate::auth::server::{create_session, register, session_cookie};
+            use crate::db::server::request_db;
+            use crate::error::IdeaError;
+
+            let db = &request_db()?;
+            let user = register(db, &email, &password).await?;
+            let user_id = user
+                .id
+                .clone()
+                .ok_or_else(|| IdeaError::Database("User has no id".to_string()))?;
+
+            let token = create_session(db, &user_id).await?;
+            crate::request::set_cookie(&session_cookie(&token, crate::request::is_https()));
+
+            Ok(user.into())
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// Sign in with email and password
+#[post("/api/auth/login")]
+pub async fn login_server(email: String, password: String) -> Result<User> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("login", {
+            crate::rate_limit::server::guard_write()?;
+
+            use crate::auth::server::{authenticate, create_session, session_cookie};
+            use crate::db::server::request_db;
+            use crate::error::IdeaError;
+
+            let db = &request_db()?;
+            let user = authenticate(db, &email, &password).await?;
+            let user_id = user
+                .id
+                .clone()
+                .ok_or_else(|| IdeaError::Database("User has no id".to_string()))?;
+
+            let token = create_session(db, &user_id).await?;
+            crate::request::set_cookie(&session_cookie(&token, crate::request::is_https()));
+
+            Ok(user.into())
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// Sign out, ending the current session
+#[post("/api/auth/logout")]
+pub async fn logout_server() -> Result<()> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("logout", {
+            use crate::auth::server::{clear_session_cookie, delete_session, SESSION_COOKIE};
+            use crate::db::server::request_db;
+
+            let db = &request_db()?;
+            if let Some(token) = crate::request::cookie(SESSION_COOKIE) {
+                delete_session(db, &token).await?;
+            }
+            crate::request::set_cookie(&clear_session_cookie());
+
+            Ok(())
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// The signed-in user, or `None` for anonymous visitors
+#[post("/api/auth/me")]
+pub async fn current_user_server() -> Result<Option<User>> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("current_user", {
+            use crate::auth::server::current_user;
+            use crate::db::server::request_db;
+
+            let db = &request_db()?;
+            Ok(current_user(db).await?.map(User::from))
+        })
+    }
+
+    #[cfg(not(feature = "server"))]
+    {
+        Err(ServerFnError::new("Server-only function"))
+    }
+}
+
+/// Write a failure seen in the client to the server log, see [`crate::client_errors`]. Works on read-only
+/// deployments too, since nothing is stored.
+#[post("/api/client-errors")]
+pub async fn report_client_error_server(context: String, message: String) -> Result<()> {
+    #[cfg(feature = "server")]
+    {
+        crate::telemetry::traced!("report_client_error", {
+            crate::rate_limit::server::guard_report()?;
+
+            let client = crate::request::client_ip();
+            crate::client_errors::server::record(&context, &message, client.as_deref());
+
+            Ok(())
+        })
     }
 
     #[cfg(not(feature = "server"))]
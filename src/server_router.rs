@@ -0,0 +1,33 @@
+//! The Axum router the server build serves.
+//!
+//! `main` hands [`with_server_routes`] the router of the Dioxus app. Tests use [`server_functions_router`] instead,
+//! which registers the server functions without the app, so they can be called over HTTP with the request parsing,
+//! error encoding and middleware the real server has, but without a web build to serve.
+
+use crate::config::AppConfig;
+use crate::rate_limit::server::{server_fn_refusal, with_body_limit};
+use axum::Router;
+use surrealdb::Surreal;
+
+/// `app` with the REST routes, the feed and the webhook merged in and the settings and the database attached, moved
+/// below `APP_BASE_PATH` when that is set. Request bodies are capped, see [`with_body_limit`]. The webhook secret is
+/// read from the environment here.
+pub fn with_server_routes(app: Router, db: Surreal<surrealdb::engine::any::Any>, config: AppConfig) -> Router {
+    let secret = crate::webhook::webhook_secret(|key| std::env::var(key).ok());
+    // The REST routes and the webhook cap their bodies themselves, answering in their own error format
+    let app = with_body_limit(app, server_fn_refusal);
+    let router = app
+        .merge(crate::rest::router(db.clone()))
+        .merge(crate::feed::server::router(db.clone()))
+        .merge(crate::webhook::server::router(db.clone(), secret));
+    let router = crate::config::server::with_config(router, config);
+    let router = crate::base_path::server::mount_at_base_path(router, &crate::base_path::base_path());
+    crate::db::server::with_db(router, db)
+}
+
+/// The server functions, the REST routes and the feed on `db`, without the Dioxus app
+pub fn server_functions_router(db: Surreal<surrealdb::engine::any::Any>, config: AppConfig) -> Router {
+    use dioxus::server::{DioxusRouterExt, FullstackState};
+    let functions = Router::new().register_server_functions().with_state(FullstackState::headless());
+    with_server_routes(functions, db, config)
+}
@@ -0,0 +1,384 @@
+//! Display preferences of the idea list and the board, how the development page saves, the name new ideas are
+//! credited to and the board of ideas last worked on.
+//!
+//! Like the theme, each preference is persisted through [`crate::storage`] and provided at the app root by
+//! [`crate::hooks::use_settings_provider`], so it applies to every page and survives a reload.
+
+use crate::boards::{is_board_slug, DEFAULT_BOARD_SLUG};
+use crate::db::IdeaStatus;
+use crate::storage::Storage;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Storage key for the list density
+pub const LIST_DENSITY_KEY: &str = "list_density";
+/// Storage key for the auto-save preference
+pub const AUTO_SAVE_KEY: &str = "auto_save";
+/// Storage key for the board's column order and collapsed columns
+pub const BOARD_LAYOUT_KEY: &str = "board_layout";
+/// Storage key for the name new ideas are credited to
+pub const DISPLAY_NAME_KEY: &str = "display_name";
+/// Storage key for the slug of the board of ideas last worked on, see [`crate::boards`]
+pub const ACTIVE_BOARD_KEY: &str = "active_board";
+/// Storage key for how times are shown
+pub const TIME_DISPLAY_KEY: &str = "time_display";
+
+/// Tags shown on a compact card before the rest collapse into a "+N" chip
+pub const COMPACT_TAGS: usize = 2;
+
+/// How much of each idea the list shows
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ListDensity {
+    /// Full cards with their description
+    #[default]
+    Comfortable,
+    /// Cards without the description and with the tags cut to [`COMPACT_TAGS`]
+    Compact,
+    /// One table row per idea, sortable by column
+    Table,
+}
+
+impl ListDensity {
+    pub const ALL: [ListDensity; 3] = [ListDensity::Comfortable, ListDensity::Compact, ListDensity::Table];
+
+    /// Stable name, used both for storage and as the picker label
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ListDensity::Comfortable => "comfortable",
+            ListDensity::Compact => "compact",
+            ListDensity::Table => "table",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|density| density.as_str() == value.trim())
+    }
+
+    /// The persisted density, or the default when nothing (or something unreadable) is stored
+    pub fn load(storage: &dyn Storage) -> Self {
+        storage
+            .get_raw(LIST_DENSITY_KEY)
+            .and_then(|value| ListDensity::parse(&value))
+            .unwrap_or_default()
+    }
+
+    pub fn save(self, storage: &dyn Storage) {
+        storage.set_raw(LIST_DENSITY_KEY, self.as_str());
+    }
+}
+
+/// When the development page saves edits on its own
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AutoSave {
+    /// Only the "save" button saves
+    Off,
+    /// Save once editing pauses for this many seconds; 0 saves every edit right away
+    After(u32),
+}
+
+impl Default for AutoSave {
+    fn default() -> Self {
+        AutoSave::After(0)
+    }
+}
+
+impl AutoSave {
+    /// The choices offered in the picker
+    pub const ALL: [AutoSave; 4] = [AutoSave::After(0), AutoSave::After(2), AutoSave::After(10), AutoSave::Off];
+
+    /// Stable name, used for storage and as the picker's option value
+    pub fn as_str(self) -> String {
+        match self {
+            AutoSave::Off => "off".to_string(),
+            AutoSave::After(seconds) => format!("{}s", seconds),
+        }
+    }
+
+    /// Only the choices in [`AutoSave::ALL`] parse
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|choice| choice.as_str() == value.trim())
+    }
+
+    pub fn label(self) -> String {
+        match self {
+            AutoSave::Off => "off".to_string(),
+            AutoSave::After(0) => "on every edit".to_string(),
+            AutoSave::After(seconds) => format!("after a {}s pause", seconds),
+        }
+    }
+
+    /// How long to wait after an edit before saving it, `None` when edits wait for the button
+    pub fn delay(self) -> Option<Duration> {
+        match self {
+            AutoSave::Off => None,
+            AutoSave::After(seconds) => Some(Duration::from_secs(seconds.into())),
+        }
+    }
+
+    /// The persisted preference, or the default when nothing (or something unreadable) is stored
+    pub fn load(storage: &dyn Storage) -> Self {
+        storage
+            .get_raw(AUTO_SAVE_KEY)
+            .and_then(|value| AutoSave::parse(&value))
+            .unwrap_or_default()
+    }
+
+    pub fn save(self, storage: &dyn Storage) {
+        storage.set_raw(AUTO_SAVE_KEY, &self.as_str());
+    }
+}
+
+/// How times like an idea's creation or a revision's save are shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimeDisplay {
+    /// "3 h ago", "last Tuesday", see [`crate::utils::relative_time`]
+    #[default]
+    Relative,
+    /// "2024-11-03 15:04 UTC"
+    Absolute,
+}
+
+impl TimeDisplay {
+    pub const ALL: [TimeDisplay; 2] = [TimeDisplay::Relative, TimeDisplay::Absolute];
+
+    /// Stable name, used both for storage and as the picker label
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TimeDisplay::Relative => "relative",
+            TimeDisplay::Absolute => "exact",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|display| display.as_str() == value.trim())
+    }
+
+    /// The persisted choice, or the default when nothing (or something unreadable) is stored
+    pub fn load(storage: &dyn Storage) -> Self {
+        storage
+            .get_raw(TIME_DISPLAY_KEY)
+            .and_then(|value| TimeDisplay::parse(&value))
+            .unwrap_or_default()
+    }
+
+    pub fn save(self, storage: &dyn Storage) {
+        storage.set_raw(TIME_DISPLAY_KEY, self.as_str());
+    }
+}
+
+/// How the board arranges its columns
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BoardLayout {
+    /// Every status exactly once, leftmost column first
+    pub order: Vec<IdeaStatus>,
+    /// Columns shown as a narrow strip with only their name and count
+    pub collapsed: Vec<IdeaStatus>,
+}
+
+impl Default for BoardLayout {
+    fn default() -> Self {
+        Self {
+            order: IdeaStatus::ALL.to_vec(),
+            collapsed: Vec::new(),
+        }
+    }
+}
+
+impl BoardLayout {
+    /// Same layout with duplicates dropped and missing statuses appended, e.g. after a status was added
+    pub fn repaired(mut self) -> Self {
+        let mut order: Vec<IdeaStatus> = Vec::with_capacity(IdeaStatus::ALL.len());
+        for status in self.order.into_iter().chain(IdeaStatus::ALL) {
+            if !order.contains(&status) {
+                order.push(status);
+            }
+        }
+        self.order = order;
+        let mut collapsed: Vec<IdeaStatus> = Vec::new();
+        for status in self.collapsed {
+            if !collapsed.contains(&status) {
+                collapsed.push(status);
+            }
+        }
+        self.collapsed = collapsed;
+        self
+    }
+
+    /// Move the column at `from` to `to`
+    pub fn move_column(&mut self, from: usize, to: usize) {
+        crate::utils::move_item(&mut self.order, from, to);
+    }
+
+    pub fn is_collapsed(&self, status: IdeaStatus) -> bool {
+        self.collapsed.contains(&status)
+    }
+
+    pub fn toggle_collapsed(&mut self, status: IdeaStatus) {
+        if self.is_collapsed(status) {
+            self.collapsed.retain(|collapsed| *collapsed != status);
+        } else {
+            self.collapsed.push(status);
+        }
+    }
+
+    /// The persisted layout, or the default when nothing (or something unreadable) is stored
+    pub fn load(storage: &dyn Storage) -> Self {
+        storage
+            .get_raw(BOARD_LAYOUT_KEY)
+            .and_then(|raw| serde_json::from_str::<BoardLayout>(&raw).ok())
+            .map(BoardLayout::repaired)
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, storage: &dyn Storage) {
+        if let Ok(raw) = serde_json::to_string(self) {
+            storage.set_raw(BOARD_LAYOUT_KEY, &raw);
+        }
+    }
+}
+
+/// The stored display name, or an empty string when none is set
+pub fn load_display_name(storage: &dyn Storage) -> String {
+    storage.get_raw(DISPLAY_NAME_KEY).unwrap_or_default()
+}
+
+/// Store `name`; a blank name removes the stored one
+pub fn save_display_name(storage: &dyn Storage, name: &str) {
+    if name.trim().is_empty() {
+        storage.remove(DISPLAY_NAME_KEY);
+    } else {
+        storage.set_raw(DISPLAY_NAME_KEY, name);
+    }
+}
+
+/// The slug of the board of ideas last worked on, the default board unless one was stored
+pub fn load_active_board(storage: &dyn Storage) -> String {
+    storage
+        .get_raw(ACTIVE_BOARD_KEY)
+        .filter(|slug| is_board_slug(slug))
+        .unwrap_or_else(|| DEFAULT_BOARD_SLUG.to_string())
+}
+
+/// Remember `slug` as the board worked on
+pub fn save_active_board(storage: &dyn Storage, slug: &str) {
+    storage.set_raw(ACTIVE_BOARD_KEY, slug);
+}
+
+/// The first `limit` tags and how many more there are
+pub fn tag_overflow(tags: &[String], limit: usize) -> (&[String], usize) {
+    let shown = tags.len().min(limit);
+    (&tags[..shown], tags.len() - shown)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::MemoryStorage;
+
+    #[test]
+    fn test_parse_roundtrip() {
+        for density in ListDensity::ALL {
+            assert_eq!(ListDensity::parse(density.as_str()), Some(density));
+        }
+        assert_eq!(ListDensity::parse("cozy"), None);
+    }
+
+    #[test]
+    fn test_load_and_save() {
+        let storage = MemoryStorage::new();
+        assert_eq!(ListDensity::load(&storage), ListDensity::Comfortable);
+
+        ListDensity::Table.save(&storage);
+        assert_eq!(ListDensity::load(&storage), ListDensity::Table);
+
+        storage.set_raw(LIST_DENSITY_KEY, "garbage");
+        assert_eq!(ListDensity::load(&storage), ListDensity::Comfortable);
+    }
+
+    #[test]
+    fn test_auto_save_roundtrip() {
+        for choice in AutoSave::ALL {
+            assert_eq!(AutoSave::parse(&choice.as_str()), Some(choice));
+        }
+        // Delays the picker doesn't offer aren't accepted from storage either
+        assert_eq!(AutoSave::parse("7s"), None);
+        assert_eq!(AutoSave::After(2).delay(), Some(Duration::from_secs(2)));
+        assert_eq!(AutoSave::Off.delay(), None);
+
+        let storage = MemoryStorage::new();
+        assert_eq!(AutoSave::load(&storage), AutoSave::After(0));
+        AutoSave::Off.save(&storage);
+        assert_eq!(AutoSave::load(&storage), AutoSave::Off);
+        storage.set_raw(AUTO_SAVE_KEY, "sometimes");
+        assert_eq!(AutoSave::load(&storage), AutoSave::default());
+    }
+
+    #[test]
+    fn test_time_display_roundtrip() {
+        for display in TimeDisplay::ALL {
+            assert_eq!(TimeDisplay::parse(display.as_str()), Some(display));
+        }
+        let storage = MemoryStorage::new();
+        assert_eq!(TimeDisplay::load(&storage), TimeDisplay::Relative);
+        TimeDisplay::Absolute.save(&storage);
+        assert_eq!(TimeDisplay::load(&storage), TimeDisplay::Absolute);
+        storage.set_raw(TIME_DISPLAY_KEY, "sundial");
+        assert_eq!(TimeDisplay::load(&storage), TimeDisplay::Relative);
+    }
+
+    #[test]
+    fn test_board_layout_load_repairs_the_order() {
+        let storage = MemoryStorage::new();
+        assert_eq!(BoardLayout::load(&storage), BoardLayout::default());
+
+        let mut layout = BoardLayout::default();
+        layout.move_column(3, 0);
+        layout.toggle_collapsed(IdeaStatus::Dropped);
+        layout.save(&storage);
+        let loaded = BoardLayout::load(&storage);
+        assert_eq!(loaded.order[0], IdeaStatus::Done);
+        assert!(loaded.is_collapsed(IdeaStatus::Dropped));
+
+        // A layout saved before some statuses existed gets them at the end
+        storage.set_raw(BOARD_LAYOUT_KEY, r#"{"order":["done","done","inbox"],"collapsed":[]}"#);
+        let loaded = BoardLayout::load(&storage);
+        assert_eq!(loaded.order.len(), IdeaStatus::ALL.len());
+        assert_eq!(loaded.order[..3], [IdeaStatus::Done, IdeaStatus::Inbox, IdeaStatus::Exploring]);
+
+        storage.set_raw(BOARD_LAYOUT_KEY, "garbage");
+        assert_eq!(BoardLayout::load(&storage), BoardLayout::default());
+    }
+
+    #[test]
+    fn test_display_name() {
+        let storage = MemoryStorage::new();
+        assert_eq!(load_display_name(&storage), "");
+
+        save_display_name(&storage, "Sam");
+        assert_eq!(load_display_name(&storage), "Sam");
+
+        save_display_name(&storage, "   ");
+        assert_eq!(storage.get_raw(DISPLAY_NAME_KEY), None);
+        assert_eq!(load_display_name(&storage), "");
+    }
+
+    #[test]
+    fn test_active_board() {
+        let storage = MemoryStorage::new();
+        assert_eq!(load_active_board(&storage), DEFAULT_BOARD_SLUG);
+
+        save_active_board(&storage, "work");
+        assert_eq!(load_active_board(&storage), "work");
+
+        storage.set_raw(ACTIVE_BOARD_KEY, "../admin");
+        assert_eq!(load_active_board(&storage), DEFAULT_BOARD_SLUG);
+    }
+
+    #[test]
+    fn test_tag_overflow() {
+        let tags: Vec<String> = ["a", "b", "c", "d", "e"].iter().map(|tag| tag.to_string()).collect();
+        assert_eq!(tag_overflow(&tags, 2), (&tags[..2], 3));
+        assert_eq!(tag_overflow(&tags[..1], 2), (&tags[..1], 0));
+        assert_eq!(tag_overflow(&[], 2), (&[][..], 0));
+    }
+}
@@ -0,0 +1,269 @@
+//! Stopping the server between two writes rather than in the middle of one.
+//!
+//! On Ctrl-C (SIGINT) or SIGTERM the server stops taking requests, answering new ones with 503, and waits up to
+//! [`ShutdownSettings::timeout`] for the ones in flight and then the background jobs they queued to finish, see
+//! [`crate::jobs`]. With [`SNAPSHOT_ON_SHUTDOWN_VAR`] set it then takes a last snapshot, see [`crate::snapshots`].
+//! Only then does the server stop, and `main` returns once the background tasks are stopped too, so the last handle
+//! on the database is dropped and the on-disk engines close cleanly instead of stopping halfway through a write.
+
+use std::time::Duration;
+
+/// Seconds to wait for requests in flight and queued jobs before exiting anyway
+pub const SHUTDOWN_TIMEOUT_VAR: &str = "IDEAS_SHUTDOWN_TIMEOUT_SECS";
+
+/// Set to `1` (or `true`, `yes`, `on`) to take a snapshot after the last request and before exiting
+pub const SNAPSHOT_ON_SHUTDOWN_VAR: &str = "IDEAS_SNAPSHOT_ON_SHUTDOWN";
+
+/// Wait for requests in flight when [`SHUTDOWN_TIMEOUT_VAR`] isn't set
+pub const DEFAULT_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long connections may stay open once the requests and jobs are done
+pub const CONNECTIONS_GRACE: Duration = Duration::from_secs(1);
+
+/// How the server shuts down
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShutdownSettings {
+    pub timeout: Duration,
+    pub snapshot: bool,
+}
+
+impl Default for ShutdownSettings {
+    fn default() -> Self {
+        Self {
+            timeout: DEFAULT_SHUTDOWN_TIMEOUT,
+            snapshot: false,
+        }
+    }
+}
+
+impl ShutdownSettings {
+    /// Read the settings from [`SHUTDOWN_TIMEOUT_VAR`] and [`SNAPSHOT_ON_SHUTDOWN_VAR`]
+    pub fn from_env() -> Self {
+        Self::from_lookup(|key| std::env::var(key).ok())
+    }
+
+    /// Same as [`ShutdownSettings::from_env`] with a custom variable lookup. Unset or unreadable values keep the
+    /// default.
+    pub fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Self {
+        let timeout = lookup(SHUTDOWN_TIMEOUT_VAR).and_then(|value| value.trim().parse::<u64>().ok());
+        let snapshot = lookup(SNAPSHOT_ON_SHUTDOWN_VAR)
+            .is_some_and(|value| matches!(value.trim().to_ascii_lowercase().as_str(), "1" | "true" | "yes" | "on"));
+        Self {
+            timeout: timeout.map_or(DEFAULT_SHUTDOWN_TIMEOUT, Duration::from_secs),
+            snapshot,
+        }
+    }
+}
+
+// Server-only tracking of requests and the signal handler
+#[cfg(feature = "server")]
+pub mod server {
+    use super::*;
+    use crate::jobs::server::ServerJobs;
+    use crate::maintenance::SystemClock;
+    use crate::snapshots::server::Snapshots;
+    use axum::extract::{Request, State};
+    use axum::http::StatusCode;
+    use axum::middleware::Next;
+    use axum::response::{IntoResponse, Response};
+    use std::future::IntoFuture;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use surrealdb::{engine::any::Any, Surreal};
+    use tokio::sync::Notify;
+
+    #[derive(Default)]
+    struct Counter {
+        running: AtomicUsize,
+        closed: AtomicBool,
+        idle: Notify,
+    }
+
+    /// The requests being handled right now. Clones share the count.
+    #[derive(Clone, Default)]
+    pub struct InFlight(Arc<Counter>);
+
+    /// One request in flight, counted until it is dropped
+    pub struct InFlightGuard(Arc<Counter>);
+
+    impl Drop for InFlightGuard {
+        fn drop(&mut self) {
+            if self.0.running.fetch_sub(1, Ordering::SeqCst) == 1 {
+                self.0.idle.notify_waiters();
+            }
+        }
+    }
+
+    impl InFlight {
+        /// Count a request, or `None` once [`InFlight::close`] was called
+        pub fn enter(&self) -> Option<InFlightGuard> {
+            self.0.running.fetch_add(1, Ordering::SeqCst);
+            // Checked after counting, so a request can't slip in unseen while `drained` looks at the count
+            if self.0.closed.load(Ordering::SeqCst) {
+                drop(InFlightGuard(self.0.clone()));
+                return None;
+            }
+            Some(InFlightGuard(self.0.clone()))
+        }
+
+        pub fn running(&self) -> usize {
+            self.0.running.load(Ordering::SeqCst)
+        }
+
+        /// Turn every request from now on away
+        pub fn close(&self) {
+            self.0.closed.store(true, Ordering::SeqCst);
+        }
+
+        pub fn is_closed(&self) -> bool {
+            self.0.closed.load(Ordering::SeqCst)
+        }
+
+        /// Wait until no request is in flight, for at most `timeout`. Whether they all finished in time.
+        pub async fn drained(&self, timeout: Duration) -> bool {
+            let wait = async {
+                loop {
+                    // Registered before looking at the count, so the last request leaving in between still wakes it
+                    let idle = self.0.idle.notified();
+                    if self.running() == 0 {
+                        return;
+                    }
+                    idle.await;
+                }
+            };
+            tokio::time::timeout(timeout, wait).await.is_ok()
+        }
+    }
+
+    /// Count every request handled by `router` in `in_flight` while it is handled, and answer with 503 once it is
+    /// closed. A streamed response, like the live change events, counts until its headers are sent.
+    pub fn with_in_flight(router: axum::Router, in_flight: InFlight) -> axum::Router {
+        router.layer(axum::middleware::from_fn_with_state(in_flight, count_in_flight))
+    }
+
+    async fn count_in_flight(State(in_flight): State<InFlight>, request: Request, next: Next) -> Response {
+        let Some(_guard) = in_flight.enter() else {
+            return (StatusCode::SERVICE_UNAVAILABLE, "The server is shutting down").into_response();
+        };
+        next.run(request).await
+    }
+
+    /// Resolves on Ctrl-C, or on SIGTERM on Unix
+    pub async fn shutdown_signal() {
+        let ctrl_c = async {
+            if let Err(error) = tokio::signal::ctrl_c().await {
+                tracing::warn!(target: "shutdown", %error, "couldn't listen for Ctrl-C");
+                std::future::pending::<()>().await;
+            }
+        };
+        #[cfg(unix)]
+        let terminate = async {
+            match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+                Ok(mut signal) => {
+                    signal.recv().await;
+                }
+                Err(error) => {
+                    tracing::warn!(target: "shutdown", %error, "couldn't listen for SIGTERM");
+                    std::future::pending::<()>().await;
+                }
+            }
+        };
+        #[cfg(not(unix))]
+        let terminate = std::future::pending::<()>();
+
+        tokio::select! {
+            _ = ctrl_c => {}
+            _ = terminate => {}
+        }
+    }
+
+    /// Stop taking requests, wait for the ones in flight, then for the `jobs` left in the queue, and take the last
+    /// snapshot if `settings` ask for one. Both waits share the timeout. Whether everything finished in time.
+    pub async fn shut_down(
+        db: &Surreal<Any>,
+        in_flight: &InFlight,
+        settings: &ShutdownSettings,
+        snapshots: Option<&Snapshots>,
+        jobs: Option<&ServerJobs>,
+    ) -> bool {
+        let deadline = tokio::time::Instant::now() + settings.timeout;
+        in_flight.close();
+        tracing::info!(target: "shutdown", running = in_flight.running(), "shutting down");
+        let mut drained = in_flight.drained(settings.timeout).await;
+        if !drained {
+            let running = in_flight.running();
+            tracing::warn!(target: "shutdown", running, "requests still running after {:?}", settings.timeout);
+        }
+        // Closed once no request is left to queue more
+        if let Some(jobs) = jobs {
+            jobs.close();
+            let left = deadline.saturating_duration_since(tokio::time::Instant::now());
+            if !jobs.drained(left).await {
+                let queued = jobs.depth();
+                tracing::warn!(target: "shutdown", queued, "jobs still queued after {:?}", settings.timeout);
+                drained = false;
+            }
+        }
+        if let (true, Some(snapshots)) = (settings.snapshot, snapshots) {
+            match snapshots.take(db, &SystemClock).await {
+                Ok(report) => tracing::info!(target: "shutdown", ideas = report.ideas, "{}", report),
+                Err(error) => tracing::warn!(target: "shutdown", %error, "last snapshot failed"),
+            }
+        }
+        drained
+    }
+
+    /// Serve `router` on `listener` until `stopping` resolves, then stop serving. `stopping` is meant to wait for
+    /// [`shutdown_signal`] and [`shut_down`], owning the server's handle on the database so it is dropped by then; the
+    /// routers of the closed connections go with theirs, so the engine is closed once the caller drops the rest.
+    /// What `stopping` returned: whether everything finished in time.
+    pub async fn serve(
+        listener: tokio::net::TcpListener,
+        router: axum::Router,
+        stopping: impl std::future::Future<Output = bool>,
+    ) -> std::io::Result<bool> {
+        let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+        let service = router.into_make_service_with_connect_info::<std::net::SocketAddr>();
+        let server = axum::serve(listener, service).with_graceful_shutdown(async {
+            stopped.await.ok();
+        });
+        let mut server = tokio::spawn(server.into_future());
+
+        let drained = tokio::select! {
+            served = &mut server => {
+                return served.map_err(std::io::Error::other)?.map(|()| true);
+            }
+            drained = stopping => drained,
+        };
+        // Connections still open, like the live change events, get a moment to close before they are cut off
+        stop.send(()).ok();
+        if tokio::time::timeout(CONNECTIONS_GRACE, &mut server).await.is_err() {
+            tracing::warn!(target: "shutdown", "connections still open after {:?}", CONNECTIONS_GRACE);
+            server.abort();
+        }
+        Ok(drained)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_settings_from_lookup() {
+        assert_eq!(ShutdownSettings::from_lookup(|_| None), ShutdownSettings::default());
+        let settings = ShutdownSettings::from_lookup(|key| match key {
+            SHUTDOWN_TIMEOUT_VAR => Some(" 3 ".to_string()),
+            SNAPSHOT_ON_SHUTDOWN_VAR => Some("yes".to_string()),
+            _ => None,
+        });
+        assert_eq!(settings.timeout, Duration::from_secs(3));
+        assert!(settings.snapshot);
+
+        let unreadable = ShutdownSettings::from_lookup(|key| match key {
+            SHUTDOWN_TIMEOUT_VAR => Some("soon".to_string()),
+            _ => Some("0".to_string()),
+        });
+        assert_eq!(unreadable, ShutdownSettings::default());
+    }
+}
@@ -0,0 +1,316 @@
+//! Nightly copies of the database written to a folder of files, so a bad day can be undone without a manual backup.
+//!
+//! Each snapshot is the SurrealQL dump of the export pipeline wrapped in a small JSON document and written to
+//! `ideas-YYYY-MM-DD.json`; a second snapshot on the same day replaces the first. After writing, all but the newest
+//! [`SnapshotSettings::keep`] snapshots are deleted. Files in the folder that aren't snapshots are left alone.
+//! [`server::spawn_nightly`] runs this once at startup and then every [`SNAPSHOT_INTERVAL`], and
+//! `trigger_snapshot_server()` runs it on demand. The `dump` field of a snapshot restores like any other backup.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Folder the snapshots are written to
+pub const SNAPSHOT_DIR_VAR: &str = "IDEAS_SNAPSHOT_DIR";
+
+/// Number of snapshots kept after each run
+pub const SNAPSHOT_KEEP_VAR: &str = "IDEAS_SNAPSHOT_KEEP";
+
+/// Snapshots kept when [`SNAPSHOT_KEEP_VAR`] isn't set: two weeks of nightly runs
+pub const DEFAULT_SNAPSHOT_KEEP: usize = 14;
+
+/// Folder inside the data folder used when [`SNAPSHOT_DIR_VAR`] isn't set
+pub const DEFAULT_SNAPSHOT_DIR: &str = "backups";
+
+/// Time between two snapshots of the background task
+pub const SNAPSHOT_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+const FILE_PREFIX: &str = "ideas-";
+const FILE_SUFFIX: &str = ".json";
+
+/// Where snapshots go and how many are kept
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotSettings {
+    pub dir: PathBuf,
+    /// Never below 1, so rotating can't delete the snapshot just written
+    pub keep: usize,
+}
+
+impl SnapshotSettings {
+    /// Read the settings from [`SNAPSHOT_DIR_VAR`] and [`SNAPSHOT_KEEP_VAR`], with the snapshots in
+    /// [`DEFAULT_SNAPSHOT_DIR`] next to the database when no folder is set
+    #[cfg(feature = "server")]
+    pub fn from_env() -> Self {
+        let db_path = crate::data_dir::db_path();
+        let data_folder = db_path.parent().unwrap_or(Path::new(""));
+        Self::from_lookup(|key| std::env::var(key).ok(), data_folder)
+    }
+
+    /// Same as [`SnapshotSettings::from_env`] with a custom variable lookup and data folder
+    pub fn from_lookup(lookup: impl Fn(&str) -> Option<String>, data_folder: &Path) -> Self {
+        let dir = lookup(SNAPSHOT_DIR_VAR)
+            .map(|dir| dir.trim().to_string())
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .unwrap_or_else(|| data_folder.join(DEFAULT_SNAPSHOT_DIR));
+        let keep = lookup(SNAPSHOT_KEEP_VAR)
+            .and_then(|value| value.trim().parse::<usize>().ok())
+            .unwrap_or(DEFAULT_SNAPSHOT_KEEP)
+            .max(1);
+        Self { dir, keep }
+    }
+}
+
+/// Name of the snapshot taken on `date`
+pub fn snapshot_file_name(date: NaiveDate) -> String {
+    format!("{}{}{}", FILE_PREFIX, date.format("%Y-%m-%d"), FILE_SUFFIX)
+}
+
+/// Day a snapshot was taken, from its file name; `None` for any other file
+pub fn snapshot_date(file_name: &str) -> Option<NaiveDate> {
+    let date = file_name.strip_prefix(FILE_PREFIX)?.strip_suffix(FILE_SUFFIX)?;
+    // The parser also takes unpadded numbers, which `snapshot_file_name` never writes
+    (date.len() == 10)
+        .then(|| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok())
+        .flatten()
+}
+
+/// The snapshots among `file_names` to delete so only the newest `keep` remain, oldest first
+pub fn snapshots_to_delete(file_names: &[String], keep: usize) -> Vec<String> {
+    let mut snapshots: Vec<(NaiveDate, &String)> = file_names
+        .iter()
+        .filter_map(|name| snapshot_date(name).map(|date| (date, name)))
+        .collect();
+    snapshots.sort();
+    let stale = snapshots.len().saturating_sub(keep);
+    snapshots.into_iter().take(stale).map(|(_, name)| name.clone()).collect()
+}
+
+/// What a snapshot wrote
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotReport {
+    /// File name inside the snapshot folder
+    pub file: String,
+    /// Ideas in the database, trashed ones included
+    pub ideas: usize,
+    /// Size of the file
+    pub bytes: u64,
+    pub taken_at: DateTime<Utc>,
+    /// Older snapshots deleted afterwards
+    pub rotated: usize,
+}
+
+impl fmt::Display for SnapshotReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "wrote {} with {} ideas ({} bytes), deleted {} older snapshots",
+            self.file, self.ideas, self.bytes, self.rotated
+        )
+    }
+}
+
+/// What `health_server()` reports about a running server
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Health {
+    /// When the last snapshot was written, if one was since the server started
+    pub last_snapshot: Option<DateTime<Utc>>,
+    /// Background jobs queued or running, see [`crate::jobs`]
+    #[serde(default)]
+    pub queued_jobs: usize,
+}
+
+#[cfg(feature = "server")]
+pub mod server {
+    use super::*;
+    use crate::error::IdeaError;
+    use crate::maintenance::{Clock, SystemClock};
+    use std::sync::{Arc, Mutex};
+    use surrealdb::{engine::any::Any, Surreal};
+
+    /// The settings of the snapshots and the time of the last one, shared by the nightly task and the requests
+    #[derive(Debug, Clone)]
+    pub struct Snapshots {
+        pub settings: SnapshotSettings,
+        last: Arc<Mutex<Option<DateTime<Utc>>>>,
+    }
+
+    impl Snapshots {
+        pub fn new(settings: SnapshotSettings) -> Self {
+            Self {
+                settings,
+                last: Arc::default(),
+            }
+        }
+
+        /// When the last snapshot succeeded
+        pub fn last_snapshot(&self) -> Option<DateTime<Utc>> {
+            *self.last.lock().unwrap_or_else(|e| e.into_inner())
+        }
+
+        /// Take a snapshot now and remember its time when it succeeds
+        pub async fn take(&self, db: &Surreal<Any>, clock: &impl Clock) -> Result<SnapshotReport, IdeaError> {
+            let report = take_snapshot(db, &self.settings, clock).await?;
+            *self.last.lock().unwrap_or_else(|e| e.into_inner()) = Some(report.taken_at);
+            Ok(report)
+        }
+    }
+
+    /// Make `snapshots` available to every request handled by `router`, like [`crate::config::server::with_config`]
+    pub fn with_snapshots(router: axum::Router, snapshots: Snapshots) -> axum::Router {
+        router.layer(axum::Extension(snapshots))
+    }
+
+    /// Snapshots of the server handling the current server function call, if it takes any
+    pub fn request_snapshots() -> Option<Snapshots> {
+        crate::request::extension::<Snapshots>()
+    }
+
+    /// The file a snapshot is written to
+    #[derive(Serialize)]
+    struct SnapshotFile<'a> {
+        taken_at: DateTime<Utc>,
+        ideas: usize,
+        /// SurrealQL dump, as restored by [`crate::db::server::restore_from_dump`]
+        dump: &'a str,
+    }
+
+    fn io_error(action: &str, path: &Path, error: std::io::Error) -> IdeaError {
+        IdeaError::Database(format!("couldn't {} {}: {}", action, path.display(), error))
+    }
+
+    /// Dump `db` into today's snapshot file in `settings.dir`, creating the folder if needed, and delete the
+    /// snapshots beyond `settings.keep`. A snapshot that can't be deleted is logged and skipped.
+    pub async fn take_snapshot(
+        db: &Surreal<Any>,
+        settings: &SnapshotSettings,
+        clock: &impl Clock,
+    ) -> Result<SnapshotReport, IdeaError> {
+        #[derive(Deserialize)]
+        struct CountRow {
+            count: usize,
+        }
+
+        let taken_at = clock.now();
+        let dump = crate::db::server::backup_to_string(db).await?;
+        let counts: Vec<CountRow> = db.query("SELECT count() AS count FROM ideas GROUP ALL").await?.take(0)?;
+        let ideas = counts.first().map_or(0, |row| row.count);
+        let json = serde_json::to_vec_pretty(&SnapshotFile { taken_at, ideas, dump: &dump })
+            .map_err(|e| IdeaError::Database(e.to_string()))?;
+
+        let dir = &settings.dir;
+        tokio::fs::create_dir_all(dir).await.map_err(|e| io_error("create", dir, e))?;
+        let file = snapshot_file_name(taken_at.date_naive());
+        let path = dir.join(&file);
+        // Written next to the snapshot and moved over it, so a crash never leaves half a file under its name
+        let partial = dir.join(format!("{}.partial", file));
+        tokio::fs::write(&partial, &json).await.map_err(|e| io_error("write", &partial, e))?;
+        tokio::fs::rename(&partial, &path).await.map_err(|e| io_error("write", &path, e))?;
+
+        let mut names = Vec::new();
+        let mut entries = tokio::fs::read_dir(dir).await.map_err(|e| io_error("list", dir, e))?;
+        while let Some(entry) = entries.next_entry().await.map_err(|e| io_error("list", dir, e))? {
+            names.push(entry.file_name().to_string_lossy().into_owned());
+        }
+        let mut rotated = 0;
+        for name in snapshots_to_delete(&names, settings.keep) {
+            match tokio::fs::remove_file(dir.join(&name)).await {
+                Ok(()) => rotated += 1,
+                Err(error) => tracing::warn!(target: "snapshots", %error, file = %name, "couldn't delete snapshot"),
+            }
+        }
+
+        Ok(SnapshotReport {
+            file,
+            ideas,
+            bytes: json.len() as u64,
+            taken_at,
+            rotated,
+        })
+    }
+
+    /// Take a snapshot now and then every [`SNAPSHOT_INTERVAL`] until the returned task is aborted, logging each
+    /// one. A failed snapshot is logged and the next one is tried on schedule.
+    pub fn spawn_nightly(db: Surreal<Any>, snapshots: Snapshots) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(SNAPSHOT_INTERVAL);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                interval.tick().await;
+                match snapshots.take(&db, &SystemClock).await {
+                    Ok(report) => {
+                        tracing::info!(target: "snapshots", ideas = report.ideas, bytes = report.bytes, "{}", report)
+                    }
+                    Err(error) => tracing::warn!(target: "snapshots", %error, "snapshot failed"),
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_snapshot_file_names_round_trip() {
+        assert_eq!(snapshot_file_name(date(2026, 3, 7)), "ideas-2026-03-07.json");
+        assert_eq!(snapshot_date("ideas-2026-03-07.json"), Some(date(2026, 3, 7)));
+        for name in [
+            "ideas-2026-3-7.json",
+            "ideas-2026-02-30.json",
+            "ideas-2026-03-07.json.partial",
+            "notes-2026-03-07.json",
+            "ideas-latest.json",
+            "README",
+        ] {
+            assert_eq!(snapshot_date(name), None, "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_rotation_keeps_the_newest_snapshots() {
+        let names: Vec<String> = [
+            "ideas-2026-01-03.json",
+            "ideas-2025-12-31.json",
+            "notes.txt",
+            "ideas-2026-01-01.json",
+            "ideas-2026-01-02.json",
+        ]
+        .iter()
+        .map(|name| name.to_string())
+        .collect();
+        assert_eq!(snapshots_to_delete(&names, 2), ["ideas-2025-12-31.json", "ideas-2026-01-01.json"]);
+        assert_eq!(snapshots_to_delete(&names, 4), Vec::<String>::new());
+        assert_eq!(snapshots_to_delete(&names, 10), Vec::<String>::new());
+        assert_eq!(snapshots_to_delete(&names, 0).len(), 4);
+    }
+
+    #[test]
+    fn test_snapshot_settings_from_lookup() {
+        let data = Path::new("/data");
+        let defaults = SnapshotSettings::from_lookup(|_| None, data);
+        assert_eq!(defaults.dir, Path::new("/data/backups"));
+        assert_eq!(defaults.keep, DEFAULT_SNAPSHOT_KEEP);
+
+        let set = SnapshotSettings::from_lookup(
+            |key| match key {
+                SNAPSHOT_DIR_VAR => Some(" /srv/snapshots ".to_string()),
+                SNAPSHOT_KEEP_VAR => Some("3".to_string()),
+                _ => None,
+            },
+            data,
+        );
+        assert_eq!(set, SnapshotSettings { dir: PathBuf::from("/srv/snapshots"), keep: 3 });
+
+        let keep = |value: &str| SnapshotSettings::from_lookup(|_| Some(value.to_string()), data).keep;
+        assert_eq!(keep("0"), 1);
+        assert_eq!(keep("many"), DEFAULT_SNAPSHOT_KEEP);
+    }
+}
@@ -0,0 +1,477 @@
+//! How many ideas were created over time and how long each tag's ideas have sat untouched, for the stats page.
+//! Nothing leaves the server it's stored on.
+//!
+//! Like the feed, this takes creation times from the activity log, since ideas carry no timestamps of their own: an
+//! idea was created when its first event was logged. Ideas from before the log existed aren't counted. Days are
+//! UTC days. The tag report likewise takes an idea's last change from its newest logged event, falling back to
+//! `created_at`.
+
+use crate::utils::UNTAGGED;
+use chrono::{DateTime, Datelike, Days, Months, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Bars shown when the range isn't given: the last 12 weeks
+pub const DEFAULT_BUCKETS: u32 = 12;
+
+/// Most bars a histogram may ask for
+pub const MAX_BUCKETS: u32 = 366;
+
+/// Length of the periods ideas are counted over
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Bucket {
+    Day,
+    /// Weeks start on Monday
+    #[default]
+    Week,
+    Month,
+}
+
+impl Bucket {
+    pub const ALL: [Bucket; 3] = [Bucket::Day, Bucket::Week, Bucket::Month];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Bucket::Day => "day",
+            Bucket::Week => "week",
+            Bucket::Month => "month",
+        }
+    }
+
+    /// Unknown values give `None`
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|bucket| bucket.as_str() == value)
+    }
+
+    /// First day of the period `date` falls in
+    pub fn start_of(self, date: NaiveDate) -> NaiveDate {
+        match self {
+            Bucket::Day => date,
+            Bucket::Week => date - Days::new(date.weekday().num_days_from_monday().into()),
+            Bucket::Month => date.with_day(1).unwrap_or(date),
+        }
+    }
+
+    /// Start of the period `count` periods before the one starting at `start`
+    fn back(self, start: NaiveDate, count: u32) -> NaiveDate {
+        match self {
+            Bucket::Day => start - Days::new(count.into()),
+            Bucket::Week => start - Days::new(u64::from(count) * 7),
+            Bucket::Month => start - Months::new(count),
+        }
+    }
+
+    /// Short name of the period starting at `start`: "Mar 4" for days and weeks, "Mar 2024" for months
+    pub fn label(self, start: NaiveDate) -> String {
+        match self {
+            Bucket::Day | Bucket::Week => start.format("%b %-d").to_string(),
+            Bucket::Month => start.format("%b %Y").to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for Bucket {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Ideas created in one period
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistogramBar {
+    /// First day of the period
+    pub start: NaiveDate,
+    pub count: usize,
+}
+
+/// Count `created` per period over the `count` periods up to and including the one holding `today`, oldest first.
+/// Every period gets a bar, with 0 when nothing was created in it; dates outside the range are left out.
+pub fn creation_histogram(created: &[NaiveDate], bucket: Bucket, count: u32, today: NaiveDate) -> Vec<HistogramBar> {
+    let last = bucket.start_of(today);
+    let mut bars: Vec<HistogramBar> = (0..count.min(MAX_BUCKETS))
+        .rev()
+        .map(|back| HistogramBar { start: bucket.back(last, back), count: 0 })
+        .collect();
+    for date in created.iter().filter(|date| **date <= today) {
+        let start = bucket.start_of(*date);
+        if let Some(bar) = bars.iter_mut().find(|bar| bar.start == start) {
+            bar.count += 1;
+        }
+    }
+    bars
+}
+
+/// Days since the last change up to which a tag's ideas count as fresh
+pub const FRESH_DAYS: f64 = 14.0;
+
+/// Days since the last change from which a tag's ideas count as rotting
+pub const ROTTING_DAYS: f64 = 60.0;
+
+/// What the tag report needs of an idea
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TaggedIdea {
+    pub tags: Vec<String>,
+    /// When the idea last changed, if anything recorded it
+    pub changed_at: Option<DateTime<Utc>>,
+    /// Number of "what must be true" statements
+    pub statements: usize,
+}
+
+/// The ideas of one tag, in the tag report
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TagReport {
+    /// [`UNTAGGED`] for the ideas without tags
+    pub tag: String,
+    pub ideas: usize,
+    /// Ideas whose last change is known, the ones [`TagReport::average_age_days`] is taken over
+    pub dated_ideas: usize,
+    /// Average days since the ideas last changed; `None` when none of them has a known last change
+    pub average_age_days: Option<f64>,
+    /// "What must be true" statements over all the ideas. There is no done state for statements yet, so the
+    /// report can't say how many of them are settled.
+    pub statements: usize,
+}
+
+/// How long a tag's ideas have gone untouched, for coloring the report
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aging {
+    /// Changed within [`FRESH_DAYS`] on average
+    Fresh,
+    Stale,
+    /// Untouched for [`ROTTING_DAYS`] or more on average
+    Rotting,
+    /// No idea of the tag has a known last change
+    Unknown,
+}
+
+impl Aging {
+    pub fn of(average_age_days: Option<f64>) -> Self {
+        match average_age_days {
+            None => Aging::Unknown,
+            Some(days) if days <= FRESH_DAYS => Aging::Fresh,
+            Some(days) if days < ROTTING_DAYS => Aging::Stale,
+            Some(_) => Aging::Rotting,
+        }
+    }
+
+    /// Class of the report row
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Aging::Fresh => "fresh",
+            Aging::Stale => "stale",
+            Aging::Rotting => "rotting",
+            Aging::Unknown => "unknown",
+        }
+    }
+}
+
+/// One row per tag of `ideas`, A to Z. An idea with several tags counts for each of them, and ideas without tags
+/// are reported under [`UNTAGGED`]. Ages are measured up to `now` in fractional days; changes after `now` count as 0.
+pub fn tag_report(ideas: &[TaggedIdea], now: DateTime<Utc>) -> Vec<TagReport> {
+    #[derive(Default)]
+    struct Totals {
+        ideas: usize,
+        dated: usize,
+        age_days: f64,
+        statements: usize,
+    }
+
+    let untagged = [UNTAGGED.to_string()];
+    let mut totals: BTreeMap<&str, Totals> = BTreeMap::new();
+    for idea in ideas {
+        let age = idea
+            .changed_at
+            .map(|changed| (now - changed).num_seconds().max(0) as f64 / (24.0 * 60.0 * 60.0));
+        let tags = if idea.tags.is_empty() { &untagged[..] } else { &idea.tags[..] };
+        for tag in tags {
+            let total = totals.entry(tag.as_str()).or_default();
+            total.ideas += 1;
+            total.statements += idea.statements;
+            if let Some(age) = age {
+                total.dated += 1;
+                total.age_days += age;
+            }
+        }
+    }
+    totals
+        .into_iter()
+        .map(|(tag, total)| TagReport {
+            tag: tag.to_string(),
+            ideas: total.ideas,
+            dated_ideas: total.dated,
+            average_age_days: (total.dated > 0).then(|| total.age_days / total.dated as f64),
+            statements: total.statements,
+        })
+        .collect()
+}
+
+/// A column the tag report can be sorted by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TagReportColumn {
+    Tag,
+    Ideas,
+    Age,
+    Statements,
+}
+
+/// Sort `rows` by `column`, ascending unless `descending`. Tags without a known age always come last, and ties keep
+/// the A to Z order of the tags.
+pub fn sort_tag_report(rows: &mut [TagReport], column: TagReportColumn, descending: bool) {
+    use std::cmp::Ordering;
+
+    rows.sort_by(|a, b| {
+        let ordering = match column {
+            TagReportColumn::Tag => a.tag.cmp(&b.tag),
+            TagReportColumn::Ideas => a.ideas.cmp(&b.ideas),
+            TagReportColumn::Statements => a.statements.cmp(&b.statements),
+            TagReportColumn::Age => match (a.average_age_days, b.average_age_days) {
+                (Some(a), Some(b)) => a.total_cmp(&b),
+                (Some(_), None) => return Ordering::Less,
+                (None, Some(_)) => return Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            },
+        };
+        let ordering = if descending { ordering.reverse() } else { ordering };
+        ordering.then_with(|| a.tag.cmp(&b.tag))
+    });
+}
+
+#[cfg(feature = "server")]
+pub mod server {
+    use super::TaggedIdea;
+    use crate::boards::server::in_board;
+    use crate::db::server::active_ideas;
+    use crate::db::IdeaId;
+    use crate::error::IdeaError;
+    use chrono::{DateTime, NaiveDate, Utc};
+    use serde::Deserialize;
+    use std::collections::HashMap;
+    use surrealdb::{sql::Thing, Surreal};
+
+    /// The day each of `owner`'s ideas outside the trash was created, in no particular order. Only ideas on `board`
+    /// count when it is given.
+    pub async fn creation_dates(
+        db: &Surreal<surrealdb::engine::any::Any>,
+        owner: Option<&Thing>,
+        board: Option<&Thing>,
+    ) -> Result<Vec<NaiveDate>, IdeaError> {
+        #[derive(Deserialize)]
+        struct EventTime {
+            idea: Thing,
+            at: DateTime<Utc>,
+        }
+
+        let ids: Vec<Thing> = active_ideas(db, owner)
+            .await?
+            .into_iter()
+            .filter(|record| board.is_none_or(|board| in_board(record, board)))
+            .filter_map(|record| record.id)
+            .collect();
+        let times: Vec<EventTime> = db
+            .query("SELECT idea, at FROM idea_events WHERE idea INSIDE $ideas")
+            .bind(("ideas", ids))
+            .await?
+            .take(0)?;
+
+        let mut first: HashMap<String, DateTime<Utc>> = HashMap::new();
+        for time in times {
+            let at = first.entry(IdeaId::from(&time.idea).into()).or_insert(time.at);
+            *at = (*at).min(time.at);
+        }
+        Ok(first.into_values().map(|at| at.date_naive()).collect())
+    }
+
+    /// `owner`'s ideas outside the trash as the tag report sees them, in two queries: the ideas, and the times of
+    /// their logged events. Only ideas on `board` count when it is given.
+    pub async fn tagged_ideas(
+        db: &Surreal<surrealdb::engine::any::Any>,
+        owner: Option<&Thing>,
+        board: Option<&Thing>,
+    ) -> Result<Vec<TaggedIdea>, IdeaError> {
+        #[derive(Deserialize)]
+        struct EventTime {
+            idea: Thing,
+            at: DateTime<Utc>,
+        }
+
+        let ideas: Vec<_> = active_ideas(db, owner)
+            .await?
+            .into_iter()
+            .filter(|record| board.is_none_or(|board| in_board(record, board)))
+            .collect();
+        let ids: Vec<Thing> = ideas.iter().filter_map(|record| record.id.clone()).collect();
+        let times: Vec<EventTime> = db
+            .query("SELECT idea, at FROM idea_events WHERE idea INSIDE $ideas")
+            .bind(("ideas", ids))
+            .await?
+            .take(0)?;
+
+        let mut last: HashMap<String, DateTime<Utc>> = HashMap::new();
+        for time in times {
+            let at = last.entry(IdeaId::from(&time.idea).into()).or_insert(time.at);
+            *at = (*at).max(time.at);
+        }
+        Ok(ideas
+            .into_iter()
+            .map(|record| {
+                let logged = record.id.as_ref().and_then(|id| last.get(&String::from(IdeaId::from(id))).copied());
+                TaggedIdea {
+                    changed_at: logged.max(record.created_at),
+                    statements: record.what_must_be_true.len(),
+                    tags: record.tags,
+                }
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(text: &str) -> NaiveDate {
+        text.parse().unwrap()
+    }
+
+    fn counts(bars: &[HistogramBar]) -> Vec<usize> {
+        bars.iter().map(|bar| bar.count).collect()
+    }
+
+    #[test]
+    fn test_start_of_period() {
+        // 2024-03-06 is a Wednesday
+        assert_eq!(Bucket::Day.start_of(date("2024-03-06")), date("2024-03-06"));
+        assert_eq!(Bucket::Week.start_of(date("2024-03-06")), date("2024-03-04"));
+        assert_eq!(Bucket::Week.start_of(date("2024-03-04")), date("2024-03-04"));
+        assert_eq!(Bucket::Week.start_of(date("2024-03-10")), date("2024-03-04"));
+        assert_eq!(Bucket::Month.start_of(date("2024-03-31")), date("2024-03-01"));
+    }
+
+    #[test]
+    fn test_empty_periods_get_zeros() {
+        let bars = creation_histogram(&[], Bucket::Week, 12, date("2024-03-06"));
+        assert_eq!(bars.len(), 12);
+        assert!(bars.iter().all(|bar| bar.count == 0));
+        assert_eq!(bars[0].start, date("2023-12-18"));
+        assert_eq!(bars[11].start, date("2024-03-04"));
+    }
+
+    #[test]
+    fn test_ideas_are_counted_in_their_period() {
+        let created = [
+            date("2024-03-06"),
+            date("2024-03-04"),
+            date("2024-03-03"),
+            date("2024-02-20"),
+            // Before the range and after today
+            date("2023-01-01"),
+            date("2024-03-07"),
+        ];
+        let bars = creation_histogram(&created, Bucket::Week, 4, date("2024-03-06"));
+        assert_eq!(counts(&bars), vec![0, 1, 1, 2]);
+
+        let bars = creation_histogram(&created, Bucket::Day, 3, date("2024-03-06"));
+        assert_eq!(counts(&bars), vec![1, 0, 1]);
+        assert_eq!(bars[0].start, date("2024-03-04"));
+    }
+
+    #[test]
+    fn test_months_cross_years_and_short_months() {
+        let created = [date("2024-01-31"), date("2023-12-01"), date("2024-02-29")];
+        let bars = creation_histogram(&created, Bucket::Month, 4, date("2024-03-31"));
+        let starts: Vec<NaiveDate> = bars.iter().map(|bar| bar.start).collect();
+        assert_eq!(
+            starts,
+            vec![date("2023-12-01"), date("2024-01-01"), date("2024-02-01"), date("2024-03-01")]
+        );
+        assert_eq!(counts(&bars), vec![1, 1, 1, 0]);
+    }
+
+    #[test]
+    fn test_range_is_capped() {
+        assert!(creation_histogram(&[], Bucket::Day, 0, date("2024-03-06")).is_empty());
+        assert_eq!(creation_histogram(&[], Bucket::Day, 10_000, date("2024-03-06")).len(), MAX_BUCKETS as usize);
+    }
+
+    fn tagged(tags: &[&str], changed_at: Option<&str>, statements: usize) -> TaggedIdea {
+        TaggedIdea {
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            changed_at: changed_at.map(|at| at.parse().unwrap()),
+            statements,
+        }
+    }
+
+    #[test]
+    fn test_tag_report_averages_over_dated_ideas_only() {
+        let now = "2024-03-11T00:00:00Z".parse().unwrap();
+        let ideas = [
+            tagged(&["ux", "api"], Some("2024-03-01T00:00:00Z"), 2),
+            tagged(&["ux"], Some("2024-03-10T12:00:00Z"), 1),
+            tagged(&["ux"], None, 3),
+            tagged(&["api"], None, 0),
+            tagged(&[], Some("2024-03-12T00:00:00Z"), 1),
+        ];
+        let report = tag_report(&ideas, now);
+        let tags: Vec<&str> = report.iter().map(|row| row.tag.as_str()).collect();
+        assert_eq!(tags, vec!["api", UNTAGGED, "ux"]);
+
+        assert_eq!(report[0].ideas, 2);
+        assert_eq!(report[0].dated_ideas, 1);
+        assert_eq!(report[0].average_age_days, Some(10.0));
+        assert_eq!(report[0].statements, 2);
+        // Changed after `now`
+        assert_eq!(report[1].average_age_days, Some(0.0));
+        // (10 + 0.5) / 2, the undated idea left out
+        assert_eq!(report[2].ideas, 3);
+        assert_eq!(report[2].dated_ideas, 2);
+        assert_eq!(report[2].average_age_days, Some(5.25));
+        assert_eq!(report[2].statements, 6);
+
+        let undated = tag_report(&[tagged(&["old"], None, 0)], now);
+        assert_eq!(undated[0].average_age_days, None);
+        assert!(tag_report(&[], now).is_empty());
+    }
+
+    #[test]
+    fn test_aging_thresholds() {
+        assert_eq!(Aging::of(None), Aging::Unknown);
+        assert_eq!(Aging::of(Some(0.0)), Aging::Fresh);
+        assert_eq!(Aging::of(Some(FRESH_DAYS)), Aging::Fresh);
+        assert_eq!(Aging::of(Some(FRESH_DAYS + 0.1)), Aging::Stale);
+        assert_eq!(Aging::of(Some(ROTTING_DAYS)), Aging::Rotting);
+    }
+
+    #[test]
+    fn test_sorting_the_tag_report() {
+        let row = |tag: &str, ideas: usize, age: Option<f64>| TagReport {
+            tag: tag.to_string(),
+            ideas,
+            dated_ideas: usize::from(age.is_some()),
+            average_age_days: age,
+            statements: 0,
+        };
+        let mut rows = vec![row("b", 2, Some(3.0)), row("a", 2, None), row("c", 5, Some(40.0))];
+        let tags = |rows: &[TagReport]| rows.iter().map(|row| row.tag.clone()).collect::<Vec<_>>();
+
+        sort_tag_report(&mut rows, TagReportColumn::Age, true);
+        assert_eq!(tags(&rows), vec!["c", "b", "a"]);
+        sort_tag_report(&mut rows, TagReportColumn::Age, false);
+        assert_eq!(tags(&rows), vec!["b", "c", "a"]);
+        // Ties fall back to the tag
+        sort_tag_report(&mut rows, TagReportColumn::Ideas, false);
+        assert_eq!(tags(&rows), vec!["a", "b", "c"]);
+        sort_tag_report(&mut rows, TagReportColumn::Ideas, true);
+        assert_eq!(tags(&rows), vec!["c", "a", "b"]);
+        sort_tag_report(&mut rows, TagReportColumn::Tag, true);
+        assert_eq!(tags(&rows), vec!["c", "b", "a"]);
+    }
+
+    #[test]
+    fn test_labels() {
+        assert_eq!(Bucket::Week.label(date("2024-03-04")), "Mar 4");
+        assert_eq!(Bucket::Month.label(date("2024-03-01")), "Mar 2024");
+        assert_eq!(Bucket::parse("month"), Some(Bucket::Month));
+        assert_eq!(Bucket::parse("year"), None);
+    }
+}
@@ -0,0 +1,186 @@
+//! Small key-value storage abstraction for client-side persistence (drafts, preferences, ...).
+//!
+//! On web the data lives in `localStorage`. Everywhere else (desktop, tests) it falls back to an in-memory map
+//! shared by the whole process, so the same component code works on every platform.
+
+use serde::{de::DeserializeOwned, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// A string key-value store
+pub trait Storage {
+    /// Read the raw string stored under `key`
+    fn get_raw(&self, key: &str) -> Option<String>;
+
+    /// Store a raw string under `key`, replacing any previous value
+    fn set_raw(&self, key: &str, value: &str);
+
+    /// Remove `key` from the store
+    fn remove(&self, key: &str);
+
+    /// Read and deserialize a JSON value. Missing keys and malformed JSON both return `None`.
+    fn get<T: DeserializeOwned>(&self, key: &str) -> Option<T>
+    where
+        Self: Sized,
+    {
+        self.get_raw(key)
+            .and_then(|raw| serde_json::from_str(&raw).ok())
+    }
+
+    /// Serialize a value as JSON and store it
+    fn set<T: Serialize>(&self, key: &str, value: &T)
+    where
+        Self: Sized,
+    {
+        if let Ok(raw) = serde_json::to_string(value) {
+            self.set_raw(key, &raw);
+        }
+    }
+}
+
+/// In-memory storage, used on desktop and in tests
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStorage {
+    entries: Arc<Mutex<HashMap<String, String>>>,
+}
+
+impl MemoryStorage {
+    /// Create an empty, independent store
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The process-wide store used by [`default_storage`] on non-web platforms
+    pub fn shared() -> Self {
+        static SHARED: OnceLock<MemoryStorage> = OnceLock::new();
+        SHARED.get_or_init(MemoryStorage::new).clone()
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn get_raw(&self, key: &str) -> Option<String> {
+        self.entries.lock().ok()?.get(key).cloned()
+    }
+
+    fn set_raw(&self, key: &str, value: &str) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.insert(key.to_string(), value.to_string());
+        }
+    }
+
+    fn remove(&self, key: &str) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.remove(key);
+        }
+    }
+}
+
+/// Browser `localStorage`. Every operation silently does nothing when storage is unavailable
+/// (private browsing, disabled cookies, ...).
+#[cfg(target_arch = "wasm32")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LocalStorage;
+
+#[cfg(target_arch = "wasm32")]
+impl LocalStorage {
+    fn inner() -> Option<web_sys::Storage> {
+        web_sys::window()?.local_storage().ok()?
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Storage for LocalStorage {
+    fn get_raw(&self, key: &str) -> Option<String> {
+        Self::inner()?.get_item(key).ok()?
+    }
+
+    fn set_raw(&self, key: &str, value: &str) {
+        if let Some(storage) = Self::inner() {
+            let _ = storage.set_item(key, value);
+        }
+    }
+
+    fn remove(&self, key: &str) {
+        if let Some(storage) = Self::inner() {
+            let _ = storage.remove_item(key);
+        }
+    }
+}
+
+/// The storage backend for the current platform
+#[cfg(target_arch = "wasm32")]
+pub fn default_storage() -> LocalStorage {
+    LocalStorage
+}
+
+/// The storage backend for the current platform
+#[cfg(not(target_arch = "wasm32"))]
+pub fn default_storage() -> MemoryStorage {
+    MemoryStorage::shared()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct Draft {
+        title: String,
+        body: String,
+    }
+
+    #[test]
+    fn test_raw_roundtrip() {
+        let storage = MemoryStorage::new();
+        assert_eq!(storage.get_raw("key"), None);
+
+        storage.set_raw("key", "value");
+        assert_eq!(storage.get_raw("key"), Some("value".to_string()));
+
+        storage.set_raw("key", "other");
+        assert_eq!(storage.get_raw("key"), Some("other".to_string()));
+
+        storage.remove("key");
+        assert_eq!(storage.get_raw("key"), None);
+    }
+
+    #[test]
+    fn test_json_roundtrip() {
+        let storage = MemoryStorage::new();
+        let draft = Draft {
+            title: "Title".to_string(),
+            body: "Body with \"quotes\"".to_string(),
+        };
+
+        storage.set("draft", &draft);
+        assert_eq!(storage.get::<Draft>("draft"), Some(draft));
+    }
+
+    #[test]
+    fn test_malformed_json_is_ignored() {
+        let storage = MemoryStorage::new();
+        storage.set_raw("draft", "{not json");
+
+        assert_eq!(storage.get::<Draft>("draft"), None);
+    }
+
+    #[test]
+    fn test_independent_stores_do_not_share_entries() {
+        let a = MemoryStorage::new();
+        let b = MemoryStorage::new();
+        a.set_raw("key", "value");
+
+        assert_eq!(b.get_raw("key"), None);
+    }
+
+    #[test]
+    fn test_shared_store_is_shared() {
+        MemoryStorage::shared().set_raw("shared-test-key", "value");
+        assert_eq!(
+            MemoryStorage::shared().get_raw("shared-test-key"),
+            Some("value".to_string())
+        );
+        MemoryStorage::shared().remove("shared-test-key");
+    }
+}
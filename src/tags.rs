@@ -0,0 +1,215 @@
+//! Tag colors.
+//!
+//! Every tag gets a color from a fixed palette by hashing its name, so the same tag looks the same everywhere and on
+//! every run. A color can be pinned per tag (so "bug" is always red); pins live in the `tag_meta` table and win over
+//! the hash. Chips carry the color name in a `data-color` attribute and the stylesheet maps it to a background and
+//! foreground pair for the current theme.
+//!
+//! Also counts how many ideas carry each tag, for the navbar's list of the most used ones.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Names of the palette colors, in the order the hash picks from
+pub const TAG_PALETTE: [&str; 8] = ["red", "orange", "amber", "green", "teal", "blue", "violet", "pink"];
+
+/// Tags are colored case-insensitively and without surrounding whitespace
+fn normalize(tag: &str) -> String {
+    tag.trim().to_lowercase()
+}
+
+/// The palette color for `tag` when it has no pinned color.
+///
+/// Uses FNV-1a rather than the standard library's hasher, whose output is randomized per process.
+pub fn tag_color(tag: &str) -> &'static str {
+    let hash = normalize(tag)
+        .bytes()
+        .fold(0x811c_9dc5_u32, |hash, byte| (hash ^ u32::from(byte)).wrapping_mul(0x0100_0193));
+    TAG_PALETTE[hash as usize % TAG_PALETTE.len()]
+}
+
+/// Whether `color` is one of the palette colors
+pub fn is_palette_color(color: &str) -> bool {
+    TAG_PALETTE.contains(&color)
+}
+
+/// Pinned tag colors, keyed by normalized tag
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TagColors {
+    pub overrides: HashMap<String, String>,
+}
+
+impl TagColors {
+    /// The color to render `tag` with: its pinned color if there is a valid one, otherwise the hashed color
+    pub fn color_for(&self, tag: &str) -> &'static str {
+        self.overrides
+            .get(&normalize(tag))
+            .and_then(|color| TAG_PALETTE.iter().find(|candidate| **candidate == color.as_str()))
+            .copied()
+            .unwrap_or_else(|| tag_color(tag))
+    }
+
+    /// The pinned color of `tag`, if any
+    pub fn pinned(&self, tag: &str) -> Option<&str> {
+        self.overrides.get(&normalize(tag)).map(String::as_str)
+    }
+}
+
+/// Tags listed in the navbar's tag menu
+pub const TOP_TAGS: usize = 5;
+
+/// How many ideas carry a tag
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TagCount {
+    pub tag: String,
+    pub count: usize,
+}
+
+/// The `limit` tags carried by the most ideas, given each idea's tags; ties go alphabetically. Tags are compared
+/// trimmed, like the tag filter does, and a tag listed twice on one idea counts once.
+pub fn top_tags<'a>(ideas: impl IntoIterator<Item = &'a [String]>, limit: usize) -> Vec<TagCount> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for tags in ideas {
+        let mut seen: Vec<&str> = Vec::new();
+        for tag in tags.iter().map(|tag| tag.trim()).filter(|tag| !tag.is_empty()) {
+            if !seen.contains(&tag) {
+                seen.push(tag);
+                *counts.entry(tag).or_default() += 1;
+            }
+        }
+    }
+
+    let mut counts: Vec<TagCount> = counts
+        .into_iter()
+        .map(|(tag, count)| TagCount {
+            tag: tag.to_string(),
+            count,
+        })
+        .collect();
+    counts.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.tag.cmp(&b.tag)));
+    counts.truncate(limit);
+    counts
+}
+
+// Server-only storage of pinned colors
+#[cfg(feature = "server")]
+pub mod server {
+    use super::*;
+    use crate::error::IdeaError;
+    use surrealdb::Surreal;
+
+    /// A pinned color as stored in the `tag_meta` table
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct TagMetaRow {
+        tag: String,
+        color: String,
+    }
+
+    /// Every pinned tag color
+    pub async fn tag_colors(db: &Surreal<surrealdb::engine::any::Any>) -> Result<TagColors, IdeaError> {
+        let rows: Vec<TagMetaRow> = db.query("SELECT tag, color FROM tag_meta").await?.take(0)?;
+        Ok(TagColors {
+            overrides: rows.into_iter().map(|row| (row.tag, row.color)).collect(),
+        })
+    }
+
+    /// Pin `tag` to `color`, or unpin it with `None` so it goes back to its hashed color
+    pub async fn set_tag_color(
+        db: &Surreal<surrealdb::engine::any::Any>,
+        tag: &str,
+        color: Option<&str>,
+    ) -> Result<(), IdeaError> {
+        let tag = normalize(tag);
+        if tag.is_empty() {
+            return Err(IdeaError::Validation("The tag is empty".to_string()));
+        }
+
+        match color {
+            Some(color) if !is_palette_color(color) => {
+                Err(IdeaError::Validation(format!("{} is not a tag color", color)))
+            }
+            Some(color) => {
+                db.query("UPSERT type::thing('tag_meta', $tag) SET tag = $tag, color = $color")
+                    .bind(("tag", tag))
+                    .bind(("color", color.to_string()))
+                    .await?
+                    .check()?;
+                Ok(())
+            }
+            None => {
+                db.query("DELETE type::thing('tag_meta', $tag)")
+                    .bind(("tag", tag))
+                    .await?
+                    .check()?;
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tag_color_is_stable() {
+        // Fixed expectations catch an accidental change of hash function or palette order, which would recolor
+        // every tag for every user
+        assert_eq!(tag_color("bug"), "blue");
+        assert_eq!(tag_color("ux"), "red");
+        assert_eq!(tag_color("idea"), "amber");
+    }
+
+    #[test]
+    fn test_tag_color_ignores_case_and_whitespace() {
+        assert_eq!(tag_color(" Bug "), tag_color("bug"));
+    }
+
+    #[test]
+    fn test_tags_spread_over_the_palette() {
+        let used: std::collections::HashSet<&str> =
+            (0..100).map(|index| tag_color(&format!("tag-{}", index))).collect();
+        assert!(used.len() > TAG_PALETTE.len() / 2);
+        assert!(used.iter().all(|color| is_palette_color(color)));
+    }
+
+    #[test]
+    fn test_override_wins_over_hash() {
+        let mut colors = TagColors::default();
+        let hashed = tag_color("bug");
+        let pinned = TAG_PALETTE.iter().find(|color| **color != hashed).unwrap();
+        colors.overrides.insert("bug".to_string(), pinned.to_string());
+
+        assert_eq!(colors.color_for("Bug"), *pinned);
+        assert_eq!(colors.pinned(" BUG"), Some(*pinned));
+        // Other tags keep their hashed color
+        assert_eq!(colors.color_for("feature"), tag_color("feature"));
+    }
+
+    #[test]
+    fn test_invalid_override_falls_back_to_hash() {
+        let mut colors = TagColors::default();
+        colors.overrides.insert("bug".to_string(), "chartreuse".to_string());
+
+        assert_eq!(colors.color_for("bug"), tag_color("bug"));
+    }
+
+    #[test]
+    fn test_top_tags() {
+        let ideas: Vec<Vec<String>> = [vec!["ux", "bug"], vec![" bug", "bug"], vec!["api", "ux", "bug"], vec!["", "zz"]]
+            .into_iter()
+            .map(|tags| tags.into_iter().map(String::from).collect())
+            .collect();
+        let count = |tag: &str, count| TagCount {
+            tag: tag.to_string(),
+            count,
+        };
+
+        assert_eq!(
+            top_tags(ideas.iter().map(Vec::as_slice), 3),
+            vec![count("bug", 3), count("ux", 2), count("api", 1)]
+        );
+        assert_eq!(top_tags(ideas.iter().map(Vec::as_slice), TOP_TAGS).len(), 4);
+        assert!(top_tags(std::iter::empty(), TOP_TAGS).is_empty());
+    }
+}
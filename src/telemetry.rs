@@ -0,0 +1,139 @@
+//! Server logs.
+//!
+//! [`init`] installs the subscriber once at startup. Server function bodies run inside [`traced!`], which opens a
+//! `server_fn` span carrying the operation and the idea id, logs how long the call took at debug level and logs a
+//! failure at warn level with the structured [`IdeaError`] when there is one.
+
+use crate::error::IdeaError;
+use dioxus::prelude::ServerFnError;
+use std::future::Future;
+use std::time::Instant;
+use tracing::Instrument;
+use tracing_subscriber::EnvFilter;
+
+/// Filter used when `RUST_LOG` isn't set
+const DEFAULT_FILTER: &str = "info";
+
+/// Install the global subscriber: log lines on stderr, filtered by `RUST_LOG` with the usual `EnvFilter` syntax.
+/// `RUST_LOG=dioxus_surrealdb_template=debug` adds the duration of every server function call.
+pub fn init() {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(DEFAULT_FILTER));
+    // Only fails when a subscriber is already installed, which is fine to keep
+    let _ = tracing_subscriber::fmt().with_env_filter(filter).try_init();
+}
+
+/// Run a server function body in a span named after `operation`, logging its duration and any failure.
+/// [`traced!`] is the shorthand server functions use.
+pub async fn run_traced<T>(
+    operation: &'static str,
+    idea_id: Option<String>,
+    body: impl Future<Output = Result<T, ServerFnError>>,
+) -> Result<T, ServerFnError> {
+    let span = tracing::info_span!("server_fn", operation, idea_id = idea_id.as_deref());
+    async move {
+        let started = Instant::now();
+        let result = body.await;
+        let elapsed_ms = started.elapsed().as_millis() as u64;
+
+        let idea_id = idea_id.as_deref();
+        match &result {
+            Ok(_) => tracing::debug!(operation, idea_id, elapsed_ms, "{} done", operation),
+            Err(error) => match IdeaError::from_error(error) {
+                Some(error) => tracing::warn!(operation, idea_id, elapsed_ms, ?error, "{} failed", operation),
+                None => tracing::warn!(operation, idea_id, elapsed_ms, %error, "{} failed", operation),
+            },
+        }
+        result
+    }
+    .instrument(span)
+    .await
+}
+
+/// Wrap a server function body in [`run_traced`]: `traced!("delete_idea", id, { ... })` for calls about one idea,
+/// `traced!("list_ideas", { ... })` for the others. The body becomes an `async move` block, so `?` and `return`
+/// work as they did in the function. Its [`ServerFnError`] is converted into the error type the function declares.
+macro_rules! traced {
+    ($operation:literal, $idea_id:expr, { $($body:tt)* }) => {
+        $crate::telemetry::run_traced($operation, Some(($idea_id).to_string()), async move { $($body)* })
+            .await
+            .map_err(Into::into)
+    };
+    ($operation:literal, { $($body:tt)* }) => {
+        $crate::telemetry::run_traced($operation, None, async move { $($body)* }).await.map_err(Into::into)
+    };
+}
+pub(crate) use traced;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::server::{trash_idea, MockIdeaRepository};
+    use std::collections::BTreeMap;
+    use std::sync::{Arc, Mutex};
+    use tracing::field::{Field, Visit};
+    use tracing::{Event, Level, Subscriber};
+    use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+
+    /// Fields of an event, with strings unquoted
+    #[derive(Default)]
+    struct Fields(BTreeMap<String, String>);
+
+    impl Visit for Fields {
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.0.insert(field.name().to_string(), value.to_string());
+        }
+
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            self.0.insert(field.name().to_string(), format!("{:?}", value));
+        }
+    }
+
+    type CapturedEvent = (Level, BTreeMap<String, String>);
+
+    /// Layer keeping every event it sees
+    #[derive(Clone, Default)]
+    struct Captured(Arc<Mutex<Vec<CapturedEvent>>>);
+
+    impl<S: Subscriber> Layer<S> for Captured {
+        fn on_event(&self, event: &Event<'_>, _: Context<'_, S>) {
+            let mut fields = Fields::default();
+            event.record(&mut fields);
+            self.0.lock().unwrap().push((*event.metadata().level(), fields.0));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failing_delete_warns_with_the_idea_id() {
+        let captured = Captured::default();
+        let _guard = tracing::subscriber::set_default(tracing_subscriber::registry().with(captured.clone()));
+
+        let repo = MockIdeaRepository::new();
+        let id = "ideas:gone".to_string();
+        let result: Result<(), ServerFnError> = traced!("delete_idea", id, {
+            trash_idea(&repo, &id, None).await?;
+            Ok(())
+        });
+        assert!(result.is_err());
+
+        let events = captured.0.lock().unwrap();
+        let (_, fields) = events.iter().find(|(level, _)| *level == Level::WARN).unwrap();
+        assert_eq!(fields["idea_id"], "ideas:gone");
+        assert_eq!(fields["operation"], "delete_idea");
+        assert_eq!(fields["error"], "NotFound(\"ideas:gone\")");
+    }
+
+    #[tokio::test]
+    async fn test_successful_calls_log_their_duration_at_debug() {
+        let captured = Captured::default();
+        let _guard = tracing::subscriber::set_default(tracing_subscriber::registry().with(captured.clone()));
+
+        let result: Result<_, ServerFnError> = traced!("count_ideas", { Ok(3) });
+        assert_eq!(result.unwrap(), 3);
+
+        let events = captured.0.lock().unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].0, Level::DEBUG);
+        assert!(events[0].1.contains_key("elapsed_ms"));
+        assert!(!events[0].1.contains_key("idea_id"));
+    }
+}
@@ -0,0 +1,357 @@
+//! Idea templates: a description skeleton plus default tags and "what must be true" statements that prefill the
+//! submission form.
+//!
+//! Two built-in templates are created by `define_schema` and can't be changed; everyone can add their own. Filling
+//! the form is decided by [`plan_template`], which never replaces text the user typed without asking.
+
+use crate::utils::normalize_newlines;
+use serde::{Deserialize, Serialize};
+
+/// A template as sent to the client
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct IdeaTemplate {
+    pub id: String,
+    pub name: String,
+    pub description_skeleton: String,
+    pub default_tags: Vec<String>,
+    pub default_wmbt: Vec<String>,
+    /// Shipped with the app rather than created by a user; read-only
+    pub builtin: bool,
+}
+
+/// The form fields a template touches
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TemplateFields {
+    pub description: String,
+    /// Comma-separated, as typed in the form
+    pub tags_input: String,
+}
+
+/// What picking a template would do to the form
+#[derive(Debug, Clone, PartialEq)]
+pub struct TemplatePlan {
+    /// The form after applying the template
+    pub fields: TemplateFields,
+    /// Applying would replace a description the user typed, so ask first.
+    /// Declining should still apply [`TemplatePlan::keep_description`].
+    pub needs_confirmation: bool,
+    /// The form with the template's tags added but the user's description kept
+    pub keep_description: TemplateFields,
+}
+
+/// Parse comma-separated tags, dropping blanks
+pub fn parse_tags(tags_input: &str) -> Vec<String> {
+    tags_input
+        .split(',')
+        .map(|tag| tag.trim().to_string())
+        .filter(|tag| !tag.is_empty())
+        .collect()
+}
+
+/// Work out how to fill the form with `template`.
+///
+/// A field counts as untouched when it is blank or still holds exactly what `previous` (the template picked before,
+/// if any) put there; untouched fields are simply replaced. Typed tags are never dropped, the template's are added
+/// after them. A typed description is only replaced after confirmation.
+pub fn plan_template(
+    current: &TemplateFields,
+    previous: Option<&IdeaTemplate>,
+    template: &IdeaTemplate,
+) -> TemplatePlan {
+    // Compared with line endings normalized, as the textarea hands back `\n` whatever the template holds
+    let previous_skeleton = previous.map(|previous| normalize_newlines(&previous.description_skeleton));
+    let description = normalize_newlines(&current.description);
+    let description_untouched =
+        description.trim().is_empty() || Some(description.trim()) == previous_skeleton.as_deref().map(str::trim);
+
+    let current_tags = parse_tags(&current.tags_input);
+    let tags_untouched = current_tags.is_empty() || previous.map(|previous| &previous.default_tags) == Some(&current_tags);
+    let tags = if tags_untouched {
+        template.default_tags.clone()
+    } else {
+        let mut tags = current_tags;
+        for tag in &template.default_tags {
+            if !tags.iter().any(|existing| existing.eq_ignore_ascii_case(tag)) {
+                tags.push(tag.clone());
+            }
+        }
+        tags
+    };
+    let tags_input = tags.join(", ");
+
+    let keep_description = TemplateFields {
+        description: current.description.clone(),
+        tags_input: tags_input.clone(),
+    };
+    let fields = TemplateFields {
+        description: template.description_skeleton.clone(),
+        tags_input,
+    };
+
+    TemplatePlan {
+        fields,
+        needs_confirmation: !description_untouched,
+        keep_description,
+    }
+}
+
+// Server-only storage of templates
+#[cfg(feature = "server")]
+pub mod server {
+    use super::*;
+    use crate::auth::server::ensure_owner;
+    use crate::error::IdeaError;
+    use surrealdb::sql::Thing;
+    use surrealdb::Surreal;
+
+    /// Template as stored in the database
+    #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+    pub struct TemplateRecord {
+        pub id: Option<Thing>,
+        pub name: String,
+        #[serde(default)]
+        pub description_skeleton: String,
+        #[serde(default)]
+        pub default_tags: Vec<String>,
+        #[serde(default)]
+        pub default_wmbt: Vec<String>,
+        /// User who created the template. `None` for built-ins and anonymous templates.
+        #[serde(default)]
+        pub owner: Option<Thing>,
+        #[serde(default)]
+        pub builtin: bool,
+    }
+
+    impl From<TemplateRecord> for IdeaTemplate {
+        fn from(record: TemplateRecord) -> Self {
+            IdeaTemplate {
+                id: record.id.map(|thing| thing.to_string()).unwrap_or_default(),
+                name: record.name,
+                description_skeleton: record.description_skeleton,
+                default_tags: record.default_tags,
+                default_wmbt: record.default_wmbt,
+                builtin: record.builtin,
+            }
+        }
+    }
+
+    /// Check a template before it is stored
+    fn validate_template(name: &str) -> Result<(), IdeaError> {
+        if name.trim().is_empty() {
+            return Err(IdeaError::Validation("The template needs a name".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Editable content of a template
+    #[derive(Debug, Clone, Default)]
+    pub struct TemplateInput {
+        pub name: String,
+        pub description_skeleton: String,
+        pub default_tags: Vec<String>,
+        pub default_wmbt: Vec<String>,
+    }
+
+    fn builtin(key: &str, name: &str, skeleton: &str, tags: &[&str], wmbt: &[&str]) -> TemplateRecord {
+        TemplateRecord {
+            id: Some(Thing::from(("templates", key))),
+            name: name.to_string(),
+            description_skeleton: skeleton.to_string(),
+            default_tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            default_wmbt: wmbt.iter().map(|statement| statement.to_string()).collect(),
+            owner: None,
+            builtin: true,
+        }
+    }
+
+    /// The templates shipped with the app
+    pub fn builtin_templates() -> Vec<TemplateRecord> {
+        vec![
+            builtin(
+                "problem_proposal",
+                "Problem / Proposal / Risks",
+                "Problem:\n\nProposal:\n\nRisks:\n",
+                &["proposal"],
+                &["The problem is real and painful", "The proposal is feasible"],
+            ),
+            builtin(
+                "experiment",
+                "Experiment",
+                "Hypothesis:\n\nHow to test it:\n\nWhat would prove it wrong:\n",
+                &["experiment"],
+                &["The result can be measured"],
+            ),
+        ]
+    }
+
+    /// Create the built-in templates that don't exist yet. Safe to run on every startup.
+    pub async fn seed_builtin_templates(db: &Surreal<surrealdb::engine::any::Any>) -> surrealdb::Result<()> {
+        db.query("INSERT IGNORE INTO templates $templates")
+            .bind(("templates", builtin_templates()))
+            .await?
+            .check()?;
+        Ok(())
+    }
+
+    /// Built-in templates followed by those created by `owner`, each sorted by name
+    pub async fn list_templates(
+        db: &Surreal<surrealdb::engine::any::Any>,
+        owner: Option<&Thing>,
+    ) -> Result<Vec<TemplateRecord>, IdeaError> {
+        let templates: Vec<TemplateRecord> = db
+            .query(
+                "SELECT * FROM templates WHERE builtin = true OR (builtin = false AND owner = $owner) \
+                 ORDER BY builtin DESC, name ASC",
+            )
+            .bind(("owner", owner.cloned()))
+            .await?
+            .take(0)?;
+        Ok(templates)
+    }
+
+    pub async fn create_template(
+        db: &Surreal<surrealdb::engine::any::Any>,
+        owner: Option<Thing>,
+        input: TemplateInput,
+    ) -> Result<TemplateRecord, IdeaError> {
+        validate_template(&input.name)?;
+
+        let record = TemplateRecord {
+            id: None,
+            name: input.name.trim().to_string(),
+            description_skeleton: input.description_skeleton,
+            default_tags: input.default_tags,
+            default_wmbt: input.default_wmbt,
+            owner,
+            builtin: false,
+        };
+        let created: Option<TemplateRecord> = db.create("templates").content(record).await?;
+        created.ok_or_else(|| IdeaError::Database("Failed to create template".to_string()))
+    }
+
+    /// Load a template `user` may change: their own, never a built-in
+    async fn load_editable(
+        db: &Surreal<surrealdb::engine::any::Any>,
+        id: &str,
+        user: Option<&Thing>,
+    ) -> Result<(String, String, TemplateRecord), IdeaError> {
+        let (table, key) = id
+            .split_once(':')
+            .filter(|(table, _)| *table == "templates")
+            .ok_or_else(|| IdeaError::InvalidId(id.to_string()))?;
+
+        let template: Option<TemplateRecord> = db.select((table, key)).await?;
+        let template = template.ok_or_else(|| IdeaError::NotFound(id.to_string()))?;
+        if template.builtin {
+            return Err(IdeaError::Validation("Built-in templates can't be changed".to_string()));
+        }
+        ensure_owner(template.owner.as_ref(), user)?;
+        Ok((table.to_string(), key.to_string(), template))
+    }
+
+    pub async fn update_template(
+        db: &Surreal<surrealdb::engine::any::Any>,
+        id: &str,
+        user: Option<&Thing>,
+        input: TemplateInput,
+    ) -> Result<TemplateRecord, IdeaError> {
+        validate_template(&input.name)?;
+        let (table, key, existing) = load_editable(db, id, user).await?;
+
+        let record = TemplateRecord {
+            id: None,
+            name: input.name.trim().to_string(),
+            description_skeleton: input.description_skeleton,
+            default_tags: input.default_tags,
+            default_wmbt: input.default_wmbt,
+            owner: existing.owner,
+            builtin: false,
+        };
+        let updated: Option<TemplateRecord> = db.update((table.as_str(), key.as_str())).content(record).await?;
+        updated.ok_or_else(|| IdeaError::NotFound(id.to_string()))
+    }
+
+    pub async fn delete_template(
+        db: &Surreal<surrealdb::engine::any::Any>,
+        id: &str,
+        user: Option<&Thing>,
+    ) -> Result<(), IdeaError> {
+        let (table, key, _) = load_editable(db, id, user).await?;
+        let _deleted: Option<TemplateRecord> = db.delete((table.as_str(), key.as_str())).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn template(skeleton: &str, tags: &[&str]) -> IdeaTemplate {
+        IdeaTemplate {
+            description_skeleton: skeleton.to_string(),
+            default_tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    fn fields(description: &str, tags_input: &str) -> TemplateFields {
+        TemplateFields {
+            description: description.to_string(),
+            tags_input: tags_input.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_empty_form_is_filled_without_asking() {
+        let plan = plan_template(&fields("  ", ""), None, &template("Problem:", &["proposal"]));
+
+        assert!(!plan.needs_confirmation);
+        assert_eq!(plan.fields, fields("Problem:", "proposal"));
+    }
+
+    #[test]
+    fn test_typed_description_needs_confirmation() {
+        let plan = plan_template(&fields("My own words", ""), None, &template("Problem:", &[]));
+
+        assert!(plan.needs_confirmation);
+        assert_eq!(plan.fields.description, "Problem:");
+        assert_eq!(plan.keep_description.description, "My own words");
+    }
+
+    #[test]
+    fn test_switching_between_untouched_templates_does_not_ask() {
+        let first = template("Problem:", &["proposal"]);
+        let second = template("Hypothesis:", &["experiment"]);
+
+        let plan = plan_template(&fields("Problem:", "proposal"), Some(&first), &second);
+        assert!(!plan.needs_confirmation);
+        assert_eq!(plan.fields, fields("Hypothesis:", "experiment"));
+
+        // The textarea's line endings and a trailing space don't make the skeleton typed text
+        let multiline = template("Problem:\r\nWho:", &[]);
+        let plan = plan_template(&fields("Problem:\nWho: ", ""), Some(&multiline), &second);
+        assert!(!plan.needs_confirmation);
+    }
+
+    #[test]
+    fn test_edited_skeleton_counts_as_user_text() {
+        let first = template("Problem:", &[]);
+        let plan = plan_template(&fields("Problem: it rains", ""), Some(&first), &template("Other", &[]));
+
+        assert!(plan.needs_confirmation);
+    }
+
+    #[test]
+    fn test_typed_tags_are_kept_and_merged() {
+        let plan = plan_template(&fields("", "mine, Proposal"), None, &template("", &["proposal", "new"]));
+
+        assert_eq!(plan.fields.tags_input, "mine, Proposal, new");
+        assert_eq!(plan.keep_description.tags_input, "mine, Proposal, new");
+    }
+
+    #[test]
+    fn test_parse_tags() {
+        assert_eq!(parse_tags(" a, ,b ,"), vec!["a".to_string(), "b".to_string()]);
+        assert!(parse_tags("").is_empty());
+    }
+}
@@ -0,0 +1,116 @@
+//! Light/dark theme selection.
+//!
+//! The choice is persisted through [`crate::storage`]; the `System` choice follows the OS preference and is
+//! resolved to a concrete theme by the caller, which knows whether the platform prefers dark.
+
+use crate::storage::Storage;
+
+/// Storage key for the selected theme
+pub const THEME_KEY: &str = "theme";
+
+/// The theme the user picked
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Theme {
+    Light,
+    Dark,
+    /// Follow the operating system's color scheme
+    #[default]
+    System,
+}
+
+impl Theme {
+    /// The next theme when cycling through them with the toggle
+    pub fn next(self) -> Self {
+        match self {
+            Theme::Light => Theme::Dark,
+            Theme::Dark => Theme::System,
+            Theme::System => Theme::Light,
+        }
+    }
+
+    /// Stable name, used both for storage and as the toggle label
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Theme::Light => "light",
+            Theme::Dark => "dark",
+            Theme::System => "system",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim() {
+            "light" => Some(Theme::Light),
+            "dark" => Some(Theme::Dark),
+            "system" => Some(Theme::System),
+            _ => None,
+        }
+    }
+
+    /// The concrete theme to render, `"light"` or `"dark"`, for the `data-theme` attribute
+    pub fn resolve(self, prefers_dark: bool) -> &'static str {
+        match self {
+            Theme::Light => "light",
+            Theme::Dark => "dark",
+            Theme::System if prefers_dark => "dark",
+            Theme::System => "light",
+        }
+    }
+
+    /// The persisted theme, or the default when nothing (or something unreadable) is stored
+    pub fn load(storage: &dyn Storage) -> Self {
+        storage
+            .get_raw(THEME_KEY)
+            .and_then(|value| Theme::parse(&value))
+            .unwrap_or_default()
+    }
+
+    pub fn save(self, storage: &dyn Storage) {
+        storage.set_raw(THEME_KEY, self.as_str());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::MemoryStorage;
+
+    #[test]
+    fn test_cycle_visits_every_theme() {
+        let mut theme = Theme::Light;
+        let mut seen = vec![];
+        for _ in 0..3 {
+            seen.push(theme);
+            theme = theme.next();
+        }
+        assert_eq!(seen, vec![Theme::Light, Theme::Dark, Theme::System]);
+        assert_eq!(theme, Theme::Light);
+    }
+
+    #[test]
+    fn test_parse_roundtrip() {
+        for theme in [Theme::Light, Theme::Dark, Theme::System] {
+            assert_eq!(Theme::parse(theme.as_str()), Some(theme));
+        }
+        assert_eq!(Theme::parse("sepia"), None);
+    }
+
+    #[test]
+    fn test_resolve() {
+        assert_eq!(Theme::Light.resolve(true), "light");
+        assert_eq!(Theme::Dark.resolve(false), "dark");
+        assert_eq!(Theme::System.resolve(true), "dark");
+        assert_eq!(Theme::System.resolve(false), "light");
+    }
+
+    #[test]
+    fn test_load_and_save() {
+        let storage = MemoryStorage::new();
+        assert_eq!(Theme::load(&storage), Theme::System);
+
+        Theme::Dark.save(&storage);
+        assert_eq!(Theme::load(&storage), Theme::Dark);
+
+        storage.set_raw(THEME_KEY, "garbage");
+        assert_eq!(Theme::load(&storage), Theme::System);
+    }
+}
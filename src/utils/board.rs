@@ -0,0 +1,112 @@
+use crate::db::{IdeaStatus, IdeaSummary};
+
+/// Split ideas into one column per status, in the order of `columns`.
+///
+/// Ideas keep their order within a column, so a column lists its ideas in the manual order the list uses. Ideas
+/// whose status isn't in `columns` are left out.
+pub fn partition_by_status(ideas: &[IdeaSummary], columns: &[IdeaStatus]) -> Vec<(IdeaStatus, Vec<IdeaSummary>)> {
+    columns
+        .iter()
+        .map(|&status| {
+            let column = ideas.iter().filter(|idea| idea.status == status).cloned().collect();
+            (status, column)
+        })
+        .collect()
+}
+
+/// A card moved to another column before the server confirmed it, with what's needed to put it back
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BoardMove {
+    pub id: String,
+    pub from: IdeaStatus,
+    pub to: IdeaStatus,
+}
+
+/// Move the idea `id` to the column `to` right away. Only its status changes, so its place in the manual order (and
+/// so within the new column) is kept. Gives `None` when the idea is unknown or already in that column.
+pub fn move_card(ideas: &mut [IdeaSummary], id: &str, to: IdeaStatus) -> Option<BoardMove> {
+    let idea = ideas.iter_mut().find(|idea| idea.id.as_deref() == Some(id))?;
+    if idea.status == to {
+        return None;
+    }
+    let from = std::mem::replace(&mut idea.status, to);
+    Some(BoardMove { id: id.to_string(), from, to })
+}
+
+/// Undo a [`move_card`] the server refused. A card moved again in the meantime is left where it is now.
+pub fn rollback_move(ideas: &mut [IdeaSummary], undo: &BoardMove) {
+    if let Some(idea) = ideas.iter_mut().find(|idea| idea.id.as_deref() == Some(undo.id.as_str())) {
+        if idea.status == undo.to {
+            idea.status = undo.from;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn idea(id: &str, status: IdeaStatus) -> IdeaSummary {
+        IdeaSummary {
+            id: Some(id.to_string()),
+            title: id.to_string(),
+            status,
+            ..Default::default()
+        }
+    }
+
+    fn titles(column: &[IdeaSummary]) -> Vec<&str> {
+        column.iter().map(|idea| idea.title.as_str()).collect()
+    }
+
+    #[test]
+    fn test_partition_keeps_column_and_rank_order() {
+        let ideas = vec![
+            idea("a", IdeaStatus::Building),
+            idea("b", IdeaStatus::Inbox),
+            idea("c", IdeaStatus::Building),
+            idea("d", IdeaStatus::Dropped),
+        ];
+        let columns = partition_by_status(&ideas, &[IdeaStatus::Building, IdeaStatus::Inbox, IdeaStatus::Done]);
+
+        let statuses: Vec<IdeaStatus> = columns.iter().map(|(status, _)| *status).collect();
+        assert_eq!(statuses, [IdeaStatus::Building, IdeaStatus::Inbox, IdeaStatus::Done]);
+        assert_eq!(titles(&columns[0].1), ["a", "c"]);
+        assert_eq!(titles(&columns[1].1), ["b"]);
+        assert!(columns[2].1.is_empty());
+    }
+
+    #[test]
+    fn test_move_and_rollback() {
+        let mut ideas = vec![
+            idea("a", IdeaStatus::Inbox),
+            idea("b", IdeaStatus::Exploring),
+            idea("c", IdeaStatus::Inbox),
+            idea("d", IdeaStatus::Exploring),
+        ];
+        let before = ideas.clone();
+
+        let undo = move_card(&mut ideas, "c", IdeaStatus::Exploring).unwrap();
+        assert_eq!(undo, BoardMove { id: "c".to_string(), from: IdeaStatus::Inbox, to: IdeaStatus::Exploring });
+        // The moved card lands between the cards ranked around it
+        let columns = partition_by_status(&ideas, &[IdeaStatus::Inbox, IdeaStatus::Exploring]);
+        assert_eq!(titles(&columns[0].1), ["a"]);
+        assert_eq!(titles(&columns[1].1), ["b", "c", "d"]);
+
+        rollback_move(&mut ideas, &undo);
+        assert_eq!(ideas, before);
+
+        assert_eq!(move_card(&mut ideas, "a", IdeaStatus::Inbox), None);
+        assert_eq!(move_card(&mut ideas, "missing", IdeaStatus::Done), None);
+    }
+
+    #[test]
+    fn test_rollback_leaves_a_card_moved_again() {
+        let mut ideas = vec![idea("a", IdeaStatus::Inbox)];
+        let first = move_card(&mut ideas, "a", IdeaStatus::Building).unwrap();
+        move_card(&mut ideas, "a", IdeaStatus::Done).unwrap();
+
+        rollback_move(&mut ideas, &first);
+        assert_eq!(ideas[0].status, IdeaStatus::Done);
+    }
+}
@@ -0,0 +1,55 @@
+/// Something an idea card lets you do with its idea
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CardAction {
+    Copy,
+    Pin,
+    Develop,
+    Delete,
+    EditTags,
+    AddStatement,
+}
+
+impl CardAction {
+    pub const ALL: [CardAction; 6] = [
+        CardAction::Copy,
+        CardAction::Pin,
+        CardAction::Develop,
+        CardAction::Delete,
+        CardAction::EditTags,
+        CardAction::AddStatement,
+    ];
+
+    /// Whether the action leaves the idea as it is
+    pub fn is_read_only(self) -> bool {
+        matches!(self, CardAction::Copy | CardAction::Develop)
+    }
+}
+
+/// The actions a card offers for an idea, in the order its buttons are shown. An idea that isn't stored yet has no
+/// id to act on, so it offers none; a read-only deployment keeps only the ones that change nothing.
+pub fn card_actions(stored: bool, read_only: bool) -> Vec<CardAction> {
+    if !stored {
+        return Vec::new();
+    }
+    CardAction::ALL
+        .into_iter()
+        .filter(|action| !read_only || action.is_read_only())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unstored_ideas_offer_nothing() {
+        assert!(card_actions(false, false).is_empty());
+        assert!(card_actions(false, true).is_empty());
+    }
+
+    #[test]
+    fn test_read_only_keeps_copy_and_develop() {
+        assert_eq!(card_actions(true, true), vec![CardAction::Copy, CardAction::Develop]);
+        assert_eq!(card_actions(true, false), CardAction::ALL.to_vec());
+    }
+}
@@ -0,0 +1,88 @@
+/// Shortest bar drawn for a value above zero, so a period with a single idea next to a busy one still shows
+pub const MIN_BAR_HEIGHT: f64 = 1.0;
+
+/// A bar of a bar chart in SVG user units, with `y` growing downwards from the top of the chart
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BarRect {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+/// Bars for `values` side by side in a `width` × `height` chart, `gap` apart, standing on the bottom edge. The
+/// largest value reaches the top and the others scale linearly from zero. All-zero values give flat bars, and a gap
+/// too wide for the bars is shrunk to leave them room.
+pub fn bar_rects(values: &[usize], width: f64, height: f64, gap: f64) -> Vec<BarRect> {
+    if values.is_empty() {
+        return Vec::new();
+    }
+    let count = values.len() as f64;
+    let gap = gap.clamp(0.0, width / count / 2.0);
+    let bar_width = (width - gap * (count - 1.0)) / count;
+    let max = values.iter().copied().max().unwrap_or(0);
+
+    values
+        .iter()
+        .enumerate()
+        .map(|(index, &value)| {
+            let bar_height = if max == 0 || value == 0 {
+                0.0
+            } else {
+                (value as f64 / max as f64 * height).max(MIN_BAR_HEIGHT.min(height))
+            };
+            BarRect {
+                x: index as f64 * (bar_width + gap),
+                y: height - bar_height,
+                width: bar_width,
+                height: bar_height,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bars_fill_the_width() {
+        let bars = bar_rects(&[1, 2, 3, 4], 100.0, 40.0, 4.0);
+        assert_eq!(bars.len(), 4);
+        assert_eq!(bars[0].x, 0.0);
+        assert_eq!(bars[0].width, 22.0);
+        assert_eq!(bars[1].x, 26.0);
+        let last = bars[3];
+        assert_eq!(last.x + last.width, 100.0);
+    }
+
+    #[test]
+    fn test_heights_scale_to_the_largest_value() {
+        let bars = bar_rects(&[0, 5, 10], 30.0, 50.0, 0.0);
+        assert_eq!(bars.iter().map(|bar| bar.height).collect::<Vec<_>>(), vec![0.0, 25.0, 50.0]);
+        // Every bar stands on the bottom edge
+        assert!(bars.iter().all(|bar| bar.y + bar.height == 50.0));
+        assert_eq!(bars[2].y, 0.0);
+    }
+
+    #[test]
+    fn test_small_values_stay_visible() {
+        let bars = bar_rects(&[1, 1000], 20.0, 20.0, 0.0);
+        assert_eq!(bars[0].height, MIN_BAR_HEIGHT);
+    }
+
+    #[test]
+    fn test_all_zero_and_empty() {
+        let bars = bar_rects(&[0, 0, 0], 30.0, 10.0, 2.0);
+        assert!(bars.iter().all(|bar| bar.height == 0.0 && bar.y == 10.0));
+        assert!(bar_rects(&[], 30.0, 10.0, 2.0).is_empty());
+    }
+
+    #[test]
+    fn test_gap_is_shrunk_for_many_bars() {
+        let bars = bar_rects(&[1; 50], 100.0, 10.0, 10.0);
+        assert!(bars.iter().all(|bar| bar.width > 0.0));
+        let last = bars[49];
+        assert!((last.x + last.width - 100.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,108 @@
+/// Refetch state of a resource whose inputs can change faster than it loads: at most one fetch in flight, and only
+/// the newest trigger kept while it runs.
+///
+/// Every trigger gets the next generation. A fetch carries the generation it was started for, and its response is
+/// only worth showing when no newer trigger came in meanwhile; otherwise the fetch started after it has newer data
+/// on the way. `K` is whatever the fetch needs, such as the filter the trigger saw.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Coalescer<K> {
+    /// Generation of the newest trigger
+    latest: u64,
+    /// Whether a fetch is running
+    in_flight: bool,
+    /// The newest trigger that came while a fetch was running, to fetch next
+    pending: Option<K>,
+}
+
+impl<K> Default for Coalescer<K> {
+    fn default() -> Self {
+        Self {
+            latest: 0,
+            in_flight: false,
+            pending: None,
+        }
+    }
+}
+
+/// What to do once a fetch finished, from [`Coalescer::settle`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settled<K> {
+    /// Whether the response is the newest and should replace what is shown
+    pub apply: bool,
+    /// The fetch to start next with its generation, when triggers came in meanwhile
+    pub next: Option<(u64, K)>,
+}
+
+impl<K> Coalescer<K> {
+    /// The inputs changed. Returns the fetch to start now with its generation, or `None` when one is running; the
+    /// newest trigger is then fetched after it and any earlier waiting one is dropped.
+    pub fn trigger(&mut self, key: K) -> Option<(u64, K)> {
+        self.latest += 1;
+        if self.in_flight {
+            self.pending = Some(key);
+            None
+        } else {
+            self.in_flight = true;
+            Some((self.latest, key))
+        }
+    }
+
+    /// The fetch started for `generation` finished
+    pub fn settle(&mut self, generation: u64) -> Settled<K> {
+        let next = self.pending.take().map(|key| (self.latest, key));
+        self.in_flight = next.is_some();
+        Settled {
+            apply: generation == self.latest,
+            next,
+        }
+    }
+
+    /// Whether a fetch is running
+    pub fn is_fetching(&self) -> bool {
+        self.in_flight
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_single_trigger_fetches_and_applies() {
+        let mut coalescer = Coalescer::default();
+        assert_eq!(coalescer.trigger("a"), Some((1, "a")));
+        assert!(coalescer.is_fetching());
+        assert_eq!(coalescer.settle(1), Settled { apply: true, next: None });
+        assert!(!coalescer.is_fetching());
+
+        // The next trigger starts right away
+        assert_eq!(coalescer.trigger("b"), Some((2, "b")));
+    }
+
+    #[test]
+    fn test_rapid_triggers_run_one_more_fetch() {
+        let mut coalescer = Coalescer::default();
+        assert_eq!(coalescer.trigger(1), Some((1, 1)));
+        // Three deletes while the first fetch runs
+        assert_eq!(coalescer.trigger(2), None);
+        assert_eq!(coalescer.trigger(3), None);
+        assert_eq!(coalescer.trigger(4), None);
+
+        // The first response is stale and only the newest trigger is fetched
+        assert_eq!(coalescer.settle(1), Settled { apply: false, next: Some((4, 4)) });
+        assert!(coalescer.is_fetching());
+        assert_eq!(coalescer.settle(4), Settled { apply: true, next: None });
+        assert!(!coalescer.is_fetching());
+    }
+
+    #[test]
+    fn test_trigger_during_follow_up_fetch() {
+        let mut coalescer = Coalescer::default();
+        coalescer.trigger("a");
+        coalescer.trigger("b");
+        assert_eq!(coalescer.settle(1).next, Some((2, "b")));
+        assert_eq!(coalescer.trigger("c"), None);
+        assert_eq!(coalescer.settle(2), Settled { apply: false, next: Some((3, "c")) });
+        assert_eq!(coalescer.settle(3), Settled { apply: true, next: None });
+    }
+}
@@ -0,0 +1,114 @@
+use crate::db::{IdeaId, IdeaSummary};
+use crate::error::IdeaError;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+/// Where a cursor page picks up: just after the idea created at `at` with the id `id`.
+///
+/// Pages go in creation order, ties broken by id, so an idea created or deleted between two requests never moves
+/// another one across a page boundary the way it would with offsets. Clients only see it as the opaque string of
+/// [`PageCursor::encode`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PageCursor {
+    #[serde(rename = "t")]
+    pub at: DateTime<Utc>,
+    #[serde(rename = "i")]
+    pub id: String,
+}
+
+impl PageCursor {
+    /// The cursor after an idea created at `created_at`, see [`creation_key`]
+    pub fn after(created_at: Option<DateTime<Utc>>, id: impl Into<String>) -> Self {
+        Self {
+            at: creation_key(created_at),
+            id: id.into(),
+        }
+    }
+
+    /// URL-safe base64 of the cursor as JSON
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(self).unwrap_or_default())
+    }
+
+    /// Read a cursor made by [`Self::encode`]
+    pub fn decode(cursor: &str) -> Result<Self, IdeaError> {
+        let invalid = || IdeaError::Validation("The page cursor is invalid".to_string());
+        let bytes = URL_SAFE_NO_PAD.decode(cursor.trim()).map_err(|_| invalid())?;
+        let cursor: Self = serde_json::from_slice(&bytes).map_err(|_| invalid())?;
+        IdeaId::parse(&cursor.id).map_err(|_| invalid())?;
+        Ok(cursor)
+    }
+
+    /// Whether an idea created at `created_at` with the id `id` belongs after the cursor
+    pub fn is_before(&self, created_at: Option<DateTime<Utc>>, id: &str) -> bool {
+        match creation_key(created_at).cmp(&self.at) {
+            Ordering::Greater => true,
+            Ordering::Equal => id > self.id.as_str(),
+            Ordering::Less => false,
+        }
+    }
+}
+
+/// The creation time pages are ordered by. Ideas stored before creation times were kept sort first, as if created
+/// at the Unix epoch.
+pub fn creation_key(created_at: Option<DateTime<Utc>>) -> DateTime<Utc> {
+    created_at.unwrap_or_default()
+}
+
+/// A page of ideas in creation order, see [`PageCursor`]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CursorPage {
+    pub ideas: Vec<IdeaSummary>,
+    /// Where the next page starts; `None` once this page holds the last idea
+    pub next_cursor: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(seconds: i64) -> DateTime<Utc> {
+        Utc.timestamp_opt(1_700_000_000 + seconds, 0).unwrap()
+    }
+
+    #[test]
+    fn test_cursor_round_trips_as_opaque_text() {
+        let cursor = PageCursor::after(Some(at(5)), "ideas:abc");
+        let encoded = cursor.encode();
+        assert!(encoded.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'), "{}", encoded);
+        assert!(!encoded.contains("ideas"));
+        assert_eq!(PageCursor::decode(&encoded).unwrap(), cursor);
+
+        // Ideas from before creation times get a cursor too
+        let legacy = PageCursor::after(None, "ideas:old");
+        assert_eq!(PageCursor::decode(&legacy.encode()).unwrap().at, DateTime::<Utc>::default());
+    }
+
+    #[test]
+    fn test_malformed_cursors_are_refused() {
+        let not_a_cursor = URL_SAFE_NO_PAD.encode(b"{\"t\":5}");
+        let wrong_id = PageCursor::after(None, "users:abc").encode();
+        for cursor in ["", "not base64!", "e30", &not_a_cursor, &wrong_id] {
+            assert!(matches!(PageCursor::decode(cursor), Err(IdeaError::Validation(_))), "{}", cursor);
+        }
+    }
+
+    #[test]
+    fn test_boundary_compares_time_then_id() {
+        let cursor = PageCursor::after(Some(at(5)), "ideas:m");
+        // Later ideas come after, earlier ones before, whatever their id
+        assert!(cursor.is_before(Some(at(6)), "ideas:a"));
+        assert!(!cursor.is_before(Some(at(4)), "ideas:z"));
+        // Same time: the id decides, and the cursor's own idea isn't repeated
+        assert!(cursor.is_before(Some(at(5)), "ideas:n"));
+        assert!(!cursor.is_before(Some(at(5)), "ideas:m"));
+        assert!(!cursor.is_before(Some(at(5)), "ideas:l"));
+        // Ideas without a creation time come before every stamped one
+        assert!(!cursor.is_before(None, "ideas:z"));
+        assert!(PageCursor::after(None, "ideas:a").is_before(None, "ideas:b"));
+        assert!(PageCursor::after(None, "ideas:z").is_before(Some(at(0)), "ideas:a"));
+    }
+}
@@ -0,0 +1,286 @@
+/// Most cells of the longest-common-subsequence table computed for one diff, after the common start and end are
+/// taken off. Past this, words are compared line by line instead, and past it again the changed middle is shown as
+/// removed and added whole, so a huge paste can't freeze the page.
+pub const MAX_DIFF_CELLS: usize = 1_000_000;
+
+/// A run of text in a diff
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiffSpan {
+    /// In both versions
+    Equal(String),
+    /// Only in the new version
+    Added(String),
+    /// Only in the old version
+    Removed(String),
+}
+
+impl DiffSpan {
+    pub fn text(&self) -> &str {
+        match self {
+            DiffSpan::Equal(text) | DiffSpan::Added(text) | DiffSpan::Removed(text) => text,
+        }
+    }
+}
+
+/// What changed from `old` to `new`, word by word.
+///
+/// Words and the whitespace between them are compared as separate tokens, so a changed word shows up alone and a
+/// whitespace-only change is still visible. Joining the `Equal` and `Removed` spans gives back `old`, and joining the
+/// `Equal` and `Added` ones gives `new`. Where both changed, the removed text comes before the added text. Neighbouring
+/// spans of the same kind are merged. Long texts fall back to a line diff, see [`MAX_DIFF_CELLS`].
+pub fn diff_text(old: &str, new: &str) -> Vec<DiffSpan> {
+    diff_tokens(&words(old), &words(new))
+        .or_else(|| diff_tokens(&lines(old), &lines(new)))
+        .unwrap_or_else(|| replace_middle(&lines(old), &lines(new)))
+}
+
+/// `text` cut into runs of whitespace and runs of everything else
+fn words(text: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    let mut in_space = None;
+    for (index, c) in text.char_indices() {
+        let space = c.is_whitespace();
+        if in_space.is_some_and(|was| was != space) {
+            tokens.push(&text[start..index]);
+            start = index;
+        }
+        in_space = Some(space);
+    }
+    if start < text.len() {
+        tokens.push(&text[start..]);
+    }
+    tokens
+}
+
+/// `text` cut into lines, each keeping its line break
+fn lines(text: &str) -> Vec<&str> {
+    text.split_inclusive('\n').collect()
+}
+
+/// Number of tokens `old` and `new` share at their start, and then at their end
+fn common_ends(old: &[&str], new: &[&str]) -> (usize, usize) {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    (prefix, suffix)
+}
+
+/// Token diff through a longest-common-subsequence table, or `None` when the table would be too big
+fn diff_tokens(old: &[&str], new: &[&str]) -> Option<Vec<DiffSpan>> {
+    let (prefix, suffix) = common_ends(old, new);
+    let a = &old[prefix..old.len() - suffix];
+    let b = &new[prefix..new.len() - suffix];
+    let width = b.len() + 1;
+    if (a.len() + 1).saturating_mul(width) > MAX_DIFF_CELLS {
+        return None;
+    }
+
+    // `table[i * width + j]` is the length of the longest common subsequence of `a[i..]` and `b[j..]`
+    let mut table = vec![0u32; (a.len() + 1) * width];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            table[i * width + j] = if a[i] == b[j] {
+                table[(i + 1) * width + j + 1] + 1
+            } else {
+                table[(i + 1) * width + j].max(table[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut spans = Vec::new();
+    push(&mut spans, DiffSpan::Equal(old[..prefix].concat()));
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        if i < a.len() && j < b.len() && a[i] == b[j] {
+            push(&mut spans, DiffSpan::Equal(a[i].to_string()));
+            i += 1;
+            j += 1;
+        } else if j == b.len() || (i < a.len() && table[(i + 1) * width + j] >= table[i * width + j + 1]) {
+            push(&mut spans, DiffSpan::Removed(a[i].to_string()));
+            i += 1;
+        } else {
+            push(&mut spans, DiffSpan::Added(b[j].to_string()));
+            j += 1;
+        }
+    }
+    push(&mut spans, DiffSpan::Equal(old[old.len() - suffix..].concat()));
+    Some(spans)
+}
+
+/// The last resort: everything between the common start and end is removed and added in one piece
+fn replace_middle(old: &[&str], new: &[&str]) -> Vec<DiffSpan> {
+    let (prefix, suffix) = common_ends(old, new);
+    let mut spans = Vec::new();
+    push(&mut spans, DiffSpan::Equal(old[..prefix].concat()));
+    push(&mut spans, DiffSpan::Removed(old[prefix..old.len() - suffix].concat()));
+    push(&mut spans, DiffSpan::Added(new[prefix..new.len() - suffix].concat()));
+    push(&mut spans, DiffSpan::Equal(old[old.len() - suffix..].concat()));
+    spans
+}
+
+/// Append `span`, merging it into the last one when they are of the same kind. Empty spans are dropped.
+fn push(spans: &mut Vec<DiffSpan>, span: DiffSpan) {
+    if span.text().is_empty() {
+        return;
+    }
+    match (spans.last_mut(), span) {
+        (Some(DiffSpan::Equal(last)), DiffSpan::Equal(text))
+        | (Some(DiffSpan::Added(last)), DiffSpan::Added(text))
+        | (Some(DiffSpan::Removed(last)), DiffSpan::Removed(text)) => last.push_str(&text),
+        (_, span) => spans.push(span),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn equal(text: &str) -> DiffSpan {
+        DiffSpan::Equal(text.to_string())
+    }
+
+    fn added(text: &str) -> DiffSpan {
+        DiffSpan::Added(text.to_string())
+    }
+
+    fn removed(text: &str) -> DiffSpan {
+        DiffSpan::Removed(text.to_string())
+    }
+
+    /// The old and the new text put back together from `spans`
+    fn sides(spans: &[DiffSpan]) -> (String, String) {
+        let mut old = String::new();
+        let mut new = String::new();
+        for span in spans {
+            match span {
+                DiffSpan::Equal(text) => {
+                    old.push_str(text);
+                    new.push_str(text);
+                }
+                DiffSpan::Removed(text) => old.push_str(text),
+                DiffSpan::Added(text) => new.push_str(text),
+            }
+        }
+        (old, new)
+    }
+
+    fn assert_round_trip(old: &str, new: &str) -> Vec<DiffSpan> {
+        let spans = diff_text(old, new);
+        assert_eq!(sides(&spans), (old.to_string(), new.to_string()));
+        assert!(spans.iter().all(|span| !span.text().is_empty()));
+        // Merged: no two neighbours of the same kind
+        assert!(spans
+            .windows(2)
+            .all(|pair| std::mem::discriminant(&pair[0]) != std::mem::discriminant(&pair[1])));
+        spans
+    }
+
+    #[test]
+    fn test_identical_and_empty() {
+        assert_eq!(diff_text("same text", "same text"), vec![equal("same text")]);
+        assert!(diff_text("", "").is_empty());
+        assert_eq!(diff_text("", "new notes"), vec![added("new notes")]);
+        assert_eq!(diff_text("old notes", ""), vec![removed("old notes")]);
+    }
+
+    #[test]
+    fn test_changed_word() {
+        assert_eq!(
+            assert_round_trip("the quick brown fox", "the slow brown fox"),
+            vec![equal("the "), removed("quick"), added("slow"), equal(" brown fox")]
+        );
+    }
+
+    #[test]
+    fn test_words_added_and_removed() {
+        assert_eq!(
+            assert_round_trip("keep this and that", "keep this and also that too"),
+            vec![equal("keep this and "), added("also "), equal("that"), added(" too")]
+        );
+        assert_eq!(
+            assert_round_trip("one two three four", "one four"),
+            vec![equal("one "), removed("two three "), equal("four")]
+        );
+    }
+
+    #[test]
+    fn test_whitespace_only_changes() {
+        assert_eq!(
+            assert_round_trip("a b", "a  b"),
+            vec![equal("a"), removed(" "), added("  "), equal("b")]
+        );
+        assert_eq!(
+            assert_round_trip("line one\nline two", "line one\n\nline two"),
+            vec![equal("line one"), removed("\n"), added("\n\n"), equal("line two")]
+        );
+        assert_eq!(assert_round_trip("trailing", "trailing "), vec![equal("trailing"), added(" ")]);
+    }
+
+    #[test]
+    fn test_unicode() {
+        assert_eq!(
+            assert_round_trip("café au lait ☕", "café crème ☕"),
+            vec![equal("café "), removed("au lait"), added("crème"), equal(" ☕")]
+        );
+        // Non-breaking and ideographic spaces are whitespace too
+        assert_eq!(
+            assert_round_trip("100\u{a0}km", "200\u{a0}km"),
+            vec![removed("100"), added("200"), equal("\u{a0}km")]
+        );
+        assert_round_trip("日本語\u{3000}テキスト", "日本語\u{3000}文章");
+        // Combining marks and emoji sequences stay whole
+        assert_round_trip("e\u{301}te\u{301} 👩‍💻", "ete\u{301} 👩‍💻 🎉");
+    }
+
+    #[test]
+    fn test_tokens() {
+        assert_eq!(words("  two  words\n"), vec!["  ", "two", "  ", "words", "\n"]);
+        assert!(words("").is_empty());
+        assert_eq!(lines("a\nb\n\nc"), vec!["a\n", "b\n", "\n", "c"]);
+    }
+
+    #[test]
+    fn test_long_notes_fall_back_to_lines() {
+        let old: String = (0..600).map(|line| format!("line {} has a few words in it\n", line)).collect();
+        let new: String = (0..600)
+            .map(|line| match line % 50 {
+                0 => format!("line {} was rewritten\n", line),
+                _ => format!("line {} has a few words in it\n", line),
+            })
+            .collect();
+        assert!(words(&old).len() * words(&new).len() > MAX_DIFF_CELLS);
+
+        let spans = assert_round_trip(&old, &new);
+        // Whole lines change rather than single words
+        for span in spans.iter().filter(|span| !matches!(span, DiffSpan::Equal(_))) {
+            assert!(span.text().ends_with('\n'));
+        }
+        assert_eq!(spans.iter().filter(|span| matches!(span, DiffSpan::Added(_))).count(), 12);
+    }
+
+    #[test]
+    fn test_huge_rewrites_replace_the_middle() {
+        let old: String = (0..1500).map(|line| format!("old {}\n", line)).collect();
+        let new: String = (0..1500).map(|line| format!("new {}\n", line)).collect();
+        let old = format!("Intro\n{}Outro", old);
+        let new = format!("Intro\n{}Outro", new);
+
+        let spans = assert_round_trip(&old, &new);
+        assert_eq!(spans.len(), 4);
+        assert_eq!(spans[0], equal("Intro\n"));
+        assert_eq!(spans[3], equal("Outro"));
+    }
+
+    #[test]
+    fn test_a_small_edit_to_long_notes_stays_word_level() {
+        let body: String = (0..5000).map(|word| format!("w{} ", word)).collect();
+        let old = format!("{}end", body);
+        let new = format!("{}finish", body);
+        assert_eq!(diff_text(&old, &new), vec![equal(&body), removed("end"), added("finish")]);
+    }
+}
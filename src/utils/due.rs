@@ -0,0 +1,127 @@
+use chrono::{NaiveDate, NaiveDateTime};
+
+/// An idea due within this many days is "due soon"
+pub const DUE_SOON_DAYS: i64 = 3;
+
+/// Format of due dates everywhere: on the wire, in the date input and in URLs
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// How close an idea is to its due date
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DueStatus {
+    /// More than [`DUE_SOON_DAYS`] days left
+    Later,
+    /// Due today or within [`DUE_SOON_DAYS`] days
+    Soon,
+    /// The due date has passed
+    Overdue,
+}
+
+impl DueStatus {
+    /// Value of the chip's `data-due` attribute
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DueStatus::Later => "later",
+            DueStatus::Soon => "soon",
+            DueStatus::Overdue => "overdue",
+        }
+    }
+}
+
+/// Status of an idea due on `due`, seen at the wall-clock time `now`.
+///
+/// Both are local and timezone-less: an idea is due for the whole of its day and overdue from the next midnight.
+pub fn due_status(due: NaiveDate, now: NaiveDateTime) -> DueStatus {
+    let days_left = (due - now.date()).num_days();
+    if days_left < 0 {
+        DueStatus::Overdue
+    } else if days_left <= DUE_SOON_DAYS {
+        DueStatus::Soon
+    } else {
+        DueStatus::Later
+    }
+}
+
+/// Chip text, e.g. "due today", "due in 2 days", "overdue by 1 day" or "due 2024-06-01"
+pub fn due_label(due: NaiveDate, now: NaiveDateTime) -> String {
+    let days_left = (due - now.date()).num_days();
+    let days = |n: i64| if n == 1 { "1 day".to_string() } else { format!("{} days", n) };
+    match days_left {
+        0 => "due today".to_string(),
+        1 => "due tomorrow".to_string(),
+        n if n < 0 => format!("overdue by {}", days(-n)),
+        n if n <= DUE_SOON_DAYS => format!("due in {}", days(n)),
+        _ => format!("due {}", format_due_date(due)),
+    }
+}
+
+/// Parse an ISO date (`2024-06-01`), as sent by date inputs whatever the user's locale. Blank or invalid input
+/// gives `None`.
+pub fn parse_due_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value.trim(), DATE_FORMAT).ok()
+}
+
+/// The ISO form of a due date, as date inputs expect it
+pub fn format_due_date(date: NaiveDate) -> String {
+    date.format(DATE_FORMAT).to_string()
+}
+
+/// Current local wall-clock time, for [`due_status`] and [`due_label`]
+pub fn local_now() -> NaiveDateTime {
+    chrono::Local::now().naive_local()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(value: &str) -> NaiveDate {
+        parse_due_date(value).unwrap()
+    }
+
+    fn at(value: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    #[test]
+    fn test_due_until_midnight() {
+        let due = date("2024-03-10");
+        assert_eq!(due_status(due, at("2024-03-10 00:00:00")), DueStatus::Soon);
+        assert_eq!(due_status(due, at("2024-03-10 23:59:59")), DueStatus::Soon);
+        assert_eq!(due_status(due, at("2024-03-11 00:00:00")), DueStatus::Overdue);
+    }
+
+    #[test]
+    fn test_soon_window_starts_at_midnight() {
+        let due = date("2024-03-10");
+        assert_eq!(due_status(due, at("2024-03-06 23:59:59")), DueStatus::Later);
+        assert_eq!(due_status(due, at("2024-03-07 00:00:00")), DueStatus::Soon);
+    }
+
+    #[test]
+    fn test_across_month_and_leap_day() {
+        let due = date("2024-03-01");
+        assert_eq!(due_status(due, at("2024-02-29 12:00:00")), DueStatus::Soon);
+        assert_eq!(due_label(due, at("2024-02-27 08:00:00")), "due in 3 days");
+        assert_eq!(due_label(due, at("2024-03-02 00:00:01")), "overdue by 1 day");
+    }
+
+    #[test]
+    fn test_labels() {
+        let due = date("2024-06-15");
+        assert_eq!(due_label(due, at("2024-06-15 09:00:00")), "due today");
+        assert_eq!(due_label(due, at("2024-06-14 23:59:59")), "due tomorrow");
+        assert_eq!(due_label(due, at("2024-06-20 00:00:00")), "overdue by 5 days");
+        assert_eq!(due_label(due, at("2024-06-01 00:00:00")), "due 2024-06-15");
+    }
+
+    #[test]
+    fn test_iso_dates_round_trip() {
+        let due = date(" 2024-06-01 ");
+        assert_eq!(format_due_date(due), "2024-06-01");
+        assert_eq!(serde_json::to_string(&due).unwrap(), "\"2024-06-01\"");
+        assert_eq!(parse_due_date(""), None);
+        assert_eq!(parse_due_date("01/06/2024"), None);
+        assert_eq!(parse_due_date("2024-02-30"), None);
+    }
+}
@@ -0,0 +1,91 @@
+/// Extra points for a matched character right after the previous one
+const CONSECUTIVE_BONUS: u32 = 5;
+/// Extra points for a matched character starting a word
+const WORD_START_BONUS: u32 = 3;
+
+/// How well `candidate` matches `query` as typed into the command palette, higher is better.
+///
+/// Every character of the query must appear in the candidate in the same order, ignoring case; `None` otherwise.
+/// Whitespace in the query is skipped, so "pin bk" finds "pin: Bike repair". Runs of consecutive characters and
+/// characters at the start of a word score more. An empty query matches everything with a score of 0.
+pub fn fuzzy_match(query: &str, candidate: &str) -> Option<u32> {
+    let query: Vec<char> = query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect();
+    let candidate: Vec<char> = candidate.chars().flat_map(char::to_lowercase).collect();
+
+    let mut wanted = query.iter().peekable();
+    let mut score = 0;
+    let mut last_match: Option<usize> = None;
+    for (index, &c) in candidate.iter().enumerate() {
+        let Some(&&next) = wanted.peek() else {
+            break;
+        };
+        if c != next {
+            continue;
+        }
+        wanted.next();
+        score += 1;
+        if index == 0 || !candidate[index - 1].is_alphanumeric() {
+            score += WORD_START_BONUS;
+        }
+        if last_match.is_some_and(|last| last + 1 == index) {
+            score += CONSECUTIVE_BONUS;
+        }
+        last_match = Some(index);
+    }
+    wanted.peek().is_none().then_some(score)
+}
+
+/// The `limit` items whose label best matches `query`, best first. Items scoring the same keep their order.
+pub fn best_matches<'a, T>(query: &str, items: &'a [T], label: impl Fn(&T) -> String, limit: usize) -> Vec<&'a T> {
+    let mut scored: Vec<(u32, &'a T)> = items
+        .iter()
+        .filter_map(|item| fuzzy_match(query, &label(item)).map(|score| (score, item)))
+        .collect();
+    scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+    scored.into_iter().take(limit).map(|(_, item)| item).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_characters_must_appear_in_order() {
+        assert!(fuzzy_match("bke", "Bike repair").is_some());
+        assert!(fuzzy_match("BIKE", "bike repair").is_some());
+        assert!(fuzzy_match("ekib", "Bike repair").is_none());
+        assert!(fuzzy_match("bikes", "Bike").is_none());
+        assert_eq!(fuzzy_match("  ", "anything"), Some(0));
+        assert_eq!(fuzzy_match("", ""), Some(0));
+    }
+
+    #[test]
+    fn test_runs_and_word_starts_score_higher() {
+        let run = fuzzy_match("rep", "Bike repair").unwrap();
+        let scattered = fuzzy_match("rep", "Bike rental plan").unwrap();
+        assert!(run > scattered);
+
+        let word_start = fuzzy_match("gp", "go to garden plan").unwrap();
+        let mid_word = fuzzy_match("gp", "bigpicture").unwrap();
+        assert!(word_start > mid_word);
+    }
+
+    #[test]
+    fn test_query_spaces_are_ignored() {
+        assert_eq!(fuzzy_match("pin bk", "pin: Bike"), fuzzy_match("pinbk", "pin: Bike"));
+    }
+
+    #[test]
+    fn test_best_matches() {
+        let labels = vec!["open: Garden", "new idea", "develop: Garden plan", "go to trash"];
+        let found = best_matches("gard", &labels, |label| label.to_string(), 5);
+        assert_eq!(found, vec![&"open: Garden", &"develop: Garden plan"]);
+
+        let all = best_matches("", &labels, |label| label.to_string(), 3);
+        assert_eq!(all, vec![&"open: Garden", &"new idea", &"develop: Garden plan"]);
+    }
+}
@@ -0,0 +1,106 @@
+use crate::db::IdeaSummary;
+use std::collections::BTreeMap;
+
+/// Bucket name used for ideas without any tags
+pub const UNTAGGED: &str = "untagged";
+
+/// Group ideas under each of their tags.
+///
+/// Ideas with several tags appear in every matching bucket and untagged ideas land in the [`UNTAGGED`] bucket.
+/// Buckets are ordered by tag name (the `BTreeMap` takes care of that) and ideas keep their original order
+/// within a bucket, so the grouping is stable across re-renders.
+pub fn group_ideas_by_tag(ideas: &[IdeaSummary]) -> BTreeMap<String, Vec<IdeaSummary>> {
+    let mut groups: BTreeMap<String, Vec<IdeaSummary>> = BTreeMap::new();
+
+    for idea in ideas {
+        if idea.tags.is_empty() {
+            groups.entry(UNTAGGED.to_string()).or_default().push(idea.clone());
+            continue;
+        }
+
+        // Skip duplicate tags on the same idea so it only shows up once per bucket
+        let mut seen: Vec<&str> = Vec::new();
+        for tag in &idea.tags {
+            let tag = tag.trim();
+            if tag.is_empty() || seen.contains(&tag) {
+                continue;
+            }
+            seen.push(tag);
+            groups.entry(tag.to_string()).or_default().push(idea.clone());
+        }
+
+        // All tags were blank, treat it as untagged
+        if seen.is_empty() {
+            groups.entry(UNTAGGED.to_string()).or_default().push(idea.clone());
+        }
+    }
+
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn idea(title: &str, tags: &[&str]) -> IdeaSummary {
+        IdeaSummary {
+            title: title.to_string(),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    fn titles(ideas: &[IdeaSummary]) -> Vec<&str> {
+        ideas.iter().map(|i| i.title.as_str()).collect()
+    }
+
+    #[test]
+    fn test_group_empty_list() {
+        assert!(group_ideas_by_tag(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_multi_tag_idea_appears_in_each_bucket() {
+        let ideas = vec![idea("A", &["rust", "wasm"])];
+        let groups = group_ideas_by_tag(&ideas);
+
+        assert_eq!(groups.len(), 2);
+        assert_eq!(titles(&groups["rust"]), vec!["A"]);
+        assert_eq!(titles(&groups["wasm"]), vec!["A"]);
+    }
+
+    #[test]
+    fn test_untagged_bucket() {
+        let ideas = vec![idea("A", &[]), idea("B", &["  "]), idea("C", &["rust"])];
+        let groups = group_ideas_by_tag(&ideas);
+
+        assert_eq!(titles(&groups[UNTAGGED]), vec!["A", "B"]);
+        assert_eq!(titles(&groups["rust"]), vec!["C"]);
+    }
+
+    #[test]
+    fn test_bucket_and_inner_ordering_is_stable() {
+        let ideas = vec![
+            idea("First", &["zeta", "alpha"]),
+            idea("Second", &["alpha"]),
+            idea("Third", &["zeta"]),
+        ];
+        let groups = group_ideas_by_tag(&ideas);
+
+        let keys: Vec<&String> = groups.keys().collect();
+        assert_eq!(keys, vec!["alpha", "zeta"]);
+        assert_eq!(titles(&groups["alpha"]), vec!["First", "Second"]);
+        assert_eq!(titles(&groups["zeta"]), vec!["First", "Third"]);
+
+        // Running it again gives the same result
+        assert_eq!(group_ideas_by_tag(&ideas), groups);
+    }
+
+    #[test]
+    fn test_duplicate_tags_on_one_idea() {
+        let ideas = vec![idea("A", &["rust", "rust"])];
+        let groups = group_ideas_by_tag(&ideas);
+
+        assert_eq!(groups["rust"].len(), 1);
+    }
+}
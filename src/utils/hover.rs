@@ -0,0 +1,171 @@
+/// Milliseconds the pointer rests on an element, or a finger presses it, before its hover card opens
+pub const HOVER_INTENT_MS: f64 = 400.0;
+
+/// Pixels between a hover card and the element it is anchored to
+pub const HOVER_CARD_GAP: f64 = 8.0;
+
+/// Whether a hover card should be open, as a pure state machine.
+///
+/// Entering starts the delay, and [`HoverIntent::tick`] opens the card once it passed. Leaving closes the card or
+/// drops the pending delay, so a pointer passing over on its way elsewhere opens nothing. Times are in milliseconds
+/// from any fixed point.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HoverIntent {
+    delay: f64,
+    /// When the pointer entered, while the card waits to open
+    since: Option<f64>,
+    open: bool,
+}
+
+impl HoverIntent {
+    pub fn new(delay_ms: f64) -> Self {
+        Self {
+            delay: delay_ms,
+            since: None,
+            open: false,
+        }
+    }
+
+    /// The pointer entered at `now`. Entering again before leaving keeps the first time.
+    pub fn enter(&mut self, now: f64) {
+        if !self.open && self.since.is_none() {
+            self.since = Some(now);
+        }
+    }
+
+    /// The pointer left: close the card or forget the pending delay. Returns whether the card was open.
+    pub fn leave(&mut self) -> bool {
+        self.since = None;
+        std::mem::take(&mut self.open)
+    }
+
+    /// When the card opens if the pointer stays, for scheduling the next [`HoverIntent::tick`]
+    pub fn opens_at(&self) -> Option<f64> {
+        self.since.map(|since| since + self.delay)
+    }
+
+    /// Check the time; returns `true` when this opened the card
+    pub fn tick(&mut self, now: f64) -> bool {
+        match self.opens_at() {
+            Some(at) if now >= at => {
+                self.since = None;
+                self.open = true;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+}
+
+/// Which side of its anchor a hover card goes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HoverPlacement {
+    Below,
+    Above,
+}
+
+/// Where to put a hover card, in viewport pixels for `position: fixed`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HoverPosition {
+    pub placement: HoverPlacement,
+    pub left: f64,
+    /// Distance from the top of the viewport to the card's top when below, or from the bottom of the viewport to
+    /// the card's bottom when above, so the card grows away from its anchor either way
+    pub offset: f64,
+}
+
+impl HoverPosition {
+    /// Inline CSS placing the card
+    pub fn style(&self) -> String {
+        match self.placement {
+            HoverPlacement::Below => format!("left: {}px; top: {}px", self.left, self.offset),
+            HoverPlacement::Above => format!("left: {}px; bottom: {}px", self.left, self.offset),
+        }
+    }
+}
+
+/// Place a card about `card_height` tall next to an anchor spanning `anchor_top` to `anchor_bottom` in a viewport
+/// `viewport_height` tall. It goes below unless it only fits above, or neither side fits and above has more room.
+pub fn hover_card_position(
+    anchor_left: f64,
+    anchor_top: f64,
+    anchor_bottom: f64,
+    card_height: f64,
+    viewport_height: f64,
+) -> HoverPosition {
+    let below = viewport_height - anchor_bottom - HOVER_CARD_GAP;
+    let above = anchor_top - HOVER_CARD_GAP;
+    let placement = if below >= card_height || below >= above {
+        HoverPlacement::Below
+    } else {
+        HoverPlacement::Above
+    };
+    let offset = match placement {
+        HoverPlacement::Below => anchor_bottom + HOVER_CARD_GAP,
+        HoverPlacement::Above => viewport_height - anchor_top + HOVER_CARD_GAP,
+    };
+    HoverPosition {
+        placement,
+        left: anchor_left.max(0.0),
+        offset,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_opens_only_after_resting_for_the_delay() {
+        let mut intent = HoverIntent::new(HOVER_INTENT_MS);
+        intent.enter(1000.0);
+        assert_eq!(intent.opens_at(), Some(1400.0));
+        assert!(!intent.tick(1399.0));
+        assert!(!intent.is_open());
+
+        // Moving within the element doesn't start the delay over
+        intent.enter(1200.0);
+        assert!(intent.tick(1400.0));
+        assert!(intent.is_open());
+        assert!(!intent.tick(2000.0));
+        assert_eq!(intent.opens_at(), None);
+
+        assert!(intent.leave());
+        assert!(!intent.is_open());
+    }
+
+    #[test]
+    fn test_passing_over_opens_nothing() {
+        let mut intent = HoverIntent::new(HOVER_INTENT_MS);
+        intent.enter(0.0);
+        assert!(!intent.leave());
+        assert!(!intent.tick(1000.0));
+
+        // The timer of the first visit fires during the second one, which waits its own delay
+        intent.enter(300.0);
+        assert!(!intent.tick(400.0));
+        assert!(intent.tick(700.0));
+    }
+
+    #[test]
+    fn test_card_flips_above_when_only_that_side_fits() {
+        let below = hover_card_position(20.0, 100.0, 200.0, 220.0, 800.0);
+        assert_eq!(below.placement, HoverPlacement::Below);
+        assert_eq!(below.offset, 208.0);
+        assert_eq!(below.style(), "left: 20px; top: 208px");
+
+        let above = hover_card_position(20.0, 600.0, 700.0, 220.0, 800.0);
+        assert_eq!(above.placement, HoverPlacement::Above);
+        assert_eq!(above.offset, 208.0);
+        assert_eq!(above.style(), "left: 20px; bottom: 208px");
+
+        // Neither side fits: the roomier one wins
+        assert_eq!(hover_card_position(0.0, 100.0, 250.0, 400.0, 500.0).placement, HoverPlacement::Below);
+        assert_eq!(hover_card_position(0.0, 250.0, 400.0, 400.0, 500.0).placement, HoverPlacement::Above);
+        assert_eq!(hover_card_position(-5.0, 0.0, 10.0, 100.0, 500.0).left, 0.0);
+    }
+}
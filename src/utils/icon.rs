@@ -0,0 +1,108 @@
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Emoji the icon picker offers before free entry
+pub const ICON_CHOICES: [&str; 24] = [
+    "💡", "🚀", "🌱", "🔧", "📚", "🎨", "🎵", "🏠", "🚲", "🍳", "💰", "📈", "🧪", "🤝", "🌍", "🎮", "📱", "✏️", "🔒",
+    "❤️", "⭐", "🔥", "🧩", "🎯",
+];
+
+/// Most code points one icon may have. The longest emoji in use, families with a skin tone per person, have 11.
+pub const MAX_ICON_CODE_POINTS: usize = 16;
+
+/// Blocks of code points an emoji can start with
+const EMOJI_RANGES: [(u32, u32); 12] = [
+    (0x1F300, 0x1F5FF), // Miscellaneous Symbols and Pictographs
+    (0x1F600, 0x1F64F), // Emoticons
+    (0x1F680, 0x1F6FF), // Transport and Map Symbols
+    (0x1F900, 0x1F9FF), // Supplemental Symbols and Pictographs
+    (0x1FA70, 0x1FAFF), // Symbols and Pictographs Extended-A
+    (0x1F1E6, 0x1F1FF), // Regional indicators, in pairs making flags
+    (0x1F004, 0x1F004), // Mahjong tile
+    (0x1F0CF, 0x1F0CF), // Playing card
+    (0x1F170, 0x1F251), // Enclosed letters and ideographs
+    (0x2600, 0x27BF),   // Miscellaneous Symbols and Dingbats
+    (0x2B50, 0x2B55),   // Star and circles
+    (0x231A, 0x23FA),   // Watch, hourglass and media controls
+];
+
+/// Skin tone modifiers, which only ever follow another emoji
+const SKIN_TONES: (u32, u32) = (0x1F3FB, 0x1F3FF);
+
+fn in_range(c: char, (low, high): (u32, u32)) -> bool {
+    (low..=high).contains(&(c as u32))
+}
+
+/// Code points that join or modify the emoji before them
+fn is_emoji_modifier(c: char) -> bool {
+    matches!(c, '\u{200D}' | '\u{FE0F}' | '\u{20E3}') || in_range(c, (0xE0020, 0xE007F)) || in_range(c, SKIN_TONES)
+}
+
+/// Whether `icon` is exactly one emoji.
+///
+/// "One" is one grapheme cluster, so sequences joined with zero-width joiners (👩‍💻), skin tones (👍🏽) and flags
+/// (🇳🇱) count once. The cluster must start with a code point from the emoji blocks and contain nothing but emoji,
+/// joiners and modifiers, which keeps out letters, digits and symbols that merely combine into one cluster.
+pub fn is_emoji_icon(icon: &str) -> bool {
+    let mut graphemes = icon.graphemes(true);
+    let (Some(grapheme), None) = (graphemes.next(), graphemes.next()) else {
+        return false;
+    };
+    if grapheme.chars().count() > MAX_ICON_CODE_POINTS {
+        return false;
+    }
+    let is_emoji = |c: char| EMOJI_RANGES.iter().any(|&range| in_range(c, range));
+    let mut chars = grapheme.chars();
+    match chars.next() {
+        Some(first) if is_emoji(first) && !in_range(first, SKIN_TONES) => {
+            chars.all(|c| is_emoji(c) || is_emoji_modifier(c))
+        }
+        _ => false,
+    }
+}
+
+/// An icon as it will be stored: trimmed, with a blank one meaning none
+pub fn tidy_icon(icon: Option<String>) -> Option<String> {
+    icon.map(|icon| icon.trim().to_string()).filter(|icon| !icon.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_single_emoji_are_icons() {
+        for icon in ["💡", "🚲", "✏️", "⭐", "❤️", "🇳🇱", "🏴‍☠️"] {
+            assert!(is_emoji_icon(icon), "{}", icon);
+        }
+        for icon in ICON_CHOICES {
+            assert!(is_emoji_icon(icon), "{}", icon);
+        }
+    }
+
+    #[test]
+    fn test_multi_code_point_emoji_count_once() {
+        // Woman, zero-width joiner, laptop
+        assert!(is_emoji_icon("👩‍💻"));
+        assert_eq!("👩‍💻".chars().count(), 3);
+        // Skin tone modifiers
+        assert!(is_emoji_icon("👍🏽"));
+        assert!(is_emoji_icon("👩🏾‍💻"));
+        // A family with a skin tone per person
+        assert!(is_emoji_icon("👩🏽‍👩🏽‍👧🏽‍👦🏽"));
+    }
+
+    #[test]
+    fn test_everything_else_is_refused() {
+        for icon in ["", " ", "a", "1", "ab", "💡💡", "💡 ", "👍 🏽", "🏽", "é", "#", "→", "💡a", "😀\u{301}"] {
+            assert!(!is_emoji_icon(icon), "{:?}", icon);
+        }
+        assert!(!is_emoji_icon(&format!("👩{}", "\u{200D}👩".repeat(10))));
+    }
+
+    #[test]
+    fn test_tidy_icon() {
+        assert_eq!(tidy_icon(Some(" 🚲 ".to_string())), Some("🚲".to_string()));
+        assert_eq!(tidy_icon(Some("  ".to_string())), None);
+        assert_eq!(tidy_icon(None), None);
+    }
+}
@@ -0,0 +1,154 @@
+/// Pointer moves needed within [`IDLE_WAKE_WINDOW_MS`] to wake an idle tab
+pub const IDLE_WAKE_MOVES: u32 = 3;
+
+/// Milliseconds in which [`IDLE_WAKE_MOVES`] pointer moves must come to wake an idle tab
+pub const IDLE_WAKE_WINDOW_MS: f64 = 1500.0;
+
+/// What the user did, as far as [`IdleTracker`] cares
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Activity {
+    /// The pointer moved, which a bumped desk or a passing cable can do too
+    Pointer,
+    /// A key press, click, touch or scroll: always someone at the keyboard
+    Input,
+}
+
+/// A change of [`IdleTracker::is_idle`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdleChange {
+    WentIdle,
+    Woke,
+}
+
+/// Whether anyone is using the tab, as a pure state machine.
+///
+/// The tab goes idle once nothing happened for the timeout. While active, every activity starts the timeout over.
+/// While idle, input wakes it right away, but pointer moves only once [`IDLE_WAKE_MOVES`] of them came within
+/// [`IDLE_WAKE_WINDOW_MS`], so a single jiggle doesn't wake it just to let it go idle again. Times are in
+/// milliseconds from any fixed point.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IdleTracker {
+    timeout: f64,
+    last_active: f64,
+    idle: bool,
+    /// While idle: when the first pointer move of the current burst came and how many came since
+    moves: Option<(f64, u32)>,
+}
+
+impl IdleTracker {
+    /// An active tab that goes idle after `timeout_ms` without activity
+    pub fn new(timeout_ms: f64, now: f64) -> Self {
+        Self {
+            timeout: timeout_ms,
+            last_active: now,
+            idle: false,
+            moves: None,
+        }
+    }
+
+    pub fn is_idle(&self) -> bool {
+        self.idle
+    }
+
+    /// The user did `activity` at `now`; returns [`IdleChange::Woke`] when that woke the tab
+    pub fn record(&mut self, activity: Activity, now: f64) -> Option<IdleChange> {
+        if !self.idle {
+            self.last_active = self.last_active.max(now);
+            return None;
+        }
+        if activity == Activity::Pointer {
+            let (start, count) = match self.moves {
+                Some((start, count)) if now - start <= IDLE_WAKE_WINDOW_MS => (start, count + 1),
+                _ => (now, 1),
+            };
+            if count < IDLE_WAKE_MOVES {
+                self.moves = Some((start, count));
+                return None;
+            }
+        }
+        self.idle = false;
+        self.last_active = now;
+        self.moves = None;
+        Some(IdleChange::Woke)
+    }
+
+    /// Check the time; returns [`IdleChange::WentIdle`] once the timeout passed without activity
+    pub fn tick(&mut self, now: f64) -> Option<IdleChange> {
+        if self.idle || now - self.last_active < self.timeout {
+            return None;
+        }
+        self.idle = true;
+        self.moves = None;
+        Some(IdleChange::WentIdle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TIMEOUT: f64 = 10_000.0;
+
+    fn idle_tracker() -> IdleTracker {
+        let mut tracker = IdleTracker::new(TIMEOUT, 0.0);
+        assert_eq!(tracker.tick(TIMEOUT), Some(IdleChange::WentIdle));
+        tracker
+    }
+
+    #[test]
+    fn test_goes_idle_after_the_timeout() {
+        let mut tracker = IdleTracker::new(TIMEOUT, 0.0);
+        assert_eq!(tracker.tick(TIMEOUT - 1.0), None);
+        assert!(!tracker.is_idle());
+        assert_eq!(tracker.tick(TIMEOUT), Some(IdleChange::WentIdle));
+        assert!(tracker.is_idle());
+        // Reported once
+        assert_eq!(tracker.tick(TIMEOUT * 2.0), None);
+    }
+
+    #[test]
+    fn test_activity_starts_the_timeout_over() {
+        let mut tracker = IdleTracker::new(TIMEOUT, 0.0);
+        assert_eq!(tracker.record(Activity::Pointer, 8_000.0), None);
+        assert_eq!(tracker.tick(TIMEOUT + 1.0), None);
+        assert_eq!(tracker.record(Activity::Input, 15_000.0), None);
+        assert_eq!(tracker.tick(24_999.0), None);
+        assert_eq!(tracker.tick(25_000.0), Some(IdleChange::WentIdle));
+    }
+
+    #[test]
+    fn test_input_wakes_right_away() {
+        let mut tracker = idle_tracker();
+        assert_eq!(tracker.record(Activity::Input, 20_000.0), Some(IdleChange::Woke));
+        assert!(!tracker.is_idle());
+        // The timeout counts from the wake-up
+        assert_eq!(tracker.tick(20_000.0 + TIMEOUT - 1.0), None);
+        assert_eq!(tracker.tick(20_000.0 + TIMEOUT), Some(IdleChange::WentIdle));
+    }
+
+    #[test]
+    fn test_a_jiggle_does_not_wake() {
+        let mut tracker = idle_tracker();
+        assert_eq!(tracker.record(Activity::Pointer, 20_000.0), None);
+        assert_eq!(tracker.record(Activity::Pointer, 20_100.0), None);
+        assert!(tracker.is_idle());
+        // Long after, the burst starts over
+        assert_eq!(tracker.record(Activity::Pointer, 30_000.0), None);
+        assert_eq!(tracker.record(Activity::Pointer, 30_000.0 + IDLE_WAKE_WINDOW_MS + 1.0), None);
+        assert!(tracker.is_idle());
+    }
+
+    #[test]
+    fn test_steady_pointer_moves_wake() {
+        let mut tracker = idle_tracker();
+        assert_eq!(tracker.record(Activity::Pointer, 20_000.0), None);
+        assert_eq!(tracker.record(Activity::Pointer, 20_400.0), None);
+        assert_eq!(tracker.record(Activity::Pointer, 20_800.0), Some(IdleChange::Woke));
+        assert_eq!(tracker.record(Activity::Pointer, 21_000.0), None);
+
+        // A half-finished burst from before doesn't count towards the next idle period
+        assert_eq!(tracker.tick(21_000.0 + TIMEOUT), Some(IdleChange::WentIdle));
+        assert_eq!(tracker.record(Activity::Pointer, 31_100.0), None);
+        assert!(tracker.is_idle());
+    }
+}
@@ -0,0 +1,233 @@
+/// A run of text that is either shown as is or as a link to itself
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TextSegment {
+    Plain(String),
+    /// An `http` or `https` URL
+    Link(String),
+}
+
+/// Characters a URL may not end with: they usually close the sentence around it rather than belong to it
+const TRAILING_PUNCTUATION: &[char] = &['.', ',', ';', ':', '!', '?', '\'', '"', '…', '*'];
+
+/// Split `text` into plain runs and the `http(s)` URLs in it.
+///
+/// The matcher is deliberately conservative: a URL starts at the beginning of the text, after whitespace or after an
+/// opening bracket or quote, its host needs a dot (or is `localhost`), and it ends at whitespace or at characters
+/// that can't appear unescaped in a URL. Punctuation closing the sentence is left out, and so is a closing bracket
+/// without its opening one, so `(see https://example.com)` keeps its `)`. Other schemes are never linked.
+pub fn linkify(text: &str) -> Vec<TextSegment> {
+    // Same byte offsets as `text`, since only ASCII letters change
+    let lower = text.to_ascii_lowercase();
+    let mut segments = Vec::new();
+    let mut plain_start = 0;
+    let mut search_from = 0;
+
+    while let Some(found) = find_scheme(text, &lower, search_from) {
+        let end = found + url_length(&text[found..]);
+        if end == found {
+            search_from = found + 1;
+            continue;
+        }
+        if plain_start < found {
+            segments.push(TextSegment::Plain(text[plain_start..found].to_string()));
+        }
+        segments.push(TextSegment::Link(text[found..end].to_string()));
+        plain_start = end;
+        search_from = end;
+    }
+    if plain_start < text.len() {
+        segments.push(TextSegment::Plain(text[plain_start..].to_string()));
+    }
+    segments
+}
+
+/// Byte index of the next `http://` or `https://` at or after `from` that may start a URL. `lower` is `text` in
+/// ASCII lowercase.
+fn find_scheme(text: &str, lower: &str, from: usize) -> Option<usize> {
+    let mut index = from;
+    while let Some(offset) = lower.get(index..)?.find("http") {
+        let start = index + offset;
+        let rest = &lower[start..];
+        let starts_scheme = rest.starts_with("http://") || rest.starts_with("https://");
+        let boundary = text[..start]
+            .chars()
+            .next_back()
+            .is_none_or(|before| before.is_whitespace() || matches!(before, '(' | '[' | '"' | '\'' | '<'));
+        if starts_scheme && boundary {
+            return Some(start);
+        }
+        index = start + 1;
+    }
+    None
+}
+
+/// Length in bytes of the URL at the start of `text`, 0 when it isn't one worth linking
+fn url_length(text: &str) -> usize {
+    let ends_url = |c: char| c.is_whitespace() || c.is_control() || "<>\"`{}|\\".contains(c);
+    let candidate_end = text
+        .char_indices()
+        .find(|&(_, c)| ends_url(c))
+        .map_or(text.len(), |(index, _)| index);
+    let mut url = &text[..candidate_end];
+
+    // Drop sentence punctuation and closing brackets that were opened before the URL
+    while let Some(last) = url.chars().next_back() {
+        let unbalanced = |open: char, close: char| {
+            last == close && url.matches(close).count() > url.matches(open).count()
+        };
+        if TRAILING_PUNCTUATION.contains(&last) || unbalanced('(', ')') || unbalanced('[', ']') {
+            url = &url[..url.len() - last.len_utf8()];
+        } else {
+            break;
+        }
+    }
+
+    if has_valid_host(url) {
+        url.len()
+    } else {
+        0
+    }
+}
+
+/// Whether the part after the scheme starts with a plausible host: labels of letters, digits and dashes (any
+/// script) separated by dots, with an optional port. `user@host` is refused, as it mostly serves to disguise where
+/// a link leads.
+fn has_valid_host(url: &str) -> bool {
+    let Some((_, rest)) = url.split_once("://") else {
+        return false;
+    };
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let host = match authority.rsplit_once(':') {
+        Some((host, port)) if !port.is_empty() && port.chars().all(|c| c.is_ascii_digit()) => host,
+        Some(_) => return false,
+        None => authority,
+    };
+    let labels_ok = host
+        .split('.')
+        .all(|label| !label.is_empty() && label.chars().all(|c| c.is_alphanumeric() || c == '-'));
+    labels_ok && (host.contains('.') || host.eq_ignore_ascii_case("localhost"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use TextSegment::{Link, Plain};
+
+    fn plain(text: &str) -> TextSegment {
+        Plain(text.to_string())
+    }
+
+    fn link(url: &str) -> TextSegment {
+        Link(url.to_string())
+    }
+
+    /// The URLs found in `text`
+    fn links(text: &str) -> Vec<String> {
+        linkify(text)
+            .into_iter()
+            .filter_map(|segment| match segment {
+                Link(url) => Some(url),
+                Plain(_) => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_plain_text_is_one_segment() {
+        assert_eq!(linkify("no links here"), vec![plain("no links here")]);
+        assert_eq!(linkify(""), Vec::<TextSegment>::new());
+    }
+
+    #[test]
+    fn test_links_are_split_out() {
+        assert_eq!(
+            linkify("see https://example.com/docs and http://test.org"),
+            vec![plain("see "), link("https://example.com/docs"), plain(" and "), link("http://test.org")]
+        );
+        assert_eq!(linkify("https://example.com"), vec![link("https://example.com")]);
+    }
+
+    #[test]
+    fn test_segments_give_back_the_text() {
+        let text = "Read (https://a.example/x_(y)), then https://b.example/?q=1&r=2#top.\nDone";
+        let joined: String = linkify(text)
+            .into_iter()
+            .map(|segment| match segment {
+                Plain(text) | Link(text) => text,
+            })
+            .collect();
+        assert_eq!(joined, text);
+    }
+
+    #[test]
+    fn test_trailing_punctuation_is_left_out() {
+        assert_eq!(links("Go to https://example.com."), vec!["https://example.com"]);
+        assert_eq!(
+            links("https://example.com/a, https://example.com/b;"),
+            vec!["https://example.com/a", "https://example.com/b"]
+        );
+        assert_eq!(links("Really? https://example.com/faq?!"), vec!["https://example.com/faq"]);
+        assert_eq!(links("\"https://example.com/quoted\""), vec!["https://example.com/quoted"]);
+        // A summary cut in the middle of a URL
+        assert_eq!(links("more at https://example.com/lo…"), vec!["https://example.com/lo"]);
+    }
+
+    #[test]
+    fn test_parentheses() {
+        assert_eq!(links("(see https://example.com/page)"), vec!["https://example.com/page"]);
+        assert_eq!(
+            links("https://en.wikipedia.org/wiki/Rust_(programming_language)"),
+            vec!["https://en.wikipedia.org/wiki/Rust_(programming_language)"]
+        );
+        assert_eq!(
+            links("(https://en.wikipedia.org/wiki/Rust_(programming_language))."),
+            vec!["https://en.wikipedia.org/wiki/Rust_(programming_language)"]
+        );
+        assert_eq!(links("[https://example.com/list]"), vec!["https://example.com/list"]);
+    }
+
+    #[test]
+    fn test_unicode_domains_and_paths() {
+        assert_eq!(links("Café: https://münchen.de/straße!"), vec!["https://münchen.de/straße"]);
+        assert_eq!(links("https://例え.jp/パス"), vec!["https://例え.jp/パス"]);
+        assert_eq!(links("https://xn--mnchen-3ya.de"), vec!["https://xn--mnchen-3ya.de"]);
+    }
+
+    #[test]
+    fn test_other_schemes_are_not_linked() {
+        assert!(links("file:///etc/passwd").is_empty());
+        assert!(links("javascript:alert(1)").is_empty());
+        assert!(links("ftp://example.com/file").is_empty());
+        assert!(links("mailto:someone@example.com").is_empty());
+        // A scheme hidden inside another one isn't a link either
+        assert!(links("file:///https://example.com").is_empty());
+        assert!(links("view-source:https://example.com").is_empty());
+    }
+
+    #[test]
+    fn test_needs_a_plausible_host() {
+        assert!(links("https://").is_empty());
+        assert!(links("https:// example.com").is_empty());
+        assert!(links("http://nodot/path").is_empty());
+        assert!(links("https://user@evil.example").is_empty());
+        assert!(links("https://example..com").is_empty());
+        assert!(links("https://example.com:port").is_empty());
+        assert_eq!(links("http://localhost:8080/api"), vec!["http://localhost:8080/api"]);
+        assert_eq!(links("https://example.com:8443"), vec!["https://example.com:8443"]);
+    }
+
+    #[test]
+    fn test_scheme_must_start_a_word() {
+        assert!(links("xhttps://example.com").is_empty());
+        assert_eq!(links("HTTPS://EXAMPLE.COM/Path"), vec!["HTTPS://EXAMPLE.COM/Path"]);
+        assert_eq!(links("<https://example.com>"), vec!["https://example.com"]);
+    }
+
+    #[test]
+    fn test_markup_is_never_part_of_a_link() {
+        assert_eq!(
+            linkify("https://example.com/\"><script>"),
+            vec![link("https://example.com/"), plain("\"><script>")]
+        );
+    }
+}
@@ -0,0 +1,648 @@
+use super::{creation_key, due_status, percent_decode, percent_encode, DueStatus};
+use crate::db::{AuthorFilter, IdeaStatus, IdeaSummary, TagMatchMode};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Ideas the list loads at a time as it is scrolled
+pub const PAGE_SIZE: usize = 20;
+/// Most ideas a single page request returns
+pub const MAX_PAGE_SIZE: usize = 100;
+
+/// Order of the idea list
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SortOrder {
+    /// The manual drag-and-drop order
+    #[default]
+    Manual,
+    /// Title, A to Z
+    Title,
+    /// Title, Z to A
+    TitleDesc,
+    /// Earliest due date first, ideas without one last
+    Due,
+    /// Latest due date first, ideas without one last
+    DueDesc,
+    /// Oldest first, in the order cursor pages come in, see [`super::PageCursor`]
+    Added,
+}
+
+/// A sortable column of the table view
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortColumn {
+    Title,
+    Due,
+}
+
+impl SortOrder {
+    pub const ALL: [SortOrder; 6] = [
+        SortOrder::Manual,
+        SortOrder::Title,
+        SortOrder::TitleDesc,
+        SortOrder::Due,
+        SortOrder::DueDesc,
+        SortOrder::Added,
+    ];
+
+    /// Value used in the query string
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SortOrder::Manual => "manual",
+            SortOrder::Title => "title",
+            SortOrder::TitleDesc => "title-desc",
+            SortOrder::Due => "due",
+            SortOrder::DueDesc => "due-desc",
+            SortOrder::Added => "added",
+        }
+    }
+
+    /// Label shown in the sort picker
+    pub fn label(self) -> &'static str {
+        match self {
+            SortOrder::Manual => "manual",
+            SortOrder::Title => "title a–z",
+            SortOrder::TitleDesc => "title z–a",
+            SortOrder::Due => "due soonest",
+            SortOrder::DueDesc => "due latest",
+            SortOrder::Added => "date added",
+        }
+    }
+
+    /// Parse a query string value. Unknown values give `None`.
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|order| order.as_str() == value)
+    }
+
+    /// Whether the list is sorted on `column`: `Some(true)` ascending, `Some(false)` descending
+    pub fn direction(self, column: SortColumn) -> Option<bool> {
+        match (self, column) {
+            (SortOrder::Title, SortColumn::Title) | (SortOrder::Due, SortColumn::Due) => Some(true),
+            (SortOrder::TitleDesc, SortColumn::Title) | (SortOrder::DueDesc, SortColumn::Due) => Some(false),
+            _ => None,
+        }
+    }
+
+    /// The order after clicking the header of `column`: ascending first, then descending
+    pub fn toggled(self, column: SortColumn) -> Self {
+        match (column, self.direction(column)) {
+            (SortColumn::Title, Some(true)) => SortOrder::TitleDesc,
+            (SortColumn::Title, _) => SortOrder::Title,
+            (SortColumn::Due, Some(true)) => SortOrder::DueDesc,
+            (SortColumn::Due, _) => SortOrder::Due,
+        }
+    }
+}
+
+/// What the idea list shows: search, tag filter, author filter, status filter, "due soon" filter and sort order.
+///
+/// Lives in the query string of the home route so going back to the list restores the same view, e.g.
+/// `?tag=ux&tag=api&match=any&status=building`. `author=Sam` lists Sam's ideas and an empty `author=` the
+/// unattributed ones. Converting from the query string never fails: missing, unknown or invalid
+/// parameters fall back to their defaults.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ListQuery {
+    pub search: String,
+    /// Selected tags, in the order they were picked
+    pub tags: Vec<String>,
+    /// Whether ideas need every selected tag or just one of them
+    pub tag_mode: TagMatchMode,
+    /// Only ideas by this author; `None` lists everyone's
+    pub author: Option<AuthorFilter>,
+    /// Only ideas with this status; `None` lists every status
+    pub status: Option<IdeaStatus>,
+    /// Only ideas that are overdue or due within [`super::DUE_SOON_DAYS`] days
+    pub due_soon: bool,
+    pub sort: SortOrder,
+}
+
+impl ListQuery {
+    /// Whether every idea is listed, in whatever order
+    pub fn is_unfiltered(&self) -> bool {
+        self.search.trim().is_empty()
+            && self.tags.is_empty()
+            && self.author.is_none()
+            && self.status.is_none()
+            && !self.due_soon
+    }
+
+    /// Whether every idea is listed in manual order, which is the only view where dragging makes sense
+    pub fn is_manual_unfiltered(&self) -> bool {
+        self.is_unfiltered() && self.sort == SortOrder::Manual
+    }
+
+    /// Whether every idea is listed in the order they were added, which the list loads by cursor instead of by
+    /// offset, so ideas added or deleted while scrolling don't shift the pages
+    pub fn pages_by_cursor(&self) -> bool {
+        self.is_unfiltered() && self.sort == SortOrder::Added
+    }
+
+    /// The same query with `tag` selected, or deselected if it already was
+    pub fn with_tag_toggled(&self, tag: &str) -> Self {
+        let tag = tag.trim();
+        let mut tags = self.tags.clone();
+        if tags.iter().any(|selected| selected == tag) {
+            tags.retain(|selected| selected != tag);
+        } else if !tag.is_empty() {
+            tags.push(tag.to_string());
+        }
+        Self {
+            tags,
+            ..self.clone()
+        }
+    }
+
+    /// The same query listing only `status`, or every status again if it already did
+    pub fn with_status_toggled(&self, status: IdeaStatus) -> Self {
+        Self {
+            status: (self.status != Some(status)).then_some(status),
+            ..self.clone()
+        }
+    }
+
+    /// The filter this query stands for, with `now` as the local wall-clock time the due filter compares with
+    pub fn filter(&self, now: NaiveDateTime) -> IdeaFilter {
+        IdeaFilter {
+            search: self.search.clone(),
+            tags: self.tags.clone(),
+            tag_mode: self.tag_mode,
+            author: self.author.clone(),
+            status: self.status,
+            due_soon: self.due_soon,
+            sort: self.sort,
+            now,
+        }
+    }
+
+    /// Ideas matching the search, tag, author, status and due filters, in the requested order, see
+    /// [`IdeaFilter::apply`]
+    pub fn apply(&self, ideas: &[IdeaSummary], now: NaiveDateTime) -> Vec<IdeaSummary> {
+        self.filter(now).apply(ideas)
+    }
+}
+
+/// The part of a [`ListQuery`] the server needs to count and page the list
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct IdeaFilter {
+    pub search: String,
+    pub tags: Vec<String>,
+    pub tag_mode: TagMatchMode,
+    pub author: Option<AuthorFilter>,
+    #[serde(default)]
+    pub status: Option<IdeaStatus>,
+    pub due_soon: bool,
+    pub sort: SortOrder,
+    /// The client's local wall-clock time, which "due soon" is measured from. The server's clock may be in another
+    /// time zone, so the client sends its own.
+    pub now: NaiveDateTime,
+}
+
+impl IdeaFilter {
+    /// Ideas matching the search, tag, author, status and due filters, in the requested order. The search looks at
+    /// the summary, so only the start of a long description.
+    pub fn apply(&self, ideas: &[IdeaSummary]) -> Vec<IdeaSummary> {
+        let now = self.now;
+        let needle = self.search.trim().to_lowercase();
+        let mut matching: Vec<IdeaSummary> = ideas
+            .iter()
+            .filter(|idea| self.tag_mode.matches(&idea.tags, &self.tags))
+            .filter(|idea| self.author.as_ref().is_none_or(|author| author.matches(idea.author.as_deref())))
+            .filter(|idea| self.status.is_none_or(|status| idea.status == status))
+            .filter(|idea| {
+                !self.due_soon
+                    || idea
+                        .due_date
+                        .is_some_and(|due| due_status(due, now) != DueStatus::Later)
+            })
+            .filter(|idea| {
+                needle.is_empty()
+                    || idea.title.to_lowercase().contains(&needle)
+                    || idea.short_description.to_lowercase().contains(&needle)
+                    || idea.tags.iter().any(|tag| tag.to_lowercase().contains(&needle))
+            })
+            .cloned()
+            .collect();
+
+        match self.sort {
+            // The server already returns ideas by rank
+            SortOrder::Manual => {}
+            SortOrder::Title => matching.sort_by_key(|idea| idea.title.to_lowercase()),
+            SortOrder::TitleDesc => {
+                matching.sort_by_key(|idea| std::cmp::Reverse(idea.title.to_lowercase()))
+            }
+            // Undated ideas go last both ways
+            SortOrder::Due => matching.sort_by_key(|idea| (idea.due_date.is_none(), idea.due_date)),
+            SortOrder::DueDesc => {
+                matching.sort_by_key(|idea| (idea.due_date.is_none(), std::cmp::Reverse(idea.due_date)))
+            }
+            SortOrder::Added => matching.sort_by(|a, b| {
+                (creation_key(a.created_at), &a.id).cmp(&(creation_key(b.created_at), &b.id))
+            }),
+        }
+        matching
+    }
+
+    /// Up to `limit` of the matching ideas starting at `offset`, counting the pinned ones first. The list shows
+    /// those in a section of their own, so they come with the first pages.
+    pub fn page(&self, ideas: &[IdeaSummary], offset: usize, limit: usize) -> Vec<IdeaSummary> {
+        let (pinned, rest) = split_pinned(self.apply(ideas));
+        pinned.into_iter().chain(rest).skip(offset).take(limit.min(MAX_PAGE_SIZE)).collect()
+    }
+}
+
+/// Append a freshly loaded page to the ideas already listed, returning how many were new.
+///
+/// Pages are requested by offset, so when ideas are added between two requests the next page starts with ideas
+/// that are already listed. Those are skipped; ideas without an id can't be matched and are always kept.
+pub fn append_page(listed: &mut Vec<IdeaSummary>, page: Vec<IdeaSummary>) -> usize {
+    let before = listed.len();
+    for idea in page {
+        let known = idea.id.is_some() && listed.iter().any(|existing| existing.id == idea.id);
+        if !known {
+            listed.push(idea);
+        }
+    }
+    listed.len() - before
+}
+
+/// Split ideas into the pinned ones and the rest, keeping the order within each part.
+///
+/// The list shows the pinned part in its own section above the rest, so pinned ideas stay on top whatever the sort
+/// order while still following it among themselves.
+pub fn split_pinned(ideas: Vec<IdeaSummary>) -> (Vec<IdeaSummary>, Vec<IdeaSummary>) {
+    ideas.into_iter().partition(|idea| idea.pinned)
+}
+
+impl From<&str> for ListQuery {
+    fn from(query: &str) -> Self {
+        let mut parsed = ListQuery::default();
+        for pair in query.trim_start_matches('?').split('&') {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            let value = percent_decode(value);
+            match key {
+                "q" => parsed.search = value,
+                "tag" if !value.trim().is_empty() && !parsed.tags.contains(&value.trim().to_string()) => {
+                    parsed.tags.push(value.trim().to_string())
+                }
+                "match" => parsed.tag_mode = TagMatchMode::parse(&value).unwrap_or_default(),
+                "author" => parsed.author = Some(AuthorFilter::for_author(Some(&value))),
+                "status" => parsed.status = IdeaStatus::parse(&value),
+                "due" => parsed.due_soon = value == "soon",
+                "sort" => parsed.sort = SortOrder::parse(&value).unwrap_or_default(),
+                _ => {}
+            }
+        }
+        parsed
+    }
+}
+
+/// Writes only the parameters that differ from the defaults, so the plain list has a bare URL
+impl fmt::Display for ListQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut pairs = Vec::new();
+        if !self.search.is_empty() {
+            pairs.push(format!("q={}", percent_encode(&self.search)));
+        }
+        for tag in &self.tags {
+            pairs.push(format!("tag={}", percent_encode(tag)));
+        }
+        if self.tag_mode != TagMatchMode::All {
+            pairs.push(format!("match={}", self.tag_mode.as_str()));
+        }
+        if let Some(author) = &self.author {
+            pairs.push(format!("author={}", percent_encode(author.name().unwrap_or_default())));
+        }
+        if let Some(status) = self.status {
+            pairs.push(format!("status={}", status.as_str()));
+        }
+        if self.due_soon {
+            pairs.push("due=soon".to_string());
+        }
+        if self.sort != SortOrder::Manual {
+            pairs.push(format!("sort={}", self.sort.as_str()));
+        }
+        write!(f, "{}", pairs.join("&"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn now() -> NaiveDateTime {
+        NaiveDateTime::parse_from_str("2024-06-10 23:30:00", "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    fn idea(title: &str, tags: &[&str]) -> IdeaSummary {
+        IdeaSummary {
+            title: title.to_string(),
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let query = ListQuery {
+            search: "rain & sun".to_string(),
+            tags: vec!["weather/now".to_string()],
+            due_soon: true,
+            sort: SortOrder::TitleDesc,
+            ..Default::default()
+        };
+        let encoded = query.to_string();
+        assert_eq!(encoded, "q=rain%20%26%20sun&tag=weather%2Fnow&due=soon&sort=title-desc");
+        assert_eq!(ListQuery::from(encoded.as_str()), query);
+    }
+
+    #[test]
+    fn test_defaults_encode_to_nothing() {
+        assert_eq!(ListQuery::default().to_string(), "");
+        assert_eq!(ListQuery::from(""), ListQuery::default());
+    }
+
+    #[test]
+    fn test_missing_parameters_use_defaults() {
+        let query = ListQuery::from("tag=ux");
+        assert_eq!(query.tags, vec!["ux"]);
+        assert_eq!(query.tag_mode, TagMatchMode::All);
+        assert_eq!(query.search, "");
+        assert_eq!(query.sort, SortOrder::Manual);
+    }
+
+    #[test]
+    fn test_extra_parameters_are_ignored() {
+        let query = ListQuery::from("?utm_source=mail&sort=title&flag&=x");
+        assert_eq!(
+            query,
+            ListQuery {
+                sort: SortOrder::Title,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_invalid_parameters_fall_back() {
+        let query = ListQuery::from("sort=sideways&page=-2&tag=%20&match=most");
+        assert_eq!(query, ListQuery::default());
+        // Links from before the list scrolled endlessly still open it
+        assert_eq!(ListQuery::from("page=3"), ListQuery::default());
+        assert_eq!(ListQuery::from("q=a+b").search, "a b");
+    }
+
+    #[test]
+    fn test_apply_filters_and_sorts() {
+        let ideas = vec![idea("beta", &["ux"]), idea("Alpha", &["ux", "api"]), idea("gamma", &["api"])];
+
+        let query = ListQuery {
+            tags: vec!["ux".to_string()],
+            sort: SortOrder::Title,
+            ..Default::default()
+        };
+        let titles: Vec<String> = query.apply(&ideas, now()).into_iter().map(|idea| idea.title).collect();
+        assert_eq!(titles, vec!["Alpha", "beta"]);
+
+        let query = ListQuery {
+            search: "AP".to_string(),
+            ..Default::default()
+        };
+        let titles: Vec<String> = query.apply(&ideas, now()).into_iter().map(|idea| idea.title).collect();
+        assert_eq!(titles, vec!["Alpha", "gamma"]);
+    }
+
+    #[test]
+    fn test_sort_by_due_date_keeps_undated_last() {
+        let dated = |title: &str, day: Option<u32>| IdeaSummary {
+            due_date: day.map(|day| chrono::NaiveDate::from_ymd_opt(2024, 6, day).unwrap()),
+            ..idea(title, &[])
+        };
+        let ideas = vec![dated("none", None), dated("late", Some(20)), dated("soon", Some(11))];
+
+        let titles = |sort| -> Vec<String> {
+            let query = ListQuery { sort, ..Default::default() };
+            query.apply(&ideas, now()).into_iter().map(|idea| idea.title).collect()
+        };
+        assert_eq!(titles(SortOrder::Due), vec!["soon", "late", "none"]);
+        assert_eq!(titles(SortOrder::DueDesc), vec!["late", "soon", "none"]);
+        assert_eq!(ListQuery::from("sort=due-desc").sort, SortOrder::DueDesc);
+    }
+
+    #[test]
+    fn test_sort_by_date_added_matches_cursor_order() {
+        let added = |title: &str, id: &str, seconds: Option<i64>| IdeaSummary {
+            id: Some(id.to_string()),
+            created_at: seconds.and_then(|seconds| chrono::DateTime::from_timestamp(1_700_000_000 + seconds, 0)),
+            ..idea(title, &[])
+        };
+        let ideas = vec![
+            added("later", "ideas:a", Some(10)),
+            added("tied b", "ideas:c", Some(5)),
+            added("legacy", "ideas:z", None),
+            added("tied a", "ideas:b", Some(5)),
+        ];
+
+        let query = ListQuery::from("sort=added");
+        assert!(query.pages_by_cursor());
+        let titles: Vec<String> = query.apply(&ideas, now()).into_iter().map(|idea| idea.title).collect();
+        assert_eq!(titles, vec!["legacy", "tied a", "tied b", "later"]);
+
+        // Filtered, the list pages by offset like in every other order
+        assert!(!ListQuery::from("sort=added&tag=ux").pages_by_cursor());
+        assert!(!ListQuery::default().pages_by_cursor());
+    }
+
+    #[test]
+    fn test_column_headers_toggle_the_sort_order() {
+        assert_eq!(SortOrder::Manual.toggled(SortColumn::Title), SortOrder::Title);
+        assert_eq!(SortOrder::Title.toggled(SortColumn::Title), SortOrder::TitleDesc);
+        assert_eq!(SortOrder::TitleDesc.toggled(SortColumn::Title), SortOrder::Title);
+        assert_eq!(SortOrder::Title.toggled(SortColumn::Due), SortOrder::Due);
+        assert_eq!(SortOrder::Due.toggled(SortColumn::Due), SortOrder::DueDesc);
+
+        assert_eq!(SortOrder::TitleDesc.direction(SortColumn::Title), Some(false));
+        assert_eq!(SortOrder::TitleDesc.direction(SortColumn::Due), None);
+        assert_eq!(SortOrder::Manual.direction(SortColumn::Title), None);
+    }
+
+    #[test]
+    fn test_several_tags_roundtrip() {
+        let query = ListQuery::from("tag=ux&tag=api&tag=ux&match=any");
+        assert_eq!(query.tags, vec!["ux", "api"]);
+        assert_eq!(query.tag_mode, TagMatchMode::Any);
+        assert_eq!(query.to_string(), "tag=ux&tag=api&match=any");
+        assert_eq!(ListQuery::from("tag=ux&match=all").to_string(), "tag=ux");
+    }
+
+    #[test]
+    fn test_apply_all_or_any_tags() {
+        let ideas = vec![idea("beta", &["ux"]), idea("Alpha", &["ux", "api"]), idea("gamma", &["api", "db"])];
+        let titles = |query: &ListQuery| -> Vec<String> {
+            query.apply(&ideas, now()).into_iter().map(|idea| idea.title).collect()
+        };
+
+        let all = ListQuery::default().with_tag_toggled("ux").with_tag_toggled(" api ");
+        assert_eq!(titles(&all), vec!["Alpha"]);
+        let any = ListQuery {
+            tag_mode: TagMatchMode::Any,
+            ..all.clone()
+        };
+        assert_eq!(titles(&any), vec!["beta", "Alpha", "gamma"]);
+
+        // Unknown tags match nothing, deselecting everything lists everything again
+        assert!(titles(&all.with_tag_toggled("nobody")).is_empty());
+        assert_eq!(titles(&all.with_tag_toggled("ux").with_tag_toggled("api")).len(), 3);
+        assert!(all.with_tag_toggled("ux").with_tag_toggled("api").is_manual_unfiltered());
+    }
+
+    #[test]
+    fn test_author_filter() {
+        let by = |title: &str, author: Option<&str>| IdeaSummary {
+            author: author.map(str::to_string),
+            ..idea(title, &[])
+        };
+        let ideas = vec![
+            by("mine", Some("Sam")),
+            by("theirs", Some("Alex")),
+            by("old", None),
+            by("also mine", Some("Sam")),
+        ];
+        let titles = |query: &str| -> Vec<String> {
+            ListQuery::from(query).apply(&ideas, now()).into_iter().map(|idea| idea.title).collect()
+        };
+
+        assert_eq!(titles("author=Sam"), vec!["mine", "also mine"]);
+        assert_eq!(titles("author="), vec!["old"]);
+        assert_eq!(titles("author=%20"), vec!["old"]);
+        assert_eq!(titles("author=Nobody"), Vec::<String>::new());
+        assert_eq!(titles("").len(), 4);
+
+        let query = ListQuery::from("author=Sam%20Lee");
+        assert_eq!(query.author, Some(AuthorFilter::By("Sam Lee".to_string())));
+        assert_eq!(query.to_string(), "author=Sam%20Lee");
+        assert_eq!(ListQuery::from("author").to_string(), "author=");
+        assert!(!ListQuery::from("author").is_manual_unfiltered());
+    }
+
+    #[test]
+    fn test_status_filter() {
+        let with = |title: &str, status| IdeaSummary {
+            status,
+            ..idea(title, &[])
+        };
+        let ideas = vec![with("new", IdeaStatus::Inbox), with("started", IdeaStatus::Building)];
+        let titles = |query: &ListQuery| -> Vec<String> {
+            query.apply(&ideas, now()).into_iter().map(|idea| idea.title).collect()
+        };
+
+        let query = ListQuery::from("status=building");
+        assert_eq!(query.status, Some(IdeaStatus::Building));
+        assert_eq!(titles(&query), vec!["started"]);
+        assert_eq!(query.to_string(), "status=building");
+        assert!(!query.is_unfiltered());
+        assert_eq!(ListQuery::from("status=someday"), ListQuery::default());
+
+        // Clicking the same status again lists every status
+        let toggled = query.with_status_toggled(IdeaStatus::Inbox);
+        assert_eq!(titles(&toggled), vec!["new"]);
+        assert_eq!(toggled.with_status_toggled(IdeaStatus::Inbox), ListQuery::default());
+    }
+
+    #[test]
+    fn test_due_soon_filter() {
+        let due = |title: &str, date: &str| IdeaSummary {
+            due_date: crate::utils::parse_due_date(date),
+            ..idea(title, &[])
+        };
+        let ideas = vec![
+            due("overdue", "2024-06-09"),
+            due("tonight", "2024-06-10"),
+            due("in three days", "2024-06-13"),
+            due("in four days", "2024-06-14"),
+            idea("undated", &[]),
+        ];
+
+        let query = ListQuery {
+            due_soon: true,
+            ..Default::default()
+        };
+        let titles: Vec<String> = query.apply(&ideas, now()).into_iter().map(|idea| idea.title).collect();
+        assert_eq!(titles, vec!["overdue", "tonight", "in three days"]);
+        assert!(!query.is_manual_unfiltered());
+        assert_eq!(ListQuery::from("due=later"), ListQuery::default());
+    }
+
+    #[test]
+    fn test_pinned_ideas_come_first_in_sort_order() {
+        let mut ideas = vec![idea("delta", &[]), idea("alpha", &["ux"]), idea("charlie", &[]), idea("bravo", &["ux"])];
+        ideas[0].pinned = true;
+        ideas[3].pinned = true;
+
+        let query = ListQuery {
+            sort: SortOrder::Title,
+            ..Default::default()
+        };
+        let (pinned, rest) = split_pinned(query.apply(&ideas, now()));
+        let titles = |ideas: &[IdeaSummary]| ideas.iter().map(|idea| idea.title.clone()).collect::<Vec<_>>();
+        assert_eq!(titles(&pinned), vec!["bravo", "delta"]);
+        assert_eq!(titles(&rest), vec!["alpha", "charlie"]);
+
+        // Filters still apply to pinned ideas
+        let query = ListQuery {
+            tags: vec!["ux".to_string()],
+            sort: SortOrder::TitleDesc,
+            ..Default::default()
+        };
+        let (pinned, rest) = split_pinned(query.apply(&ideas, now()));
+        assert_eq!(titles(&pinned), vec!["bravo"]);
+        assert_eq!(titles(&rest), vec!["alpha"]);
+    }
+
+    #[test]
+    fn test_split_without_pins() {
+        let ideas = vec![idea("a", &[]), idea("b", &[])];
+        let (pinned, rest) = split_pinned(ideas.clone());
+        assert!(pinned.is_empty());
+        assert_eq!(rest, ideas);
+    }
+
+    #[test]
+    fn test_pages_list_pinned_ideas_first() {
+        let mut ideas: Vec<IdeaSummary> = (0..5).map(|n| idea(&format!("idea {}", n), &[])).collect();
+        ideas[3].pinned = true;
+        let filter = ListQuery::default().filter(now());
+        let titles = |page: Vec<IdeaSummary>| page.into_iter().map(|idea| idea.title).collect::<Vec<_>>();
+
+        assert_eq!(titles(filter.page(&ideas, 0, 2)), vec!["idea 3", "idea 0"]);
+        assert_eq!(titles(filter.page(&ideas, 2, 2)), vec!["idea 1", "idea 2"]);
+        assert_eq!(titles(filter.page(&ideas, 4, 2)), vec!["idea 4"]);
+        assert!(filter.page(&ideas, 9, 2).is_empty());
+        assert_eq!(filter.page(&ideas, 0, usize::MAX).len(), 5);
+    }
+
+    #[test]
+    fn test_filter_survives_serialization() {
+        let filter = ListQuery::from("q=rain&tag=ux&match=any&due=soon&sort=title-desc").filter(now());
+        let json = serde_json::to_string(&filter).unwrap();
+        assert!(json.contains("\"sort\":\"title-desc\""));
+        assert_eq!(serde_json::from_str::<IdeaFilter>(&json).unwrap(), filter);
+    }
+
+    #[test]
+    fn test_append_page_skips_ideas_already_listed() {
+        let with_id = |id: &str| IdeaSummary {
+            id: Some(id.to_string()),
+            ..idea(id, &[])
+        };
+        let mut listed = vec![with_id("a"), with_id("b")];
+
+        // An idea added at the top after the first page pushes "b" onto the second page too
+        assert_eq!(append_page(&mut listed, vec![with_id("b"), with_id("c")]), 1);
+        let ids: Vec<_> = listed.iter().map(|idea| idea.title.as_str()).collect();
+        assert_eq!(ids, vec!["a", "b", "c"]);
+
+        assert_eq!(append_page(&mut listed, vec![with_id("a"), with_id("c")]), 0);
+        assert_eq!(append_page(&mut listed, vec![idea("unsaved", &[]), idea("unsaved", &[])]), 2);
+        assert_eq!(listed.len(), 5);
+    }
+}
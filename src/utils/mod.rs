@@ -0,0 +1,104 @@
+//! The utils module contains pure helper functions shared between the client and the server. Nothing in here
+//! touches the database or the DOM, which keeps every helper easy to unit test.
+
+mod grouping;
+pub use grouping::{group_ideas_by_tag, UNTAGGED};
+
+mod slug;
+pub use slug::{slugify, unique_slug};
+
+mod stale;
+pub use stale::StaleData;
+
+mod rank;
+pub use rank::{move_item, neighbors_at, next_rank, plan_reorder, rank_between, RankUpdate};
+
+mod retry;
+pub use retry::RetryPolicy;
+
+mod text_stats;
+pub use text_stats::{reading_minutes, text_stats_label, textarea_rows, truncate_text, word_count};
+
+mod url;
+pub use url::{percent_decode, percent_encode, search_from_path};
+
+mod due;
+pub use due::{due_label, due_status, format_due_date, local_now, parse_due_date, DueStatus, DUE_SOON_DAYS};
+
+mod relative_time;
+pub use relative_time::{absolute_time, local_time, relative_time, RELATIVE_DAYS};
+
+mod list_query;
+pub use list_query::{append_page, split_pinned, IdeaFilter, ListQuery, SortColumn, SortOrder, MAX_PAGE_SIZE, PAGE_SIZE};
+
+mod similarity;
+pub use similarity::{is_possible_duplicate, title_similarity};
+
+mod report;
+pub use report::{report_ideas, ReportBlock, ReportIdea};
+
+mod fuzzy;
+pub use fuzzy::best_matches;
+
+mod search;
+pub use search::{merge_search_results, search_score};
+
+mod snippet;
+pub use snippet::{find_matches, highlight, search_hit, snippet_around, MatchedField, SearchHit, SNIPPET_CHARS};
+
+mod save_queue;
+pub use save_queue::SaveQueue;
+
+mod undo;
+pub use undo::{UndoStack, MAX_UNDO};
+
+mod unsaved;
+pub use unsaved::{unsaved_fields, LeaveGuard};
+
+mod board;
+pub use board::{move_card, partition_by_status, rollback_move, BoardMove};
+
+mod status_bar;
+pub use status_bar::{segment_widths, status_counts, MIN_SEGMENT_PERCENT};
+
+mod card_actions;
+pub use card_actions::{card_actions, CardAction};
+
+mod tag_edit;
+pub use tag_edit::{apply_tag_change, edit_tags, rollback_tags, settle_tags, TagChange, TagEdit};
+
+mod linkify;
+pub use linkify::{linkify, TextSegment};
+
+mod swipe;
+pub use swipe::{SwipeAxis, SwipeGesture, SWIPE_ACTIONS_WIDTH, SWIPE_FLICK_VELOCITY, SWIPE_SLOP};
+
+mod chart;
+pub use chart::{bar_rects, BarRect, MIN_BAR_HEIGHT};
+
+mod diff;
+pub use diff::{diff_text, DiffSpan, MAX_DIFF_CELLS};
+
+mod coalesce;
+pub use coalesce::{Coalescer, Settled};
+
+mod sanitize;
+pub use sanitize::sanitize_text;
+
+mod idle;
+pub use idle::{Activity, IdleChange, IdleTracker, IDLE_WAKE_MOVES, IDLE_WAKE_WINDOW_MS};
+
+mod whitespace;
+pub use whitespace::{normalize_newlines, normalize_statements, normalize_title};
+
+mod cursor;
+pub use cursor::{creation_key, CursorPage, PageCursor};
+
+mod hover;
+pub use hover::{hover_card_position, HoverIntent, HoverPlacement, HoverPosition, HOVER_CARD_GAP, HOVER_INTENT_MS};
+
+mod icon;
+pub use icon::{is_emoji_icon, tidy_icon, ICON_CHOICES, MAX_ICON_CODE_POINTS};
+
+mod quick_capture;
+pub use quick_capture::parse_quick_capture;
@@ -0,0 +1,69 @@
+/// Punctuation that ends a sentence rather than a tag, as in "call the bank #money."
+const TAG_TRAILING_PUNCTUATION: &[char] = &[',', '.', ';', ':', '!', '?'];
+
+/// Split a line typed into the quick capture bar into a title and tags.
+///
+/// Every word starting with `#` is a tag, without the `#` and any punctuation after it, and is left out of the
+/// title; tags are kept once each, in the order typed. A word starting with `##` stays in the title with one `#`,
+/// so "fix ##42 #bug" is titled "fix #42", and a lone `#` stays as it is. Whitespace in the title collapses to
+/// single spaces. `None` when no title is left, as for a line of only tags.
+pub fn parse_quick_capture(text: &str) -> Option<(String, Vec<String>)> {
+    let mut words = Vec::new();
+    let mut tags: Vec<String> = Vec::new();
+    for word in text.split_whitespace() {
+        if let Some(escaped) = word.strip_prefix("##") {
+            words.push(format!("#{}", escaped));
+            continue;
+        }
+        match word.strip_prefix('#').map(|tag| tag.trim_end_matches(TAG_TRAILING_PUNCTUATION)) {
+            Some("") => words.push(word.to_string()),
+            Some(tag) => {
+                if !tags.iter().any(|known| known == tag) {
+                    tags.push(tag.to_string());
+                }
+            }
+            None => words.push(word.to_string()),
+        }
+    }
+    let title = words.join(" ");
+    (!title.is_empty()).then_some((title, tags))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parsed(text: &str) -> (String, Vec<String>) {
+        parse_quick_capture(text).unwrap()
+    }
+
+    #[test]
+    fn test_plain_text_is_the_title() {
+        assert_eq!(parsed("  Bike   repair café "), ("Bike repair café".to_string(), vec![]));
+    }
+
+    #[test]
+    fn test_hash_words_become_tags() {
+        assert_eq!(
+            parsed("#garden Rain barrel #water for the #garden beds #diy."),
+            (
+                "Rain barrel for the beds".to_string(),
+                vec!["garden".to_string(), "water".to_string(), "diy".to_string()]
+            )
+        );
+    }
+
+    #[test]
+    fn test_double_hash_is_a_literal_hash() {
+        assert_eq!(parsed("fix ##42 #bug"), ("fix #42".to_string(), vec!["bug".to_string()]));
+        assert_eq!(parsed("## heading"), ("# heading".to_string(), vec![]));
+        assert_eq!(parsed("C# tips # and tricks"), ("C# tips # and tricks".to_string(), vec![]));
+    }
+
+    #[test]
+    fn test_lines_without_a_title_are_refused() {
+        assert_eq!(parse_quick_capture("#ux #api"), None);
+        assert_eq!(parse_quick_capture("   "), None);
+        assert_eq!(parse_quick_capture(""), None);
+    }
+}
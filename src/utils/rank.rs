@@ -0,0 +1,227 @@
+/// Smallest gap between neighbouring ranks before the whole list gets renumbered.
+/// Repeatedly inserting between the same two items halves the gap each time, so this is reached after ~50 moves.
+pub const MIN_RANK_GAP: f64 = 1e-9;
+
+/// Rank for a newly created idea: one past the current maximum
+pub fn next_rank(max_rank: Option<f64>) -> f64 {
+    match max_rank {
+        Some(max) => max.floor() + 1.0,
+        None => 1.0,
+    }
+}
+
+/// Rank strictly between two neighbours, or `None` when the gap is too small and the list needs renumbering.
+///
+/// `before` is the rank of the item that will sit above the moved one, `after` the one below it.
+pub fn rank_between(before: Option<f64>, after: Option<f64>) -> Option<f64> {
+    match (before, after) {
+        (None, None) => Some(1.0),
+        (Some(before), None) => Some(before + 1.0),
+        (None, Some(after)) => Some(after - 1.0),
+        (Some(before), Some(after)) => {
+            if after - before < MIN_RANK_GAP {
+                return None;
+            }
+            let mid = before + (after - before) / 2.0;
+            // Floating point may round the midpoint onto one of the neighbours
+            (mid > before && mid < after).then_some(mid)
+        }
+    }
+}
+
+/// How to persist a reorder
+#[derive(Debug, Clone, PartialEq)]
+pub enum RankUpdate {
+    /// Only the moved idea gets a new rank
+    Single(f64),
+    /// Every idea gets renumbered 1, 2, 3, ... in the new order
+    Renormalize(Vec<(String, f64)>),
+}
+
+/// Work out the rank change for moving `moving` between `before` and `after`.
+///
+/// `ordered` is every idea id with its rank, sorted by rank ascending.
+pub fn plan_reorder(
+    ordered: &[(String, f64)],
+    moving: &str,
+    before: Option<&str>,
+    after: Option<&str>,
+) -> Result<RankUpdate, String> {
+    if before == Some(moving) || after == Some(moving) {
+        return Err("An idea can't be placed next to itself".to_string());
+    }
+    if before.is_some() && before == after {
+        return Err("The neighbours of a move must be different ideas".to_string());
+    }
+
+    let rank_of = |id: &str| {
+        ordered
+            .iter()
+            .find(|(candidate, _)| candidate == id)
+            .map(|(_, rank)| *rank)
+            .ok_or_else(|| format!("Idea not found: {}", id))
+    };
+
+    rank_of(moving)?;
+    let before_rank = before.map(rank_of).transpose()?;
+    let after_rank = after.map(rank_of).transpose()?;
+
+    if let Some(rank) = rank_between(before_rank, after_rank) {
+        return Ok(RankUpdate::Single(rank));
+    }
+
+    // Gap exhausted: rebuild the order with the idea in its new slot and renumber everything
+    let mut ids: Vec<&str> = ordered
+        .iter()
+        .map(|(id, _)| id.as_str())
+        .filter(|id| *id != moving)
+        .collect();
+    let insert_at = match (before, after) {
+        (Some(before), _) => ids.iter().position(|id| *id == before).map_or(0, |i| i + 1),
+        (None, Some(after)) => ids.iter().position(|id| *id == after).unwrap_or(0),
+        (None, None) => ids.len(),
+    };
+    ids.insert(insert_at, moving);
+
+    Ok(RankUpdate::Renormalize(
+        ids.into_iter()
+            .enumerate()
+            .map(|(i, id)| (id.to_string(), (i + 1) as f64))
+            .collect(),
+    ))
+}
+
+/// Move the item at `from` to index `to`, shifting the items in between
+pub fn move_item<T>(items: &mut Vec<T>, from: usize, to: usize) {
+    if from >= items.len() || to >= items.len() || from == to {
+        return;
+    }
+    let item = items.remove(from);
+    items.insert(to, item);
+}
+
+/// The items directly above and below `index`
+pub fn neighbors_at<T: Clone>(items: &[T], index: usize) -> (Option<T>, Option<T>) {
+    let before = index.checked_sub(1).and_then(|i| items.get(i)).cloned();
+    let after = items.get(index + 1).cloned();
+    (before, after)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ordered(ranks: &[(&str, f64)]) -> Vec<(String, f64)> {
+        ranks.iter().map(|(id, rank)| (id.to_string(), *rank)).collect()
+    }
+
+    /// Apply a plan to a list the way the server does and return the ids sorted by rank
+    fn apply(list: &mut Vec<(String, f64)>, moving: &str, update: RankUpdate) -> Vec<String> {
+        match update {
+            RankUpdate::Single(rank) => {
+                list.iter_mut().find(|(id, _)| id == moving).unwrap().1 = rank;
+            }
+            RankUpdate::Renormalize(ranks) => *list = ranks,
+        }
+        list.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+        list.iter().map(|(id, _)| id.clone()).collect()
+    }
+
+    #[test]
+    fn test_next_rank() {
+        assert_eq!(next_rank(None), 1.0);
+        assert_eq!(next_rank(Some(3.0)), 4.0);
+        assert_eq!(next_rank(Some(2.5)), 3.0);
+    }
+
+    #[test]
+    fn test_rank_between() {
+        assert_eq!(rank_between(None, None), Some(1.0));
+        assert_eq!(rank_between(Some(2.0), None), Some(3.0));
+        assert_eq!(rank_between(None, Some(2.0)), Some(1.0));
+        assert_eq!(rank_between(Some(1.0), Some(2.0)), Some(1.5));
+        assert_eq!(rank_between(Some(1.0), Some(1.0 + MIN_RANK_GAP / 2.0)), None);
+    }
+
+    #[test]
+    fn test_plan_reorder_simple_move() {
+        let list = ordered(&[("a", 1.0), ("b", 2.0), ("c", 3.0)]);
+        let plan = plan_reorder(&list, "c", Some("a"), Some("b")).unwrap();
+        assert_eq!(plan, RankUpdate::Single(1.5));
+    }
+
+    #[test]
+    fn test_plan_reorder_rejects_bad_input() {
+        let list = ordered(&[("a", 1.0), ("b", 2.0)]);
+        assert!(plan_reorder(&list, "a", Some("a"), None).is_err());
+        assert!(plan_reorder(&list, "a", Some("b"), Some("b")).is_err());
+        assert!(plan_reorder(&list, "missing", None, Some("a")).is_err());
+        assert!(plan_reorder(&list, "a", Some("missing"), None).is_err());
+    }
+
+    #[test]
+    fn test_repeated_insertion_at_same_spot_renormalizes_and_keeps_order() {
+        // Adversarial: keep moving the last item between the first two
+        let mut list = ordered(&[("a", 1.0), ("b", 2.0)]);
+        let mut expected = vec!["a".to_string(), "b".to_string()];
+        let mut renormalized = false;
+
+        for i in 0..200 {
+            let id = format!("n{}", i);
+            let max = list.iter().map(|(_, r)| *r).fold(f64::MIN, f64::max);
+            list.push((id.clone(), next_rank(Some(max))));
+
+            let plan = plan_reorder(&list, &id, Some("a"), Some(&expected[1])).unwrap();
+            renormalized |= matches!(plan, RankUpdate::Renormalize(_));
+            let order = apply(&mut list, &id, plan);
+
+            expected.insert(1, id);
+            assert_eq!(order, expected);
+        }
+
+        assert!(renormalized, "the gap should have run out at least once");
+    }
+
+    #[test]
+    fn test_repeated_insertion_at_top() {
+        let mut list = ordered(&[("a", 1.0)]);
+        let mut expected = vec!["a".to_string()];
+
+        for i in 0..100 {
+            let id = format!("n{}", i);
+            list.push((id.clone(), next_rank(Some(100.0 + i as f64))));
+
+            let plan = plan_reorder(&list, &id, None, Some(&expected[0])).unwrap();
+            let order = apply(&mut list, &id, plan);
+
+            expected.insert(0, id);
+            assert_eq!(order, expected);
+        }
+    }
+
+    #[test]
+    fn test_renormalize_moves_to_requested_slot() {
+        let gap = MIN_RANK_GAP / 4.0;
+        let list = ordered(&[("a", 1.0), ("b", 1.0 + gap), ("c", 5.0)]);
+
+        let plan = plan_reorder(&list, "c", Some("a"), Some("b")).unwrap();
+        assert_eq!(
+            plan,
+            RankUpdate::Renormalize(ordered(&[("a", 1.0), ("c", 2.0), ("b", 3.0)]))
+        );
+    }
+
+    #[test]
+    fn test_move_item_and_neighbors() {
+        let mut items = vec!["a", "b", "c", "d"];
+        move_item(&mut items, 3, 1);
+        assert_eq!(items, vec!["a", "d", "b", "c"]);
+        assert_eq!(neighbors_at(&items, 1), (Some("a"), Some("b")));
+        assert_eq!(neighbors_at(&items, 0), (None, Some("d")));
+        assert_eq!(neighbors_at(&items, 3), (Some("b"), None));
+
+        // Out of range moves are ignored
+        move_item(&mut items, 7, 0);
+        assert_eq!(items, vec!["a", "d", "b", "c"]);
+    }
+}
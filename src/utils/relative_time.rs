@@ -0,0 +1,154 @@
+use chrono::{DateTime, Datelike, Local, NaiveDateTime, Utc};
+
+/// Times this many calendar days away or more show as a date rather than a weekday
+pub const RELATIVE_DAYS: i64 = 7;
+
+/// Within this many hours a time reads as "3 h ago" even when it was yesterday, as "yesterday" for something an
+/// hour before midnight would be misleading just after it
+const RELATIVE_HOURS: i64 = 12;
+
+/// How long ago `at` was, or how far off it is, as seen at `now`: "just now", "5 min ago", "3 h ago",
+/// "yesterday", "last Tuesday", and the date ("Nov 3", with the year when it isn't this year's) from
+/// [`RELATIVE_DAYS`] days on. Future times read the same way forward: "in 5 min", "tomorrow", "on Tuesday".
+///
+/// Both are local and timezone-less, like [`super::due_label`]; a minute either way, as between two clocks a bit
+/// apart, is "just now".
+pub fn relative_time(at: NaiveDateTime, now: NaiveDateTime) -> String {
+    let elapsed = now - at;
+    if elapsed.num_minutes() == 0 {
+        return "just now".to_string();
+    }
+    let days = (now.date() - at.date()).num_days();
+    let weekday = at.format("%A");
+    if elapsed.num_seconds() > 0 {
+        let minutes = elapsed.num_minutes();
+        let hours = elapsed.num_hours();
+        match days {
+            _ if minutes < 60 => format!("{} min ago", minutes),
+            _ if hours < RELATIVE_HOURS || days == 0 => format!("{} h ago", hours),
+            1 => "yesterday".to_string(),
+            days if days < RELATIVE_DAYS => format!("last {}", weekday),
+            _ => date_label(at, now),
+        }
+    } else {
+        let minutes = -elapsed.num_minutes();
+        let hours = -elapsed.num_hours();
+        match -days {
+            _ if minutes < 60 => format!("in {} min", minutes),
+            _ if hours < RELATIVE_HOURS || days == 0 => format!("in {} h", hours),
+            1 => "tomorrow".to_string(),
+            days if days < RELATIVE_DAYS => format!("on {}", weekday),
+            _ => date_label(at, now),
+        }
+    }
+}
+
+/// "Nov 3", or "Nov 3, 2023" outside the year of `now`
+fn date_label(at: NaiveDateTime, now: NaiveDateTime) -> String {
+    if at.year() == now.year() {
+        at.format("%b %-d").to_string()
+    } else {
+        at.format("%b %-d, %Y").to_string()
+    }
+}
+
+/// The exact time, as shown when relative times are turned off
+pub fn absolute_time(at: DateTime<Utc>) -> String {
+    at.format("%Y-%m-%d %H:%M UTC").to_string()
+}
+
+/// `at` on the local wall clock, for [`relative_time`]
+pub fn local_time(at: DateTime<Utc>) -> NaiveDateTime {
+    at.with_timezone(&Local).naive_local()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(value: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    fn check(now: &str, cases: &[(&str, &str)]) {
+        let now = at(now);
+        for (time, expected) in cases {
+            assert_eq!(relative_time(at(time), now), *expected, "{} seen at {}", time, now);
+        }
+    }
+
+    #[test]
+    fn test_past_times() {
+        // A Thursday afternoon
+        check(
+            "2024-11-07 15:00:00",
+            &[
+                ("2024-11-07 15:00:00", "just now"),
+                ("2024-11-07 14:59:30", "just now"),
+                ("2024-11-07 14:59:00", "1 min ago"),
+                ("2024-11-07 14:01:00", "59 min ago"),
+                ("2024-11-07 14:00:00", "1 h ago"),
+                ("2024-11-07 03:00:00", "12 h ago"),
+                ("2024-11-07 00:00:00", "15 h ago"),
+                ("2024-11-06 23:00:00", "yesterday"),
+                ("2024-11-06 00:00:00", "yesterday"),
+                ("2024-11-05 10:00:00", "last Tuesday"),
+                ("2024-11-01 09:00:00", "last Friday"),
+                ("2024-10-31 16:00:00", "Oct 31"),
+                ("2024-01-02 09:00:00", "Jan 2"),
+                ("2023-12-25 10:00:00", "Dec 25, 2023"),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_future_times() {
+        check(
+            "2024-11-07 15:00:00",
+            &[
+                ("2024-11-07 15:00:30", "just now"),
+                ("2024-11-07 15:01:00", "in 1 min"),
+                ("2024-11-07 15:05:00", "in 5 min"),
+                ("2024-11-07 18:00:00", "in 3 h"),
+                ("2024-11-08 02:00:00", "in 11 h"),
+                ("2024-11-08 09:00:00", "tomorrow"),
+                ("2024-11-12 09:00:00", "on Tuesday"),
+                ("2024-11-14 09:00:00", "Nov 14"),
+                ("2025-01-02 09:00:00", "Jan 2, 2025"),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_just_after_midnight() {
+        check(
+            "2024-11-07 00:30:00",
+            &[
+                ("2024-11-06 23:50:00", "40 min ago"),
+                ("2024-11-06 20:00:00", "4 h ago"),
+                ("2024-11-06 08:00:00", "yesterday"),
+                ("2024-11-05 23:00:00", "last Tuesday"),
+                ("2024-11-07 11:00:00", "in 10 h"),
+                ("2024-11-07 23:59:00", "in 23 h"),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_new_year() {
+        check(
+            "2025-01-01 09:00:00",
+            &[
+                ("2024-12-31 22:00:00", "11 h ago"),
+                ("2024-12-31 20:00:00", "yesterday"),
+                ("2024-12-20 08:00:00", "Dec 20, 2024"),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_absolute_time() {
+        let time = DateTime::parse_from_rfc3339("2024-11-03T15:04:11Z").unwrap().with_timezone(&Utc);
+        assert_eq!(absolute_time(time), "2024-11-03 15:04 UTC");
+    }
+}
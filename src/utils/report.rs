@@ -0,0 +1,138 @@
+//! Printable reports of ideas.
+//!
+//! The report pages render what [`report_ideas`] assembles and nothing else, so the formatting rules live here.
+//! There is no markdown renderer in the app: notes and descriptions are split into paragraphs, and lines starting
+//! with `#` (like the headings left by merging ideas) become headings.
+
+use super::format_due_date;
+use crate::db::{Idea, TagMatchMode};
+use crate::markdown::join_note_sections;
+
+/// A piece of a long text as the report shows it
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReportBlock {
+    Heading(String),
+    Paragraph(String),
+}
+
+/// One idea as the report shows it
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReportIdea {
+    pub title: String,
+    pub description: Vec<ReportBlock>,
+    pub tags: Vec<String>,
+    /// "What must be true" statements, printed with a box to tick
+    pub statements: Vec<String>,
+    pub notes: Vec<ReportBlock>,
+    /// ISO due date, if any
+    pub due: Option<String>,
+}
+
+impl From<&Idea> for ReportIdea {
+    fn from(idea: &Idea) -> Self {
+        Self {
+            title: idea.title.clone(),
+            description: report_blocks(&idea.description),
+            tags: idea.tags.clone(),
+            statements: idea
+                .what_must_be_true
+                .iter()
+                .map(|statement| statement.trim().to_string())
+                .filter(|statement| !statement.is_empty())
+                .collect(),
+            notes: [
+                report_blocks(&idea.development_notes),
+                report_blocks(&join_note_sections(&idea.sections)),
+            ]
+            .concat(),
+            due: idea.due_date.map(format_due_date),
+        }
+    }
+}
+
+/// Split a text into blocks at blank lines and heading lines. Lines within a paragraph are joined with spaces,
+/// the way a printed page reflows them anyway.
+pub fn report_blocks(text: &str) -> Vec<ReportBlock> {
+    fn flush(paragraph: &mut Vec<&str>, blocks: &mut Vec<ReportBlock>) {
+        if !paragraph.is_empty() {
+            blocks.push(ReportBlock::Paragraph(paragraph.join(" ")));
+            paragraph.clear();
+        }
+    }
+
+    let mut blocks = Vec::new();
+    let mut paragraph: Vec<&str> = Vec::new();
+
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() {
+            flush(&mut paragraph, &mut blocks);
+        } else if line.starts_with('#') {
+            flush(&mut paragraph, &mut blocks);
+            let heading = line.trim_start_matches('#').trim();
+            if !heading.is_empty() {
+                blocks.push(ReportBlock::Heading(heading.to_string()));
+            }
+        } else {
+            paragraph.push(line);
+        }
+    }
+    flush(&mut paragraph, &mut blocks);
+    blocks
+}
+
+/// The ideas of a report, in the given order, keeping those that pass the tag filter the same way the list does
+pub fn report_ideas(ideas: &[Idea], tags: &[String], mode: TagMatchMode) -> Vec<ReportIdea> {
+    ideas
+        .iter()
+        .filter(|idea| mode.matches(&idea.tags, tags))
+        .map(ReportIdea::from)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paragraph(text: &str) -> ReportBlock {
+        ReportBlock::Paragraph(text.to_string())
+    }
+
+    #[test]
+    fn test_blocks_split_at_blank_lines_and_headings() {
+        let text = "First line\nsame paragraph\n\n\nSecond\n## merged from Basil pot\nAsk the neighbours\n#\n";
+        assert_eq!(
+            report_blocks(text),
+            vec![
+                paragraph("First line same paragraph"),
+                paragraph("Second"),
+                ReportBlock::Heading("merged from Basil pot".to_string()),
+                paragraph("Ask the neighbours"),
+            ]
+        );
+        assert!(report_blocks("  \n\n ").is_empty());
+    }
+
+    #[test]
+    fn test_report_ideas_filter_by_tag() {
+        let idea = |title: &str, tags: &[&str]| Idea {
+            title: title.to_string(),
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            what_must_be_true: vec!["  ".to_string(), " People care ".to_string()],
+            due_date: chrono::NaiveDate::from_ymd_opt(2024, 6, 1),
+            ..Default::default()
+        };
+        let ideas = vec![idea("Herbs", &["garden"]), idea("Budget", &["money"])];
+
+        let all = report_ideas(&ideas, &[], TagMatchMode::All);
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].statements, vec!["People care"]);
+        assert_eq!(all[0].due.as_deref(), Some("2024-06-01"));
+
+        let garden = report_ideas(&ideas, &["garden".to_string()], TagMatchMode::All);
+        assert_eq!(garden.len(), 1);
+        assert_eq!(garden[0].title, "Herbs");
+
+        let either = report_ideas(&ideas, &["garden".to_string(), "money".to_string()], TagMatchMode::Any);
+        assert_eq!(either.len(), 2);
+    }
+}
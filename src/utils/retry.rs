@@ -0,0 +1,103 @@
+use std::time::Duration;
+
+/// How often and how patiently a failed fetch is retried
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    /// Total attempts, including the first one
+    pub max_attempts: u32,
+    /// Delay before the first retry; each further retry doubles it
+    pub base_delay: Duration,
+    /// Upper bound for a single delay
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(250),
+            max_delay: Duration::from_secs(4),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Delay before retry number `retry` (0 for the first retry).
+    ///
+    /// `jitter` in `[0, 1]` picks a point in the upper half of the exponential delay, so clients that failed
+    /// together don't all come back at the same moment.
+    pub fn delay(&self, retry: u32, jitter: f64) -> Duration {
+        let exponential = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_delay);
+        exponential.mul_f64(0.5 + jitter.clamp(0.0, 1.0) / 2.0)
+    }
+
+    /// Every delay this policy waits through before giving up
+    pub fn schedule(&self, jitter: f64) -> Vec<Duration> {
+        (0..self.max_attempts.saturating_sub(1))
+            .map(|retry| self.delay(retry, jitter))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 5,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(500),
+        }
+    }
+
+    #[test]
+    fn test_delays_double_until_capped() {
+        let delays = policy().schedule(1.0);
+        assert_eq!(
+            delays,
+            vec![
+                Duration::from_millis(100),
+                Duration::from_millis(200),
+                Duration::from_millis(400),
+                Duration::from_millis(500),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_jitter_stays_in_upper_half() {
+        assert_eq!(policy().delay(1, 0.0), Duration::from_millis(100));
+        assert_eq!(policy().delay(1, 0.5), Duration::from_millis(150));
+        assert_eq!(policy().delay(1, 1.0), Duration::from_millis(200));
+
+        // Out-of-range jitter is clamped rather than producing negative or oversized delays
+        assert_eq!(policy().delay(1, -3.0), Duration::from_millis(100));
+        assert_eq!(policy().delay(1, 7.0), Duration::from_millis(200));
+    }
+
+    #[test]
+    fn test_schedule_length_matches_retries() {
+        assert_eq!(RetryPolicy::default().schedule(0.5).len(), 2);
+
+        let once = RetryPolicy {
+            max_attempts: 1,
+            ..RetryPolicy::default()
+        };
+        assert!(once.schedule(0.5).is_empty());
+
+        let never = RetryPolicy {
+            max_attempts: 0,
+            ..RetryPolicy::default()
+        };
+        assert!(never.schedule(0.5).is_empty());
+    }
+
+    #[test]
+    fn test_huge_retry_counts_do_not_overflow() {
+        assert_eq!(policy().delay(u32::MAX, 1.0), Duration::from_millis(500));
+    }
+}
@@ -0,0 +1,248 @@
+/// HTML elements whose tags are taken out of text wherever they appear, attributes and all
+const HTML_ELEMENTS: &[&str] = &[
+    "a", "abbr", "address", "animate", "applet", "area", "article", "aside", "audio", "b", "base", "basefont", "bdi",
+    "bdo", "bgsound", "blink", "blockquote", "body", "br", "button", "canvas", "caption", "center", "cite", "code",
+    "col", "colgroup", "data", "datalist", "dd", "del", "details", "dfn", "dialog", "dir", "div", "dl", "dt", "em",
+    "embed", "fieldset", "figcaption", "figure", "font", "footer", "foreignobject", "form", "frame", "frameset", "h1",
+    "h2", "h3", "h4", "h5", "h6", "head", "header", "hr", "html", "i", "iframe", "image", "img", "input", "ins",
+    "isindex", "kbd", "keygen", "label", "layer", "legend", "li", "link", "listing", "main", "map", "mark",
+    "marquee", "math", "menu", "meta", "meter", "nav", "noembed", "noframes", "noscript", "object", "ol", "optgroup",
+    "option", "output", "p", "param", "picture", "plaintext", "pre", "progress", "q", "rp", "rt", "ruby", "s",
+    "samp", "script", "section", "select", "set", "slot", "small", "source", "span", "strike", "strong", "style",
+    "sub", "summary", "sup", "svg", "table", "tbody", "td", "template", "textarea", "tfoot", "th", "thead", "time",
+    "title", "tr", "track", "tt", "u", "ul", "use", "var", "video", "wbr", "xmp",
+];
+
+/// Elements dropped together with everything up to their closing tag
+const DROPPED_WITH_CONTENT: &[&str] = &["script", "style"];
+
+/// URL schemes that run code when followed, taken out wherever they appear
+const SCRIPT_SCHEMES: &[&str] = &["javascript:", "vbscript:", "data:text/html"];
+
+/// Most passes over a text; each pass can uncover a tag that was split by another one, like `<scr<b>ipt>`
+const MAX_PASSES: usize = 8;
+
+/// `text` with markup that could run in a browser taken out, for every free-text field before it is stored.
+///
+/// Control characters go, except line breaks and tabs. HTML tags go with their attributes, and `<script>` and
+/// `<style>` with their content too, along with comments and `javascript:`-like URL schemes. What only looks like a
+/// tag stays as typed: `Vec<String>`, `a < b` or `Box<dyn Error>` aren't HTML elements and carry no attributes, and
+/// the app shows text as text, so keeping them is safe where escaping them would put `&lt;` on screen. Markdown code
+/// spans and fences (`` `<b>` ``) are kept whole for the same reason. Running it twice changes nothing more.
+pub fn sanitize_text(text: &str) -> String {
+    let mut current: String = text.chars().filter(|c| !c.is_control() || *c == '\n' || *c == '\t').collect();
+    for _ in 0..MAX_PASSES {
+        let next = sanitize_pass(&current);
+        if next == current {
+            break;
+        }
+        current = next;
+    }
+    current
+}
+
+/// One sweep over `text`, outside code spans
+fn sanitize_pass(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        if c == '`' {
+            let (code, after) = code_span(rest);
+            out.push_str(code);
+            rest = after;
+        } else if c == '<' {
+            match markup_len(rest) {
+                Some(len) => rest = &rest[len..],
+                None => {
+                    out.push('<');
+                    rest = &rest[1..];
+                }
+            }
+        } else if let Some(scheme) = SCRIPT_SCHEMES.iter().find(|scheme| starts_with_ignore_case(rest, scheme)) {
+            rest = &rest[scheme.len()..];
+        } else {
+            out.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    out
+}
+
+/// Split `text`, which starts with a backtick, after its code span: a run of backticks up to the next run of the
+/// same length. Without one, only the opening run is taken and the rest is sanitized as usual.
+fn code_span(text: &str) -> (&str, &str) {
+    let ticks = text.len() - text.trim_start_matches('`').len();
+    let fence = &text[..ticks];
+    let mut search = ticks;
+    while let Some(found) = text[search..].find(fence) {
+        let start = search + found;
+        let run = text[start..].len() - text[start..].trim_start_matches('`').len();
+        if run == ticks {
+            return text.split_at(start + ticks);
+        }
+        search = start + run;
+    }
+    text.split_at(ticks)
+}
+
+/// Length of the markup `text` starts with, or `None` when its `<` is just a character
+fn markup_len(text: &str) -> Option<usize> {
+    if text.starts_with("<!--") {
+        return Some(text.find("-->").map_or(text.len(), |end| end + 3));
+    }
+    if text.starts_with("<!") || text.starts_with("<?") {
+        return text.find('>').map(|end| end + 1);
+    }
+
+    let closing = text[1..].starts_with('/');
+    let name_start = if closing { 2 } else { 1 };
+    let name_len = text[name_start..]
+        .find(|c: char| !c.is_ascii_alphanumeric() && c != '-')
+        .unwrap_or(text.len() - name_start);
+    let name = text[name_start..name_start + name_len].to_ascii_lowercase();
+    if name.is_empty() || !name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        return None;
+    }
+    // After the name comes the end of the tag or its attributes, `<svg/onload=…>` included
+    let after_name = &text[name_start + name_len..];
+    if !after_name.starts_with(|c: char| c == '>' || c == '/' || c.is_whitespace()) {
+        return None;
+    }
+    let tag_len = name_start + name_len + tag_end(after_name)?;
+
+    let is_element = HTML_ELEMENTS.contains(&name.as_str());
+    // Anything with an attribute value is treated as a tag, known element or not: browsers run the handlers of
+    // made-up elements too
+    if !is_element && !text[..tag_len].contains('=') {
+        return None;
+    }
+    if !closing && DROPPED_WITH_CONTENT.contains(&name.as_str()) {
+        let close = format!("</{}", name);
+        let end = find_ignore_case(&text[tag_len..], &close)
+            .map(|start| {
+                let from = tag_len + start;
+                from + text[from..].find('>').map_or(text.len() - from, |end| end + 1)
+            })
+            .unwrap_or(text.len());
+        return Some(end);
+    }
+    Some(tag_len)
+}
+
+/// Length up to and including the `>` closing a tag whose attributes `text` starts at. Quoted values may hold `>`.
+fn tag_end(text: &str) -> Option<usize> {
+    let mut quote = None;
+    for (index, c) in text.char_indices() {
+        match (quote, c) {
+            (Some(open), c) if c == open => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '>') => return Some(index + 1),
+            (None, _) => {}
+        }
+    }
+    None
+}
+
+fn starts_with_ignore_case(text: &str, prefix: &str) -> bool {
+    text.get(..prefix.len()).is_some_and(|start| start.eq_ignore_ascii_case(prefix))
+}
+
+/// Byte offset of the first `needle`, ignoring ASCII case
+fn find_ignore_case(text: &str, needle: &str) -> Option<usize> {
+    text.char_indices()
+        .map(|(index, _)| index)
+        .find(|&index| starts_with_ignore_case(&text[index..], needle))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plain_text_is_untouched() {
+        for text in ["", "A plain idea", "Line one\n\tindented", "café ☕ 日本語", "5 > 3 and 2 < 4", "AT&T"] {
+            assert_eq!(sanitize_text(text), text);
+        }
+    }
+
+    #[test]
+    fn test_code_looking_text_is_kept() {
+        for text in [
+            "Return a Vec<String> of names",
+            "HashMap<K, V> and Result<String, Error>",
+            "Box<dyn Trait> or Option<u8>",
+            "Vec<Box<dyn Fn(u32) -> u32>>",
+            "x<y>z",
+            "a <- b",
+        ] {
+            assert_eq!(sanitize_text(text), text);
+        }
+        // Code spans keep even real tags, since markdown shows them as code
+        assert_eq!(sanitize_text("Use `<br>` for breaks"), "Use `<br>` for breaks");
+        assert_eq!(sanitize_text("```\n<div class=\"x\"></div>\n```"), "```\n<div class=\"x\"></div>\n```");
+        // An unclosed span doesn't protect what follows
+        assert_eq!(sanitize_text("a ` <b>bold</b>"), "a ` bold");
+    }
+
+    #[test]
+    fn test_script_tags_go_with_their_content() {
+        assert_eq!(sanitize_text("Hi<script>alert('xss')</script> there"), "Hi there");
+        assert_eq!(sanitize_text("<SCRIPT SRC=//evil.example/x.js></SCRIPT>ok"), "ok");
+        assert_eq!(sanitize_text("<style>body { display: none }</style>Shown"), "Shown");
+        // Unclosed: everything after it goes
+        assert_eq!(sanitize_text("Before<script>alert(1)"), "Before");
+        // A tag split by another one doesn't come back together
+        assert_eq!(sanitize_text("<scr<script>x</script>ipt>alert(1)</script>"), "");
+        assert_eq!(sanitize_text("<<script>script>alert(1)<</script>/script>"), "");
+    }
+
+    #[test]
+    fn test_event_handler_attributes() {
+        assert_eq!(sanitize_text("<img src=x onerror=alert(1)>"), "");
+        assert_eq!(sanitize_text("<svg/onload=alert(1)>drawing"), "drawing");
+        assert_eq!(sanitize_text("<body onload=\"alert('x')\">"), "");
+        assert_eq!(sanitize_text("<div title=\"a > b\" onclick=\"steal()\">text</div>"), "text");
+        // Made-up elements run handlers too
+        assert_eq!(sanitize_text("<String onmouseover=alert(1)>hover"), "hover");
+        assert_eq!(sanitize_text("\"><img src=x onerror=alert(1)>"), "\">");
+    }
+
+    #[test]
+    fn test_javascript_urls() {
+        assert_eq!(sanitize_text("<a href=\"javascript:alert(1)\">click</a>"), "click");
+        assert_eq!(sanitize_text("[click](javascript:alert(document.cookie))"), "[click](alert(document.cookie))");
+        assert_eq!(sanitize_text("JaVaScRiPt:alert(1)"), "alert(1)");
+        assert_eq!(sanitize_text("java\u{0}script:alert(1)"), "alert(1)");
+        assert_eq!(sanitize_text("javajavascript:script:alert(1)"), "alert(1)");
+        assert_eq!(sanitize_text("data:text/html;base64,PHNjcmlwdD4="), ";base64,PHNjcmlwdD4=");
+        assert_eq!(sanitize_text("see https://example.com/page"), "see https://example.com/page");
+    }
+
+    #[test]
+    fn test_formatting_tags_and_comments() {
+        assert_eq!(sanitize_text("<b>Bold</b> and <I>italic</I>"), "Bold and italic");
+        assert_eq!(sanitize_text("one<br/>two<br>three"), "onetwothree");
+        assert_eq!(sanitize_text("a<!-- hidden -->b<!doctype html>c"), "abc");
+        assert_eq!(sanitize_text("<iframe src=\"https://evil.example\"></iframe>"), "");
+    }
+
+    #[test]
+    fn test_control_characters() {
+        assert_eq!(sanitize_text("bell\u{7} and\u{1b}[31m escape"), "bell and[31m escape");
+        assert_eq!(sanitize_text("windows\r\nline"), "windows\nline");
+        assert_eq!(sanitize_text("keeps\nlines\tand tabs"), "keeps\nlines\tand tabs");
+    }
+
+    #[test]
+    fn test_running_twice_changes_nothing() {
+        for text in [
+            "<scr<script>x</script>ipt>alert(1)</script>",
+            "Vec<String> <b>x</b> `<i>` javascript:x",
+            "<<script>script>alert(1)<</script>/script>",
+        ] {
+            let once = sanitize_text(text);
+            assert_eq!(sanitize_text(&once), once);
+        }
+    }
+}
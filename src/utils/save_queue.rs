@@ -0,0 +1,142 @@
+/// Auto-save state of an editor: at most one save in flight, and only the latest unsaved edit kept.
+///
+/// Each transition returns the payload to send next, if any, and the caller sends it. After a failed save nothing
+/// is sent until [`SaveQueue::retry`], so a dropped connection doesn't turn every keystroke into another failure;
+/// the unsaved edit waits in the queue instead.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SaveQueue<T> {
+    /// The latest edit not sent yet
+    queued: Option<T>,
+    /// What the save in flight is sending
+    in_flight: Option<T>,
+    /// Why the last save failed, cleared by a retry
+    error: Option<String>,
+}
+
+impl<T> Default for SaveQueue<T> {
+    fn default() -> Self {
+        Self {
+            queued: None,
+            in_flight: None,
+            error: None,
+        }
+    }
+}
+
+impl<T: Clone> SaveQueue<T> {
+    /// Record an edit. Returns it when it can be sent right away; otherwise it waits for the save in flight or for
+    /// a retry, replacing any older edit still waiting.
+    pub fn edit(&mut self, payload: T) -> Option<T> {
+        self.queued = Some(payload);
+        self.next()
+    }
+
+    /// The save in flight went through. Returns the edit made meanwhile, to send next.
+    pub fn saved(&mut self) -> Option<T> {
+        self.in_flight = None;
+        self.error = None;
+        self.next()
+    }
+
+    /// The save in flight failed. Its payload goes back in the queue unless a newer edit is already waiting.
+    pub fn failed(&mut self, error: String) {
+        if let Some(payload) = self.in_flight.take() {
+            self.queued.get_or_insert(payload);
+        }
+        self.error = Some(error);
+    }
+
+    /// Try again after a failure, from a retry button or once the server answers again. Returns the edit to send.
+    pub fn retry(&mut self) -> Option<T> {
+        self.error = None;
+        self.next()
+    }
+
+    /// Move the waiting edit in flight when nothing blocks it
+    fn next(&mut self) -> Option<T> {
+        if self.in_flight.is_some() || self.error.is_some() {
+            return None;
+        }
+        self.in_flight = self.queued.take();
+        self.in_flight.clone()
+    }
+}
+
+impl<T> SaveQueue<T> {
+    /// Whether a save is in flight
+    pub fn is_saving(&self) -> bool {
+        self.in_flight.is_some()
+    }
+
+    /// Whether some edit isn't known to be saved yet, in flight or waiting
+    pub fn has_unsaved(&self) -> bool {
+        self.in_flight.is_some() || self.queued.is_some()
+    }
+
+    /// Why the last save failed, until a retry
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edits_during_a_save_are_sent_after_it() {
+        let mut queue = SaveQueue::default();
+        assert_eq!(queue.edit("a"), Some("a"));
+        assert!(queue.is_saving());
+
+        // Only the latest of the edits made meanwhile is kept
+        assert_eq!(queue.edit("ab"), None);
+        assert_eq!(queue.edit("abc"), None);
+        assert_eq!(queue.saved(), Some("abc"));
+        assert!(queue.has_unsaved());
+
+        assert_eq!(queue.saved(), None);
+        assert!(!queue.has_unsaved());
+        assert!(!queue.is_saving());
+    }
+
+    #[test]
+    fn test_a_failed_save_waits_for_a_retry() {
+        let mut queue = SaveQueue::default();
+        queue.edit("a");
+        queue.failed("offline".to_string());
+        assert_eq!(queue.error(), Some("offline"));
+        assert!(queue.has_unsaved());
+        assert!(!queue.is_saving());
+
+        // Edits while failing are queued, not sent
+        assert_eq!(queue.edit("ab"), None);
+        assert_eq!(queue.retry(), Some("ab"));
+        assert_eq!(queue.error(), None);
+        assert_eq!(queue.saved(), None);
+        assert!(!queue.has_unsaved());
+    }
+
+    #[test]
+    fn test_a_failed_payload_is_requeued_unless_newer_edits_wait() {
+        let mut queue = SaveQueue::default();
+        queue.edit("a");
+        queue.failed("offline".to_string());
+        assert_eq!(queue.retry(), Some("a"));
+
+        queue.edit("ab");
+        queue.failed("offline again".to_string());
+        assert_eq!(queue.retry(), Some("ab"));
+    }
+
+    #[test]
+    fn test_retry_with_nothing_to_send() {
+        let mut queue = SaveQueue::<&str>::default();
+        assert_eq!(queue.retry(), None);
+
+        queue.edit("a");
+        // A retry while the save is in flight doesn't send it twice
+        assert_eq!(queue.retry(), None);
+        assert!(queue.is_saving());
+    }
+}
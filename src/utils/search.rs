@@ -0,0 +1,84 @@
+use super::fuzzy::fuzzy_match;
+use crate::db::IdeaSummary;
+use std::collections::HashSet;
+
+/// How well `idea` matches `query` in the list search: the best [`fuzzy_match`] of its title and each of its tags
+pub fn search_score(query: &str, idea: &IdeaSummary) -> Option<u32> {
+    std::iter::once(&idea.title)
+        .chain(&idea.tags)
+        .filter_map(|text| fuzzy_match(query, text))
+        .max()
+}
+
+/// What the list shows while `query` is being searched for.
+///
+/// `local` are the ideas already on the client, filtered on the spot by [`search_score`] and ranked best first, ideas
+/// scoring the same keeping their order. `server` are the results of the server search once they are in; the ones
+/// not already listed follow in the order the server sent them, since the server also looks at text the client
+/// doesn't have. Ideas are listed once, by id.
+pub fn merge_search_results(query: &str, local: &[IdeaSummary], server: &[IdeaSummary]) -> Vec<IdeaSummary> {
+    let mut ranked: Vec<(u32, &IdeaSummary)> = local
+        .iter()
+        .filter_map(|idea| search_score(query, idea).map(|score| (score, idea)))
+        .collect();
+    ranked.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+
+    let mut seen = HashSet::new();
+    ranked
+        .into_iter()
+        .map(|(_, idea)| idea)
+        .chain(server)
+        // Ideas without an id can't be told apart, so they are all kept
+        .filter(|idea| idea.id.as_ref().is_none_or(|id| seen.insert(id.clone())))
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn idea(id: &str, title: &str, tags: &[&str]) -> IdeaSummary {
+        IdeaSummary {
+            id: Some(id.to_string()),
+            title: title.to_string(),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    fn ids(ideas: &[IdeaSummary]) -> Vec<&str> {
+        ideas.iter().filter_map(|i| i.id.as_deref()).collect()
+    }
+
+    #[test]
+    fn test_title_and_tags_are_searched() {
+        assert!(search_score("bke", &idea("1", "Bike repair", &[])).is_some());
+        assert!(search_score("cycl", &idea("1", "Repair", &["cycling"])).is_some());
+        assert!(search_score("zz", &idea("1", "Bike repair", &["cycling"])).is_none());
+    }
+
+    #[test]
+    fn test_local_matches_are_ranked_best_first() {
+        let local = vec![
+            idea("scattered", "Bike rental plan", &[]),
+            idea("miss", "Garden", &[]),
+            idea("run", "Bike repair", &[]),
+        ];
+        assert_eq!(ids(&merge_search_results("rep", &local, &[])), ["run", "scattered"]);
+    }
+
+    #[test]
+    fn test_server_results_follow_without_duplicates() {
+        let local = vec![idea("a", "Bike repair", &[]), idea("b", "Garden", &[])];
+        // The server found "c" in its description, and "a" again
+        let server = vec![idea("c", "Weekend plans", &[]), idea("a", "Bike repair", &[]), idea("d", "Tools", &[])];
+        assert_eq!(ids(&merge_search_results("bike", &local, &server)), ["a", "c", "d"]);
+    }
+
+    #[test]
+    fn test_blank_query_keeps_local_order() {
+        let local = vec![idea("b", "Garden", &[]), idea("a", "Bike repair", &[])];
+        assert_eq!(ids(&merge_search_results(" ", &local, &[])), ["b", "a"]);
+    }
+}
@@ -0,0 +1,89 @@
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+/// Titles at least this similar (see [`title_similarity`]) are reported as possible duplicates
+pub const DUPLICATE_THRESHOLD: f64 = 0.8;
+
+/// A title reduced to what matters for comparing: compatibility-decomposed, without accents, lowercase, with runs of
+/// punctuation and whitespace collapsed to a single space. "Café  Finder!" and "cafe finder" normalize alike.
+pub fn normalize_title(title: &str) -> String {
+    let folded: String = title
+        .nfkd()
+        .filter(|c| !is_combining_mark(*c))
+        .flat_map(char::to_lowercase)
+        .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+        .collect();
+    folded.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Edit distance between two strings in characters
+fn levenshtein(a: &[char], b: &[char]) -> usize {
+    // One row of the classic table at a time
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, ca) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+/// How alike two titles are, from 0.0 (nothing in common) to 1.0 (equal after [`normalize_title`]).
+///
+/// This is the normalized Levenshtein similarity: one minus the edit distance divided by the longer length.
+pub fn title_similarity(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = normalize_title(a).chars().collect();
+    let b: Vec<char> = normalize_title(b).chars().collect();
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 1.0;
+    }
+    1.0 - levenshtein(&a, &b) as f64 / longest as f64
+}
+
+/// Whether two titles are similar enough to be the same idea
+pub fn is_possible_duplicate(a: &str, b: &str) -> bool {
+    title_similarity(a, b) >= DUPLICATE_THRESHOLD
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_title() {
+        assert_eq!(normalize_title("  Café  Finder! "), "cafe finder");
+        // Compatibility forms: full-width letters and the "ﬁ" ligature
+        assert_eq!(normalize_title("Ｒｕｓｔ ﬁles"), "rust files");
+        assert_eq!(normalize_title("Crème-brûlée / tracker"), "creme brulee tracker");
+        assert_eq!(normalize_title("?!"), "");
+    }
+
+    #[test]
+    fn test_near_miss_titles_are_duplicates() {
+        assert!(is_possible_duplicate("Bike repair cafe", "Bike repair café"));
+        assert!(is_possible_duplicate("Recipe planner", "Recipe planer"));
+        assert!(is_possible_duplicate("Garden tracker", "garden-tracker!"));
+        assert!(is_possible_duplicate("Language exchange club", "Languag exchange clubs"));
+    }
+
+    #[test]
+    fn test_different_titles_are_not() {
+        assert!(!is_possible_duplicate("Bike repair cafe", "Book club"));
+        assert!(!is_possible_duplicate("Budget", "Garden"));
+        // Short titles need to be nearly equal
+        assert!(!is_possible_duplicate("Cat", "Car"));
+    }
+
+    #[test]
+    fn test_similarity_bounds() {
+        assert_eq!(title_similarity("Same", "same"), 1.0);
+        assert_eq!(title_similarity("", ""), 1.0);
+        assert_eq!(title_similarity("abc", ""), 0.0);
+        assert_eq!(title_similarity("abcd", "abce"), 0.75);
+    }
+}
@@ -0,0 +1,90 @@
+/// Maximum number of characters kept in a generated slug
+const MAX_SLUG_CHARS: usize = 60;
+
+/// Fallback slug for titles without any alphanumeric characters
+const FALLBACK_SLUG: &str = "idea";
+
+/// Turn a title into a lowercase, hyphen-separated URL slug.
+///
+/// Unicode letters and digits are kept (lowercased), apostrophes are dropped so "Don't" becomes "dont", and every
+/// other run of characters collapses into a single hyphen.
+pub fn slugify(title: &str) -> String {
+    let mut slug = String::new();
+    let mut pending_hyphen = false;
+
+    for c in title.chars() {
+        if c.is_alphanumeric() {
+            if pending_hyphen && !slug.is_empty() {
+                slug.push('-');
+            }
+            pending_hyphen = false;
+            slug.extend(c.to_lowercase());
+        } else if c != '\'' && c != '’' {
+            pending_hyphen = true;
+        }
+    }
+
+    let slug: String = slug.chars().take(MAX_SLUG_CHARS).collect();
+    let slug = slug.trim_end_matches('-');
+
+    if slug.is_empty() {
+        FALLBACK_SLUG.to_string()
+    } else {
+        slug.to_string()
+    }
+}
+
+/// Make `base` unique among `taken` by appending `-2`, `-3`, ... on collision
+pub fn unique_slug(base: &str, taken: &[String]) -> String {
+    if !taken.iter().any(|t| t == base) {
+        return base.to_string();
+    }
+
+    (2..)
+        .map(|n| format!("{}-{}", base, n))
+        .find(|candidate| !taken.contains(candidate))
+        .expect("an unused suffix always exists")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slugify_basic() {
+        assert_eq!(slugify("My Great Idea"), "my-great-idea");
+        assert_eq!(slugify("  Spaces   everywhere  "), "spaces-everywhere");
+        assert_eq!(slugify("Rust + WASM = ❤"), "rust-wasm");
+        assert_eq!(slugify("Don't panic"), "dont-panic");
+    }
+
+    #[test]
+    fn test_slugify_unicode_titles() {
+        assert_eq!(slugify("Café Ideen"), "café-ideen");
+        assert_eq!(slugify("ÜBER Größe"), "über-größe");
+        assert_eq!(slugify("日本語 タイトル"), "日本語-タイトル");
+    }
+
+    #[test]
+    fn test_slugify_fallback_for_symbol_only_titles() {
+        assert_eq!(slugify("!!!"), "idea");
+        assert_eq!(slugify(""), "idea");
+        assert_eq!(slugify("🚀🚀"), "idea");
+    }
+
+    #[test]
+    fn test_slugify_caps_length() {
+        let slug = slugify(&"word ".repeat(40));
+        assert!(slug.chars().count() <= 60);
+        assert!(!slug.ends_with('-'));
+    }
+
+    #[test]
+    fn test_unique_slug_collisions() {
+        let taken = vec!["idea".to_string(), "idea-2".to_string(), "other".to_string()];
+
+        assert_eq!(unique_slug("fresh", &taken), "fresh");
+        assert_eq!(unique_slug("idea", &taken), "idea-3");
+        assert_eq!(unique_slug("other", &taken), "other-2");
+    }
+}
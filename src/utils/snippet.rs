@@ -0,0 +1,314 @@
+use crate::db::{Idea, IdeaSummary};
+use serde::{Deserialize, Serialize};
+
+/// Characters of text a search snippet shows around the first match
+pub const SNIPPET_CHARS: usize = 120;
+
+/// Marks text cut off at either end of a snippet
+const ELLIPSIS: &str = "…";
+
+/// The part of an idea a search matched in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MatchedField {
+    Title,
+    Description,
+    /// The development notes, or the bodies of the structured note sections
+    Notes,
+    Tags,
+}
+
+impl MatchedField {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MatchedField::Title => "title",
+            MatchedField::Description => "description",
+            MatchedField::Notes => "notes",
+            MatchedField::Tags => "tags",
+        }
+    }
+}
+
+/// An idea the server search found, with the text around its first match. `ranges` are the byte ranges of the
+/// matches within `snippet`, for highlighting them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SearchHit {
+    pub idea: IdeaSummary,
+    pub field: MatchedField,
+    pub snippet: String,
+    pub ranges: Vec<(usize, usize)>,
+}
+
+/// Where `query` matches `idea` first, looking at the title, the description, the notes and the tags in that order.
+/// `None` when it matches none of them, or is blank.
+pub fn search_hit(idea: &Idea, query: &str) -> Option<SearchHit> {
+    let notes = if idea.sections.is_empty() {
+        idea.development_notes.clone()
+    } else {
+        idea.sections.iter().map(|section| section.body.as_str()).collect::<Vec<_>>().join("\n\n")
+    };
+    let fields = [
+        (MatchedField::Title, idea.title.clone()),
+        (MatchedField::Description, idea.description.clone()),
+        (MatchedField::Notes, notes),
+        (MatchedField::Tags, idea.tags.join(", ")),
+    ];
+    fields.into_iter().find_map(|(field, text)| {
+        let matches = find_matches(&text, query);
+        if matches.is_empty() {
+            return None;
+        }
+        let (snippet, ranges) = snippet_around(&text, &matches);
+        Some(SearchHit {
+            idea: idea.clone().into(),
+            field,
+            snippet,
+            ranges,
+        })
+    })
+}
+
+/// Byte ranges of the occurrences of `query` in `text`, ignoring case and surrounding whitespace, without overlaps.
+///
+/// Both are lowercased a character at a time rather than as whole strings, as lowercasing can change the length of
+/// a character ("İ" becomes two). The ranges so always start and end on character boundaries of `text` itself.
+pub fn find_matches(text: &str, query: &str) -> Vec<(usize, usize)> {
+    let needle: Vec<char> = query.trim().chars().flat_map(char::to_lowercase).collect();
+    if needle.is_empty() {
+        return Vec::new();
+    }
+    let mut matches = Vec::new();
+    let mut searched_to = 0;
+    for (start, _) in text.char_indices() {
+        if start < searched_to {
+            continue;
+        }
+        if let Some(length) = match_length(&text[start..], &needle) {
+            matches.push((start, start + length));
+            searched_to = start + length;
+        }
+    }
+    matches
+}
+
+/// Length in bytes of the start of `text` that lowercases to `needle`, if there is one
+fn match_length(text: &str, needle: &[char]) -> Option<usize> {
+    let mut rest = needle;
+    for (index, c) in text.char_indices() {
+        for lower in c.to_lowercase() {
+            // A character whose lowercase only partly matches the end of the needle doesn't count
+            let (&expected, tail) = rest.split_first()?;
+            if expected != lower {
+                return None;
+            }
+            rest = tail;
+        }
+        if rest.is_empty() {
+            return Some(index + c.len_utf8());
+        }
+    }
+    None
+}
+
+/// About [`SNIPPET_CHARS`] characters of `text` around the first of `matches`, which sits near the middle unless
+/// the text starts or ends close to it, and the matches within the snippet, cut to it. A match longer than the
+/// snippet makes it as long. Text left out at either end is marked with "…".
+///
+/// `matches` are byte ranges in `text` on character boundaries, in order, as [`find_matches`] gives them.
+pub fn snippet_around(text: &str, matches: &[(usize, usize)]) -> (String, Vec<(usize, usize)>) {
+    let Some(&(first_start, first_end)) = matches.first() else {
+        return (String::new(), Vec::new());
+    };
+    // Byte offset of every character, and of the end
+    let bounds: Vec<usize> = text.char_indices().map(|(index, _)| index).chain([text.len()]).collect();
+    let char_at = |byte: usize| bounds.partition_point(|&bound| bound < byte);
+    let (match_start, match_end) = (char_at(first_start), char_at(first_end));
+    let total = bounds.len() - 1;
+
+    let width = SNIPPET_CHARS.max(match_end - match_start);
+    let before = (width - (match_end - match_start)) / 2;
+    // Shifted back when the text ends early, so the snippet is as long as the text allows
+    let end = (match_start.saturating_sub(before) + width).min(total);
+    let start = end.saturating_sub(width);
+    let (start_byte, end_byte) = (bounds[start], bounds[end]);
+
+    let prefix = if start > 0 { ELLIPSIS } else { "" };
+    let suffix = if end < total { ELLIPSIS } else { "" };
+    let snippet = format!("{}{}{}", prefix, &text[start_byte..end_byte], suffix);
+    let ranges = matches
+        .iter()
+        .map(|&(from, to)| (from.max(start_byte), to.min(end_byte)))
+        .filter(|(from, to)| from < to)
+        .map(|(from, to)| (from - start_byte + prefix.len(), to - start_byte + prefix.len()))
+        .collect();
+    (snippet, ranges)
+}
+
+/// `snippet` as runs of text, each marked when one of `ranges` covers it. Ranges that overlap an earlier one or don't
+/// fall on character boundaries, which a well-behaved server never sends, are ignored.
+pub fn highlight<'a>(snippet: &'a str, ranges: &[(usize, usize)]) -> Vec<(&'a str, bool)> {
+    let mut runs = Vec::new();
+    let mut shown_to = 0;
+    for &(from, to) in ranges {
+        if from < shown_to || from >= to || snippet.get(from..to).is_none() {
+            continue;
+        }
+        if shown_to < from {
+            runs.push((&snippet[shown_to..from], false));
+        }
+        runs.push((&snippet[from..to], true));
+        shown_to = to;
+    }
+    if shown_to < snippet.len() {
+        runs.push((&snippet[shown_to..], false));
+    }
+    runs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::NoteSection;
+
+    fn marked<'a>(snippet: &'a str, ranges: &[(usize, usize)]) -> Vec<&'a str> {
+        ranges.iter().map(|&(from, to)| &snippet[from..to]).collect()
+    }
+
+    #[test]
+    fn test_matches_ignore_case() {
+        let text = "Rust, rust and RUST";
+        assert_eq!(find_matches(text, " rust "), vec![(0, 4), (6, 10), (15, 19)]);
+        assert_eq!(find_matches(text, "go"), vec![]);
+        assert_eq!(find_matches(text, "  "), vec![]);
+        // Overlapping occurrences count once
+        assert_eq!(find_matches("aaaa", "aa"), vec![(0, 2), (2, 4)]);
+    }
+
+    #[test]
+    fn test_matches_fall_on_char_boundaries() {
+        let text = "Café au lait, CAFÉ noir";
+        let matches = find_matches(text, "café");
+        assert_eq!(marked(text, &matches), ["Café", "CAFÉ"]);
+
+        // "İ" lowercases to "i̇", two characters, so offsets in the lowercased text would be off by one
+        let text = "İstanbul trip, then istanbul again";
+        assert_eq!(marked(text, &find_matches(text, "stanbul")), ["stanbul", "stanbul"]);
+        assert_eq!(marked(text, &find_matches(text, "i̇stanbul")), ["İstanbul"]);
+        // Half of a lowercased character isn't a match
+        assert_eq!(find_matches("İ", "i"), vec![]);
+    }
+
+    #[test]
+    fn test_short_text_is_the_whole_snippet() {
+        let text = "Bike repair café";
+        let (snippet, ranges) = snippet_around(text, &find_matches(text, "repair"));
+        assert_eq!(snippet, text);
+        assert_eq!(marked(&snippet, &ranges), ["repair"]);
+    }
+
+    #[test]
+    fn test_snippet_centers_the_first_match() {
+        let text = format!("{}needle{}", "a".repeat(200), "b".repeat(200));
+        let (snippet, ranges) = snippet_around(&text, &find_matches(&text, "needle"));
+        assert!(snippet.starts_with('…') && snippet.ends_with('…'), "{}", snippet);
+        assert_eq!(snippet.chars().count(), SNIPPET_CHARS + 2);
+        assert_eq!(marked(&snippet, &ranges), ["needle"]);
+        let (before, after) = snippet.split_once("needle").unwrap();
+        assert_eq!(before.chars().count(), after.chars().count());
+    }
+
+    #[test]
+    fn test_snippet_near_either_end_uses_the_whole_width() {
+        let text = format!("needle{}", "x".repeat(300));
+        let (snippet, _) = snippet_around(&text, &find_matches(&text, "needle"));
+        assert!(snippet.starts_with("needle"));
+        assert_eq!(snippet.chars().count(), SNIPPET_CHARS + 1);
+
+        let text = format!("{}needle", "x".repeat(300));
+        let (snippet, ranges) = snippet_around(&text, &find_matches(&text, "needle"));
+        assert!(snippet.starts_with('…') && snippet.ends_with("needle"));
+        assert_eq!(snippet.chars().count(), SNIPPET_CHARS + 1);
+        assert_eq!(ranges, vec![(snippet.len() - 6, snippet.len())]);
+    }
+
+    #[test]
+    fn test_snippet_of_multibyte_text() {
+        // Every character takes two to four bytes, so a window counted in bytes would cut one in half
+        let text = format!("{}ñandú{}", "é".repeat(100), "🚲".repeat(100));
+        let (snippet, ranges) = snippet_around(&text, &find_matches(&text, "ÑANDÚ"));
+        assert_eq!(snippet.chars().count(), SNIPPET_CHARS + 2);
+        assert_eq!(marked(&snippet, &ranges), ["ñandú"]);
+        assert!(snippet.contains("é") && snippet.contains("🚲"));
+    }
+
+    #[test]
+    fn test_later_matches_are_kept_or_cut_at_the_edge() {
+        let text = format!("rust {} rust {}rust", "x".repeat(40), "y".repeat(68));
+        let matches = find_matches(&text, "rust");
+        assert_eq!(matches.len(), 3);
+        let (snippet, ranges) = snippet_around(&text, &matches);
+        // The third match starts at character 119 of the 123, so only its first letter is in the snippet
+        assert_eq!(marked(&snippet, &ranges), ["rust", "rust", "r"]);
+        assert!(snippet.ends_with("r…"));
+    }
+
+    #[test]
+    fn test_match_longer_than_the_snippet() {
+        let text = format!("start {} end", "z".repeat(200));
+        let query = "z".repeat(200);
+        let (snippet, ranges) = snippet_around(&text, &find_matches(&text, &query));
+        assert_eq!(marked(&snippet, &ranges), [query.as_str()]);
+        assert_eq!(snippet.chars().count(), 202);
+    }
+
+    #[test]
+    fn test_highlight_splits_into_runs() {
+        assert_eq!(
+            highlight("a bike, a Bike", &[(2, 6), (10, 14)]),
+            vec![("a ", false), ("bike", true), (", a ", false), ("Bike", true)]
+        );
+        assert_eq!(highlight("plain", &[]), vec![("plain", false)]);
+        // "é" takes bytes 0 and 1, so a range from 1 is ignored, as is one past the end and one overlapping the last
+        assert_eq!(
+            highlight("éa", &[(1, 3), (2, 3), (2, 3), (3, 9)]),
+            vec![("é", false), ("a", true)]
+        );
+    }
+
+    #[test]
+    fn test_search_hit_picks_the_first_field_that_matches() {
+        let idea = Idea {
+            id: Some("ideas:a".to_string()),
+            title: "Garden".to_string(),
+            description: "Raised beds for the herbs".to_string(),
+            development_notes: "Ask about herbs at the market".to_string(),
+            tags: vec!["outdoor".to_string(), "herbal".to_string()],
+            ..Default::default()
+        };
+        let hit = search_hit(&idea, "HERB").unwrap();
+        assert_eq!(hit.field, MatchedField::Description);
+        assert_eq!(hit.snippet, "Raised beds for the herbs");
+        assert_eq!(marked(&hit.snippet, &hit.ranges), ["herb"]);
+        assert_eq!(hit.idea.id.as_deref(), Some("ideas:a"));
+
+        assert_eq!(search_hit(&idea, "market").unwrap().field, MatchedField::Notes);
+        assert_eq!(search_hit(&idea, "outdoor").unwrap().field, MatchedField::Tags);
+        assert_eq!(search_hit(&idea, "garden").unwrap().field, MatchedField::Title);
+        assert_eq!(search_hit(&idea, "pond"), None);
+        assert_eq!(search_hit(&idea, " "), None);
+    }
+
+    #[test]
+    fn test_structured_notes_are_searched_by_section_body() {
+        let idea = Idea {
+            title: "Garden".to_string(),
+            sections: vec![NoteSection {
+                heading: "Soil".to_string(),
+                body: "Compost from the neighbours".to_string(),
+            }],
+            ..Default::default()
+        };
+        let hit = search_hit(&idea, "compost").unwrap();
+        assert_eq!((hit.field, hit.snippet.as_str()), (MatchedField::Notes, "Compost from the neighbours"));
+        assert_eq!(search_hit(&idea, "soil"), None);
+    }
+}
@@ -0,0 +1,103 @@
+/// Last-known data for a refetching list, so a refresh never blanks out content we already have.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StaleData<T> {
+    /// The most recent successful result, if there ever was one
+    pub data: Option<T>,
+    /// The error from the most recent fetch, cleared by the next success
+    pub error: Option<String>,
+    /// Whether a fetch is currently in flight
+    pub is_refreshing: bool,
+}
+
+impl<T> Default for StaleData<T> {
+    fn default() -> Self {
+        Self {
+            data: None,
+            error: None,
+            is_refreshing: false,
+        }
+    }
+}
+
+impl<T> StaleData<T> {
+    /// Mark a fetch as started without touching the current data
+    pub fn start_refresh(&mut self) {
+        self.is_refreshing = true;
+    }
+
+    /// Merge the result of a fetch. Successes replace the data, failures keep the stale data visible.
+    pub fn apply(&mut self, result: Result<T, String>) {
+        match result {
+            Ok(data) => {
+                self.data = Some(data);
+                self.error = None;
+            }
+            Err(error) => {
+                self.error = Some(error);
+            }
+        }
+        self.is_refreshing = false;
+    }
+
+    /// True until the very first fetch finishes, the only time placeholders should be shown
+    pub fn is_first_load(&self) -> bool {
+        self.data.is_none() && self.error.is_none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_load() {
+        let mut state = StaleData::<Vec<u32>>::default();
+        assert!(state.is_first_load());
+
+        state.start_refresh();
+        assert!(state.is_first_load());
+        assert!(state.is_refreshing);
+
+        state.apply(Ok(vec![1, 2]));
+        assert!(!state.is_first_load());
+        assert!(!state.is_refreshing);
+        assert_eq!(state.data, Some(vec![1, 2]));
+    }
+
+    #[test]
+    fn test_refresh_keeps_stale_data_until_new_data_arrives() {
+        let mut state = StaleData::default();
+        state.apply(Ok(vec![1]));
+
+        state.start_refresh();
+        assert_eq!(state.data, Some(vec![1]));
+
+        state.apply(Ok(vec![1, 2]));
+        assert_eq!(state.data, Some(vec![1, 2]));
+    }
+
+    #[test]
+    fn test_error_keeps_stale_data() {
+        let mut state = StaleData::default();
+        state.apply(Ok(vec![1]));
+
+        state.start_refresh();
+        state.apply(Err("network down".to_string()));
+
+        assert_eq!(state.data, Some(vec![1]));
+        assert_eq!(state.error.as_deref(), Some("network down"));
+        assert!(!state.is_refreshing);
+    }
+
+    #[test]
+    fn test_error_on_first_load_and_recovery() {
+        let mut state = StaleData::<Vec<u32>>::default();
+        state.apply(Err("boom".to_string()));
+        assert!(!state.is_first_load());
+        assert_eq!(state.data, None);
+
+        state.apply(Ok(vec![3]));
+        assert_eq!(state.error, None);
+        assert_eq!(state.data, Some(vec![3]));
+    }
+}
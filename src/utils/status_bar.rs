@@ -0,0 +1,119 @@
+use crate::db::IdeaStatus;
+
+/// Narrowest segment drawn for a status with any ideas, in percent of the bar, so one idea among hundreds still
+/// shows and can be clicked
+pub const MIN_SEGMENT_PERCENT: u32 = 4;
+
+/// How many of `statuses` there are of each status, in [`IdeaStatus::ALL`] order, with a zero for the missing ones
+pub fn status_counts(statuses: impl IntoIterator<Item = IdeaStatus>) -> Vec<(IdeaStatus, usize)> {
+    let mut counts: Vec<(IdeaStatus, usize)> = IdeaStatus::ALL.into_iter().map(|status| (status, 0)).collect();
+    for status in statuses {
+        if let Some((_, count)) = counts.iter_mut().find(|(counted, _)| *counted == status) {
+            *count += 1;
+        }
+    }
+    counts
+}
+
+/// Widths in whole percent of a bar split between `counts`. Every non-zero count gets at least
+/// [`MIN_SEGMENT_PERCENT`] (less only when there are too many to fit), the rest is shared in proportion to the
+/// counts, and the widths always add up to 100. Rounding goes to the largest remainders, earlier segments first on
+/// a tie. All-zero or no counts give zero widths: an empty bar.
+pub fn segment_widths(counts: &[usize]) -> Vec<u32> {
+    let shown = counts.iter().filter(|&&count| count > 0).count() as u32;
+    if shown == 0 {
+        return vec![0; counts.len()];
+    }
+    let min = MIN_SEGMENT_PERCENT.min(100 / shown) as f64;
+
+    // Segments that would come out below the minimum are held at it, which leaves less for the others and may push
+    // more of them below it, so this repeats until none is left
+    let mut held = vec![false; counts.len()];
+    let exact = loop {
+        let held_count = held.iter().filter(|&&held| held).count() as f64;
+        let budget = 100.0 - min * held_count;
+        let free: usize = counts.iter().zip(&held).filter(|(_, &held)| !held).map(|(&count, _)| count).sum();
+        let exact: Vec<f64> = counts
+            .iter()
+            .zip(&held)
+            .map(|(&count, &held)| match (count, held) {
+                (0, _) => 0.0,
+                (_, true) => min,
+                _ => count as f64 * budget / free as f64,
+            })
+            .collect();
+        let mut changed = false;
+        for (index, width) in exact.iter().enumerate() {
+            if counts[index] > 0 && !held[index] && *width < min {
+                held[index] = true;
+                changed = true;
+            }
+        }
+        if !changed {
+            break exact;
+        }
+    };
+
+    let mut widths: Vec<u32> = exact.iter().map(|width| width.floor() as u32).collect();
+    let mut by_remainder: Vec<usize> = (0..counts.len()).filter(|&index| counts[index] > 0).collect();
+    by_remainder.sort_by(|&a, &b| (exact[b] - exact[b].floor()).total_cmp(&(exact[a] - exact[a].floor())));
+    let missing = 100u32.saturating_sub(widths.iter().sum());
+    for &index in by_remainder.iter().cycle().take(missing as usize) {
+        widths[index] += 1;
+    }
+    widths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counts_follow_the_status_order() {
+        let counts = status_counts([IdeaStatus::Done, IdeaStatus::Inbox, IdeaStatus::Done]);
+        assert_eq!(
+            counts,
+            vec![
+                (IdeaStatus::Inbox, 1),
+                (IdeaStatus::Exploring, 0),
+                (IdeaStatus::Building, 0),
+                (IdeaStatus::Done, 2),
+                (IdeaStatus::Dropped, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_widths_always_sum_to_100() {
+        for counts in [vec![1, 1, 1], vec![1, 2, 3, 4, 5], vec![7, 0, 13, 1, 0], vec![1, 1000, 0, 0, 1], vec![3]] {
+            let widths = segment_widths(&counts);
+            assert_eq!(widths.iter().sum::<u32>(), 100, "{:?} gave {:?}", counts, widths);
+        }
+        assert_eq!(segment_widths(&[1, 1, 1]), vec![34, 33, 33]);
+        assert_eq!(segment_widths(&[1, 3]), vec![25, 75]);
+    }
+
+    #[test]
+    fn test_tiny_segments_stay_visible() {
+        let widths = segment_widths(&[1, 1000, 1]);
+        assert_eq!(widths, vec![MIN_SEGMENT_PERCENT, 100 - 2 * MIN_SEGMENT_PERCENT, MIN_SEGMENT_PERCENT]);
+        // Holding one at the minimum can push another below it
+        let widths = segment_widths(&[2, 3, 200]);
+        assert!(widths[..2].iter().all(|&width| width >= MIN_SEGMENT_PERCENT), "{:?}", widths);
+        assert_eq!(widths.iter().sum::<u32>(), 100);
+    }
+
+    #[test]
+    fn test_zero_counts_get_no_width() {
+        assert_eq!(segment_widths(&[0, 5, 0]), vec![0, 100, 0]);
+        assert_eq!(segment_widths(&[0, 0]), vec![0, 0]);
+        assert!(segment_widths(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_too_many_segments_for_the_minimum() {
+        let widths = segment_widths(&[1; 30]);
+        assert_eq!(widths.iter().sum::<u32>(), 100);
+        assert!(widths.iter().all(|&width| width >= 3));
+    }
+}
@@ -0,0 +1,183 @@
+/// Width in pixels of the actions a swipe reveals behind a card
+pub const SWIPE_ACTIONS_WIDTH: f64 = 144.0;
+
+/// Movement in pixels before a gesture is taken as a swipe or as a scroll
+pub const SWIPE_SLOP: f64 = 10.0;
+
+/// Speed in pixels per millisecond above which a short flick still opens or closes the actions
+pub const SWIPE_FLICK_VELOCITY: f64 = 0.5;
+
+/// What a gesture turned out to be once it moved past [`SWIPE_SLOP`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SwipeAxis {
+    /// Not moved far enough to tell yet
+    Undecided,
+    /// A swipe, moving the card
+    Horizontal,
+    /// A scroll, left to the page; the card stays where it was
+    Vertical,
+}
+
+/// One touch on a card, from pointer down to pointer up, as a pure state machine.
+///
+/// The card sits at offset 0 when closed and at `-SWIPE_ACTIONS_WIDTH` when its actions are revealed. Dragging moves
+/// it between the two; on release it snaps to whichever side is nearer, unless the finger was flicked fast enough,
+/// in which case the direction of the flick wins. Times are in milliseconds from any fixed point.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SwipeGesture {
+    start: (f64, f64),
+    /// The latest position and the one before it, with their times, for the release velocity
+    last: (f64, f64),
+    previous: (f64, f64),
+    axis: SwipeAxis,
+    was_open: bool,
+}
+
+impl SwipeGesture {
+    /// A touch at `(x, y)` on a card whose actions are revealed or not
+    pub fn start(x: f64, y: f64, at: f64, was_open: bool) -> Self {
+        Self {
+            start: (x, y),
+            last: (x, at),
+            previous: (x, at),
+            axis: SwipeAxis::Undecided,
+            was_open,
+        }
+    }
+
+    /// The finger moved to `(x, y)`. The axis is settled by the first move past [`SWIPE_SLOP`] and kept afterwards,
+    /// so a scroll never turns into a swipe halfway.
+    pub fn move_to(&mut self, x: f64, y: f64, at: f64) {
+        if self.axis == SwipeAxis::Undecided {
+            let (dx, dy) = ((x - self.start.0).abs(), (y - self.start.1).abs());
+            if dx.max(dy) >= SWIPE_SLOP {
+                self.axis = if dx > dy { SwipeAxis::Horizontal } else { SwipeAxis::Vertical };
+            }
+        }
+        if at > self.last.1 {
+            self.previous = self.last;
+        }
+        self.last = (x, at);
+    }
+
+    /// What the gesture is so far
+    pub fn axis(&self) -> SwipeAxis {
+        self.axis
+    }
+
+    /// Horizontal offset of the card right now, between `-SWIPE_ACTIONS_WIDTH` and 0
+    pub fn offset(&self) -> f64 {
+        let rest = if self.was_open { -SWIPE_ACTIONS_WIDTH } else { 0.0 };
+        if self.axis != SwipeAxis::Horizontal {
+            return rest;
+        }
+        (rest + self.last.0 - self.start.0).clamp(-SWIPE_ACTIONS_WIDTH, 0.0)
+    }
+
+    /// Horizontal speed over the last move, in pixels per millisecond; negative towards the left
+    pub fn velocity(&self) -> f64 {
+        let elapsed = self.last.1 - self.previous.1;
+        if elapsed <= 0.0 {
+            0.0
+        } else {
+            (self.last.0 - self.previous.0) / elapsed
+        }
+    }
+
+    /// Whether the actions stay revealed when the finger lifts
+    pub fn finish(&self) -> bool {
+        if self.axis != SwipeAxis::Horizontal {
+            return self.was_open;
+        }
+        let velocity = self.velocity();
+        if velocity <= -SWIPE_FLICK_VELOCITY {
+            true
+        } else if velocity >= SWIPE_FLICK_VELOCITY {
+            false
+        } else {
+            self.offset() < -SWIPE_ACTIONS_WIDTH / 2.0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A slow drag from `from` to `to` along the x axis, one pixel every 10ms
+    fn slow_drag(from: f64, to: f64, was_open: bool) -> SwipeGesture {
+        let mut gesture = SwipeGesture::start(from, 100.0, 0.0, was_open);
+        let steps = (to - from).abs() as usize;
+        for step in 1..=steps {
+            let x = from + (to - from) * step as f64 / steps as f64;
+            gesture.move_to(x, 100.0, step as f64 * 10.0);
+        }
+        gesture
+    }
+
+    #[test]
+    fn test_small_moves_are_undecided() {
+        let mut gesture = SwipeGesture::start(200.0, 100.0, 0.0, false);
+        gesture.move_to(195.0, 104.0, 16.0);
+        assert_eq!(gesture.axis(), SwipeAxis::Undecided);
+        assert_eq!(gesture.offset(), 0.0);
+        assert!(!gesture.finish());
+    }
+
+    #[test]
+    fn test_threshold_decides_on_release() {
+        assert!(slow_drag(300.0, 300.0 - SWIPE_ACTIONS_WIDTH * 0.6, false).finish());
+        assert!(!slow_drag(300.0, 300.0 - SWIPE_ACTIONS_WIDTH * 0.4, false).finish());
+        // Closing an open card works the same way from the other side
+        assert!(!slow_drag(100.0, 100.0 + SWIPE_ACTIONS_WIDTH * 0.6, true).finish());
+        assert!(slow_drag(100.0, 100.0 + SWIPE_ACTIONS_WIDTH * 0.4, true).finish());
+    }
+
+    #[test]
+    fn test_offset_is_clamped() {
+        assert_eq!(slow_drag(400.0, 100.0, false).offset(), -SWIPE_ACTIONS_WIDTH);
+        assert_eq!(slow_drag(100.0, 300.0, false).offset(), 0.0);
+        assert_eq!(slow_drag(100.0, 60.0, true).offset(), -SWIPE_ACTIONS_WIDTH);
+    }
+
+    #[test]
+    fn test_vertical_scroll_cancels_the_swipe() {
+        let mut gesture = SwipeGesture::start(200.0, 100.0, 0.0, false);
+        gesture.move_to(198.0, 130.0, 16.0);
+        assert_eq!(gesture.axis(), SwipeAxis::Vertical);
+        // Moving sideways afterwards doesn't turn the scroll into a swipe
+        gesture.move_to(20.0, 140.0, 32.0);
+        assert_eq!(gesture.axis(), SwipeAxis::Vertical);
+        assert_eq!(gesture.offset(), 0.0);
+        assert!(!gesture.finish());
+
+        let mut open = SwipeGesture::start(200.0, 100.0, 0.0, true);
+        open.move_to(200.0, 60.0, 16.0);
+        assert!(open.finish());
+        assert_eq!(open.offset(), -SWIPE_ACTIONS_WIDTH);
+    }
+
+    #[test]
+    fn test_a_flick_beats_the_threshold() {
+        let mut flick = SwipeGesture::start(300.0, 100.0, 0.0, false);
+        flick.move_to(290.0, 100.0, 10.0);
+        flick.move_to(270.0, 100.0, 20.0);
+        assert!(flick.velocity() <= -SWIPE_FLICK_VELOCITY);
+        assert!(flick.offset() > -SWIPE_ACTIONS_WIDTH / 2.0);
+        assert!(flick.finish());
+
+        // A long drag ending with a flick back the other way closes
+        let mut back = slow_drag(300.0, 300.0 - SWIPE_ACTIONS_WIDTH, false);
+        let end = 10.0 * SWIPE_ACTIONS_WIDTH;
+        back.move_to(300.0 - SWIPE_ACTIONS_WIDTH + 20.0, 100.0, end + 10.0);
+        assert!(!back.finish());
+    }
+
+    #[test]
+    fn test_velocity_ignores_repeated_timestamps() {
+        let mut gesture = SwipeGesture::start(300.0, 100.0, 0.0, false);
+        gesture.move_to(280.0, 100.0, 10.0);
+        gesture.move_to(260.0, 100.0, 10.0);
+        assert_eq!(gesture.velocity(), -4.0);
+    }
+}
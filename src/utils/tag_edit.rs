@@ -0,0 +1,155 @@
+use crate::db::{normalize_tags, IdeaSummary};
+use crate::error::IdeaError;
+
+/// One tag added to or removed from an idea
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TagChange {
+    Add(String),
+    Remove(String),
+}
+
+/// `tags` after `change`, normalized the way the server stores them (see [`normalize_tags`]), so a tag shown before
+/// the server answers is spelled and placed as it will be. Removing ignores case and surrounding whitespace, like
+/// the normalizer's duplicate check; adding a tag the idea already has in another case keeps the existing spelling.
+pub fn apply_tag_change(tags: &[String], change: &TagChange) -> Result<Vec<String>, IdeaError> {
+    let mut changed = tags.to_vec();
+    match change {
+        TagChange::Add(tag) => changed.push(tag.clone()),
+        TagChange::Remove(tag) => {
+            let removed = tag.trim().to_lowercase();
+            changed.retain(|kept| kept.trim().to_lowercase() != removed);
+        }
+    }
+    normalize_tags(changed)
+}
+
+/// A tag change shown before the server confirmed it, with what's needed to settle it either way
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagEdit {
+    pub id: String,
+    pub before: Vec<String>,
+    pub after: Vec<String>,
+}
+
+/// Apply `change` to the tags of the idea `id` right away. Gives `None` when the idea is unknown or the change
+/// leaves its tags as they are, and an error for a tag the server would refuse.
+pub fn edit_tags(ideas: &mut [IdeaSummary], id: &str, change: &TagChange) -> Result<Option<TagEdit>, IdeaError> {
+    let Some(idea) = ideas.iter_mut().find(|idea| idea.id.as_deref() == Some(id)) else {
+        return Ok(None);
+    };
+    let after = apply_tag_change(&idea.tags, change)?;
+    if after == idea.tags {
+        return Ok(None);
+    }
+    let before = std::mem::replace(&mut idea.tags, after.clone());
+    Ok(Some(TagEdit { id: id.to_string(), before, after }))
+}
+
+/// Show the tags the server stored for `edit`. Tags edited again in the meantime are left alone, as that edit's
+/// answer is still to come and will carry this one's too.
+pub fn settle_tags(ideas: &mut [IdeaSummary], edit: &TagEdit, stored: Vec<String>) {
+    if let Some(idea) = unchanged_since(ideas, edit) {
+        idea.tags = stored;
+    }
+}
+
+/// Undo an [`edit_tags`] the server refused. Tags edited again in the meantime are left as they are now.
+pub fn rollback_tags(ideas: &mut [IdeaSummary], edit: &TagEdit) {
+    if let Some(idea) = unchanged_since(ideas, edit) {
+        idea.tags = edit.before.clone();
+    }
+}
+
+fn unchanged_since<'a>(ideas: &'a mut [IdeaSummary], edit: &TagEdit) -> Option<&'a mut IdeaSummary> {
+    ideas
+        .iter_mut()
+        .find(|idea| idea.id.as_deref() == Some(edit.id.as_str()))
+        .filter(|idea| idea.tags == edit.after)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::MAX_TAG_CHARS;
+
+    fn tags(list: &[&str]) -> Vec<String> {
+        list.iter().map(|tag| tag.to_string()).collect()
+    }
+
+    fn idea(id: &str, list: &[&str]) -> IdeaSummary {
+        IdeaSummary {
+            id: Some(id.to_string()),
+            title: id.to_string(),
+            tags: tags(list),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_changes_are_normalized_like_the_server() {
+        let current = tags(&["Rust", "web"]);
+        let add = |tag: &str| apply_tag_change(&current, &TagChange::Add(tag.to_string())).unwrap();
+        assert_eq!(add("  api "), tags(&["api", "Rust", "web"]));
+        assert_eq!(add("rust"), current);
+        assert_eq!(add("   "), current);
+
+        let remove = |tag: &str| apply_tag_change(&current, &TagChange::Remove(tag.to_string())).unwrap();
+        assert_eq!(remove(" RUST"), tags(&["web"]));
+        assert_eq!(remove("missing"), current);
+
+        let long = "x".repeat(MAX_TAG_CHARS + 1);
+        assert!(matches!(apply_tag_change(&current, &TagChange::Add(long)), Err(IdeaError::Validation(_))));
+    }
+
+    #[test]
+    fn test_edit_settles_to_what_the_server_stored() {
+        let mut ideas = vec![idea("a", &["web"]), idea("b", &[])];
+        let edit = edit_tags(&mut ideas, "a", &TagChange::Add("api".to_string())).unwrap().unwrap();
+        assert_eq!(ideas[0].tags, tags(&["api", "web"]));
+        assert_eq!(edit.before, tags(&["web"]));
+
+        // Another client added a tag meanwhile
+        settle_tags(&mut ideas, &edit, tags(&["api", "ux", "web"]));
+        assert_eq!(ideas[0].tags, tags(&["api", "ux", "web"]));
+        assert!(ideas[1].tags.is_empty());
+    }
+
+    #[test]
+    fn test_refused_edit_rolls_back() {
+        let mut ideas = vec![idea("a", &["web"])];
+        let before = ideas.clone();
+        let edit = edit_tags(&mut ideas, "a", &TagChange::Remove("web".to_string())).unwrap().unwrap();
+        assert!(ideas[0].tags.is_empty());
+
+        rollback_tags(&mut ideas, &edit);
+        assert_eq!(ideas, before);
+    }
+
+    #[test]
+    fn test_overlapping_edits_settle_on_the_last_answer() {
+        let mut ideas = vec![idea("a", &["web"])];
+        let first = edit_tags(&mut ideas, "a", &TagChange::Add("api".to_string())).unwrap().unwrap();
+        let second = edit_tags(&mut ideas, "a", &TagChange::Add("ux".to_string())).unwrap().unwrap();
+        assert_eq!(ideas[0].tags, tags(&["api", "ux", "web"]));
+
+        // The first answer would hide the second tag until its own answer came, so it is not shown
+        settle_tags(&mut ideas, &first, tags(&["api", "web"]));
+        assert_eq!(ideas[0].tags, tags(&["api", "ux", "web"]));
+        // Nor is a refusal of the first rolled back over the second
+        rollback_tags(&mut ideas, &first);
+        assert_eq!(ideas[0].tags, tags(&["api", "ux", "web"]));
+
+        settle_tags(&mut ideas, &second, tags(&["ux", "web"]));
+        assert_eq!(ideas[0].tags, tags(&["ux", "web"]));
+    }
+
+    #[test]
+    fn test_edits_that_change_nothing_are_skipped() {
+        let mut ideas = vec![idea("a", &["web"])];
+        assert_eq!(edit_tags(&mut ideas, "a", &TagChange::Add("Web".to_string())).unwrap(), None);
+        assert_eq!(edit_tags(&mut ideas, "a", &TagChange::Remove("api".to_string())).unwrap(), None);
+        assert_eq!(edit_tags(&mut ideas, "missing", &TagChange::Add("api".to_string())).unwrap(), None);
+        assert!(edit_tags(&mut ideas, "a", &TagChange::Add("y".repeat(MAX_TAG_CHARS + 1))).is_err());
+        assert_eq!(ideas[0].tags, tags(&["web"]));
+    }
+}
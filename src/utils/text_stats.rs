@@ -0,0 +1,125 @@
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Average silent reading speed used for the reading time estimate
+pub const WORDS_PER_MINUTE: usize = 200;
+
+/// Number of words in `text`, using Unicode word boundaries.
+///
+/// Punctuation and emoji don't count, contractions like "don't" count once, and scripts without spaces between
+/// words (Chinese, Japanese) count one word per ideograph.
+pub fn word_count(text: &str) -> usize {
+    text.unicode_words().count()
+}
+
+/// Estimated minutes needed to read `words` words, rounded up. Zero only for an empty text.
+pub fn reading_minutes(words: usize) -> usize {
+    words.div_ceil(WORDS_PER_MINUTE)
+}
+
+/// Footer line summarizing a text, e.g. "312 words · 2 min read"
+pub fn text_stats_label(text: &str) -> String {
+    let words = word_count(text);
+    let unit = if words == 1 { "word" } else { "words" };
+    format!("{} {} · {} min read", words, unit, reading_minutes(words))
+}
+
+/// Rows a textarea needs to show `text` without scrolling, never fewer than `min_rows`.
+///
+/// Lines longer than `columns` characters are assumed to wrap. This is an estimate (proportional fonts and word
+/// wrapping break it slightly), which is why the textarea should still scroll if it comes up short.
+pub fn textarea_rows(text: &str, min_rows: usize, columns: usize) -> usize {
+    let columns = columns.max(1);
+    let needed: usize = text
+        .split('\n')
+        .map(|line| line.chars().count().div_ceil(columns).max(1))
+        .sum();
+
+    // One spare row so the caret never sits on the bottom edge while typing
+    (needed + 1).max(min_rows)
+}
+
+/// The first `max_chars` characters of `text`, followed by "…" when anything was cut.
+///
+/// Counts user-perceived characters (grapheme clusters), so neither a multi-byte character nor a composed emoji
+/// like 👩‍💻 is ever split. The text is returned as is otherwise; escaping is left to whoever renders it.
+pub fn truncate_text(text: &str, max_chars: usize) -> String {
+    match text.grapheme_indices(true).nth(max_chars) {
+        Some((cut, _)) => format!("{}…", text[..cut].trim_end()),
+        None => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_word_count_basic() {
+        assert_eq!(word_count(""), 0);
+        assert_eq!(word_count("   \n\t "), 0);
+        assert_eq!(word_count("one two  three\nfour"), 4);
+    }
+
+    #[test]
+    fn test_word_count_ignores_punctuation() {
+        assert_eq!(word_count("Hello, world! -- ... ?"), 2);
+        assert_eq!(word_count("Don't panic."), 2);
+        assert_eq!(word_count("ship it 🚀"), 2);
+    }
+
+    #[test]
+    fn test_word_count_unicode() {
+        assert_eq!(word_count("Grüße aus Köln"), 3);
+        assert_eq!(word_count("Привет мир"), 2);
+        assert_eq!(word_count("数据库"), 3);
+    }
+
+    #[test]
+    fn test_reading_minutes_rounds_up() {
+        assert_eq!(reading_minutes(0), 0);
+        assert_eq!(reading_minutes(1), 1);
+        assert_eq!(reading_minutes(200), 1);
+        assert_eq!(reading_minutes(201), 2);
+    }
+
+    #[test]
+    fn test_text_stats_label() {
+        assert_eq!(text_stats_label(""), "0 words · 0 min read");
+        assert_eq!(text_stats_label("hello"), "1 word · 1 min read");
+        assert_eq!(text_stats_label(&"word ".repeat(450)), "450 words · 3 min read");
+    }
+
+    #[test]
+    fn test_textarea_rows() {
+        assert_eq!(textarea_rows("", 10, 80), 10);
+        assert_eq!(textarea_rows(&"line\n".repeat(20), 10, 80), 22);
+        // A single long line wraps over several rows
+        assert_eq!(textarea_rows(&"x".repeat(250), 1, 100), 4);
+        assert_eq!(textarea_rows("abc", 1, 0), 4);
+    }
+
+    #[test]
+    fn test_truncate_text_short_input_is_unchanged() {
+        assert_eq!(truncate_text("", 5), "");
+        assert_eq!(truncate_text("hello", 5), "hello");
+        // No escaping: markup comes back exactly as given
+        assert_eq!(truncate_text("<b>fish & chips</b>", 100), "<b>fish & chips</b>");
+    }
+
+    #[test]
+    fn test_truncate_text_cuts_and_marks() {
+        assert_eq!(truncate_text("hello world", 5), "hello…");
+        // The cut doesn't leave a dangling space before the ellipsis
+        assert_eq!(truncate_text("hello world", 6), "hello…");
+    }
+
+    #[test]
+    fn test_truncate_text_keeps_characters_whole() {
+        assert_eq!(truncate_text("héllo", 2), "hé…");
+        assert_eq!(truncate_text("🚀🚀🚀", 2), "🚀🚀…");
+        // A ZWJ sequence and a flag are one character each
+        assert_eq!(truncate_text("👩‍💻👩‍💻x", 2), "👩‍💻👩‍💻…");
+        assert_eq!(truncate_text("🇳🇱🇳🇱", 1), "🇳🇱…");
+        assert_eq!(truncate_text("日本語のテキスト", 3), "日本語…");
+    }
+}
@@ -0,0 +1,161 @@
+/// Most states an [`UndoStack`] can go back through
+pub const MAX_UNDO: usize = 50;
+
+/// States to step back and forth through, like an editor's undo history.
+///
+/// [`UndoStack::record`] adds the state just reached, dropping whatever was undone before it, and
+/// [`UndoStack::undo`] / [`UndoStack::redo`] move through the history and return the state to show. Recording the
+/// state the stack is already at does nothing, so undoing and saving the result doesn't add a step. Only the last
+/// `cap` states before the current one are kept.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UndoStack<T> {
+    /// Earlier states, oldest first
+    past: Vec<T>,
+    current: Option<T>,
+    /// Undone states, the next redo last
+    future: Vec<T>,
+    cap: usize,
+}
+
+impl<T: Clone + PartialEq> Default for UndoStack<T> {
+    fn default() -> Self {
+        Self::with_cap(MAX_UNDO)
+    }
+}
+
+impl<T: Clone + PartialEq> UndoStack<T> {
+    /// An empty stack keeping at most `cap` earlier states
+    pub fn with_cap(cap: usize) -> Self {
+        Self {
+            past: Vec::new(),
+            current: None,
+            future: Vec::new(),
+            cap,
+        }
+    }
+
+    /// Forget the history and start over at `state`
+    pub fn reset(&mut self, state: T) {
+        self.past.clear();
+        self.future.clear();
+        self.current = Some(state);
+    }
+
+    /// Record `state` as the newest step. Returns whether it was one, i.e. whether it differs from the current state.
+    pub fn record(&mut self, state: T) -> bool {
+        if self.current.as_ref() == Some(&state) {
+            return false;
+        }
+        if let Some(previous) = self.current.replace(state) {
+            self.past.push(previous);
+            if self.past.len() > self.cap {
+                self.past.remove(0);
+            }
+        }
+        self.future.clear();
+        true
+    }
+
+    /// Step back, returning the state to show, or `None` with nothing to undo
+    pub fn undo(&mut self) -> Option<T> {
+        let previous = self.past.pop()?;
+        if let Some(current) = self.current.replace(previous.clone()) {
+            self.future.push(current);
+        }
+        Some(previous)
+    }
+
+    /// Step forward again after an undo, returning the state to show, or `None` with nothing to redo
+    pub fn redo(&mut self) -> Option<T> {
+        let next = self.future.pop()?;
+        if let Some(current) = self.current.replace(next.clone()) {
+            self.past.push(current);
+        }
+        Some(next)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.past.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.future.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stack(states: &[&'static str]) -> UndoStack<&'static str> {
+        let mut stack = UndoStack::default();
+        for state in states {
+            stack.record(*state);
+        }
+        stack
+    }
+
+    #[test]
+    fn test_undo_and_redo_walk_the_history() {
+        let mut stack = stack(&["a", "b", "c"]);
+        assert_eq!(stack.undo(), Some("b"));
+        assert_eq!(stack.undo(), Some("a"));
+        assert_eq!(stack.undo(), None);
+        assert!(!stack.can_undo());
+
+        assert_eq!(stack.redo(), Some("b"));
+        assert_eq!(stack.redo(), Some("c"));
+        assert_eq!(stack.redo(), None);
+        assert_eq!(stack.undo(), Some("b"));
+    }
+
+    #[test]
+    fn test_recording_after_undo_drops_the_redo_steps() {
+        let mut stack = stack(&["a", "b", "c"]);
+        stack.undo();
+        assert!(stack.can_redo());
+        assert!(stack.record("d"));
+        assert!(!stack.can_redo());
+        assert_eq!(stack.undo(), Some("b"));
+        assert_eq!(stack.undo(), Some("a"));
+    }
+
+    #[test]
+    fn test_unchanged_states_are_not_recorded() {
+        let mut stack = stack(&["a", "b"]);
+        assert!(!stack.record("b"));
+        assert_eq!(stack.undo(), Some("a"));
+        // Saving the undone state doesn't count as a step, so redo still works
+        assert!(!stack.record("a"));
+        assert_eq!(stack.redo(), Some("b"));
+    }
+
+    #[test]
+    fn test_only_the_newest_states_are_kept() {
+        let mut stack = UndoStack::with_cap(2);
+        for state in 0..5 {
+            stack.record(state);
+        }
+        assert_eq!(stack.undo(), Some(3));
+        assert_eq!(stack.undo(), Some(2));
+        assert_eq!(stack.undo(), None);
+
+        let mut stack = UndoStack::default();
+        for state in 0..=MAX_UNDO + 10 {
+            stack.record(state);
+        }
+        let mut undone = 0;
+        while stack.undo().is_some() {
+            undone += 1;
+        }
+        assert_eq!(undone, MAX_UNDO);
+    }
+
+    #[test]
+    fn test_reset_forgets_the_history() {
+        let mut stack = stack(&["a", "b"]);
+        stack.reset("other idea");
+        assert!(!stack.can_undo() && !stack.can_redo());
+        assert!(!stack.record("other idea"));
+    }
+}
@@ -0,0 +1,208 @@
+use super::{normalize_newlines, normalize_statements};
+use crate::db::Idea;
+
+/// A part of an idea the development page edits
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditedField {
+    Statements,
+    Notes,
+    DueDate,
+}
+
+impl EditedField {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            EditedField::Statements => "statements",
+            EditedField::Notes => "notes",
+            EditedField::DueDate => "due date",
+        }
+    }
+}
+
+/// What the development page changed in `edited` compared to `saved`, the version last loaded from or saved to the
+/// server. Empty when there is nothing to save.
+///
+/// Both sides are normalized the way the server stores them first, so what a save wouldn't change doesn't count.
+/// Statements compare trimmed and without blank ones, but in order, so reordering them counts as a change. Notes
+/// compare with line endings normalized: a browser textarea hands back `\n` even where the stored notes, e.g. from
+/// an import, have `\r\n`.
+pub fn unsaved_fields(saved: &Idea, edited: &Idea) -> Vec<EditedField> {
+    let mut fields = Vec::new();
+    if normalize_statements(&saved.what_must_be_true) != normalize_statements(&edited.what_must_be_true) {
+        fields.push(EditedField::Statements);
+    }
+    if normalize_newlines(&saved.development_notes) != normalize_newlines(&edited.development_notes) {
+        fields.push(EditedField::Notes);
+    }
+    if saved.due_date != edited.due_date {
+        fields.push(EditedField::DueDate);
+    }
+    fields
+}
+
+/// Editors that registered for the leave warning, whether each has unsaved changes, and a navigation held back until
+/// the user decides whether to leave them. `T` is where to navigate, a route in the app.
+///
+/// Editors register when they mount and unregister when they unmount, so an editor that is gone never blocks.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LeaveGuard<T> {
+    next_id: u64,
+    editors: Vec<(u64, bool)>,
+    pending: Option<T>,
+}
+
+impl<T> Default for LeaveGuard<T> {
+    fn default() -> Self {
+        Self { next_id: 0, editors: Vec::new(), pending: None }
+    }
+}
+
+impl<T> LeaveGuard<T> {
+    /// Add a clean editor and return the id to report its state with
+    pub fn register(&mut self) -> u64 {
+        self.next_id += 1;
+        self.editors.push((self.next_id, false));
+        self.next_id
+    }
+
+    /// Whether the editor `id` has unsaved changes. Ignored once it unregistered.
+    pub fn set_dirty(&mut self, id: u64, dirty: bool) {
+        if let Some(editor) = self.editors.iter_mut().find(|(editor, _)| *editor == id) {
+            editor.1 = dirty;
+        }
+    }
+
+    /// Forget the editor `id`
+    pub fn unregister(&mut self, id: u64) {
+        self.editors.retain(|(editor, _)| *editor != id);
+    }
+
+    /// Whether any editor has unsaved changes
+    pub fn is_dirty(&self) -> bool {
+        self.editors.iter().any(|(_, dirty)| *dirty)
+    }
+
+    /// Navigate to `to`: handed back to go there now, or held until [`LeaveGuard::confirm`] or
+    /// [`LeaveGuard::cancel`] while an editor is dirty. A newer navigation replaces the one held.
+    pub fn navigate(&mut self, to: T) -> Option<T> {
+        if self.is_dirty() {
+            self.pending = Some(to);
+            None
+        } else {
+            Some(to)
+        }
+    }
+
+    /// The navigation waiting for the user's answer
+    pub fn pending(&self) -> Option<&T> {
+        self.pending.as_ref()
+    }
+
+    /// Leave anyway: the held navigation, to go there now
+    pub fn confirm(&mut self) -> Option<T> {
+        self.pending.take()
+    }
+
+    /// Stay: drop the held navigation
+    pub fn cancel(&mut self) {
+        self.pending = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn saved() -> Idea {
+        Idea {
+            title: "Garden".to_string(),
+            what_must_be_true: vec!["soil".to_string(), "sun".to_string()],
+            development_notes: "line one\r\nline two".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_unchanged_idea_is_clean() {
+        assert!(unsaved_fields(&saved(), &saved()).is_empty());
+
+        // The textarea's line endings alone don't make the notes dirty
+        let edited = Idea { development_notes: "line one\nline two".to_string(), ..saved() };
+        assert!(unsaved_fields(&saved(), &edited).is_empty());
+
+        // Fields the page doesn't edit don't count
+        let edited = Idea { title: "Renamed elsewhere".to_string(), ..saved() };
+        assert!(unsaved_fields(&saved(), &edited).is_empty());
+
+        // Whitespace the server would trim away isn't worth a save
+        let edited = Idea {
+            what_must_be_true: vec!["soil ".to_string(), "  ".to_string(), " sun".to_string()],
+            development_notes: "line one\rline two".to_string(),
+            ..saved()
+        };
+        assert!(unsaved_fields(&saved(), &edited).is_empty());
+    }
+
+    #[test]
+    fn test_each_edited_field_is_reported() {
+        let edited = Idea {
+            what_must_be_true: vec!["sun".to_string(), "soil".to_string()],
+            development_notes: "line one\nline two\nline three".to_string(),
+            due_date: NaiveDate::from_ymd_opt(2024, 6, 1),
+            ..saved()
+        };
+        assert_eq!(
+            unsaved_fields(&saved(), &edited),
+            vec![EditedField::Statements, EditedField::Notes, EditedField::DueDate]
+        );
+
+        let edited = Idea { what_must_be_true: vec!["soil".to_string()], ..saved() };
+        assert_eq!(unsaved_fields(&saved(), &edited), vec![EditedField::Statements]);
+    }
+
+    #[test]
+    fn test_editors_register_and_unregister() {
+        let mut guard = LeaveGuard::<&str>::default();
+        assert!(!guard.is_dirty());
+        let notes = guard.register();
+        let form = guard.register();
+        assert_ne!(notes, form);
+        assert!(!guard.is_dirty());
+
+        guard.set_dirty(notes, true);
+        assert!(guard.is_dirty());
+        guard.set_dirty(form, true);
+        guard.set_dirty(notes, false);
+        assert!(guard.is_dirty());
+
+        // An editor that unmounted while dirty doesn't keep blocking, and late reports from it are ignored
+        guard.unregister(form);
+        assert!(!guard.is_dirty());
+        guard.set_dirty(form, true);
+        assert!(!guard.is_dirty());
+    }
+
+    #[test]
+    fn test_navigation_waits_for_an_answer_while_dirty() {
+        let mut guard = LeaveGuard::default();
+        let editor = guard.register();
+        assert_eq!(guard.navigate("/board"), Some("/board"));
+        assert_eq!(guard.pending(), None);
+
+        guard.set_dirty(editor, true);
+        assert_eq!(guard.navigate("/board"), None);
+        assert_eq!(guard.navigate("/stats"), None);
+        assert_eq!(guard.pending(), Some(&"/stats"));
+        guard.cancel();
+        assert_eq!(guard.pending(), None);
+        assert_eq!(guard.confirm(), None);
+
+        assert_eq!(guard.navigate("/trash"), None);
+        assert_eq!(guard.confirm(), Some("/trash"));
+        assert_eq!(guard.pending(), None);
+
+        guard.set_dirty(editor, false);
+        assert_eq!(guard.navigate("/review"), Some("/review"));
+    }
+}
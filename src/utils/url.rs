@@ -0,0 +1,89 @@
+/// Percent-encode everything but unreserved characters, for use in a path segment or query value
+pub fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (byte as char).to_string(),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+/// Undo [`percent_encode`] for a query value. `+` is read as a space, as browsers encode forms that way.
+///
+/// Malformed escapes are kept as they are rather than rejected, and invalid UTF-8 is replaced.
+pub fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        match bytes[index] {
+            b'%' => {
+                let escaped = bytes
+                    .get(index + 1..index + 3)
+                    .and_then(|hex| std::str::from_utf8(hex).ok())
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+                match escaped {
+                    Some(byte) => {
+                        decoded.push(byte);
+                        index += 3;
+                        continue;
+                    }
+                    None => decoded.push(b'%'),
+                }
+            }
+            b'+' => decoded.push(b' '),
+            byte => decoded.push(byte),
+        }
+        index += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Words to search for when nothing lives at a path, from its last segment: `/ideas/bike-repair-cafe` offers
+/// "bike repair cafe". A `table:` prefix is dropped, as in a mistyped `/ideas/ideas:abc`. Empty when the path has
+/// no words.
+pub fn search_from_path(segments: &[String]) -> String {
+    let Some(last) = segments.iter().rev().find(|segment| !segment.trim().is_empty()) else {
+        return String::new();
+    };
+    let decoded = percent_decode(last);
+    let key = decoded.rsplit(':').next().unwrap_or_default();
+    key.split(|c: char| c.is_whitespace() || matches!(c, '-' | '_' | '.'))
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() {
+        for value in ["ideas:abc", "a b&c=d", "naïve ✓", ""] {
+            assert_eq!(percent_decode(&percent_encode(value)), value);
+        }
+        assert_eq!(percent_encode("ideas:abc"), "ideas%3Aabc");
+    }
+
+    #[test]
+    fn test_decode_plus_and_malformed_escapes() {
+        assert_eq!(percent_decode("a+b"), "a b");
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("%zz%4"), "%zz%4");
+    }
+
+    #[test]
+    fn test_search_from_path() {
+        let search = |path: &str| {
+            let segments: Vec<String> = path.split('/').map(str::to_string).collect();
+            search_from_path(&segments)
+        };
+        assert_eq!(search("ideas/bike-repair-cafe"), "bike repair cafe");
+        assert_eq!(search("ideas/ideas%3Arooftop_garden/"), "rooftop garden");
+        assert_eq!(search("caf%C3%A9+plans.html"), "café plans html");
+        assert_eq!(search(""), "");
+        assert_eq!(search("---/"), "");
+    }
+}
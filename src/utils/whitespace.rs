@@ -0,0 +1,113 @@
+/// A title as it is stored: trimmed, with every run of whitespace inside it, line breaks included, turned into one
+/// space
+pub fn normalize_title(title: &str) -> String {
+    title.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// `text` with Windows (`\r\n`) and old Mac (`\r`) line endings turned into `\n`. Nothing else changes, so the
+/// indentation and blank lines of a description or of notes stay as typed.
+pub fn normalize_newlines(text: &str) -> String {
+    text.replace("\r\n", "\n").replace('\r', "\n")
+}
+
+/// "What must be true" statements as they are stored: each one trimmed, and the ones left blank dropped
+pub fn normalize_statements(statements: &[String]) -> Vec<String> {
+    statements
+        .iter()
+        .map(|statement| statement.trim())
+        .filter(|statement| !statement.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Whitespace an input could carry anywhere
+    const SPACES: &[&str] = &[" ", "  ", "\t", "\n", "\r\n", "\r", "\u{a0}", " \t "];
+
+    /// A small xorshift generator, so the random cases are the same on every run
+    struct Cases(u64);
+
+    impl Cases {
+        fn next(&mut self, below: usize) -> usize {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            (self.0 % below as u64) as usize
+        }
+
+        /// `words` with random whitespace before, between and after them
+        fn padded(&mut self, words: &[&str]) -> String {
+            let mut text = String::new();
+            for word in words {
+                for _ in 0..self.next(3) {
+                    text.push_str(SPACES[self.next(SPACES.len())]);
+                }
+                if !text.is_empty() || self.next(2) == 0 {
+                    text.push_str(SPACES[self.next(SPACES.len())]);
+                }
+                text.push_str(word);
+            }
+            for _ in 0..self.next(3) {
+                text.push_str(SPACES[self.next(SPACES.len())]);
+            }
+            text
+        }
+    }
+
+    const WORDS: &[&str] = &["Ship", "the", "café", "v2.0", "日本語", "a<b", "--flag"];
+
+    #[test]
+    fn test_title_keeps_its_words_with_single_spaces() {
+        let mut cases = Cases(0x5EED);
+        for _ in 0..500 {
+            let words = &WORDS[..1 + cases.next(WORDS.len())];
+            let title = cases.padded(words);
+            let normalized = normalize_title(&title);
+            assert_eq!(normalized, words.join(" "), "{:?}", title);
+            assert_eq!(normalize_title(&normalized), normalized);
+        }
+        assert_eq!(normalize_title(" \t\r\n "), "");
+    }
+
+    #[test]
+    fn test_line_endings_become_newlines() {
+        let mut cases = Cases(0xC0FFEE);
+        for _ in 0..500 {
+            let text = cases.padded(WORDS);
+            let normalized = normalize_newlines(&text);
+            assert!(!normalized.contains('\r'), "{:?}", text);
+            // Every line break survives as one `\n`, and the words and other whitespace stay as they were
+            let breaks = text.matches("\r\n").count() + text.replace("\r\n", "").matches(['\r', '\n']).count();
+            assert_eq!(normalized.matches('\n').count(), breaks, "{:?}", text);
+            assert_eq!(normalized.split_whitespace().collect::<Vec<_>>(), WORDS);
+            assert_eq!(normalize_newlines(&normalized), normalized);
+        }
+        assert_eq!(normalize_newlines("a\r\n\r\nb\rc\n"), "a\n\nb\nc\n");
+        assert_eq!(normalize_newlines("  indented\n\n"), "  indented\n\n");
+    }
+
+    #[test]
+    fn test_statements_are_trimmed_and_blank_ones_dropped() {
+        let mut cases = Cases(0xBEEF);
+        for _ in 0..500 {
+            let statements: Vec<String> = (0..cases.next(5))
+                .map(|_| {
+                    let words = &WORDS[..cases.next(3)];
+                    cases.padded(words)
+                })
+                .collect();
+            let normalized = normalize_statements(&statements);
+            let expected: Vec<&String> = statements.iter().filter(|s| !s.trim().is_empty()).collect();
+            assert_eq!(normalized.len(), expected.len(), "{:?}", statements);
+            for (statement, original) in normalized.iter().zip(expected) {
+                assert_eq!(statement, original.trim());
+            }
+            assert_eq!(normalize_statements(&normalized), normalized);
+        }
+        // Whitespace inside a statement is its own business
+        assert_eq!(normalize_statements(&["  two  spaces ".to_string()]), vec!["two  spaces"]);
+    }
+}
@@ -0,0 +1,396 @@
+use crate::components::{ConfirmDialog, TagManager};
+use crate::error::error_message;
+use crate::hooks::{use_app_config, use_idea_list_revision, use_stylesheet};
+use crate::form_config::FormConfig;
+use crate::server_functions::{
+    create_backup_server, delete_malformed_idea_server, get_form_config_server, get_malformed_ideas_server,
+    normalize_existing_tags_server, restore_backup_server, run_maintenance_server, set_form_config_server,
+    trigger_snapshot_server,
+};
+use dioxus::prelude::*;
+
+const ADMIN_CSS: Asset = asset!("/assets/styling/admin.css");
+
+/// Admin page with database maintenance tools
+#[component]
+pub fn Admin() -> Element {
+    use_stylesheet(ADMIN_CSS);
+
+    // The latest backup dump, shown so it can be copied somewhere safe
+    let mut backup = use_signal(|| String::new());
+    // Dump pasted in by the user for restoring
+    let mut restore_input = use_signal(|| String::new());
+    let mut wipe_first = use_signal(|| true);
+    let mut is_busy = use_signal(|| false);
+    let mut message = use_signal(|| String::new());
+    let retention_days = use_app_config().get().trash_retention_days;
+
+    rsx! {
+        div {
+            id: "admin",
+
+            section {
+                class: "admin-section",
+                h2 { "backup" }
+                p { class: "hint", "Exports every idea as a SurrealQL dump. Copy it somewhere safe." }
+                button {
+                    r#type: "button",
+                    class: "admin-btn",
+                    disabled: is_busy(),
+                    onclick: move |_| async move {
+                        is_busy.set(true);
+                        match create_backup_server().await {
+                            Ok(dump) => {
+                                backup.set(dump);
+                                message.set("backup created".to_string());
+                            }
+                            Err(e) => message.set(format!("error: {}", e)),
+                        }
+                        is_busy.set(false);
+                    },
+                    "create backup"
+                }
+                if !backup().is_empty() {
+                    textarea {
+                        class: "dump",
+                        readonly: true,
+                        rows: 10,
+                        value: "{backup}",
+                    }
+                }
+            }
+
+            section {
+                class: "admin-section",
+                h2 { "restore" }
+                p { class: "hint", "Paste a dump from a previous backup. A dump that fails leaves the current data untouched." }
+                textarea {
+                    class: "dump",
+                    rows: 10,
+                    placeholder: "Paste a backup dump here...",
+                    value: "{restore_input}",
+                    oninput: move |e| restore_input.set(e.value()),
+                }
+                label {
+                    class: "checkbox",
+                    input {
+                        r#type: "checkbox",
+                        checked: wipe_first(),
+                        onchange: move |e| wipe_first.set(e.checked()),
+                    }
+                    "wipe existing ideas first"
+                }
+                button {
+                    r#type: "button",
+                    class: "admin-btn",
+                    disabled: is_busy() || restore_input().trim().is_empty(),
+                    onclick: move |_| async move {
+                        is_busy.set(true);
+                        match restore_backup_server(restore_input(), wipe_first()).await {
+                            Ok(()) => {
+                                restore_input.set(String::new());
+                                message.set("backup restored".to_string());
+                            }
+                            Err(e) => message.set(format!("error: {}", e)),
+                        }
+                        is_busy.set(false);
+                    },
+                    "restore"
+                }
+            }
+
+            section {
+                class: "admin-section",
+                h2 { "tags" }
+                p { class: "hint", "Tags get a color from their name. Pin one to give a tag the same color everywhere." }
+                TagManager {}
+                p {
+                    class: "hint",
+                    "Ideas saved before tags were tidied can hold duplicates like \"Rust\" and \"rust \". Tidy them once to merge those and cut tags that are too long."
+                }
+                button {
+                    r#type: "button",
+                    class: "admin-btn",
+                    disabled: is_busy(),
+                    onclick: move |_| async move {
+                        is_busy.set(true);
+                        match normalize_existing_tags_server().await {
+                            Ok(0) => message.set("tags already tidy".to_string()),
+                            Ok(changed) => message.set(format!("tidied the tags of {} ideas", changed)),
+                            Err(e) => message.set(format!("error: {}", e)),
+                        }
+                        is_busy.set(false);
+                    },
+                    "tidy stored tags"
+                }
+            }
+
+            FormFields {}
+
+            UnreadableIdeas {}
+
+            section {
+                class: "admin-section",
+                h2 { "maintenance" }
+                p {
+                    class: "hint",
+                    if retention_days == 0 {
+                        "Runs daily: drops revisions beyond the limit. Trashed ideas are kept until the trash is emptied."
+                    } else {
+                        "Runs daily: drops revisions beyond the limit and deletes ideas trashed more than {retention_days} days ago."
+                    }
+                }
+                button {
+                    r#type: "button",
+                    class: "admin-btn",
+                    disabled: is_busy(),
+                    onclick: move |_| async move {
+                        is_busy.set(true);
+                        match run_maintenance_server().await {
+                            Ok(report) => message.set(report.to_string()),
+                            Err(e) => message.set(format!("error: {}", e)),
+                        }
+                        is_busy.set(false);
+                    },
+                    "run maintenance now"
+                }
+                p { class: "hint", "Nightly, the database is also written to a dated file in the snapshot folder." }
+                button {
+                    r#type: "button",
+                    class: "admin-btn",
+                    disabled: is_busy(),
+                    onclick: move |_| async move {
+                        is_busy.set(true);
+                        match trigger_snapshot_server().await {
+                            Ok(report) => message.set(report.to_string()),
+                            Err(e) => message.set(format!("error: {}", error_message(&e))),
+                        }
+                        is_busy.set(false);
+                    },
+                    "take a snapshot now"
+                }
+            }
+
+            {dev_tools_section()}
+
+            if !message().is_empty() {
+                p { class: "message", "{message}" }
+            }
+        }
+    }
+}
+
+/// The new-idea form as JSON, to require or hide built-in fields and add the deployment's own
+#[component]
+fn FormFields() -> Element {
+    let read_only = use_app_config().read_only();
+    let mut input = use_signal(|| String::new());
+    let mut saving = use_signal(|| false);
+    let mut status = use_signal(|| String::new());
+
+    use_future(move || async move {
+        match get_form_config_server().await {
+            Ok(config) => input.set(serde_json::to_string_pretty(&config).unwrap_or_default()),
+            Err(e) => status.set(format!("error: {}", error_message(&e))),
+        }
+    });
+
+    let save = move |_| async move {
+        let config: FormConfig = match serde_json::from_str(&input()) {
+            Ok(config) => config,
+            Err(e) => {
+                status.set(format!("error: the form isn't valid JSON: {}", e));
+                return;
+            }
+        };
+        saving.set(true);
+        match set_form_config_server(config).await {
+            Ok(saved) => {
+                // Shown as stored, with names and options trimmed
+                input.set(serde_json::to_string_pretty(&saved).unwrap_or_default());
+                status.set("form saved".to_string());
+            }
+            Err(e) => status.set(format!("error: {}", error_message(&e))),
+        }
+        saving.set(false);
+    };
+
+    rsx! {
+        section {
+            class: "admin-section",
+            h2 { "form" }
+            p {
+                class: "hint",
+                "Fields of the new-idea form. \"description\" and \"tags\" are \"optional\", \"required\" or \"hidden\". Each custom field has a \"name\", a \"kind\" (\"text\", \"select\" or \"number\"), the \"options\" of a select and whether it is \"required\"."
+            }
+            textarea {
+                class: "dump",
+                rows: 12,
+                spellcheck: false,
+                value: "{input}",
+                oninput: move |e| input.set(e.value()),
+            }
+            button {
+                r#type: "button",
+                class: "admin-btn",
+                disabled: saving() || read_only,
+                onclick: save,
+                if saving() { "saving…" } else { "save form" }
+            }
+            if !status().is_empty() {
+                p { class: "message", "{status}" }
+            }
+        }
+    }
+}
+
+/// Stored ideas that can't be read any more, e.g. after hand edits to the database, each with why and a delete
+/// button. The lists leave them out until then.
+#[component]
+fn UnreadableIdeas() -> Element {
+    let mut idea_list_revision = use_idea_list_revision();
+    let mut records = use_resource(|| async move { get_malformed_ideas_server().await });
+    let mut deleting = use_signal(|| None::<String>);
+    let mut message = use_signal(String::new);
+
+    rsx! {
+        section {
+            class: "admin-section",
+            h2 { "unreadable ideas" }
+            match records() {
+                None => rsx! { p { class: "hint", "checking stored ideas..." } },
+                Some(Err(e)) => rsx! { p { class: "message", "error: {error_message(&e)}" } },
+                Some(Ok(found)) if found.is_empty() => rsx! { p { class: "hint", "Every stored idea can be read." } },
+                Some(Ok(found)) => rsx! {
+                    p {
+                        class: "hint",
+                        "These records don't match what the app expects, so the lists leave them out. Fix them in the database or delete them."
+                    }
+                    ul {
+                        class: "malformed-records",
+                        for record in found {
+                            li {
+                                key: "{record.id}",
+                                code { "{record.id}" }
+                                p { class: "malformed-error", "{record.error}" }
+                                button {
+                                    r#type: "button",
+                                    class: "admin-btn",
+                                    onclick: {
+                                        let id = record.id.clone();
+                                        move |_| deleting.set(Some(id.clone()))
+                                    },
+                                    "delete"
+                                }
+                            }
+                        }
+                    }
+                },
+            }
+            if let Some(id) = deleting() {
+                ConfirmDialog {
+                    message: format!("Delete {} for good? It can't be restored from the trash.", id),
+                    confirm_label: "delete",
+                    on_confirm: move |_| {
+                        let id = id.clone();
+                        async move {
+                            deleting.set(None);
+                            match delete_malformed_idea_server(id.clone()).await {
+                                Ok(()) => {
+                                    message.set(format!("deleted {}", id));
+                                    records.restart();
+                                    idea_list_revision.bump();
+                                }
+                                Err(e) => message.set(format!("error: {}", error_message(&e))),
+                            }
+                        }
+                    },
+                    on_cancel: move |_| deleting.set(None),
+                }
+            }
+            if !message().is_empty() {
+                p { class: "message", "{message}" }
+            }
+        }
+    }
+}
+
+/// The developer reset, or nothing when built without the `dev-tools` feature
+fn dev_tools_section() -> Element {
+    #[cfg(feature = "dev-tools")]
+    {
+        rsx! { DevReset {} }
+    }
+
+    #[cfg(not(feature = "dev-tools"))]
+    {
+        rsx! {}
+    }
+}
+
+/// Empty the database after a confirmation, optionally filling it with sample ideas
+#[cfg(feature = "dev-tools")]
+#[component]
+fn DevReset() -> Element {
+    use crate::hooks::use_trash_revision;
+    use crate::server_functions::{get_reset_token_server, reset_database_server};
+
+    let mut idea_list_revision = use_idea_list_revision();
+    let mut trash_revision = use_trash_revision();
+    let mut seed = use_signal(|| true);
+    let mut confirming = use_signal(|| false);
+    let mut message = use_signal(String::new);
+
+    rsx! {
+        section {
+            class: "admin-section",
+            h2 { "developer reset" }
+            p {
+                class: "hint",
+                "Deletes every idea, attachment, tag color and custom template. Accounts are kept."
+            }
+            label {
+                input {
+                    r#type: "checkbox",
+                    checked: seed(),
+                    onchange: move |evt| seed.set(evt.checked()),
+                }
+                " add sample ideas afterwards"
+            }
+            button {
+                r#type: "button",
+                class: "admin-btn",
+                onclick: move |_| confirming.set(true),
+                "reset database"
+            }
+            if confirming() {
+                ConfirmDialog {
+                    message: "Delete everything except accounts?",
+                    confirm_label: "reset",
+                    on_confirm: move |_| async move {
+                        confirming.set(false);
+                        let result = match get_reset_token_server().await {
+                            Ok(token) => reset_database_server(token, seed()).await,
+                            Err(e) => Err(e),
+                        };
+                        match result {
+                            Ok(report) => {
+                                idea_list_revision.bump();
+                                trash_revision.bump();
+                                message.set(format!(
+                                    "removed {} ideas, added {} sample ideas",
+                                    report.ideas_removed, report.ideas_seeded
+                                ));
+                            }
+                            Err(e) => message.set(format!("error: {}", error_message(&e))),
+                        }
+                    },
+                    on_cancel: move |_| confirming.set(false),
+                }
+            }
+            if !message().is_empty() {
+                p { class: "message", "{message}" }
+            }
+        }
+    }
+}
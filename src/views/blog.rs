@@ -1,3 +1,4 @@
+use crate::hooks::use_stylesheet;
 use crate::Route;
 use dioxus::prelude::*;
 
@@ -9,9 +10,9 @@ const BLOG_CSS: Asset = asset!("/assets/styling/blog.css");
 /// re-run and the rendered HTML will be updated.
 #[component]
 pub fn Blog(id: i32) -> Element {
-    rsx! {
-        document::Link { rel: "stylesheet", href: BLOG_CSS }
+    use_stylesheet(BLOG_CSS);
 
+    rsx! {
         div {
             id: "blog",
 
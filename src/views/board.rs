@@ -0,0 +1,256 @@
+use crate::db::{IdeaStatus, IdeaSummary};
+use crate::error::error_message;
+use crate::hooks::{
+    use_app_config, use_drag_list, use_idea_list_revision, use_settings, use_stylesheet, use_toasts, DragList,
+};
+use crate::server_functions::{get_idea_summaries_server, set_idea_status_server};
+use crate::utils::{move_card, partition_by_status, rollback_move};
+use crate::Route;
+use dioxus::prelude::*;
+
+const BOARD_CSS: Asset = asset!("/assets/styling/board.css");
+
+/// Where a card is on the board: its column and its position in that column. Entering a column's empty space gives
+/// the position after its last card.
+type CardSlot = (IdeaStatus, usize);
+
+/// The signed-in user's ideas as a kanban board with a column per status.
+///
+/// Dragging a card to another column changes its status right away and puts it back if the server refuses. Only the
+/// status changes, so a card keeps its place in the manual order and shows up in the new column between the ideas
+/// ranked around it. Column headers can be dragged to reorder the columns and clicked to collapse them; both are kept
+/// in the settings.
+#[component]
+pub fn Board() -> Element {
+    use_stylesheet(BOARD_CSS);
+
+    let mut idea_list_revision = use_idea_list_revision();
+    let mut settings = use_settings();
+    let mut toasts = use_toasts();
+    let read_only = use_app_config().read_only();
+
+    let ideas = use_resource(move || async move {
+        let _ = idea_list_revision.get();
+        get_idea_summaries_server().await
+    });
+    // The fetched ideas with the moves not yet confirmed applied on top
+    let mut board = use_signal(|| Option::<Vec<IdeaSummary>>::None);
+    use_effect(move || {
+        if let Some(Ok(list)) = &*ideas.read() {
+            board.set(Some(list.clone()));
+        }
+    });
+
+    let mut cards = use_drag_list::<CardSlot>();
+    let mut columns_drag = use_drag_list::<usize>();
+    let layout = settings.board();
+
+    let mut move_to = move |id: String, to: IdeaStatus| {
+        let Some(undo) = board.write().as_mut().and_then(|list| move_card(list, &id, to)) else {
+            return;
+        };
+        spawn(async move {
+            match set_idea_status_server(id, to).await {
+                // Other pages showing the idea refetch; the board does too, which changes nothing
+                Ok(_) => idea_list_revision.bump(),
+                Err(e) => {
+                    if let Some(list) = board.write().as_mut() {
+                        rollback_move(list, &undo);
+                    }
+                    toasts.error(format!("Couldn't move the idea: {}", error_message(&e)));
+                }
+            }
+        });
+    };
+
+    let columns = board.read().as_deref().map(|list| partition_by_status(list, &layout.order));
+
+    rsx! {
+        div {
+            id: "board",
+            h2 { "board" }
+
+            match (columns, &*ideas.read()) {
+                (Some(columns), _) => rsx! {
+                    div {
+                        class: "board-columns",
+                        onpointerup: move |_| {
+                            if let Some((from, to)) = columns_drag.finish() {
+                                settings.update_board(|layout| layout.move_column(from, to));
+                            }
+                            if let Some(((from, index), (to, _))) = cards.finish() {
+                                // Positions in a column are positions among the ideas with that status
+                                let dragged = board
+                                    .read()
+                                    .iter()
+                                    .flatten()
+                                    .filter(|idea| idea.status == from)
+                                    .nth(index)
+                                    .and_then(|idea| idea.id.clone());
+                                if let (Some(id), true) = (dragged, from != to) {
+                                    move_to(id, to);
+                                }
+                            }
+                        },
+                        onpointerleave: move |_| {
+                            cards.cancel();
+                            columns_drag.cancel();
+                        },
+
+                        for (position, (status, column)) in columns.into_iter().enumerate() {
+                            BoardColumn {
+                                key: "{status}",
+                                status,
+                                position,
+                                ideas: column,
+                                collapsed: layout.is_collapsed(status),
+                                read_only,
+                                cards,
+                                columns_drag,
+                                on_toggle: move |status| settings.update_board(|layout| layout.toggle_collapsed(status)),
+                                on_move: move |(id, to): (String, IdeaStatus)| move_to(id, to),
+                            }
+                        }
+                    }
+                },
+                (None, Some(Err(e))) => rsx! {
+                    p { class: "message", "Failed to load the board: {error_message(e)}" }
+                },
+                (None, _) => rsx! {
+                    p { class: "board-hint", "Loading..." }
+                },
+            }
+        }
+    }
+}
+
+/// One status: a header that collapses the column and drags it, and the cards in that status
+#[component]
+fn BoardColumn(
+    status: IdeaStatus,
+    position: usize,
+    ideas: Vec<IdeaSummary>,
+    collapsed: bool,
+    read_only: bool,
+    cards: DragList<CardSlot>,
+    columns_drag: DragList<usize>,
+    on_toggle: EventHandler<IdeaStatus>,
+    on_move: EventHandler<(String, IdeaStatus)>,
+) -> Element {
+    let (mut cards, mut columns_drag) = (cards, columns_drag);
+    let count = ideas.len();
+
+    rsx! {
+        section {
+            class: "board-column",
+            class: if collapsed { "collapsed" },
+            class: if cards.is_over_where(|(over, _)| over == status) { "drop-target" },
+            class: if columns_drag.is_dragging(position) { "dragging" },
+            class: if columns_drag.is_over(position) { "column-drop-target" },
+            aria_label: "{status}",
+            onpointerenter: move |_| {
+                columns_drag.enter(position);
+                cards.enter((status, count));
+            },
+
+            div {
+                class: "board-column-header",
+                button {
+                    r#type: "button",
+                    class: "column-handle",
+                    title: "drag to reorder the columns",
+                    aria_label: "move the {status} column",
+                    onpointerdown: move |evt| {
+                        evt.prevent_default();
+                        columns_drag.start(position);
+                    },
+                    "⋮⋮"
+                }
+                button {
+                    r#type: "button",
+                    class: "column-toggle",
+                    aria_expanded: !collapsed,
+                    onclick: move |_| on_toggle.call(status),
+                    span { class: "column-name", "{status}" }
+                    span { class: "column-count", "{count}" }
+                }
+            }
+
+            if !collapsed {
+                ul {
+                    class: "board-cards",
+                    if ideas.is_empty() {
+                        li { class: "board-empty", "nothing here" }
+                    }
+                    for (index, idea) in ideas.into_iter().enumerate() {
+                        BoardCard {
+                            key: "{idea.id.clone().unwrap_or_default()}",
+                            idea,
+                            slot: (status, index),
+                            read_only,
+                            cards,
+                            on_move,
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// A card: drag its handle to another column, or pick the column from its menu with the keyboard
+#[component]
+fn BoardCard(
+    idea: IdeaSummary,
+    slot: CardSlot,
+    read_only: bool,
+    cards: DragList<CardSlot>,
+    on_move: EventHandler<(String, IdeaStatus)>,
+) -> Element {
+    let mut cards = cards;
+    let id = idea.id.clone().unwrap_or_default();
+    let to = if idea.slug.is_empty() {
+        Route::IdeaDevelopment { id: id.clone() }
+    } else {
+        Route::IdeaBySlug { slug: idea.slug.clone() }
+    };
+
+    rsx! {
+        li {
+            class: "board-card",
+            class: if cards.is_dragging(slot) { "dragging" },
+            class: if cards.is_over(slot) { "drop-target" },
+            onpointerenter: move |_| cards.enter(slot),
+
+            if !read_only {
+                button {
+                    r#type: "button",
+                    class: "drag-handle",
+                    title: "drag to another column",
+                    aria_label: "move {idea.title}",
+                    onpointerdown: move |evt| {
+                        evt.prevent_default();
+                        cards.start(slot);
+                    },
+                    "⋮⋮"
+                }
+            }
+            Link { to, class: "board-card-title", "{idea.title}" }
+            if !read_only {
+                select {
+                    class: "board-card-status",
+                    aria_label: "status of {idea.title}",
+                    value: "{idea.status}",
+                    onchange: move |evt| {
+                        if let Some(status) = IdeaStatus::parse(&evt.value()) {
+                            on_move.call((id.clone(), status));
+                        }
+                    },
+                    for choice in IdeaStatus::ALL {
+                        option { value: "{choice}", selected: choice == idea.status, "{choice}" }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,41 @@
+use crate::error::error_message;
+use crate::hooks::{use_idea_list_revision, use_settings, use_trash_revision};
+use crate::server_functions::select_board_server;
+use crate::utils::ListQuery;
+use crate::views::Home;
+use dioxus::prelude::*;
+
+/// The idea list of the board with `slug`, for links like `/b/work` that open a board directly.
+///
+/// The board becomes the one worked on before the list loads, so the list, the search and the navbar counts all
+/// come from it. A board that doesn't exist or belongs to someone else shows an error instead.
+#[component]
+pub fn BoardIdeas(slug: String, query: ListQuery) -> Element {
+    let mut settings = use_settings();
+    let mut idea_list_revision = use_idea_list_revision();
+    let mut trash_revision = use_trash_revision();
+
+    let board = use_resource(use_reactive!(|(slug,)| async move { select_board_server(slug).await }));
+
+    // The navbar counted the previous board
+    use_effect(move || {
+        if let Some(Ok(board)) = &*board.read() {
+            settings.set_active_board(board.slug.clone());
+            idea_list_revision.bump();
+            trash_revision.bump();
+        }
+    });
+
+    let board = board.read();
+    match &*board {
+        Some(Ok(board)) => rsx! {
+            Home { key: "{board.slug}", query }
+        },
+        Some(Err(e)) => rsx! {
+            p { class: "error", "Failed to open board: {error_message(e)}" }
+        },
+        None => rsx! {
+            p { class: "loading", "Loading board..." }
+        },
+    }
+}
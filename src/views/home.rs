@@ -1,47 +1,102 @@
-use crate::components::{IdeaForm, IdeaList};
+use crate::components::{
+    IdeaForm, IdeaList, Onboarding, QuickCapture, RecentIdeasStrip, StatusStrip, StorageBanner, UpcomingIdeas,
+};
+use crate::db::{Idea, IdeaSummary};
+use crate::hooks::{use_app_config, use_focus_handle, use_idea_list_revision, use_new_idea_request};
+use crate::server_functions::get_storage_usage_server;
+use crate::utils::ListQuery;
 use dioxus::prelude::*;
 
-/// The Home page component that will be rendered when the current route is `[Route::Home]`
+/// The Home page component that will be rendered when the current route is `[Route::Home]`. `query` is the idea
+/// list's view state, taken from the URL.
 #[component]
-pub fn Home() -> Element {
-    // Signal to trigger list refresh when a new idea is submitted
-    let mut refresh_trigger = use_signal(|| 0u32);
+pub fn Home(query: ListQuery) -> Element {
+    // Shared with the navbar, so its counts follow submissions and deletions made here
+    let mut idea_list_revision = use_idea_list_revision();
     // Signal to control form visibility
     let mut show_form = use_signal(|| false);
+    // The "add idea" button is removed while the form is open, so focus goes back to it once it is mounted again
+    let mut add_button = use_focus_handle();
+    let mut return_focus = use_signal(|| false);
+    let mut close_form = move || {
+        show_form.set(false);
+        return_focus.set(true);
+    };
+
+    // The last idea added with the quick capture bar, which the list shows first right away
+    let mut captured = use_signal(|| None::<IdeaSummary>);
+
+    // Measured again whenever the list changes, so the banner follows additions and deletions
+    let storage = use_resource(move || async move {
+        let _ = idea_list_revision.get();
+        get_storage_usage_server().await.unwrap_or_default()
+    });
+    let report = storage().unwrap_or_default();
+
+    // A read-only deployment takes no new ideas, and neither does a full one, so there is nothing to add them with
+    let closed = use_app_config().read_only() || report.is_full();
+
+    // Open the form when the command palette asks for it
+    let mut new_idea = use_new_idea_request();
+    use_effect(move || {
+        if new_idea.take() {
+            show_form.set(true);
+        }
+    });
 
     rsx! {
         div {
             id: "ideas-section",
 
+            // Only there while the list is empty
+            Onboarding {}
+            StatusStrip { query: query.clone() }
+            RecentIdeasStrip {}
+            UpcomingIdeas {}
+            StorageBanner { report }
+
             // Show "Add Idea" button when form is hidden
-            if !show_form() {
+            if !show_form() && !closed {
                 button {
                     r#type: "button",
                     class: "add-idea-btn",
+                    onmounted: move |event| {
+                        add_button.mounted(event);
+                        if return_focus() {
+                            return_focus.set(false);
+                            add_button.focus();
+                        }
+                    },
                     onclick: move |_| show_form.set(true),
                     "add idea"
                 }
             }
 
             // Show form when toggled on
-            if show_form() {
+            if show_form() && !closed {
                 IdeaForm {
                     on_submit_success: move |_| {
-                        *refresh_trigger.write() += 1;
-                        show_form.set(false);
+                        idea_list_revision.bump();
+                        close_form();
                     },
-                    on_cancel: move |_| {
-                        show_form.set(false);
-                    }
+                    on_cancel: move |_| close_form(),
                 }
             }
 
-            IdeaList {
-                refresh_trigger: refresh_trigger,
-                on_delete_success: move |_| {
-                    *refresh_trigger.write() += 1;
+            if !closed {
+                QuickCapture {
+                    on_captured: move |idea: Idea| {
+                        captured.set(Some(idea.into()));
+                        idea_list_revision.bump();
+                    },
                 }
             }
+
+            IdeaList {
+                query,
+                captured: captured(),
+                on_delete_success: move |_| idea_list_revision.bump(),
+            }
         }
     }
 }
@@ -0,0 +1,43 @@
+use crate::server_functions::get_idea_by_slug_server;
+use crate::views::IdeaDevelopment;
+use crate::Route;
+use dioxus::prelude::*;
+
+/// Shareable idea page addressed by slug instead of the raw record id.
+///
+/// Old slugs (from before a rename) resolve to the same idea and the URL is replaced with the current slug.
+#[component]
+pub fn IdeaBySlug(slug: String) -> Element {
+    let requested = slug.clone();
+    let idea = use_resource(use_reactive!(|(slug,)| async move {
+        get_idea_by_slug_server(slug).await
+    }));
+
+    // Redirect old slugs to the current one without adding a history entry
+    use_effect(move || {
+        if let Some(Ok(idea)) = idea.read().as_ref() {
+            if !idea.slug.is_empty() && idea.slug != requested {
+                navigator().replace(Route::IdeaBySlug {
+                    slug: idea.slug.clone(),
+                });
+            }
+        }
+    });
+
+    match idea() {
+        Some(Ok(idea)) => match idea.id {
+            Some(id) => rsx! {
+                IdeaDevelopment { key: "{id}", id }
+            },
+            None => rsx! {
+                p { class: "error", "Idea has no id" }
+            },
+        },
+        Some(Err(e)) => rsx! {
+            p { class: "error", "Failed to load idea: {e}" }
+        },
+        None => rsx! {
+            p { class: "loading", "Loading idea..." }
+        },
+    }
+}
@@ -1,66 +1,384 @@
-use crate::server_functions::{get_idea_by_id_server, update_idea_server};
+use crate::components::{
+    Attachments, CopyIdeaButton, GuardedLink, IconPicker, IdeaHistory, IdeaRevisions, LinkedText, MergePicker,
+    NoteSections, VisibilityToggle,
+};
+use crate::error::{error_message, IdeaError, LoadFailure};
+use crate::changes::new_client_id;
+use crate::client_errors::log_error;
+use crate::db::{Idea, IdeaEdit, NoteSection, Visibility};
+use crate::form_config::display_value;
+use crate::hooks::{
+    use_app_config, use_debounce, use_idea_changes, use_idle_state, use_reconnect, use_retrying_resource,
+    use_settings, use_stylesheet, use_tag_colors, use_undo_shortcuts, use_unsaved_registration, use_viewport,
+};
+use crate::recent::RecentIdeas;
+use crate::settings::AutoSave;
+use crate::server_functions::{
+    find_archived_by_id_server, get_idea_by_id_server, restore_idea_server, update_idea_server,
+};
+use crate::storage::default_storage;
+use crate::tags::TagColors;
+use crate::Route;
+use crate::utils::{
+    due_label, due_status, format_due_date, local_now, parse_due_date, text_stats_label, textarea_rows,
+    unsaved_fields, ListQuery, RetryPolicy, SaveQueue, UndoStack,
+};
 use dioxus::prelude::*;
+use dioxus::CapturedError;
+use std::collections::HashMap;
 
 const IDEA_DEV_CSS: Asset = asset!("/assets/styling/idea_development.css");
 
+/// Height of the notes textarea before it starts growing
+const MIN_NOTES_ROWS: usize = 12;
+/// Characters that fit on one line of the notes textarea at its full width
+const NOTES_COLUMNS: usize = 80;
+
 #[component]
-pub fn IdeaDevelopment(id: String) -> Element {
-    // Load idea data
-    let idea_data = use_resource(move || {
-        let id = id.clone();
-        async move { get_idea_by_id_server(id).await }
+pub fn IdeaDevelopment(id: ReadSignal<String>) -> Element {
+    use_stylesheet(IDEA_DEV_CSS);
+
+    // Load idea data, retrying transient failures. Reading `id` inside makes it reload when navigating between ideas.
+    // An idea in the trash loads like any other, but is shown as deleted here.
+    let mut idea_data = use_retrying_resource(RetryPolicy::default(), move || async move {
+        let id = id();
+        if id.trim().is_empty() {
+            return Err(CapturedError::from_display(IdeaError::InvalidId(id).encode()));
+        }
+        match get_idea_by_id_server(id.clone()).await? {
+            idea if idea.deleted_at.is_some() => Err(CapturedError::from_display(IdeaError::NotFound(id).encode())),
+            idea => Ok(idea),
+        }
     });
 
+    // Identifies this tab's saves, so only edits made elsewhere raise the banner
+    let client_id = use_signal(new_client_id);
+    let idle = use_idle_state();
+    let tag_colors = use_tag_colors();
+    // A read-only deployment shows the idea without any editors
+    let read_only = use_app_config().read_only();
+    // Phones get finger-sized statement controls
+    let touch = use_viewport().is_narrow();
+    // Bumped every time the idea loads, so the notes textarea is rebuilt with the fresh text
+    let mut loads = use_signal(|| 0u32);
+
     // Local state for editing
     let mut what_must_be_true = use_signal(|| Vec::<String>::new());
     let mut development_notes = use_signal(|| String::new());
+    // The notes as sections, which save on their own through the sections editor
+    let mut note_sections = use_signal(Vec::<NoteSection>::new);
+    // Saved on its own by the icon picker
+    let mut icon = use_signal(|| Option::<String>::None);
+    let mut visibility = use_signal(Visibility::default);
+    let mut due_date = use_signal(|| Option::<chrono::NaiveDate>::None);
     let mut new_statement = use_signal(|| String::new());
-    let mut is_saving = use_signal(|| false);
+    // Edits waiting to be saved; a failed save keeps the latest one until the retry button or the reconnect check
+    let mut save_queue = use_signal(SaveQueue::<Idea>::default);
+    // The idea as last loaded from or saved to the server, which the local edits are compared against
+    let mut saved = use_signal(|| Option::<Idea>::None);
+    let mut settings = use_settings();
+    let mut debounce = use_debounce();
+    // Statements and notes as of each save in this visit, for Cmd+Z; starts over whenever the idea loads
+    let mut undo = use_signal(UndoStack::<(Vec<String>, String)>::default);
 
-    // Initialize local state when data loads
+    // The idea with the local edits applied
+    let edited = move || {
+        saved.read().clone().map(|idea| Idea {
+            what_must_be_true: what_must_be_true(),
+            development_notes: development_notes(),
+            due_date: due_date(),
+            ..idea
+        })
+    };
+    let changed_fields = use_memo(move || match (saved.read().as_ref(), edited()) {
+        (Some(saved), Some(edited)) => unsaved_fields(saved, &edited),
+        _ => Vec::new(),
+    });
+    let unsaved = use_memo(move || save_queue.read().has_unsaved() || !changed_fields.read().is_empty());
+    let save_failed = use_memo(move || save_queue.read().error().is_some());
+    // Links and the palette ask before leaving these edits behind
+    use_unsaved_registration(unsaved);
+    // Stop listening for edits made elsewhere while the tab is idle, but only once the local edits are saved
+    let paused = use_memo(move || idle.is_idle() && !unsaved());
+    let mut changed_elsewhere = use_idea_changes(id, client_id(), paused);
+
+    // Initialize local state when data loads, and remember the visit for the "recently viewed" strip
     use_effect(move || {
         if let Some(Ok(idea)) = idea_data.read().as_ref() {
+            saved.set(Some(idea.clone()));
             what_must_be_true.set(idea.what_must_be_true.clone());
             development_notes.set(idea.development_notes.clone());
+            note_sections.set(idea.sections.clone());
+            icon.set(idea.icon.clone());
+            visibility.set(idea.visibility);
+            due_date.set(idea.due_date);
+            *loads.write() += 1;
+            undo.write().reset((idea.what_must_be_true.clone(), idea.development_notes.clone()));
+
+            if let Some(id) = &idea.id {
+                let storage = default_storage();
+                let mut recent = RecentIdeas::load(&storage);
+                recent.record(id, chrono::Utc::now());
+                recent.save(&storage);
+            }
         }
     });
 
-    // Auto-save function
-    let auto_save = move || {
-        if let Some(Ok(idea)) = idea_data.read().as_ref() {
-            let id = idea.id.clone().unwrap_or_default();
-            let title = idea.title.clone();
-            let description = idea.description.clone();
-            let tags = idea.tags.clone();
-            let wmbt = what_must_be_true();
-            let notes = development_notes();
-
-            spawn(async move {
-                is_saving.set(true);
-                let _ = update_idea_server(id, title, description, tags, wmbt, notes).await;
-                is_saving.set(false);
-            });
+    // Send `idea`, then whatever was edited while it was in flight, until the queue is empty or a save fails
+    let send = move |idea: Idea| {
+        spawn(async move {
+            let mut next = Some(idea);
+            while let Some(idea) = next.take() {
+                let sent = idea.clone();
+                let id = idea.id.unwrap_or_default();
+                let edit = IdeaEdit {
+                    title: idea.title,
+                    description: idea.description,
+                    tags: idea.tags,
+                    what_must_be_true: idea.what_must_be_true,
+                    development_notes: idea.development_notes,
+                    due_date: idea.due_date,
+                };
+                let result = update_idea_server(id, edit, client_id()).await;
+                match result {
+                    Ok(_) => {
+                        saved.set(Some(sent));
+                        next = save_queue.write().saved();
+                    }
+                    Err(e) => {
+                        log_error("save idea", &e);
+                        save_queue.write().failed(error_message(&e));
+                    }
+                }
+            }
+        });
+    };
+
+    let mut retry_save = move || {
+        if let Some(idea) = save_queue.write().retry() {
+            send(idea);
         }
     };
+    use_reconnect(save_failed, use_callback(move |_| retry_save()));
 
-    rsx! {
-        document::Link { rel: "stylesheet", href: IDEA_DEV_CSS }
+    // Queue the local edits; they go out unless a save is in flight or the last one failed
+    let mut queue_edits = move || {
+        undo.write().record((what_must_be_true(), development_notes()));
+        if let Some(edited) = edited() {
+            if let Some(idea) = save_queue.write().edit(edited) {
+                send(idea);
+            }
+        }
+    };
+
+    // The "save" button, shown while auto-save is off: also tries again after a failed save
+    let mut save_now = move || {
+        undo.write().record((what_must_be_true(), development_notes()));
+        if let Some(edited) = edited() {
+            let next = {
+                let mut queue = save_queue.write();
+                queue.edit(edited).or_else(|| queue.retry())
+            };
+            if let Some(idea) = next {
+                send(idea);
+            }
+        }
+    };
+
+    // Called after every edit: saves right away, after a pause in editing, or not at all, as picked in the settings
+    let mut auto_save = move || match settings.auto_save().delay() {
+        None => {}
+        Some(delay) if delay.is_zero() => queue_edits(),
+        Some(delay) => debounce.call(delay, queue_edits),
+    };
+
+    // Show a step of the undo history and save it. Edits still waiting for the debounce count as a step first, so
+    // undo goes back to the last save rather than past it.
+    let mut step = move |redo: bool| {
+        debounce.cancel();
+        let stepped = {
+            let mut undo = undo.write();
+            undo.record((what_must_be_true(), development_notes()));
+            if redo {
+                undo.redo()
+            } else {
+                undo.undo()
+            }
+        };
+        if let Some((statements, notes)) = stepped {
+            what_must_be_true.set(statements);
+            development_notes.set(notes);
+            // The notes textarea only reads its text when built
+            *loads.write() += 1;
+            // Recording the state just stepped to changes nothing, so the history survives the save
+            queue_edits();
+        }
+    };
+    use_undo_shortcuts(
+        use_callback(move |_| {
+            if !read_only {
+                step(false)
+            }
+        }),
+        use_callback(move |_| {
+            if !read_only {
+                step(true)
+            }
+        }),
+    );
+
+    // Going idle sends pending edits now instead of after the debounce, so the subscription can pause
+    use_effect(move || {
+        if idle.is_idle() && settings.auto_save().delay().is_some() && !changed_fields.read().is_empty() {
+            debounce.cancel();
+            queue_edits();
+        }
+    });
+    // Edits made elsewhere while idle went unnoticed, so waking up reloads the idea, unless that would drop local
+    // edits; the banner offers the reload then
+    use_effect(move || {
+        if idle.wakes() == 0 {
+            return;
+        }
+        if *unsaved.peek() {
+            changed_elsewhere.set(true);
+        } else {
+            idea_data.restart();
+        }
+    });
+
+    let notes_rows = textarea_rows(&development_notes.read(), MIN_NOTES_ROWS, NOTES_COLUMNS);
+    let notes_stats = text_stats_label(&development_notes.read());
+    let save_title = if unsaved() {
+        let fields: Vec<&str> = changed_fields.read().iter().map(|field| field.as_str()).collect();
+        format!("unsaved: {}", fields.join(", "))
+    } else {
+        "nothing to save".to_string()
+    };
 
+    rsx! {
         div {
             class: "idea-development",
 
+            if let Some(message) = save_queue.read().error().map(str::to_string) {
+                div {
+                    class: "unsaved-banner",
+                    role: "alert",
+                    span { "Changes not saved: {message}" }
+                    button {
+                        r#type: "button",
+                        onclick: move |_| retry_save(),
+                        "retry"
+                    }
+                }
+            }
+
+            if changed_elsewhere() {
+                div {
+                    class: "changed-banner",
+                    span { "This idea was updated elsewhere" }
+                    button {
+                        r#type: "button",
+                        onclick: move |_| {
+                            changed_elsewhere.set(false);
+                            idea_data.restart();
+                        },
+                        "reload"
+                    }
+                }
+            }
+
             match idea_data() {
+                Some(Ok(idea)) if read_only => rsx! {
+                    IdeaDetail { idea, tag_colors: tag_colors() }
+                },
                 Some(Ok(idea)) => rsx! {
                     // Idea header (read-only)
                     div {
                         class: "idea-header",
+                        if idea.pinned {
+                            span { class: "pinned-badge", "pinned" }
+                        }
+                        if let Some(id) = idea.id.clone() {
+                            GuardedLink { class: "report-link", to: Route::IdeaReport { id }, "print" }
+                        }
+                        // Copies what is on screen, including edits that are still being saved
+                        if let Some(idea_id) = idea.id.clone() {
+                            CopyIdeaButton {
+                                idea_id,
+                                title: idea.title.clone(),
+                                idea: Idea {
+                                    what_must_be_true: what_must_be_true(),
+                                    development_notes: development_notes(),
+                                    sections: note_sections(),
+                                    due_date: due_date(),
+                                    ..idea.clone()
+                                },
+                                class: "report-link",
+                            }
+                        }
+                        if let Some(idea_id) = idea.id.clone() {
+                            VisibilityToggle {
+                                idea_id,
+                                visibility: visibility(),
+                                class: "report-link",
+                                on_changed: move |stored| visibility.set(stored),
+                            }
+                        }
+                        if let Some(idea_id) = idea.id.clone() {
+                            IconPicker {
+                                idea_id,
+                                icon: icon(),
+                                client_id: client_id(),
+                                on_changed: move |stored| icon.set(stored),
+                            }
+                        }
                         h1 { "{idea.title}" }
-                        p { class: "description", "{idea.description}" }
+                        if let Some(author) = &idea.author {
+                            p { class: "idea-author", "added by {author}" }
+                        }
+                        CustomFieldValues { values: idea.custom_fields.clone() }
+                        p {
+                            class: "description",
+                            LinkedText { text: idea.description.clone() }
+                        }
                         if !idea.tags.is_empty() {
                             div {
                                 class: "tags",
                                 for tag in idea.tags {
-                                    span { class: "tag", "{tag}" }
+                                    span { class: "tag", "data-color": tag_colors().color_for(&tag), "{tag}" }
+                                }
+                            }
+                        }
+
+                        // Time box. Date inputs always use ISO dates, whatever the user's locale.
+                        div {
+                            class: "due-row",
+                            label { r#for: "due-date", "due" }
+                            input {
+                                id: "due-date",
+                                r#type: "date",
+                                value: due_date().map(format_due_date).unwrap_or_default(),
+                                onchange: move |e| {
+                                    due_date.set(parse_due_date(&e.value()));
+                                    auto_save();
+                                }
+                            }
+                            if let Some(due) = due_date() {
+                                span {
+                                    class: "due-chip",
+                                    "data-due": due_status(due, local_now()).as_str(),
+                                    "{due_label(due, local_now())}"
+                                }
+                                button {
+                                    r#type: "button",
+                                    class: "clear-due",
+                                    aria_label: "remove due date",
+                                    onclick: move |_| {
+                                        due_date.set(None);
+                                        auto_save();
+                                    },
+                                    "×"
                                 }
                             }
                         }
@@ -69,6 +387,7 @@ pub fn IdeaDevelopment(id: String) -> Element {
                     // What must be true section
                     div {
                         class: "wmbt-section",
+                        class: if touch { "touch" },
                         h2 { "what must be true?" }
 
                         // List of statements
@@ -94,6 +413,7 @@ pub fn IdeaDevelopment(id: String) -> Element {
                                         button {
                                             r#type: "button",
                                             class: "delete-wmbt",
+                                            aria_label: "remove this statement",
                                             onclick: move |_| {
                                                 let mut list = what_must_be_true();
                                                 list.remove(idx);
@@ -118,6 +438,7 @@ pub fn IdeaDevelopment(id: String) -> Element {
                             }
                             button {
                                 r#type: "button",
+                                aria_label: "add the statement",
                                 onclick: move |_| {
                                     if !new_statement().is_empty() {
                                         let mut list = what_must_be_true();
@@ -136,23 +457,132 @@ pub fn IdeaDevelopment(id: String) -> Element {
                     div {
                         class: "notes-section",
                         h2 { "development notes" }
-                        textarea {
-                            value: "{development_notes}",
-                            placeholder: "Write your detailed development notes here...",
-                            oninput: move |e| {
-                                development_notes.set(e.value());
-                                auto_save();
+                        // Only the initial value is set: writing `value` back on every keystroke makes some
+                        // browsers move the cursor to the end. The key rebuilds the textarea, and with it the
+                        // initial value, whenever the idea (re)loads.
+                        for key in [loads()] {
+                            textarea {
+                                key: "{key}",
+                                initial_value: "{development_notes}",
+                                rows: "{notes_rows}",
+                                placeholder: "Write your detailed development notes here...",
+                                oninput: move |e| {
+                                    development_notes.set(e.value());
+                                    auto_save();
+                                }
+                            }
+                        }
+                        p { class: "notes-stats", "{notes_stats}" }
+                        if let Some(idea_id) = idea.id.clone() {
+                            for key in [loads()] {
+                                NoteSections {
+                                    key: "{key}",
+                                    idea_id: idea_id.clone(),
+                                    client_id: client_id(),
+                                    notes: development_notes(),
+                                    sections: note_sections(),
+                                    on_saved: move |idea: Idea| {
+                                        // Converting rewrote the plain notes, so the textarea is rebuilt with them
+                                        let converted = saved
+                                            .peek()
+                                            .as_ref()
+                                            .is_none_or(|saved| saved.development_notes != idea.development_notes);
+                                        note_sections.set(idea.sections.clone());
+                                        if converted {
+                                            development_notes.set(idea.development_notes.clone());
+                                            *loads.write() += 1;
+                                        }
+                                        saved.set(Some(idea));
+                                    },
+                                }
+                            }
+                        }
+                    }
+
+                    // Uploaded sketches, screenshots, ...
+                    if let Some(idea_id) = idea.id.clone() {
+                        Attachments { idea_id }
+                    }
+
+                    // Earlier versions of the notes and statements; a restore saves on the server, so reload
+                    if let Some(idea_id) = idea.id.clone() {
+                        IdeaRevisions {
+                            idea_id,
+                            client_id: client_id(),
+                            current_notes: development_notes(),
+                            on_restored: move |_| idea_data.restart(),
+                        }
+                    }
+
+                    // Everything that happened to the idea, collapsed until asked for
+                    if let Some(idea_id) = idea.id.clone() {
+                        IdeaHistory { idea_id }
+                    }
+
+                    // Fold a duplicate into another idea; this one is deleted, so go to the one that remains
+                    if let Some(idea_id) = idea.id.clone() {
+                        MergePicker {
+                            idea_id,
+                            on_merged: move |target: String| {
+                                navigator().push(Route::IdeaDevelopment { id: target });
+                            }
+                        }
+                    }
+
+                    // When to save, and the button saving by hand while auto-save is off
+                    div {
+                        class: "save-bar",
+                        label { r#for: "auto-save", "auto-save" }
+                        select {
+                            id: "auto-save",
+                            value: settings.auto_save().as_str(),
+                            onchange: move |e| {
+                                if let Some(choice) = AutoSave::parse(&e.value()) {
+                                    settings.set_auto_save(choice);
+                                    // Drop a pending delayed save, then save whatever waits under the new setting
+                                    debounce.cancel();
+                                    auto_save();
+                                }
+                            },
+                            for choice in AutoSave::ALL {
+                                option {
+                                    value: choice.as_str(),
+                                    selected: choice == settings.auto_save(),
+                                    "{choice.label()}"
+                                }
+                            }
+                        }
+                        if settings.auto_save() == AutoSave::Off {
+                            button {
+                                r#type: "button",
+                                class: "save-btn",
+                                class: if save_failed() { "failed" },
+                                disabled: !unsaved() || save_queue.read().is_saving(),
+                                title: "{save_title}",
+                                onclick: move |_| save_now(),
+                                if save_queue.read().is_saving() {
+                                    "saving..."
+                                } else if save_failed() {
+                                    "retry save"
+                                } else {
+                                    "save"
+                                }
                             }
                         }
                     }
 
                     // Saving indicator
-                    if is_saving() {
+                    if save_queue.read().is_saving() {
                         p { class: "saving-indicator", "Saving..." }
                     }
                 },
                 Some(Err(e)) => rsx! {
-                    p { class: "error", "Failed to load idea: {e}" }
+                    IdeaLoadFailed {
+                        id: id(),
+                        failure: LoadFailure::of(&e),
+                        message: error_message(&e),
+                        on_retry: move |_| idea_data.restart(),
+                    }
                 },
                 None => rsx! {
                     p { class: "loading", "Loading idea..." }
@@ -161,3 +591,193 @@ pub fn IdeaDevelopment(id: String) -> Element {
         }
     }
 }
+
+/// What the page shows instead of an idea it couldn't load, with a way back to the list in every case. A deleted
+/// idea that is still in the trash can be restored from here; `on_retry` loads the idea again.
+#[component]
+fn IdeaLoadFailed(id: String, failure: LoadFailure, message: String, on_retry: EventHandler<()>) -> Element {
+    let read_only = use_app_config().read_only();
+    let mut is_restoring = use_signal(|| false);
+    let mut restore_error = use_signal(|| Option::<String>::None);
+    let archived = use_resource({
+        let id = id.clone();
+        move || {
+            let id = id.clone();
+            async move {
+                if failure != LoadFailure::Deleted {
+                    return None;
+                }
+                find_archived_by_id_server(id).await.ok().flatten()
+            }
+        }
+    });
+
+    let (title, detail) = match failure {
+        LoadFailure::Malformed => ("This link is malformed", "No idea can live at this address. Check the link."),
+        LoadFailure::Deleted => ("This idea was deleted", "It is no longer in the list."),
+        LoadFailure::NoAccess => ("This idea isn't yours to see", "Sign in with the account it belongs to."),
+        LoadFailure::Transient => ("The idea couldn't be loaded", "Something went wrong. Trying again may help."),
+    };
+
+    rsx! {
+        div {
+            class: "load-failed",
+            role: "alert",
+            h1 { "{title}" }
+            p { "{detail}" }
+            if failure == LoadFailure::Transient {
+                p { class: "load-failed-detail", "{message}" }
+            }
+            div {
+                class: "load-failed-actions",
+                if let Some(Some(trashed)) = archived() {
+                    if !read_only {
+                        button {
+                            r#type: "button",
+                            class: "retry-btn restore-btn",
+                            disabled: is_restoring(),
+                            onclick: move |_| {
+                                let id = id.clone();
+                                async move {
+                                    is_restoring.set(true);
+                                    match restore_idea_server(id).await {
+                                        Ok(_) => on_retry.call(()),
+                                        Err(e) => {
+                                            log_error("restore idea", &e);
+                                            restore_error.set(Some(error_message(&e)));
+                                        }
+                                    }
+                                    is_restoring.set(false);
+                                }
+                            },
+                            "restore \"{trashed.title}\" from trash"
+                        }
+                    }
+                }
+                if failure == LoadFailure::Transient {
+                    button {
+                        r#type: "button",
+                        class: "retry-btn",
+                        onclick: move |_| on_retry.call(()),
+                        "retry"
+                    }
+                }
+                if failure == LoadFailure::NoAccess {
+                    Link { to: Route::Login {}, "sign in" }
+                }
+                Link { to: Route::Home { query: ListQuery::default() }, "back to all ideas" }
+            }
+            if let Some(message) = restore_error() {
+                p { class: "load-failed-detail", "{message}" }
+            }
+        }
+    }
+}
+
+/// The idea as a page to read, for deployments that take no edits: statements as a list and the notes as text
+#[component]
+fn IdeaDetail(idea: Idea, tag_colors: TagColors) -> Element {
+    rsx! {
+        div {
+            class: "idea-header",
+            if let Some(id) = idea.id.clone() {
+                Link { class: "report-link", to: Route::IdeaReport { id }, "print" }
+            }
+            if let Some(idea_id) = idea.id.clone() {
+                CopyIdeaButton { idea_id, title: idea.title.clone(), idea: idea.clone(), class: "report-link" }
+            }
+            h1 {
+                if let Some(icon) = &idea.icon {
+                    span { class: "idea-icon", aria_hidden: "true", "{icon} " }
+                }
+                "{idea.title}"
+            }
+            if let Some(author) = &idea.author {
+                p { class: "idea-author", "added by {author}" }
+            }
+            CustomFieldValues { values: idea.custom_fields.clone() }
+            p {
+                class: "description",
+                LinkedText { text: idea.description.clone() }
+            }
+            if !idea.tags.is_empty() {
+                div {
+                    class: "tags",
+                    for tag in idea.tags.clone() {
+                        span { class: "tag", "data-color": tag_colors.color_for(&tag), "{tag}" }
+                    }
+                }
+            }
+            if let Some(due) = idea.due_date {
+                p {
+                    class: "due-chip",
+                    "data-due": due_status(due, local_now()).as_str(),
+                    "{due_label(due, local_now())}"
+                }
+            }
+        }
+
+        div {
+            class: "wmbt-section",
+            h2 { "what must be true?" }
+            if idea.what_must_be_true.is_empty() {
+                p { class: "empty-state", "Nothing yet." }
+            } else {
+                ul {
+                    class: "wmbt-list read-only",
+                    for (idx, statement) in idea.what_must_be_true.iter().enumerate() {
+                        li { class: "wmbt-item", key: "{idx}", "{statement}" }
+                    }
+                }
+            }
+        }
+
+        div {
+            class: "notes-section",
+            h2 { "development notes" }
+            if idea.development_notes.is_empty() && idea.sections.is_empty() {
+                p { class: "empty-state", "No notes yet." }
+            } else if !idea.development_notes.is_empty() {
+                p { class: "notes-text", "{idea.development_notes}" }
+            }
+            for (idx, section) in idea.sections.iter().enumerate() {
+                div {
+                    key: "{idx}",
+                    class: "note-section",
+                    if !section.heading.is_empty() {
+                        h3 { "{section.heading}" }
+                    }
+                    p { class: "notes-text", "{section.body}" }
+                }
+            }
+        }
+
+        if let Some(idea_id) = idea.id.clone() {
+            IdeaHistory { idea_id }
+        }
+    }
+}
+
+/// The values of the deployment's own form fields, by name. Fields the form no longer has still show.
+#[component]
+fn CustomFieldValues(values: HashMap<String, serde_json::Value>) -> Element {
+    let mut values: Vec<(String, String)> =
+        values.iter().map(|(name, value)| (name.clone(), display_value(value))).collect();
+    values.retain(|(_, value)| !value.is_empty());
+    values.sort();
+
+    rsx! {
+        if !values.is_empty() {
+            dl {
+                class: "custom-fields",
+                for (name, value) in values {
+                    div {
+                        key: "{name}",
+                        dt { "{name}" }
+                        dd { "{value}" }
+                    }
+                }
+            }
+        }
+    }
+}
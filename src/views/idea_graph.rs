@@ -0,0 +1,203 @@
+use crate::error::error_message;
+use crate::graph::{self, GRAPH_HEIGHT, GRAPH_WIDTH};
+use crate::hooks::use_stylesheet;
+use crate::server_functions::get_idea_graph_server;
+use crate::Route;
+use dioxus::prelude::*;
+
+const IDEA_GRAPH_CSS: Asset = asset!("/assets/styling/idea_graph.css");
+
+/// Zoom limits, as multiples of the whole layout area
+const MIN_ZOOM: f64 = 0.5;
+const MAX_ZOOM: f64 = 4.0;
+/// Zoom change per wheel notch
+const ZOOM_STEP: f64 = 1.2;
+/// Pointer movement, in pixels, after which a press counts as a drag rather than a click
+const DRAG_THRESHOLD: f64 = 3.0;
+
+/// Part of the layout area on screen: its top-left corner and the zoom
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Viewport {
+    x: f64,
+    y: f64,
+    zoom: f64,
+}
+
+impl Default for Viewport {
+    fn default() -> Self {
+        Self { x: 0.0, y: 0.0, zoom: 1.0 }
+    }
+}
+
+impl Viewport {
+    /// The SVG `viewBox` showing this part
+    fn view_box(&self) -> String {
+        format!("{} {} {} {}", self.x, self.y, GRAPH_WIDTH / self.zoom, GRAPH_HEIGHT / self.zoom)
+    }
+
+    /// Zoom by `factor`, keeping the centre in place
+    fn zoomed(self, factor: f64) -> Self {
+        let zoom = (self.zoom * factor).clamp(MIN_ZOOM, MAX_ZOOM);
+        let centre_x = self.x + GRAPH_WIDTH / self.zoom / 2.0;
+        let centre_y = self.y + GRAPH_HEIGHT / self.zoom / 2.0;
+        Self {
+            x: centre_x - GRAPH_WIDTH / zoom / 2.0,
+            y: centre_y - GRAPH_HEIGHT / zoom / 2.0,
+            zoom,
+        }
+    }
+}
+
+/// The signed-in user's ideas as a graph of their `relates_to` links
+#[component]
+pub fn IdeaGraph() -> Element {
+    use_stylesheet(IDEA_GRAPH_CSS);
+
+    let graph = use_resource(get_idea_graph_server);
+
+    rsx! {
+        div {
+            id: "idea-graph",
+            h2 { "graph" }
+
+            match &*graph.read() {
+                Some(Ok(graph)) if graph.nodes.is_empty() => rsx! {
+                    p { class: "empty-state", "No ideas to draw yet." }
+                },
+                Some(Ok(graph)) => rsx! {
+                    if graph.edges.is_empty() {
+                        p { class: "graph-hint", "None of these ideas are linked yet." }
+                    }
+                    GraphCanvas { graph: graph.clone() }
+                },
+                Some(Err(e)) => rsx! {
+                    p { class: "message", "Failed to load the graph: {error_message(e)}" }
+                },
+                None => rsx! {
+                    p { class: "graph-hint", "Loading..." }
+                },
+            }
+        }
+    }
+}
+
+/// The laid out graph: drag to pan, wheel to zoom, click an idea to open it
+#[component]
+fn GraphCanvas(graph: ReadSignal<graph::IdeaGraph>) -> Element {
+    let positions = use_memo(move || graph.read().layout());
+    let mut viewport = use_signal(Viewport::default);
+    // Where the pointer was at the last move of a drag, and whether it went past a click
+    let mut drag = use_signal(|| Option::<(f64, f64)>::None);
+    let mut dragged = use_signal(|| false);
+
+    let graph = graph.read();
+    let positions = positions.read();
+    let lines: Vec<(f64, f64, f64, f64)> = graph
+        .edge_indices()
+        .into_iter()
+        .map(|(a, b)| (positions[a].x, positions[a].y, positions[b].x, positions[b].y))
+        .collect();
+
+    rsx! {
+        div {
+            class: "graph-toolbar",
+            button {
+                r#type: "button",
+                class: "graph-btn",
+                title: "zoom in",
+                onclick: move |_| viewport.set(viewport().zoomed(ZOOM_STEP)),
+                "+"
+            }
+            button {
+                r#type: "button",
+                class: "graph-btn",
+                title: "zoom out",
+                onclick: move |_| viewport.set(viewport().zoomed(1.0 / ZOOM_STEP)),
+                "−"
+            }
+            button {
+                r#type: "button",
+                class: "graph-btn",
+                onclick: move |_| viewport.set(Viewport::default()),
+                "reset"
+            }
+        }
+
+        svg {
+            class: "graph-canvas",
+            class: if drag().is_some() { "dragging" },
+            view_box: "{viewport().view_box()}",
+            role: "img",
+            "aria-label": "graph of linked ideas",
+            onpointerdown: move |event| {
+                let point = event.client_coordinates();
+                drag.set(Some((point.x, point.y)));
+                dragged.set(false);
+            },
+            onpointermove: move |event| {
+                let Some((last_x, last_y)) = drag() else {
+                    return;
+                };
+                let point = event.client_coordinates();
+                let (dx, dy) = (point.x - last_x, point.y - last_y);
+                if !dragged() && dx.abs().max(dy.abs()) < DRAG_THRESHOLD {
+                    return;
+                }
+                dragged.set(true);
+                drag.set(Some((point.x, point.y)));
+                let current = viewport();
+                viewport.set(Viewport {
+                    x: current.x - dx / current.zoom,
+                    y: current.y - dy / current.zoom,
+                    ..current
+                });
+            },
+            onpointerup: move |_| drag.set(None),
+            onpointerleave: move |_| drag.set(None),
+            onwheel: move |event| {
+                event.prevent_default();
+                let factor = if event.delta().strip_units().y < 0.0 { ZOOM_STEP } else { 1.0 / ZOOM_STEP };
+                viewport.set(viewport().zoomed(factor));
+            },
+
+            for (index, (x1, y1, x2, y2)) in lines.into_iter().enumerate() {
+                line { key: "edge-{index}", class: "graph-edge", x1, y1, x2, y2 }
+            }
+
+            for (node, point) in graph.nodes.iter().zip(positions.iter()) {
+                GraphNodeMark {
+                    key: "{node.id}",
+                    node: node.clone(),
+                    x: point.x,
+                    y: point.y,
+                    dragged,
+                }
+            }
+        }
+    }
+}
+
+/// One idea: a circle and its title, opening the idea when clicked (but not at the end of a drag)
+#[component]
+fn GraphNodeMark(node: graph::GraphNode, x: f64, y: f64, dragged: Signal<bool>) -> Element {
+    let to = if node.slug.is_empty() {
+        Route::IdeaDevelopment { id: node.id.clone() }
+    } else {
+        Route::IdeaBySlug { slug: node.slug.clone() }
+    };
+    let label_y = y - 14.0;
+
+    rsx! {
+        g {
+            class: "graph-node",
+            onclick: move |_| {
+                if !dragged() {
+                    navigator().push(to.clone());
+                }
+            },
+            title { "{node.title}" }
+            circle { cx: x, cy: y, r: 8 }
+            text { x, y: label_y, text_anchor: "middle", "{node.title}" }
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use crate::auth::User;
+use crate::error::error_message;
+use crate::hooks::use_stylesheet;
+use crate::server_functions::{login_server, register_server};
+use crate::utils::ListQuery;
+use crate::Route;
+use dioxus::prelude::*;
+
+const LOGIN_CSS: Asset = asset!("/assets/styling/login.css");
+
+/// Sign-in and registration page
+#[component]
+pub fn Login() -> Element {
+    use_stylesheet(LOGIN_CSS);
+
+    // Provided by the Navbar layout so the signed-in email updates everywhere
+    let mut current_user = use_context::<Signal<Option<User>>>();
+
+    let mut email = use_signal(|| String::new());
+    let mut password = use_signal(|| String::new());
+    let mut is_registering = use_signal(|| false);
+    let mut is_submitting = use_signal(|| false);
+    let mut error = use_signal(|| String::new());
+
+    rsx! {
+        div {
+            id: "login",
+            h2 { if is_registering() { "create account" } else { "sign in" } }
+
+            form {
+                onsubmit: move |event| async move {
+                    event.prevent_default();
+                    is_submitting.set(true);
+                    error.set(String::new());
+
+                    let result = if is_registering() {
+                        register_server(email(), password()).await
+                    } else {
+                        login_server(email(), password()).await
+                    };
+
+                    match result {
+                        Ok(user) => {
+                            current_user.set(Some(user));
+                            navigator().push(Route::Home { query: ListQuery::default() });
+                        }
+                        Err(e) => error.set(error_message(&e)),
+                    }
+
+                    is_submitting.set(false);
+                },
+
+                div {
+                    class: "form-field",
+                    label { "email" }
+                    input {
+                        r#type: "email",
+                        autocomplete: "email",
+                        value: "{email}",
+                        oninput: move |e| email.set(e.value()),
+                        required: true,
+                    }
+                }
+
+                div {
+                    class: "form-field",
+                    label { "password" }
+                    input {
+                        r#type: "password",
+                        autocomplete: if is_registering() { "new-password" } else { "current-password" },
+                        value: "{password}",
+                        oninput: move |e| password.set(e.value()),
+                        required: true,
+                    }
+                }
+
+                button {
+                    r#type: "submit",
+                    class: "submit-btn",
+                    disabled: is_submitting(),
+                    if is_registering() { "create account" } else { "sign in" }
+                }
+            }
+
+            if !error().is_empty() {
+                p { class: "message", "{error}" }
+            }
+
+            button {
+                r#type: "button",
+                class: "switch-mode",
+                onclick: move |_| {
+                    is_registering.toggle();
+                    error.set(String::new());
+                },
+                if is_registering() { "already have an account? sign in" } else { "no account yet? create one" }
+            }
+        }
+    }
+}
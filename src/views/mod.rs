@@ -19,3 +19,36 @@ pub use navbar::Navbar;
 
 mod idea_development;
 pub use idea_development::IdeaDevelopment;
+
+mod idea_by_slug;
+pub use idea_by_slug::IdeaBySlug;
+
+mod admin;
+pub use admin::Admin;
+
+mod login;
+pub use login::Login;
+
+mod trash;
+pub use trash::Trash;
+
+mod report;
+pub use report::{IdeaReport, ListReport};
+
+mod idea_graph;
+pub use idea_graph::IdeaGraph;
+
+mod board;
+pub use board::Board;
+
+mod board_ideas;
+pub use board_ideas::BoardIdeas;
+
+mod stats;
+pub use stats::Stats;
+
+mod review;
+pub use review::Review;
+
+mod not_found;
+pub use not_found::NotFound;
@@ -1,3 +1,12 @@
+use crate::auth::User;
+use crate::error::error_message;
+use crate::components::{BoardSwitcher, CommandPalette, GuardedLink, LeaveDialog, RouteError, ToastHost};
+use crate::hooks::{
+    use_idea_list_revision_provider, use_new_idea_request_provider, use_palette_shortcut, use_stylesheet, use_theme,
+    use_toasts_provider, use_trash_revision_provider, use_unsaved_changes_provider,
+};
+use crate::server_functions::{count_archived_ideas_server, count_ideas_server, current_user_server, logout_server};
+use crate::utils::ListQuery;
 use crate::Route;
 use dioxus::prelude::*;
 
@@ -6,18 +15,170 @@ const NAVBAR_CSS: Asset = asset!("/assets/styling/navbar.css");
 /// The Navbar component that will be rendered on all pages of our app
 #[component]
 pub fn Navbar() -> Element {
-    rsx! {
-        document::Link { rel: "stylesheet", href: NAVBAR_CSS }
+    use_stylesheet(NAVBAR_CSS);
+
+    // The signed-in user, shared with every route rendered inside the layout
+    let mut current_user = use_context_provider(|| Signal::new(Option::<User>::None));
+
+    // Load the session once when the app starts
+    use_resource(move || async move {
+        if let Ok(user) = current_user_server().await {
+            current_user.set(user);
+        }
+    });
+
+    let mut theme = use_theme();
+
+    // Trash size for the badge, refetched when the trash or the signed-in user changes
+    let trash_revision = use_trash_revision_provider();
+    let trash_count = use_resource(move || async move {
+        let _ = trash_revision.get();
+        let _ = current_user();
+        count_archived_ideas_server().await.unwrap_or(0)
+    });
 
+    // Idea count and most used tags, refetched whenever a page or the palette changes the ideas
+    let idea_list_revision = use_idea_list_revision_provider();
+    let idea_counts = use_resource(move || async move {
+        let _ = idea_list_revision.get();
+        let _ = current_user();
+        count_ideas_server(None).await.ok()
+    });
+    let mut tags_open = use_signal(|| false);
+
+    // Confirmations and failures raised by any page, e.g. "copied"
+    use_toasts_provider();
+
+    // Editors with unsaved changes, which every link in the layout asks about before leaving
+    use_unsaved_changes_provider();
+
+    // Command palette, opened from the button or with Cmd/Ctrl+K on any page
+    use_new_idea_request_provider();
+    let mut palette_open = use_signal(|| false);
+    use_palette_shortcut(palette_open);
+
+    rsx! {
         div {
             id: "navbar",
-            Link {
-                to: Route::Home {},
+            GuardedLink {
+                to: Route::Home { query: ListQuery::default() },
                 h1 { "ideas..." }
             }
+
+            div {
+                class: "navbar-user",
+                BoardSwitcher {}
+                if let Some(counts) = idea_counts().flatten() {
+                    div {
+                        class: "navbar-tags",
+                        button {
+                            r#type: "button",
+                            class: "navbar-link",
+                            title: "most used tags",
+                            aria_expanded: tags_open(),
+                            onclick: move |_| tags_open.toggle(),
+                            "ideas"
+                            span { class: "trash-badge", "{counts.total}" }
+                        }
+                        if tags_open() {
+                            ul {
+                                class: "navbar-tag-menu",
+                                if counts.top_tags.is_empty() {
+                                    li { class: "navbar-tag-empty", "no tags yet" }
+                                }
+                                for entry in counts.top_tags {
+                                    li {
+                                        key: "{entry.tag}",
+                                        GuardedLink {
+                                            to: Route::Home { query: ListQuery::default().with_tag_toggled(&entry.tag) },
+                                            onclick: move |_| tags_open.set(false),
+                                            "{entry.tag}"
+                                            span { class: "navbar-tag-count", "{entry.count}" }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+                button {
+                    r#type: "button",
+                    class: "navbar-link",
+                    title: "command palette (Ctrl+K)",
+                    onclick: move |_| palette_open.set(true),
+                    "commands"
+                }
+                button {
+                    r#type: "button",
+                    class: "navbar-link theme-toggle",
+                    title: "switch theme",
+                    onclick: move |_| theme.cycle(),
+                    "{theme.theme().as_str()}"
+                }
+                SettingsMenu {}
+                GuardedLink { to: Route::Board {}, class: "navbar-link", "board" }
+                GuardedLink { to: Route::IdeaGraph {}, class: "navbar-link", "graph" }
+                GuardedLink { to: Route::Stats {}, class: "navbar-link", "stats" }
+                GuardedLink { to: Route::Review {}, class: "navbar-link", "review" }
+                GuardedLink {
+                    to: Route::Trash {},
+                    class: "navbar-link",
+                    "trash"
+                    if let Some(count) = trash_count().filter(|&count| count > 0) {
+                        span { class: "trash-badge", "{count}" }
+                    }
+                }
+                match current_user() {
+                    Some(user) => rsx! {
+                        span { class: "navbar-email", "{user.email}" }
+                        button {
+                            r#type: "button",
+                            class: "navbar-link",
+                            onclick: move |_| async move {
+                                if logout_server().await.is_ok() {
+                                    current_user.set(None);
+                                    navigator().push(Route::Home { query: ListQuery::default() });
+                                }
+                            },
+                            "sign out"
+                        }
+                    },
+                    None => rsx! {
+                        GuardedLink { to: Route::Login {}, class: "navbar-link", "sign in" }
+                    },
+                }
+            }
         }
 
-        // The `Outlet` component is used to render the next component inside the layout
-        Outlet::<Route> {}
+        CommandPalette { open: palette_open }
+        ToastHost {}
+        LeaveDialog {}
+
+        // The `Outlet` component is used to render the next component inside the layout. A page that fails to render
+        // is replaced by an error panel instead of taking the navbar down with it.
+        ErrorBoundary {
+            handle_error: move |errors: ErrorContext| {
+                let message = errors.error().map(|error| error_message(&error)).unwrap_or_default();
+                rsx! {
+                    RouteError { message, on_retry: move |_| errors.clear_errors() }
+                }
+            },
+            Outlet::<Route> {}
+        }
     }
 }
+
+/// The desktop build keeps its database on this machine and has a menu to move it; the web build has nothing here
+#[cfg(feature = "desktop")]
+#[component]
+fn SettingsMenu() -> Element {
+    rsx! {
+        crate::components::DesktopSettings {}
+    }
+}
+
+#[cfg(not(feature = "desktop"))]
+#[component]
+fn SettingsMenu() -> Element {
+    rsx! {}
+}
@@ -0,0 +1,49 @@
+use crate::hooks::use_stylesheet;
+use crate::utils::{search_from_path, ListQuery};
+use crate::Route;
+use dioxus::prelude::*;
+
+const NOT_FOUND_CSS: Asset = asset!("/assets/styling/not_found.css");
+
+/// Page for any address no route matches, e.g. a mistyped link. Offers a search of the ideas, started from the
+/// words of the address, see [`search_from_path`].
+#[component]
+pub fn NotFound(segments: Vec<String>) -> Element {
+    use_stylesheet(NOT_FOUND_CSS);
+
+    let path = format!("/{}", segments.join("/"));
+    let mut search = use_signal(|| search_from_path(&segments));
+
+    rsx! {
+        div {
+            class: "not-found",
+            h1 { "Nothing lives at this address" }
+            p {
+                "There is no page at "
+                code { "{path}" }
+                ". The idea may have been renamed or deleted, or the link mistyped."
+            }
+            form {
+                class: "not-found-search",
+                role: "search",
+                onsubmit: move |event| {
+                    event.prevent_default();
+                    let query = ListQuery {
+                        search: search().trim().to_string(),
+                        ..ListQuery::default()
+                    };
+                    navigator().push(Route::Home { query });
+                },
+                input {
+                    r#type: "search",
+                    placeholder: "search ideas",
+                    aria_label: "search ideas",
+                    value: "{search}",
+                    oninput: move |event| search.set(event.value()),
+                }
+                button { r#type: "submit", "search" }
+            }
+            Link { to: Route::Home { query: ListQuery::default() }, "back to all ideas" }
+        }
+    }
+}
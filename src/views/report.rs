@@ -0,0 +1,149 @@
+use crate::db::TagMatchMode;
+use crate::error::error_message;
+use crate::hooks::use_stylesheet;
+use crate::server_functions::{get_all_ideas_server, get_idea_by_id_server};
+use crate::utils::{report_ideas, ListQuery, ReportBlock, ReportIdea};
+use crate::Route;
+use dioxus::prelude::*;
+
+const REPORT_CSS: Asset = asset!("/assets/styling/report.css");
+
+/// Printable one-pager of a single idea
+#[component]
+pub fn IdeaReport(id: ReadSignal<String>) -> Element {
+    let idea = use_resource(move || async move { get_idea_by_id_server(id()).await });
+
+    let back = Route::IdeaDevelopment { id: id() };
+    let idea = idea.read();
+    match &*idea {
+        Some(Ok(idea)) => rsx! {
+            ReportPage {
+                back,
+                ReportEntry { idea: ReportIdea::from(idea) }
+            }
+        },
+        Some(Err(e)) => rsx! {
+            p { class: "error", "Failed to load idea: {error_message(e)}" }
+        },
+        None => rsx! {
+            p { class: "loading", "Loading idea..." }
+        },
+    }
+}
+
+/// Printable report of every idea, or of the ideas passing the list's tag filter when `query` has one. The other
+/// list settings (search, sort, page) don't apply to the report.
+#[component]
+pub fn ListReport(query: ReadSignal<ListQuery>) -> Element {
+    let ideas = use_resource(get_all_ideas_server);
+
+    let ListQuery { tags, tag_mode, .. } = query();
+    let back = Route::Home {
+        query: ListQuery { tags: tags.clone(), tag_mode, ..Default::default() },
+    };
+    let joiner = match tag_mode {
+        TagMatchMode::All => " and ",
+        TagMatchMode::Any => " or ",
+    };
+    let heading = if tags.is_empty() { "ideas".to_string() } else { format!("ideas tagged {}", tags.join(joiner)) };
+    let ideas = ideas.read();
+    match &*ideas {
+        Some(Ok(ideas)) => {
+            let entries = report_ideas(ideas, &tags, tag_mode);
+            rsx! {
+                ReportPage {
+                    back,
+                    h1 { class: "report-heading", "{heading}" }
+                    if entries.is_empty() {
+                        p { class: "report-empty", "No ideas to report." }
+                    }
+                    for (index, idea) in entries.into_iter().enumerate() {
+                        ReportEntry { key: "{index}", idea }
+                    }
+                }
+            }
+        }
+        Some(Err(e)) => rsx! {
+            p { class: "error", "Failed to load ideas: {error_message(e)}" }
+        },
+        None => rsx! {
+            p { class: "loading", "Loading ideas..." }
+        },
+    }
+}
+
+/// Page around a report: always light, with a back link and a print button that don't show up on paper
+#[component]
+fn ReportPage(back: Route, children: Element) -> Element {
+    use_stylesheet(REPORT_CSS);
+
+    rsx! {
+        div {
+            class: "report",
+            "data-theme": "light",
+            div {
+                class: "report-toolbar",
+                Link { to: back, "← back" }
+                button {
+                    r#type: "button",
+                    class: "print-btn",
+                    // Browsers and the desktop webview both open the system print dialog, which can save a PDF
+                    onclick: move |_| {
+                        document::eval("window.print();");
+                    },
+                    "print"
+                }
+            }
+            {children}
+        }
+    }
+}
+
+#[component]
+fn ReportEntry(idea: ReportIdea) -> Element {
+    rsx! {
+        article {
+            class: "report-idea",
+            h2 { "{idea.title}" }
+            if !idea.tags.is_empty() || idea.due.is_some() {
+                p {
+                    class: "report-meta",
+                    if !idea.tags.is_empty() {
+                        span { "{idea.tags.join(\", \")}" }
+                    }
+                    if let Some(due) = &idea.due {
+                        span { "due {due}" }
+                    }
+                }
+            }
+            Blocks { blocks: idea.description }
+
+            if !idea.statements.is_empty() {
+                h3 { "what must be true" }
+                ul {
+                    class: "report-statements",
+                    for statement in idea.statements {
+                        li { "{statement}" }
+                    }
+                }
+            }
+
+            if !idea.notes.is_empty() {
+                h3 { "development notes" }
+                Blocks { blocks: idea.notes }
+            }
+        }
+    }
+}
+
+#[component]
+fn Blocks(blocks: Vec<ReportBlock>) -> Element {
+    rsx! {
+        for block in blocks {
+            match block {
+                ReportBlock::Heading(text) => rsx! { h4 { "{text}" } },
+                ReportBlock::Paragraph(text) => rsx! { p { "{text}" } },
+            }
+        }
+    }
+}
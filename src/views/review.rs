@@ -0,0 +1,220 @@
+use crate::client_errors::log_error;
+use crate::components::Timestamp;
+use crate::db::IdeaStatus;
+use crate::error::error_message;
+use crate::hooks::{use_idea_list_revision, use_stylesheet, use_trash_revision};
+use crate::review::{ReviewAction, ReviewQueue, StaleIdea, STALE_DAYS};
+use crate::server_functions::{
+    delete_idea_server, get_stale_ideas_server, restore_idea_server, set_idea_status_server, touch_idea_server,
+};
+use crate::Route;
+use dioxus::prelude::*;
+
+const REVIEW_CSS: Asset = asset!("/assets/styling/review.css");
+
+/// Ideas without activity for [`STALE_DAYS`] days, stalest first, one at a time: keep, develop, archive (mark as
+/// dropped) or delete (move to the trash) each, or skip it. The last action can be undone, which reverts it on the
+/// server too, except that a kept idea stays freshly active.
+#[component]
+pub fn Review() -> Element {
+    use_stylesheet(REVIEW_CSS);
+
+    let mut trash_revision = use_trash_revision();
+    let mut idea_list_revision = use_idea_list_revision();
+    let mut queue = use_signal(ReviewQueue::<StaleIdea>::default);
+    let mut error = use_signal(|| Option::<String>::None);
+    let mut busy = use_signal(|| false);
+
+    // Fetched once: the queue is the list as it was when the review began
+    let stale = use_resource(move || async move {
+        let stale = get_stale_ideas_server(STALE_DAYS).await;
+        if let Ok(ideas) = &stale {
+            queue.set(ReviewQueue::new(ideas.clone()));
+        }
+        stale
+    });
+
+    let mut decide = move |action: ReviewAction| {
+        if busy() {
+            return;
+        }
+        let Some(stale) = queue.write().decide(action) else {
+            return;
+        };
+        let id = stale.idea.id.clone().unwrap_or_default();
+        spawn(async move {
+            busy.set(true);
+            let result = match action {
+                ReviewAction::Keep => touch_idea_server(id).await.map(|_| ()),
+                ReviewAction::Archive => set_idea_status_server(id, IdeaStatus::Dropped).await.map(|_| ()),
+                ReviewAction::Delete => delete_idea_server(id).await,
+                ReviewAction::Skip => Ok(()),
+            };
+            match result {
+                Ok(()) => error.set(None),
+                Err(e) => {
+                    // Not done, so the idea comes back to be decided again
+                    log_error("review", &e);
+                    error.set(Some(error_message(&e)));
+                    queue.write().undo();
+                }
+            }
+            if action == ReviewAction::Delete {
+                trash_revision.bump();
+            }
+            idea_list_revision.bump();
+            busy.set(false);
+        });
+    };
+
+    let undo = move |_| {
+        if busy() {
+            return;
+        }
+        let Some((stale, action)) = queue.write().undo() else {
+            return;
+        };
+        let id = stale.idea.id.clone().unwrap_or_default();
+        spawn(async move {
+            busy.set(true);
+            let result = match action {
+                ReviewAction::Archive => set_idea_status_server(id, stale.idea.status).await.map(|_| ()),
+                ReviewAction::Delete => restore_idea_server(id).await.map(|_| ()),
+                ReviewAction::Keep | ReviewAction::Skip => Ok(()),
+            };
+            match result {
+                Ok(()) => error.set(None),
+                Err(e) => {
+                    // Still done on the server, so the decision stands
+                    log_error("review undo", &e);
+                    error.set(Some(error_message(&e)));
+                    queue.write().decide(action);
+                }
+            }
+            if action == ReviewAction::Delete {
+                trash_revision.bump();
+            }
+            idea_list_revision.bump();
+            busy.set(false);
+        });
+    };
+
+    let review = queue.read().clone();
+    let (position, total) = review.progress();
+
+    rsx! {
+        div {
+            id: "review",
+            div {
+                class: "review-header",
+                h2 { "review" }
+                if total > 0 && !review.is_finished() {
+                    span { class: "review-progress", aria_live: "polite", "{position} of {total}" }
+                }
+            }
+
+            if let Some(message) = error() {
+                p { class: "message", role: "alert", "{message}" }
+            }
+
+            match &*stale.read() {
+                None => rsx! {
+                    p { class: "loading", "Loading..." }
+                },
+                Some(Err(e)) => rsx! {
+                    p { class: "message", "Failed to load the ideas to review: {error_message(e)}" }
+                },
+                Some(Ok(_)) if total == 0 => rsx! {
+                    p {
+                        class: "empty-state",
+                        "Nothing to review: every idea saw some activity in the last {STALE_DAYS} days."
+                    }
+                },
+                Some(Ok(_)) => match review.current().cloned() {
+                    Some(stale) => {
+                        let id = stale.idea.id.clone().unwrap_or_default();
+                        rsx! {
+                            div {
+                                class: "review-card",
+                                h3 { "{stale.idea.title}" }
+                                if !stale.idea.description.is_empty() {
+                                    p { class: "review-description", "{stale.idea.description}" }
+                                }
+                                p {
+                                    class: "review-last-active",
+                                    match stale.last_active {
+                                        Some(at) => rsx! { "last active " Timestamp { at } },
+                                        None => rsx! { "no recorded activity" },
+                                    }
+                                }
+                                div {
+                                    class: "review-actions",
+                                    button {
+                                        r#type: "button",
+                                        class: "review-keep",
+                                        disabled: busy(),
+                                        onclick: move |_| decide(ReviewAction::Keep),
+                                        "keep"
+                                    }
+                                    button {
+                                        r#type: "button",
+                                        class: "review-develop",
+                                        onclick: move |_| {
+                                            navigator().push(Route::IdeaDevelopment { id: id.clone() });
+                                        },
+                                        "develop"
+                                    }
+                                    button {
+                                        r#type: "button",
+                                        class: "review-archive",
+                                        title: "mark as dropped",
+                                        disabled: busy(),
+                                        onclick: move |_| decide(ReviewAction::Archive),
+                                        "archive"
+                                    }
+                                    button {
+                                        r#type: "button",
+                                        class: "review-delete",
+                                        title: "move to the trash",
+                                        disabled: busy(),
+                                        onclick: move |_| decide(ReviewAction::Delete),
+                                        "delete"
+                                    }
+                                    button {
+                                        r#type: "button",
+                                        class: "review-skip",
+                                        disabled: busy(),
+                                        onclick: move |_| decide(ReviewAction::Skip),
+                                        "skip"
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    None => {
+                        let summary = ReviewAction::ALL
+                            .into_iter()
+                            .filter(|action| review.count(*action) > 0)
+                            .map(|action| format!("{} {}", review.count(action), action.past_tense()))
+                            .collect::<Vec<_>>()
+                            .join(", ");
+                        rsx! {
+                            p { class: "review-done", "Review done: {summary}." }
+                        }
+                    }
+                },
+            }
+
+            if let Some(action) = review.last_decision() {
+                button {
+                    r#type: "button",
+                    class: "review-undo",
+                    disabled: busy(),
+                    title: "take back \"{action.past_tense()}\"",
+                    onclick: undo,
+                    "undo"
+                }
+            }
+        }
+    }
+}
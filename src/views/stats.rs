@@ -0,0 +1,203 @@
+use crate::components::Sparkline;
+use crate::error::error_message;
+use crate::hooks::{use_idea_list_revision, use_stylesheet};
+use crate::server_functions::{get_creation_histogram_server, get_tag_report_server};
+use crate::stats::{sort_tag_report, Aging, Bucket, TagReportColumn, DEFAULT_BUCKETS};
+use dioxus::prelude::*;
+
+const STATS_CSS: Asset = asset!("/assets/styling/stats.css");
+
+/// Number of periods the range picker offers
+const RANGES: [u32; 4] = [7, DEFAULT_BUCKETS, 26, 52];
+
+/// How many ideas were created per day, week or month over a chosen range, as a bar chart
+#[component]
+pub fn Stats() -> Element {
+    use_stylesheet(STATS_CSS);
+
+    let idea_list_revision = use_idea_list_revision();
+    let mut bucket = use_signal(Bucket::default);
+    let mut range = use_signal(|| DEFAULT_BUCKETS);
+
+    let histogram = use_resource(move || async move {
+        let _ = idea_list_revision.get();
+        get_creation_histogram_server(bucket(), Some(range())).await
+    });
+
+    rsx! {
+        div {
+            id: "stats",
+            h2 { "stats" }
+
+            div {
+                class: "stats-controls",
+                label {
+                    "per "
+                    select {
+                        aria_label: "period",
+                        onchange: move |evt| {
+                            if let Some(picked) = Bucket::parse(&evt.value()) {
+                                bucket.set(picked);
+                            }
+                        },
+                        for choice in Bucket::ALL {
+                            option { value: "{choice}", selected: choice == bucket(), "{choice}" }
+                        }
+                    }
+                }
+                label {
+                    "over the last "
+                    select {
+                        aria_label: "range",
+                        onchange: move |evt| {
+                            if let Ok(picked) = evt.value().parse() {
+                                range.set(picked);
+                            }
+                        },
+                        for choice in RANGES {
+                            option { value: "{choice}", selected: choice == range(), "{choice}" }
+                        }
+                    }
+                    " {bucket}s"
+                }
+            }
+
+            match &*histogram.read() {
+                Some(Ok(bars)) => {
+                    let total: usize = bars.iter().map(|bar| bar.count).sum();
+                    let busiest = bars.iter().filter(|bar| bar.count > 0).max_by_key(|bar| bar.count);
+                    let summary = format!("{} ideas created over the last {} {}s", total, bars.len(), bucket());
+                    rsx! {
+                        section {
+                            class: "stats-chart",
+                            h3 { "ideas created" }
+                            Sparkline {
+                                values: bars.iter().map(|bar| bar.count).collect::<Vec<_>>(),
+                                label: summary.clone(),
+                                width: 480.0,
+                                height: 96.0,
+                            }
+                            if let (Some(first), Some(last)) = (bars.first(), bars.last()) {
+                                div {
+                                    class: "stats-axis",
+                                    span { "{bucket().label(first.start)}" }
+                                    span { "{bucket().label(last.start)}" }
+                                }
+                            }
+                            p { class: "stats-summary", "{summary}" }
+                            if let Some(bar) = busiest {
+                                p {
+                                    class: "stats-summary",
+                                    "busiest {bucket}: {bucket().label(bar.start)}, with {bar.count}"
+                                }
+                            }
+                        }
+                    }
+                }
+                Some(Err(e)) => rsx! {
+                    p { class: "message", "Failed to load the stats: {error_message(e)}" }
+                },
+                None => rsx! {
+                    p { class: "stats-summary", "Loading..." }
+                },
+            }
+
+            TagAging {}
+        }
+    }
+}
+
+/// Table of the tags with their idea count, average days since their ideas last changed and statement count,
+/// sortable by every column and colored by how stale the tag is
+#[component]
+fn TagAging() -> Element {
+    let idea_list_revision = use_idea_list_revision();
+    let mut column = use_signal(|| TagReportColumn::Age);
+    let mut descending = use_signal(|| true);
+
+    let report = use_resource(move || async move {
+        let _ = idea_list_revision.get();
+        get_tag_report_server().await
+    });
+
+    let mut sort_by = move |picked: TagReportColumn| {
+        if column() == picked {
+            descending.toggle();
+        } else {
+            column.set(picked);
+            // Names read A to Z, numbers biggest first
+            descending.set(picked != TagReportColumn::Tag);
+        }
+    };
+    let aria_sort = move |of: TagReportColumn| match (column() == of, descending()) {
+        (false, _) => "none",
+        (true, true) => "descending",
+        (true, false) => "ascending",
+    };
+    let headers = [
+        (TagReportColumn::Tag, "tag"),
+        (TagReportColumn::Ideas, "ideas"),
+        (TagReportColumn::Age, "days since change"),
+        (TagReportColumn::Statements, "statements"),
+    ];
+
+    rsx! {
+        section {
+            class: "stats-tags",
+            h3 { "tag aging" }
+            match &*report.read() {
+                Some(Ok(rows)) if rows.is_empty() => rsx! {
+                    p { class: "stats-summary", "No ideas to report on yet." }
+                },
+                Some(Ok(rows)) => {
+                    let mut rows = rows.clone();
+                    sort_tag_report(&mut rows, column(), descending());
+                    rsx! {
+                        table {
+                            class: "tag-report",
+                            thead {
+                                tr {
+                                    for (of, label) in headers {
+                                        th {
+                                            aria_sort: aria_sort(of),
+                                            button { r#type: "button", onclick: move |_| sort_by(of), "{label}" }
+                                        }
+                                    }
+                                }
+                            }
+                            tbody {
+                                for row in rows {
+                                    tr {
+                                        key: "{row.tag}",
+                                        "data-aging": Aging::of(row.average_age_days).as_str(),
+                                        td { "{row.tag}" }
+                                        td { "{row.ideas}" }
+                                        td {
+                                            title: "{row.dated_ideas} of {row.ideas} ideas have a known last change",
+                                            {age_label(row.average_age_days)}
+                                        }
+                                        td { "{row.statements}" }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+                Some(Err(e)) => rsx! {
+                    p { class: "message", "Failed to load the tag report: {error_message(e)}" }
+                },
+                None => rsx! {
+                    p { class: "stats-summary", "Loading..." }
+                },
+            }
+        }
+    }
+}
+
+/// Average age as shown in the report, to a tenth of a day
+fn age_label(average_age_days: Option<f64>) -> String {
+    match average_age_days {
+        Some(days) => format!("{:.1}", days),
+        None => "unknown".to_string(),
+    }
+}
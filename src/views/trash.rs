@@ -0,0 +1,157 @@
+use crate::components::Timestamp;
+use crate::db::Idea;
+use crate::error::error_message;
+use crate::hooks::{use_idea_list_revision, use_stylesheet, use_trash_revision};
+use crate::server_functions::{
+    empty_trash_server, get_archived_ideas_server, get_delete_impact_server, purge_idea_server, restore_idea_server,
+};
+use dioxus::prelude::*;
+
+const TRASH_CSS: Asset = asset!("/assets/styling/trash.css");
+
+/// Ask the user to confirm a destructive action. Only the browser can ask, so other platforms never confirm.
+fn confirm(message: &str) -> bool {
+    #[cfg(target_arch = "wasm32")]
+    {
+        web_sys::window()
+            .and_then(|window| window.confirm_with_message(message).ok())
+            .unwrap_or(false)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let _ = message;
+        false
+    }
+}
+
+/// Ideas that were deleted, with restore and purge actions
+#[component]
+pub fn Trash() -> Element {
+    use_stylesheet(TRASH_CSS);
+
+    let mut trash_revision = use_trash_revision();
+    let mut idea_list_revision = use_idea_list_revision();
+    let mut error = use_signal(|| String::new());
+
+    let mut ideas = use_resource(move || async move {
+        // Refetch whenever something moves in or out of the trash
+        let _ = trash_revision.get();
+        get_archived_ideas_server().await
+    });
+
+    // After a trash action: show its error, if any, and refresh the list, the navbar badge and the idea counts
+    let mut finish = move |result: Result<()>| {
+        match result {
+            Ok(()) => error.set(String::new()),
+            Err(e) => error.set(error_message(&e)),
+        }
+        trash_revision.bump();
+        idea_list_revision.bump();
+        ideas.restart();
+    };
+
+    rsx! {
+        div {
+            id: "trash",
+
+            div {
+                class: "trash-header",
+                h2 { "trash" }
+
+                if matches!(&*ideas.read(), Some(Ok(list)) if !list.is_empty()) {
+                    button {
+                        r#type: "button",
+                        class: "empty-trash-btn",
+                        onclick: move |_| async move {
+                            if confirm("Permanently delete every idea in the trash? This can't be undone.") {
+                                finish(empty_trash_server().await.map(|_| ()));
+                            }
+                        },
+                        "empty trash"
+                    }
+                }
+            }
+
+            if !error().is_empty() {
+                p { class: "message", "{error}" }
+            }
+
+            match &*ideas.read() {
+                Some(Ok(list)) if list.is_empty() => rsx! {
+                    p { class: "empty-state", "The trash is empty." }
+                },
+                Some(Ok(list)) => rsx! {
+                    for idea in list.clone() {
+                        TrashCard {
+                            key: "{idea.id.clone().unwrap_or_default()}",
+                            idea: idea.clone(),
+                            on_restore: move |id: String| async move {
+                                finish(restore_idea_server(id).await.map(|_| ()));
+                            },
+                            on_purge: move |id: String| async move {
+                                // Say what goes with the idea, so attachments and links aren't lost unawares
+                                let impact = match get_delete_impact_server(id.clone()).await {
+                                    Ok(impact) => impact,
+                                    Err(e) => return finish(Err(e)),
+                                };
+                                let message = match impact.summary() {
+                                    Some(also) => format!("Delete this idea forever? {}. This can't be undone.", also),
+                                    None => "Delete this idea forever? This can't be undone.".to_string(),
+                                };
+                                if confirm(&message) {
+                                    finish(purge_idea_server(id).await.map(|_| ()));
+                                }
+                            },
+                        }
+                    }
+                },
+                Some(Err(e)) => rsx! {
+                    p { class: "message", "Failed to load the trash: {error_message(e)}" }
+                },
+                None => rsx! {
+                    p { class: "loading", "Loading..." }
+                },
+            }
+        }
+    }
+}
+
+/// A trashed idea with its deletion time
+#[component]
+fn TrashCard(idea: Idea, on_restore: EventHandler<String>, on_purge: EventHandler<String>) -> Element {
+    let id = idea.id.clone().unwrap_or_default();
+    let purge_id = id.clone();
+
+    rsx! {
+        div {
+            class: "trash-card",
+            div {
+                class: "trash-card-body",
+                h3 { "{idea.title}" }
+                if let Some(at) = idea.deleted_at {
+                    p {
+                        class: "deleted-at",
+                        "deleted "
+                        Timestamp { at }
+                    }
+                }
+            }
+            div {
+                class: "trash-actions",
+                button {
+                    r#type: "button",
+                    class: "restore-btn",
+                    onclick: move |_| on_restore.call(id.clone()),
+                    "restore"
+                }
+                button {
+                    r#type: "button",
+                    class: "purge-btn",
+                    onclick: move |_| on_purge.call(purge_id.clone()),
+                    "delete forever"
+                }
+            }
+        }
+    }
+}
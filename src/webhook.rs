@@ -0,0 +1,281 @@
+//! Adding ideas from outside the app with a single request, for phone shortcuts and chat slash commands.
+//!
+//! `POST /api/hooks/idea` takes `{ "title": .., "description": .., "tags": [..] }` and creates the idea like the
+//! new-idea form does, checked against the same form settings, as an anonymous idea on the default board. Instead of
+//! a session it needs the `X-Webhook-Secret` header to match [`WEBHOOK_SECRET_VAR`]; without that variable the route
+//! isn't served at all. Writes through the hook are rate limited and refused on read-only deployments like any other.
+
+use serde::{Deserialize, Serialize};
+
+/// The secret callers of the hook must send. Unset or blank turns the hook off.
+pub const WEBHOOK_SECRET_VAR: &str = "IDEAS_WEBHOOK_SECRET";
+
+/// Header carrying the secret
+pub const WEBHOOK_SECRET_HEADER: &str = "x-webhook-secret";
+
+/// The secret from `lookup`, trimmed; `None` when it is unset or blank
+pub fn webhook_secret(lookup: impl Fn(&str) -> Option<String>) -> Option<String> {
+    lookup(WEBHOOK_SECRET_VAR)
+        .map(|secret| secret.trim().to_string())
+        .filter(|secret| !secret.is_empty())
+}
+
+/// Whether `given` is `expected`, taking as long for a near miss as for a wrong first byte, so the time a refusal
+/// takes doesn't give the secret away a byte at a time
+pub fn secrets_match(given: &[u8], expected: &[u8]) -> bool {
+    let mut difference = given.len() ^ expected.len();
+    for i in 0..given.len().max(expected.len()) {
+        let (a, b) = (given.get(i).copied().unwrap_or(0), expected.get(i).copied().unwrap_or(0));
+        difference |= usize::from(a ^ b);
+    }
+    difference == 0
+}
+
+/// An idea sent to the hook
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WebhookIdea {
+    pub title: String,
+    pub description: String,
+    pub tags: Vec<String>,
+}
+
+/// What is wrong with one field of a request body
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldError {
+    /// `body` when the body as a whole is unusable
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    fn new(field: &str, message: &str) -> Self {
+        Self {
+            field: field.to_string(),
+            message: message.to_string(),
+        }
+    }
+}
+
+/// Read a hook request body, listing every field that is wrong rather than stopping at the first. `description` and
+/// `tags` may be left out or `null`; other fields are ignored.
+pub fn parse_webhook_idea(body: &[u8]) -> Result<WebhookIdea, Vec<FieldError>> {
+    use serde_json::Value;
+
+    let Ok(Value::Object(fields)) = serde_json::from_slice::<Value>(body) else {
+        return Err(vec![FieldError::new("body", "must be a JSON object")]);
+    };
+    let mut errors = Vec::new();
+
+    let title = match fields.get("title") {
+        Some(Value::String(title)) if !title.trim().is_empty() => title.clone(),
+        Some(Value::String(_)) | None | Some(Value::Null) => {
+            errors.push(FieldError::new("title", "is required"));
+            String::new()
+        }
+        Some(_) => {
+            errors.push(FieldError::new("title", "must be a string"));
+            String::new()
+        }
+    };
+    let description = match fields.get("description") {
+        None | Some(Value::Null) => String::new(),
+        Some(Value::String(description)) => description.clone(),
+        Some(_) => {
+            errors.push(FieldError::new("description", "must be a string"));
+            String::new()
+        }
+    };
+    let tags = match fields.get("tags") {
+        None | Some(Value::Null) => Vec::new(),
+        Some(Value::Array(tags)) if tags.iter().all(Value::is_string) => tags
+            .iter()
+            .filter_map(|tag| tag.as_str().map(str::to_string))
+            .collect(),
+        Some(_) => {
+            errors.push(FieldError::new("tags", "must be a list of strings"));
+            Vec::new()
+        }
+    };
+
+    if errors.is_empty() {
+        Ok(WebhookIdea { title, description, tags })
+    } else {
+        Err(errors)
+    }
+}
+
+#[cfg(feature = "server")]
+pub mod server {
+    use super::*;
+    use crate::config::AppConfig;
+    use crate::db::server::{submit_idea_with_fields, IdeaCache, IdeaChanges, SurrealIdeaRepository};
+    use crate::db::Idea;
+    use crate::error::IdeaError;
+    use crate::form_config::server::form_config;
+    use crate::limits::{
+        server::{check_room, UsageCache},
+        StorageLimits, StorageUsage,
+    };
+    use crate::rate_limit::server::with_body_limit;
+    use crate::rest::{guard_rest_write, status_for, ApiError};
+    use axum::body::Bytes;
+    use axum::extract::{ConnectInfo, State};
+    use axum::http::{HeaderMap, StatusCode};
+    use axum::response::{IntoResponse, Response};
+    use axum::routing::post;
+    use axum::{Extension, Json, Router};
+    use std::collections::HashMap;
+    use std::net::SocketAddr;
+    use std::sync::Arc;
+    use surrealdb::Surreal;
+
+    #[derive(Clone)]
+    struct HookState {
+        db: Surreal<surrealdb::engine::any::Any>,
+        secret: Arc<str>,
+    }
+
+    /// The `/api/hooks/idea` route, backed by `db` and answering to `secret`. No route without a secret.
+    pub fn router(db: Surreal<surrealdb::engine::any::Any>, secret: Option<String>) -> Router {
+        match secret {
+            Some(secret) => {
+                let router = Router::new()
+                    .route("/api/hooks/idea", post(receive_idea))
+                    .with_state(HookState { db, secret: secret.into() });
+                with_body_limit(router, |error| ApiError(error).into_response())
+            }
+            None => Router::new(),
+        }
+    }
+
+    /// `error` as JSON like [`ApiError`] does, with its own `message` and, for a bad body, the fields at fault
+    fn refusal(error: IdeaError, message: &str, fields: &[FieldError]) -> Response {
+        let mut body = serde_json::to_value(&error).unwrap_or_default();
+        if let serde_json::Value::Object(object) = &mut body {
+            object.insert("message".to_string(), message.into());
+            if !fields.is_empty() {
+                object.insert("fields".to_string(), serde_json::to_value(fields).unwrap_or_default());
+            }
+        }
+        (status_for(&error), Json(body)).into_response()
+    }
+
+    async fn receive_idea(
+        State(state): State<HookState>,
+        cache: Option<Extension<IdeaCache>>,
+        usage: Option<Extension<UsageCache>>,
+        config: Option<Extension<AppConfig>>,
+        peer: Option<Extension<ConnectInfo<SocketAddr>>>,
+        headers: HeaderMap,
+        body: Bytes,
+    ) -> Response {
+        let limits = config.as_ref().map(|Extension(config)| config.limits).unwrap_or_default();
+        // Charged before the secret is checked, so guessing it is limited too
+        if let Err(error) = guard_rest_write(config, peer, &headers) {
+            return ApiError(error).into_response();
+        }
+        let given = headers.get(WEBHOOK_SECRET_HEADER).map(|value| value.as_bytes()).unwrap_or_default();
+        if !secrets_match(given, state.secret.as_bytes()) {
+            return refusal(IdeaError::Unauthorized, "Missing or wrong X-Webhook-Secret header", &[]);
+        }
+        let idea = match parse_webhook_idea(&body) {
+            Ok(idea) => idea,
+            Err(fields) => {
+                let summary: Vec<String> = fields.iter().map(|e| format!("{} {}", e.field, e.message)).collect();
+                let summary = summary.join(", ");
+                return refusal(IdeaError::Validation(summary.clone()), &summary, &fields);
+            }
+        };
+
+        let usage = usage.map(|Extension(usage)| usage).unwrap_or_default();
+        match create(&state, cache, &usage, &limits, idea).await {
+            Ok(created) => (StatusCode::CREATED, Json(created)).into_response(),
+            Err(error) => ApiError(error).into_response(),
+        }
+    }
+
+    /// Create `idea` the way `submit_idea_server` does, minus the look-alike check: nobody is there to confirm it
+    async fn create(
+        state: &HookState,
+        cache: Option<Extension<IdeaCache>>,
+        usage: &UsageCache,
+        limits: &StorageLimits,
+        idea: WebhookIdea,
+    ) -> Result<Idea, IdeaError> {
+        let custom_fields = form_config(&state.db)
+            .await?
+            .validate(&idea.description, &idea.tags, HashMap::new())?;
+        let one_idea = StorageUsage {
+            ideas: 1,
+            ..Default::default()
+        };
+        check_room(&state.db, usage, limits, &one_idea).await?;
+        let repo = SurrealIdeaRepository::new(state.db.clone());
+        let repo = match cache {
+            Some(Extension(cache)) => repo.with_cache(cache),
+            None => repo,
+        };
+        let content = IdeaChanges {
+            title: idea.title,
+            description: idea.description,
+            tags: idea.tags,
+            what_must_be_true: Vec::new(),
+            development_notes: String::new(),
+            due_date: None,
+            sections: None,
+        };
+        Ok(submit_idea_with_fields(&repo, None, None, content, custom_fields).await?.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields(result: Result<WebhookIdea, Vec<FieldError>>) -> Vec<String> {
+        result.unwrap_err().into_iter().map(|error| error.field).collect()
+    }
+
+    #[test]
+    fn test_secrets_match_only_exactly() {
+        assert!(secrets_match(b"hunter2", b"hunter2"));
+        assert!(!secrets_match(b"hunter3", b"hunter2"));
+        assert!(!secrets_match(b"hunter", b"hunter2"));
+        assert!(!secrets_match(b"hunter22", b"hunter2"));
+        assert!(!secrets_match(b"", b"hunter2"));
+    }
+
+    #[test]
+    fn test_webhook_secret_from_lookup() {
+        assert_eq!(webhook_secret(|_| Some(" s3cret ".to_string())), Some("s3cret".to_string()));
+        assert_eq!(webhook_secret(|_| Some("  ".to_string())), None);
+        assert_eq!(webhook_secret(|_| None), None);
+    }
+
+    #[test]
+    fn test_parse_webhook_idea() {
+        let idea = parse_webhook_idea(br#"{"title": "From my phone", "tags": ["inbox"], "via": "shortcut"}"#);
+        assert_eq!(
+            idea,
+            Ok(WebhookIdea {
+                title: "From my phone".to_string(),
+                description: String::new(),
+                tags: vec!["inbox".to_string()],
+            })
+        );
+        let idea = parse_webhook_idea(br#"{"title": "Bare", "description": null, "tags": null}"#).unwrap();
+        assert!(idea.tags.is_empty());
+    }
+
+    #[test]
+    fn test_every_bad_field_is_reported() {
+        assert_eq!(fields(parse_webhook_idea(b"not json")), ["body"]);
+        assert_eq!(fields(parse_webhook_idea(b"[1, 2]")), ["body"]);
+        assert_eq!(fields(parse_webhook_idea(b"{}")), ["title"]);
+        assert_eq!(fields(parse_webhook_idea(br#"{"title": "  "}"#)), ["title"]);
+        assert_eq!(
+            fields(parse_webhook_idea(br#"{"title": 5, "description": [], "tags": ["ok", 3]}"#)),
+            ["title", "description", "tags"]
+        );
+    }
+}
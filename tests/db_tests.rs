@@ -8,11 +8,115 @@
 
 #[cfg(feature = "server")]
 mod db_integration {
-    use dioxus_surrealdb_template::db::{server::get_test_db, Idea, IdeaRecord};
-    use surrealdb::Surreal;
+    use dioxus_surrealdb_template::attachments::{
+        server::{add_attachment, attachments_for, decode_upload, delete_attachment, AttachmentRecord},
+        MAX_ATTACHMENT_BYTES,
+    };
+    use dioxus_surrealdb_template::auth::server::{
+        authenticate, create_session, delete_session, register, user_for_token,
+    };
+    use dioxus_surrealdb_template::base_path::{normalize_base_path, server::mount_at_base_path};
+    use dioxus_surrealdb_template::boards::{
+        server::{board_thing, create_board, default_board, delete_board, list_boards, load_board, rename_board},
+        DEFAULT_BOARD_SLUG,
+    };
+    use dioxus_surrealdb_template::bundle::{
+        parse_bundle,
+        server::{export_bundle, import_bundle},
+        BundledAttachment, IdeaBundle, BUNDLE_SCHEMA_VERSION,
+    };
+    use dioxus_surrealdb_template::db::{
+        server::{
+            active_ideas, append_wmbt, backup_to_string, change_tags, delete_impact, connect_with, count_trashed_ideas, delete_malformed_idea, edit_idea,
+            empty_trash, malformed_ideas,
+            find_by_slug, normalize_stored_tags, get_test_db, idea_authors, idea_revisions, ideas_after, ideas_by_author, ideas_due_before, ideas_with_tags, load_owned_idea, merge_ideas, new_test_db, purge_idea,
+            rank_for_new_idea, restore_from_dump, restore_idea, restore_revision, set_status, slug_for_title, submit_idea,
+            submit_idea_content, submit_idea_with_fields, submit_ideas, toggle_pin, touch_idea, trash_idea, trashed_ideas, with_db, with_transaction, IdeaCache, IdeaChanges, IdeaRepository, StorageSettings,
+            SurrealIdeaRepository,
+        },
+        AuthorFilter, CacheStats, DeleteImpact, Idea, IdeaId, IdeaRecord, IdeaStatus, NoteSection, SubmitOutcome,
+        TagMatchMode,
+        MAX_TAG_CHARS,
+    };
+    use dioxus_surrealdb_template::config::{server::with_config, AppConfig};
+    use dioxus_surrealdb_template::digest::server::digest_for;
+    use dioxus_surrealdb_template::error::IdeaError;
+    use dioxus_surrealdb_template::feed;
+    use dioxus_surrealdb_template::form_config::{
+        server::{form_config, set_form_config},
+        CustomField, CustomFieldKind, FieldRule, FormConfig,
+    };
+    use dioxus_surrealdb_template::graph::{server::idea_links, GraphEdge};
+    use dioxus_surrealdb_template::history::{server::IdeaEventRecord, IdeaEventKind};
+    use dioxus_surrealdb_template::idempotency::{server::submit_once, IDEMPOTENCY_TTL};
+    use dioxus_surrealdb_template::jobs::server::{start_server_jobs, with_jobs, Job, QueuedJob};
+    use dioxus_surrealdb_template::limits::{
+        server::{check_room, storage_usage, UsageCache},
+        LimitLevel, StorageLimits, StorageReport, StorageUsage,
+    };
+    use dioxus_surrealdb_template::maintenance::{
+        server::{prune_revisions, purge_old_trash, run_maintenance},
+        FixedClock, MaintenanceReport,
+    };
+    use dioxus_surrealdb_template::migrations::{
+        read_export,
+        server::{export_ideas, import_ideas},
+        IdeaExport, EXPORT_VERSION,
+    };
+    use dioxus_surrealdb_template::onboarding::example_ideas;
+    use dioxus_surrealdb_template::review::{server::stale_ideas_for, STALE_DAYS};
+    use dioxus_surrealdb_template::rest;
+    use dioxus_surrealdb_template::shutdown::{
+        server::{serve, shut_down, with_in_flight, InFlight},
+        ShutdownSettings,
+    };
+    use dioxus_surrealdb_template::snapshots::{server::Snapshots, SnapshotSettings};
+    use dioxus_surrealdb_template::stats::{
+        creation_histogram,
+        server::{creation_dates, tagged_ideas},
+        tag_report, Bucket,
+    };
+    use dioxus_surrealdb_template::revisions::{server::IdeaRevisionRecord, MAX_REVISIONS};
+    use dioxus_surrealdb_template::tags::{server::{set_tag_color, tag_colors}, tag_color};
+    use dioxus_surrealdb_template::templates::server::{
+        create_template, delete_template, list_templates, update_template, TemplateInput,
+    };
+    use dioxus_surrealdb_template::utils::{percent_encode, search_hit, MatchedField, TagChange};
+    use surrealdb::{engine::any::Any, sql::Thing, Surreal};
+
+    /// Run `test` on a fresh database of every engine this build has: memory always, RocksDB and SurrealKV in a
+    /// temporary folder when their features are on, and TiKV when `IDEAS_TEST_TIKV_URL` points at a cluster
+    async fn for_each_engine<F, Fut>(test: F)
+    where
+        F: Fn(Surreal<Any>) -> Fut,
+        Fut: std::future::Future<Output = ()>,
+    {
+        let dir = tempfile::tempdir().unwrap();
+        let mut engines = vec![("mem", StorageSettings::memory().endpoint)];
+        if cfg!(feature = "storage-rocksdb") {
+            engines.push(("rocksdb", format!("rocksdb://{}", dir.path().join("rocksdb").display())));
+        }
+        if cfg!(feature = "storage-surrealkv") {
+            engines.push(("surrealkv", format!("surrealkv://{}", dir.path().join("surrealkv").display())));
+        }
+        if let (true, Ok(url)) = (cfg!(feature = "storage-tikv"), std::env::var("IDEAS_TEST_TIKV_URL")) {
+            engines.push(("tikv", url));
+        }
+
+        for (engine, endpoint) in engines {
+            let settings = StorageSettings { endpoint, credentials: None };
+            // A database of its own, so a shared TiKV cluster starts out empty too
+            let database = format!("test_{}", uuid::Uuid::new_v4().simple());
+            let db = connect_with(&settings, "test_ns", &database)
+                .await
+                .unwrap_or_else(|e| panic!("{} engine: {}", engine, e));
+            eprintln!("running on the {} engine", engine);
+            test(db).await;
+        }
+    }
 
     /// Helper function to set up a fresh test database
-    async fn setup_test_db() -> &'static Surreal<surrealdb::engine::local::Db> {
+    async fn setup_test_db() -> &'static Surreal<surrealdb::engine::any::Any> {
         get_test_db().await
     }
 
@@ -25,108 +129,114 @@ mod db_integration {
             tags: vec!["test".to_string()],
             what_must_be_true: vec![],
             development_notes: String::new(),
+            ..Default::default()
         }
     }
 
     #[tokio::test]
     async fn test_create_idea() {
-        let db = setup_test_db().await;
+        for_each_engine(|db| async move {
+            let idea = create_test_idea("Test Idea", "This is a test");
 
-        let idea = create_test_idea("Test Idea", "This is a test");
+            // Create the idea
+            let created: Option<IdeaRecord> = db
+                .create("ideas")
+                .content(idea.clone())
+                .await
+                .expect("Failed to create idea");
 
-        // Create the idea
-        let created: Option<IdeaRecord> = db
-            .create("ideas")
-            .content(idea.clone())
-            .await
-            .expect("Failed to create idea");
-
-        assert!(created.is_some());
-        let created = created.unwrap();
-        assert_eq!(created.title, "Test Idea");
-        assert_eq!(created.description, "This is a test");
-        assert!(created.id.is_some());
+            assert!(created.is_some());
+            let created = created.unwrap();
+            assert_eq!(created.title, "Test Idea");
+            assert_eq!(created.description, "This is a test");
+            assert!(created.id.is_some());
+        })
+        .await;
     }
 
     #[tokio::test]
     async fn test_get_all_ideas() {
-        let db = setup_test_db().await;
-
-        // Create multiple test ideas
-        let idea1 = create_test_idea("Idea 1", "First test idea");
-        let idea2 = create_test_idea("Idea 2", "Second test idea");
+        for_each_engine(|db| async move {
+            // Create multiple test ideas
+            let idea1 = create_test_idea("Idea 1", "First test idea");
+            let idea2 = create_test_idea("Idea 2", "Second test idea");
 
-        let _: Option<IdeaRecord> = db.create("ideas").content(idea1).await.unwrap();
-        let _: Option<IdeaRecord> = db.create("ideas").content(idea2).await.unwrap();
+            let _: Option<IdeaRecord> = db.create("ideas").content(idea1).await.unwrap();
+            let _: Option<IdeaRecord> = db.create("ideas").content(idea2).await.unwrap();
 
-        // Get all ideas
-        let ideas: Vec<IdeaRecord> = db.select("ideas").await.expect("Failed to get ideas");
+            // Get all ideas
+            let ideas: Vec<IdeaRecord> = db.select("ideas").await.expect("Failed to get ideas");
 
-        assert!(ideas.len() >= 2);
-        assert!(ideas.iter().any(|i| i.title == "Idea 1"));
-        assert!(ideas.iter().any(|i| i.title == "Idea 2"));
+            assert!(ideas.len() >= 2);
+            assert!(ideas.iter().any(|i| i.title == "Idea 1"));
+            assert!(ideas.iter().any(|i| i.title == "Idea 2"));
+        })
+        .await;
     }
 
     #[tokio::test]
     async fn test_update_idea_development_fields() {
-        let db = setup_test_db().await;
-
-        // Create an idea
-        let idea = create_test_idea("Update Test", "Testing updates");
-        let created: Option<IdeaRecord> = db.create("ideas").content(idea).await.unwrap();
-        let created = created.unwrap();
-        let id = created.id.unwrap();
+        for_each_engine(|db| async move {
+            // Create an idea
+            let idea = create_test_idea("Update Test", "Testing updates");
+            let created: Option<IdeaRecord> = db.create("ideas").content(idea).await.unwrap();
+            let created = created.unwrap();
+            let id = created.id.unwrap();
 
-        // Update with development fields
-        let updated = IdeaRecord {
-            id: None,
-            title: "Update Test".to_string(),
-            description: "Testing updates".to_string(),
-            tags: vec!["test".to_string()],
-            what_must_be_true: vec![
-                "Must have tests".to_string(),
-                "Must be fast".to_string(),
-            ],
-            development_notes: "This is a note".to_string(),
-        };
+            // Update with development fields
+            let updated = IdeaRecord {
+                id: None,
+                title: "Update Test".to_string(),
+                description: "Testing updates".to_string(),
+                tags: vec!["test".to_string()],
+                what_must_be_true: vec![
+                    "Must have tests".to_string(),
+                    "Must be fast".to_string(),
+                ],
+                development_notes: "This is a note".to_string(),
+                ..Default::default()
+            };
 
-        let result: Option<IdeaRecord> = db
-            .update((id.tb.as_str(), id.id.to_string().as_str()))
-            .content(updated)
-            .await
-            .expect("Failed to update");
+            let result: Option<IdeaRecord> = db
+                .update((id.tb.as_str(), id.id.to_string().as_str()))
+                .content(updated)
+                .await
+                .expect("Failed to update");
 
-        assert!(result.is_some());
-        let result = result.unwrap();
-        assert_eq!(result.what_must_be_true.len(), 2);
-        assert_eq!(result.development_notes, "This is a note");
+            assert!(result.is_some());
+            let result = result.unwrap();
+            assert_eq!(result.what_must_be_true.len(), 2);
+            assert_eq!(result.development_notes, "This is a note");
+        })
+        .await;
     }
 
     #[tokio::test]
     async fn test_delete_idea() {
-        let db = setup_test_db().await;
-
-        // Create an idea
-        let idea = create_test_idea("Delete Test", "Will be deleted");
-        let created: Option<IdeaRecord> = db.create("ideas").content(idea).await.unwrap();
-        let created = created.unwrap();
-        let id = created.id.unwrap();
+        for_each_engine(|db| async move {
+            // Create an idea
+            let idea = create_test_idea("Delete Test", "Will be deleted");
+            let created: Option<IdeaRecord> = db.create("ideas").content(idea).await.unwrap();
+            let created = created.unwrap();
+            let id = created.id.unwrap();
 
-        // Delete it
-        let deleted: Option<IdeaRecord> = db
-            .delete((id.tb.as_str(), id.id.to_string().as_str()))
-            .await
-            .expect("Failed to delete");
+            // Delete it
+            let deleted: Option<IdeaRecord> = db
+                .delete((id.tb.as_str(), id.id.to_string().as_str()))
+                .await
+                .expect("Failed to delete");
 
-        assert!(deleted.is_some());
+            assert!(deleted.is_some());
 
-        // Verify it's gone
-        let result: Option<IdeaRecord> = db
-            .select((id.tb.as_str(), id.id.to_string().as_str()))
-            .await
-            .expect("Failed to select");
+            // Verify it's gone
+            let result: Option<IdeaRecord> = db
+                .select((id.tb.as_str(), id.id.to_string().as_str()))
+                .await
+                .expect("Failed to select");
 
-        assert!(result.is_none());
+            assert!(result.is_none());
+        })
+        .await;
     }
 
     #[tokio::test]
@@ -140,6 +250,7 @@ mod db_integration {
             tags: vec![],
             what_must_be_true: vec![],
             development_notes: String::new(),
+            ..Default::default()
         };
 
         let created: Option<IdeaRecord> = db.create("ideas").content(idea).await.unwrap();
@@ -149,4 +260,3171 @@ mod db_integration {
         assert_eq!(created.what_must_be_true.len(), 0);
         assert_eq!(created.development_notes, "");
     }
+
+    /// Helper to create a record with a collision-free slug, like submit_idea_server does
+    async fn create_with_slug(db: &Surreal<surrealdb::engine::any::Any>, title: &str) -> IdeaRecord {
+        let slug = slug_for_title(db, title, None).await.expect("Failed to build slug");
+
+        let idea = IdeaRecord {
+            slug,
+            ..create_test_idea(title, "Slug test")
+        };
+
+        let created: Option<IdeaRecord> = db.create("ideas").content(idea).await.unwrap();
+        created.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_slug_collision_gets_numeric_suffix() {
+        let db = new_test_db().await;
+        let title = format!("Collision {}", uuid::Uuid::new_v4());
+
+        let first = create_with_slug(&db, &title).await;
+        let second = create_with_slug(&db, &title).await;
+        let third = create_with_slug(&db, &title).await;
+
+        assert_eq!(second.slug, format!("{}-2", first.slug));
+        assert_eq!(third.slug, format!("{}-3", first.slug));
+    }
+
+    #[tokio::test]
+    async fn test_unicode_title_slug_resolves() {
+        let title = format!("Café Größe {}", uuid::Uuid::new_v4());
+        let db = new_test_db().await;
+        let created = create_with_slug(&db, &title).await;
+
+        assert!(created.slug.starts_with("café-größe-"));
+
+        let found = find_by_slug(&db, &created.slug).await.unwrap();
+        assert_eq!(found.unwrap().id, created.id);
+    }
+
+    #[tokio::test]
+    async fn test_old_slug_resolves_after_rename() {
+        let db = &new_test_db().await;
+        let created = create_with_slug(db, &format!("Before {}", uuid::Uuid::new_v4())).await;
+        let id = created.id.clone().unwrap();
+        let old_slug = created.slug.clone();
+
+        // Rename the way update_idea_server does: new slug, old one kept in the history
+        let new_title = format!("After {}", uuid::Uuid::new_v4());
+        let new_slug = slug_for_title(db, &new_title, Some(id.clone())).await.unwrap();
+        let renamed = IdeaRecord {
+            id: None,
+            title: new_title,
+            slug: new_slug.clone(),
+            previous_slugs: vec![old_slug.clone()],
+            ..created
+        };
+        let _: Option<IdeaRecord> = db
+            .update((id.tb.as_str(), id.id.to_string().as_str()))
+            .content(renamed)
+            .await
+            .unwrap();
+
+        let by_old = find_by_slug(db, &old_slug).await.unwrap().unwrap();
+        assert_eq!(by_old.id, Some(id.clone()));
+        assert_eq!(by_old.slug, new_slug);
+
+        // The old slug stays reserved for the renamed idea
+        let title_again = slug_for_title(db, &by_old.previous_slugs[0], None).await.unwrap();
+        assert_ne!(title_again, old_slug);
+    }
+
+    #[tokio::test]
+    async fn test_backup_and_restore_roundtrip() {
+        let db = new_test_db().await;
+        let _: Option<IdeaRecord> = db
+            .create("ideas")
+            .content(create_test_idea("Backed up", "Survives a restore"))
+            .await
+            .unwrap();
+
+        let dump = backup_to_string(&db).await.expect("Failed to back up");
+
+        // Add another idea after the backup, then restore with a wipe
+        let _: Option<IdeaRecord> = db
+            .create("ideas")
+            .content(create_test_idea("After backup", "Gone after restore"))
+            .await
+            .unwrap();
+        restore_from_dump(&db, &dump, true).await.expect("Failed to restore");
+
+        let ideas: Vec<IdeaRecord> = db.select("ideas").await.unwrap();
+        assert_eq!(ideas.len(), 1);
+        assert_eq!(ideas[0].title, "Backed up");
+    }
+
+    #[tokio::test]
+    async fn test_backup_leaves_accounts_out() {
+        let db = new_test_db().await;
+        let user = register(&db, "me@example.com", "correct horse").await.unwrap();
+        let token = create_session(&db, &user.id.unwrap()).await.unwrap();
+        let _: Option<IdeaRecord> = db
+            .create("ideas")
+            .content(create_test_idea("Backed up", "Survives a restore"))
+            .await
+            .unwrap();
+
+        let dump = backup_to_string(&db).await.unwrap();
+        assert!(!dump.contains("password_hash"));
+        assert!(!dump.contains("me@example.com"));
+
+        // A wiping restore keeps everyone signed in
+        restore_from_dump(&db, &dump, true).await.unwrap();
+        assert!(user_for_token(&db, &token).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_corrupted_dump_leaves_data_untouched() {
+        let db = new_test_db().await;
+        let _: Option<IdeaRecord> = db
+            .create("ideas")
+            .content(create_test_idea("Keep me", "Must survive a bad restore"))
+            .await
+            .unwrap();
+
+        let dump = backup_to_string(&db).await.unwrap();
+        let corrupted = format!("{}\nCREATE ideas SET title = 'broken", &dump[..dump.len() / 2]);
+
+        let result = restore_from_dump(&db, &corrupted, true).await;
+        assert!(result.is_err());
+
+        let ideas: Vec<IdeaRecord> = db.select("ideas").await.unwrap();
+        assert_eq!(ideas.len(), 1);
+        assert_eq!(ideas[0].title, "Keep me");
+    }
+
+    #[tokio::test]
+    async fn test_failing_dump_is_rolled_back() {
+        let db = new_test_db().await;
+        let _: Option<IdeaRecord> = db
+            .create("ideas")
+            .content(create_test_idea("Keep me", "Must survive a failing restore"))
+            .await
+            .unwrap();
+
+        // Valid SurrealQL that fails while running, after the wipe already happened
+        let failing = "CREATE ideas:partial SET title = 'partial'; THROW 'boom';";
+
+        let result = restore_from_dump(&db, failing, true).await;
+        assert!(result.is_err());
+
+        let ideas: Vec<IdeaRecord> = db.select("ideas").await.unwrap();
+        assert_eq!(ideas.len(), 1);
+        assert_eq!(ideas[0].title, "Keep me");
+    }
+
+    #[tokio::test]
+    async fn test_new_ideas_are_ranked_last() {
+        let db = new_test_db().await;
+        assert_eq!(rank_for_new_idea(&db).await.unwrap(), 1.0);
+
+        for title in ["First", "Second", "Third"] {
+            let rank = rank_for_new_idea(&db).await.unwrap();
+            let idea = IdeaRecord {
+                rank,
+                ..create_test_idea(title, "Ranked")
+            };
+            let _: Option<IdeaRecord> = db.create("ideas").content(idea).await.unwrap();
+        }
+
+        let ideas: Vec<IdeaRecord> = db
+            .query("SELECT * FROM ideas ORDER BY rank ASC")
+            .await
+            .unwrap()
+            .take(0)
+            .unwrap();
+        let titles: Vec<&str> = ideas.iter().map(|i| i.title.as_str()).collect();
+        assert_eq!(titles, vec!["First", "Second", "Third"]);
+        assert_eq!(rank_for_new_idea(&db).await.unwrap(), 4.0);
+    }
+
+    #[tokio::test]
+    async fn test_register_and_authenticate() {
+        let db = new_test_db().await;
+
+        let user = register(&db, "Me@Example.com", "correct horse").await.unwrap();
+        assert_eq!(user.email, "me@example.com");
+        assert_ne!(user.password_hash, "correct horse");
+
+        let signed_in = authenticate(&db, " me@example.com ", "correct horse").await.unwrap();
+        assert_eq!(signed_in.id, user.id);
+
+        assert!(authenticate(&db, "me@example.com", "wrong password").await.is_err());
+        assert!(authenticate(&db, "nobody@example.com", "correct horse").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_register_rejects_duplicate_email() {
+        let db = new_test_db().await;
+        register(&db, "me@example.com", "correct horse").await.unwrap();
+
+        let result = register(&db, "ME@example.com", "another password").await;
+        assert!(matches!(result, Err(IdeaError::Validation(_))));
+    }
+
+    #[tokio::test]
+    async fn test_session_lifecycle() {
+        let db = new_test_db().await;
+        let user = register(&db, "me@example.com", "correct horse").await.unwrap();
+        let user_id = user.id.clone().unwrap();
+
+        let token = create_session(&db, &user_id).await.unwrap();
+        let found = user_for_token(&db, &token).await.unwrap().unwrap();
+        assert_eq!(found.id, Some(user_id));
+
+        // Only a hash of the token is stored
+        let stored: Vec<String> = db.query("SELECT VALUE token FROM sessions").await.unwrap().take(0).unwrap();
+        assert_eq!(stored.len(), 1);
+        assert_ne!(stored[0], token);
+
+        assert!(user_for_token(&db, "not-a-token").await.unwrap().is_none());
+
+        delete_session(&db, &token).await.unwrap();
+        assert!(user_for_token(&db, &token).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_ideas_are_only_reachable_by_their_owner() {
+        let db = new_test_db().await;
+        let alice = register(&db, "alice@example.com", "correct horse")
+            .await
+            .unwrap()
+            .id
+            .unwrap();
+        let bob = register(&db, "bob@example.com", "battery staple")
+            .await
+            .unwrap()
+            .id
+            .unwrap();
+
+        let idea = IdeaRecord {
+            owner: Some(alice.clone()),
+            ..create_test_idea("Alice's idea", "Private")
+        };
+        let created: Option<IdeaRecord> = db.create("ideas").content(idea).await.unwrap();
+        let id = created.unwrap().id.unwrap();
+        let id = format!("{}:{}", id.tb, id.id);
+
+        // Update, delete and reorder all load the record through this check first
+        let loaded = load_owned_idea(&db, &id, Some(&alice)).await.unwrap();
+        assert_eq!(loaded.title, "Alice's idea");
+
+        assert_eq!(
+            load_owned_idea(&db, &id, Some(&bob)).await.unwrap_err(),
+            IdeaError::Forbidden
+        );
+        assert_eq!(
+            load_owned_idea(&db, &id, None).await.unwrap_err(),
+            IdeaError::Forbidden
+        );
+    }
+
+    #[tokio::test]
+    async fn test_load_owned_idea_reports_missing_and_malformed_ids() {
+        let db = new_test_db().await;
+
+        assert_eq!(
+            load_owned_idea(&db, "ideas:missing", None).await.unwrap_err(),
+            IdeaError::NotFound("ideas:missing".to_string())
+        );
+        assert_eq!(
+            load_owned_idea(&db, "no-colon", None).await.unwrap_err(),
+            IdeaError::NotFound("ideas:no-colon".to_string())
+        );
+        assert!(matches!(
+            load_owned_idea(&db, "users:abc", None).await,
+            Err(IdeaError::InvalidId(_))
+        ));
+    }
+
+    fn repo(db: &Surreal<surrealdb::engine::any::Any>) -> SurrealIdeaRepository {
+        SurrealIdeaRepository::new(db.clone())
+    }
+
+    /// Create an unowned idea and return its id string
+    async fn create_idea_id(db: &Surreal<surrealdb::engine::any::Any>, title: &str) -> String {
+        let created: Option<IdeaRecord> = db
+            .create("ideas")
+            .content(create_test_idea(title, "Trash test"))
+            .await
+            .unwrap();
+        IdeaId::from(&created.unwrap().id.unwrap()).into()
+    }
+
+    fn titles(ideas: &[IdeaRecord]) -> Vec<&str> {
+        ideas.iter().map(|idea| idea.title.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_trash_round_trip() {
+        let db = new_test_db().await;
+        let id = create_idea_id(&db, "Round trip").await;
+        create_idea_id(&db, "Bystander").await;
+
+        trash_idea(&repo(&db), &id, None).await.unwrap();
+        assert_eq!(titles(&active_ideas(&db, None).await.unwrap()), vec!["Bystander"]);
+
+        let trashed = trashed_ideas(&db, None).await.unwrap();
+        assert_eq!(titles(&trashed), vec!["Round trip"]);
+        assert!(trashed[0].deleted_at.is_some());
+        assert_eq!(count_trashed_ideas(&db, None).await.unwrap(), 1);
+
+        let restored = restore_idea(&repo(&db), &id, None).await.unwrap();
+        assert!(restored.deleted_at.is_none());
+        assert_eq!(restored.title, "Round trip");
+
+        assert_eq!(active_ideas(&db, None).await.unwrap().len(), 2);
+        assert!(trashed_ideas(&db, None).await.unwrap().is_empty());
+        assert_eq!(count_trashed_ideas(&db, None).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_trashing_twice_keeps_first_deletion_time() {
+        let db = new_test_db().await;
+        let id = create_idea_id(&db, "Twice").await;
+
+        trash_idea(&repo(&db), &id, None).await.unwrap();
+        let first = load_owned_idea(&db, &id, None).await.unwrap().deleted_at;
+
+        trash_idea(&repo(&db), &id, None).await.unwrap();
+        let second = load_owned_idea(&db, &id, None).await.unwrap().deleted_at;
+        assert_eq!(first, second);
+    }
+
+    #[tokio::test]
+    async fn test_only_trashed_ideas_can_be_purged() {
+        let db = new_test_db().await;
+        let id = create_idea_id(&db, "Purge me").await;
+
+        let result = purge_idea(&repo(&db), &id, None).await;
+        assert!(matches!(result, Err(IdeaError::Validation(_))));
+        assert!(load_owned_idea(&db, &id, None).await.is_ok());
+
+        trash_idea(&repo(&db), &id, None).await.unwrap();
+        purge_idea(&repo(&db), &id, None).await.unwrap();
+        assert_eq!(
+            load_owned_idea(&db, &id, None).await.unwrap_err(),
+            IdeaError::NotFound(id)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_empty_trash_only_touches_own_trashed_ideas() {
+        let db = new_test_db().await;
+        let user = register(&db, "me@example.com", "correct horse")
+            .await
+            .unwrap()
+            .id
+            .unwrap();
+
+        let kept = create_idea_id(&db, "Still active").await;
+        let gone = create_idea_id(&db, "In the trash").await;
+        trash_idea(&repo(&db), &gone, None).await.unwrap();
+
+        // Someone else's trash is out of reach
+        let theirs = IdeaRecord {
+            owner: Some(user.clone()),
+            ..create_test_idea("Theirs", "Trashed by another user")
+        };
+        let theirs: Option<IdeaRecord> = db.create("ideas").content(theirs).await.unwrap();
+        let theirs = theirs.unwrap().id.unwrap().to_string();
+        trash_idea(&repo(&db), &theirs, Some(&user)).await.unwrap();
+
+        assert_eq!(empty_trash(&db, None).await.unwrap(), 1);
+        assert!(load_owned_idea(&db, &kept, None).await.is_ok());
+        assert!(load_owned_idea(&db, &gone, None).await.is_err());
+        assert_eq!(count_trashed_ideas(&db, Some(&user)).await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_attachment_roundtrip() {
+        let db = new_test_db().await;
+        let idea_id = create_idea_id(&db, "With a sketch").await;
+
+        let stored = add_attachment(&db, &idea_id, None, "sketch.png", "image/png", vec![1, 2, 3])
+            .await
+            .unwrap();
+        assert_eq!(stored.size, 3);
+
+        let listed = attachments_for(&db, &idea_id, None).await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].filename, "sketch.png");
+        assert_eq!(listed[0].data.clone().into_inner(), vec![1, 2, 3]);
+
+        let attachment_id = stored.id.unwrap().to_string();
+        delete_attachment(&db, &attachment_id, None).await.unwrap();
+        assert!(attachments_for(&db, &idea_id, None).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_oversize_attachment_is_rejected() {
+        let db = new_test_db().await;
+        let idea_id = create_idea_id(&db, "Too big").await;
+
+        let result = add_attachment(
+            &db,
+            &idea_id,
+            None,
+            "huge.png",
+            "image/png",
+            vec![0; MAX_ATTACHMENT_BYTES + 1],
+        )
+        .await;
+        assert_eq!(
+            result.unwrap_err(),
+            IdeaError::PayloadTooLarge(MAX_ATTACHMENT_BYTES)
+        );
+
+        // Oversized uploads are refused before decoding
+        let encoded = "A".repeat(MAX_ATTACHMENT_BYTES * 2);
+        assert!(matches!(
+            decode_upload(&encoded),
+            Err(IdeaError::PayloadTooLarge(_))
+        ));
+
+        assert!(attachments_for(&db, &idea_id, None).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_disallowed_mime_type_is_rejected() {
+        let db = new_test_db().await;
+        let idea_id = create_idea_id(&db, "Scripted").await;
+
+        let result = add_attachment(&db, &idea_id, None, "x.html", "text/html", vec![1]).await;
+        assert!(matches!(result, Err(IdeaError::Validation(_))));
+    }
+
+    #[tokio::test]
+    async fn test_attachments_need_idea_ownership() {
+        let db = new_test_db().await;
+        let user = register(&db, "me@example.com", "correct horse")
+            .await
+            .unwrap()
+            .id
+            .unwrap();
+        let idea_id = create_idea_id(&db, "Anonymous idea").await;
+
+        let result = add_attachment(&db, &idea_id, Some(&user), "a.png", "image/png", vec![1]).await;
+        assert_eq!(result.unwrap_err(), IdeaError::Forbidden);
+    }
+
+    #[tokio::test]
+    async fn test_purging_an_idea_deletes_its_attachments() {
+        let db = new_test_db().await;
+        let doomed = create_idea_id(&db, "Doomed").await;
+        let kept = create_idea_id(&db, "Kept").await;
+
+        add_attachment(&db, &doomed, None, "a.png", "image/png", vec![1]).await.unwrap();
+        add_attachment(&db, &doomed, None, "b.pdf", "application/pdf", vec![2]).await.unwrap();
+        add_attachment(&db, &kept, None, "c.png", "image/png", vec![3]).await.unwrap();
+
+        // Trashing keeps attachments so a restore brings them back
+        trash_idea(&repo(&db), &doomed, None).await.unwrap();
+        let all: Vec<AttachmentRecord> = db.select("attachments").await.unwrap();
+        assert_eq!(all.len(), 3);
+
+        purge_idea(&repo(&db), &doomed, None).await.unwrap();
+        let all: Vec<AttachmentRecord> = db.select("attachments").await.unwrap();
+        assert_eq!(all.len(), 1);
+        assert_eq!(all[0].filename, "c.png");
+    }
+
+    #[tokio::test]
+    async fn test_surreal_repository_crud() {
+        let db = new_test_db().await;
+        let repo = repo(&db);
+
+        let created = submit_idea(
+            &repo,
+            None,
+            "Repository Idea".to_string(),
+            "Stored through the trait".to_string(),
+            vec!["Storage".to_string()],
+        )
+        .await
+        .unwrap();
+        let id = created.id.clone().unwrap().to_string();
+        assert_eq!(created.slug, "repository-idea");
+
+        let loaded = repo.get(&id).await.unwrap();
+        assert_eq!(loaded.title, "Repository Idea");
+        assert_eq!(repo.list(None).await.unwrap().len(), 1);
+
+        let changes = IdeaChanges {
+            title: "Renamed Idea".to_string(),
+            description: "Edited".to_string(),
+            ..Default::default()
+        };
+        let edited = edit_idea(&repo, &id, None, changes).await.unwrap();
+        assert_eq!(edited.slug, "renamed-idea");
+        assert_eq!(edited.previous_slugs, vec!["repository-idea".to_string()]);
+        assert_eq!(edited.rank, created.rank);
+
+        let merged = repo
+            .merge(&id, serde_json::json!({ "development_notes": "merged" }))
+            .await
+            .unwrap();
+        assert_eq!(merged.development_notes, "merged");
+        assert_eq!(merged.title, "Renamed Idea");
+
+        repo.delete(&id).await.unwrap();
+        assert_eq!(
+            repo.get(&id).await.unwrap_err(),
+            IdeaError::NotFound(id.clone())
+        );
+        assert!(matches!(repo.delete(&id).await, Err(IdeaError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_surreal_repository_search() {
+        let db = new_test_db().await;
+        let repo = repo(&db);
+        for (title, tags) in [("Rust Compiler", vec![]), ("Garden", vec!["RUSTIC".to_string()]), ("Cooking", vec![])] {
+            submit_idea(&repo, None, title.to_string(), String::new(), tags).await.unwrap();
+        }
+
+        let found = repo.search(None, "rust").await.unwrap();
+        let titles: Vec<&str> = found.iter().map(|idea| idea.title.as_str()).collect();
+        assert_eq!(titles, vec!["Rust Compiler", "Garden"]);
+
+        assert_eq!(repo.search(None, "").await.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_surreal_repository_rejects_empty_title() {
+        let db = new_test_db().await;
+
+        let result = submit_idea(&repo(&db), None, " ".to_string(), String::new(), vec![]).await;
+        assert!(matches!(result, Err(IdeaError::Validation(_))));
+    }
+
+    fn numbered_ideas(count: usize) -> Vec<Idea> {
+        (0..count)
+            .map(|n| Idea {
+                title: format!("Imported idea {}", n),
+                description: "From a batch".to_string(),
+                tags: vec!["import".to_string()],
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_batch_inserts_two_hundred_ideas_in_one_call() {
+        let db = new_test_db().await;
+
+        let started = std::time::Instant::now();
+        let created = submit_ideas(&repo(&db), None, numbered_ideas(200)).await.unwrap();
+        let elapsed = started.elapsed();
+
+        assert_eq!(created.len(), 200);
+        assert!(created.iter().all(|idea| idea.id.is_some()));
+        assert_eq!(active_ideas(&db, None).await.unwrap().len(), 200);
+        // Generous bound: this only guards against falling back to one round trip per record at scale
+        assert!(elapsed < std::time::Duration::from_secs(10), "batch took {:?}", elapsed);
+
+        let ordered = active_ideas(&db, None).await.unwrap();
+        assert_eq!(ordered.first().unwrap().title, "Imported idea 0");
+        assert_eq!(ordered.last().unwrap().title, "Imported idea 199");
+    }
+
+    #[tokio::test]
+    async fn test_batch_with_invalid_idea_leaves_table_unchanged() {
+        let db = new_test_db().await;
+        create_idea_id(&db, "Already here").await;
+
+        let mut ideas = numbered_ideas(50);
+        ideas[17].title = "   ".to_string();
+
+        let result = submit_ideas(&repo(&db), None, ideas).await;
+        assert_eq!(
+            result.unwrap_err(),
+            IdeaError::Validation("idea 17: Title is required".to_string())
+        );
+        assert_eq!(titles(&active_ideas(&db, None).await.unwrap()), vec!["Already here"]);
+    }
+
+    /// The connection a request from `client` comes in on, as the server records it. Each name gets its own address,
+    /// so the write limits of different clients don't interfere.
+    fn peer(client: &str) -> axum::extract::ConnectInfo<std::net::SocketAddr> {
+        use std::hash::{Hash, Hasher};
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        client.hash(&mut hasher);
+        let address = std::net::Ipv6Addr::from(u128::from(hasher.finish()));
+        axum::extract::ConnectInfo(std::net::SocketAddr::from((address, 443)))
+    }
+
+    /// Send a request to the REST router. Each test uses its own client address so write limits don't interfere.
+    async fn rest_call(
+        db: &Surreal<surrealdb::engine::any::Any>,
+        client: &str,
+        method: &str,
+        uri: &str,
+        token: Option<&str>,
+        body: Option<serde_json::Value>,
+    ) -> (axum::http::StatusCode, serde_json::Value) {
+        use http_body_util::BodyExt;
+        use tower::ServiceExt;
+
+        let mut request = axum::http::Request::builder()
+            .method(method)
+            .uri(uri)
+            .extension(peer(client))
+            .header("content-type", "application/json");
+        if let Some(token) = token {
+            request = request.header("authorization", format!("Bearer {}", token));
+        }
+        let body = body.map(|json| json.to_string()).unwrap_or_default();
+
+        let response = rest::router(db.clone())
+            .oneshot(request.body(axum::body::Body::from(body)).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let json = serde_json::from_slice(&bytes).unwrap_or(serde_json::Value::Null);
+        (status, json)
+    }
+
+    #[tokio::test]
+    async fn test_rest_idea_lifecycle() {
+        use axum::http::StatusCode;
+        let db = new_test_db().await;
+        let client = "rest-lifecycle";
+
+        let (status, created) = rest_call(
+            &db,
+            client,
+            "POST",
+            "/api/v1/ideas",
+            None,
+            Some(serde_json::json!({ "title": "From curl", "tags": ["cli"] })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(created["slug"], "from-curl");
+        let id = created["id"].as_str().unwrap().to_string();
+        let path = format!("/api/v1/ideas/{}", id);
+
+        let (status, list) = rest_call(&db, client, "GET", "/api/v1/ideas", None, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(list.as_array().unwrap().len(), 1);
+
+        let (status, patched) = rest_call(
+            &db,
+            client,
+            "PATCH",
+            &path,
+            None,
+            Some(serde_json::json!({ "development_notes": "scripted" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(patched["development_notes"], "scripted");
+        assert_eq!(patched["title"], "From curl");
+        assert_eq!(patched["tags"], serde_json::json!(["cli"]));
+
+        let (status, _) = rest_call(&db, client, "DELETE", &path, None, None).await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+
+        // Deleting moves the idea to the trash, exactly like the app
+        let (_, list) = rest_call(&db, client, "GET", "/api/v1/ideas", None, None).await;
+        assert!(list.as_array().unwrap().is_empty());
+        assert_eq!(count_trashed_ideas(&db, None).await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_rest_errors_are_structured() {
+        use axum::http::StatusCode;
+        let db = new_test_db().await;
+        let client = "rest-errors";
+
+        let (status, error) = rest_call(&db, client, "GET", "/api/v1/ideas/ideas:missing", None, None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(error["kind"], "NotFound");
+        assert_eq!(error["message"], "Idea not found: ideas:missing");
+
+        let (status, error) = rest_call(
+            &db,
+            client,
+            "POST",
+            "/api/v1/ideas",
+            None,
+            Some(serde_json::json!({ "title": "  " })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(error["kind"], "Validation");
+    }
+
+    #[tokio::test]
+    async fn test_rest_uses_session_ownership() {
+        use axum::http::StatusCode;
+        let db = new_test_db().await;
+        let client = "rest-ownership";
+        let alice = register(&db, "alice@example.com", "correct horse").await.unwrap().id.unwrap();
+        let token = create_session(&db, &alice).await.unwrap();
+
+        let (status, created) = rest_call(
+            &db,
+            client,
+            "POST",
+            "/api/v1/ideas",
+            Some(&token),
+            Some(serde_json::json!({ "title": "Alice via REST" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        let path = format!("/api/v1/ideas/{}", created["id"].as_str().unwrap());
+
+        let (status, _) = rest_call(&db, client, "GET", &path, Some(&token), None).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let (status, error) = rest_call(&db, client, "GET", &path, None, None).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(error["kind"], "Forbidden");
+
+        let (_, anonymous_list) = rest_call(&db, client, "GET", "/api/v1/ideas", None, None).await;
+        assert!(anonymous_list.as_array().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_rest_serves_openapi_spec() {
+        let db = new_test_db().await;
+
+        let (status, spec) = rest_call(&db, "rest-openapi", "GET", "/api/v1/openapi.json", None, None).await;
+        assert_eq!(status, axum::http::StatusCode::OK);
+        assert_eq!(spec["openapi"], "3.0.3");
+        assert!(spec["paths"].get("/api/v1/ideas/{id}").is_some());
+    }
+
+    fn template_input(name: &str) -> TemplateInput {
+        TemplateInput {
+            name: name.to_string(),
+            description_skeleton: "Why:\n".to_string(),
+            default_tags: vec!["mine".to_string()],
+            default_wmbt: vec!["It works".to_string()],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_builtin_templates_are_seeded_once() {
+        let db = new_test_db().await;
+        // Startup runs the schema again; the built-ins must not be duplicated
+        dioxus_surrealdb_template::db::server::define_schema(&db).await.unwrap();
+
+        let templates = list_templates(&db, None).await.unwrap();
+        assert_eq!(templates.len(), 2);
+        assert!(templates.iter().all(|template| template.builtin));
+        assert!(templates.iter().any(|template| template.name == "Problem / Proposal / Risks"));
+    }
+
+    #[tokio::test]
+    async fn test_template_crud() {
+        let db = new_test_db().await;
+        let user = register(&db, "templates@example.com", "correct horse")
+            .await
+            .unwrap()
+            .id
+            .unwrap();
+
+        let created = create_template(&db, Some(user.clone()), template_input("Weekly bet")).await.unwrap();
+        let id = created.id.unwrap().to_string();
+        let listed = list_templates(&db, Some(&user)).await.unwrap();
+        assert_eq!(listed.len(), 3);
+        assert!(listed.iter().any(|template| template.name == "Weekly bet" && !template.builtin));
+
+        // Other people only see the built-ins
+        assert_eq!(list_templates(&db, None).await.unwrap().len(), 2);
+
+        let updated = update_template(&db, &id, Some(&user), template_input("Monthly bet")).await.unwrap();
+        assert_eq!(updated.name, "Monthly bet");
+        assert_eq!(updated.owner, Some(user.clone()));
+
+        assert_eq!(
+            delete_template(&db, &id, None).await.unwrap_err(),
+            IdeaError::Forbidden
+        );
+        delete_template(&db, &id, Some(&user)).await.unwrap();
+        assert_eq!(list_templates(&db, Some(&user)).await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_template_validation_and_builtins_are_read_only() {
+        let db = new_test_db().await;
+
+        let result = create_template(&db, None, template_input("  ")).await;
+        assert!(matches!(result, Err(IdeaError::Validation(_))));
+
+        let builtin = list_templates(&db, None).await.unwrap().remove(0);
+        let builtin_id = builtin.id.unwrap().to_string();
+        let result = update_template(&db, &builtin_id, None, template_input("Hijacked")).await;
+        assert!(matches!(result, Err(IdeaError::Validation(_))));
+        let result = delete_template(&db, &builtin_id, None).await;
+        assert!(matches!(result, Err(IdeaError::Validation(_))));
+
+        let result = delete_template(&db, "ideas:abc", None).await;
+        assert!(matches!(result, Err(IdeaError::InvalidId(_))));
+    }
+
+    #[tokio::test]
+    async fn test_pinned_tag_colors() {
+        let db = new_test_db().await;
+        assert!(tag_colors(&db).await.unwrap().overrides.is_empty());
+
+        set_tag_color(&db, " Bug", Some("red")).await.unwrap();
+        // Pinning again replaces the color rather than adding a second row
+        set_tag_color(&db, "bug", Some("violet")).await.unwrap();
+        let colors = tag_colors(&db).await.unwrap();
+        assert_eq!(colors.overrides.len(), 1);
+        assert_eq!(colors.color_for("BUG"), "violet");
+
+        set_tag_color(&db, "bug", None).await.unwrap();
+        assert_eq!(tag_colors(&db).await.unwrap().color_for("bug"), tag_color("bug"));
+    }
+
+    #[tokio::test]
+    async fn test_tag_color_must_be_in_the_palette() {
+        let db = new_test_db().await;
+
+        let result = set_tag_color(&db, "bug", Some("chartreuse")).await;
+        assert!(matches!(result, Err(IdeaError::Validation(_))));
+        let result = set_tag_color(&db, "  ", Some("red")).await;
+        assert!(matches!(result, Err(IdeaError::Validation(_))));
+    }
+
+    #[tokio::test]
+    async fn test_idea_history_follows_the_idea() {
+        let db = new_test_db().await;
+        let repo = repo(&db);
+        let idea = submit_idea(&repo, None, "Tracked".to_string(), String::new(), vec![])
+            .await
+            .unwrap();
+        let id = idea.id.clone().unwrap();
+
+        let changes = IdeaChanges {
+            title: "Tracked".to_string(),
+            tags: vec!["ux".to_string()],
+            ..Default::default()
+        };
+        edit_idea(&repo, &id.to_string(), None, changes).await.unwrap();
+        trash_idea(&repo, &id.to_string(), None).await.unwrap();
+
+        // The log stays while the idea is in the trash
+        let history = repo.history(&id).await.unwrap();
+        let kinds: Vec<IdeaEventKind> = history.iter().map(|event| event.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![IdeaEventKind::Trashed, IdeaEventKind::Edited, IdeaEventKind::Created]
+        );
+        assert_eq!(history[1].summary, "added 1 tag");
+
+        // ... and goes with it when it is purged
+        purge_idea(&repo, &id.to_string(), None).await.unwrap();
+        assert!(repo.history(&id).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_pinned_flag_is_stored() {
+        let db = new_test_db().await;
+        let id = create_idea_id(&db, "Pinnable").await;
+        assert!(!load_owned_idea(&db, &id, None).await.unwrap().pinned);
+
+        let pinned = toggle_pin(&repo(&db), &id, None).await.unwrap();
+        assert!(pinned.pinned);
+        assert!(load_owned_idea(&db, &id, None).await.unwrap().pinned);
+        assert!(active_ideas(&db, None).await.unwrap()[0].pinned);
+
+        toggle_pin(&repo(&db), &id, None).await.unwrap();
+        assert!(!load_owned_idea(&db, &id, None).await.unwrap().pinned);
+    }
+
+    #[tokio::test]
+    async fn test_ideas_due_before() {
+        let db = new_test_db().await;
+        let date = |value: &str| chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap();
+
+        let mut ids = Vec::new();
+        for (title, due) in [
+            ("Later", Some("2024-06-20")),
+            ("Undated", None),
+            ("Soon", Some("2024-06-12")),
+            ("Overdue", Some("2024-06-01")),
+            ("On the bound", Some("2024-06-14")),
+            ("Trashed", Some("2024-06-02")),
+        ] {
+            let id = create_idea_id(&db, title).await;
+            let changes = IdeaChanges {
+                title: title.to_string(),
+                due_date: due.map(date),
+                ..Default::default()
+            };
+            edit_idea(&repo(&db), &id, None, changes).await.unwrap();
+            ids.push(id);
+        }
+        trash_idea(&repo(&db), &ids[5], None).await.unwrap();
+
+        // Soonest first; the bound itself, undated and trashed ideas are left out
+        let upcoming = ideas_due_before(&db, None, date("2024-06-14")).await.unwrap();
+        assert_eq!(titles(&upcoming), vec!["Overdue", "Soon"]);
+    }
+
+    /// A server like the one `main` builds: REST routes plus a probe reading the request's database the way server
+    /// functions do
+    fn test_server(db: &Surreal<surrealdb::engine::any::Any>) -> axum::Router {
+        async fn probe(
+            axum::Extension(db): axum::Extension<Surreal<surrealdb::engine::any::Any>>,
+        ) -> String {
+            active_ideas(&db, None).await.unwrap().len().to_string()
+        }
+
+        let router = axum::Router::new()
+            .route("/probe", axum::routing::get(probe))
+            .merge(rest::router(db.clone()));
+        with_db(router, db.clone())
+    }
+
+    async fn server_call(server: axum::Router, method: &str, uri: &str, body: Option<serde_json::Value>) -> String {
+        use http_body_util::BodyExt;
+        use tower::ServiceExt;
+
+        let request = axum::http::Request::builder()
+            .method(method)
+            .uri(uri)
+            .extension(peer("two-servers"))
+            .header("content-type", "application/json")
+            .body(axum::body::Body::from(body.map(|json| json.to_string()).unwrap_or_default()))
+            .unwrap();
+        let response = server.oneshot(request).await.unwrap();
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        String::from_utf8_lossy(&bytes).into_owned()
+    }
+
+    #[tokio::test]
+    async fn test_servers_keep_their_own_database() {
+        let first_db = new_test_db().await;
+        let second_db = new_test_db().await;
+        let first = test_server(&first_db);
+        let second = test_server(&second_db);
+
+        let body = serde_json::json!({ "title": "Only on the first" });
+        server_call(first.clone(), "POST", "/api/v1/ideas", Some(body)).await;
+
+        assert_eq!(server_call(first.clone(), "GET", "/probe", None).await, "1");
+        assert_eq!(server_call(second.clone(), "GET", "/probe", None).await, "0");
+        assert_eq!(server_call(second, "GET", "/api/v1/ideas", None).await, "[]");
+        assert_eq!(titles(&active_ideas(&first_db, None).await.unwrap()), vec!["Only on the first"]);
+        assert!(active_ideas(&second_db, None).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_merge_moves_attachments_and_deletes_the_secondary() {
+        let db = new_test_db().await;
+        let primary_id = create_idea_id(&db, "Herb box").await;
+        // Submitted rather than created directly, so it has a slug for the primary to keep
+        let basil = submit_idea(&repo(&db), None, "Basil pot".to_string(), "Grow basil".to_string(), vec![])
+            .await
+            .unwrap();
+        let secondary_id = IdeaId::from(basil.id.as_ref().unwrap()).to_string();
+        let secondary = load_owned_idea(&db, &secondary_id, None).await.unwrap();
+        add_attachment(&db, &secondary_id, None, "basil.png", "image/png", vec![1, 2, 3])
+            .await
+            .unwrap();
+
+        let merged = merge_ideas(&repo(&db), &primary_id, &secondary_id, None).await.unwrap();
+        assert!(merged.description.contains("## merged from Basil pot"));
+        assert!(merged.previous_slugs.contains(&secondary.slug));
+
+        // Gone for good, with its attachment now on the primary rather than cascaded away
+        assert!(matches!(
+            load_owned_idea(&db, &secondary_id, None).await,
+            Err(IdeaError::NotFound(_))
+        ));
+        let files = attachments_for(&db, &primary_id, None).await.unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].filename, "basil.png");
+        assert_eq!(find_by_slug(&db, &secondary.slug).await.unwrap().unwrap().id, merged.id);
+
+        let result = merge_ideas(&repo(&db), &primary_id, &primary_id, None).await;
+        assert!(matches!(result, Err(IdeaError::Validation(_))));
+    }
+
+    /// The loop a surrealdb upgrade broke before ids went through `IdeaId`: create, fetch by the id string the
+    /// client gets, delete. Run these after bumping the version pinned in Cargo.lock.
+    #[tokio::test]
+    async fn test_id_string_round_trip() {
+        let db = new_test_db().await;
+        let repo = repo(&db);
+
+        let created = repo.create(create_test_idea("Round trip", "Id loop")).await.unwrap();
+        let idea = Idea::from(created);
+        let id = idea.id.clone().unwrap();
+        let parsed = IdeaId::parse(&id).unwrap();
+        assert!(parsed.key().chars().all(|c| c.is_ascii_alphanumeric()), "unexpected id {}", id);
+
+        let fetched = repo.get(&id).await.unwrap();
+        assert_eq!(Idea::from(fetched).id.as_deref(), Some(id.as_str()));
+        assert_eq!(load_owned_idea(&db, &id, None).await.unwrap().title, "Round trip");
+
+        repo.delete(&id).await.unwrap();
+        assert_eq!(repo.get(&id).await.unwrap_err(), IdeaError::NotFound(id.clone()));
+        assert_eq!(repo.delete(&id).await.unwrap_err(), IdeaError::NotFound(id));
+    }
+
+    #[tokio::test]
+    async fn test_escaped_keys_keep_their_client_id() {
+        let db = new_test_db().await;
+
+        // A key the library prints as `ideas:⟨with-dash⟩`
+        let created: Option<IdeaRecord> = db
+            .create(("ideas", "with-dash"))
+            .content(create_test_idea("Dashed", "Escaped key"))
+            .await
+            .unwrap();
+        let id = Idea::from(created.unwrap()).id.unwrap();
+        assert_eq!(id, "ideas:with-dash");
+
+        let repo = repo(&db);
+        assert_eq!(repo.get(&id).await.unwrap().title, "Dashed");
+        repo.delete(&id).await.unwrap();
+        assert!(matches!(repo.get(&id).await, Err(IdeaError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_ideas_with_all_or_any_tags() {
+        let db = new_test_db().await;
+        let repo = repo(&db);
+        for (title, tags) in [
+            ("Herbs", vec!["garden", "kitchen"]),
+            ("Compost", vec!["garden"]),
+            ("Spices", vec!["kitchen", "money"]),
+            ("Untagged", vec![]),
+            ("Trashed", vec!["garden", "kitchen"]),
+        ] {
+            let tags = tags.into_iter().map(String::from).collect();
+            let created = submit_idea(&repo, None, title.to_string(), String::new(), tags).await.unwrap();
+            if title == "Trashed" {
+                let id = IdeaId::from(created.id.as_ref().unwrap());
+                trash_idea(&repo, id.as_str(), None).await.unwrap();
+            }
+        }
+
+        let tags = |list: &[&str]| list.iter().map(|tag| tag.to_string()).collect::<Vec<_>>();
+        let all = ideas_with_tags(&db, None, &tags(&["garden", "kitchen"]), TagMatchMode::All).await.unwrap();
+        assert_eq!(titles(&all), vec!["Herbs"]);
+        let any = ideas_with_tags(&db, None, &tags(&["garden", "kitchen"]), TagMatchMode::Any).await.unwrap();
+        assert_eq!(titles(&any), vec!["Herbs", "Compost", "Spices"]);
+
+        // No tags is no filter; unknown tags are simply unmatched
+        let everything = ideas_with_tags(&db, None, &[], TagMatchMode::All).await.unwrap();
+        assert_eq!(everything.len(), 4);
+        for mode in [TagMatchMode::All, TagMatchMode::Any] {
+            assert!(ideas_with_tags(&db, None, &tags(&["nobody"]), mode).await.unwrap().is_empty());
+        }
+        let partly_unknown = ideas_with_tags(&db, None, &tags(&["money", "nobody"]), TagMatchMode::Any).await.unwrap();
+        assert_eq!(titles(&partly_unknown), vec!["Spices"]);
+    }
+
+    #[tokio::test]
+    async fn test_revisions_are_capped_and_restorable() {
+        let db = new_test_db().await;
+        let repo = repo(&db);
+        let id = create_idea_id(&db, "Drafts").await;
+        let thing = Thing::from(&IdeaId::parse(&id).unwrap());
+
+        for n in 0..MAX_REVISIONS + 3 {
+            let revision = IdeaRevisionRecord::now(thing.clone(), vec![], format!("version {}", n));
+            repo.save_revision(revision).await.unwrap();
+        }
+        let revisions = idea_revisions(&repo, &id, None).await.unwrap();
+        assert_eq!(revisions.len(), MAX_REVISIONS);
+        assert_eq!(revisions[0].development_notes, format!("version {}", MAX_REVISIONS + 2));
+        assert_eq!(revisions[MAX_REVISIONS - 1].development_notes, "version 3");
+
+        // Restoring the oldest kept version saves it as the newest snapshot and prunes the next oldest
+        let oldest = revisions[MAX_REVISIONS - 1].id.as_ref().unwrap().to_string();
+        let restored = restore_revision(&repo, &id, &oldest, None).await.unwrap();
+        assert_eq!(restored.development_notes, "version 3");
+        let revisions = idea_revisions(&repo, &id, None).await.unwrap();
+        assert_eq!(revisions.len(), MAX_REVISIONS);
+        assert_eq!(revisions[0].development_notes, "version 3");
+        assert_eq!(revisions[MAX_REVISIONS - 1].development_notes, "version 4");
+
+        // Snapshots go with their idea
+        trash_idea(&repo, &id, None).await.unwrap();
+        purge_idea(&repo, &id, None).await.unwrap();
+        assert!(repo.revisions(&thing).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_graph_links_stay_among_the_given_ideas() {
+        let db = new_test_db().await;
+        let garden = create_idea_id(&db, "Garden").await;
+        let compost = create_idea_id(&db, "Compost").await;
+        let elsewhere = create_idea_id(&db, "Elsewhere").await;
+        db.query(format!("RELATE {garden}->relates_to->{compost}; RELATE {compost}->relates_to->{elsewhere};"))
+            .await
+            .unwrap()
+            .check()
+            .unwrap();
+
+        let things = |ids: &[&String]| {
+            ids.iter().map(|id| Thing::from(&IdeaId::parse(id).unwrap())).collect::<Vec<_>>()
+        };
+        let links = idea_links(&db, &things(&[&garden, &compost])).await.unwrap();
+        assert_eq!(links, vec![GraphEdge { from: garden.clone(), to: compost.clone() }]);
+
+        // Links go with their idea
+        let repo = repo(&db);
+        trash_idea(&repo, &compost, None).await.unwrap();
+        purge_idea(&repo, &compost, None).await.unwrap();
+        let links = idea_links(&db, &things(&[&garden, &elsewhere])).await.unwrap();
+        assert!(links.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_read_only_server_refuses_rest_writes() {
+        let db = new_test_db().await;
+        let id = create_idea_id(&db, "Public").await;
+        // Visitors of a read-only server only see public ideas
+        db.query("UPDATE ideas SET visibility = 'public'").await.unwrap();
+        let server = with_config(test_server(&db), AppConfig { read_only: true, ..Default::default() });
+
+        let body = serde_json::json!({ "title": "Sneaked in" });
+        let created = server_call(server.clone(), "POST", "/api/v1/ideas", Some(body)).await;
+        assert!(created.contains("\"ReadOnly\""), "{}", created);
+        let body = serde_json::json!({ "title": "Renamed" });
+        let patched = server_call(server.clone(), "PATCH", &format!("/api/v1/ideas/{}", id), Some(body)).await;
+        assert!(patched.contains("\"ReadOnly\""), "{}", patched);
+        let deleted = server_call(server.clone(), "DELETE", &format!("/api/v1/ideas/{}", id), None).await;
+        assert!(deleted.contains("\"ReadOnly\""), "{}", deleted);
+
+        // Reads still work and nothing changed
+        assert_eq!(server_call(server.clone(), "GET", "/probe", None).await, "1");
+        assert_eq!(titles(&active_ideas(&db, None).await.unwrap()), vec!["Public"]);
+
+        // The same server without the flag accepts writes
+        let writable = with_config(test_server(&db), AppConfig::default());
+        let body = serde_json::json!({ "title": "Allowed" });
+        server_call(writable, "POST", "/api/v1/ideas", Some(body)).await;
+        assert_eq!(active_ideas(&db, None).await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_feed_lists_new_ideas() {
+        let db = new_test_db().await;
+        let repo = repo(&db);
+        for title in ["Fish & chips", "Trashed"] {
+            let created = submit_idea(&repo, None, title.to_string(), "Cheap <and> quick".to_string(), vec![])
+                .await
+                .unwrap();
+            if title == "Trashed" {
+                let id = IdeaId::from(created.id.as_ref().unwrap());
+                trash_idea(&repo, id.as_str(), None).await.unwrap();
+            }
+        }
+
+        let xml = server_call(feed::server::router(db.clone()), "GET", "/feed.xml", None).await;
+        assert!(xml.contains("<title>Fish &amp; chips</title>"), "{}", xml);
+        assert!(xml.contains("<summary>Cheap &lt;and&gt; quick</summary>"), "{}", xml);
+        assert!(xml.contains("href=\"http://localhost:8080/i/fish-chips\""), "{}", xml);
+        assert!(!xml.contains("Trashed"), "{}", xml);
+    }
+
+    #[tokio::test]
+    async fn test_tags_are_tidied_when_stored_and_by_the_migration() {
+        let db = new_test_db().await;
+        let repo = repo(&db);
+
+        let strings = |values: &[&str]| values.iter().map(|value| value.to_string()).collect::<Vec<_>>();
+        let created = submit_idea(&repo, None, "New".to_string(), String::new(), strings(&["Rust ", "rust", "api"]))
+            .await
+            .unwrap();
+        assert_eq!(created.tags, strings(&["api", "Rust"]));
+        let long_tag = vec!["x".repeat(MAX_TAG_CHARS + 1)];
+        let too_long = submit_idea(&repo, None, "Long".to_string(), String::new(), long_tag).await;
+        assert!(matches!(too_long, Err(IdeaError::Validation(_))));
+
+        // Ideas stored before tags were tidied, one of them in the trash
+        let long = "y".repeat(MAX_TAG_CHARS + 3);
+        db.query(
+            "CREATE ideas SET title = 'Old', description = '', tags = ['ux', ' UX', 'db'];
+             CREATE ideas SET title = 'Trashed', description = '', tags = [$long], deleted_at = time::now();",
+        )
+        .bind(("long", long))
+        .await
+        .unwrap()
+        .check()
+        .unwrap();
+
+        assert_eq!(normalize_stored_tags(&db).await.unwrap(), 2);
+        let mut tags: Vec<Vec<String>> = {
+            let mut response = db.query("SELECT VALUE tags FROM ideas").await.unwrap();
+            response.take(0).unwrap()
+        };
+        tags.sort();
+        assert_eq!(tags, vec![strings(&["api", "Rust"]), strings(&["db", "ux"]), vec!["y".repeat(MAX_TAG_CHARS)]]);
+
+        // Nothing left to do
+        assert_eq!(normalize_stored_tags(&db).await.unwrap(), 0);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_concurrent_statement_appends_are_all_kept() {
+        let db = new_test_db().await;
+        let id = create_idea_id(&db, "Busy idea").await;
+
+        // Two clients appending at the same time; a read-modify-write would lose some of these
+        let appender = |prefix: &'static str| {
+            let repo = repo(&db);
+            let id = id.clone();
+            tokio::spawn(async move {
+                for n in 0..10 {
+                    append_wmbt(&repo, &id, None, &format!("{prefix} {n}")).await.unwrap();
+                }
+            })
+        };
+        let (first, second) = tokio::join!(appender("first"), appender("second"));
+        first.unwrap();
+        second.unwrap();
+
+        let statements = repo(&db).get(&id).await.unwrap().what_must_be_true;
+        assert_eq!(statements.len(), 20);
+        for prefix in ["first", "second"] {
+            // Each client's statements keep their order
+            let own: Vec<&String> = statements.iter().filter(|statement| statement.starts_with(prefix)).collect();
+            let expected: Vec<String> = (0..10).map(|n| format!("{prefix} {n}")).collect();
+            assert_eq!(own, expected.iter().collect::<Vec<_>>());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_status_is_stored_and_defaults_to_inbox() {
+        let db = new_test_db().await;
+        let id = create_idea_id(&db, "Board idea").await;
+        assert_eq!(repo(&db).get(&id).await.unwrap().status, IdeaStatus::Inbox);
+
+        let rank = repo(&db).get(&id).await.unwrap().rank;
+        let moved = set_status(&repo(&db), &id, None, IdeaStatus::Exploring).await.unwrap();
+        assert_eq!(moved.status, IdeaStatus::Exploring);
+        assert_eq!(moved.rank, rank);
+
+        // Stored as its lowercase name, and kept by a full edit
+        let stored: Option<String> = {
+            let mut response = db.query("SELECT VALUE status FROM ideas").await.unwrap();
+            response.take(0).unwrap()
+        };
+        assert_eq!(stored.as_deref(), Some("exploring"));
+        let changes = IdeaChanges { title: "Board idea, edited".to_string(), ..Default::default() };
+        assert_eq!(edit_idea(&repo(&db), &id, None, changes).await.unwrap().status, IdeaStatus::Exploring);
+    }
+
+    #[tokio::test]
+    async fn test_maintenance_prunes_revisions_beyond_the_cap() {
+        let db = new_test_db().await;
+        let id = create_idea_id(&db, "Chatty").await;
+        let thing = Thing::from(&IdeaId::parse(&id).unwrap());
+        // Written directly, the way an old backup would hold them, so saving doesn't prune them first
+        for n in 0..MAX_REVISIONS + 4 {
+            let mut revision = IdeaRevisionRecord::now(thing.clone(), vec![], format!("version {}", n));
+            revision.at += chrono::Duration::seconds(n as i64);
+            let _: Option<IdeaRevisionRecord> = db.create("idea_revisions").content(revision).await.unwrap();
+        }
+
+        assert_eq!(prune_revisions(&db).await.unwrap(), 4);
+        let revisions = repo(&db).revisions(&thing).await.unwrap();
+        assert_eq!(revisions.len(), MAX_REVISIONS);
+        assert_eq!(revisions[MAX_REVISIONS - 1].development_notes, "version 4");
+        assert_eq!(prune_revisions(&db).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_maintenance_purges_only_old_trash() {
+        let db = new_test_db().await;
+        let old = create_idea_id(&db, "Long gone").await;
+        let recent = create_idea_id(&db, "Just trashed").await;
+        let kept = create_idea_id(&db, "Still here").await;
+        trash_idea(&repo(&db), &old, None).await.unwrap();
+        trash_idea(&repo(&db), &recent, None).await.unwrap();
+
+        let now = chrono::Utc::now();
+        // Nothing is old enough yet, and 0 days never purges
+        assert_eq!(purge_old_trash(&db, &FixedClock(now), 30).await.unwrap(), 0);
+        assert_eq!(purge_old_trash(&db, &FixedClock(now + chrono::Duration::days(365)), 0).await.unwrap(), 0);
+
+        db.query("UPDATE $id SET deleted_at = $at")
+            .bind(("id", Thing::from(&IdeaId::parse(&old).unwrap())))
+            .bind(("at", now - chrono::Duration::days(31)))
+            .await
+            .unwrap()
+            .check()
+            .unwrap();
+        assert_eq!(purge_old_trash(&db, &FixedClock(now), 30).await.unwrap(), 1);
+
+        assert!(load_owned_idea(&db, &old, None).await.is_err());
+        assert!(load_owned_idea(&db, &recent, None).await.unwrap().deleted_at.is_some());
+        assert!(load_owned_idea(&db, &kept, None).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_run_maintenance_reports_every_step() {
+        let db = new_test_db().await;
+        let id = create_idea_id(&db, "Trashed").await;
+        trash_idea(&repo(&db), &id, None).await.unwrap();
+
+        let config = AppConfig { trash_retention_days: 7, ..Default::default() };
+        let later = FixedClock(chrono::Utc::now() + chrono::Duration::days(8));
+        let report = run_maintenance(&db, &later, &config).await.unwrap();
+        assert_eq!(report, MaintenanceReport { revisions_pruned: 0, trash_purged: 1 });
+        assert_eq!(count_trashed_ideas(&db, None).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_creation_dates_come_from_the_first_event() {
+        let db = new_test_db().await;
+        let logged = create_idea_id(&db, "Logged").await;
+        let trashed = create_idea_id(&db, "Trashed").await;
+        // Created before the activity log existed, so it has no date
+        create_idea_id(&db, "Unlogged").await;
+        let events = [
+            (&logged, "2024-03-05T09:00:00Z"),
+            (&logged, "2024-03-01T23:30:00Z"),
+            (&trashed, "2024-03-02T10:00:00Z"),
+        ];
+        for (id, at) in events {
+            let mut event = IdeaEventRecord::now(Thing::from(&IdeaId::parse(id).unwrap()), IdeaEventKind::Edited, "");
+            event.at = at.parse().unwrap();
+            let _: Option<IdeaEventRecord> = db.create("idea_events").content(event).await.unwrap();
+        }
+        db.query("UPDATE $id SET deleted_at = time::now()")
+            .bind(("id", Thing::from(&IdeaId::parse(&trashed).unwrap())))
+            .await
+            .unwrap();
+
+        let dates = creation_dates(&db, None, None).await.unwrap();
+        let first: chrono::NaiveDate = "2024-03-01".parse().unwrap();
+        assert_eq!(dates, vec![first]);
+        let bars = creation_histogram(&dates, Bucket::Week, 2, "2024-03-06".parse().unwrap());
+        assert_eq!(bars.iter().map(|bar| bar.count).collect::<Vec<_>>(), vec![1, 0]);
+    }
+
+    #[cfg(feature = "dev-tools")]
+    #[tokio::test]
+    async fn test_reset_empties_the_idea_tables_and_keeps_users() {
+        use dioxus_surrealdb_template::dev_tools::server::reset_database;
+
+        let db = new_test_db().await;
+        let user = register(&db, "reset@example.com", "correct horse")
+            .await
+            .unwrap()
+            .id
+            .unwrap();
+        create_template(&db, Some(user.clone()), template_input("Weekly bet")).await.unwrap();
+        let kept = create_idea_id(&db, "Kept").await;
+        let trashed = create_idea_id(&db, "Trashed").await;
+        db.query("UPDATE $id SET deleted_at = time::now()")
+            .bind(("id", Thing::from(&IdeaId::parse(&trashed).unwrap())))
+            .await
+            .unwrap();
+        let event = IdeaEventRecord::now(Thing::from(&IdeaId::parse(&kept).unwrap()), IdeaEventKind::Edited, "");
+        let _: Option<IdeaEventRecord> = db.create("idea_events").content(event).await.unwrap();
+
+        let report = reset_database(&db, false).await.unwrap();
+        assert_eq!(report.ideas_removed, 2);
+        assert_eq!(report.ideas_seeded, 0);
+        let ideas: Vec<IdeaRecord> = db.select("ideas").await.unwrap();
+        assert!(ideas.is_empty());
+        let events: Vec<IdeaEventRecord> = db.select("idea_events").await.unwrap();
+        assert!(events.is_empty());
+        // Custom templates are gone and the built-ins are back
+        let templates = list_templates(&db, Some(&user)).await.unwrap();
+        assert_eq!(templates.len(), 2);
+        assert!(templates.iter().all(|template| template.builtin));
+        // The account survives
+        assert!(authenticate(&db, "reset@example.com", "correct horse").await.is_ok());
+
+        let report = reset_database(&db, true).await.unwrap();
+        assert_eq!(report.ideas_removed, 0);
+        assert_eq!(report.ideas_seeded, dioxus_surrealdb_template::sample::DEFAULT_SAMPLE_COUNT);
+        assert_eq!(active_ideas(&db, None).await.unwrap().len(), report.ideas_seeded);
+    }
+
+    #[tokio::test]
+    async fn test_ideas_by_author_include_records_from_before_authors() {
+        let db = new_test_db().await;
+        let repo = repo(&db);
+        let changes = |title: &str| IdeaChanges { title: title.to_string(), ..Default::default() };
+        for (title, author) in [
+            ("Sam's", Some("Sam")),
+            ("Alex's", Some("Alex")),
+            ("Also Sam's", Some(" Sam ")),
+            ("Anonymous", None),
+        ] {
+            submit_idea_content(&repo, None, author.map(String::from), changes(title)).await.unwrap();
+        }
+        // Stored before ideas had authors, so without the field at all
+        db.query(
+            "CREATE ideas CONTENT { title: 'Old', description: '', tags: [], what_must_be_true: [], \
+             development_notes: '', slug: 'old', previous_slugs: [], rank: 100.0 }",
+        )
+            .await
+            .unwrap()
+            .check()
+            .unwrap();
+
+        let sams = ideas_by_author(&db, None, &AuthorFilter::By("Sam".to_string())).await.unwrap();
+        assert_eq!(titles(&sams), vec!["Sam's", "Also Sam's"]);
+        let unattributed = ideas_by_author(&db, None, &AuthorFilter::Unattributed).await.unwrap();
+        assert_eq!(titles(&unattributed), vec!["Anonymous", "Old"]);
+        assert!(unattributed.iter().all(|record| record.author.is_none()));
+
+        let old = find_by_slug(&db, "old").await.unwrap().unwrap();
+        assert_eq!(Idea::from(old).author, None);
+        assert_eq!(idea_authors(&db, None).await.unwrap(), vec!["Alex", "Sam"]);
+    }
+
+    #[tokio::test]
+    async fn test_unreadable_records_are_left_out_and_reported() {
+        let db = new_test_db().await;
+        let repo = repo(&db);
+        for title in ["First", "Second"] {
+            submit_idea(&repo, None, title.to_string(), String::new(), Vec::new()).await.unwrap();
+        }
+        assert!(malformed_ideas(&db, None).await.unwrap().is_empty());
+
+        // A title of the wrong type, tags that aren't a list, and no title at all
+        db.query(
+            "CREATE ideas:numbered CONTENT { title: 42, description: '', tags: [], rank: 1.5 };
+             CREATE ideas:stringly CONTENT { title: 'Stringly', description: '', tags: 'a, b', rank: 1.6 };
+             CREATE ideas:untitled CONTENT { description: 'no title', tags: [], rank: 1.7 };",
+        )
+        .await
+        .unwrap()
+        .check()
+        .unwrap();
+
+        let titles_listed: Vec<String> = repo.list(None).await.unwrap().into_iter().map(|idea| idea.title).collect();
+        assert_eq!(titles_listed, vec!["First", "Second"]);
+
+        let malformed = malformed_ideas(&db, None).await.unwrap();
+        let ids: Vec<&str> = malformed.iter().map(|record| record.id.as_str()).collect();
+        assert_eq!(ids, vec!["ideas:numbered", "ideas:stringly", "ideas:untitled"]);
+        assert!(malformed.iter().all(|record| !record.error.is_empty()));
+
+        // Someone else's list doesn't count them
+        let other = Thing::from(("users", "someone"));
+        assert!(malformed_ideas(&db, Some(&other)).await.unwrap().is_empty());
+        assert!(matches!(
+            delete_malformed_idea(&db, Some(&other), "ideas:numbered").await,
+            Err(IdeaError::NotFound(_))
+        ));
+
+        delete_malformed_idea(&db, None, "ideas:numbered").await.unwrap();
+        delete_malformed_idea(&db, None, "ideas:stringly").await.unwrap();
+        delete_malformed_idea(&db, None, "ideas:untitled").await.unwrap();
+        assert!(malformed_ideas(&db, None).await.unwrap().is_empty());
+        assert_eq!(active_ideas(&db, None).await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_base_path_moves_every_route_below_it() {
+        use tower::ServiceExt;
+
+        let db = new_test_db().await;
+        let router = test_server(&db).merge(feed::server::router(db.clone()));
+        let server = mount_at_base_path(router, &normalize_base_path("ideas/"));
+        let status = |uri: &str| {
+            let request = axum::http::Request::get(uri).body(axum::body::Body::empty()).unwrap();
+            let server = server.clone();
+            async move { server.oneshot(request).await.unwrap().status() }
+        };
+
+        for uri in ["/ideas/probe", "/ideas/api/v1/ideas", "/ideas/api/v1/openapi.json", "/ideas/feed.xml"] {
+            assert_eq!(status(uri).await, axum::http::StatusCode::OK, "{}", uri);
+        }
+        for uri in ["/probe", "/api/v1/ideas", "/api/v1/openapi.json", "/feed.xml"] {
+            assert_eq!(status(uri).await, axum::http::StatusCode::NOT_FOUND, "{}", uri);
+        }
+
+        let body = serde_json::json!({ "title": "Behind the proxy" });
+        server_call(server.clone(), "POST", "/ideas/api/v1/ideas", Some(body)).await;
+        assert_eq!(server_call(server, "GET", "/ideas/probe", None).await, "1");
+    }
+
+    #[tokio::test]
+    async fn test_example_ideas_fill_an_empty_list() {
+        let db = new_test_db().await;
+        assert!(active_ideas(&db, None).await.unwrap().is_empty());
+
+        let created = submit_ideas(&repo(&db), None, example_ideas()).await.unwrap();
+        assert_eq!(created.len(), 3);
+        let stored = active_ideas(&db, None).await.unwrap();
+        assert_eq!(stored.len(), 3);
+        for (record, example) in created.iter().zip(example_ideas()) {
+            assert_eq!(record.title, example.title);
+            assert_eq!(record.status, example.status);
+            assert_eq!(record.what_must_be_true, example.what_must_be_true);
+            assert!(!record.slug.is_empty());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_every_pasted_id_shape_finds_the_idea() {
+        let db = new_test_db().await;
+        let id = create_idea_id(&db, "Findable").await;
+        let key = id.strip_prefix("ideas:").unwrap();
+
+        for mangled in [
+            key.to_string(),
+            id.clone(),
+            format!("ideas:⟨{}⟩", key),
+            percent_encode(&id),
+        ] {
+            let found = repo(&db).get(&mangled).await.unwrap();
+            assert_eq!(found.title, "Findable", "{}", mangled);
+            assert_eq!(IdeaId::from(found.id.as_ref().unwrap()).as_str(), id);
+            assert!(load_owned_idea(&db, &mangled, None).await.is_ok(), "{}", mangled);
+        }
+
+        assert_eq!(
+            repo(&db).get("ideas%3Anope").await.unwrap_err(),
+            IdeaError::NotFound("ideas:nope".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cached_list_is_fresh_after_every_write() {
+        let db = new_test_db().await;
+        let cache = IdeaCache::default();
+        let repo = repo(&db).with_cache(cache.clone());
+        let id = submit_idea(&repo, None, "Cached".to_string(), String::new(), Vec::new()).await.unwrap().id;
+        let id = IdeaId::from(&id.unwrap()).to_string();
+
+        assert_eq!(titles(&repo.list(None).await.unwrap()), vec!["Cached"]);
+        assert_eq!(cache.stats(), CacheStats { hits: 0, misses: 1, entries: 1 });
+        assert_eq!(titles(&repo.list(None).await.unwrap()), vec!["Cached"]);
+        assert_eq!(cache.stats(), CacheStats { hits: 1, misses: 1, entries: 1 });
+
+        let changes = IdeaChanges {
+            title: "Renamed".to_string(),
+            ..Default::default()
+        };
+        edit_idea(&repo, &id, None, changes).await.unwrap();
+        assert_eq!(cache.stats().entries, 0);
+        assert_eq!(titles(&repo.list(None).await.unwrap()), vec!["Renamed"]);
+
+        append_wmbt(&repo, &id, None, "It stays fresh").await.unwrap();
+        assert_eq!(repo.list(None).await.unwrap()[0].what_must_be_true, vec!["It stays fresh"]);
+
+        trash_idea(&repo, &id, None).await.unwrap();
+        assert!(repo.list(None).await.unwrap().is_empty());
+        restore_idea(&repo, &id, None).await.unwrap();
+        assert_eq!(titles(&repo.list(None).await.unwrap()), vec!["Renamed"]);
+        let second = submit_idea(&repo, None, "Second".to_string(), String::new(), Vec::new()).await.unwrap();
+        assert_eq!(titles(&repo.list(None).await.unwrap()), vec!["Renamed", "Second"]);
+        let second = IdeaId::from(&second.id.unwrap()).to_string();
+        merge_ideas(&repo, &id, &second, None).await.unwrap();
+        assert_eq!(titles(&repo.list(None).await.unwrap()), vec!["Renamed"]);
+    }
+
+    #[tokio::test]
+    async fn test_rest_list_sees_its_own_writes() {
+        let db = new_test_db().await;
+        let server = test_server(&db);
+
+        assert_eq!(server_call(server.clone(), "GET", "/api/v1/ideas", None).await, "[]");
+        let body = serde_json::json!({ "title": "Through REST" });
+        let created: serde_json::Value =
+            serde_json::from_str(&server_call(server.clone(), "POST", "/api/v1/ideas", Some(body)).await).unwrap();
+        let listed: Vec<serde_json::Value> =
+            serde_json::from_str(&server_call(server.clone(), "GET", "/api/v1/ideas", None).await).unwrap();
+        assert_eq!(listed.len(), 1);
+
+        let uri = format!("/api/v1/ideas/{}", percent_encode(created["id"].as_str().unwrap()));
+        let patch = serde_json::json!({ "title": "Patched" });
+        server_call(server.clone(), "PATCH", &uri, Some(patch)).await;
+        let listed: Vec<serde_json::Value> =
+            serde_json::from_str(&server_call(server.clone(), "GET", "/api/v1/ideas", None).await).unwrap();
+        assert_eq!(listed[0]["title"], "Patched");
+
+        server_call(server.clone(), "DELETE", &uri, None).await;
+        assert_eq!(server_call(server, "GET", "/api/v1/ideas", None).await, "[]");
+    }
+
+    /// `bundle` without what moving it changes: the ids of the idea and its links, its rank and the export time
+    fn bundle_content(mut bundle: IdeaBundle) -> IdeaBundle {
+        let old_id = bundle.idea.id.take().unwrap();
+        let own = |id: &mut String| {
+            if *id == old_id {
+                *id = "self".to_string();
+            }
+        };
+        for link in &mut bundle.links {
+            own(&mut link.from);
+            own(&mut link.to);
+        }
+        bundle.idea.rank = 0.0;
+        // An imported idea is stamped when it is created, whether or not its bundle had a creation time
+        bundle.idea.created_at = None;
+        bundle.exported_at = chrono::DateTime::default();
+        bundle
+    }
+
+    #[tokio::test]
+    async fn test_bundle_moves_an_idea_between_databases() {
+        let source = new_test_db().await;
+        let id = create_idea_id(&source, "Rain barrel").await;
+        let changes = IdeaChanges {
+            title: "Rain barrel".to_string(),
+            description: "Water the garden from the roof".to_string(),
+            tags: vec!["garden".to_string()],
+            what_must_be_true: vec!["It rains enough".to_string()],
+            development_notes: "Measure the downpipe".to_string(),
+            ..Default::default()
+        };
+        edit_idea(&repo(&source), &id, None, changes).await.unwrap();
+        add_attachment(&source, &id, None, "plan.png", "image/png", vec![1, 2, 3]).await.unwrap();
+        let other = create_idea_id(&source, "Garden").await;
+        source
+            .query(format!("RELATE {id}->relates_to->{other}"))
+            .await
+            .unwrap()
+            .check()
+            .unwrap();
+
+        let exported = export_bundle(&source, &id, None).await.unwrap();
+        assert_eq!(exported.schema_version, BUNDLE_SCHEMA_VERSION);
+        assert_eq!(exported.attachments.len(), 1);
+        assert_eq!(exported.links.len(), 1);
+        assert!(!exported.revisions.is_empty());
+        // Only the edit is logged: the fixture is created directly and attachments leave no event
+        assert_eq!(exported.history.len(), 1);
+        let text = serde_json::to_string(&exported).unwrap();
+
+        // Into an empty database, keeping the link although its target isn't there
+        let destination = new_test_db().await;
+        let imported = import_bundle(&repo(&destination), parse_bundle(&text).unwrap(), None, false)
+            .await
+            .unwrap();
+        let new_id = imported.idea.id.clone().unwrap();
+        assert_ne!(new_id, id);
+        assert_eq!(
+            (imported.attachments, imported.revisions, imported.history, imported.links, imported.links_dropped),
+            (1, exported.revisions.len(), exported.history.len(), 1, 0)
+        );
+        let round_trip = export_bundle(&destination, &new_id, None).await.unwrap();
+        assert_eq!(bundle_content(round_trip), bundle_content(exported.clone()));
+        assert_eq!(
+            attachments_for(&destination, &new_id, None).await.unwrap()[0].data.clone().into_inner(),
+            vec![1, 2, 3]
+        );
+
+        // Remapping drops the link to an idea the destination doesn't have
+        let remapped = new_test_db().await;
+        let imported = import_bundle(&repo(&remapped), parse_bundle(&text).unwrap(), None, true)
+            .await
+            .unwrap();
+        assert_eq!((imported.links, imported.links_dropped), (0, 1));
+        let new_id = imported.idea.id.unwrap();
+        assert!(export_bundle(&remapped, &new_id, None).await.unwrap().links.is_empty());
+
+        // Back into the source, where the target exists, the link is kept and points at the copy
+        let copy = import_bundle(&repo(&source), parse_bundle(&text).unwrap(), None, true)
+            .await
+            .unwrap();
+        assert_eq!((copy.links, copy.links_dropped), (1, 0));
+        assert_ne!(copy.idea.slug, exported.idea.slug);
+        let links = export_bundle(&source, copy.idea.id.as_ref().unwrap(), None).await.unwrap().links;
+        assert_eq!((links[0].from.as_str(), links[0].to.as_str()), (copy.idea.id.as_deref().unwrap(), other.as_str()));
+    }
+
+    #[tokio::test]
+    async fn test_bundle_from_a_newer_version_is_refused() {
+        let db = new_test_db().await;
+        let id = create_idea_id(&db, "Versioned").await;
+        let mut bundle = serde_json::to_value(export_bundle(&db, &id, None).await.unwrap()).unwrap();
+        bundle["schema_version"] = (BUNDLE_SCHEMA_VERSION + 1).into();
+
+        assert!(matches!(parse_bundle(&bundle.to_string()), Err(IdeaError::Validation(_))));
+        assert_eq!(active_ideas(&db, None).await.unwrap().len(), 1);
+    }
+
+    fn business_value_form() -> FormConfig {
+        FormConfig {
+            tags: FieldRule::Required,
+            custom_fields: vec![
+                CustomField {
+                    name: "business value".to_string(),
+                    kind: CustomFieldKind::Select,
+                    options: vec!["low".to_string(), "high".to_string()],
+                    required: true,
+                },
+                CustomField {
+                    name: "estimate".to_string(),
+                    kind: CustomFieldKind::Number,
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_form_config_is_stored_and_checked() {
+        let db = new_test_db().await;
+        assert_eq!(form_config(&db).await.unwrap(), FormConfig::default());
+
+        let stored = set_form_config(&db, business_value_form()).await.unwrap();
+        assert_eq!(form_config(&db).await.unwrap(), stored);
+        assert_eq!(stored, business_value_form());
+
+        // A broken schema is refused and the stored one stays
+        let mut broken = business_value_form();
+        broken.custom_fields[0].options.clear();
+        assert!(matches!(set_form_config(&db, broken).await, Err(IdeaError::Validation(_))));
+        assert_eq!(form_config(&db).await.unwrap(), stored);
+    }
+
+    #[tokio::test]
+    async fn test_custom_fields_are_stored_and_unknown_ones_still_load() {
+        let db = new_test_db().await;
+        let repo = repo(&db);
+        let form = set_form_config(&db, business_value_form()).await.unwrap();
+
+        let submitted = [
+            ("business value".to_string(), serde_json::json!("high")),
+            ("estimate".to_string(), serde_json::json!("1.5")),
+        ]
+        .into_iter()
+        .collect();
+        let values = form.validate("Saves a day a week", &["ops".to_string()], submitted).unwrap();
+        let content = IdeaChanges {
+            title: "Valued".to_string(),
+            description: "Saves a day a week".to_string(),
+            tags: vec!["ops".to_string()],
+            ..Default::default()
+        };
+        let created = submit_idea_with_fields(&repo, None, None, content, values.clone()).await.unwrap();
+        let id = IdeaId::from(created.id.as_ref().unwrap()).to_string();
+        let loaded = Idea::from(repo.get(&id).await.unwrap());
+        assert_eq!(loaded.custom_fields, values);
+        assert_eq!(loaded.custom_fields["estimate"], serde_json::json!(1.5));
+
+        // Missing required values never reach the database
+        assert!(matches!(
+            form.validate("Saves a day a week", &["ops".to_string()], Default::default()),
+            Err(IdeaError::Validation(message)) if message == "business value is required"
+        ));
+
+        // Values of fields the form doesn't have, in shapes it never had, and ideas from before custom fields
+        db.query(
+            "CREATE ideas CONTENT { title: 'Legacy', description: '', tags: [], slug: 'legacy', rank: 50.0, \
+             custom_fields: { cost_centre: 'R&D', history: { scores: [1, 2] } } };
+             CREATE ideas CONTENT { title: 'Older', description: '', tags: [], slug: 'older', rank: 60.0 };",
+        )
+        .await
+        .unwrap()
+        .check()
+        .unwrap();
+        let ideas: Vec<Idea> = repo.list(None).await.unwrap().into_iter().map(Idea::from).collect();
+        assert_eq!(ideas.len(), 3);
+        let legacy = ideas.iter().find(|idea| idea.title == "Legacy").unwrap();
+        assert_eq!(legacy.custom_fields["cost_centre"], serde_json::json!("R&D"));
+        assert_eq!(legacy.custom_fields["history"], serde_json::json!({ "scores": [1, 2] }));
+        assert!(ideas.iter().find(|idea| idea.title == "Older").unwrap().custom_fields.is_empty());
+        assert!(malformed_ideas(&db, None).await.unwrap().is_empty());
+    }
+
+    /// Submit an idea titled `title` through [`submit_once`] with `key`, at `clock`'s time
+    async fn submit_keyed(db: &Surreal<Any>, clock: &FixedClock, key: &str, title: &str) -> SubmitOutcome {
+        submit_keyed_as(db, clock, None, key, title).await
+    }
+
+    /// [`submit_keyed`] for `owner`
+    async fn submit_keyed_as(
+        db: &Surreal<Any>,
+        clock: &FixedClock,
+        owner: Option<&Thing>,
+        key: &str,
+        title: &str,
+    ) -> SubmitOutcome {
+        let repo = repo(db);
+        submit_once(db, &repo, clock, owner, Some(key), || async {
+            let content = IdeaChanges { title: title.to_string(), ..Default::default() };
+            let created = submit_idea_content(&repo, owner.cloned(), None, content).await?;
+            Ok(SubmitOutcome { created: Some(created.into()), possible_duplicates: Vec::new() })
+        })
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_repeated_submission_keys_create_one_idea() {
+        let db = new_test_db().await;
+        let clock = FixedClock(chrono::Utc::now());
+
+        // The response of the first submit was lost and the user clicked again
+        let first = submit_keyed(&db, &clock, "form-session-1", "Flaky").await.created.unwrap();
+        let retry = submit_keyed(&db, &clock, "form-session-1", "Flaky").await;
+        assert_eq!(retry.created.unwrap().id, first.id);
+        assert_eq!(repo(&db).list(None).await.unwrap().len(), 1);
+
+        // A new form session is a new idea, even with the same title
+        let second = submit_keyed(&db, &clock, "form-session-2", "Flaky").await.created.unwrap();
+        assert_ne!(second.id, first.id);
+        assert_eq!(repo(&db).list(None).await.unwrap().len(), 2);
+
+        // Malformed keys are refused before anything is written
+        let refused = submit_once(&db, &repo(&db), &clock, None, Some("not a key"), || async {
+            Err(IdeaError::Database("a refused key must not submit".to_string()))
+        })
+        .await;
+        assert!(matches!(refused, Err(IdeaError::Validation(_))));
+    }
+
+    #[tokio::test]
+    async fn test_submission_keys_are_kept_per_owner() {
+        let db = new_test_db().await;
+        let clock = FixedClock(chrono::Utc::now());
+        let alice = register(&db, "alice@example.com", "correct horse").await.unwrap().id.unwrap();
+        let bob = register(&db, "bob@example.com", "correct horse").await.unwrap().id.unwrap();
+
+        let first = submit_keyed_as(&db, &clock, Some(&alice), "shared-key", "Alice's").await.created.unwrap();
+        // Another user sending the same key gets an idea of their own, not Alice's
+        let other = submit_keyed_as(&db, &clock, Some(&bob), "shared-key", "Bob's").await.created.unwrap();
+        assert_ne!(other.id, first.id);
+        assert_eq!(other.title, "Bob's");
+        let retry = submit_keyed_as(&db, &clock, Some(&alice), "shared-key", "Alice's").await.created.unwrap();
+        assert_eq!(retry.id, first.id);
+        assert_eq!(repo(&db).list(Some(&bob)).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_expired_or_unused_submission_keys_allow_creating_again() {
+        let db = new_test_db().await;
+        let now = chrono::Utc::now();
+        let ttl = chrono::Duration::from_std(IDEMPOTENCY_TTL).unwrap();
+
+        let first = submit_keyed(&db, &FixedClock(now), "form-session", "Once").await.created.unwrap();
+        let almost = FixedClock(now + ttl - chrono::Duration::seconds(1));
+        let within = submit_keyed(&db, &almost, "form-session", "Once").await;
+        assert_eq!(within.created.unwrap().id, first.id);
+        let after = submit_keyed(&db, &FixedClock(now + ttl), "form-session", "Once").await.created.unwrap();
+        assert_ne!(after.id, first.id);
+        assert_eq!(repo(&db).list(None).await.unwrap().len(), 2);
+
+        // A submit held back for possible duplicates created nothing, so its key isn't used up
+        let repo = repo(&db);
+        let held_back = submit_once(&db, &repo, &FixedClock(now), None, Some("held-back"), || async {
+            Ok(SubmitOutcome { created: None, possible_duplicates: Vec::new() })
+        })
+        .await
+        .unwrap();
+        assert!(held_back.created.is_none());
+        let forced = submit_keyed(&db, &FixedClock(now), "held-back", "Forced").await.created.unwrap();
+        assert_eq!(forced.title, "Forced");
+
+        // The key of a deleted idea doesn't bring it back
+        repo.delete(forced.id.as_deref().unwrap()).await.unwrap();
+        let again = submit_keyed(&db, &FixedClock(now), "held-back", "Forced").await.created.unwrap();
+        assert_ne!(again.id, forced.id);
+    }
+
+    #[tokio::test]
+    async fn test_cursor_pages_skip_and_repeat_nothing_while_ideas_change() {
+        let db = new_test_db().await;
+        let repo = repo(&db);
+        // Stored before creation times were kept, or with the time as a string
+        db.query(
+            "CREATE ideas CONTENT { title: 'Legacy', description: '', tags: [] };
+             CREATE ideas CONTENT { title: 'Stringly', description: '', tags: [],
+                                    created_at: '2020-01-01T00:00:00Z' };",
+        )
+        .await
+        .unwrap()
+        .check()
+        .unwrap();
+        for title in ["One", "Two", "Three", "Four", "Five"] {
+            submit_idea(&repo, None, title.to_string(), String::new(), Vec::new()).await.unwrap();
+        }
+
+        let mut seen: Vec<String> = Vec::new();
+        let mut cursor: Option<String> = None;
+        let mut added = 0;
+        loop {
+            let (page, next) = ideas_after(&db, None, None, cursor.as_deref(), 2).await.unwrap();
+            assert!(page.len() <= 2);
+            seen.extend(page.iter().map(|idea| idea.title.clone()));
+            let Some(next) = next else { break };
+            cursor = Some(next);
+
+            // Ideas come and go between two requests
+            if added < 3 {
+                added += 1;
+                submit_idea(&repo, None, format!("Added {}", added), String::new(), Vec::new()).await.unwrap();
+            }
+            if added == 2 {
+                let trashed = repo.list(None).await.unwrap().into_iter().find(|idea| idea.title == "Five");
+                if let Some(trashed) = trashed {
+                    trash_idea(&repo, &IdeaId::from(trashed.id.as_ref().unwrap()).to_string(), None).await.unwrap();
+                }
+            }
+        }
+
+        // Oldest first, each idea once, and the ones added meanwhile at the end
+        assert_eq!(
+            seen,
+            vec!["Legacy", "Stringly", "One", "Two", "Three", "Four", "Added 1", "Added 2", "Added 3"]
+        );
+
+        // A made-up cursor is refused rather than starting over
+        assert!(matches!(ideas_after(&db, None, None, Some("bogus"), 2).await, Err(IdeaError::Validation(_))));
+    }
+
+    #[tokio::test]
+    async fn test_ideas_on_one_board_never_show_on_another() {
+        let db = new_test_db().await;
+        let work = create_board(&db, None, "Work").await.unwrap();
+        let home = create_board(&db, None, "Home").await.unwrap();
+        let on_work = repo(&db).in_board(board_thing(&work.slug));
+        let on_home = repo(&db).in_board(board_thing(&home.slug));
+        let on_default = repo(&db).in_board(default_board());
+        // Stored before boards existed
+        db.query("CREATE ideas CONTENT { title: 'Legacy plan', description: '', tags: [] }")
+            .await
+            .unwrap()
+            .check()
+            .unwrap();
+
+        submit_idea(&on_work, None, "Work plan".to_string(), String::new(), Vec::new()).await.unwrap();
+        submit_idea(&on_home, None, "Garden plan".to_string(), String::new(), Vec::new()).await.unwrap();
+
+        let titles = |ideas: Vec<IdeaRecord>| ideas.into_iter().map(|idea| idea.title).collect::<Vec<_>>();
+        assert_eq!(titles(on_work.list(None).await.unwrap()), ["Work plan"]);
+        assert_eq!(titles(on_home.list(None).await.unwrap()), ["Garden plan"]);
+        assert_eq!(titles(on_default.list(None).await.unwrap()), ["Legacy plan"]);
+        assert_eq!(titles(on_home.search(None, "plan").await.unwrap()), ["Garden plan"]);
+        assert_eq!(titles(on_work.search(None, "garden").await.unwrap()), Vec::<String>::new());
+
+        let (page, _) = ideas_after(&db, None, Some(&board_thing(&work.slug)), None, 10).await.unwrap();
+        assert_eq!(titles(page), ["Work plan"]);
+        let (page, _) = ideas_after(&db, None, Some(&default_board()), None, 10).await.unwrap();
+        assert_eq!(titles(page), ["Legacy plan"]);
+        let dates = creation_dates(&db, None, Some(&board_thing(&home.slug))).await.unwrap();
+        assert_eq!(dates.len(), 1);
+
+        // Unscoped callers like the REST API still see every board
+        assert_eq!(repo(&db).list(None).await.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_board_crud_keeps_slugs_and_owners() {
+        let db = new_test_db().await;
+        let user = register(&db, "boards@example.com", "correct horse").await.unwrap().id.unwrap();
+
+        let default = list_boards(&db, None).await.unwrap();
+        assert_eq!(default.len(), 1);
+        assert_eq!(default[0].slug, DEFAULT_BOARD_SLUG);
+        // Startup runs the schema again; the default board is not duplicated
+        dioxus_surrealdb_template::db::server::define_schema(&db).await.unwrap();
+        assert_eq!(list_boards(&db, None).await.unwrap().len(), 1);
+
+        let first = create_board(&db, Some(user.clone()), "Side projects").await.unwrap();
+        let second = create_board(&db, Some(user.clone()), "Side Projects").await.unwrap();
+        assert_eq!((first.slug.as_str(), second.slug.as_str()), ("side-projects", "side-projects-2"));
+        assert!(matches!(create_board(&db, None, " ").await, Err(IdeaError::Validation(_))));
+
+        let renamed = rename_board(&db, &first.slug, Some(&user), "Weekend").await.unwrap();
+        assert_eq!((renamed.name.as_str(), renamed.slug.as_str()), ("Weekend", "side-projects"));
+        assert_eq!(rename_board(&db, &first.slug, None, "Mine").await.unwrap_err(), IdeaError::Forbidden);
+
+        let names: Vec<String> = list_boards(&db, Some(&user)).await.unwrap().into_iter().map(|b| b.name).collect();
+        assert_eq!(names, ["Ideas", "Side Projects", "Weekend"]);
+        // Other people only see the default board
+        assert_eq!(list_boards(&db, None).await.unwrap().len(), 1);
+        assert!(load_board(&db, &second.slug, None).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_deleting_a_board_needs_it_empty_or_a_board_to_move_to() {
+        let db = new_test_db().await;
+        let doomed = create_board(&db, None, "Doomed").await.unwrap();
+        let keeper = create_board(&db, None, "Keeper").await.unwrap();
+        let on_doomed = repo(&db).in_board(board_thing(&doomed.slug));
+        let kept = submit_idea(&on_doomed, None, "Survivor".to_string(), String::new(), Vec::new()).await.unwrap();
+        let trashed = submit_idea(&on_doomed, None, "Binned".to_string(), String::new(), Vec::new()).await.unwrap();
+        trash_idea(&on_doomed, &IdeaId::from(trashed.id.as_ref().unwrap()).to_string(), None).await.unwrap();
+
+        assert!(matches!(delete_board(&db, &doomed.slug, None, None).await, Err(IdeaError::Validation(_))));
+        assert!(matches!(
+            delete_board(&db, &doomed.slug, None, Some(&doomed.slug)).await,
+            Err(IdeaError::Validation(_))
+        ));
+        assert!(matches!(
+            delete_board(&db, DEFAULT_BOARD_SLUG, None, Some(&keeper.slug)).await,
+            Err(IdeaError::Validation(_))
+        ));
+
+        // Trashed ideas move too, so restoring one doesn't strand it on a board that is gone
+        assert_eq!(delete_board(&db, &doomed.slug, None, Some(&keeper.slug)).await.unwrap(), 2);
+        assert!(load_board(&db, &doomed.slug, None).await.is_err());
+        let moved = repo(&db).get(&IdeaId::from(kept.id.as_ref().unwrap()).to_string()).await.unwrap();
+        assert_eq!(moved.board, Some(board_thing(&keeper.slug)));
+        let trashed = trashed_ideas(&db, None).await.unwrap();
+        assert_eq!(trashed[0].board, Some(board_thing(&keeper.slug)));
+
+        // The default board can take them as well, and an empty board goes without a target
+        assert_eq!(delete_board(&db, &keeper.slug, None, Some(DEFAULT_BOARD_SLUG)).await.unwrap(), 2);
+        let empty = create_board(&db, None, "Empty").await.unwrap();
+        assert_eq!(delete_board(&db, &empty.slug, None, None).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_note_sections_round_trip_and_survive_plain_edits() {
+        let db = new_test_db().await;
+        let ideas = repo(&db);
+        let created = submit_idea(&ideas, None, "Sectioned".to_string(), String::new(), Vec::new()).await.unwrap();
+        let id = IdeaId::from(created.id.as_ref().unwrap()).to_string();
+        assert!(created.sections.is_empty());
+
+        let sections = vec![
+            NoteSection {
+                heading: "  Problem ".to_string(),
+                body: "Bikes rust.\r\nQuickly.".to_string(),
+            },
+            NoteSection {
+                heading: "Experiment".to_string(),
+                body: "One meetup.".to_string(),
+            },
+        ];
+        let changes = rest::IdeaPatch {
+            sections: Some(sections),
+            development_notes: Some(String::new()),
+            ..Default::default()
+        }
+        .apply_to(&Idea::from(created));
+        let edited = edit_idea(&ideas, &id, None, changes).await.unwrap();
+        assert_eq!(edited.sections[0].heading, "Problem");
+        assert_eq!(edited.sections[0].body, "Bikes rust.\nQuickly.");
+
+        // The plain editor doesn't send sections, and saving it leaves them alone
+        let changes = IdeaChanges {
+            title: "Sectioned".to_string(),
+            development_notes: "Loose end".to_string(),
+            ..Default::default()
+        };
+        let edited = edit_idea(&ideas, &id, None, changes).await.unwrap();
+        assert_eq!(edited.development_notes, "Loose end");
+        assert_eq!(edited.sections.len(), 2);
+
+        let loaded = Idea::from(ideas.get(&id).await.unwrap());
+        assert_eq!(loaded.sections, edited.sections);
+        let backup = serde_json::to_string(&loaded).unwrap();
+        assert_eq!(serde_json::from_str::<Idea>(&backup).unwrap().sections, loaded.sections);
+    }
+
+    /// Call a server function over HTTP, the way the app's client does: a POST with the arguments as a JSON object.
+    /// Returns the status and the body, which is JSON on success and an encoded `IdeaError` on failure.
+    async fn server_fn_call(
+        db: &Surreal<surrealdb::engine::any::Any>,
+        client: &str,
+        path: &str,
+        args: serde_json::Value,
+    ) -> (axum::http::StatusCode, String) {
+        use dioxus_surrealdb_template::server_router::server_functions_router;
+        use http_body_util::BodyExt;
+        use tower::ServiceExt;
+
+        let request = axum::http::Request::builder()
+            .method("POST")
+            .uri(path)
+            .extension(peer(client))
+            .header("content-type", "application/json")
+            .body(axum::body::Body::from(args.to_string()))
+            .unwrap();
+        let response = server_functions_router(db.clone(), AppConfig::default()).oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        (status, String::from_utf8_lossy(&bytes).into_owned())
+    }
+
+    /// The [`IdeaError`] of a failed server function call, whose body is a `ServerFnError` as JSON
+    fn body_error(body: &str) -> Option<IdeaError> {
+        let error: serde_json::Value = serde_json::from_str(body).ok()?;
+        IdeaError::from_error(&error["message"].as_str()?)
+    }
+
+    #[tokio::test]
+    async fn test_server_functions_over_http() {
+        use axum::http::StatusCode;
+        let db = new_test_db().await;
+        let client = "server-fn-lifecycle";
+
+        let (status, body) = server_fn_call(
+            &db,
+            client,
+            "/api/ideas/submit",
+            serde_json::json!({
+                "submission": {
+                    "title": "Over the wire",
+                    "description": "Sent as JSON",
+                    "tags": ["http"],
+                    "what_must_be_true": ["The router works"],
+                    "author": null,
+                    "custom_fields": {},
+                },
+                "idempotency_key": null,
+                "force": false,
+            }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{}", body);
+        let outcome: SubmitOutcome = serde_json::from_str(&body).unwrap();
+        let created = outcome.created.unwrap();
+        let id = created.id.clone().unwrap();
+        assert!(id.starts_with("ideas:"));
+        assert_eq!(created.tags, vec!["http"]);
+
+        let (status, body) = server_fn_call(&db, client, "/api/ideas/all", serde_json::json!({})).await;
+        assert_eq!(status, StatusCode::OK, "{}", body);
+        let all: Vec<Idea> = serde_json::from_str(&body).unwrap();
+        assert_eq!(all.len(), 1);
+        assert_eq!(all[0].title, "Over the wire");
+
+        let (status, body) = server_fn_call(&db, client, "/api/ideas/get", serde_json::json!({ "id": id })).await;
+        assert_eq!(status, StatusCode::OK, "{}", body);
+        assert_eq!(serde_json::from_str::<Idea>(&body).unwrap().description, "Sent as JSON");
+
+        let (status, body) = server_fn_call(
+            &db,
+            client,
+            "/api/ideas/update",
+            serde_json::json!({
+                "id": id,
+                "edit": {
+                    "title": "Over the wire, edited",
+                    "description": "Sent as JSON",
+                    "tags": ["http"],
+                    "what_must_be_true": ["The router works", "Updates too"],
+                    "development_notes": "Notes",
+                    "due_date": "2024-07-01",
+                },
+                "client_id": "tab",
+            }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{}", body);
+        let updated: Idea = serde_json::from_str(&body).unwrap();
+        assert_eq!(updated.slug, "over-the-wire-edited");
+        assert_eq!(updated.what_must_be_true.len(), 2);
+        assert_eq!(updated.due_date, chrono::NaiveDate::from_ymd_opt(2024, 7, 1));
+
+        let (status, body) = server_fn_call(&db, client, "/api/ideas/delete", serde_json::json!({ "id": id })).await;
+        assert_eq!(status, StatusCode::OK, "{}", body);
+        let (_, body) = server_fn_call(&db, client, "/api/ideas/all", serde_json::json!({})).await;
+        assert!(serde_json::from_str::<Vec<Idea>>(&body).unwrap().is_empty());
+        assert_eq!(trashed_ideas(&db, None).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_admin_functions_refuse_visitors() {
+        let db = new_test_db().await;
+        create_idea_id(&db, "Not for visitors").await;
+
+        for (path, args) in [
+            ("/api/admin/backup", serde_json::json!({})),
+            ("/api/admin/restore", serde_json::json!({ "dump": "REMOVE TABLE ideas;", "wipe_first": true })),
+            ("/api/admin/normalize-tags", serde_json::json!({})),
+            ("/api/admin/maintenance", serde_json::json!({})),
+            ("/api/admin/snapshot", serde_json::json!({})),
+        ] {
+            let (_, body) = server_fn_call(&db, "admin-visitor", path, args).await;
+            assert_eq!(body_error(&body), Some(IdeaError::Unauthorized), "{}: {}", path, body);
+        }
+
+        let ideas: Vec<IdeaRecord> = db.select("ideas").await.unwrap();
+        assert_eq!(ideas.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_server_function_errors_keep_their_structure_over_http() {
+        use axum::http::StatusCode;
+        let db = new_test_db().await;
+        let client = "server-fn-errors";
+
+        // An id of another table is refused before the database is asked
+        let (status, body) =
+            server_fn_call(&db, client, "/api/ideas/get", serde_json::json!({ "id": "users:abc" })).await;
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(matches!(body_error(&body), Some(IdeaError::InvalidId(_))), "{}", body);
+
+        let (status, body) =
+            server_fn_call(&db, client, "/api/ideas/get", serde_json::json!({ "id": "ideas:missing" })).await;
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(matches!(body_error(&body), Some(IdeaError::NotFound(_))), "{}", body);
+
+        let (_, body) = server_fn_call(&db, client, "/api/ideas/delete", serde_json::json!({ "id": "ideas:missing" }))
+            .await;
+        assert!(matches!(body_error(&body), Some(IdeaError::NotFound(_))), "{}", body);
+
+        // Validation happens in the server function, so a blank title comes back as a structured error too
+        let (status, body) = server_fn_call(
+            &db,
+            client,
+            "/api/ideas/submit",
+            serde_json::json!({
+                "submission": {
+                    "title": "   ",
+                    "description": "",
+                    "tags": [],
+                    "what_must_be_true": [],
+                    "author": null,
+                    "custom_fields": {},
+                },
+                "idempotency_key": null,
+                "force": false,
+            }),
+        )
+        .await;
+        assert!(!status.is_success());
+        assert!(matches!(body_error(&body), Some(IdeaError::Validation(_))), "{}", body);
+
+        // Arguments that don't parse never reach the function
+        let (status, _) = server_fn_call(&db, client, "/api/ideas/get", serde_json::json!({ "id": 5 })).await;
+        assert!(!status.is_success());
+        assert!(active_ideas(&db, None).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_oversized_bodies_are_refused_before_anything_runs() {
+        use axum::http::StatusCode;
+        use dioxus_surrealdb_template::rate_limit::DEFAULT_MAX_PAYLOAD_BYTES;
+        let db = new_test_db().await;
+        let long = "x".repeat(DEFAULT_MAX_PAYLOAD_BYTES);
+
+        let (status, body) = server_fn_call(
+            &db,
+            "oversized-bodies",
+            "/api/ideas/submit",
+            serde_json::json!({
+                "submission": { "title": "Too long", "description": long },
+                "idempotency_key": null,
+                "force": true,
+            }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(body_error(&body), Some(IdeaError::PayloadTooLarge(DEFAULT_MAX_PAYLOAD_BYTES)));
+
+        // An import takes a whole export, so the same size is fine there
+        let idea = Idea {
+            title: "Imported".to_string(),
+            description: long.clone(),
+            ..Default::default()
+        };
+        let export = serde_json::to_string(&IdeaExport::new(vec![idea])).unwrap();
+        let (status, body) =
+            server_fn_call(&db, "oversized-bodies", "/api/ideas/import", serde_json::json!({ "export": export })).await;
+        assert_eq!(status, StatusCode::OK, "{}", body);
+
+        let (status, body) = rest_call(
+            &db,
+            "oversized-bodies",
+            "POST",
+            "/api/v1/ideas",
+            None,
+            Some(serde_json::json!({ "title": "Too long", "description": long })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(body["kind"], "PayloadTooLarge");
+        assert_eq!(body["detail"], DEFAULT_MAX_PAYLOAD_BYTES);
+        assert_eq!(titles(&active_ideas(&db, None).await.unwrap()), vec!["Imported"]);
+    }
+
+    #[tokio::test]
+    async fn test_snapshots_write_a_dated_dump_and_rotate_old_ones() {
+        let db = new_test_db().await;
+        create_idea_id(&db, "Snapshotted").await;
+        create_idea_id(&db, "Also snapshotted").await;
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["ideas-2026-01-01.json", "ideas-2026-01-02.json", "ideas-2026-01-03.json", "notes.txt"] {
+            std::fs::write(dir.path().join(name), "{}").unwrap();
+        }
+
+        let snapshots = Snapshots::new(SnapshotSettings { dir: dir.path().to_path_buf(), keep: 2 });
+        assert_eq!(snapshots.last_snapshot(), None);
+        let at = "2026-01-04T03:00:00Z".parse::<chrono::DateTime<chrono::Utc>>().unwrap();
+        let report = snapshots.take(&db, &FixedClock(at)).await.unwrap();
+        assert_eq!(report.file, "ideas-2026-01-04.json");
+        assert_eq!(report.ideas, 2);
+        assert_eq!(report.rotated, 2);
+        assert_eq!(snapshots.last_snapshot(), Some(at));
+
+        let written = std::fs::read(dir.path().join(&report.file)).unwrap();
+        assert_eq!(report.bytes, written.len() as u64);
+        let file: serde_json::Value = serde_json::from_slice(&written).unwrap();
+        assert_eq!(file["ideas"], 2);
+        assert!(file["dump"].as_str().unwrap().contains("Also snapshotted"));
+
+        let mut left: Vec<String> = std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        left.sort();
+        assert_eq!(left, ["ideas-2026-01-03.json", "ideas-2026-01-04.json", "notes.txt"]);
+    }
+
+    #[tokio::test]
+    async fn test_failed_snapshot_keeps_the_last_successful_time() {
+        let db = new_test_db().await;
+        let dir = tempfile::tempdir().unwrap();
+        // A file where the folder should be
+        let blocked = dir.path().join("blocked");
+        std::fs::write(&blocked, "").unwrap();
+
+        let snapshots = Snapshots::new(SnapshotSettings { dir: blocked, keep: 3 });
+        let result = snapshots.take(&db, &FixedClock(chrono::Utc::now())).await;
+        assert!(matches!(result, Err(IdeaError::Database(_))), "{:?}", result);
+        assert_eq!(snapshots.last_snapshot(), None);
+    }
+
+    #[tokio::test]
+    async fn test_find_archived_by_id_only_finds_trashed_ideas() {
+        let db = new_test_db().await;
+        let client = "find-archived";
+        let kept = create_idea_id(&db, "Kept").await;
+        let trashed = create_idea_id(&db, "Trashed").await;
+        trash_idea(&repo(&db), &trashed, None).await.unwrap();
+        let find = |id: &str| server_fn_call(&db, client, "/api/ideas/archived/find", serde_json::json!({ "id": id }));
+
+        let (status, body) = find(&trashed).await;
+        assert_eq!(status, axum::http::StatusCode::OK, "{}", body);
+        let found: Option<Idea> = serde_json::from_str(&body).unwrap();
+        assert_eq!(found.map(|idea| idea.title), Some("Trashed".to_string()));
+
+        for id in [kept.as_str(), "ideas:neverexisted"] {
+            let (status, body) = find(id).await;
+            assert_eq!(status, axum::http::StatusCode::OK, "{}", body);
+            assert_eq!(serde_json::from_str::<Option<Idea>>(&body).unwrap(), None, "{}", id);
+        }
+
+        let (_, body) = find("users:abc").await;
+        assert!(matches!(body_error(&body), Some(IdeaError::InvalidId(_))), "{}", body);
+    }
+
+    #[tokio::test]
+    async fn test_tag_report_ages_ideas_by_their_last_change() {
+        let db = new_test_db().await;
+        let seed = |title: &str, tags: &[&str], created_at: Option<&str>, statements: usize| IdeaRecord {
+            title: title.to_string(),
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            what_must_be_true: vec!["True".to_string(); statements],
+            created_at: created_at.map(|at| at.parse().unwrap()),
+            ..Default::default()
+        };
+        let mut ids = Vec::new();
+        for idea in [
+            seed("Both", &["ux", "api"], Some("2024-03-01T00:00:00Z"), 2),
+            // From before creation times were kept, and never logged
+            seed("Undated", &["ux"], None, 1),
+            seed("Old", &["api"], Some("2024-02-20T00:00:00Z"), 0),
+            seed("Trashed", &["ux"], Some("2024-01-01T00:00:00Z"), 4),
+            seed("Loose", &[], Some("2024-03-09T12:00:00Z"), 1),
+        ] {
+            let created: Option<IdeaRecord> = db.create("ideas").content(idea).await.unwrap();
+            ids.push(created.unwrap().id.unwrap());
+        }
+        for at in ["2024-03-05T00:00:00Z", "2024-03-03T00:00:00Z"] {
+            let mut event = IdeaEventRecord::now(ids[0].clone(), IdeaEventKind::Edited, "");
+            event.at = at.parse().unwrap();
+            let _: Option<IdeaEventRecord> = db.create("idea_events").content(event).await.unwrap();
+        }
+        db.query("UPDATE $id SET deleted_at = time::now()").bind(("id", ids[3].clone())).await.unwrap();
+
+        let ideas = tagged_ideas(&db, None, None).await.unwrap();
+        assert_eq!(ideas.len(), 4);
+        let report = tag_report(&ideas, "2024-03-10T00:00:00Z".parse().unwrap());
+        let rows: Vec<(&str, usize, usize, Option<f64>, usize)> = report
+            .iter()
+            .map(|row| (row.tag.as_str(), row.ideas, row.dated_ideas, row.average_age_days, row.statements))
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                // 5 days since the newest event of "Both", 19 since "Old" was created
+                ("api", 2, 2, Some(12.0), 2),
+                ("untagged", 1, 1, Some(0.5), 1),
+                // "Undated" counts as an idea but not towards the average
+                ("ux", 2, 1, Some(5.0), 3),
+            ]
+        );
+    }
+
+    /// Send `body` to the webhook of a server whose secret is "s3cret", as `client`
+    async fn webhook_call(
+        db: &Surreal<surrealdb::engine::any::Any>,
+        client: &str,
+        secret: Option<&str>,
+        body: &str,
+    ) -> (axum::http::StatusCode, serde_json::Value) {
+        webhook_call_with(db, AppConfig::default(), client, secret, body).await
+    }
+
+    /// [`webhook_call`] to a server with `config`
+    async fn webhook_call_with(
+        db: &Surreal<surrealdb::engine::any::Any>,
+        config: AppConfig,
+        client: &str,
+        secret: Option<&str>,
+        body: &str,
+    ) -> (axum::http::StatusCode, serde_json::Value) {
+        use dioxus_surrealdb_template::db::server::with_db;
+        use dioxus_surrealdb_template::webhook;
+        use http_body_util::BodyExt;
+        use tower::ServiceExt;
+
+        let mut request = axum::http::Request::builder()
+            .method("POST")
+            .uri("/api/hooks/idea")
+            .extension(peer(client))
+            .header("content-type", "application/json");
+        if let Some(secret) = secret {
+            request = request.header(webhook::WEBHOOK_SECRET_HEADER, secret);
+        }
+        let router = webhook::server::router(db.clone(), Some("s3cret".to_string())).layer(axum::Extension(config));
+        let router = with_db(router, db.clone());
+        let response = router
+            .oneshot(request.body(axum::body::Body::from(body.to_string())).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        (status, serde_json::from_slice(&bytes).unwrap_or(serde_json::Value::Null))
+    }
+
+    #[tokio::test]
+    async fn test_webhook_creates_ideas_with_the_right_secret() {
+        use axum::http::StatusCode;
+        let db = new_test_db().await;
+
+        let body = r#"{"title": "  From the shortcut  ", "description": "Sent from my phone", "tags": ["Inbox"]}"#;
+        let (status, created) = webhook_call(&db, "hook-ok", Some("s3cret"), body).await;
+        assert_eq!(status, StatusCode::CREATED, "{}", created);
+        let created: Idea = serde_json::from_value(created).unwrap();
+        // Cleaned up like the form's submissions
+        assert_eq!(created.title, "From the shortcut");
+        assert_eq!(created.slug, "from-the-shortcut");
+
+        let stored = active_ideas(&db, None).await.unwrap();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].description, "Sent from my phone");
+        assert_eq!(stored[0].owner, None);
+    }
+
+    #[tokio::test]
+    async fn test_webhook_refuses_wrong_secrets_and_bad_bodies() {
+        use axum::http::StatusCode;
+        let db = new_test_db().await;
+        let body = r#"{"title": "Sneaky"}"#;
+
+        for secret in [None, Some(""), Some("s3cre"), Some("s3cret!"), Some("S3CRET")] {
+            let (status, error) = webhook_call(&db, "hook-secrets", secret, body).await;
+            assert_eq!(status, StatusCode::UNAUTHORIZED, "{:?}", secret);
+            assert_eq!(error["kind"], "Unauthorized");
+        }
+
+        let (status, error) = webhook_call(&db, "hook-bodies", Some("s3cret"), "title=Sneaky").await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(error["fields"][0]["field"], "body");
+
+        let body = r#"{"title": "", "tags": "one,two"}"#;
+        let (status, error) = webhook_call(&db, "hook-bodies", Some("s3cret"), body).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        let fields: Vec<&str> = error["fields"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|field| field["field"].as_str().unwrap())
+            .collect();
+        assert_eq!(fields, ["title", "tags"]);
+        assert_eq!(error["kind"], "Validation");
+
+        assert!(active_ideas(&db, None).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_webhook_is_off_without_a_secret() {
+        use tower::ServiceExt;
+        let db = new_test_db().await;
+        let request = axum::http::Request::builder()
+            .method("POST")
+            .uri("/api/hooks/idea")
+            .header(dioxus_surrealdb_template::webhook::WEBHOOK_SECRET_HEADER, "")
+            .body(axum::body::Body::from(r#"{"title": "Nobody listens"}"#))
+            .unwrap();
+        let router = dioxus_surrealdb_template::webhook::server::router(db.clone(), None);
+        let response = router.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), axum::http::StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_v1_export_imports_under_its_old_ids() {
+        let db = new_test_db().await;
+        let ideas = read_export(include_str!("fixtures/v1_export.json")).unwrap();
+        let summary = import_ideas(&repo(&db), None, ideas).await.unwrap();
+        assert_eq!((summary.imported, summary.skipped.len()), (4, 0));
+
+        let stored = repo(&db).get("ideas:seed-library").await.unwrap();
+        assert_eq!(stored.title, "Seed library");
+        assert_eq!(stored.development_notes, "Ask the librarian about shelf space");
+        assert!(repo(&db).get("ideas:bike-repair").await.unwrap().pinned);
+        assert_eq!(repo(&db).get("ideas:rooftop").await.unwrap().description, "");
+
+        // Importing it again adds nothing, and says so for each idea that had an id
+        let ideas = read_export(include_str!("fixtures/v1_export.json")).unwrap();
+        let again = import_ideas(&repo(&db), None, ideas).await.unwrap();
+        assert_eq!((again.imported, again.skipped.len()), (1, 3));
+        assert_eq!(active_ideas(&db, None).await.unwrap().len(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_export_round_trips_into_another_database() {
+        let source = new_test_db().await;
+        let idea = Idea {
+            title: "Rain barrel".to_string(),
+            description: "Water the garden from the roof".to_string(),
+            what_must_be_true: vec!["It rains enough".to_string()],
+            ..Default::default()
+        };
+        let created = submit_ideas(&repo(&source), None, vec![idea]).await.unwrap();
+        let id: String = IdeaId::from(created[0].id.as_ref().unwrap()).into();
+
+        let export = export_ideas(&repo(&source), None).await.unwrap();
+        assert_eq!(export.version, EXPORT_VERSION);
+        let text = serde_json::to_string(&export).unwrap();
+
+        let destination = new_test_db().await;
+        let summary = import_ideas(&repo(&destination), None, read_export(&text).unwrap()).await.unwrap();
+        assert_eq!(summary.imported, 1);
+        let copied = repo(&destination).get(&id).await.unwrap();
+        assert_eq!(copied.what_must_be_true, ["It rains enough"]);
+        assert_eq!(copied.created_at, repo(&source).get(&id).await.unwrap().created_at);
+    }
+
+    /// A trashed idea with one attachment, one link to `other`, one revision and its history, and that other idea
+    async fn idea_with_one_of_each(db: &Surreal<Any>) -> (String, String) {
+        let id = create_idea_id(db, "Doomed").await;
+        let other = create_idea_id(db, "Survivor").await;
+        add_attachment(db, &id, None, "plan.png", "image/png", vec![1, 2, 3]).await.unwrap();
+        db.query(format!("RELATE {id}->relates_to->{other}")).await.unwrap().check().unwrap();
+        let thing = Thing::from(&IdeaId::parse(&id).unwrap());
+        let revision = IdeaRevisionRecord::now(thing, vec![], "first draft".to_string());
+        repo(db).save_revision(revision).await.unwrap();
+        trash_idea(&repo(db), &id, None).await.unwrap();
+        (id, other)
+    }
+
+    #[tokio::test]
+    async fn test_delete_impact_counts_what_goes_with_an_idea() {
+        let db = new_test_db().await;
+        let (id, other) = idea_with_one_of_each(&db).await;
+        let one_of_each = DeleteImpact { attachments: 1, links: 1, revisions: 1 };
+
+        assert_eq!(delete_impact(&repo(&db), &id, None).await.unwrap(), one_of_each);
+        // The link counts for the other end too, but nothing else of this idea does
+        let other_impact = delete_impact(&repo(&db), &other, None).await.unwrap();
+        assert_eq!(other_impact, DeleteImpact { links: 1, ..Default::default() });
+    }
+
+    #[tokio::test]
+    async fn test_purge_removes_everything_attached_and_reports_it() {
+        let db = new_test_db().await;
+        let (id, other) = idea_with_one_of_each(&db).await;
+
+        let impact = purge_idea(&repo(&db), &id, None).await.unwrap();
+        assert_eq!(impact, DeleteImpact { attachments: 1, links: 1, revisions: 1 });
+
+        for table in ["attachments", "relates_to", "idea_revisions", "idea_events"] {
+            let thing = Thing::from(&IdeaId::parse(&id).unwrap());
+            let left: Option<u64> = db
+                .query(format!("SELECT count() AS count FROM {table} WHERE idea = $id OR in = $id GROUP ALL"))
+                .bind(("id", thing))
+                .await
+                .unwrap()
+                .take((0, "count"))
+                .unwrap();
+            assert_eq!(left.unwrap_or(0), 0, "{}", table);
+        }
+        assert!(load_owned_idea(&db, &other, None).await.is_ok());
+        assert_eq!(delete_impact(&repo(&db), &other, None).await.unwrap(), DeleteImpact::default());
+
+        // Purging it again finds nothing and removes nothing
+        assert_eq!(purge_idea(&repo(&db), &id, None).await.unwrap_err(), IdeaError::NotFound(id));
+    }
+
+    #[tokio::test]
+    async fn test_failed_transaction_writes_nothing() {
+        let db = new_test_db().await;
+        let result = with_transaction(&db, |txn| {
+            txn.push("CREATE ideas CONTENT { title: 'First' }");
+            txn.push("THROW 'injected'");
+            txn.push("CREATE ideas CONTENT { title: 'Second' }");
+            Ok(())
+        })
+        .await;
+        assert!(matches!(result, Err(IdeaError::Database(_))));
+
+        // A closure that fails part way sends nothing at all
+        let result = with_transaction(&db, |txn| {
+            txn.push("CREATE ideas CONTENT { title: 'Third' }");
+            Err::<(), _>(IdeaError::Validation("not this time".to_string()))
+        })
+        .await;
+        assert!(matches!(result, Err(IdeaError::Validation(_))));
+
+        let ideas: Vec<IdeaRecord> = db.select("ideas").await.unwrap();
+        assert!(ideas.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_transaction_results_are_indexed_from_the_first_statement() {
+        let db = new_test_db().await;
+        let ((first, second), mut response) = with_transaction(&db, |txn| {
+            txn.bind("title", "Bound");
+            let first = txn.push("CREATE ideas CONTENT { title: $title }");
+            let second = txn.push("SELECT VALUE title FROM ideas");
+            Ok((first, second))
+        })
+        .await
+        .unwrap();
+        assert_eq!((first, second), (0, 1));
+        let titles: Vec<String> = response.take(second).unwrap();
+        assert_eq!(titles, vec!["Bound".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_failed_bundle_import_leaves_nothing_behind() {
+        let source = new_test_db().await;
+        let id = create_idea_id(&source, "Half imported").await;
+        let mut bundle = export_bundle(&source, &id, None).await.unwrap();
+        bundle.attachments.push(BundledAttachment {
+            filename: "run.exe".to_string(),
+            mime_type: "application/x-msdownload".to_string(),
+            data: "AQID".to_string(),
+        });
+
+        let destination = new_test_db().await;
+        let result = import_bundle(&repo(&destination), bundle, None, false).await;
+        assert!(matches!(result, Err(IdeaError::Validation(_))));
+        let ideas: Vec<IdeaRecord> = destination.select("ideas").await.unwrap();
+        assert!(ideas.is_empty());
+        let events: Vec<IdeaEventRecord> = destination.select("idea_events").await.unwrap();
+        assert!(events.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_kept_ideas_leave_the_review() {
+        let db = new_test_db().await;
+        let long_ago = chrono::Utc::now() - chrono::Duration::days(90);
+        let mut ids = Vec::new();
+        for title in ["Dusty", "Forgotten", "Binned", "Finished"] {
+            let record = IdeaRecord {
+                created_at: Some(long_ago),
+                ..create_test_idea(title, "Untouched")
+            };
+            let created: Option<IdeaRecord> = db.create("ideas").content(record).await.unwrap();
+            ids.push(IdeaId::from(&created.unwrap().id.unwrap()).to_string());
+        }
+        create_idea_id(&db, "Unknown age").await;
+        submit_idea(&repo(&db), None, "Fresh".to_string(), String::new(), vec![]).await.unwrap();
+        trash_idea(&repo(&db), &ids[2], None).await.unwrap();
+        set_status(&repo(&db), &ids[3], None, IdeaStatus::Done).await.unwrap();
+
+        let stale = stale_ideas_for(&db, None, &default_board(), STALE_DAYS, chrono::Utc::now()).await.unwrap();
+        let titles: Vec<&str> = stale.iter().map(|stale| stale.idea.title.as_str()).collect();
+        assert_eq!(titles, vec!["Unknown age", "Dusty", "Forgotten"]);
+
+        let before: Vec<IdeaRecord> = db.select("ideas").await.unwrap();
+        touch_idea(&repo(&db), &ids[0], None).await.unwrap();
+        let after: Vec<IdeaRecord> = db.select("ideas").await.unwrap();
+        assert_eq!(
+            after.into_iter().map(Idea::from).collect::<Vec<_>>(),
+            before.into_iter().map(Idea::from).collect::<Vec<_>>()
+        );
+        let stale = stale_ideas_for(&db, None, &default_board(), STALE_DAYS, chrono::Utc::now()).await.unwrap();
+        let titles: Vec<&str> = stale.iter().map(|stale| stale.idea.title.as_str()).collect();
+        assert_eq!(titles, vec!["Unknown age", "Forgotten"]);
+    }
+
+    #[tokio::test]
+    async fn test_racing_tag_changes_all_land() {
+        let db = new_test_db().await;
+        let repo = repo(&db);
+        let idea = submit_idea(&repo, None, "Raced".to_string(), String::new(), vec!["old".to_string()])
+            .await
+            .unwrap();
+        let id = IdeaId::from(&idea.id.unwrap()).to_string();
+
+        // Each change reads the tags and writes them back, so without the compare-and-set most would be lost
+        let mut changes: Vec<TagChange> = (0..3).map(|n| TagChange::Add(format!("tag{n}"))).collect();
+        changes.push(TagChange::Remove("old".to_string()));
+        let racing = changes.into_iter().map(|change| change_tags(&repo, &id, None, change));
+        let results = futures::future::join_all(racing).await;
+        assert!(results.iter().all(Result::is_ok), "{results:?}");
+
+        let stored = repo.get(&id).await.unwrap();
+        let expected: Vec<String> = (0..3).map(|n| format!("tag{n}")).collect();
+        assert_eq!(stored.tags, expected);
+        let history = repo.history(stored.id.as_ref().unwrap()).await.unwrap();
+        assert_eq!(history.iter().filter(|event| event.kind == IdeaEventKind::Edited).count(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_digest_covers_the_period() {
+        let db = new_test_db().await;
+        let repo = repo(&db);
+        let start = chrono::Utc::now();
+        let rocket = submit_idea(&repo, None, "Rocket".to_string(), "To the moon".to_string(), vec![]).await.unwrap();
+        let id = IdeaId::from(&rocket.id.unwrap()).to_string();
+        set_status(&repo, &id, None, IdeaStatus::Building).await.unwrap();
+        append_wmbt(&repo, &id, None, "Fuel is cheap").await.unwrap();
+        append_wmbt(&repo, &id, None, "Nobody minds the noise").await.unwrap();
+        let alice = Some(Thing::from(("users", "alice")));
+        submit_idea(&repo, alice, "Private".to_string(), String::new(), vec![]).await.unwrap();
+
+        let digest = digest_for(&db, None, start, chrono::Utc::now()).await.unwrap();
+        assert_eq!(digest.new_ideas.iter().map(|idea| idea.title.as_str()).collect::<Vec<_>>(), vec!["Rocket"]);
+        assert_eq!(digest.status_changes.len(), 1);
+        assert_eq!(digest.statements[0].count, 2);
+        let text = digest.to_text("http://localhost:8080");
+        assert!(text.contains("* Rocket: now building"), "{}", text);
+
+        // Nothing happened after everything above
+        let later = chrono::Utc::now() + chrono::Duration::seconds(1);
+        assert!(digest_for(&db, None, later, later).await.unwrap().is_empty());
+        assert!(matches!(
+            digest_for(&db, None, later, start).await,
+            Err(IdeaError::Validation(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_storage_usage_counts_notes_and_attachments() {
+        let db = new_test_db().await;
+        assert_eq!(storage_usage(&db).await.unwrap(), StorageUsage::default());
+
+        let id = create_idea_id(&db, "Sketchbook").await;
+        let notes = IdeaRecord {
+            // Bytes, not characters: "é" takes two
+            development_notes: "café".to_string(),
+            sections: vec![NoteSection {
+                heading: "Plan".to_string(),
+                body: "draw".to_string(),
+            }],
+            ..create_test_idea("Notebook", "With notes")
+        };
+        let _: Option<IdeaRecord> = db.create("ideas").content(notes).await.unwrap();
+        add_attachment(&db, &id, None, "sketch.png", "image/png", vec![0; 300]).await.unwrap();
+        trash_idea(&repo(&db), &id, None).await.unwrap();
+
+        let usage = storage_usage(&db).await.unwrap();
+        assert_eq!(
+            usage,
+            StorageUsage {
+                ideas: 2,
+                notes_bytes: 9,
+                attachment_bytes: 300,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_storage_limit_warns_then_refuses() {
+        let db = new_test_db().await;
+        let limits = StorageLimits {
+            max_ideas: Some(5),
+            ..Default::default()
+        };
+        // Measured on every check, so the ideas created below are seen right away
+        let cache = UsageCache::with_ttl(std::time::Duration::ZERO);
+        let one_idea = StorageUsage {
+            ideas: 1,
+            ..Default::default()
+        };
+
+        for n in 1..=5 {
+            check_room(&db, &cache, &limits, &one_idea).await.unwrap();
+            create_idea_id(&db, &format!("Idea {}", n)).await;
+            let report = StorageReport {
+                usage: storage_usage(&db).await.unwrap(),
+                limits,
+            };
+            let expected = match n {
+                1..=3 => LimitLevel::Fine,
+                4 => LimitLevel::Warning,
+                _ => LimitLevel::Full,
+            };
+            assert_eq!(report.level(), expected, "after {} ideas", n);
+        }
+
+        assert_eq!(
+            check_room(&db, &cache, &limits, &one_idea).await,
+            Err(IdeaError::StorageFull("ideas".to_string()))
+        );
+        // Without limits the same database takes more
+        check_room(&db, &cache, &StorageLimits::default(), &one_idea).await.unwrap();
+
+        // The webhook is held to the same limits
+        let config = AppConfig { limits, ..Default::default() };
+        let body = r#"{"title": "One too many", "description": "No room left"}"#;
+        let (status, error) = webhook_call_with(&db, config, "hook-full", Some("s3cret"), body).await;
+        assert_eq!(status, axum::http::StatusCode::INSUFFICIENT_STORAGE, "{}", error);
+        assert_eq!(error["kind"], "StorageFull");
+        assert_eq!(active_ideas(&db, None).await.unwrap().len(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_cached_usage_counts_checked_writes() {
+        let db = new_test_db().await;
+        let limits = StorageLimits {
+            max_ideas: Some(2),
+            ..Default::default()
+        };
+        let cache = UsageCache::default();
+        let one_idea = StorageUsage {
+            ideas: 1,
+            ..Default::default()
+        };
+
+        // Nothing is written, yet the two accepted checks fill the cached usage before it is measured again
+        check_room(&db, &cache, &limits, &one_idea).await.unwrap();
+        check_room(&db, &cache, &limits, &one_idea).await.unwrap();
+        assert!(matches!(
+            check_room(&db, &cache, &limits, &one_idea).await,
+            Err(IdeaError::StorageFull(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_search_finds_notes_with_a_snippet() {
+        let db = new_test_db().await;
+        let repo = repo(&db);
+        let id = create_idea_id(&db, "Garden").await;
+        let changes = IdeaChanges {
+            title: "Garden".to_string(),
+            development_notes: format!("{} Ask the neighbours about their compost heap.", "Soil first. ".repeat(20)),
+            ..Default::default()
+        };
+        edit_idea(&repo, &id, None, changes).await.unwrap();
+        create_idea_id(&db, "Compost bin").await;
+        create_idea_id(&db, "Bike").await;
+
+        let found = repo.search(None, "COMPOST").await.unwrap();
+        let mut hits: Vec<_> = found
+            .into_iter()
+            .filter_map(|record| search_hit(&Idea::from(record), "COMPOST"))
+            .collect();
+        hits.sort_by(|a, b| a.idea.title.cmp(&b.idea.title));
+        let fields: Vec<_> = hits.iter().map(|hit| (hit.idea.title.as_str(), hit.field)).collect();
+        assert_eq!(fields, vec![("Compost bin", MatchedField::Title), ("Garden", MatchedField::Notes)]);
+
+        let notes = &hits[1];
+        assert!(notes.snippet.starts_with('…'), "{}", notes.snippet);
+        let (from, to) = notes.ranges[0];
+        assert_eq!(&notes.snippet[from..to], "compost");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_drained_waits_for_requests_in_flight() {
+        let in_flight = InFlight::default();
+        for seconds in [1, 3] {
+            let guard = in_flight.enter().unwrap();
+            tokio::spawn(async move {
+                tokio::time::sleep(std::time::Duration::from_secs(seconds)).await;
+                drop(guard);
+            });
+        }
+        assert_eq!(in_flight.running(), 2);
+        in_flight.close();
+        assert!(in_flight.enter().is_none());
+        assert_eq!(in_flight.running(), 2);
+
+        let started = tokio::time::Instant::now();
+        assert!(in_flight.drained(std::time::Duration::from_secs(10)).await);
+        assert_eq!(started.elapsed(), std::time::Duration::from_secs(3));
+        assert_eq!(in_flight.running(), 0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_drained_gives_up_after_the_timeout() {
+        let in_flight = InFlight::default();
+        let guard = in_flight.enter().unwrap();
+        let stuck = tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_secs(60)).await;
+            drop(guard);
+        });
+
+        let started = tokio::time::Instant::now();
+        assert!(!in_flight.drained(std::time::Duration::from_secs(2)).await);
+        assert_eq!(started.elapsed(), std::time::Duration::from_secs(2));
+        assert_eq!(in_flight.running(), 1);
+        stuck.abort();
+        let _ = stuck.await;
+        assert_eq!(in_flight.running(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_closed_router_finishes_running_requests_and_refuses_new_ones() {
+        use tower::ServiceExt;
+        let (started_tx, started_rx) = tokio::sync::oneshot::channel::<()>();
+        let started_tx = std::sync::Arc::new(std::sync::Mutex::new(Some(started_tx)));
+        let slow = axum::routing::get(move || {
+            let started_tx = started_tx.clone();
+            async move {
+                if let Some(tx) = started_tx.lock().unwrap().take() {
+                    let _ = tx.send(());
+                }
+                tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+                "done"
+            }
+        });
+        let in_flight = InFlight::default();
+        let router = with_in_flight(axum::Router::new().route("/slow", slow), in_flight.clone());
+        let request = || axum::http::Request::get("/slow").body(axum::body::Body::empty()).unwrap();
+
+        let running = tokio::spawn(router.clone().oneshot(request()));
+        started_rx.await.unwrap();
+        assert_eq!(in_flight.running(), 1);
+        in_flight.close();
+
+        let refused = router.oneshot(request()).await.unwrap();
+        assert_eq!(refused.status(), axum::http::StatusCode::SERVICE_UNAVAILABLE);
+        assert!(in_flight.drained(std::time::Duration::from_secs(5)).await);
+        assert_eq!(running.await.unwrap().unwrap().status(), axum::http::StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_snapshot_includes_the_last_write() {
+        let db = new_test_db().await;
+        let dir = tempfile::tempdir().unwrap();
+        let snapshots = Snapshots::new(SnapshotSettings { dir: dir.path().to_path_buf(), keep: 2 });
+        let in_flight = InFlight::default();
+        let guard = in_flight.enter().unwrap();
+        let writer = db.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+            create_idea_id(&writer, "Written while shutting down").await;
+            drop(guard);
+        });
+
+        let settings = ShutdownSettings { timeout: std::time::Duration::from_secs(5), snapshot: true };
+        assert!(shut_down(&db, &in_flight, &settings, Some(&snapshots), None).await);
+        assert!(in_flight.is_closed());
+        assert!(snapshots.last_snapshot().is_some());
+        let file = std::fs::read_dir(dir.path()).unwrap().next().unwrap().unwrap().path();
+        let file: serde_json::Value = serde_json::from_slice(&std::fs::read(file).unwrap()).unwrap();
+        assert_eq!(file["ideas"], 1);
+    }
+
+    #[tokio::test]
+    async fn test_serve_returns_after_shutting_down_and_drops_the_router() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        let held = std::sync::Arc::new(());
+        let router = axum::Router::new()
+            .route("/ping", axum::routing::get(|| async { "pong" }))
+            .layer(axum::Extension(held.clone()));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let (signal, signalled) = tokio::sync::oneshot::channel::<()>();
+        let served = tokio::spawn(serve(listener, router, async {
+            signalled.await.ok();
+            true
+        }));
+
+        // A kept-alive connection stays open after its request
+        let mut client = tokio::net::TcpStream::connect(address).await.unwrap();
+        client.write_all(b"GET /ping HTTP/1.1\r\nHost: localhost\r\n\r\n").await.unwrap();
+        let mut response = [0; 256];
+        let read = client.read(&mut response).await.unwrap();
+        assert!(String::from_utf8_lossy(&response[..read]).ends_with("pong"));
+
+        signal.send(()).unwrap();
+        let drained = tokio::time::timeout(std::time::Duration::from_secs(5), served).await.unwrap();
+        assert!(drained.unwrap().unwrap());
+        // Every router is gone, and with them their handles on the database
+        assert_eq!(std::sync::Arc::strong_count(&held), 1);
+    }
+
+    /// Send `method` to `uri` on a public (read-only) deployment of `db` without a session, returning status and body.
+    /// The live events stream never ends, so its body is only read when the request was refused.
+    async fn public_call(
+        db: &Surreal<surrealdb::engine::any::Any>,
+        method: &str,
+        uri: &str,
+        args: Option<serde_json::Value>,
+    ) -> (axum::http::StatusCode, String) {
+        use dioxus_surrealdb_template::server_router::server_functions_router;
+        use http_body_util::BodyExt;
+        use tower::ServiceExt;
+
+        let request = axum::http::Request::builder()
+            .method(method)
+            .uri(uri)
+            .extension(peer("public-visitor"))
+            .header("content-type", "application/json")
+            .body(axum::body::Body::from(args.map(|json| json.to_string()).unwrap_or_default()))
+            .unwrap();
+        let config = AppConfig { read_only: true, ..Default::default() };
+        let response = server_functions_router(db.clone(), config).oneshot(request).await.unwrap();
+        let status = response.status();
+        if uri.ends_with("/events") && status.is_success() {
+            return (status, String::new());
+        }
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        (status, String::from_utf8_lossy(&bytes).into_owned())
+    }
+
+    #[tokio::test]
+    async fn test_public_deployment_hides_private_ideas() {
+        use axum::http::StatusCode;
+        use dioxus_surrealdb_template::db::Visibility;
+        use dioxus_surrealdb_template::db::server::set_visibility;
+
+        let db = new_test_db().await;
+        let repo = repo(&db);
+        let shown = submit_idea(&repo, None, "Shown fixture".into(), "Plain fixture".into(), vec!["fixture".into()])
+            .await
+            .unwrap();
+        let shown_id = IdeaId::from(shown.id.as_ref().unwrap()).to_string();
+        set_visibility(&repo, &shown_id, None, Visibility::Public).await.unwrap();
+        let secret = submit_idea(
+            &repo,
+            None,
+            "Secret fixture".into(),
+            "Secret plans".into(),
+            vec!["fixture".into(), "secret-tag".into()],
+        )
+        .await
+        .unwrap();
+        let secret_id = IdeaId::from(secret.id.as_ref().unwrap()).to_string();
+        let trashed = submit_idea(&repo, None, "Secret trash".into(), "Gone".into(), vec![]).await.unwrap();
+        let trashed_id = IdeaId::from(trashed.id.as_ref().unwrap()).to_string();
+        trash_idea(&repo, &trashed_id, None).await.unwrap();
+        db.query("UPDATE ideas SET author = 'Secret author', due_date = '2030-01-01' WHERE title CONTAINS 'Secret'")
+            .await
+            .unwrap();
+        db.query("UPDATE ideas SET author = 'Ann', due_date = '2030-01-01' WHERE title = 'Shown fixture'")
+            .await
+            .unwrap();
+
+        let filter = serde_json::to_value(dioxus_surrealdb_template::utils::IdeaFilter::default()).unwrap();
+        let lists = [
+            ("/api/ideas/all", serde_json::json!({})),
+            ("/api/ideas/summaries", serde_json::json!({})),
+            ("/api/ideas/search", serde_json::json!({ "query": "fixture", "limit": 10 })),
+            ("/api/ideas/page", serde_json::json!({ "filter": filter, "offset": 0, "limit": 50 })),
+            ("/api/ideas/after", serde_json::json!({ "cursor": null, "limit": 50 })),
+            ("/api/ideas/graph", serde_json::json!({})),
+            ("/api/ideas/due-before", serde_json::json!({ "date": "2031-01-01" })),
+            ("/api/ideas/by-tags", serde_json::json!({ "tags": ["fixture"], "mode": "any" })),
+            ("/api/ideas/by-author", serde_json::json!({ "name": null })),
+            ("/api/ideas/authors", serde_json::json!({})),
+            ("/api/ideas/stale", serde_json::json!({ "days": 1 })),
+            ("/api/stats/tags", serde_json::json!({})),
+            ("/api/digest", serde_json::json!({ "since": "2000-01-01T00:00:00Z" })),
+            ("/api/ideas/export", serde_json::json!({})),
+        ];
+        for (path, args) in lists {
+            let (status, body) = public_call(&db, "POST", path, Some(args)).await;
+            assert_eq!(status, StatusCode::OK, "{}: {}", path, body);
+            assert!(!body.contains("Secret") && !body.contains("secret"), "{} leaks: {}", path, body);
+        }
+        for uri in ["/api/v1/ideas", "/api/v1/ideas?limit=10", "/api/v1/digest", "/feed.xml"] {
+            let (status, body) = public_call(&db, "GET", uri, None).await;
+            assert_eq!(status, StatusCode::OK, "{}: {}", uri, body);
+            assert!(body.contains("Shown fixture"), "{}: {}", uri, body);
+            assert!(!body.contains("Secret"), "{} leaks: {}", uri, body);
+        }
+
+        // Counts leave the private ideas out too
+        let (_, body) = public_call(&db, "POST", "/api/ideas/count", Some(serde_json::json!({ "filter": null }))).await;
+        let counts: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(counts["total"], 1, "{}", body);
+        let (_, body) = public_call(&db, "POST", "/api/ideas/archived/count", Some(serde_json::json!({}))).await;
+        assert_eq!(body, "0");
+        let args = serde_json::json!({ "bucket": "day", "buckets": null });
+        let (_, body) = public_call(&db, "POST", "/api/stats/creation", Some(args)).await;
+        let bars: Vec<serde_json::Value> = serde_json::from_str(&body).unwrap();
+        assert_eq!(bars.iter().map(|bar| bar["count"].as_u64().unwrap()).sum::<u64>(), 1);
+
+        // A private idea looks like one that doesn't exist
+        for (path, args) in [
+            ("/api/ideas/get", serde_json::json!({ "id": secret_id })),
+            ("/api/ideas/by-slug", serde_json::json!({ "slug": secret.slug })),
+            ("/api/ideas/history", serde_json::json!({ "id": secret_id })),
+            ("/api/ideas/revisions", serde_json::json!({ "id": secret_id })),
+            ("/api/ideas/bundle/export", serde_json::json!({ "id": secret_id })),
+            ("/api/attachments/list", serde_json::json!({ "idea_id": secret_id })),
+        ] {
+            let (status, body) = public_call(&db, "POST", path, Some(args)).await;
+            assert_ne!(status, StatusCode::OK, "{}: {}", path, body);
+            assert!(body.contains("NotFound"), "{}: {}", path, body);
+        }
+        let args = serde_json::json!({ "id": trashed_id });
+        let (_, body) = public_call(&db, "POST", "/api/ideas/archived/find", Some(args)).await;
+        assert_eq!(body, "null");
+        for uri in [format!("/api/v1/ideas/{}", secret_id), format!("/api/v1/ideas/{}/events", secret_id)] {
+            let (status, body) = public_call(&db, "GET", &uri, None).await;
+            assert_eq!(status, StatusCode::NOT_FOUND, "{}: {}", uri, body);
+        }
+        let args = serde_json::json!({ "id": shown_id });
+        let (status, body) = public_call(&db, "POST", "/api/ideas/get", Some(args)).await;
+        assert_eq!(status, StatusCode::OK, "{}", body);
+
+        // A backup would hold everything, so visitors don't get one
+        let (status, body) = public_call(&db, "POST", "/api/admin/backup", Some(serde_json::json!({}))).await;
+        assert_ne!(status, StatusCode::OK, "{}", body);
+        assert!(!body.contains("Secret"), "{}", body);
+
+        // The writable deployment on the same database still lists both
+        let (_, body) = server_fn_call(&db, "private-reader", "/api/ideas/all", serde_json::json!({})).await;
+        assert_eq!(serde_json::from_str::<Vec<Idea>>(&body).unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_server_jobs_warm_the_cache_and_measure_usage() {
+        use dioxus_surrealdb_template::server_router::server_functions_router;
+        use http_body_util::BodyExt;
+        use tower::ServiceExt;
+
+        let db = new_test_db().await;
+        let ideas = IdeaCache::default();
+        let usage = UsageCache::default();
+        let jobs = start_server_jobs();
+        let id = create_idea_id(&db, "Queued").await;
+        let queued = |job| QueuedJob { job, db: db.clone(), ideas: Some(ideas.clone()), usage: usage.clone() };
+        assert!(jobs.enqueue(queued(Job::ReindexIdea { id, owner: None })));
+        assert!(jobs.enqueue(queued(Job::RecomputeStats)));
+        assert!(jobs.enqueue(queued(Job::PruneRevisions)));
+        assert!(jobs.drained(std::time::Duration::from_secs(5)).await);
+
+        // The list was read back, so the next read is served from the cache
+        assert_eq!(titles(&ideas.lookup(None).unwrap()), vec!["Queued"]);
+        // And the usage was measured: an idea added since only shows once the measure expires
+        create_idea_id(&db, "Unmeasured").await;
+        assert_eq!(usage.usage(&db).await.unwrap().ideas, 1);
+
+        let server = with_jobs(server_functions_router(db.clone(), AppConfig::default()), jobs.clone());
+        let request = axum::http::Request::post("/api/health")
+            .header("content-type", "application/json")
+            .body(axum::body::Body::from("{}"))
+            .unwrap();
+        let response = server.oneshot(request).await.unwrap();
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let health: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(health["queued_jobs"], 0, "{}", health);
+    }
 }